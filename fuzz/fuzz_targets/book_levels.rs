@@ -0,0 +1,38 @@
+/*
+Structured books with arbitrary level strings (empty sides, extreme or non-numeric
+prices and sizes, "NaN", "inf", "1e308") and arbitrary timestamps, fed book after
+book into the signal engine, quote builder and risk manager.
+
+    cargo +nightly fuzz run book_levels
+*/
+#![no_main]
+
+use hyperliquid_rust_sdk::{top_of_book, BookLevel, QuoteLayerManager, RiskManager, SignalEngine};
+use libfuzzer_sys::fuzz_target;
+
+type Side = Vec<(String, String)>;
+
+fn levels(side: &Side) -> Vec<BookLevel> {
+    side.iter()
+        .map(|(px, sz)| BookLevel {
+            px: px.clone(),
+            sz: sz.clone(),
+            n: 1,
+        })
+        .collect()
+}
+
+fuzz_target!(|books: Vec<(u64, Side, Side)>| {
+    let mut engine = SignalEngine::new();
+    let risk = RiskManager::new(5.0);
+    for (time, bids, asks) in &books {
+        let Some((bid, ask, bid_vol, ask_vol)) = top_of_book(&levels(bids), &levels(asks)) else {
+            continue;
+        };
+        assert!(bid > 0.0 && ask > bid && ask.is_finite());
+        engine.process_l2_book(*time, bid, ask, bid_vol, ask_vol);
+        let quotes = QuoteLayerManager::build_quotes(&engine.state);
+        risk.evaluate(&mut engine.state, &quotes);
+        risk.overflow_hedge(&engine.state);
+    }
+});
@@ -0,0 +1,42 @@
+/*
+Raw websocket frames through the same path the live router uses: frame parsing and
+identifier lookup, normalization into market events, then the signal, quoting and
+risk stack. Any panic is a bug; malformed frames must surface as errors or be dropped.
+
+    cargo +nightly fuzz run ws_frame
+*/
+#![no_main]
+
+use hyperliquid_rust_sdk::{
+    parse_ws_frame, top_of_book, MarketEvent, QuoteLayerManager, RiskManager, SignalEngine,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(Some((_, message))) = parse_ws_frame(text) else {
+        return;
+    };
+    let mut engine = SignalEngine::new();
+    let risk = RiskManager::new(5.0);
+    for event in MarketEvent::from_message(&message) {
+        match event {
+            MarketEvent::Book { time, bids, asks } => {
+                if let Some((bid, ask, bid_vol, ask_vol)) = top_of_book(&bids, &asks) {
+                    engine.process_l2_book(time, bid, ask, bid_vol, ask_vol);
+                    let quotes = QuoteLayerManager::build_quotes(&engine.state);
+                    risk.evaluate(&mut engine.state, &quotes);
+                    risk.overflow_hedge(&engine.state);
+                }
+            }
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => engine.process_trade(px, sz, is_buy, time),
+        }
+    }
+});
@@ -0,0 +1,56 @@
+/*
+ * C ABI for the hyperliquid_rust_sdk signal/quoting/risk core (feature "ffi").
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Usage: create an engine, push book and trade updates, poll quote intents after
+ * each book, and report fills with hl_engine_on_fill; only fills move the position.
+ * An engine is not thread-safe; use one per thread or lock around calls.
+ */
+#ifndef HL_ENGINE_H
+#define HL_ENGINE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define HL_OK 0
+#define HL_ERR_NULL (-1)
+#define HL_ERR_INVALID (-2)
+#define HL_ERR_PANIC (-3)
+
+typedef struct HlEngine HlEngine;
+
+typedef struct {
+    int32_t is_buy;
+    double price;
+    double size;
+} HlQuoteIntent;
+
+uint32_t hl_abi_version(void);
+
+/* NULL if max_position is not a positive finite number */
+HlEngine *hl_engine_new(double max_position);
+void hl_engine_free(HlEngine *engine);
+
+/* Replaces the pending intents with the risk-checked quotes for this book */
+int32_t hl_engine_push_book(HlEngine *engine, uint64_t time_ms, double bid, double ask,
+                            double bid_volume, double ask_volume);
+int32_t hl_engine_push_trade(HlEngine *engine, uint64_t time_ms, double price, double size,
+                             int32_t is_buy);
+
+/* Number of intents written to out (at most capacity), or a negative error */
+int64_t hl_engine_poll_quotes(HlEngine *engine, HlQuoteIntent *out, size_t capacity);
+
+int32_t hl_engine_on_fill(HlEngine *engine, int32_t is_buy, double price, double size);
+/* NaN for a NULL engine */
+double hl_engine_position(const HlEngine *engine);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HL_ENGINE_H */
@@ -0,0 +1,203 @@
+use ethers::types::H160;
+use log::{error, warn};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::{prelude::*, Alert, AlertLevel, InfoClient, Message, Notifier, Subscription, UserData};
+
+pub(crate) const MARGIN_PAUSE_MS: u64 = 60_000; // Entries paused after the exchange cancels an order or deleverages
+pub(crate) const ADL_DIR: &str = "Auto-Deleveraging"; // Fill direction of an ADL fill
+
+// Something the exchange did to the account rather than the bot
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountEvent {
+    // The account was liquidated; notional and account value in USD
+    Liquidation { notional: f64, account_value: f64 },
+    // A position was auto-deleveraged against a liquidated one
+    Adl { coin: String, size: f64, px: f64 },
+    // An order the exchange cancelled, typically for insufficient margin
+    MarginCancel { coin: String, oid: u64 },
+}
+
+impl AccountEvent {
+    // The events in a user events message. Liquidations where `user` was
+    // the liquidator, not the liquidated, are left out.
+    pub fn from_message(msg: &Message, user: H160) -> Vec<Self> {
+        let Message::User(events) = msg else {
+            return Vec::new();
+        };
+        match &events.data {
+            UserData::Liquidation(l) if l.liquidated_user.parse::<H160>().ok() == Some(user) => {
+                vec![AccountEvent::Liquidation {
+                    notional: l.liquidated_ntl_pos.parse().unwrap_or_default(),
+                    account_value: l.liquidated_account_value.parse().unwrap_or_default(),
+                }]
+            }
+            UserData::Fills(fills) => fills
+                .iter()
+                .filter(|f| f.dir == ADL_DIR)
+                .map(|f| AccountEvent::Adl {
+                    coin: f.coin.clone(),
+                    size: f.sz.parse().unwrap_or_default(),
+                    px: f.px.parse().unwrap_or_default(),
+                })
+                .collect(),
+            UserData::NonUserCancel(cancels) => cancels
+                .iter()
+                .map(|c| AccountEvent::MarginCancel {
+                    coin: c.coin.clone(),
+                    oid: c.oid,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn alert(&self) -> Alert {
+        match self {
+            AccountEvent::Liquidation {
+                notional,
+                account_value,
+            } => Alert::new(
+                AlertLevel::Critical,
+                "account:liquidation",
+                format!(
+                    "account liquidated: ${notional:.2} notional, account value ${account_value:.2}; halting"
+                ),
+            ),
+            AccountEvent::Adl { coin, size, px } => Alert::new(
+                AlertLevel::Critical,
+                format!("{coin}:adl"),
+                format!("{coin} auto-deleveraged {size} @ {px}; pausing entries"),
+            ),
+            AccountEvent::MarginCancel { coin, oid } => Alert::new(
+                AlertLevel::Warning,
+                format!("{coin}:margin"),
+                format!("{coin} order {oid} cancelled by the exchange; pausing entries"),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    halted: Option<String>,
+    paused_until: u64, // ms
+}
+
+// Acts on account events as they arrive instead of on the next `user_state`
+// poll. A liquidation halts quoting on every coin, which flattens through
+// the kill switch when the loss limits' `flatten_on_halt` is set; an ADL
+// fill or an exchange cancel pauses entries on every coin for
+// `MARGIN_PAUSE_MS`. Risk managers given the guard apply both. Clones share
+// one guard.
+#[derive(Debug, Clone, Default)]
+pub struct AccountGuard {
+    state: Arc<Mutex<GuardState>>,
+    notifier: Option<Arc<Notifier>>,
+}
+
+impl AccountGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    // Follows `user`'s events on a connection of its own, which the task
+    // keeps open: the exchange allows one user events subscription per
+    // connection and an OrderManager may already hold it
+    pub async fn spawn(&self, mut info: InfoClient, user: H160) -> Result<()> {
+        let (tx, mut rx) = unbounded_channel();
+        info.subscribe(Subscription::UserEvents { user }, tx)
+            .await?;
+        let guard = self.clone();
+        tokio::spawn(async move {
+            let _info = info;
+            while let Some(msg) = rx.recv().await {
+                for event in AccountEvent::from_message(&msg, user) {
+                    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+                    guard.on_event(&event, now_ms);
+                    if let Some(notifier) = &guard.notifier {
+                        if let Err(e) = notifier.notify(event.alert()).await {
+                            warn!("[Account] Alert not sent: {e}");
+                        }
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    pub fn on_event(&self, event: &AccountEvent, now_ms: u64) {
+        let mut state = self.state.lock().unwrap();
+        error!("[Account] {}", event.alert().message);
+        match event {
+            AccountEvent::Liquidation { .. } => {
+                state.halted.get_or_insert("account liquidated".to_string());
+            }
+            AccountEvent::Adl { .. } | AccountEvent::MarginCancel { .. } => {
+                state.paused_until = state.paused_until.max(now_ms + MARGIN_PAUSE_MS);
+            }
+        }
+    }
+
+    // Why quoting is halted, if it is
+    pub fn halted(&self) -> Option<String> {
+        self.state.lock().unwrap().halted.clone()
+    }
+
+    pub fn entries_paused(&self, time: u64) -> bool {
+        time < self.state.lock().unwrap().paused_until
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user_events(data: serde_json::Value) -> Message {
+        serde_json::from_value(json!({"channel": "user", "data": data})).unwrap()
+    }
+
+    #[test]
+    fn test_liquidation_halts_and_cancels_pause_entries() {
+        let user: H160 = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let liquidation = user_events(json!({"liquidation": {
+            "lid": 1,
+            "liquidator": "0x0000000000000000000000000000000000000002",
+            "liquidated_user": "0x0000000000000000000000000000000000000001",
+            "liquidated_ntl_pos": "1500.0",
+            "liquidated_account_value": "40.5",
+        }}));
+        let events = AccountEvent::from_message(&liquidation, user);
+        assert_eq!(
+            events,
+            [AccountEvent::Liquidation {
+                notional: 1_500.0,
+                account_value: 40.5
+            }]
+        );
+        // The liquidator sees the same event about someone else
+        assert!(AccountEvent::from_message(&liquidation, H160::zero()).is_empty());
+
+        let cancels = user_events(json!({"nonUserCancel": [{"coin": "BTC", "oid": 7}]}));
+        let guard = AccountGuard::new();
+        for event in AccountEvent::from_message(&cancels, user) {
+            assert_eq!(event.alert().level, AlertLevel::Warning);
+            guard.on_event(&event, 1_000);
+        }
+        assert!(guard.entries_paused(1_000 + MARGIN_PAUSE_MS - 1));
+        assert!(!guard.entries_paused(1_000 + MARGIN_PAUSE_MS));
+        assert_eq!(guard.halted(), None);
+
+        guard.on_event(&events[0], 2_000);
+        assert_eq!(guard.halted().as_deref(), Some("account liquidated"));
+    }
+}
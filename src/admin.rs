@@ -0,0 +1,119 @@
+use log::{debug, warn};
+use std::path::Path;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{mpsc::UnboundedSender, oneshot},
+};
+
+use crate::{prelude::*, Error};
+
+const ADMIN_PROMPT: &str = "hl> ";
+
+// One command line from the admin socket. The bot answers from its own loop, so
+// commands see the same state the strategy does without extra locking.
+#[derive(Debug)]
+pub struct AdminRequest {
+    pub command: String,
+    pub args: Vec<String>,
+    reply: oneshot::Sender<String>,
+}
+
+impl AdminRequest {
+    // For relaying a command to another handler, e.g. fanning out per coin
+    pub fn new(command: &str, args: Vec<String>) -> (Self, oneshot::Receiver<String>) {
+        let (reply, response) = oneshot::channel();
+        let request = Self {
+            command: command.to_string(),
+            args,
+            reply,
+        };
+        (request, response)
+    }
+
+    pub fn reply(self, text: impl Into<String>) {
+        let _ = self.reply.send(text.into());
+    }
+}
+
+// Serves a line-based REPL on a Unix socket (e.g. `nc -U <path>` or the `admin`
+// binary), forwarding each command to `requests`. Only local users with access to
+// the socket file can connect.
+pub async fn serve_admin(
+    path: impl AsRef<Path>,
+    requests: UnboundedSender<AdminRequest>,
+) -> Result<()> {
+    let path = path.as_ref();
+    // A socket left behind by a previous run would make bind fail
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path).map_err(|e| Error::Admin(e.to_string()))?;
+    debug!("admin REPL on {}", path.display());
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                warn!("admin accept failed: {e}");
+                continue;
+            }
+        };
+        tokio::spawn(admin_session(stream, requests.clone()));
+    }
+}
+
+async fn admin_session(stream: UnixStream, requests: UnboundedSender<AdminRequest>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let _ = writer.write_all(ADMIN_PROMPT.as_bytes()).await;
+    while let Ok(Some(line)) = lines.next_line().await {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let Some(command) = words.next() else {
+            let _ = writer.write_all(ADMIN_PROMPT.as_bytes()).await;
+            continue;
+        };
+        if command == "quit" || command == "exit" {
+            break;
+        }
+        let (request, response) = AdminRequest::new(&command.to_lowercase(), words.collect());
+        let text = match requests.send(request) {
+            Ok(()) => response
+                .await
+                .unwrap_or_else(|_| "no response from bot".to_string()),
+            Err(_) => "bot is shutting down".to_string(),
+        };
+        let out = format!("{}\n{ADMIN_PROMPT}", text.trim_end());
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{io::AsyncReadExt, sync::mpsc::unbounded_channel};
+
+    #[tokio::test]
+    async fn test_admin_round_trip() {
+        let path = std::env::temp_dir().join(format!("hl-admin-{}.sock", uuid::Uuid::new_v4()));
+        let (tx, mut rx) = unbounded_channel::<AdminRequest>();
+        tokio::spawn(serve_admin(path.clone(), tx));
+        tokio::spawn(async move {
+            while let Some(req) = rx.recv().await {
+                let text = format!("{} {}", req.command, req.args.join(","));
+                req.reply(text);
+            }
+        });
+
+        let mut stream = loop {
+            match UnixStream::connect(&path).await {
+                Ok(s) => break s,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        stream.write_all(b"SIGNALS btc\n\nquit\n").await.unwrap();
+        let mut out = String::new();
+        stream.read_to_string(&mut out).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(out, "hl> signals btc\nhl> hl> ");
+    }
+}
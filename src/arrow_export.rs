@@ -0,0 +1,206 @@
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use std::{fs::File, path::Path, sync::Arc};
+
+use crate::{prelude::*, Error, SignalState};
+
+// Arrow IPC file (Feather v2) export for research tooling: pandas/polars read these
+// directly with proper types, including UTC millisecond timestamps.
+#[derive(Debug, Clone)]
+pub struct TickRow {
+    pub time: u64,
+    pub coin: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub bid_volume: f64,
+    pub ask_volume: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureRow {
+    pub time: u64,
+    pub coin: String,
+    pub mid: f64,
+    pub trend_score: f64,
+    pub twap: f64,
+    pub twap_deviation: f64,
+    pub normalized_slide: f64,
+    pub fill_score: f64,
+    pub volatility: f64,
+    pub realized_vol: f64,
+    pub update_rate: f64,
+    pub aggressive_mode: bool,
+    pub quoting_paused: bool,
+}
+
+impl FeatureRow {
+    pub fn from_state(time: u64, coin: &str, state: &SignalState) -> Self {
+        Self {
+            time,
+            coin: coin.to_string(),
+            mid: (state.best_bid + state.best_ask) / 2.0,
+            trend_score: state.trend_score,
+            twap: state.twap,
+            twap_deviation: state.twap_deviation,
+            normalized_slide: state.normalized_slide,
+            fill_score: state.fill_score,
+            volatility: state.volatility,
+            realized_vol: state.realized_vol,
+            update_rate: state.update_rate,
+            aggressive_mode: state.aggressive_mode,
+            quoting_paused: state.quoting_paused,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FillRow {
+    pub time: u64,
+    pub coin: String,
+    pub side: String,
+    pub price: f64,
+    pub size: f64,
+}
+
+fn time_column<T>(rows: &[T], f: impl Fn(&T) -> u64) -> (Field, ArrayRef) {
+    let values: Vec<i64> = rows.iter().map(|r| f(r) as i64).collect();
+    (
+        Field::new(
+            "time",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Arc::new(TimestampMillisecondArray::from(values).with_timezone("UTC")),
+    )
+}
+
+fn f64_column<T>(name: &str, rows: &[T], f: impl Fn(&T) -> f64) -> (Field, ArrayRef) {
+    let values: Float64Array = rows.iter().map(|r| Some(f(r))).collect();
+    (Field::new(name, DataType::Float64, false), Arc::new(values))
+}
+
+fn str_column<'a, T>(name: &str, rows: &'a [T], f: impl Fn(&'a T) -> &'a str) -> (Field, ArrayRef) {
+    let values: StringArray = rows.iter().map(|r| Some(f(r))).collect();
+    (Field::new(name, DataType::Utf8, false), Arc::new(values))
+}
+
+fn bool_column<T>(name: &str, rows: &[T], f: impl Fn(&T) -> bool) -> (Field, ArrayRef) {
+    let values: BooleanArray = rows.iter().map(|r| Some(f(r))).collect();
+    (Field::new(name, DataType::Boolean, false), Arc::new(values))
+}
+
+fn write_table(path: &Path, columns: Vec<(Field, ArrayRef)>) -> Result<()> {
+    let (fields, arrays): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+    let schema = Arc::new(Schema::new(fields));
+    let batch =
+        RecordBatch::try_new(schema.clone(), arrays).map_err(|e| Error::Arrow(e.to_string()))?;
+    let file = File::create(path).map_err(|e| Error::Arrow(e.to_string()))?;
+    let mut writer = FileWriter::try_new(file, &schema).map_err(|e| Error::Arrow(e.to_string()))?;
+    writer
+        .write(&batch)
+        .and_then(|_| writer.finish())
+        .map_err(|e| Error::Arrow(e.to_string()))
+}
+
+pub fn write_ticks(path: impl AsRef<Path>, rows: &[TickRow]) -> Result<()> {
+    write_table(
+        path.as_ref(),
+        vec![
+            time_column(rows, |r| r.time),
+            str_column("coin", rows, |r| &r.coin),
+            f64_column("best_bid", rows, |r| r.best_bid),
+            f64_column("best_ask", rows, |r| r.best_ask),
+            f64_column("bid_volume", rows, |r| r.bid_volume),
+            f64_column("ask_volume", rows, |r| r.ask_volume),
+        ],
+    )
+}
+
+pub fn write_features(path: impl AsRef<Path>, rows: &[FeatureRow]) -> Result<()> {
+    write_table(
+        path.as_ref(),
+        vec![
+            time_column(rows, |r| r.time),
+            str_column("coin", rows, |r| &r.coin),
+            f64_column("mid", rows, |r| r.mid),
+            f64_column("trend_score", rows, |r| r.trend_score),
+            f64_column("twap", rows, |r| r.twap),
+            f64_column("twap_deviation", rows, |r| r.twap_deviation),
+            f64_column("normalized_slide", rows, |r| r.normalized_slide),
+            f64_column("fill_score", rows, |r| r.fill_score),
+            f64_column("volatility", rows, |r| r.volatility),
+            f64_column("realized_vol", rows, |r| r.realized_vol),
+            f64_column("update_rate", rows, |r| r.update_rate),
+            bool_column("aggressive_mode", rows, |r| r.aggressive_mode),
+            bool_column("quoting_paused", rows, |r| r.quoting_paused),
+        ],
+    )
+}
+
+pub fn write_fills(path: impl AsRef<Path>, rows: &[FillRow]) -> Result<()> {
+    write_table(
+        path.as_ref(),
+        vec![
+            time_column(rows, |r| r.time),
+            str_column("coin", rows, |r| &r.coin),
+            str_column("side", rows, |r| &r.side),
+            f64_column("price", rows, |r| r.price),
+            f64_column("size", rows, |r| r.size),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use arrow_ipc::reader::FileReader;
+
+    #[test]
+    fn test_write_fills_round_trip() {
+        let path = std::env::temp_dir().join(format!("fills-{}.arrow", uuid::Uuid::new_v4()));
+        let fills = vec![
+            FillRow {
+                time: 1_700_000_000_000,
+                coin: "BTC".into(),
+                side: "Buy".into(),
+                price: 100.5,
+                size: 0.25,
+            },
+            FillRow {
+                time: 1_700_000_000_500,
+                coin: "BTC".into(),
+                side: "Sell".into(),
+                price: 101.0,
+                size: 0.25,
+            },
+        ];
+        write_fills(&path, &fills).unwrap();
+
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let batches: Vec<_> = reader.map(|b| b.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            batch.schema().field(0).data_type(),
+            &DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()))
+        );
+        let times = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(times.value(1), 1_700_000_000_500);
+        let sides = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(sides.value(0), "Buy");
+        assert_eq!(batch.column(4).len(), 2);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use crate::{prelude::*, InfoClient, Meta, SpotMeta};
+
+pub(crate) const PRICE_SIG_FIGS: u32 = 5; // Non-integer prices carry at most this many significant figures
+pub(crate) const PERP_MAX_DECIMALS: u32 = 6; // Price decimals plus size decimals, perps
+pub(crate) const SPOT_MAX_DECIMALS: u32 = 8; // The same for spot pairs
+pub(crate) const MIN_ORDER_NOTIONAL: f64 = 10.0; // USD; the exchange rejects smaller orders
+pub(crate) const DEFAULT_SZ_DECIMALS: u32 = 3; // Size precision assumed for assets without metadata
+pub(crate) const TICK_TOLERANCE: f64 = 1e-9; // Relative slack for float noise in tick checks
+
+// One asset's trading precision. Sizes are multiples of 10^-sz_decimals.
+// Prices have at most `PRICE_SIG_FIGS` significant figures and
+// `max_decimals - sz_decimals` decimals; integer prices are always valid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssetSpec {
+    pub sz_decimals: u32,
+    pub max_decimals: u32, // PERP_MAX_DECIMALS or SPOT_MAX_DECIMALS
+    pub min_notional: f64, // USD
+}
+
+impl Default for AssetSpec {
+    fn default() -> Self {
+        Self::perp(DEFAULT_SZ_DECIMALS)
+    }
+}
+
+impl AssetSpec {
+    pub fn perp(sz_decimals: u32) -> Self {
+        Self {
+            sz_decimals,
+            max_decimals: PERP_MAX_DECIMALS,
+            min_notional: MIN_ORDER_NOTIONAL,
+        }
+    }
+
+    pub fn spot(sz_decimals: u32) -> Self {
+        Self {
+            max_decimals: SPOT_MAX_DECIMALS,
+            ..Self::perp(sz_decimals)
+        }
+    }
+
+    pub fn price_decimals(&self) -> u32 {
+        self.max_decimals.saturating_sub(self.sz_decimals)
+    }
+
+    // The nearest valid price
+    pub fn round_price(&self, px: f64) -> f64 {
+        round_to_significant_and_decimal(px, PRICE_SIG_FIGS, self.price_decimals())
+    }
+
+    // Smallest price step at `px`: the last significant figure, or the last
+    // allowed decimal when that is coarser
+    pub fn tick_size(&self, px: f64) -> f64 {
+        if px <= 0.0 {
+            return 10f64.powi(-(self.price_decimals() as i32));
+        }
+        let magnitude = px.abs().log10().floor() as i32;
+        let sig_fig_step = 10f64.powi(magnitude + 1 - PRICE_SIG_FIGS as i32);
+        sig_fig_step.max(10f64.powi(-(self.price_decimals() as i32)))
+    }
+
+    pub fn round_size(&self, sz: f64) -> f64 {
+        round_to_decimals(sz, self.sz_decimals)
+    }
+
+    // Order size for a margin budget at the given leverage
+    pub fn qty(&self, price: f64, usd_margin: f64, leverage: f64) -> f64 {
+        self.round_size(usd_margin * leverage / price)
+    }
+
+    pub fn is_valid_price(&self, px: f64) -> bool {
+        on_tick(px, px.round()) || on_tick(px, self.round_price(px))
+    }
+
+    pub fn is_valid_size(&self, sz: f64) -> bool {
+        on_tick(sz, self.round_size(sz))
+    }
+}
+
+// Per-asset specs from the exchange's perp and spot metadata, keyed by the
+// names orders use: perp coins, spot pair names such as "@107" and
+// "PURR/USDC". Assets it does not know get `AssetSpec::default()`.
+#[derive(Debug, Clone, Default)]
+pub struct AssetSpecs {
+    specs: HashMap<String, AssetSpec>,
+}
+
+impl AssetSpecs {
+    pub fn from_meta(meta: &Meta) -> Self {
+        Self {
+            specs: meta
+                .universe
+                .iter()
+                .map(|asset| (asset.name.clone(), AssetSpec::perp(asset.sz_decimals)))
+                .collect(),
+        }
+    }
+
+    // Adds every spot pair, sized in its base token's decimals
+    pub fn with_spot(mut self, spot: &SpotMeta) -> Self {
+        let tokens: HashMap<usize, _> = spot.tokens.iter().map(|t| (t.index, t)).collect();
+        for pair in &spot.universe {
+            let (Some(base), Some(quote)) =
+                (tokens.get(&pair.tokens[0]), tokens.get(&pair.tokens[1]))
+            else {
+                continue;
+            };
+            let spec = AssetSpec::spot(base.sz_decimals as u32);
+            self.specs.insert(pair.name.clone(), spec);
+            self.specs
+                .insert(format!("{}/{}", base.name, quote.name), spec);
+        }
+        self
+    }
+
+    // Perp and spot metadata, fetched once
+    pub async fn load(info: &InfoClient) -> Result<Self> {
+        let meta = info.meta().await?;
+        let spot = info.spot_meta().await?;
+        Ok(Self::from_meta(&meta).with_spot(&spot))
+    }
+
+    pub fn insert(&mut self, coin: &str, spec: AssetSpec) {
+        self.specs.insert(coin.to_string(), spec);
+    }
+
+    pub fn get(&self, coin: &str) -> Option<AssetSpec> {
+        self.specs.get(coin).copied()
+    }
+
+    pub fn spec(&self, coin: &str) -> AssetSpec {
+        self.get(coin).unwrap_or_default()
+    }
+}
+
+pub(crate) fn round_to_decimals(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+pub(crate) fn round_to_significant_and_decimal(
+    value: f64,
+    sig_figs: u32,
+    max_decimals: u32,
+) -> f64 {
+    let abs_value = value.abs();
+    let magnitude = abs_value.log10().floor() as i32;
+    let scale = 10f64.powi(sig_figs as i32 - magnitude - 1);
+    let rounded = (abs_value * scale).round() / scale;
+    round_to_decimals(rounded.copysign(value), max_decimals)
+}
+
+fn on_tick(value: f64, rounded: f64) -> bool {
+    (value - rounded).abs() <= value.abs() * TICK_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{meta::TokenInfo, AssetMeta, SpotAssetMeta};
+
+    fn token(name: &str, index: usize, sz_decimals: u8) -> TokenInfo {
+        TokenInfo {
+            name: name.to_string(),
+            sz_decimals,
+            wei_decimals: 8,
+            index,
+            token_id: Default::default(),
+            is_canonical: true,
+        }
+    }
+
+    #[test]
+    fn test_specs_round_to_the_exchange_precision() {
+        let meta = Meta {
+            universe: vec![AssetMeta {
+                name: "BTC".to_string(),
+                sz_decimals: 5,
+            }],
+        };
+        let spot = SpotMeta {
+            universe: vec![SpotAssetMeta {
+                tokens: [1, 0],
+                name: "@1".to_string(),
+                index: 1,
+                is_canonical: false,
+            }],
+            tokens: vec![token("USDC", 0, 8), token("PURR", 1, 0)],
+        };
+        let specs = AssetSpecs::from_meta(&meta).with_spot(&spot);
+
+        let btc = specs.spec("BTC");
+        assert_eq!(btc.price_decimals(), 1);
+        assert_eq!(btc.round_price(43_251.67), 43_252.0);
+        assert_eq!(btc.tick_size(43_251.0), 1.0);
+        assert_eq!(btc.round_size(0.123_456_7), 0.123_46);
+        assert_eq!(btc.qty(50_000.0, 11.0, 20.0), 0.0044);
+        assert!(btc.is_valid_price(123_456.0)); // integers always are
+        assert!(!btc.is_valid_price(43_251.5));
+        assert!(!btc.is_valid_size(0.000_001));
+
+        // Spot pairs answer to both names, with 8 - 0 price decimals
+        let purr = specs.spec("PURR/USDC");
+        assert_eq!(specs.get("@1"), Some(purr));
+        assert_eq!(purr.price_decimals(), 8);
+        assert_eq!(purr.round_price(0.123_456_78), 0.123_46);
+        assert_eq!(purr.tick_size(0.12), 0.000_01);
+        assert_eq!(purr.round_size(12.7), 13.0);
+
+        // Unknown assets fall back to three size decimals
+        assert_eq!(specs.spec("ETH"), AssetSpec::perp(3));
+    }
+}
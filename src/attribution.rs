@@ -0,0 +1,167 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+use crate::{execution_quality::REALIZED_SPREAD_HORIZON_MS, SignalState};
+
+pub(crate) const MIN_VOTE: f64 = 1e-6; // Contributions smaller than this abstain
+
+// What each signal said about buying when an order was decided, as a vote in
+// the buy direction: positive favours a buy, negative a sell. TWAP deviation
+// is read the way the mean-reversion signal reads it, so a mid above TWAP
+// votes to sell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SignalContributions {
+    pub trend_strength: f64, // tanh of the trend score
+    pub micro_pressure: f64, // normalized trade-flow slide
+    pub imbalance: f64,      // touch volume imbalance
+    pub deviation: f64,      // negated TWAP deviation
+}
+
+impl SignalContributions {
+    pub fn from_state(state: &SignalState) -> Self {
+        Self {
+            trend_strength: state.trend_score.tanh(),
+            micro_pressure: state.normalized_slide,
+            imbalance: state.imbalance,
+            deviation: -state.twap_deviation,
+        }
+    }
+
+    pub fn named(&self) -> [(&'static str, f64); 4] {
+        [
+            ("trend_strength", self.trend_strength),
+            ("micro_pressure", self.micro_pressure),
+            ("imbalance", self.imbalance),
+            ("deviation", self.deviation),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingDecision {
+    ts: u64,
+    px: f64,
+    is_buy: bool,
+    contributions: SignalContributions,
+}
+
+// Which signals are earning: every fill is marked against the mid a horizon
+// later, like the realized spread, and counts for each signal that voted for
+// its side. A signal's hit rate is the share of the fills it voted for that
+// the market then moved in favour of.
+#[derive(Debug, Clone)]
+pub struct SignalAttribution {
+    horizon_ms: u64,
+    pending: VecDeque<PendingDecision>,
+    voted: [u64; 4],
+    hits: [u64; 4],
+}
+
+impl Default for SignalAttribution {
+    fn default() -> Self {
+        Self::new(REALIZED_SPREAD_HORIZON_MS)
+    }
+}
+
+impl SignalAttribution {
+    pub fn new(horizon_ms: u64) -> Self {
+        Self {
+            horizon_ms,
+            pending: VecDeque::new(),
+            voted: [0; 4],
+            hits: [0; 4],
+        }
+    }
+
+    pub fn on_fill(&mut self, ts: u64, px: f64, is_buy: bool, contributions: SignalContributions) {
+        self.pending.push_back(PendingDecision {
+            ts,
+            px,
+            is_buy,
+            contributions,
+        });
+    }
+
+    // Feeds the current mid; fills at least the horizon old are scored
+    pub fn on_mid(&mut self, ts: u64, mid: f64) {
+        while let Some(fill) = self.pending.front() {
+            if ts < fill.ts + self.horizon_ms {
+                break;
+            }
+            let earned = if fill.is_buy {
+                mid > fill.px
+            } else {
+                mid < fill.px
+            };
+            let side = if fill.is_buy { 1.0 } else { -1.0 };
+            for (i, (_, vote)) in fill.contributions.named().iter().enumerate() {
+                if vote * side > MIN_VOTE {
+                    self.voted[i] += 1;
+                    self.hits[i] += earned as u64;
+                }
+            }
+            self.pending.pop_front();
+        }
+    }
+
+    // Per signal: (name, fills it voted for, hit rate), None before any
+    pub fn hit_rates(&self) -> Vec<(&'static str, u64, Option<f64>)> {
+        SignalContributions::default()
+            .named()
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                let rate = (self.voted[i] > 0).then(|| self.hits[i] as f64 / self.voted[i] as f64);
+                (*name, self.voted[i], rate)
+            })
+            .collect()
+    }
+
+    // One line for the session report
+    pub fn summary(&self) -> String {
+        self.hit_rates()
+            .iter()
+            .map(|(name, voted, rate)| match rate {
+                Some(rate) => format!("{name} {:.0}% of {voted}", rate * 100.0),
+                None => format!("{name} -"),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_rates_count_the_signals_that_voted_for_each_fill() {
+        let mut attribution = SignalAttribution::new(1_000);
+        let state = SignalState {
+            trend_score: 1.0,
+            normalized_slide: -0.5,
+            imbalance: 0.3,
+            twap_deviation: 0.001,
+            ..Default::default()
+        };
+        let contributions = SignalContributions::from_state(&state);
+        assert!(contributions.deviation < 0.0);
+        // Trend and imbalance vote for both buys, flow and deviation for the sell
+        attribution.on_fill(0, 100.0, true, contributions);
+        attribution.on_fill(0, 100.0, true, contributions);
+        attribution.on_fill(500, 100.0, false, contributions);
+        attribution.on_mid(999, 90.0);
+        assert!(attribution
+            .hit_rates()
+            .iter()
+            .all(|(_, voted, _)| *voted == 0));
+        // The mid rose after the buys, and the sell was caught out
+        attribution.on_mid(1_000, 101.0);
+        attribution.on_mid(1_500, 101.0);
+        let rates = attribution.hit_rates();
+        assert_eq!(rates[0], ("trend_strength", 2, Some(1.0)));
+        assert_eq!(rates[1], ("micro_pressure", 1, Some(0.0)));
+        assert_eq!(rates[2], ("imbalance", 2, Some(1.0)));
+        assert_eq!(rates[3], ("deviation", 1, Some(0.0)));
+    }
+}
@@ -0,0 +1,820 @@
+use futures_util::future::BoxFuture;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use uuid::Uuid;
+
+use crate::{
+    helpers::uuid_to_hex_string, limit_order, prelude::*, BasicOrder, BookSide,
+    ClientModifyRequest, ClientOrder, ClientOrderRequest, Error, ExchangeClient,
+    ExchangeDataStatus, ExchangeDataStatuses, ExchangeResponse, ExchangeResponseStatus, FeeModel,
+    FilledOrder, MarketEvent, Message, OrderBook, OrderIntent, OrderOutcome, OrderUpdate,
+    OrderUpdates, QueuePosition, RestingOrder, TimeInForce, TradeInfo, User, UserData,
+};
+
+// Where the executor sends order intents. Responses have the exchange's shape
+// whichever backend produced them, so callers cannot tell paper from live.
+pub trait ExecutionBackend: Send + Sync {
+    fn execute(&self, intent: OrderIntent) -> BoxFuture<'_, Result<ExchangeResponseStatus>>;
+
+    // Several intents at once, one result per intent in the same order.
+    // Backends with bulk endpoints send each kind in one request; by default
+    // they are executed one at a time.
+    fn execute_batch(
+        &self,
+        intents: Vec<OrderIntent>,
+    ) -> BoxFuture<'_, Vec<Result<ExchangeResponseStatus>>> {
+        Box::pin(async move {
+            let mut results = Vec::with_capacity(intents.len());
+            for intent in intents {
+                results.push(self.execute(intent).await);
+            }
+            results
+        })
+    }
+
+    // Market data for backends that simulate fills; the live exchange has its own
+    fn on_market_event(&self, _coin: &str, _event: &MarketEvent) {}
+
+    // Cancels every open order, then closes every position with reduce-only
+    // market orders. Orders go first so nothing resting can re-open a position.
+    fn flatten(&self) -> BoxFuture<'_, Result<Vec<ExchangeResponseStatus>>>;
+}
+
+pub struct LiveExchange {
+    client: Arc<ExchangeClient>,
+}
+
+impl LiveExchange {
+    pub fn new(client: Arc<ExchangeClient>) -> Self {
+        Self { client }
+    }
+}
+
+impl ExecutionBackend for LiveExchange {
+    fn execute(&self, intent: OrderIntent) -> BoxFuture<'_, Result<ExchangeResponseStatus>> {
+        Box::pin(async move {
+            match intent {
+                OrderIntent::Place(order) => self.client.order(order, None).await,
+                OrderIntent::Cancel(cancel) => self.client.cancel(cancel, None).await,
+                OrderIntent::CancelByCloid(cancel) => {
+                    self.client.cancel_by_cloid(cancel, None).await
+                }
+                OrderIntent::Modify(modify) => self.client.modify(modify, None).await,
+            }
+        })
+    }
+
+    // Cancels, then modifies, then new orders, each kind in one bulk request.
+    // An order failing the pre-trade checks is refused on its own instead of
+    // failing the whole request.
+    fn execute_batch(
+        &self,
+        intents: Vec<OrderIntent>,
+    ) -> BoxFuture<'_, Vec<Result<ExchangeResponseStatus>>> {
+        Box::pin(async move {
+            let mut results: Vec<_> = intents.iter().map(|_| None).collect();
+            let (mut cancels, mut by_cloid) = (Vec::new(), Vec::new());
+            let (mut modifies, mut orders) = (Vec::new(), Vec::new());
+            for (i, intent) in intents.into_iter().enumerate() {
+                match intent {
+                    OrderIntent::Cancel(cancel) => cancels.push((i, cancel)),
+                    OrderIntent::CancelByCloid(cancel) => by_cloid.push((i, cancel)),
+                    OrderIntent::Modify(modify) => modifies.push((i, modify)),
+                    OrderIntent::Place(order) => {
+                        match self.client.pre_trade_check(std::slice::from_ref(&order)) {
+                            Ok(()) => orders.push((i, order)),
+                            Err(e) => results[i] = Some(Err(e)),
+                        }
+                    }
+                }
+            }
+            if !cancels.is_empty() {
+                let (slots, cancels): (Vec<_>, Vec<_>) = cancels.into_iter().unzip();
+                let response = self.client.bulk_cancel(cancels, None).await;
+                scatter(&mut results, &slots, response);
+            }
+            if !by_cloid.is_empty() {
+                let (slots, cancels): (Vec<_>, Vec<_>) = by_cloid.into_iter().unzip();
+                let response = self.client.bulk_cancel_by_cloid(cancels, None).await;
+                scatter(&mut results, &slots, response);
+            }
+            if !modifies.is_empty() {
+                let (slots, modifies): (Vec<_>, Vec<_>) = modifies.into_iter().unzip();
+                let response = self.client.bulk_modify(modifies, None).await;
+                scatter(&mut results, &slots, response);
+            }
+            if !orders.is_empty() {
+                let (slots, orders): (Vec<_>, Vec<_>) = orders.into_iter().unzip();
+                let response = self.client.bulk_order(orders, None).await;
+                scatter(&mut results, &slots, response);
+            }
+            results
+                .into_iter()
+                .map(|result| {
+                    result.unwrap_or_else(|| Err(Error::GenericRequest("not sent".to_string())))
+                })
+                .collect()
+        })
+    }
+
+    // The pre-trade price collar follows the bot's books
+    fn on_market_event(&self, coin: &str, event: &MarketEvent) {
+        if let Some(checks) = &self.client.pre_trade {
+            checks.on_market_event(coin, event);
+        }
+    }
+
+    fn flatten(&self) -> BoxFuture<'_, Result<Vec<ExchangeResponseStatus>>> {
+        Box::pin(async move {
+            // Positions are closed even when the open orders could not be listed
+            let mut responses = self
+                .client
+                .cancel_all_orders(None)
+                .await
+                .unwrap_or_else(|e| vec![ExchangeResponseStatus::Err(format!("cancel all: {e}"))]);
+            responses.extend(self.client.close_all_positions(None).await?);
+            Ok(responses)
+        })
+    }
+}
+
+// Hands each intent of a bulk request its own status out of the response,
+// or the whole response when it does not carry one status per intent
+fn scatter(
+    results: &mut [Option<Result<ExchangeResponseStatus>>],
+    slots: &[usize],
+    response: Result<ExchangeResponseStatus>,
+) {
+    let statuses = match &response {
+        Ok(ExchangeResponseStatus::Ok(ExchangeResponse {
+            response_type,
+            data: Some(data),
+        })) if data.statuses.len() == slots.len() => Some((response_type, &data.statuses)),
+        _ => None,
+    };
+    for (n, &slot) in slots.iter().enumerate() {
+        results[slot] = Some(match statuses {
+            Some((response_type, statuses)) => {
+                Ok(order_response(response_type, statuses[n].clone()))
+            }
+            None => response.clone(),
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub latency_ms: u64, // delay before an intent reaches the simulated matching engine
+    // Share of the size already resting at our price that is ahead of a new
+    // order: 1.0 joins the back of the queue
+    pub queue_ahead_fraction: f64,
+    pub fees: FeeModel,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 100,
+            queue_ahead_fraction: 1.0,
+            fees: FeeModel::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    oid: u64,
+    cloid: Option<Uuid>,
+    coin: String,
+    is_buy: bool,
+    px: f64,
+    remaining: f64,
+    orig_sz: f64,
+    queue: QueuePosition,
+    placed_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct PaperState {
+    books: HashMap<String, OrderBook>,
+    resting: Vec<PaperOrder>,
+    positions: HashMap<String, f64>,
+    next_oid: u64,
+    next_tid: u64,
+}
+
+// Simulates the exchange against the local order book: crossing orders take
+// visible depth up to their limit, resting orders wait behind the size that
+// was at their price and fill as trades consume that queue or the book moves
+// through them. Resting fills are partial: a trade fills at most its own size,
+// and a book through our price at most the size shown at or beyond it, so a
+// large order fills over a sequence of events. Fills and order updates are published in the exchange's
+// WebSocket message shapes, so an OrderManager can consume them unchanged.
+// Each resting order's queue is tracked independently, so two of our orders
+// at one price do not queue behind each other.
+pub struct PaperExchange {
+    config: PaperConfig,
+    state: Mutex<PaperState>,
+    events: Option<UnboundedSender<Message>>,
+}
+
+fn order_response(response_type: &str, status: ExchangeDataStatus) -> ExchangeResponseStatus {
+    ExchangeResponseStatus::Ok(ExchangeResponse {
+        response_type: response_type.to_string(),
+        data: Some(ExchangeDataStatuses {
+            statuses: vec![status],
+        }),
+    })
+}
+
+impl PaperExchange {
+    pub fn new(config: PaperConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(PaperState::default()),
+            events: None,
+        }
+    }
+
+    // Publish simulated fills (as `Message::User`) and order status changes
+    // (as `Message::OrderUpdates`) to this channel
+    pub fn with_events(mut self, events: UnboundedSender<Message>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn position(&self, coin: &str) -> f64 {
+        let state = self.state.lock().unwrap();
+        state.positions.get(coin).copied().unwrap_or(0.0)
+    }
+
+    pub fn resting_orders(&self) -> usize {
+        self.state.lock().unwrap().resting.len()
+    }
+
+    // Cloids of the resting orders that were placed with one
+    pub fn resting_cloids(&self) -> Vec<Uuid> {
+        let state = self.state.lock().unwrap();
+        state.resting.iter().filter_map(|o| o.cloid).collect()
+    }
+
+    fn emit(&self, msg: Message) {
+        if let Some(events) = &self.events {
+            let _ = events.send(msg);
+        }
+    }
+
+    fn emit_update(&self, order: &PaperOrder, status: &str, time: u64) {
+        self.emit(Message::OrderUpdates(OrderUpdates {
+            data: vec![OrderUpdate {
+                order: BasicOrder {
+                    coin: order.coin.clone(),
+                    side: if order.is_buy { "B" } else { "A" }.to_string(),
+                    limit_px: order.px.to_string(),
+                    sz: order.remaining.to_string(),
+                    oid: order.oid,
+                    timestamp: order.placed_ms,
+                    orig_sz: order.orig_sz.to_string(),
+                    cloid: order.cloid.map(uuid_to_hex_string),
+                },
+                status: status.to_string(),
+                status_timestamp: time,
+            }],
+        }));
+    }
+
+    fn fill(
+        &self,
+        state: &mut PaperState,
+        order: &PaperOrder,
+        px: f64,
+        sz: f64,
+        crossed: bool,
+        time: u64,
+    ) {
+        let position = state.positions.entry(order.coin.clone()).or_default();
+        let start = *position;
+        *position += if order.is_buy { sz } else { -sz };
+        state.next_tid += 1;
+        let dir = match (order.is_buy, start) {
+            (true, s) if s < 0.0 => "Close Short",
+            (true, _) => "Open Long",
+            (false, s) if s > 0.0 => "Close Long",
+            (false, _) => "Open Short",
+        };
+        self.emit(Message::User(User {
+            data: UserData::Fills(vec![TradeInfo {
+                coin: order.coin.clone(),
+                side: if order.is_buy { "B" } else { "A" }.to_string(),
+                px: px.to_string(),
+                sz: sz.to_string(),
+                time,
+                hash: String::new(),
+                start_position: start.to_string(),
+                dir: dir.to_string(),
+                closed_pnl: "0".to_string(),
+                oid: order.oid,
+                cloid: order.cloid.map(uuid_to_hex_string),
+                crossed,
+                fee: self.config.fees.fee(px * sz, crossed).to_string(),
+                fee_token: "USDC".to_string(),
+                tid: state.next_tid,
+            }]),
+        }));
+    }
+
+    // `replaces` is the oid of a resting order this one takes over from, which
+    // it keeps
+    fn place(&self, order: ClientOrderRequest, replaces: Option<u64>) -> ExchangeResponseStatus {
+        let tif = match &order.order_type {
+            ClientOrder::Limit(limit) => limit.tif.clone(),
+            ClientOrder::Trigger(_) => {
+                return ExchangeResponseStatus::Err(
+                    "paper trading supports limit orders only".to_string(),
+                )
+            }
+        };
+        let mut state = self.state.lock().unwrap();
+        let Some(book) = state.books.get(&order.asset).cloned() else {
+            return order_response(
+                "order",
+                ExchangeDataStatus::Error(format!("no market data yet for {}", order.asset)),
+            );
+        };
+        let mut size = order.sz;
+        if order.reduce_only {
+            let position = state.positions.get(&order.asset).copied().unwrap_or(0.0);
+            let reduces = (order.is_buy && position < 0.0) || (!order.is_buy && position > 0.0);
+            if !reduces {
+                return order_response(
+                    "order",
+                    ExchangeDataStatus::Error(
+                        "Reduce only order would increase position.".to_string(),
+                    ),
+                );
+            }
+            size = size.min(position.abs());
+        }
+        let oid = replaces.unwrap_or_else(|| {
+            state.next_oid += 1;
+            state.next_oid
+        });
+        let mut paper = PaperOrder {
+            oid,
+            cloid: order.cloid,
+            coin: order.asset.clone(),
+            is_buy: order.is_buy,
+            px: order.limit_px,
+            remaining: size,
+            orig_sz: size,
+            queue: QueuePosition::default(),
+            placed_ms: book.time,
+        };
+        // Take visible liquidity up to the limit price
+        let far = if order.is_buy {
+            BookSide::Ask
+        } else {
+            BookSide::Bid
+        };
+        let takes: Vec<(f64, f64)> = book
+            .levels(far)
+            .take_while(|(px, _)| {
+                if order.is_buy {
+                    *px <= order.limit_px
+                } else {
+                    *px >= order.limit_px
+                }
+            })
+            .collect();
+        if !takes.is_empty() && tif == "Alo" {
+            return order_response(
+                "order",
+                ExchangeDataStatus::Error(
+                    "Post only order would have immediately matched".to_string(),
+                ),
+            );
+        }
+        let (mut filled, mut notional) = (0.0, 0.0);
+        for (px, sz) in takes {
+            let take = sz.min(paper.remaining);
+            if take <= 0.0 {
+                break;
+            }
+            self.fill(&mut state, &paper, px, take, true, book.time);
+            paper.remaining -= take;
+            filled += take;
+            notional += take * px;
+        }
+        if paper.remaining <= f64::EPSILON {
+            self.emit_update(&paper, "filled", book.time);
+            return order_response(
+                "order",
+                ExchangeDataStatus::Filled(FilledOrder {
+                    total_sz: filled.to_string(),
+                    avg_px: (notional / filled).to_string(),
+                    oid: paper.oid,
+                }),
+            );
+        }
+        if tif == "Ioc" {
+            if filled > 0.0 {
+                self.emit_update(&paper, "canceled", book.time);
+                return order_response(
+                    "order",
+                    ExchangeDataStatus::Filled(FilledOrder {
+                        total_sz: filled.to_string(),
+                        avg_px: (notional / filled).to_string(),
+                        oid: paper.oid,
+                    }),
+                );
+            }
+            return order_response(
+                "order",
+                ExchangeDataStatus::Error(
+                    "Order could not immediately match against any resting orders.".to_string(),
+                ),
+            );
+        }
+        let near = if order.is_buy {
+            BookSide::Bid
+        } else {
+            BookSide::Ask
+        };
+        paper.queue = QueuePosition::join(
+            book.depth_at_price(near, order.limit_px),
+            self.config.queue_ahead_fraction,
+        );
+        self.emit_update(&paper, "open", book.time);
+        let oid = paper.oid;
+        state.resting.push(paper);
+        order_response("order", ExchangeDataStatus::Resting(RestingOrder { oid }))
+    }
+
+    // A resting order moved to a new price and size. It rejoins the queue at
+    // its new price, and may cross; a modify the exchange refuses leaves the
+    // original order resting.
+    fn modify(&self, modify: ClientModifyRequest) -> ExchangeResponseStatus {
+        let ClientModifyRequest { oid, order } = modify;
+        let original = {
+            let mut state = self.state.lock().unwrap();
+            let found = state
+                .resting
+                .iter()
+                .position(|o| o.oid == oid && o.coin == order.asset);
+            match found {
+                Some(i) => state.resting.remove(i),
+                None => {
+                    return order_response(
+                        "order",
+                        ExchangeDataStatus::Error(
+                            "Cannot modify canceled or filled order".to_string(),
+                        ),
+                    )
+                }
+            }
+        };
+        let response = self.place(order, Some(oid));
+        if let OrderOutcome::Rejected(_) = OrderOutcome::from(response.clone()) {
+            self.state.lock().unwrap().resting.push(original);
+        }
+        response
+    }
+
+    fn cancel(&self, coin: &str, matches: impl Fn(&PaperOrder) -> bool) -> ExchangeResponseStatus {
+        let mut state = self.state.lock().unwrap();
+        let time = state.books.get(coin).map_or(0, |b| b.time);
+        match state
+            .resting
+            .iter()
+            .position(|o| o.coin == coin && matches(o))
+        {
+            Some(i) => {
+                let order = state.resting.remove(i);
+                self.emit_update(&order, "canceled", time);
+                order_response("cancel", ExchangeDataStatus::Success)
+            }
+            None => order_response(
+                "cancel",
+                ExchangeDataStatus::Error(
+                    "Order was never placed, already canceled, or filled.".to_string(),
+                ),
+            ),
+        }
+    }
+
+    // Matches resting orders against a new book or trade
+    fn match_resting(&self, state: &mut PaperState, coin: &str, event: &MarketEvent) {
+        let mut resting = std::mem::take(&mut state.resting);
+        for order in resting.iter_mut().filter(|o| o.coin == coin) {
+            let fill_px = order.px;
+            let fill = match *event {
+                MarketEvent::Book { time, .. } => {
+                    let Some(book) = state.books.get(coin) else {
+                        continue;
+                    };
+                    let near = if order.is_buy {
+                        BookSide::Bid
+                    } else {
+                        BookSide::Ask
+                    };
+                    order.queue.on_book(book.depth_at_price(near, order.px));
+                    // Only the size shown through our price could have traded with us
+                    let far = if order.is_buy {
+                        BookSide::Ask
+                    } else {
+                        BookSide::Bid
+                    };
+                    let shown: f64 = book
+                        .levels(far)
+                        .take_while(|(px, _)| {
+                            if order.is_buy {
+                                *px <= order.px
+                            } else {
+                                *px >= order.px
+                            }
+                        })
+                        .map(|(_, sz)| sz)
+                        .sum();
+                    (shown > 0.0).then(|| (order.remaining.min(shown), time))
+                }
+                MarketEvent::Trade {
+                    time,
+                    px,
+                    sz,
+                    is_buy,
+                } => {
+                    // Only aggressors on the other side trade against us
+                    if is_buy == order.is_buy {
+                        continue;
+                    }
+                    let through = if order.is_buy {
+                        px < order.px
+                    } else {
+                        px > order.px
+                    };
+                    if through {
+                        Some((order.remaining.min(sz), time))
+                    } else if px == order.px {
+                        let left = order.queue.on_trade(sz);
+                        (left > 0.0).then(|| (left.min(order.remaining), time))
+                    } else {
+                        None
+                    }
+                }
+            };
+            if let Some((sz, time)) = fill {
+                self.fill(state, order, fill_px, sz, false, time);
+                order.remaining -= sz;
+                if order.remaining <= f64::EPSILON {
+                    self.emit_update(order, "filled", time);
+                }
+            }
+        }
+        resting.retain(|o| o.remaining > f64::EPSILON);
+        state.resting = resting;
+    }
+}
+
+impl ExecutionBackend for PaperExchange {
+    fn execute(&self, intent: OrderIntent) -> BoxFuture<'_, Result<ExchangeResponseStatus>> {
+        Box::pin(async move {
+            if self.config.latency_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(self.config.latency_ms)).await;
+            }
+            Ok(match intent {
+                OrderIntent::Place(order) => self.place(order, None),
+                OrderIntent::Cancel(cancel) => self.cancel(&cancel.asset, |o| o.oid == cancel.oid),
+                OrderIntent::CancelByCloid(cancel) => {
+                    self.cancel(&cancel.asset, |o| o.cloid == Some(cancel.cloid))
+                }
+                OrderIntent::Modify(modify) => self.modify(modify),
+            })
+        })
+    }
+
+    // Closes with IOCs that take whatever depth the book shows
+    fn flatten(&self) -> BoxFuture<'_, Result<Vec<ExchangeResponseStatus>>> {
+        Box::pin(async move {
+            let mut responses = Vec::new();
+            let positions: Vec<(String, f64)> = {
+                let mut state = self.state.lock().unwrap();
+                for order in std::mem::take(&mut state.resting) {
+                    let time = state.books.get(&order.coin).map_or(0, |b| b.time);
+                    self.emit_update(&order, "canceled", time);
+                    responses.push(order_response("cancel", ExchangeDataStatus::Success));
+                }
+                state
+                    .positions
+                    .iter()
+                    .filter(|(_, size)| **size != 0.0)
+                    .map(|(coin, size)| (coin.clone(), *size))
+                    .collect()
+            };
+            for (coin, size) in positions {
+                let (is_buy, px) = if size < 0.0 {
+                    (true, f64::MAX)
+                } else {
+                    (false, 0.0)
+                };
+                let close =
+                    limit_order(&coin, is_buy, px, size.abs(), true, TimeInForce::Ioc, None);
+                responses.push(self.place(close, None));
+            }
+            Ok(responses)
+        })
+    }
+
+    fn on_market_event(&self, coin: &str, event: &MarketEvent) {
+        let mut state = self.state.lock().unwrap();
+        if let MarketEvent::Book { time, bids, asks } = event {
+            let book = state.books.entry(coin.to_string()).or_default();
+            if !book.apply_snapshot(*time, bids, asks) {
+                return;
+            }
+        }
+        self.match_resting(&mut state, coin, event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{limit_order, BookLevel, OrderManager, OrderOutcome, OrderStatus};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn level(px: f64, sz: f64) -> BookLevel {
+        BookLevel {
+            px: px.to_string(),
+            sz: sz.to_string(),
+            n: 1,
+        }
+    }
+
+    fn book(time: u64, bid: f64, ask: f64) -> MarketEvent {
+        MarketEvent::Book {
+            time,
+            bids: vec![level(bid, 2.0), level(bid - 1.0, 5.0)],
+            asks: vec![level(ask, 1.0), level(ask + 1.0, 5.0)],
+        }
+    }
+
+    fn paper() -> PaperExchange {
+        PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+    }
+
+    async fn send(paper: &PaperExchange, order: ClientOrderRequest) -> OrderOutcome {
+        OrderOutcome::from(paper.execute(OrderIntent::Place(order)).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_crossing_orders_walk_the_book_and_ioc_never_rests() {
+        let paper = paper();
+        paper.on_market_event("BTC", &book(1, 100.0, 101.0));
+        let outcome = send(
+            &paper,
+            limit_order("BTC", true, 102.0, 1.5, false, TimeInForce::Ioc, None),
+        )
+        .await;
+        match outcome {
+            OrderOutcome::Filled { size, avg_px, .. } => {
+                assert_eq!(size, 1.5);
+                assert!((avg_px - (101.0 + 0.5 * 102.0) / 1.5).abs() < 1e-9);
+            }
+            other => panic!("expected a fill, got {other:?}"),
+        }
+        let outcome = send(
+            &paper,
+            limit_order("BTC", true, 99.0, 1.0, false, TimeInForce::Ioc, None),
+        )
+        .await;
+        assert!(matches!(outcome, OrderOutcome::Rejected(_)));
+        let outcome = send(
+            &paper,
+            limit_order("BTC", false, 99.0, 1.0, false, TimeInForce::Alo, None),
+        )
+        .await;
+        assert!(matches!(outcome, OrderOutcome::Rejected(_)));
+        assert_eq!(paper.resting_orders(), 0);
+        assert_eq!(paper.position("BTC"), 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_fill_once_the_queue_ahead_trades() {
+        let (tx, mut rx) = unbounded_channel();
+        let paper = paper().with_events(tx);
+        let orders = OrderManager::new();
+        paper.on_market_event("BTC", &book(1, 100.0, 101.0));
+        let cloid = Uuid::new_v4();
+        orders.track(cloid, "BTC", true, 100.0, 1.0);
+        let order = limit_order(
+            "BTC",
+            true,
+            100.0,
+            1.0,
+            false,
+            TimeInForce::Gtc,
+            Some(cloid),
+        );
+        let outcome = send(&paper, order).await;
+        assert!(matches!(outcome, OrderOutcome::Resting { .. }));
+        orders.on_outcome(cloid, &outcome);
+
+        // 2.0 was resting ahead of us at 100
+        let sell = |time, sz| MarketEvent::Trade {
+            time,
+            px: 100.0,
+            sz,
+            is_buy: false,
+        };
+        paper.on_market_event("BTC", &sell(2, 1.5));
+        paper.on_market_event("BTC", &sell(3, 1.0));
+        while let Ok(msg) = rx.try_recv() {
+            orders.on_message(&msg);
+        }
+        let tracked = orders.get(cloid).unwrap();
+        assert_eq!(tracked.status, OrderStatus::PartiallyFilled);
+        assert_eq!(tracked.filled, 0.5);
+
+        // The ask drops through our bid: the rest fills at our price
+        paper.on_market_event("BTC", &book(4, 99.0, 100.0));
+        while let Ok(msg) = rx.try_recv() {
+            orders.on_message(&msg);
+        }
+        assert_eq!(orders.get(cloid).unwrap().status, OrderStatus::Filled);
+        assert_eq!(paper.resting_orders(), 0);
+        assert_eq!(paper.position("BTC"), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_modify_moves_a_resting_order_under_its_oid() {
+        let paper = paper();
+        paper.on_market_event("BTC", &book(1, 100.0, 101.0));
+        let order = limit_order("BTC", true, 99.0, 1.0, false, TimeInForce::Gtc, None);
+        let OrderOutcome::Resting { oid } = send(&paper, order).await else {
+            panic!("expected the bid to rest");
+        };
+        let modify = |px| {
+            let order = limit_order("BTC", true, px, 1.0, false, TimeInForce::Alo, None);
+            let intent = OrderIntent::Modify(ClientModifyRequest { oid, order });
+            async { OrderOutcome::from(paper.execute(intent).await.unwrap()) }
+        };
+        assert_eq!(modify(99.5).await, OrderOutcome::Resting { oid });
+        // A refused modify leaves the order where it was
+        assert!(matches!(modify(101.0).await, OrderOutcome::Rejected(_)));
+        assert_eq!(paper.resting_orders(), 1);
+        paper.on_market_event("BTC", &book(2, 99.0, 99.5));
+        assert_eq!(paper.position("BTC"), 1.0);
+        assert!(matches!(modify(98.0).await, OrderOutcome::Rejected(_)));
+    }
+
+    #[tokio::test]
+    async fn test_large_resting_orders_fill_piecewise() {
+        let (tx, mut rx) = unbounded_channel();
+        let paper = paper().with_events(tx);
+        let orders = OrderManager::new();
+        paper.on_market_event("BTC", &book(1, 100.0, 101.0));
+        let cloid = Uuid::new_v4();
+        orders.track(cloid, "BTC", false, 101.0, 4.0);
+        let order = limit_order(
+            "BTC",
+            false,
+            101.0,
+            4.0,
+            false,
+            TimeInForce::Gtc,
+            Some(cloid),
+        );
+        orders.on_outcome(cloid, &send(&paper, order).await);
+        let mut filled = |event: MarketEvent| {
+            paper.on_market_event("BTC", &event);
+            while let Ok(msg) = rx.try_recv() {
+                orders.on_message(&msg);
+            }
+            let tracked = orders.get(cloid).unwrap();
+            (tracked.filled, tracked.status)
+        };
+
+        // A buy printing through our ask fills no more than its own size
+        let buy = MarketEvent::Trade {
+            time: 2,
+            px: 101.5,
+            sz: 1.5,
+            is_buy: true,
+        };
+        assert_eq!(filled(buy), (1.5, OrderStatus::PartiallyFilled));
+        // A bid through our ask fills what the book shows there, one book at a time
+        assert_eq!(
+            filled(book(3, 101.0, 102.0)),
+            (3.5, OrderStatus::PartiallyFilled)
+        );
+        assert_eq!(filled(book(4, 101.0, 102.0)), (4.0, OrderStatus::Filled));
+        assert_eq!(paper.position("BTC"), -4.0);
+    }
+}
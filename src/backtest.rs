@@ -0,0 +1,801 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use crate::{
+    prelude::*, top_of_book, BookLevel, CandlesSnapshotResponse, Error, FeeModel,
+    FundingHistoryResponse, MarketEvent, QueuePosition, QuoteLayerManager, QuoteProposal,
+    RiskManager, SignalEngine, SignalWindows, EPSILON,
+};
+
+pub const DEFAULT_SIGNIFICANCE_Z: f64 = 1.96; // two-sided 95% bounds
+const YEAR_MS: f64 = 365.0 * 24.0 * 3_600_000.0;
+
+// Output of one backtest: headline metrics plus the per-trade PnL series that
+// comparisons and resampling work from. Stored as pretty JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub label: String, // e.g. branch or commit the result was produced from
+    #[serde(default)]
+    pub metrics: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub trade_pnls: Vec<f64>,
+}
+
+impl BacktestResult {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::Backtest(format!("{}: {e}", path.display())))?;
+        serde_json::from_str(&text).map_err(|e| Error::Backtest(format!("{}: {e}", path.display())))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let text =
+            serde_json::to_string_pretty(self).map_err(|e| Error::Backtest(e.to_string()))?;
+        fs::write(path, text).map_err(|e| Error::Backtest(e.to_string()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub max_position: f64,
+    pub fees: FeeModel, // maker for resting quotes, taker for overflow hedges
+    pub sample_ms: u64, // equity sampling period for the Sharpe ratio
+    pub windows: SignalWindows,
+    pub quoting: QuoteLayerManager,
+    pub funding: Vec<(u64, f64)>, // (time, rate) charged on the position held then
+    // Queue-aware fills: a quote joins behind this share of the size shown at
+    // its price, as on the paper exchange. None fills on any print at the price.
+    pub queue_ahead_fraction: Option<f64>,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            max_position: 5.0,
+            fees: FeeModel::default(),
+            sample_ms: 60_000,
+            windows: SignalWindows::default(),
+            quoting: QuoteLayerManager::default(),
+            funding: Vec::new(),
+            queue_ahead_fraction: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestFill {
+    pub time: u64,
+    pub is_buy: bool,
+    pub px: f64,
+    pub sz: f64,
+    pub fee: f64,
+    pub taker: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BacktestStats {
+    pub total_pnl: f64, // net of fees and funding, marked to the last mid
+    pub fees: f64,
+    pub funding: f64, // paid, so negative when the position collected funding
+    pub sharpe: f64,  // annualized, from `sample_ms` equity changes
+    pub max_drawdown: f64,
+    pub turnover: f64, // traded notional
+    pub fills: usize,
+    pub hedges: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BacktestRun {
+    pub fills: Vec<BacktestFill>,
+    pub equity_curve: Vec<(u64, f64)>, // (time, PnL) after every book
+    pub trade_pnls: Vec<f64>,          // realized on each reducing fill, after fees
+    pub stats: BacktestStats,
+}
+
+impl BacktestRun {
+    pub fn to_result(&self, label: &str) -> BacktestResult {
+        let s = &self.stats;
+        let metrics = [
+            ("total_pnl", s.total_pnl),
+            ("fees", s.fees),
+            ("funding", s.funding),
+            ("sharpe", s.sharpe),
+            ("max_drawdown", s.max_drawdown),
+            ("turnover", s.turnover),
+            ("fills", s.fills as f64),
+            ("hedges", s.hedges as f64),
+        ];
+        BacktestResult {
+            label: label.to_string(),
+            metrics: metrics
+                .into_iter()
+                .map(|(name, v)| (name.to_string(), v))
+                .collect(),
+            trade_pnls: self.trade_pnls.clone(),
+        }
+    }
+}
+
+impl fmt::Display for BacktestStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PnL {:.2} (fees {:.2}, funding {:.2}) | Sharpe {:.2} | max drawdown {:.2} | turnover ${:.2} | fills {} | hedges {}",
+            self.total_pnl, self.fees, self.funding, self.sharpe, self.max_drawdown, self.turnover, self.fills, self.hedges
+        )
+    }
+}
+
+// Average-cost inventory, so each reducing fill yields a realized trade PnL
+#[derive(Debug, Default)]
+struct Book {
+    base: f64,
+    cash: f64,
+    avg_px: f64,
+    fees: f64,
+    funding: f64,
+}
+
+impl Book {
+    // Longs pay positive rates to shorts, on the position's notional at `mark`
+    fn pay_funding(&mut self, rate: f64, mark: f64) {
+        let payment = self.base * mark * rate;
+        self.cash -= payment;
+        self.funding += payment;
+    }
+
+    fn fill(&mut self, fill: &BacktestFill) -> Option<f64> {
+        let signed = if fill.is_buy { fill.sz } else { -fill.sz };
+        self.cash -= signed * fill.px + fill.fee;
+        self.fees += fill.fee;
+        let reduces = self.base * signed < 0.0;
+        let realized = reduces.then(|| {
+            let closed = fill.sz.min(self.base.abs());
+            closed * (fill.px - self.avg_px) * self.base.signum() - fill.fee
+        });
+        let next = self.base + signed;
+        if next.abs() <= EPSILON {
+            self.avg_px = 0.0;
+        } else if !reduces {
+            self.avg_px = (self.avg_px * self.base.abs() + fill.px * fill.sz) / next.abs();
+        } else if next * self.base < 0.0 {
+            // Flipped through flat: the remainder opens at the fill price
+            self.avg_px = fill.px;
+        }
+        self.base = next;
+        realized
+    }
+}
+
+fn sharpe(curve: &[(u64, f64)], sample_ms: u64) -> f64 {
+    let (Some(first), true) = (curve.first(), sample_ms > 0) else {
+        return 0.0;
+    };
+    let mut samples = vec![first.1];
+    let mut next = first.0 + sample_ms;
+    for &(time, pnl) in curve {
+        while time >= next {
+            samples.push(pnl);
+            next += sample_ms;
+        }
+        *samples.last_mut().unwrap() = pnl;
+    }
+    let changes: Vec<f64> = samples.windows(2).map(|w| w[1] - w[0]).collect();
+    if changes.len() < 2 {
+        return 0.0;
+    }
+    let (mean, var) = mean_var(&changes);
+    if var <= 0.0 {
+        return 0.0;
+    }
+    mean / var.sqrt() * (YEAR_MS / sample_ms as f64).sqrt()
+}
+
+fn max_drawdown(curve: &[(u64, f64)]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst: f64 = 0.0;
+    for &(_, pnl) in curve {
+        peak = peak.max(pnl);
+        worst = worst.max(peak - pnl);
+    }
+    worst
+}
+
+// Total size of the levels whose price passes `through`
+fn depth_through(levels: &[BookLevel], through: impl Fn(f64) -> bool) -> f64 {
+    levels
+        .iter()
+        .filter_map(|l| Some((l.px.parse::<f64>().ok()?, l.sz.parse::<f64>().ok()?)))
+        .filter(|(px, _)| through(*px))
+        .map(|(_, sz)| sz)
+        .sum()
+}
+
+// A quote resting in the simulation, with its place in the queue at its price
+// when the run is queue-aware
+struct Resting {
+    quote: QuoteProposal,
+    queue: Option<QueuePosition>,
+}
+
+// Size shown on `levels` at exactly `px`
+fn depth_at(levels: &[BookLevel], px: f64) -> f64 {
+    depth_through(levels, |level| (level - px).abs() <= EPSILON)
+}
+
+// Replays one coin's events through the signal engine, quote builder and risk
+// manager. Unlike the soak and stress runs, approved quotes are not assumed
+// filled: they rest until the next book and fill at their price when a trade
+// prints at or through them, or when that book trades through them. Fills are
+// partial: a trade fills no more than its size and a book no more than the
+// size it shows through the quote. Overflow hedges fill at the touch as
+// takers. Funding in `config.funding` is charged at each rate's time on the
+// position then held, marked to the last mid.
+//
+// With `queue_ahead_fraction` set, a print at the quote's price must first
+// trade through the size estimated ahead of it. The estimate follows the
+// paper exchange's `QueuePosition`: set on joining, shrunk by cancels seen in
+// later books and by prints at the price. A quote re-posted at an unchanged
+// price keeps its place.
+pub fn run_backtest(events: &[MarketEvent], config: &BacktestConfig) -> BacktestRun {
+    let mut engine = SignalEngine::new().with_windows(config.windows);
+    let risk = RiskManager::new(config.max_position);
+    let mut book = Book::default();
+    let mut resting: Vec<Resting> = Vec::new();
+    let mut run = BacktestRun::default();
+    let mut funding = config.funding.iter().peekable();
+    let mut mid = None;
+
+    let fill = |run: &mut BacktestRun, book: &mut Book, f: BacktestFill| {
+        if let Some(pnl) = book.fill(&f) {
+            run.trade_pnls.push(pnl);
+        }
+        run.stats.turnover += f.px * f.sz;
+        run.fills.push(f);
+    };
+    for event in events {
+        while let Some((_, rate)) = funding.next_if(|(time, _)| *time <= event.time()) {
+            if let Some(mark) = mid {
+                book.pay_funding(*rate, mark);
+            }
+        }
+        match event {
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => {
+                engine.process_trade(*px, *sz, *is_buy, *time);
+                // Only aggressors from the other side trade against our quotes
+                let mut left = *sz;
+                for Resting { quote: q, queue } in resting.iter_mut() {
+                    let buy = q.side == "Buy";
+                    if buy == *is_buy || left <= 0.0 {
+                        continue;
+                    }
+                    let through = if buy { *px < q.price } else { *px > q.price };
+                    let at_price = (*px - q.price).abs() <= EPSILON;
+                    let reaches = match queue {
+                        Some(queue) if at_price => queue.on_trade(left),
+                        _ if through || at_price => left,
+                        _ => 0.0,
+                    };
+                    let take = q.size.min(reaches);
+                    if take <= 0.0 {
+                        continue;
+                    }
+                    left -= take;
+                    q.size -= take;
+                    let f = BacktestFill {
+                        time: *time,
+                        is_buy: buy,
+                        px: q.price,
+                        sz: take,
+                        fee: config.fees.fee(q.price * take, false),
+                        taker: false,
+                    };
+                    fill(&mut run, &mut book, f);
+                }
+                resting.retain(|r| r.quote.size > EPSILON);
+            }
+            MarketEvent::Book { time, bids, asks } => {
+                let Some((bid_px, ask_px, bid_vol, ask_vol)) = top_of_book(bids, asks) else {
+                    continue;
+                };
+                // A book through a quote fills it up to the size shown at or
+                // beyond its price
+                let mut queues = Vec::new();
+                for Resting { quote: q, queue } in resting.drain(..) {
+                    let buy = q.side == "Buy";
+                    let shown = if buy {
+                        depth_through(asks, |px| px <= q.price)
+                    } else {
+                        depth_through(bids, |px| px >= q.price)
+                    };
+                    let sz = q.size.min(shown);
+                    if sz > EPSILON {
+                        let f = BacktestFill {
+                            time: *time,
+                            is_buy: buy,
+                            px: q.price,
+                            sz,
+                            fee: config.fees.fee(q.price * sz, false),
+                            taker: false,
+                        };
+                        fill(&mut run, &mut book, f);
+                    }
+                    // Size ahead can only shrink: cancels at our level
+                    if let Some(mut queue) = queue {
+                        queue.on_book(depth_at(if buy { bids } else { asks }, q.price));
+                        queues.push((buy, q.price, queue));
+                    }
+                }
+                engine.process_l2_book(*time, bid_px, ask_px, bid_vol, ask_vol);
+                engine.state.position.base = book.base;
+                engine.state.position.quote = book.cash;
+                if let Some(hedge) = risk.overflow_hedge(&engine.state) {
+                    let px = if hedge.is_buy { ask_px } else { bid_px };
+                    let f = BacktestFill {
+                        time: *time,
+                        is_buy: hedge.is_buy,
+                        px,
+                        sz: hedge.size,
+                        fee: config.fees.fee(px * hedge.size, true),
+                        taker: true,
+                    };
+                    fill(&mut run, &mut book, f);
+                    run.stats.hedges += 1;
+                    engine.state.position.base = book.base;
+                    engine.state.position.quote = book.cash;
+                }
+                // `evaluate` books approved quotes as filled; here they rest
+                // instead, so the position is put back
+                let position = engine.state.position.clone();
+                let quotes = config.quoting.quotes_for(&engine.state);
+                resting = risk
+                    .evaluate(&mut engine.state, &quotes)
+                    .into_iter()
+                    .map(|quote| {
+                        let buy = quote.side == "Buy";
+                        let queue = config.queue_ahead_fraction.map(|fraction| {
+                            queues
+                                .iter()
+                                .find(|(b, px, _)| *b == buy && (px - quote.price).abs() <= EPSILON)
+                                .map(|(_, _, queue)| *queue)
+                                .unwrap_or_else(|| {
+                                    let near = if buy { bids } else { asks };
+                                    QueuePosition::join(depth_at(near, quote.price), fraction)
+                                })
+                        });
+                        Resting { quote, queue }
+                    })
+                    .collect();
+                engine.state.position = position;
+                let touch_mid = (bid_px + ask_px) / 2.0;
+                mid = Some(touch_mid);
+                run.equity_curve
+                    .push((*time, book.cash + book.base * touch_mid));
+            }
+        }
+    }
+    run.stats.fills = run.fills.len();
+    run.stats.fees = book.fees;
+    run.stats.funding = book.funding;
+    run.stats.total_pnl = run.equity_curve.last().map_or(0.0, |(_, pnl)| *pnl);
+    run.stats.sharpe = sharpe(&run.equity_curve, config.sample_ms);
+    run.stats.max_drawdown = max_drawdown(&run.equity_curve);
+    run
+}
+
+fn candle_px(name: &str, value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|_| Error::Backtest(format!("bad candle {name} {value:?}")))
+}
+
+// Synthetic events from `InfoClient::candles_snapshot` for periods without
+// recorded depth: a one-level book `spread_bps` wide around the open, trades at
+// the low and high (in the order the candle's direction implies) splitting its
+// volume, and a book around the close
+pub fn candle_events(
+    candles: &[CandlesSnapshotResponse],
+    spread_bps: f64,
+) -> Result<Vec<MarketEvent>> {
+    let book = |time: u64, mid: f64| {
+        let half = mid * spread_bps / 20_000.0;
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        MarketEvent::Book {
+            time,
+            bids: vec![level(mid - half)],
+            asks: vec![level(mid + half)],
+        }
+    };
+    let mut events = Vec::with_capacity(candles.len() * 4);
+    for c in candles {
+        let open = candle_px("open", &c.open)?;
+        let close = candle_px("close", &c.close)?;
+        let high = candle_px("high", &c.high)?;
+        let low = candle_px("low", &c.low)?;
+        let sz = candle_px("volume", &c.vlm)? / 2.0;
+        let step = c.time_close.saturating_sub(c.time_open) / 3;
+        // Sellers print the low, buyers the high
+        let mut extremes = [(low, false), (high, true)];
+        if close < open {
+            extremes.reverse();
+        }
+        events.push(book(c.time_open, open));
+        for (i, (px, is_buy)) in extremes.into_iter().enumerate() {
+            events.push(MarketEvent::Trade {
+                time: c.time_open + step * (i as u64 + 1),
+                px,
+                sz,
+                is_buy,
+            });
+        }
+        events.push(book(c.time_close, close));
+    }
+    Ok(events)
+}
+
+// Funding history as (time, rate) pairs in time order, for `BacktestConfig::funding`
+pub fn funding_rates(history: &[FundingHistoryResponse]) -> Result<Vec<(u64, f64)>> {
+    let mut rates = history
+        .iter()
+        .map(|f| {
+            let rate = f
+                .funding_rate
+                .parse()
+                .map_err(|_| Error::Backtest(format!("bad funding rate {:?}", f.funding_rate)))?;
+            Ok((f.time, rate))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    rates.sort_by_key(|(time, _)| *time);
+    Ok(rates)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDelta {
+    pub name: String,
+    pub baseline: Option<f64>,
+    pub candidate: Option<f64>,
+}
+
+impl MetricDelta {
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.candidate? - self.baseline?)
+    }
+
+    pub fn delta_pct(&self) -> Option<f64> {
+        let base = self.baseline?;
+        if base.abs() < 1e-12 {
+            return None;
+        }
+        Some(100.0 * self.delta()? / base.abs())
+    }
+}
+
+// Candidate minus baseline mean trade PnL with its normal-approximation bounds
+// (Welch standard error, so the two runs may differ in trade count and variance)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeanDiff {
+    pub diff: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Improved,
+    NoSignificantChange,
+    Regressed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub baseline: String,
+    pub candidate: String,
+    pub metrics: Vec<MetricDelta>,
+    pub trade_pnl: Option<MeanDiff>, // None when either side has fewer than two trades
+    pub verdict: Verdict,
+}
+
+fn mean_var(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var)
+}
+
+fn mean_diff(baseline: &[f64], candidate: &[f64], z: f64) -> Option<MeanDiff> {
+    if baseline.len() < 2 || candidate.len() < 2 {
+        return None;
+    }
+    let (mb, vb) = mean_var(baseline);
+    let (mc, vc) = mean_var(candidate);
+    let se = (vb / baseline.len() as f64 + vc / candidate.len() as f64).sqrt();
+    let diff = mc - mb;
+    Some(MeanDiff {
+        diff,
+        lower: diff - z * se,
+        upper: diff + z * se,
+    })
+}
+
+// Diffs every metric present in either result and tests whether the candidate's
+// mean trade PnL differs from the baseline's beyond `z` standard errors. Only a
+// significant drop counts as a regression.
+pub fn compare_results(
+    baseline: &BacktestResult,
+    candidate: &BacktestResult,
+    z: f64,
+) -> Comparison {
+    let mut names: Vec<&String> = baseline.metrics.keys().collect();
+    names.extend(candidate.metrics.keys());
+    names.sort();
+    names.dedup();
+    let metrics = names
+        .into_iter()
+        .map(|name| MetricDelta {
+            name: name.clone(),
+            baseline: baseline.metrics.get(name).copied(),
+            candidate: candidate.metrics.get(name).copied(),
+        })
+        .collect();
+    let trade_pnl = mean_diff(&baseline.trade_pnls, &candidate.trade_pnls, z);
+    let verdict = match trade_pnl {
+        Some(d) if d.upper < 0.0 => Verdict::Regressed,
+        Some(d) if d.lower > 0.0 => Verdict::Improved,
+        _ => Verdict::NoSignificantChange,
+    };
+    Comparison {
+        baseline: baseline.label.clone(),
+        candidate: candidate.label.clone(),
+        metrics,
+        trade_pnl,
+        verdict,
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{v:.4}"));
+        writeln!(
+            f,
+            "baseline {} vs candidate {}",
+            self.baseline, self.candidate
+        )?;
+        writeln!(
+            f,
+            "{:<24} {:>14} {:>14} {:>14} {:>9}",
+            "metric", "baseline", "candidate", "delta", "delta%"
+        )?;
+        for m in &self.metrics {
+            writeln!(
+                f,
+                "{:<24} {:>14} {:>14} {:>14} {:>9}",
+                m.name,
+                show(m.baseline),
+                show(m.candidate),
+                show(m.delta()),
+                m.delta_pct()
+                    .map_or("-".to_string(), |p| format!("{p:+.1}%"))
+            )?;
+        }
+        match &self.trade_pnl {
+            Some(d) => writeln!(
+                f,
+                "mean trade PnL delta {:.6} [{:.6}, {:.6}]",
+                d.diff, d.lower, d.upper
+            )?,
+            None => writeln!(f, "mean trade PnL delta: not enough trades")?,
+        }
+        write!(f, "verdict: {:?}", self.verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(label: &str, pnls: &[f64]) -> BacktestResult {
+        BacktestResult {
+            label: label.to_string(),
+            metrics: BTreeMap::from([("total_pnl".to_string(), pnls.iter().sum())]),
+            trade_pnls: pnls.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_resting_quotes_fill_only_when_traded_through() {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        let book = |time, bid: f64, ask: f64| MarketEvent::Book {
+            time,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+        };
+        let trade = |time, px, is_buy| MarketEvent::Trade {
+            time,
+            px,
+            sz: 10.0,
+            is_buy,
+        };
+        let mut events = vec![trade(0, 100.0, true), trade(1, 100.0, false)];
+        events.push(book(1_000, 99.0, 101.0));
+        // Prints inside the spread but short of our quotes
+        events.push(trade(1_200, 100.0, true));
+        let run = run_backtest(&events, &BacktestConfig::default());
+        assert!(run.fills.is_empty());
+
+        // A buyer lifts our ask, then a seller hits our bid
+        events.push(trade(1_500, 101.0, true));
+        events.push(trade(2_000, 99.0, false));
+        events.push(book(3_000, 99.0, 101.0));
+        let run = run_backtest(&events, &BacktestConfig::default());
+        let [sell, buy] = &run.fills[..] else {
+            panic!("expected two fills, got {:?}", run.fills);
+        };
+        assert!(!sell.is_buy && !sell.taker && sell.px < 101.0);
+        assert!(buy.is_buy && buy.px > 99.0 && buy.px < sell.px);
+        // The round trip captured the spread between our quotes, less fees
+        assert_eq!(run.trade_pnls.len(), 1);
+        assert!(run.trade_pnls[0] > 0.0);
+        // Flat again: the curve ends at the trade's PnL less the opening fee
+        assert!((run.stats.total_pnl - (run.trade_pnls[0] - sell.fee)).abs() < 1e-9);
+        assert_eq!(run.to_result("t").metrics["fills"], run.fills.len() as f64);
+    }
+
+    #[test]
+    fn test_queue_aware_quotes_wait_for_the_size_ahead() {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "5".to_string(),
+            n: 1,
+        };
+        let book = |time, bid: f64, ask: f64| MarketEvent::Book {
+            time,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+        };
+        let sell = |time, sz| MarketEvent::Trade {
+            time,
+            px: 99.0,
+            sz,
+            is_buy: false,
+        };
+        let buy = MarketEvent::Trade {
+            time: 0,
+            px: 100.0,
+            sz: 10.0,
+            is_buy: true,
+        };
+        // No spread inside the touch: the bid joins the 5 resting at 99
+        let quoting = QuoteLayerManager {
+            spread_ticks: 0.0,
+            aggressive_spread_ticks: 0.0,
+            ..Default::default()
+        };
+        let events = vec![
+            buy,
+            sell(1, 10.0),
+            book(1_000, 99.0, 101.0),
+            sell(2_000, 3.0),
+        ];
+        let naive = run_backtest(
+            &events,
+            &BacktestConfig {
+                quoting: quoting.clone(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(naive.fills.len(), 1);
+        assert_eq!(naive.fills[0].px, 99.0);
+
+        let config = BacktestConfig {
+            quoting,
+            queue_ahead_fraction: Some(1.0),
+            ..Default::default()
+        };
+        assert!(run_backtest(&events, &config).fills.is_empty());
+        // A later print eats the rest of the queue and reaches us
+        let mut events = events;
+        events.push(sell(2_500, 3.0));
+        let run = run_backtest(&events, &config);
+        assert_eq!(run.fills.len(), 1);
+        assert!((run.fills[0].sz - naive.fills[0].sz.min(1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_funding_is_charged_on_the_held_position() {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        let book = |time, bid: f64, ask: f64| MarketEvent::Book {
+            time,
+            bids: vec![level(bid)],
+            asks: vec![level(ask)],
+        };
+        let trade = |time, px, is_buy| MarketEvent::Trade {
+            time,
+            px,
+            sz: 10.0,
+            is_buy,
+        };
+        // A seller hits our bid, leaving us long through the funding time
+        let events = vec![
+            trade(0, 100.0, true),
+            trade(1, 100.0, false),
+            book(1_000, 99.0, 101.0),
+            trade(2_000, 99.0, false),
+            book(3_000, 99.0, 101.0),
+        ];
+        let unfunded = run_backtest(&events, &BacktestConfig::default());
+        let history: Vec<FundingHistoryResponse> = serde_json::from_value(serde_json::json!([
+            {"coin": "BTC", "fundingRate": "0.001", "premium": "0", "time": 2_500},
+            {"coin": "BTC", "fundingRate": "0.5", "premium": "0", "time": 9_000},
+        ]))
+        .unwrap();
+        let config = BacktestConfig {
+            funding: funding_rates(&history).unwrap(),
+            ..Default::default()
+        };
+        let funded = run_backtest(&events, &config);
+
+        let long: f64 = unfunded.fills.iter().map(|f| f.sz).sum();
+        assert!(long > 0.0 && unfunded.fills.iter().all(|f| f.is_buy));
+        // Only the rate inside the replayed period applies, on the 100 mid
+        let paid = long * 100.0 * 0.001;
+        assert!((funded.stats.funding - paid).abs() < 1e-9);
+        assert!((unfunded.stats.total_pnl - funded.stats.total_pnl - paid).abs() < 1e-9);
+        assert_eq!(
+            funded.to_result("t").metrics["funding"],
+            funded.stats.funding
+        );
+    }
+
+    #[test]
+    fn test_candles_become_book_and_trade_events() {
+        let candle: CandlesSnapshotResponse = serde_json::from_value(serde_json::json!({
+            "t": 0, "T": 59_999, "s": "BTC", "i": "1m", "o": "100", "c": "98",
+            "h": "101", "l": "97", "v": "4", "n": 10
+        }))
+        .unwrap();
+        let events = candle_events(&[candle], 10.0).unwrap();
+        assert_eq!(events.len(), 4);
+        // A down candle prints the high before the low
+        assert!(
+            matches!(events[1], MarketEvent::Trade { px, is_buy: true, sz, .. } if px == 101.0 && sz == 2.0)
+        );
+        assert!(matches!(events[2], MarketEvent::Trade { px, is_buy: false, .. } if px == 97.0));
+        assert_eq!(events[3].time(), 59_999);
+    }
+
+    #[test]
+    fn test_compare_flags_only_significant_drops() {
+        let base = result("main", &[1.0, 1.2, 0.8, 1.1, 0.9, 1.0, 1.05, 0.95]);
+        let noisy = result("noisy", &[1.1, 0.7, 1.3, 0.9, 1.0, 0.85, 1.15, 0.95]);
+        let worse = result("worse", &[0.1, 0.2, -0.1, 0.0, 0.15, 0.05, -0.05, 0.1]);
+
+        let cmp = compare_results(&base, &noisy, DEFAULT_SIGNIFICANCE_Z);
+        assert_eq!(cmp.verdict, Verdict::NoSignificantChange);
+
+        let cmp = compare_results(&base, &worse, DEFAULT_SIGNIFICANCE_Z);
+        assert_eq!(cmp.verdict, Verdict::Regressed);
+        assert_eq!(cmp.metrics[0].name, "total_pnl");
+        assert!(cmp.metrics[0].delta_pct().unwrap() < -90.0);
+
+        let cmp = compare_results(&worse, &base, DEFAULT_SIGNIFICANCE_Z);
+        assert_eq!(cmp.verdict, Verdict::Improved);
+    }
+}
@@ -0,0 +1,252 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{prelude::*, Error, TapeEvent, TapeRecord};
+
+// Mid-price OHLC plus traded volume for one coin over one interval. A bar that
+// opens on a trade, before any book, starts from the trade price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bar {
+    pub coin: String,
+    pub start_ms: u64,
+    pub interval_ms: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub buy_volume: f64, // taken by buy aggressors
+    pub notional: f64,
+    pub trades: u64,
+    pub updates: u64, // book and BBO updates
+}
+
+impl Bar {
+    fn new(coin: &str, start_ms: u64, interval_ms: u64, px: f64) -> Self {
+        Self {
+            coin: coin.to_string(),
+            start_ms,
+            interval_ms,
+            open: px,
+            high: px,
+            low: px,
+            close: px,
+            volume: 0.0,
+            buy_volume: 0.0,
+            notional: 0.0,
+            trades: 0,
+            updates: 0,
+        }
+    }
+
+    pub fn vwap(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.notional / self.volume)
+    }
+
+    fn mark(&mut self, px: f64) {
+        self.high = self.high.max(px);
+        self.low = self.low.min(px);
+        self.close = px;
+    }
+}
+
+// Event time (exchange time for the update) and the price it implies: the mid
+// for books and BBO, the print for trades
+fn observation(event: &TapeEvent) -> Option<(u64, f64)> {
+    let mid = |bid: f64, ask: f64| (bid > 0.0 && ask > bid).then(|| (bid + ask) / 2.0);
+    match event {
+        TapeEvent::Book { time, bids, asks } => {
+            Some((*time, mid(bids.first()?.0, asks.first()?.0)?))
+        }
+        TapeEvent::Bbo { time, bid, ask } => Some((*time, mid((*bid)?.0, (*ask)?.0)?)),
+        TapeEvent::Trade { time, px, .. } => Some((*time, *px)),
+        TapeEvent::Gap { .. } => None,
+    }
+}
+
+// Rolls tape records into bars of one interval, per coin. Intervals with no
+// updates produce no bar.
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    pub interval_ms: u64,
+    open: HashMap<String, Bar>,
+}
+
+impl BarBuilder {
+    pub fn new(interval_ms: u64) -> Self {
+        Self {
+            interval_ms,
+            open: HashMap::new(),
+        }
+    }
+
+    // Returns the coin's previous bar once a record lands in a later interval
+    pub fn on_record(&mut self, record: &TapeRecord) -> Option<Bar> {
+        let (time, px) = observation(&record.event)?;
+        let start = time - time % self.interval_ms;
+        let mut done = None;
+        let bar = match self.open.get_mut(&record.coin) {
+            Some(bar) if bar.start_ms >= start => bar,
+            _ => {
+                let bar = Bar::new(&record.coin, start, self.interval_ms, px);
+                done = self.open.insert(record.coin.clone(), bar);
+                self.open.get_mut(&record.coin).unwrap()
+            }
+        };
+        match record.event {
+            TapeEvent::Trade { sz, is_buy, .. } => {
+                bar.volume += sz;
+                bar.notional += sz * px;
+                bar.trades += 1;
+                if is_buy {
+                    bar.buy_volume += sz;
+                }
+                // Prints stand in for the mid until a book arrives
+                if bar.updates == 0 {
+                    bar.mark(px);
+                }
+            }
+            _ => {
+                bar.updates += 1;
+                bar.mark(px);
+            }
+        }
+        done
+    }
+
+    // Bars still open, e.g. at shutdown
+    pub fn finish(&mut self) -> Vec<Bar> {
+        let mut bars: Vec<_> = self.open.drain().map(|(_, bar)| bar).collect();
+        bars.sort_by(|a, b| a.coin.cmp(&b.coin));
+        bars
+    }
+}
+
+// Appends bars as JSON lines to `dir/bars-<resolution>/<YYYY-MM-DD>.jsonl`, one
+// file per UTC day of the bar's start. Bars are kept forever; only ticks expire.
+#[derive(Debug)]
+pub struct BarStore {
+    dir: PathBuf,
+}
+
+impl BarStore {
+    pub fn new(dir: impl AsRef<Path>, resolution: &str) -> Result<Self> {
+        let dir = dir.as_ref().join(format!("bars-{resolution}"));
+        fs::create_dir_all(&dir).map_err(|e| Error::Recording(e.to_string()))?;
+        Ok(Self { dir })
+    }
+
+    pub fn append(&self, bar: &Bar) -> Result<()> {
+        let day = DateTime::from_timestamp_millis(bar.start_ms as i64)
+            .ok_or_else(|| Error::Recording(format!("bad bar time {}", bar.start_ms)))?;
+        let path = self.dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")));
+        let mut line = serde_json::to_string(bar).map_err(|e| Error::Recording(e.to_string()))?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| Error::Recording(format!("{}: {e}", path.display())))
+    }
+}
+
+// Every bar in one bar file, or in all day files of a `bars-<resolution>`
+// directory in date order
+pub fn load_bars(path: impl AsRef<Path>) -> Result<Vec<Bar>> {
+    let path = path.as_ref();
+    let mut files = if path.is_dir() {
+        fs::read_dir(path)
+            .map_err(|e| Error::Recording(e.to_string()))?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    files.sort();
+    let mut bars = Vec::new();
+    for file in files {
+        let reader =
+            BufReader::new(File::open(&file).map_err(|e| Error::Recording(e.to_string()))?);
+        for line in reader.lines() {
+            let line = line.map_err(|e| Error::Recording(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            bars.push(
+                serde_json::from_str(&line)
+                    .map_err(|e| Error::Recording(format!("{}: {e}", file.display())))?,
+            );
+        }
+    }
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(event: TapeEvent) -> TapeRecord {
+        TapeRecord {
+            received_ms: 0,
+            coin: "BTC".to_string(),
+            event,
+        }
+    }
+
+    fn book(time: u64, bid: f64) -> TapeRecord {
+        record(TapeEvent::Book {
+            time,
+            bids: vec![(bid, 1.0, 1)],
+            asks: vec![(bid + 2.0, 1.0, 1)],
+        })
+    }
+
+    fn trade(time: u64, px: f64, sz: f64, is_buy: bool) -> TapeRecord {
+        record(TapeEvent::Trade {
+            time,
+            px,
+            sz,
+            is_buy,
+        })
+    }
+
+    #[test]
+    fn test_rolls_mids_and_trades_into_bars() {
+        let mut builder = BarBuilder::new(1_000);
+        // Opens on a trade, then the book takes over the price
+        assert!(builder.on_record(&trade(100, 99.0, 2.0, false)).is_none());
+        assert!(builder.on_record(&book(200, 100.0)).is_none());
+        assert!(builder.on_record(&book(300, 104.0)).is_none());
+        assert!(builder.on_record(&trade(400, 105.0, 1.0, true)).is_none());
+        assert!(builder.on_record(&book(900, 98.0)).is_none());
+
+        let bar = builder.on_record(&book(2_500, 100.0)).unwrap();
+        assert_eq!((bar.start_ms, bar.open, bar.close), (0, 99.0, 99.0));
+        assert_eq!((bar.high, bar.low), (105.0, 99.0));
+        assert_eq!(
+            (bar.volume, bar.buy_volume, bar.trades, bar.updates),
+            (3.0, 1.0, 2, 3)
+        );
+        assert!((bar.vwap().unwrap() - 101.0).abs() < 1e-9);
+
+        let open = builder.finish();
+        assert_eq!(open[0].start_ms, 2_000);
+        assert_eq!(open[0].vwap(), None);
+
+        let dir = std::env::temp_dir().join(format!("bars-{}", uuid::Uuid::new_v4()));
+        let store = BarStore::new(&dir, "1s").unwrap();
+        store.append(&bar).unwrap();
+        store.append(&open[0]).unwrap();
+        let loaded = load_bars(dir.join("bars-1s")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, vec![bar, open[0].clone()]);
+    }
+}
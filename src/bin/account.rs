@@ -0,0 +1,168 @@
+/*
+Prints a formatted account summary: balances, margin usage, positions,
+open orders and today's (UTC) fills.
+
+    account <ADDRESS> [--network mainnet|testnet]
+*/
+use chrono::{TimeZone, Utc};
+use ethers::types::H160;
+use hyperliquid_rust_sdk::{BaseUrl, InfoClient};
+use std::{env, process};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: account <ADDRESS> [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn fail(what: &str, err: impl std::fmt::Display) -> ! {
+    eprintln!("could not fetch {what}: {err}");
+    process::exit(1)
+}
+
+fn num(s: &str) -> f64 {
+    s.parse().unwrap_or(0.0)
+}
+
+fn time_of(ms: u64) -> String {
+    Utc.timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (address, network) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [address] => (address, "mainnet"),
+        [address, "--network", network] => (address, network),
+        _ => usage("expected an address"),
+    };
+    let user: H160 = address
+        .parse()
+        .unwrap_or_else(|_| usage(&format!("invalid address {address}")));
+    let base_url = match network {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| fail("info client", e));
+
+    let user_state = info_client
+        .user_state(user)
+        .await
+        .unwrap_or_else(|e| fail("user state", e));
+    let summary = &user_state.margin_summary;
+    let account_value = num(&summary.account_value);
+    let margin_used = num(&summary.total_margin_used);
+    let margin_pct = if account_value > 0.0 {
+        margin_used / account_value * 100.0
+    } else {
+        0.0
+    };
+
+    println!("Account {address} ({network})");
+    println!();
+    println!("== Balances ==");
+    println!("  account value   {account_value:>14.2}");
+    println!("  withdrawable    {:>14.2}", num(&user_state.withdrawable));
+    println!("  margin used     {margin_used:>14.2}  ({margin_pct:.1}%)");
+    println!("  total notional  {:>14.2}", num(&summary.total_ntl_pos));
+    match info_client.user_token_balances(user).await {
+        Ok(balances) => {
+            for balance in balances.balances.iter().filter(|b| num(&b.total) != 0.0) {
+                println!(
+                    "  spot {:<10} {:>14} (hold {})",
+                    balance.coin, balance.total, balance.hold
+                );
+            }
+        }
+        Err(e) => eprintln!("  spot balances unavailable: {e}"),
+    }
+
+    println!();
+    println!("== Positions ==");
+    let positions: Vec<_> = user_state
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .filter(|p| num(&p.szi) != 0.0)
+        .collect();
+    if positions.is_empty() {
+        println!("  none");
+    } else {
+        println!(
+            "  {:<8} {:>12} {:>12} {:>12} {:>12} {:>12} {:>8}",
+            "coin", "size", "entry", "liq", "value", "uPnL", "lev"
+        );
+        for p in positions {
+            println!(
+                "  {:<8} {:>12} {:>12} {:>12} {:>12.2} {:>12.2} {:>5}x {}",
+                p.coin,
+                p.szi,
+                p.entry_px.as_deref().unwrap_or("-"),
+                p.liquidation_px.as_deref().unwrap_or("-"),
+                num(&p.position_value),
+                num(&p.unrealized_pnl),
+                p.leverage.value,
+                p.leverage.type_string,
+            );
+        }
+    }
+
+    println!();
+    println!("== Open orders ==");
+    let orders = info_client
+        .open_orders(user)
+        .await
+        .unwrap_or_else(|e| fail("open orders", e));
+    if orders.is_empty() {
+        println!("  none");
+    }
+    for order in orders {
+        let side = if order.side == "B" { "buy" } else { "sell" };
+        println!(
+            "  {} {:<8} {:<4} {:>12} @ {:>12}  oid {}",
+            time_of(order.timestamp),
+            order.coin,
+            side,
+            order.sz,
+            order.limit_px,
+            order.oid
+        );
+    }
+
+    println!();
+    println!("== Today's fills (UTC) ==");
+    let day_start = Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc().timestamp_millis() as u64)
+        .unwrap_or(0);
+    let fills = info_client
+        .user_fills(user)
+        .await
+        .unwrap_or_else(|e| fail("fills", e));
+    let (mut volume, mut fees, mut pnl, mut count) = (0.0, 0.0, 0.0, 0);
+    for fill in fills.iter().filter(|f| f.time >= day_start) {
+        println!(
+            "  {} {:<8} {:<14} {:>12} @ {:>12}  fee {:>8}  pnl {:>8}",
+            time_of(fill.time),
+            fill.coin,
+            fill.dir,
+            fill.sz,
+            fill.px,
+            fill.fee,
+            fill.closed_pnl
+        );
+        volume += num(&fill.sz) * num(&fill.px);
+        fees += num(&fill.fee);
+        pnl += num(&fill.closed_pnl);
+        count += 1;
+    }
+    println!("  {count} fills, volume {volume:.2}, fees {fees:.4}, closed PnL {pnl:.4}");
+}
@@ -0,0 +1,275 @@
+/*
+Backtest tooling.
+
+    backtest run (--file orderbook_log.json [--coin BTC] | --candles COIN --start MS --end MS
+                 [--interval 1m] [--spread-bps 2] [--network mainnet|testnet])
+                 [--config bot.toml] [--max-position 5] [--maker-bps 1.5] [--taker-bps 4.5]
+                 [--funding] [--queue FRACTION] [--label NAME] [--out RESULT.json]
+                 [--equity-csv FILE]
+    backtest compare BASELINE.json CANDIDATE.json [--z 1.96]
+    backtest montecarlo RESULT.json|journal.jsonl [--paths 10000] [--trades N]
+                        [--equity 1000] [--ruin-dd 0.5] [--dd-budget PNL] [--seed N]
+
+`run` replays recorded book and trade data, or candles fetched from the info
+API, through the signal engine, quote builder and risk manager with simulated
+fills, printing PnL, Sharpe, max drawdown and turnover. --config takes the
+coin's position limit, signal windows, quote spreads and inventory skew from a
+bot config file, and the account's fee tier, rebate and builder fee from its
+[fees] section; --maker-bps and --taker-bps override the exchange fees it picks.
+--queue makes maker fills queue-aware: each quote joins behind FRACTION of the
+size shown at its price (1 is the back of the queue) and fills only once prints
+there have traded through it, as on the paper exchange. --funding fetches
+the coin's funding history for the replayed period from the info API and charges
+it on the simulated position, so PnL includes carry as well as fees. --out saves
+a result file for `compare` and `montecarlo`; --equity-csv writes the PnL curve.
+
+`compare` diffs two result files (e.g. main vs a feature branch), printing every
+metric's delta and the candidate's mean trade PnL change with its significance
+bounds. Exits 1 if the candidate is significantly worse, so it can gate CI.
+
+`montecarlo` bootstraps the trade PnLs of a backtest result, or the realized PnLs
+of a live session's journal, into final PnL, drawdown and risk-of-ruin
+distributions. With --dd-budget it also prints the position-size scale that keeps
+the p99 drawdown inside that budget.
+*/
+use hyperliquid_rust_sdk::{
+    candle_events, compare_results, funding_rates, load_recording, read_journal, run_backtest,
+    run_monte_carlo, trade_pnls_from_journal, BacktestConfig, BacktestResult, BaseUrl, BotConfig,
+    InfoClient, MarketEvent, MonteCarloConfig, Verdict, DEFAULT_SIGNIFICANCE_Z,
+};
+use std::{env, fs, process};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: backtest run (--file PATH [--coin COIN] | --candles COIN --start MS --end MS [--interval I] [--spread-bps BPS] [--network N]) [--config FILE] [--max-position SIZE] [--maker-bps BPS] [--taker-bps BPS] [--funding] [--queue FRACTION] [--label NAME] [--out FILE] [--equity-csv FILE]");
+    eprintln!("       backtest compare BASELINE.json CANDIDATE.json [--z 1.96]");
+    eprintln!("       backtest montecarlo FILE [--paths N] [--trades N] [--equity E] [--ruin-dd FRAC] [--dd-budget PNL] [--seed N]");
+    process::exit(2)
+}
+
+fn load(path: &str) -> BacktestResult {
+    BacktestResult::load(path).unwrap_or_else(|e| {
+        eprintln!("failed to load {path}: {e}");
+        process::exit(1)
+    })
+}
+
+fn compare(args: &[String]) {
+    let mut files = Vec::new();
+    let mut z = DEFAULT_SIGNIFICANCE_Z;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--z" => {
+                z = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| usage("--z must be a number"))
+            }
+            flag if flag.starts_with("--") => usage(&format!("unknown flag {flag}")),
+            file => files.push(file),
+        }
+    }
+    let [baseline, candidate] = files[..] else {
+        usage("compare needs a baseline and a candidate file")
+    };
+    let comparison = compare_results(&load(baseline), &load(candidate), z);
+    println!("{comparison}");
+    if comparison.verdict == Verdict::Regressed {
+        process::exit(1)
+    }
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> T {
+    value
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| usage(&format!("{flag} needs a numeric value")))
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{msg}");
+    process::exit(1)
+}
+
+async fn run(args: &[String]) {
+    let mut config = BacktestConfig::default();
+    let (mut file, mut coin, mut candles) = (None, None, None);
+    let (mut start, mut end): (Option<u64>, Option<u64>) = (None, None);
+    let (mut interval, mut spread_bps) = ("1m".to_string(), 2.0);
+    let mut base_url = BaseUrl::Mainnet;
+    let (mut label, mut out, mut equity_csv) = ("backtest".to_string(), None, None);
+    let (mut bot_config, mut max_position) = (None, None);
+    let (mut maker_bps, mut taker_bps) = (None, None);
+    let mut with_funding = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .unwrap_or_else(|| usage(&format!("missing value for {arg}")))
+        };
+        match arg.as_str() {
+            "--file" => file = Some(value()),
+            "--coin" => coin = Some(value().to_uppercase()),
+            "--candles" => candles = Some(value().to_uppercase()),
+            "--start" => start = Some(parse(arg, Some(&value()))),
+            "--end" => end = Some(parse(arg, Some(&value()))),
+            "--interval" => interval = value(),
+            "--spread-bps" => spread_bps = parse(arg, Some(&value())),
+            "--network" => {
+                base_url = match value().as_str() {
+                    "mainnet" => BaseUrl::Mainnet,
+                    "testnet" => BaseUrl::Testnet,
+                    other => usage(&format!("unknown network {other}")),
+                }
+            }
+            "--config" => bot_config = Some(value()),
+            "--max-position" => max_position = Some(parse(arg, Some(&value()))),
+            "--maker-bps" => maker_bps = Some(parse(arg, Some(&value()))),
+            "--taker-bps" => taker_bps = Some(parse(arg, Some(&value()))),
+            "--funding" => with_funding = true,
+            "--queue" => config.queue_ahead_fraction = Some(parse(arg, Some(&value()))),
+            "--label" => label = value(),
+            "--out" => out = Some(value()),
+            "--equity-csv" => equity_csv = Some(value()),
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+    let (coin, events): (String, Vec<MarketEvent>) = match (file, candles) {
+        (Some(file), None) => {
+            let recording = load_recording(&file)
+                .unwrap_or_else(|e| fail(format!("failed to load {file}: {e}")));
+            let coin = coin
+                .or_else(|| recording.events.first().map(|e| e.coin.clone()))
+                .unwrap_or_else(|| fail(format!("{file} has no events")));
+            for (since, until) in &recording.gaps {
+                eprintln!("warning: no data recorded from {since} to {until}");
+            }
+            let events = recording
+                .events
+                .into_iter()
+                .filter(|e| e.coin == coin)
+                .map(|e| e.event)
+                .collect();
+            (coin, events)
+        }
+        (None, Some(coin)) => {
+            let (Some(start), Some(end)) = (start, end) else {
+                usage("--candles needs --start and --end")
+            };
+            let info = InfoClient::new(None, Some(base_url))
+                .await
+                .unwrap_or_else(|e| fail(format!("failed to connect: {e}")));
+            let snapshot = info
+                .candles_snapshot(coin.clone(), interval, start, end)
+                .await
+                .unwrap_or_else(|e| fail(format!("failed to fetch {coin} candles: {e}")));
+            let events =
+                candle_events(&snapshot, spread_bps).unwrap_or_else(|e| fail(e.to_string()));
+            (coin, events)
+        }
+        _ => usage("run needs either --file or --candles"),
+    };
+    if let Some(path) = bot_config {
+        let loaded = BotConfig::load(Some(&path))
+            .unwrap_or_else(|e| fail(format!("failed to load {path}: {e}")));
+        let coin_config = loaded
+            .for_coin(&coin)
+            .unwrap_or_else(|e| fail(format!("failed to load {path}: {e}")));
+        config.fees = loaded.fees.schedule();
+        config.max_position = coin_config.risk.position_limit;
+        config.windows = coin_config.strategy.signal_windows();
+        config.quoting.spread_ticks = coin_config.strategy.spread_ticks;
+        config.quoting.aggressive_spread_ticks = coin_config.strategy.aggressive_spread_ticks;
+        config.quoting.risk_aversion = coin_config.strategy.risk_aversion;
+    }
+    if let Some(size) = max_position {
+        config.max_position = size;
+    }
+    if let Some(bps) = maker_bps {
+        config.fees.maker_bps = bps;
+    }
+    if let Some(bps) = taker_bps {
+        config.fees.taker_bps = bps;
+    }
+    if let (true, Some(first), Some(last)) = (with_funding, events.first(), events.last()) {
+        let info = InfoClient::new(None, Some(base_url))
+            .await
+            .unwrap_or_else(|e| fail(format!("failed to connect: {e}")));
+        let history = info
+            .funding_history_range(&coin, first.time(), last.time())
+            .await
+            .unwrap_or_else(|e| fail(format!("failed to fetch {coin} funding: {e}")));
+        config.funding = funding_rates(&history).unwrap_or_else(|e| fail(e.to_string()));
+        println!("{} funding intervals", config.funding.len());
+    }
+    let run = run_backtest(&events, &config);
+    println!("{} events | {}", events.len(), run.stats);
+    if let Some(path) = equity_csv {
+        let mut csv = String::from("time,pnl\n");
+        for (time, pnl) in &run.equity_curve {
+            csv.push_str(&format!("{time},{pnl}\n"));
+        }
+        fs::write(&path, csv).unwrap_or_else(|e| fail(format!("failed to write {path}: {e}")));
+    }
+    if let Some(path) = out {
+        run.to_result(&label)
+            .save(&path)
+            .unwrap_or_else(|e| fail(format!("failed to write {path}: {e}")));
+    }
+}
+
+fn monte_carlo(args: &[String]) {
+    let mut file = None;
+    let mut config = MonteCarloConfig::default();
+    let mut dd_budget: Option<f64> = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--paths" => config.paths = parse(arg, args.next()),
+            "--trades" => config.trades_per_path = Some(parse(arg, args.next())),
+            "--equity" => config.starting_equity = parse(arg, args.next()),
+            "--ruin-dd" => config.ruin_drawdown = parse(arg, args.next()),
+            "--dd-budget" => dd_budget = Some(parse(arg, args.next())),
+            "--seed" => config.seed = Some(parse(arg, args.next())),
+            flag if flag.starts_with("--") => usage(&format!("unknown flag {flag}")),
+            path => file = Some(path.to_string()),
+        }
+    }
+    let file = file.unwrap_or_else(|| usage("montecarlo needs a result or journal file"));
+    // A .jsonl file is a live session journal; anything else a backtest result
+    let pnls = if file.ends_with(".jsonl") {
+        let records = read_journal(&file).unwrap_or_else(|e| {
+            eprintln!("failed to read {file}: {e}");
+            process::exit(1)
+        });
+        trade_pnls_from_journal(&records)
+    } else {
+        load(&file).trade_pnls
+    };
+    let Some(report) = run_monte_carlo(&pnls, &config) else {
+        eprintln!("{file} has no trades to resample");
+        process::exit(1)
+    };
+    println!("{} trades in {file}", pnls.len());
+    println!("{report}");
+    if let Some(budget) = dd_budget {
+        println!(
+            "size scale for p99 drawdown <= {budget}: {:.3}",
+            report.size_scale_for(budget)
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]).await,
+        Some("compare") => compare(&args[1..]),
+        Some("montecarlo") => monte_carlo(&args[1..]),
+        Some(other) => usage(&format!("unknown command {other}")),
+        None => usage("missing command"),
+    }
+}
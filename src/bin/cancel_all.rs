@@ -0,0 +1,66 @@
+/*
+Cancels every open order for the wallet in HL_PRIVATE_KEY, in batches.
+
+    cancel_all [--network mainnet|testnet]
+*/
+use ethers::signers::LocalWallet;
+use hyperliquid_rust_sdk::{BaseUrl, ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus};
+use std::{env, process};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: cancel_all [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let base_url = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] | ["--network", "testnet"] => BaseUrl::Testnet,
+        ["--network", "mainnet"] => BaseUrl::Mainnet,
+        _ => usage(&format!("unexpected arguments {args:?}")),
+    };
+    let wallet: LocalWallet = env::var("HL_PRIVATE_KEY")
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not set"))
+        .parse()
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not a valid private key"));
+    let exchange_client = ExchangeClient::new(None, wallet, Some(base_url), None, None)
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create exchange client: {e}")));
+
+    let responses = match exchange_client.cancel_all_orders(None).await {
+        Ok(responses) => responses,
+        Err(e) => {
+            eprintln!("cancel failed: {e}");
+            process::exit(1);
+        }
+    };
+
+    let (mut cancelled, mut failed) = (0, 0);
+    for response in responses {
+        match response {
+            ExchangeResponseStatus::Ok(response) => {
+                for status in response.data.map(|data| data.statuses).unwrap_or_default() {
+                    match status {
+                        ExchangeDataStatus::Success => cancelled += 1,
+                        ExchangeDataStatus::Error(e) => {
+                            failed += 1;
+                            eprintln!("cancel rejected: {e}");
+                        }
+                        status => eprintln!("unexpected status {status:?}"),
+                    }
+                }
+            }
+            ExchangeResponseStatus::Err(e) => {
+                failed += 1;
+                eprintln!("batch rejected: {e}");
+            }
+        }
+    }
+    println!("cancelled {cancelled} orders, {failed} failed");
+    if failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,110 @@
+/*
+Replays a recorded market-data file through the signal engine and the paper quoting
+stack and writes three Arrow IPC (Feather v2) files for research:
+
+  ticks.arrow     top of book per book update
+  features.arrow  signal state after each book update
+  fills.arrow     paper fills (approved quotes are assumed filled)
+
+    export_arrow [--file orderbook_log.json] [--out-dir .] [--max-position 5.0]
+
+Needs `--features arrow`. In Python: `pl.read_ipc("features.arrow")` or
+`pd.read_feather("features.arrow")`.
+*/
+use hyperliquid_rust_sdk::{
+    load_recording, top_of_book, write_features, write_fills, write_ticks, FeatureRow, FillRow,
+    MarketEvent, QuoteLayerManager, RiskManager, SignalEngine, TickRow,
+};
+use std::{collections::HashMap, env, path::PathBuf, process};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: export_arrow [--file PATH] [--out-dir DIR] [--max-position SIZE]");
+    process::exit(2)
+}
+
+fn main() {
+    env_logger::init();
+    let mut file = "orderbook_log.json".to_string();
+    let mut out_dir = PathBuf::from(".");
+    let mut max_position = 5.0;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--file" => file = value,
+            "--out-dir" => out_dir = PathBuf::from(value),
+            "--max-position" => {
+                max_position = value
+                    .parse()
+                    .unwrap_or_else(|_| usage("--max-position must be a number"))
+            }
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+
+    let recording = load_recording(&file).unwrap_or_else(|e| {
+        eprintln!("failed to load {file}: {e}");
+        process::exit(1)
+    });
+    let risk = RiskManager::new(max_position);
+    let mut engines: HashMap<String, SignalEngine> = HashMap::new();
+    let (mut ticks, mut features, mut fills) = (Vec::new(), Vec::new(), Vec::new());
+    for recorded in &recording.events {
+        let coin = recorded.coin.as_str();
+        let engine = engines.entry(recorded.coin.clone()).or_default();
+        let (time, bids, asks) = match &recorded.event {
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => {
+                engine.process_trade(*px, *sz, *is_buy, *time);
+                continue;
+            }
+            MarketEvent::Book { time, bids, asks } => (*time, bids, asks),
+        };
+        let Some((best_bid, best_ask, bid_volume, ask_volume)) = top_of_book(bids, asks) else {
+            continue;
+        };
+        engine.process_l2_book(time, best_bid, best_ask, bid_volume, ask_volume);
+        ticks.push(TickRow {
+            time,
+            coin: coin.to_string(),
+            best_bid,
+            best_ask,
+            bid_volume,
+            ask_volume,
+        });
+        features.push(FeatureRow::from_state(time, coin, &engine.state));
+        let quotes = QuoteLayerManager::build_quotes(&engine.state);
+        for q in risk.evaluate(&mut engine.state, &quotes) {
+            fills.push(FillRow {
+                time,
+                coin: coin.to_string(),
+                side: q.side,
+                price: q.price,
+                size: q.size,
+            });
+        }
+    }
+
+    let written = write_ticks(out_dir.join("ticks.arrow"), &ticks)
+        .and_then(|_| write_features(out_dir.join("features.arrow"), &features))
+        .and_then(|_| write_fills(out_dir.join("fills.arrow"), &fills));
+    if let Err(e) = written {
+        eprintln!("export failed: {e}");
+        process::exit(1);
+    }
+    println!(
+        "Wrote {} ticks, {} feature rows and {} fills to {} ({} unparsed lines skipped)",
+        ticks.len(),
+        features.len(),
+        fills.len(),
+        out_dir.display(),
+        recording.skipped
+    );
+}
@@ -0,0 +1,256 @@
+/*
+Exports the order and fill history of an address over a date range to CSV.
+
+    export_history <ADDRESS> --from 2024-01-01 [--to 2024-01-31] [--out history] [--network mainnet|testnet]
+
+Writes <out>_fills.csv and <out>_orders.csv. Fills are paged through
+userFillsByTime, so accounts with thousands of fills are exported in full.
+The historical orders endpoint only serves the most recent orders and has no
+time filter; if the range reaches past what it returns, a warning is printed.
+
+Also writes <out>_heatmap_pnl.csv and <out>_heatmap_fills.csv: the fills' PnL
+(closed PnL less fees) and count by UTC weekday and hour, for when the
+strategy actually makes money.
+*/
+use chrono::NaiveDate;
+use ethers::types::H160;
+use hyperliquid_rust_sdk::{
+    BaseUrl, HeatmapCell, InfoClient, PerformanceHeatmap, UserFillsResponse,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fs::File,
+    io::{BufWriter, Write},
+    process,
+};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: export_history <ADDRESS> --from YYYY-MM-DD [--to YYYY-MM-DD] [--out PREFIX] [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn fail(what: &str, err: impl std::fmt::Display) -> ! {
+    eprintln!("{what}: {err}");
+    process::exit(1)
+}
+
+fn day_start_ms(date: &str) -> u64 {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc().timestamp_millis() as u64)
+        .unwrap_or_else(|| usage(&format!("invalid date {date}")))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_row(out: &mut impl Write, fields: &[&str]) -> std::io::Result<()> {
+    let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    writeln!(out, "{}", row.join(","))
+}
+
+// Pages forward from `start` until the endpoint stops returning new fills. Pages may
+// overlap on the boundary millisecond, so fills are de-duplicated.
+async fn fetch_fills(
+    info_client: &InfoClient,
+    user: H160,
+    start: u64,
+    end: Option<u64>,
+) -> Vec<UserFillsResponse> {
+    let mut fills = Vec::new();
+    let mut seen = HashSet::new();
+    let mut cursor = start;
+    loop {
+        let page = info_client
+            .user_fills_by_time(user, cursor, end)
+            .await
+            .unwrap_or_else(|e| fail("could not fetch fills", e));
+        let last_time = page.iter().map(|f| f.time).max();
+        let mut new = 0;
+        for fill in page {
+            let key = (
+                fill.hash.clone(),
+                fill.oid,
+                fill.time,
+                fill.px.clone(),
+                fill.sz.clone(),
+            );
+            if seen.insert(key) {
+                fills.push(fill);
+                new += 1;
+            }
+        }
+        match last_time {
+            Some(last_time) if new > 0 && end.is_none_or(|end| last_time < end) => {
+                eprintln!("fetched {} fills up to {last_time}", fills.len());
+                cursor = last_time;
+            }
+            _ => break,
+        }
+    }
+    fills.sort_by_key(|f| f.time);
+    fills
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut args = env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| usage("expected an address"));
+    let mut flags = HashMap::new();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+    let flag = |name: &str| flags.get(name).map(String::as_str);
+
+    let user: H160 = address
+        .parse()
+        .unwrap_or_else(|_| usage(&format!("invalid address {address}")));
+    let start = day_start_ms(flag("--from").unwrap_or_else(|| usage("--from is required")));
+    // Without --to the endpoint runs up to now
+    let end = flag("--to").map(|d| day_start_ms(d) + DAY_MS - 1);
+    let prefix = flag("--out").unwrap_or("history");
+    let base_url = match flag("--network").unwrap_or("mainnet") {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| fail("could not create info client", e));
+
+    let fills = fetch_fills(&info_client, user, start, end).await;
+    let path = format!("{prefix}_fills.csv");
+    let mut out = BufWriter::new(File::create(&path).unwrap_or_else(|e| fail(&path, e)));
+    let header = [
+        "time",
+        "coin",
+        "side",
+        "dir",
+        "px",
+        "sz",
+        "fee",
+        "closed_pnl",
+        "start_position",
+        "crossed",
+        "oid",
+        "hash",
+    ];
+    write_row(&mut out, &header).unwrap_or_else(|e| fail(&path, e));
+    for f in &fills {
+        let (time, oid, crossed) = (f.time.to_string(), f.oid.to_string(), f.crossed.to_string());
+        write_row(
+            &mut out,
+            &[
+                &time,
+                &f.coin,
+                &f.side,
+                &f.dir,
+                &f.px,
+                &f.sz,
+                &f.fee,
+                &f.closed_pnl,
+                &f.start_position,
+                &crossed,
+                &oid,
+                &f.hash,
+            ],
+        )
+        .unwrap_or_else(|e| fail(&path, e));
+    }
+    out.flush().unwrap_or_else(|e| fail(&path, e));
+    println!("wrote {} fills to {path}", fills.len());
+
+    let heatmap = PerformanceHeatmap::from_user_fills(&fills);
+    for (name, value) in [
+        ("pnl", (|c| c.pnl) as fn(&HeatmapCell) -> f64),
+        ("fills", |c| c.fills as f64),
+    ] {
+        let path = format!("{prefix}_heatmap_{name}.csv");
+        let mut out = BufWriter::new(File::create(&path).unwrap_or_else(|e| fail(&path, e)));
+        heatmap
+            .write_csv(&mut out, value)
+            .and_then(|()| out.flush())
+            .unwrap_or_else(|e| fail(&path, e));
+        println!("wrote {name} by weekday and hour to {path}");
+    }
+    println!("{}", heatmap.summary());
+
+    let mut orders = info_client
+        .historical_orders(user)
+        .await
+        .unwrap_or_else(|e| fail("could not fetch historical orders", e));
+    if let Some(oldest) = orders.iter().map(|o| o.order.timestamp).min() {
+        if oldest > start {
+            eprintln!(
+                "warning: the historical orders endpoint only goes back to {oldest}; older orders in the range are missing"
+            );
+        }
+    }
+    orders.retain(|o| o.order.timestamp >= start && end.is_none_or(|end| o.order.timestamp <= end));
+    orders.sort_by_key(|o| o.order.timestamp);
+
+    let path = format!("{prefix}_orders.csv");
+    let mut out = BufWriter::new(File::create(&path).unwrap_or_else(|e| fail(&path, e)));
+    let header = [
+        "timestamp",
+        "status_timestamp",
+        "status",
+        "coin",
+        "side",
+        "order_type",
+        "tif",
+        "limit_px",
+        "orig_sz",
+        "sz",
+        "reduce_only",
+        "trigger_px",
+        "oid",
+        "cloid",
+    ];
+    write_row(&mut out, &header).unwrap_or_else(|e| fail(&path, e));
+    for o in &orders {
+        let b = &o.order;
+        let (timestamp, status_timestamp, reduce_only, oid) = (
+            b.timestamp.to_string(),
+            o.status_timestamp.to_string(),
+            b.reduce_only.to_string(),
+            b.oid.to_string(),
+        );
+        write_row(
+            &mut out,
+            &[
+                &timestamp,
+                &status_timestamp,
+                &o.status,
+                &b.coin,
+                &b.side,
+                &b.order_type,
+                &b.tif,
+                &b.limit_px,
+                &b.orig_sz,
+                &b.sz,
+                &reduce_only,
+                &b.trigger_px,
+                &oid,
+                b.cloid.as_deref().unwrap_or(""),
+            ],
+        )
+        .unwrap_or_else(|e| fail(&path, e));
+    }
+    out.flush().unwrap_or_else(|e| fail(&path, e));
+    println!("wrote {} orders to {path}", orders.len());
+}
@@ -0,0 +1,400 @@
+/*
+Delta-neutral funding harvest for the wallet in HL_PRIVATE_KEY: holds a coin
+long on spot against the same size short on the perp, so price moves cancel
+out while the short collects the funding longs pay.
+
+    funding_arb --coin ETH [--spot UETH] [--margin-pct 0.5] [--leverage 2]
+                [--rebalance-pct 0.02] [--min-rate 0.00001] [--poll 60]
+                [--slippage 0.01] [--network mainnet|testnet]
+
+The spot leg is the `spot_meta` pair of the --spot token (default: the coin's
+name) against USDC. --margin-pct of the account's USDC value, perp and spot,
+is committed, split between buying the spot leg and margining the perp leg at
+--leverage. Every --poll seconds:
+
+- while the hourly rate is at least --min-rate, spot is bought and the perp
+  sold in equal sizes until the legs reach the target, moving USDC from the
+  perp to the spot account as the purchase needs it;
+- once the legs drift apart by more than --rebalance-pct of the target, the
+  perp is traded back to the size of the spot leg;
+- once shorts pay longs more than --min-rate, both legs are unwound;
+- the legs, their delta and the funding collected since start are printed.
+
+Stopping it (Ctrl-C) leaves the legs open.
+*/
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::H160,
+};
+use hyperliquid_rust_sdk::{
+    shutdown_signal, AssetSpecs, BaseUrl, ExchangeClient, FundingMonitor, InfoClient,
+    MarketCloseParams, MarketOrderParams, OrderOutcome, PnlTracker, SpotMeta,
+};
+use log::{error, info, warn};
+use std::{env, process, time::Duration};
+
+const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+const MIN_TRADE_USD: f64 = 10.0; // The exchange rejects smaller orders
+const DEFAULT_MARGIN_PCT: f64 = 0.5;
+const DEFAULT_LEVERAGE: u32 = 2;
+const DEFAULT_REBALANCE_PCT: f64 = 0.02;
+const DEFAULT_MIN_RATE: f64 = 0.00001; // Hourly; about 9% a year
+const DEFAULT_POLL_SECS: u64 = 60;
+const DEFAULT_SLIPPAGE: f64 = 0.01;
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: funding_arb --coin COIN [--spot TOKEN] [--margin-pct FRAC] [--leverage N] [--rebalance-pct FRAC] [--min-rate HOURLY] [--poll SECS] [--slippage FRAC] [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn number<T: std::str::FromStr>(flag: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| usage(&format!("{flag} must be a number")))
+}
+
+struct Settings {
+    coin: String,
+    token: String, // spot token bought against the perp
+    margin_pct: f64,
+    leverage: u32,
+    rebalance_pct: f64,
+    min_rate: f64,
+    slippage: f64,
+}
+
+// Both legs and the USDC backing them, read at the start of each round
+#[derive(Debug)]
+struct Legs {
+    spot: f64,         // token balance
+    perp: f64,         // signed position; short is negative
+    spot_usdc: f64,    // free USDC in the spot account
+    perp_equity: f64,  // perp account value
+    withdrawable: f64, // USDC the perp account can move to spot
+    spot_px: f64,
+    perp_px: f64,
+}
+
+impl Legs {
+    fn delta(&self) -> f64 {
+        self.spot + self.perp
+    }
+
+    // USDC value of the account, both legs included
+    fn capital(&self) -> f64 {
+        self.perp_equity + self.spot_usdc + self.spot * self.spot_px
+    }
+}
+
+// Order name of `token`'s pair against USDC, e.g. "@107" or "PURR/USDC"
+fn spot_pair(meta: &SpotMeta, token: &str) -> Option<String> {
+    let index = |name: &str| meta.tokens.iter().find(|t| t.name == name).map(|t| t.index);
+    let (base, usdc) = (index(token)?, index("USDC")?);
+    meta.universe
+        .iter()
+        .find(|pair| pair.tokens == [base, usdc])
+        .map(|pair| pair.name.clone())
+}
+
+async fn read_legs(
+    info: &InfoClient,
+    user: H160,
+    settings: &Settings,
+    pair: &str,
+) -> Result<Legs, hyperliquid_rust_sdk::Error> {
+    let parse = |v: &str| v.parse::<f64>().unwrap_or(0.0);
+    let state = info.user_state(user).await?;
+    let balances = info.user_token_balances(user).await?.balances;
+    let mids = info.all_mids().await?;
+    let balance = |coin: &str| {
+        balances
+            .iter()
+            .find(|b| b.coin == coin)
+            .map_or(0.0, |b| parse(&b.total) - parse(&b.hold))
+    };
+    Ok(Legs {
+        spot: balance(&settings.token),
+        perp: state
+            .asset_positions
+            .iter()
+            .find(|p| p.position.coin == settings.coin)
+            .map_or(0.0, |p| parse(&p.position.szi)),
+        spot_usdc: balance("USDC"),
+        perp_equity: parse(&state.margin_summary.account_value),
+        withdrawable: parse(&state.withdrawable),
+        spot_px: mids.get(pair).map_or(0.0, |px| parse(px)),
+        perp_px: mids.get(&settings.coin).map_or(0.0, |px| parse(px)),
+    })
+}
+
+// Size filled by an IOC, or why nothing was
+fn filled(outcome: OrderOutcome) -> Result<f64, String> {
+    match outcome {
+        OrderOutcome::Filled { size, .. } => Ok(size),
+        OrderOutcome::Rejected(e) => Err(e),
+        other => Err(format!("not filled: {other:?}")),
+    }
+}
+
+// An IOC for `sz` of `asset`, limited by the book to `slippage`
+async fn market(
+    exchange: &ExchangeClient,
+    asset: &str,
+    is_buy: bool,
+    sz: f64,
+    slippage: f64,
+) -> Result<f64, String> {
+    let params = MarketOrderParams {
+        asset,
+        is_buy,
+        sz,
+        px: None,
+        slippage: None,
+        cloid: None,
+        wallet: None,
+    };
+    let response = exchange
+        .market_open_protected(params, slippage)
+        .await
+        .map_err(|e| e.to_string())?;
+    filled(OrderOutcome::from(response))
+}
+
+// Buys `size` of the spot leg and sells the perp against what filled
+async fn build(
+    exchange: &ExchangeClient,
+    settings: &Settings,
+    pair: &str,
+    legs: &Legs,
+    size: f64,
+) -> Result<(), String> {
+    let cost = size * legs.spot_px * (1.0 + settings.slippage);
+    if legs.spot_usdc < cost {
+        let usdc = (cost - legs.spot_usdc).min(legs.withdrawable);
+        info!("Moving {usdc:.2} USDC to the spot account");
+        let response = exchange
+            .class_transfer(usdc, false, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let OrderOutcome::Rejected(e) = OrderOutcome::from(response) {
+            return Err(format!("transfer to spot failed: {e}"));
+        }
+    }
+    let bought = market(exchange, pair, true, size, settings.slippage).await?;
+    info!("Bought {bought} {} on spot", settings.token);
+    let sold = market(exchange, &settings.coin, false, bought, settings.slippage)
+        .await
+        .map_err(|e| format!("perp hedge of {bought} failed, legs unbalanced: {e}"))?;
+    info!("Sold {sold} {} perp", settings.coin);
+    Ok(())
+}
+
+// Sells the spot leg and buys the perp leg back
+async fn unwind(
+    exchange: &ExchangeClient,
+    settings: &Settings,
+    pair: &str,
+    legs: &Legs,
+) -> Result<(), String> {
+    if legs.perp < 0.0 {
+        let params = MarketCloseParams {
+            asset: &settings.coin,
+            sz: None,
+            px: None,
+            slippage: None,
+            cloid: None,
+            wallet: None,
+        };
+        let response = exchange
+            .market_close_protected(params, settings.slippage)
+            .await
+            .map_err(|e| e.to_string())?;
+        let closed = filled(OrderOutcome::from(response))?;
+        info!("Bought back {closed} {} perp", settings.coin);
+    }
+    if legs.spot * legs.spot_px >= MIN_TRADE_USD {
+        let sold = market(exchange, pair, false, legs.spot, settings.slippage).await?;
+        info!("Sold {sold} {} on spot", settings.token);
+    }
+    Ok(())
+}
+
+// One round: unwind on adverse funding, else re-hedge drift, else build toward
+// the target
+async fn step(
+    exchange: &ExchangeClient,
+    settings: &Settings,
+    pair: &str,
+    legs: &Legs,
+    rate: f64,
+) -> Result<(), String> {
+    if rate <= -settings.min_rate {
+        if legs.spot * legs.spot_px >= MIN_TRADE_USD || legs.perp < 0.0 {
+            warn!("Shorts pay {:.4}%/h, unwinding both legs", -rate * 100.0);
+            unwind(exchange, settings, pair, legs).await?;
+        }
+        return Ok(());
+    }
+    let leverage = settings.leverage as f64;
+    let target_usd = legs.capital() * settings.margin_pct * leverage / (leverage + 1.0);
+    let band_usd = (target_usd * settings.rebalance_pct).max(MIN_TRADE_USD);
+    let drift_usd = legs.delta().abs() * legs.perp_px;
+    if drift_usd > band_usd {
+        let is_buy = legs.delta() < 0.0;
+        info!(
+            "Legs {:.2} USDC apart, {} perp to rebalance",
+            drift_usd,
+            if is_buy { "buying" } else { "selling" }
+        );
+        market(
+            exchange,
+            &settings.coin,
+            is_buy,
+            legs.delta().abs(),
+            settings.slippage,
+        )
+        .await?;
+        return Ok(());
+    }
+    let missing_usd = target_usd - legs.spot * legs.spot_px;
+    if rate >= settings.min_rate && missing_usd > band_usd {
+        build(exchange, settings, pair, legs, missing_usd / legs.spot_px).await?;
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut coin = None;
+    let mut token = None;
+    let mut network = "testnet".to_string();
+    let mut margin_pct = DEFAULT_MARGIN_PCT;
+    let mut leverage = DEFAULT_LEVERAGE;
+    let mut rebalance_pct = DEFAULT_REBALANCE_PCT;
+    let mut min_rate = DEFAULT_MIN_RATE;
+    let mut poll_secs = DEFAULT_POLL_SECS;
+    let mut slippage = DEFAULT_SLIPPAGE;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--coin" => coin = Some(value.to_uppercase()),
+            "--spot" => token = Some(value),
+            "--network" => network = value,
+            "--margin-pct" => margin_pct = number(&flag, &value),
+            "--leverage" => leverage = number(&flag, &value),
+            "--rebalance-pct" => rebalance_pct = number(&flag, &value),
+            "--min-rate" => min_rate = number(&flag, &value),
+            "--poll" => poll_secs = number(&flag, &value),
+            "--slippage" => slippage = number(&flag, &value),
+            other => usage(&format!("unexpected argument {other}")),
+        }
+    }
+    let coin = coin.unwrap_or_else(|| usage("--coin is required"));
+    let settings = Settings {
+        token: token.unwrap_or_else(|| coin.clone()),
+        coin,
+        margin_pct: margin_pct.clamp(0.0, 1.0),
+        leverage: leverage.max(1),
+        rebalance_pct,
+        min_rate,
+        slippage,
+    };
+    let base_url = match network.as_str() {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+    let wallet: LocalWallet = env::var("HL_PRIVATE_KEY")
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not set"))
+        .parse()
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not a valid private key"));
+    let user = wallet.address();
+
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+    let exchange = ExchangeClient::new(None, wallet, Some(base_url), None, None)
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create exchange client: {e}")));
+    let specs = AssetSpecs::load(&info_client)
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not load metadata: {e}")));
+    if specs.get(&settings.coin).is_none() {
+        usage(&format!("no perp for {}", settings.coin));
+    }
+    let spot_meta = info_client
+        .spot_meta()
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not load spot metadata: {e}")));
+    let pair = spot_pair(&spot_meta, &settings.token)
+        .unwrap_or_else(|| usage(&format!("no {}/USDC spot pair", settings.token)));
+    let leverage = exchange
+        .update_leverage(settings.leverage, &settings.coin, true, None)
+        .await
+        .map_err(|e| e.to_string())
+        .map(OrderOutcome::from);
+    if let Err(e) | Ok(OrderOutcome::Rejected(e)) = leverage {
+        usage(&format!("could not set leverage: {e}"));
+    }
+    println!(
+        "Harvesting {} funding: spot {pair} ({}) against the perp at {}x",
+        settings.coin, settings.token, settings.leverage
+    );
+
+    let funding = FundingMonitor::new(&settings.coin);
+    let pnl = PnlTracker::new();
+    let since_ms = chrono::Utc::now().timestamp_millis() as u64;
+    let mut timer = tokio::time::interval(Duration::from_secs(poll_secs.max(1)));
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = timer.tick() => {}
+            signal = &mut shutdown => {
+                println!("{} received, stopping with the legs open", signal.unwrap_or("signal"));
+                break;
+            }
+        }
+        if let Err(e) = funding.load(&info_client).await {
+            error!("Funding history failed: {e}");
+            continue;
+        }
+        if let Err(e) = pnl.load_funding(&info_client, user, since_ms).await {
+            warn!("Funding payments failed: {e}");
+        }
+        let Some(rate) = funding.rate() else {
+            warn!("No funding rate for {} yet", settings.coin);
+            continue;
+        };
+        let legs = match read_legs(&info_client, user, &settings, &pair).await {
+            Ok(legs) if legs.spot_px > 0.0 && legs.perp_px > 0.0 => legs,
+            Ok(_) => {
+                warn!("No mid for {pair} or {}", settings.coin);
+                continue;
+            }
+            Err(e) => {
+                error!("Reading the account failed: {e}");
+                continue;
+            }
+        };
+        println!(
+            "{} spot {:.4} perp {:.4} delta {:+.4} (${:.2}) | rate {:.4}%/h ({:.1}% a year) | funding {:+.4} USDC",
+            settings.coin,
+            legs.spot,
+            legs.perp,
+            legs.delta(),
+            legs.delta() * legs.perp_px,
+            rate * 100.0,
+            rate * HOURS_PER_YEAR * 100.0,
+            pnl.coin(&settings.coin).funding
+        );
+        if let Err(e) = step(&exchange, &settings, &pair, &legs, rate).await {
+            error!("{e}");
+        }
+    }
+}
@@ -0,0 +1,156 @@
+/*
+Funding analytics over a lookback window.
+
+    funding_report [--coins BTC,ETH,SOL|all] [--days 7] [--network mainnet|testnet]
+
+For each coin: mean hourly rate, annualized rate, sign persistence (share of
+intervals with the same sign as the mean) and lag-1 autocorrelation. Coins are
+ranked by |annualized| x persistence as candidates for funding harvesting:
+collect positive funding by shorting the perp, negative by going long.
+*/
+use hyperliquid_rust_sdk::{BaseUrl, InfoClient};
+use std::{collections::HashMap, env, process};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+const DEFAULT_COINS: &str = "BTC,ETH,SOL";
+const MIN_SAMPLES: usize = 24;
+const TOP_CANDIDATES: usize = 5;
+
+#[derive(Debug)]
+struct FundingStats {
+    coin: String,
+    samples: usize,
+    mean: f64,
+    annualized: f64,
+    persistence: f64,
+    autocorr: f64,
+}
+
+impl FundingStats {
+    fn from_rates(coin: &str, rates: &[f64]) -> Option<FundingStats> {
+        if rates.len() < MIN_SAMPLES {
+            return None;
+        }
+        let n = rates.len() as f64;
+        let mean = rates.iter().sum::<f64>() / n;
+        let same_sign = rates
+            .iter()
+            .filter(|r| r.signum() == mean.signum() && **r != 0.0)
+            .count();
+        let var = rates.iter().map(|r| (r - mean).powi(2)).sum::<f64>();
+        let cov = rates
+            .windows(2)
+            .map(|w| (w[0] - mean) * (w[1] - mean))
+            .sum::<f64>();
+        Some(FundingStats {
+            coin: coin.to_string(),
+            samples: rates.len(),
+            mean,
+            annualized: mean * HOURS_PER_YEAR,
+            persistence: same_sign as f64 / n,
+            autocorr: if var > 0.0 { cov / var } else { 0.0 },
+        })
+    }
+
+    fn score(&self) -> f64 {
+        self.annualized.abs() * self.persistence
+    }
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: funding_report [--coins A,B,C|all] [--days N] [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut flags = HashMap::new();
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+    let days: u64 = flags
+        .get("--days")
+        .map(|d| {
+            d.parse()
+                .unwrap_or_else(|_| usage("--days must be an integer"))
+        })
+        .unwrap_or(7);
+    let base_url = match flags.get("--network").map(String::as_str) {
+        None | Some("mainnet") => BaseUrl::Mainnet,
+        Some("testnet") => BaseUrl::Testnet,
+        Some(other) => usage(&format!("unknown network {other}")),
+    };
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+
+    let coins: Vec<String> = match flags.get("--coins").map(String::as_str) {
+        Some("all") => match info_client.meta().await {
+            Ok(meta) => meta.universe.into_iter().map(|a| a.name).collect(),
+            Err(e) => usage(&format!("could not fetch meta: {e}")),
+        },
+        coins => coins
+            .unwrap_or(DEFAULT_COINS)
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .collect(),
+    };
+
+    let end = chrono::Utc::now().timestamp_millis() as u64;
+    let start = end.saturating_sub(days * 24 * HOUR_MS);
+    let mut stats = Vec::new();
+    for coin in &coins {
+        let history = match info_client.funding_history_range(coin, start, end).await {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("{coin}: {e}");
+                continue;
+            }
+        };
+        let rates: Vec<f64> = history
+            .iter()
+            .filter_map(|f| f.funding_rate.parse().ok())
+            .collect();
+        match FundingStats::from_rates(coin, &rates) {
+            Some(s) => stats.push(s),
+            None => eprintln!("{coin}: only {} samples, skipped", rates.len()),
+        }
+    }
+
+    println!("Funding over the last {days} days");
+    println!(
+        "{:<10} {:>7} {:>12} {:>11} {:>11} {:>9}",
+        "coin", "samples", "mean/hr", "annual %", "persist %", "autocorr"
+    );
+    for s in &stats {
+        println!(
+            "{:<10} {:>7} {:>12.8} {:>11.2} {:>11.1} {:>9.2}",
+            s.coin,
+            s.samples,
+            s.mean,
+            s.annualized * 100.0,
+            s.persistence * 100.0,
+            s.autocorr
+        );
+    }
+
+    stats.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    println!();
+    println!("Harvest candidates");
+    for s in stats.iter().take(TOP_CANDIDATES) {
+        let side = if s.mean > 0.0 { "short" } else { "long" };
+        println!(
+            "  {:<10} {side:<5} perp  annual {:>7.2}%  persistence {:>5.1}%",
+            s.coin,
+            s.annualized * 100.0,
+            s.persistence * 100.0
+        );
+    }
+}
@@ -0,0 +1,197 @@
+/*
+Continuous latency probe. Measures websocket message latency (exchange timestamp
+vs local receive time) and REST round trips to the info and exchange endpoints,
+exports them as Prometheus metrics and alerts when they degrade.
+
+    latency_probe [--coin BTC] [--network mainnet|testnet] [--metrics-addr 127.0.0.1:9184]
+                  [--max-ws-ms 500] [--max-rest-ms 800]
+
+WS latency includes local clock skew; keep the host NTP-synced.
+The exchange probe posts an empty payload, which the exchange rejects without
+acting on, so no signing key is needed.
+*/
+use hyperliquid_rust_sdk::{
+    Alert, AlertLevel, BaseUrl, InfoClient, Message, Metrics, Notifier, Subscription,
+};
+use log::warn;
+use std::{
+    collections::{HashMap, VecDeque},
+    env, process,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::mpsc::unbounded_channel,
+    time::{interval, Duration},
+};
+
+const WINDOW: usize = 200;
+const REST_PROBE_SECS: u64 = 5;
+const REPORT_SECS: u64 = 10;
+
+#[derive(Debug, Default)]
+struct Window {
+    samples: VecDeque<f64>,
+}
+
+impl Window {
+    fn push(&mut self, v: f64) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(v);
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+        Some(sorted[idx])
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: latency_probe [--coin COIN] [--network mainnet|testnet] [--metrics-addr ADDR] [--max-ws-ms MS] [--max-rest-ms MS]");
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut flags = HashMap::new();
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+    let flag = |name: &str| flags.get(name).map(String::as_str);
+    let threshold = |name: &str, default: f64| {
+        flag(name)
+            .map(|v| {
+                v.parse()
+                    .unwrap_or_else(|_| usage(&format!("{name} must be a number")))
+            })
+            .unwrap_or(default)
+    };
+    let coin = flag("--coin").unwrap_or("BTC").to_string();
+    let metrics_addr = flag("--metrics-addr")
+        .unwrap_or("127.0.0.1:9184")
+        .to_string();
+    let max_ws_ms = threshold("--max-ws-ms", 500.0);
+    let max_rest_ms = threshold("--max-rest-ms", 800.0);
+    let base_url = match flag("--network").unwrap_or("mainnet") {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+
+    let metrics = Metrics::new();
+    let notifier = Notifier::from_env();
+    let server = metrics.clone();
+    tokio::spawn(async move {
+        if let Err(e) = server.serve(&metrics_addr).await {
+            warn!("metrics server stopped: {e}");
+        }
+    });
+
+    let mut info_client = InfoClient::with_reconnect(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+    let (sender, mut receiver) = unbounded_channel();
+    for subscription in [
+        Subscription::L2Book { coin: coin.clone() },
+        Subscription::Trades { coin: coin.clone() },
+    ] {
+        if let Err(e) = info_client.subscribe(subscription, sender.clone()).await {
+            usage(&format!("subscription failed: {e}"));
+        }
+    }
+
+    let http = reqwest::Client::new();
+    let exchange_url = format!("{}/exchange", info_client.http_client.base_url);
+    let mut windows: HashMap<&'static str, Window> = HashMap::new();
+    let mut rest_timer = interval(Duration::from_secs(REST_PROBE_SECS));
+    let mut report_timer = interval(Duration::from_secs(REPORT_SECS));
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => {
+                let (series, exchange_ms) = match message {
+                    Some(Message::L2Book(book)) => ("l2Book", book.data.time),
+                    Some(Message::Trades(trades)) => match trades.data.iter().map(|t| t.time).max() {
+                        Some(time) => ("trades", time),
+                        None => continue,
+                    },
+                    Some(_) => continue,
+                    None => {
+                        eprintln!("subscription channel closed");
+                        process::exit(1);
+                    }
+                };
+                let latency = now_ms().saturating_sub(exchange_ms) as f64;
+                metrics.inc(&format!("ws_messages_total{{channel=\"{series}\"}}"), 1.0);
+                metrics.observe(&format!("ws_latency_ms{{channel=\"{series}\"}}"), latency);
+                windows.entry(series).or_default().push(latency);
+            }
+            _ = rest_timer.tick() => {
+                let start = Instant::now();
+                let info = info_client.all_mids().await;
+                let info_ms = start.elapsed().as_secs_f64() * 1000.0;
+                let start = Instant::now();
+                let exchange = http.post(&exchange_url).header("Content-Type", "application/json").body("{}").send().await;
+                let exchange_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+                for (endpoint, ok, rtt) in [("info", info.is_ok(), info_ms), ("exchange", exchange.is_ok(), exchange_ms)] {
+                    if ok {
+                        metrics.observe(&format!("rest_rtt_ms{{endpoint=\"{endpoint}\"}}"), rtt);
+                        windows.entry(endpoint).or_default().push(rtt);
+                    } else {
+                        metrics.inc(&format!("rest_errors_total{{endpoint=\"{endpoint}\"}}"), 1.0);
+                        let alert = Alert::new(AlertLevel::Warning, format!("{endpoint}:error"), format!("{endpoint} endpoint request failed"));
+                        if let Err(e) = notifier.notify(alert).await {
+                            warn!("alert delivery failed: {e}");
+                        }
+                    }
+                }
+            }
+            _ = report_timer.tick() => {
+                let mut line = Vec::new();
+                let mut series: Vec<_> = windows.iter().collect();
+                series.sort_by_key(|(name, _)| *name);
+                for (name, window) in series {
+                    let (Some(p50), Some(p95)) = (window.quantile(0.5), window.quantile(0.95)) else {
+                        continue;
+                    };
+                    metrics.set(&format!("latency_p50_ms{{series=\"{name}\"}}"), p50);
+                    metrics.set(&format!("latency_p95_ms{{series=\"{name}\"}}"), p95);
+                    line.push(format!("{name} p50 {p50:.0}ms p95 {p95:.0}ms"));
+
+                    let limit = if *name == "info" || *name == "exchange" { max_rest_ms } else { max_ws_ms };
+                    if p95 > limit {
+                        let alert = Alert::new(
+                            AlertLevel::Warning,
+                            format!("{name}:latency"),
+                            format!("{name} latency degraded: p95 {p95:.0}ms > {limit:.0}ms (p50 {p50:.0}ms)"),
+                        );
+                        if let Err(e) = notifier.notify(alert).await {
+                            warn!("alert delivery failed: {e}");
+                        }
+                    }
+                }
+                println!("[Latency] {}", line.join(" | "));
+            }
+        }
+    }
+}
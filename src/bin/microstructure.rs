@@ -0,0 +1,309 @@
+/*
+Per-coin market microstructure statistics by UTC hour of day: average spread,
+top-of-book depth, trade arrival rate and realized volatility. Used to pick which
+markets and hours the maker should quote.
+
+    microstructure --file orderbook_log.json
+    microstructure --live 30 --coins BTC,ETH [--network mainnet|testnet]
+
+Recorded files are JSON lines; book lines carry `coin`, `timestamp` and `bids`/`asks`
+levels, trade lines carry `coin`, `time`, `px` and `sz`.
+*/
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use hyperliquid_rust_sdk::{BaseUrl, InfoClient, Message, Subscription};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    fs::File,
+    io::{BufRead, BufReader},
+    process,
+};
+use tokio::{
+    sync::mpsc::unbounded_channel,
+    time::{sleep, Duration},
+};
+
+// Gaps longer than this are treated as the feed being off, not as observed time
+const MAX_SAMPLE_GAP_MS: u64 = 60_000;
+
+#[derive(Debug, Default)]
+struct HourStats {
+    book_samples: u64,
+    spread_bps_sum: f64,
+    depth_sum: f64,
+    trades: u64,
+    trade_notional: f64,
+    sq_returns: f64,
+    observed_ms: u64,
+    days: HashSet<NaiveDate>,
+}
+
+impl HourStats {
+    fn avg_spread_bps(&self) -> f64 {
+        self.spread_bps_sum / self.book_samples.max(1) as f64
+    }
+
+    fn avg_depth(&self) -> f64 {
+        self.depth_sum / self.book_samples.max(1) as f64
+    }
+
+    fn trades_per_min(&self) -> f64 {
+        if self.observed_ms == 0 {
+            return 0.0;
+        }
+        self.trades as f64 / (self.observed_ms as f64 / 60_000.0)
+    }
+
+    // Realized volatility over one hour, in bps
+    fn hourly_vol_bps(&self) -> f64 {
+        if self.observed_ms == 0 {
+            return 0.0;
+        }
+        let hours = self.observed_ms as f64 / 3_600_000.0;
+        (self.sq_returns / hours).sqrt() * 10_000.0
+    }
+}
+
+#[derive(Debug, Default)]
+struct CoinStats {
+    hours: BTreeMap<u32, HourStats>,
+    last_mid: Option<(u64, f64)>,
+}
+
+impl CoinStats {
+    fn on_book(&mut self, time_ms: u64, bid: (f64, f64), ask: (f64, f64)) {
+        if bid.0 <= 0.0 || ask.0 <= bid.0 {
+            return;
+        }
+        let Some(dt) = Utc.timestamp_millis_opt(time_ms as i64).single() else {
+            return;
+        };
+        let mid = (bid.0 + ask.0) / 2.0;
+        let hour = self.hours.entry(dt.hour()).or_default();
+        hour.book_samples += 1;
+        hour.spread_bps_sum += (ask.0 - bid.0) / mid * 10_000.0;
+        hour.depth_sum += bid.0 * bid.1 + ask.0 * ask.1;
+        hour.days.insert(dt.date_naive());
+        if let Some((last_ms, last_mid)) = self.last_mid {
+            let gap = time_ms.saturating_sub(last_ms);
+            if gap <= MAX_SAMPLE_GAP_MS {
+                hour.observed_ms += gap;
+                hour.sq_returns += (mid / last_mid).ln().powi(2);
+            }
+        }
+        self.last_mid = Some((time_ms, mid));
+    }
+
+    fn on_trade(&mut self, time_ms: u64, px: f64, sz: f64) {
+        let Some(dt) = Utc.timestamp_millis_opt(time_ms as i64).single() else {
+            return;
+        };
+        let hour = self.hours.entry(dt.hour()).or_default();
+        hour.trades += 1;
+        hour.trade_notional += px * sz;
+    }
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: microstructure (--file PATH | --live MINUTES --coins A,B) [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn num(v: &Value) -> Option<f64> {
+    match v {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_f64(),
+    }
+}
+
+fn time_ms(v: &Value) -> Option<u64> {
+    match v {
+        Value::String(s) => NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|t| t.and_utc().timestamp_millis() as u64),
+        v => v.as_u64(),
+    }
+}
+
+fn top_level(levels: &Value) -> Option<(f64, f64)> {
+    let level = levels.as_array()?.first()?;
+    Some((num(&level["px"])?, num(&level["sz"])?))
+}
+
+fn load_file(path: &str, stats: &mut HashMap<String, CoinStats>) {
+    let file = File::open(path).unwrap_or_else(|e| usage(&format!("{path}: {e}")));
+    let mut skipped = 0;
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else { break };
+        let Ok(v) = serde_json::from_str::<Value>(&line) else {
+            skipped += 1;
+            continue;
+        };
+        let Some(coin) = v["coin"].as_str() else {
+            skipped += 1;
+            continue;
+        };
+        let coin_stats = stats.entry(coin.to_string()).or_default();
+        if v.get("bids").is_some() {
+            let parsed = (
+                time_ms(&v["timestamp"]).or_else(|| time_ms(&v["time"])),
+                top_level(&v["bids"]),
+                top_level(&v["asks"]),
+            );
+            match parsed {
+                (Some(t), Some(bid), Some(ask)) => coin_stats.on_book(t, bid, ask),
+                _ => skipped += 1,
+            }
+        } else {
+            match (time_ms(&v["time"]), num(&v["px"]), num(&v["sz"])) {
+                (Some(t), Some(px), Some(sz)) => coin_stats.on_trade(t, px, sz),
+                _ => skipped += 1,
+            }
+        }
+    }
+    if skipped > 0 {
+        eprintln!("skipped {skipped} unparseable lines");
+    }
+}
+
+async fn collect_live(
+    coins: &[String],
+    minutes: u64,
+    base_url: BaseUrl,
+    stats: &mut HashMap<String, CoinStats>,
+) {
+    let mut info_client = InfoClient::with_reconnect(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+    let (sender, mut receiver) = unbounded_channel();
+    for coin in coins {
+        for subscription in [
+            Subscription::L2Book { coin: coin.clone() },
+            Subscription::Trades { coin: coin.clone() },
+        ] {
+            if let Err(e) = info_client.subscribe(subscription, sender.clone()).await {
+                usage(&format!("subscription for {coin} failed: {e}"));
+            }
+        }
+    }
+    eprintln!("collecting {minutes} minutes of live data for {coins:?}");
+
+    let deadline = sleep(Duration::from_secs(minutes * 60));
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            message = receiver.recv() => match message {
+                Some(Message::L2Book(book)) => {
+                    let level = |side: usize| {
+                        let l = book.data.levels.get(side)?.first()?;
+                        Some((l.px.parse().ok()?, l.sz.parse().ok()?))
+                    };
+                    if let (Some(bid), Some(ask)) = (level(0), level(1)) {
+                        stats
+                            .entry(book.data.coin.clone())
+                            .or_default()
+                            .on_book(book.data.time, bid, ask);
+                    }
+                }
+                Some(Message::Trades(trades)) => {
+                    for t in trades.data {
+                        if let (Ok(px), Ok(sz)) = (t.px.parse(), t.sz.parse()) {
+                            stats.entry(t.coin).or_default().on_trade(t.time, px, sz);
+                        }
+                    }
+                }
+                Some(_) => {}
+                None => break,
+            },
+        }
+    }
+}
+
+fn print_report(stats: &HashMap<String, CoinStats>) {
+    let mut coins: Vec<_> = stats.keys().collect();
+    coins.sort();
+    for coin in coins {
+        let coin_stats = &stats[coin];
+        println!("== {coin} ==");
+        println!(
+            "  {:>4} {:>8} {:>10} {:>14} {:>10} {:>12} {:>5}",
+            "hour", "samples", "spread bp", "L1 depth $", "trades/m", "vol bp/hr", "days"
+        );
+        for (hour, h) in &coin_stats.hours {
+            println!(
+                "  {:>4} {:>8} {:>10.3} {:>14.0} {:>10.2} {:>12.2} {:>5}",
+                hour,
+                h.book_samples,
+                h.avg_spread_bps(),
+                h.avg_depth(),
+                h.trades_per_min(),
+                h.hourly_vol_bps(),
+                h.days.len()
+            );
+        }
+        let all = coin_stats
+            .hours
+            .values()
+            .fold(HourStats::default(), |mut acc, h| {
+                acc.book_samples += h.book_samples;
+                acc.spread_bps_sum += h.spread_bps_sum;
+                acc.depth_sum += h.depth_sum;
+                acc.trades += h.trades;
+                acc.trade_notional += h.trade_notional;
+                acc.sq_returns += h.sq_returns;
+                acc.observed_ms += h.observed_ms;
+                acc
+            });
+        println!(
+            "  {:>4} {:>8} {:>10.3} {:>14.0} {:>10.2} {:>12.2}  traded ${:.0}",
+            "all",
+            all.book_samples,
+            all.avg_spread_bps(),
+            all.avg_depth(),
+            all.trades_per_min(),
+            all.hourly_vol_bps(),
+            all.trade_notional
+        );
+        println!();
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut flags = HashMap::new();
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+
+    let mut stats = HashMap::new();
+    match (flags.get("--file"), flags.get("--live")) {
+        (Some(path), None) => load_file(path, &mut stats),
+        (None, Some(minutes)) => {
+            let minutes = minutes
+                .parse()
+                .unwrap_or_else(|_| usage("--live takes a number of minutes"));
+            let coins: Vec<String> = flags
+                .get("--coins")
+                .unwrap_or_else(|| usage("--coins is required with --live"))
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .collect();
+            let base_url = match flags.get("--network").map(String::as_str) {
+                None | Some("mainnet") => BaseUrl::Mainnet,
+                Some("testnet") => BaseUrl::Testnet,
+                Some(other) => usage(&format!("unknown network {other}")),
+            };
+            collect_live(&coins, minutes, base_url, &mut stats).await;
+        }
+        _ => usage("pass exactly one of --file or --live"),
+    }
+    print_report(&stats);
+}
@@ -0,0 +1,226 @@
+/*
+Read-only PnL monitor. Subscribes to user events and mids for a wallet and prints
+live position, margin and PnL updates. Never places orders.
+
+    monitor <ADDRESS> [--network mainnet|testnet]
+
+Fills, funding and liquidations are printed as they arrive. Positions are marked
+to the latest mid in between, and margin is refreshed from user_state periodically
+so the local view can't drift from the exchange.
+*/
+use ethers::types::H160;
+use hyperliquid_rust_sdk::{BaseUrl, InfoClient, Message, Subscription, UserData};
+use log::warn;
+use std::{collections::HashMap, env, process};
+use tokio::{
+    sync::mpsc::unbounded_channel,
+    time::{interval, Duration},
+};
+
+const PRINT_INTERVAL_SECS: u64 = 5;
+const REFRESH_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Default)]
+struct PositionView {
+    szi: f64,
+    entry_px: f64,
+    mark_px: f64,
+    liquidation_px: Option<f64>,
+}
+
+impl PositionView {
+    fn unrealized_pnl(&self) -> f64 {
+        self.szi * (self.mark_px - self.entry_px)
+    }
+}
+
+#[derive(Debug, Default)]
+struct AccountView {
+    positions: HashMap<String, PositionView>,
+    account_value: f64,
+    margin_used: f64,
+    realized_pnl: f64,
+    fees: f64,
+    funding: f64,
+}
+
+impl AccountView {
+    fn unrealized_pnl(&self) -> f64 {
+        self.positions
+            .values()
+            .map(PositionView::unrealized_pnl)
+            .sum()
+    }
+
+    fn print(&self) {
+        let margin_pct = if self.account_value > 0.0 {
+            self.margin_used / self.account_value * 100.0
+        } else {
+            0.0
+        };
+        println!(
+            "[Monitor] Value: {:.2} | Margin: {:.2} ({:.1}%) | uPnL: {:.2} | rPnL: {:.2} | Fees: {:.4} | Funding: {:.4}",
+            self.account_value,
+            self.margin_used,
+            margin_pct,
+            self.unrealized_pnl(),
+            self.realized_pnl,
+            self.fees,
+            self.funding
+        );
+        let mut coins: Vec<_> = self.positions.keys().collect();
+        coins.sort();
+        for coin in coins {
+            let p = &self.positions[coin];
+            let liq = p
+                .liquidation_px
+                .map(|px| format!("{px:.4}"))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "    {coin:<8} sz {:>12.5} entry {:>12.4} mark {:>12.4} liq {:>12} uPnL {:>10.2}",
+                p.szi,
+                p.entry_px,
+                p.mark_px,
+                liq,
+                p.unrealized_pnl()
+            );
+        }
+    }
+}
+
+fn num(s: &str) -> f64 {
+    s.parse().unwrap_or(0.0)
+}
+
+async fn refresh(info_client: &InfoClient, user: H160, view: &mut AccountView) {
+    let user_state = match info_client.user_state(user).await {
+        Ok(user_state) => user_state,
+        Err(e) => {
+            warn!("user_state refresh failed: {e}");
+            return;
+        }
+    };
+    view.account_value = num(&user_state.margin_summary.account_value);
+    view.margin_used = num(&user_state.margin_summary.total_margin_used);
+
+    let mut positions = HashMap::new();
+    for asset_position in user_state.asset_positions {
+        let p = asset_position.position;
+        let szi = num(&p.szi);
+        if szi == 0.0 {
+            continue;
+        }
+        let entry_px = p.entry_px.as_deref().map(num).unwrap_or(0.0);
+        let mark_px = view
+            .positions
+            .get(&p.coin)
+            .map(|old| old.mark_px)
+            .unwrap_or(entry_px);
+        positions.insert(
+            p.coin,
+            PositionView {
+                szi,
+                entry_px,
+                mark_px,
+                liquidation_px: p.liquidation_px.as_deref().map(num),
+            },
+        );
+    }
+    view.positions = positions;
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: monitor <ADDRESS> [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (address, network) = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [address] => (address, "mainnet"),
+        [address, "--network", network] => (address, network),
+        _ => usage("expected an address"),
+    };
+    let user: H160 = address
+        .parse()
+        .unwrap_or_else(|_| usage(&format!("invalid address {address}")));
+    let base_url = match network {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+
+    let mut info_client = InfoClient::with_reconnect(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+    let (sender, mut receiver) = unbounded_channel();
+    for subscription in [Subscription::UserEvents { user }, Subscription::AllMids] {
+        if let Err(e) = info_client.subscribe(subscription, sender.clone()).await {
+            usage(&format!("subscription failed: {e}"));
+        }
+    }
+
+    let mut view = AccountView::default();
+    refresh(&info_client, user, &mut view).await;
+    view.print();
+
+    let mut print_timer = interval(Duration::from_secs(PRINT_INTERVAL_SECS));
+    let mut refresh_timer = interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Some(Message::AllMids(all_mids)) => {
+                    for (coin, position) in view.positions.iter_mut() {
+                        if let Some(mid) = all_mids.data.mids.get(coin) {
+                            position.mark_px = num(mid);
+                        }
+                    }
+                }
+                Some(Message::User(user_event)) => match user_event.data {
+                    UserData::Fills(fills) => {
+                        for fill in fills {
+                            println!(
+                                "[Fill] {} {} {} @ {} | closed PnL {} | fee {}",
+                                fill.coin, fill.dir, fill.sz, fill.px, fill.closed_pnl, fill.fee
+                            );
+                            view.realized_pnl += num(&fill.closed_pnl);
+                            view.fees += num(&fill.fee);
+                        }
+                        // Entry price and size after a fill are only authoritative from the exchange
+                        refresh(&info_client, user, &mut view).await;
+                    }
+                    UserData::Funding(funding) => {
+                        println!(
+                            "[Funding] {} {} USDC at rate {}",
+                            funding.coin, funding.usdc, funding.funding_rate
+                        );
+                        view.funding += num(&funding.usdc);
+                    }
+                    UserData::Liquidation(liquidation) => {
+                        println!(
+                            "[Liquidation] notional {} account value {}",
+                            liquidation.liquidated_ntl_pos, liquidation.liquidated_account_value
+                        );
+                        refresh(&info_client, user, &mut view).await;
+                    }
+                    UserData::NonUserCancel(cancels) => {
+                        for cancel in cancels {
+                            println!("[Cancelled by exchange] {} oid {}", cancel.coin, cancel.oid);
+                        }
+                    }
+                },
+                Some(Message::HyperliquidError(e)) => warn!("websocket error: {e}"),
+                Some(_) => {}
+                None => {
+                    eprintln!("subscription channel closed");
+                    process::exit(1);
+                }
+            },
+            _ = print_timer.tick() => view.print(),
+            _ = refresh_timer.tick() => refresh(&info_client, user, &mut view).await,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+/*
+Emergency flatten: cancels every open order and market-closes every open position
+for the wallet in HL_PRIVATE_KEY. Intended for when the main bot is misbehaving.
+
+    panic_close [--network mainnet|testnet] [--slippage 0.05]
+
+Each close is an IOC whose limit walks the live book, bounded by --slippage.
+Failures are reported and the remaining assets are still processed.
+*/
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid_rust_sdk::{
+    BaseUrl, ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
+    MarketCloseParams,
+};
+use std::{env, process};
+
+const DEFAULT_SLIPPAGE: f64 = 0.05;
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: panic_close [--network mainnet|testnet] [--slippage FRAC]");
+    process::exit(2)
+}
+
+fn describe(response: ExchangeResponseStatus) -> Result<String, String> {
+    let response = match response {
+        ExchangeResponseStatus::Ok(response) => response,
+        ExchangeResponseStatus::Err(e) => return Err(format!("exchange error: {e}")),
+    };
+    let statuses = response.data.map(|data| data.statuses).unwrap_or_default();
+    let mut out = Vec::new();
+    for status in statuses {
+        match status {
+            ExchangeDataStatus::Filled(order) => {
+                out.push(format!("filled {} @ {}", order.total_sz, order.avg_px))
+            }
+            ExchangeDataStatus::Success => out.push("ok".to_string()),
+            ExchangeDataStatus::Error(e) => return Err(e),
+            status => out.push(format!("{status:?}")),
+        }
+    }
+    Ok(out.join(", "))
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut network = "testnet".to_string();
+    let mut slippage = DEFAULT_SLIPPAGE;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--network" => network = value,
+            "--slippage" => {
+                slippage = value
+                    .parse()
+                    .unwrap_or_else(|_| usage("--slippage must be a number"))
+            }
+            other => usage(&format!("unexpected argument {other}")),
+        }
+    }
+    let base_url = match network.as_str() {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+    let wallet: LocalWallet = env::var("HL_PRIVATE_KEY")
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not set"))
+        .parse()
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not a valid private key"));
+
+    let exchange_client = ExchangeClient::new(None, wallet.clone(), Some(base_url), None, None)
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create exchange client: {e}")));
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+    let user = wallet.address();
+    let mut failures = 0;
+
+    // Orders first, so nothing resting can re-open a position we just closed
+    match exchange_client.cancel_all_orders(None).await {
+        Ok(responses) if responses.is_empty() => println!("no open orders"),
+        Ok(responses) => {
+            for response in responses {
+                match describe(response) {
+                    Ok(_) => println!("cancelled a batch of open orders"),
+                    Err(e) => {
+                        failures += 1;
+                        eprintln!("cancel failed: {e}");
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            failures += 1;
+            eprintln!("cancel failed: {e}");
+        }
+    }
+
+    let positions = match info_client.user_state(user).await {
+        Ok(user_state) => user_state.asset_positions,
+        Err(e) => {
+            eprintln!("could not fetch positions: {e}");
+            process::exit(1);
+        }
+    };
+    for asset_position in positions {
+        let position = asset_position.position;
+        let szi: f64 = position.szi.parse().unwrap_or(0.0);
+        if szi == 0.0 {
+            continue;
+        }
+        let params = MarketCloseParams {
+            asset: &position.coin,
+            sz: None,
+            px: None,
+            slippage: None,
+            cloid: None,
+            wallet: None,
+        };
+        let result = match exchange_client
+            .market_close_protected(params, slippage)
+            .await
+        {
+            Ok(response) => describe(response),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            Ok(fills) => println!("{} {szi}: {fills}", position.coin),
+            Err(e) => {
+                failures += 1;
+                eprintln!("{} {szi}: close failed: {e}", position.coin);
+            }
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{failures} step(s) failed, check the account manually");
+        process::exit(1);
+    }
+}
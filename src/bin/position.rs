@@ -0,0 +1,231 @@
+/*
+Open, close or flip a perp position from the command line.
+
+    position open  --asset ETH --side buy --size 0.01 [--slippage 0.01] [--network testnet]
+    position close --asset ETH [--size 0.005] [--slippage 0.01] [--network testnet]
+    position flip  --asset ETH [--size 0.02] [--slippage 0.01] [--network testnet]
+
+Orders are IOC with a limit derived from live book depth and bounded by --slippage.
+A flip opens the other side only once the close has filled in full, by the size
+closed unless --size is given.
+The private key is read from the HL_PRIVATE_KEY environment variable.
+*/
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid_rust_sdk::{
+    BaseUrl, ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
+    MarketCloseParams, MarketOrderParams, EPSILON,
+};
+use log::info;
+use std::{collections::HashMap, env, process};
+
+const DEFAULT_SLIPPAGE: f64 = 0.01;
+
+struct Args {
+    command: String,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse() -> Result<Args, String> {
+        let mut args = env::args().skip(1);
+        let command = args.next().ok_or("missing command (open|close|flip)")?;
+        let mut flags = HashMap::new();
+        while let Some(flag) = args.next() {
+            let name = flag
+                .strip_prefix("--")
+                .ok_or(format!("unexpected argument {flag}"))?;
+            let value = args.next().ok_or(format!("missing value for --{name}"))?;
+            flags.insert(name.to_string(), value);
+        }
+        Ok(Args { command, flags })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    fn require(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or(format!("--{name} is required"))
+    }
+
+    fn float(&self, name: &str) -> Result<Option<f64>, String> {
+        self.get(name)
+            .map(|v| v.parse().map_err(|_| format!("--{name} must be a number")))
+            .transpose()
+    }
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: position <open|close|flip> --asset COIN [--side buy|sell] [--size SZ] [--slippage FRAC] [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{msg}");
+    process::exit(1)
+}
+
+// Returns the filled size, or an error message for anything that didn't fill
+fn check_fill(label: &str, response: ExchangeResponseStatus) -> Result<f64, String> {
+    let response = match response {
+        ExchangeResponseStatus::Ok(response) => response,
+        ExchangeResponseStatus::Err(e) => return Err(format!("{label}: exchange error: {e}")),
+    };
+    let status = response
+        .data
+        .and_then(|data| data.statuses.into_iter().next())
+        .ok_or(format!("{label}: empty response"))?;
+    match status {
+        ExchangeDataStatus::Filled(order) => {
+            println!(
+                "{label}: filled {} @ {} (oid {})",
+                order.total_sz, order.avg_px, order.oid
+            );
+            Ok(order.total_sz.parse().unwrap_or(0.0))
+        }
+        ExchangeDataStatus::Error(e) => Err(format!("{label}: rejected: {e}")),
+        status => Err(format!("{label}: unexpected status {status:?}")),
+    }
+}
+
+async fn current_size(info_client: &InfoClient, wallet: &LocalWallet, asset: &str) -> f64 {
+    let user_state = match info_client.user_state(wallet.address()).await {
+        Ok(user_state) => user_state,
+        Err(e) => fail(format!("could not fetch user state: {e}")),
+    };
+    user_state
+        .asset_positions
+        .iter()
+        .find(|p| p.position.coin == asset)
+        .and_then(|p| p.position.szi.parse().ok())
+        .unwrap_or(0.0)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args = Args::parse().unwrap_or_else(|e| usage(&e));
+    let asset = args.require("asset").unwrap_or_else(|e| usage(&e));
+    let size = args.float("size").unwrap_or_else(|e| usage(&e));
+    let slippage = args
+        .float("slippage")
+        .unwrap_or_else(|e| usage(&e))
+        .unwrap_or(DEFAULT_SLIPPAGE);
+    let base_url = match args.get("network").unwrap_or("testnet") {
+        "mainnet" => BaseUrl::Mainnet,
+        "testnet" => BaseUrl::Testnet,
+        other => usage(&format!("unknown network {other}")),
+    };
+    let wallet: LocalWallet = env::var("HL_PRIVATE_KEY")
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not set"))
+        .parse()
+        .unwrap_or_else(|_| usage("HL_PRIVATE_KEY is not a valid private key"));
+
+    let exchange_client = ExchangeClient::new(None, wallet.clone(), Some(base_url), None, None)
+        .await
+        .unwrap_or_else(|e| fail(format!("could not create exchange client: {e}")));
+    let info_client = InfoClient::new(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| fail(format!("could not create info client: {e}")));
+
+    let result = match args.command.as_str() {
+        "open" => {
+            let is_buy = match args.require("side").unwrap_or_else(|e| usage(&e)) {
+                "buy" | "long" => true,
+                "sell" | "short" => false,
+                other => usage(&format!("unknown side {other}")),
+            };
+            let sz = size.unwrap_or_else(|| usage("--size is required for open"));
+            let params = MarketOrderParams {
+                asset,
+                is_buy,
+                sz,
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: None,
+            };
+            match exchange_client
+                .market_open_protected(params, slippage)
+                .await
+            {
+                Ok(response) => check_fill("open", response).map(|_| ()),
+                Err(e) => Err(format!("open: {e}")),
+            }
+        }
+        "close" => {
+            if current_size(&info_client, &wallet, asset).await == 0.0 {
+                fail(format!("no open {asset} position"));
+            }
+            let params = MarketCloseParams {
+                asset,
+                sz: size,
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: None,
+            };
+            match exchange_client
+                .market_close_protected(params, slippage)
+                .await
+            {
+                Ok(response) => check_fill("close", response).map(|_| ()),
+                Err(e) => Err(format!("close: {e}")),
+            }
+        }
+        "flip" => {
+            let szi = current_size(&info_client, &wallet, asset).await;
+            if szi == 0.0 {
+                fail(format!("no open {asset} position to flip"));
+            }
+            let close = MarketCloseParams {
+                asset,
+                sz: None,
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: None,
+            };
+            let closed = match exchange_client
+                .market_close_protected(close, slippage)
+                .await
+            {
+                Ok(response) => check_fill("flip/close", response),
+                Err(e) => Err(format!("flip/close: {e}")),
+            };
+            match closed {
+                // Opening the full size after a partial close would leave the
+                // wrong net position
+                Ok(closed) if closed < szi.abs() - EPSILON => Err(format!(
+                    "flip/close: only {closed} of {} closed, not opening the other side",
+                    szi.abs()
+                )),
+                Ok(closed) => {
+                    info!("Closed {closed} of {szi}, opening the other side");
+                    let open = MarketOrderParams {
+                        asset,
+                        is_buy: szi < 0.0,
+                        sz: size.unwrap_or(closed),
+                        px: None,
+                        slippage: None,
+                        cloid: None,
+                        wallet: None,
+                    };
+                    match exchange_client.market_open_protected(open, slippage).await {
+                        Ok(response) => check_fill("flip/open", response).map(|_| ()),
+                        Err(e) => Err(format!("flip/open: {e}")),
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        }
+        other => usage(&format!("unknown command {other}")),
+    };
+
+    if let Err(e) = result {
+        fail(e);
+    }
+    let szi = current_size(&info_client, &wallet, asset).await;
+    println!("{asset} position is now {szi}");
+}
@@ -0,0 +1,168 @@
+/*
+Deterministic replay of a recorded session. Feeds recorder tapes (or an
+orderbook_log.json) through the same `MessageRouter` trade_new runs live, one
+router per coin, so a strategy bug seen in production can be reproduced with the
+same signals, quotes, risk decisions and journal entries.
+
+    replay --file PATH [--coin COIN ...] [--speed realtime|10x|max] [--config bot.toml]
+           [--strategy NAME] [--journal PATH]
+
+--speed paces events by their exchange timestamps: `realtime`, a multiple such as
+`10x`, or `max` (the default) for as fast as possible. Events are handled one at
+a time in recorded order, so the outcome does not depend on the speed. Hedges
+are simulated at the touch. Each coin's position, ledger and volume efficiency
+(fees at the config's schedule plus PnL lost, per $1M traded) are printed at the
+end; --journal writes the run journal for the viewer. --config applies the same
+bot config file as trade_new, per-coin overrides included.
+*/
+use hyperliquid_rust_sdk::{
+    build_strategy, load_recording, registered_strategies, AdminRequest, BotConfig, Journal,
+    MessageRouter, RunManifest,
+};
+use log::{info, warn};
+use serde_json::json;
+use std::{collections::BTreeMap, env, process, sync::Arc};
+use tokio::time::{sleep_until, Duration, Instant};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: replay --file PATH [--coin COIN ...] [--speed realtime|Nx|max] [--config FILE] [--strategy NAME] [--journal PATH]");
+    process::exit(2)
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{msg}");
+    process::exit(1)
+}
+
+// Replay speed as a multiple of recorded time; None replays as fast as possible
+fn parse_speed(value: &str) -> Option<Option<f64>> {
+    match value {
+        "max" => Some(None),
+        "realtime" => Some(Some(1.0)),
+        v => {
+            let speed: f64 = v.strip_suffix('x').unwrap_or(v).parse().ok()?;
+            (speed.is_finite() && speed > 0.0).then_some(Some(speed))
+        }
+    }
+}
+
+async fn ask(router: &MessageRouter, command: &str) -> String {
+    let (req, response) = AdminRequest::new(command, vec![]);
+    router.handle_admin(req).await;
+    response.await.unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() {
+    let (mut file, mut journal_path, mut config_path) = (None, None, None);
+    let mut coins = Vec::new();
+    let mut speed = None;
+    let mut strategy_name = None;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--file" => file = Some(value),
+            "--coin" => coins.push(value.to_uppercase()),
+            "--speed" => {
+                speed =
+                    parse_speed(&value).unwrap_or_else(|| usage(&format!("bad --speed {value}")))
+            }
+            "--config" => config_path = Some(value),
+            "--strategy" => strategy_name = Some(value),
+            "--journal" => journal_path = Some(value),
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+    let file = file.unwrap_or_else(|| usage("--file is required"));
+    let config =
+        BotConfig::load(config_path.as_ref()).unwrap_or_else(|e| fail(format!("bad config: {e}")));
+    let manifest = RunManifest::new(
+        "replay",
+        "recorded",
+        None,
+        json!({
+            "file": file,
+            "coins": coins,
+            "speed": speed,
+            "strategy": strategy_name,
+            "config": config,
+        }),
+    );
+    manifest.init_logging();
+    let journal = journal_path.as_ref().map(|path| {
+        Arc::new(
+            Journal::open(path, &manifest)
+                .unwrap_or_else(|e| fail(format!("failed to open {path}: {e}"))),
+        )
+    });
+
+    let recording =
+        load_recording(&file).unwrap_or_else(|e| fail(format!("failed to load {file}: {e}")));
+    if recording.skipped > 0 {
+        info!("skipped {} unparseable lines", recording.skipped);
+    }
+    for (since, until) in &recording.gaps {
+        warn!("no data recorded from {since} to {until}");
+    }
+    let events: Vec<_> = recording
+        .events
+        .into_iter()
+        .filter(|e| coins.is_empty() || coins.contains(&e.coin))
+        .collect();
+    if events.is_empty() {
+        fail(format!("{file} has no events for the selected coins"));
+    }
+
+    let mut routers = BTreeMap::new();
+    for coin in events.iter().map(|e| e.coin.clone()) {
+        if routers.contains_key(&coin) {
+            continue;
+        }
+        let coin_config = config
+            .for_coin(&coin)
+            .unwrap_or_else(|e| fail(format!("bad config for {coin}: {e}")));
+        let name = strategy_name.as_ref().unwrap_or(&coin_config.strategy.name);
+        let mut strategy = build_strategy(name).unwrap_or_else(|e| {
+            let names: Vec<_> = registered_strategies().iter().map(|r| r.name).collect();
+            usage(&format!("{e}; available: {}", names.join(", ")))
+        });
+        strategy.configure(&coin_config.strategy);
+        let risk_mgr = Arc::new(coin_config.risk.risk_manager());
+        let mut router = MessageRouter::new(strategy, risk_mgr, &coin)
+            .with_signal_windows(coin_config.strategy.signal_windows())
+            .with_fee_model(config.fees.schedule());
+        if let Some(journal) = &journal {
+            router = router.with_journal(journal.clone());
+        }
+        routers.insert(coin, router);
+    }
+    info!(
+        "replaying {} events for {} at {}",
+        events.len(),
+        routers.keys().cloned().collect::<Vec<_>>().join(", "),
+        speed.map_or("max speed".to_string(), |s| format!("{s}x"))
+    );
+
+    let started = Instant::now();
+    let first_ms = events[0].event.time();
+    for recorded in events {
+        if let Some(speed) = speed {
+            let offset_ms = recorded.event.time().saturating_sub(first_ms) as f64 / speed;
+            sleep_until(started + Duration::from_secs_f64(offset_ms / 1000.0)).await;
+        }
+        routers[&recorded.coin].handle_event(recorded.event).await;
+    }
+
+    // Ends the status line the reporter keeps rewriting
+    println!();
+    for router in routers.values() {
+        println!("{}", ask(router, "pos").await);
+        println!("{}", ask(router, "ledger").await);
+        println!("{}", ask(router, "efficiency").await);
+    }
+    info!("replay done in {:.1}s", started.elapsed().as_secs_f64());
+}
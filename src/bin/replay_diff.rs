@@ -0,0 +1,131 @@
+/*
+Side-by-side diff of two short replay windows for strategy bug reports: the
+"expected" window, from a run or moment that behaved, next to the "actual" one
+from the report. Each window is a market-data recording and the journal of the
+run that traded it, replayed book by book as in the viewer; the two are paired
+step by step from their first book, with the bot's fills and hedges annotated
+on every step and the steps where they differ flagged.
+
+    replay_diff --expected REC --expected-journal JOURNAL --expected-from MS [--expected-to MS] [--expected-run ID]
+                --actual REC --actual-journal JOURNAL --actual-from MS [--actual-to MS] [--actual-run ID]
+                [--coin BTC] [--frames 20] [--title TEXT] [--out replay_diff.html]
+
+Writes a standalone HTML page to attach to the report, or plain text when
+--out does not end in .html. Journals default to their last run and the coin
+to the expected recording's first.
+*/
+use hyperliquid_rust_sdk::{load_recording, read_journal, DiffSide, ReplayDiff, SessionViewer};
+use std::{collections::HashMap, env, fs, process};
+
+const DEFAULT_FRAMES: usize = 20;
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: replay_diff --expected REC --expected-journal JOURNAL --expected-from MS [--expected-to MS] [--expected-run ID] --actual REC --actual-journal JOURNAL --actual-from MS [--actual-to MS] [--actual-run ID] [--coin COIN] [--frames N] [--title TEXT] [--out PATH]");
+    process::exit(2)
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{msg}");
+    process::exit(1)
+}
+
+// The `side` ("expected" or "actual") window of `coin`, or of the recording's
+// first coin
+fn capture(
+    side: &str,
+    flags: &HashMap<String, String>,
+    coin: Option<&str>,
+    frames: usize,
+) -> (String, DiffSide) {
+    let flag = |name: &str| flags.get(&format!("--{side}{name}")).map(String::as_str);
+    let millis = |name: &str| {
+        flag(name).map(|v| {
+            v.parse::<u64>()
+                .unwrap_or_else(|_| usage(&format!("--{side}{name} must be a timestamp in ms")))
+        })
+    };
+    let file = flag("").unwrap_or_else(|| usage(&format!("--{side} is required")));
+    let journal =
+        flag("-journal").unwrap_or_else(|| usage(&format!("--{side}-journal is required")));
+    let from = millis("-from").unwrap_or_else(|| usage(&format!("--{side}-from is required")));
+    let to = millis("-to").unwrap_or(u64::MAX);
+
+    let recording =
+        load_recording(file).unwrap_or_else(|e| fail(format!("failed to load {file}: {e}")));
+    let mut records =
+        read_journal(journal).unwrap_or_else(|e| fail(format!("failed to read {journal}: {e}")));
+    let run_id = flag("-run")
+        .map(str::to_string)
+        .or_else(|| records.last().map(|r| r.run_id.clone()))
+        .unwrap_or_else(|| fail(format!("{journal} is empty")));
+    records.retain(|r| r.run_id == run_id);
+    let coin = coin
+        .map(str::to_string)
+        .or_else(|| recording.events.first().map(|e| e.coin.clone()))
+        .unwrap_or_else(|| fail(format!("{file} has no events")));
+    let events = recording
+        .events
+        .into_iter()
+        .filter(|e| e.coin == coin)
+        .map(|e| e.event)
+        .collect();
+    let mut viewer = SessionViewer::new(&coin, events, records);
+    let label = format!("{side}: {coin} run {run_id} from {from}");
+    let window = DiffSide::capture(&label, &mut viewer, from, to, frames);
+    if window.panels.is_empty() {
+        fail(format!("no {coin} books in {file} from {from}"));
+    }
+    (coin, window)
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut flags = HashMap::new();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+    let known = ["", "-journal", "-from", "-to", "-run"]
+        .iter()
+        .flat_map(|suffix| ["expected", "actual"].map(|side| format!("--{side}{suffix}")))
+        .chain(["--coin", "--frames", "--title", "--out"].map(str::to_string))
+        .collect::<Vec<_>>();
+    if let Some(unknown) = flags.keys().find(|f| !known.contains(f)) {
+        usage(&format!("unknown flag {unknown}"));
+    }
+    let frames = flags.get("--frames").map_or(DEFAULT_FRAMES, |n| {
+        n.parse()
+            .unwrap_or_else(|_| usage("--frames must be a number"))
+    });
+    let coin = flags.get("--coin").map(|c| c.to_uppercase());
+    let (coin, expected) = capture("expected", &flags, coin.as_deref(), frames);
+    let (_, actual) = capture("actual", &flags, Some(&coin), frames);
+    let diff = ReplayDiff::new(expected, actual);
+
+    let out = flags
+        .get("--out")
+        .map_or("replay_diff.html", String::as_str);
+    let title = flags
+        .get("--title")
+        .cloned()
+        .unwrap_or_else(|| format!("{coin} expected vs actual"));
+    let artifact = if out.ends_with(".html") {
+        diff.render_html(&title)
+    } else {
+        format!("{title}\n{}", diff.render_text())
+    };
+    fs::write(out, artifact).unwrap_or_else(|e| fail(format!("failed to write {out}: {e}")));
+    let divergent = diff.divergent_steps();
+    println!(
+        "wrote {out}: {} of {} steps decided differently{}",
+        divergent.len(),
+        diff.expected.panels.len().max(diff.actual.panels.len()),
+        match divergent.first() {
+            Some(step) => format!(", first at step {step}"),
+            None => String::new(),
+        }
+    );
+}
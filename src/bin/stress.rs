@@ -0,0 +1,55 @@
+/*
+Runs every synthetic stress scenario (flash crash, spread blowout, one-sided book,
+feed freeze) through the signal, quoting and risk stack in simulation and reports
+whether the risk limits, the burst circuit and the drawdown kill switch held.
+
+    stress [--max-position 5.0] [--seed 7]
+
+Exits non-zero if any scenario recorded a violation.
+*/
+use hyperliquid_rust_sdk::{run_scenario, Scenario};
+use std::{env, process};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: stress [--max-position SIZE] [--seed N]");
+    process::exit(2)
+}
+
+fn main() {
+    env_logger::init();
+    let mut max_position = 5.0;
+    let mut seed = 7;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--max-position" => {
+                max_position = value
+                    .parse()
+                    .unwrap_or_else(|_| usage("--max-position must be a number"))
+            }
+            "--seed" => {
+                seed = value
+                    .parse()
+                    .unwrap_or_else(|_| usage("--seed must be an integer"))
+            }
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+
+    let mut failed = 0;
+    for scenario in Scenario::ALL {
+        let report = run_scenario(scenario, max_position, seed);
+        if !report.passed() {
+            failed += 1;
+        }
+        println!("{report}");
+    }
+    if failed > 0 {
+        eprintln!("{failed} scenario(s) failed");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,203 @@
+/*
+Testnet bootstrap check. Validates the wallet in HL_PRIVATE_KEY and the local
+environment before running any strategy:
+
+  1. connectivity to the info endpoint
+  2. testnet account balances
+  3. leverage can be set
+  4. a tiny resting order can be placed and cancelled
+
+    testnet_setup [--coin ETH] [--leverage 3]
+
+Only ever talks to testnet.
+*/
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid_rust_sdk::{
+    AssetSpecs, BaseUrl, ClientCancelRequest, ClientLimit, ClientOrder, ClientOrderRequest,
+    ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
+};
+use std::{collections::HashMap, env, process, time::Instant};
+
+// Exchange minimum order value is $10; stay a little above it
+const PROBE_NOTIONAL: f64 = 11.0;
+// Far enough from the mid that the probe can't fill before it is cancelled
+const PROBE_DISTANCE: f64 = 0.05;
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: testnet_setup [--coin COIN] [--leverage N]");
+    process::exit(2)
+}
+
+fn pass(step: &str, detail: impl std::fmt::Display) {
+    println!("[PASS] {step}: {detail}");
+}
+
+fn fail(step: &str, detail: impl std::fmt::Display) -> ! {
+    println!("[FAIL] {step}: {detail}");
+    process::exit(1)
+}
+
+fn first_status(step: &str, response: ExchangeResponseStatus) -> ExchangeDataStatus {
+    match response {
+        ExchangeResponseStatus::Ok(response) => response
+            .data
+            .and_then(|data| data.statuses.into_iter().next())
+            .unwrap_or_else(|| fail(step, "empty response")),
+        ExchangeResponseStatus::Err(e) => fail(step, e),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let mut flags = HashMap::new();
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        flags.insert(flag, value);
+    }
+    let coin = flags.get("--coin").map(String::as_str).unwrap_or("ETH");
+    let leverage: u32 = flags
+        .get("--leverage")
+        .map(|l| {
+            l.parse()
+                .unwrap_or_else(|_| usage("--leverage must be an integer"))
+        })
+        .unwrap_or(3);
+
+    let wallet: LocalWallet = match env::var("HL_PRIVATE_KEY") {
+        Ok(key) => key
+            .parse()
+            .unwrap_or_else(|_| fail("wallet", "HL_PRIVATE_KEY is not a valid private key")),
+        Err(_) => fail("wallet", "HL_PRIVATE_KEY is not set"),
+    };
+    let address = wallet.address();
+    pass("wallet", format!("{address:?}"));
+
+    let start = Instant::now();
+    let info_client = InfoClient::new(None, Some(BaseUrl::Testnet))
+        .await
+        .unwrap_or_else(|e| fail("connectivity", e));
+    let meta = info_client
+        .meta()
+        .await
+        .unwrap_or_else(|e| fail("connectivity", e));
+    let mids = info_client
+        .all_mids()
+        .await
+        .unwrap_or_else(|e| fail("connectivity", e));
+    pass(
+        "connectivity",
+        format!(
+            "{} perps listed, round trips took {:.0}ms",
+            meta.universe.len(),
+            start.elapsed().as_secs_f64() * 1000.0
+        ),
+    );
+
+    let user_state = info_client
+        .user_state(address)
+        .await
+        .unwrap_or_else(|e| fail("balances", e));
+    let account_value: f64 = user_state
+        .margin_summary
+        .account_value
+        .parse()
+        .unwrap_or(0.0);
+    if account_value < PROBE_NOTIONAL {
+        fail(
+            "balances",
+            format!("account value is {account_value:.2} USDC; claim testnet USDC from the faucet first"),
+        );
+    }
+    pass(
+        "balances",
+        format!(
+            "account value {account_value:.2} USDC, withdrawable {}",
+            user_state.withdrawable
+        ),
+    );
+
+    let exchange_client = ExchangeClient::new(
+        None,
+        wallet,
+        Some(BaseUrl::Testnet),
+        Some(meta.clone()),
+        None,
+    )
+    .await
+    .unwrap_or_else(|e| fail("exchange client", e));
+    match exchange_client
+        .update_leverage(leverage, coin, true, None)
+        .await
+    {
+        Ok(ExchangeResponseStatus::Ok(_)) => {
+            pass("leverage", format!("{coin} set to {leverage}x cross"))
+        }
+        Ok(ExchangeResponseStatus::Err(e)) => fail("leverage", e),
+        Err(e) => fail("leverage", e),
+    }
+
+    let spec = AssetSpecs::from_meta(&meta)
+        .get(coin)
+        .unwrap_or_else(|| fail("probe order", format!("{coin} is not listed")));
+    let mid: f64 = mids
+        .get(coin)
+        .and_then(|m| m.parse().ok())
+        .unwrap_or_else(|| fail("probe order", format!("no mid for {coin}")));
+    let px = spec.round_price(mid * (1.0 - PROBE_DISTANCE));
+    let step = 10f64.powi(-(spec.sz_decimals as i32));
+    let sz = spec.round_size((PROBE_NOTIONAL / px / step).ceil() * step);
+
+    let order = ClientOrderRequest {
+        asset: coin.to_string(),
+        is_buy: true,
+        reduce_only: false,
+        limit_px: px,
+        sz,
+        cloid: None,
+        order_type: ClientOrder::Limit(ClientLimit {
+            tif: "Alo".to_string(),
+        }),
+    };
+    let response = exchange_client
+        .order(order, None)
+        .await
+        .unwrap_or_else(|e| fail("probe order", e));
+    let oid = match first_status("probe order", response) {
+        ExchangeDataStatus::Resting(order) => order.oid,
+        ExchangeDataStatus::Filled(order) => fail(
+            "probe order",
+            format!("unexpectedly filled (oid {}), close it manually", order.oid),
+        ),
+        status => fail("probe order", format!("{status:?}")),
+    };
+    pass(
+        "probe order",
+        format!("buy {sz} {coin} @ {px} resting, oid {oid}"),
+    );
+
+    let response = exchange_client
+        .cancel(
+            ClientCancelRequest {
+                asset: coin.to_string(),
+                oid,
+            },
+            None,
+        )
+        .await
+        .unwrap_or_else(|e| fail("probe cancel", e));
+    match first_status("probe cancel", response) {
+        ExchangeDataStatus::Success => pass("probe cancel", format!("oid {oid} cancelled")),
+        status => fail(
+            "probe cancel",
+            format!("{status:?}, cancel oid {oid} manually"),
+        ),
+    }
+
+    println!();
+    println!("Environment looks good; strategies can run against testnet.");
+}
@@ -1,373 +1,689 @@
-use hyperliquid_rust_sdk::{BaseUrl, InfoClient, Message, Subscription};
-use std::{
-    collections::VecDeque,
-    io::{self, Write},
-    sync::Arc,
+use ethers::signers::{LocalWallet, Signer};
+use hyperliquid_rust_sdk::{
+    build_strategy, registered_strategies, scan_markets, serve_admin, AccountGuard, AssetSpecs,
+    BotConfig, Chaos, ChaosConfig, CoinConfig, ConfigWatcher, DecayKernel, EventLog,
+    ExchangeClient, Executor, FairValue, FlowMeasure, FundingMonitor, GlobalExposure, InfoClient,
+    Journal, KillSwitch, LatencyTracker, MarginGuard, MarketRotation, Message, MessageRouter,
+    Metrics, Notifier, OrderManager, PaperConfig, PaperExchange, PnlTracker, PreTradeChecks,
+    RateLimiter, Reconciler, ReportLayout, RotationConfig, RunManifest, StatusReporter, Strategy,
+    Subscription, SymbolManager,
 };
-use tokio::sync::{mpsc::unbounded_channel, Mutex};
-// Parameters for signal windows and thresholds
-const TWAP_WINDOW: usize = 120;
-const TRADE_WINDOW: usize = 80;
-const DEVIATION_THRESHOLD: f64 = 0.002;
-const AGGRESSIVE_SPREAD_TICKS: f64 = 0.5;
-const BASE_QUOTE_SIZE: f64 = 1.0;
-const POSITION_LIMIT: f64 = 5.0; // Max inventory
-                                 // Market data samples
-#[derive(Debug, Clone)]
-pub struct BookSample {
-    pub timestamp_ms: u64,
-    pub mid_price: f64,
-    pub best_bid: f64,
-    pub best_ask: f64,
-    pub bid_volume: f64,
-    pub ask_volume: f64,
+use log::{error, info};
+use serde_json::json;
+use std::{collections::BTreeMap, env, sync::Arc, time::Duration};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedSender},
+    watch,
+};
+
+const VPIN_BUCKETS: usize = 50; // Volume buckets in the flow window with `--vpin-bucket`
+const CONFIG_POLL: Duration = Duration::from_secs(2); // How often `--config` is checked for edits
+const FUNDING_POLL: Duration = Duration::from_secs(300); // How often funding history is checked, with `--execution live`
+const RETIRE_POLL: Duration = Duration::from_secs(1); // How often wound-down markets are removed, with `--rotate`
+const FEED_STALE: Duration = Duration::from_secs(10); // Silence after which the market data websocket is reconnected
+const MARGIN_POLL: Duration = Duration::from_secs(5); // How often free margin is read, with `--min-free-margin`
+
+#[cfg(feature = "scripting")]
+fn with_script(
+    path: &str,
+    strategy: Box<dyn Strategy>,
+) -> Result<Box<dyn Strategy>, Box<dyn std::error::Error>> {
+    let script = hyperliquid_rust_sdk::SignalScript::from_file(path)?;
+    Ok(Box::new(hyperliquid_rust_sdk::ScriptedStrategy::new(
+        script, strategy,
+    )))
 }
-#[derive(Debug, Clone)]
-pub struct TradeSample {
-    pub price: f64,
-    pub size: f64,
-    pub is_buy: bool,
-    pub timestamp_ms: u64,
+#[cfg(not(feature = "scripting"))]
+fn with_script(
+    _path: &str,
+    _strategy: Box<dyn Strategy>,
+) -> Result<Box<dyn Strategy>, Box<dyn std::error::Error>> {
+    Err("--script needs a build with `--features scripting`".into())
 }
-// Internal position tracking
-#[derive(Debug, Default, Clone)]
-pub struct Position {
-    pub base: f64,  // Asset holdings (e.g. BTC)
-    pub quote: f64, // Quote currency (e.g. USD)
+#[cfg(feature = "wasm")]
+fn load_wasm(path: &str) -> Result<Box<dyn Strategy>, Box<dyn std::error::Error>> {
+    Ok(Box::new(hyperliquid_rust_sdk::WasmStrategy::from_file(
+        path,
+    )?))
 }
-// State holding recent history and signals
-#[derive(Debug, Default, Clone)]
-pub struct SignalState {
-    pub book_history: VecDeque<BookSample>,
-    pub trade_history: VecDeque<TradeSample>,
-    pub trend_score: f64,
-    pub twap: f64,
-    pub sliding_signal: f64,
-    pub normalized_slide: f64,
-    pub fill_score: f64,
-    pub twap_deviation: f64,
-    pub mean_revert_signal: String,
-    pub best_bid: f64,
-    pub best_ask: f64,
-    pub volatility: f64,
-    pub aggressive_mode: bool,
-    pub position: Position, // track current inventory
+#[cfg(not(feature = "wasm"))]
+fn load_wasm(_path: &str) -> Result<Box<dyn Strategy>, Box<dyn std::error::Error>> {
+    Err("--wasm needs a build with `--features wasm`".into())
 }
-// Compute standard deviation of mid-prices
-pub fn compute_volatility(history: &VecDeque<BookSample>) -> f64 {
-    let n = history.len();
-    if n < 2 {
-        return 0.0;
+// Book, trades and, for the oracle fair value or the funding rate, the asset
+// context of one coin
+async fn subscribe_coin(
+    info_client: &mut InfoClient,
+    coin: &str,
+    asset_ctx: bool,
+    feed: &UnboundedSender<Message>,
+) -> Result<Vec<u32>, hyperliquid_rust_sdk::Error> {
+    let coin = coin.to_string();
+    let mut subscriptions = vec![
+        Subscription::L2Book { coin: coin.clone() },
+        Subscription::Trades { coin: coin.clone() },
+    ];
+    if asset_ctx {
+        subscriptions.push(Subscription::ActiveAssetCtx { coin });
     }
-    let mean = history.iter().map(|s| s.mid_price).sum::<f64>() / n as f64;
-    let var = history
-        .iter()
-        .map(|s| (s.mid_price - mean).powi(2))
-        .sum::<f64>()
-        / n as f64;
-    var.sqrt()
-}
-// Core signal processing engine
-pub struct SignalEngine {
-    pub state: SignalState,
+    let mut ids = Vec::new();
+    for subscription in subscriptions {
+        ids.push(info_client.subscribe(subscription, feed.clone()).await?);
+    }
+    Ok(ids)
 }
-impl SignalEngine {
-    pub fn new() -> Self {
-        Self {
-            state: SignalState::default(),
+
+// === Main Execution ===
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    // `--coins BTC,ETH,...`; every coin runs its own task (default: BTC)
+    let coins: Vec<String> = flag("--coins").map_or(vec!["BTC".to_string()], |c| {
+        c.split(',')
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .collect()
+    });
+    // `--chaos` injects disconnects, delayed/duplicated messages and exchange errors
+    let chaos = args
+        .iter()
+        .any(|a| a == "--chaos")
+        .then(|| Chaos::new(ChaosConfig::moderate()));
+    // `--config PATH` loads strategy, risk and exchange settings from TOML, with
+    // per-coin tables and `HL_CFG_*` environment overrides (see BotConfig). The
+    // flags below take precedence for every coin. Edits to the file are picked up
+    // while running (see the main loop).
+    let config = BotConfig::load(flag("--config"))?;
+    // `--strategy NAME` picks any registered strategy (default: layered), `--wasm FILE`
+    // runs a sandboxed WASM strategy instead (wasm feature) and `--script FILE` lets a
+    // Rhai script pick the quote side (scripting feature)
+    let make_strategy = |name: &str| -> Result<Box<dyn Strategy>, Box<dyn std::error::Error>> {
+        let mut strategy = build_strategy(name).map_err(|e| {
+            let names: Vec<_> = registered_strategies().iter().map(|r| r.name).collect();
+            format!("{e}; available: {}", names.join(", "))
+        })?;
+        if let Some(path) = flag("--wasm") {
+            strategy = load_wasm(path)?;
         }
-    }
-    // Process each order-book update
-    pub fn process_l2_book(
-        &mut self,
-        ts: u64,
-        bid_px: f64,
-        ask_px: f64,
-        bid_vol: f64,
-        ask_vol: f64,
-    ) {
-        // Add new book sample
-        let mid = (bid_px + ask_px) / 2.0;
-        self.state.book_history.push_back(BookSample {
-            timestamp_ms: ts,
-            mid_price: mid,
-            best_bid: bid_px,
-            best_ask: ask_px,
-            bid_volume: bid_vol,
-            ask_volume: ask_vol,
-        });
-        if self.state.book_history.len() > TWAP_WINDOW {
-            self.state.book_history.pop_front();
+        if let Some(path) = flag("--script") {
+            strategy = with_script(path, strategy)?;
         }
-        // Update best prices
+        Ok(strategy)
+    };
+    let strategy_label = make_strategy(flag("--strategy").unwrap_or(&config.strategy.name))?
+        .name()
+        .to_string();
+    // `--vpin-bucket VOLUME` clocks trade flow in buckets of VOLUME (base units);
+    // otherwise `--decay exp:MS|linear:MS|power:MS:EXP` picks the age weighting
+    let flow = match flag("--vpin-bucket") {
+        Some(v) => FlowMeasure::VolumeBuckets {
+            bucket_volume: v.parse().map_err(|_| format!("bad --vpin-bucket {v}"))?,
+            buckets: VPIN_BUCKETS,
+        },
+        None => FlowMeasure::DecayedSlide(match flag("--decay") {
+            Some(kernel) => kernel.parse()?,
+            None => DecayKernel::default(),
+        }),
+    };
 
-        self.state.best_bid = bid_px;
-        self.state.best_ask = ask_px;
-        // Compute signals:
-        self.state.trend_score = compute_momentum(&self.state.book_history);
-        self.state.twap = compute_twap(&self.state.book_history);
-        self.state.twap_deviation = compute_twap_deviation(mid, self.state.twap);
-        self.state.mean_revert_signal = interpret_mean_reversion(self.state.twap_deviation);
-        self.state.volatility = compute_volatility(&self.state.book_history);
-        // Determine aggressive mode (tight market & low vol)
-        let current_spread = ask_px - bid_px;
-        self.state.aggressive_mode = current_spread <= 2.0 && self.state.volatility < 10.0;
-        // Compute order-flow imbalance (decay-weighted)
-        let (slide, norm) = compute_decay_weighted_slide(&self.state.trade_history, ts);
-        self.state.sliding_signal = slide;
-        self.state.normalized_slide = norm;
-        // Combine signals into final directional fill_score
-        let trend_strength = self.state.trend_score.tanh();
-        let micro_pressure = self.state.normalized_slide;
-        self.state.fill_score = if trend_strength.abs() > 0.1 {
-            trend_strength.signum()
-        } else if micro_pressure.abs() > 0.4 {
-            micro_pressure.signum()
-        } else {
-            0.0
-        };
-    }
-    // Process trade executions for trade flow
-    pub fn process_trade(&mut self, price: f64, size: f64, is_buy: bool, ts: u64) {
-        self.state.trade_history.push_back(TradeSample {
-            price,
-            size,
-            is_buy,
-            timestamp_ms: ts,
-        });
-        if self.state.trade_history.len() > TRADE_WINDOW {
-            self.state.trade_history.pop_front();
+    // `--max-notional USD` caps inventory by value at the live mid, and
+    // `--max-equity-pct PCT` at PCT% of the account equity given by `--equity USD`
+    let parse_usd = |name: &str| -> Result<Option<f64>, String> {
+        flag(name)
+            .map(|v| v.parse().map_err(|_| format!("bad {name} {v}")))
+            .transpose()
+    };
+    let max_notional = parse_usd("--max-notional")?;
+    let max_equity_pct = parse_usd("--max-equity-pct")?;
+    let equity = parse_usd("--equity")?;
+    // `--max-net-notional USD` caps the net exposure of all coins combined, and
+    // `--max-coin-margin-pct PCT` the margin any one coin's position may take
+    // to PCT% of the `--equity USD`, at the exchange leverage
+    let max_net_notional = parse_usd("--max-net-notional")?.or(config.risk.max_net_notional);
+    let max_coin_margin_pct =
+        parse_usd("--max-coin-margin-pct")?.or(config.risk.max_coin_margin_pct);
+    let global = match (max_net_notional, max_coin_margin_pct) {
+        (None, None) => None,
+        (net, pct) => {
+            let global = GlobalExposure::new(net.unwrap_or(f64::INFINITY));
+            if let Some(pct) = pct {
+                let equity = equity
+                    .or(config.risk.equity)
+                    .ok_or("--max-coin-margin-pct needs --equity")?;
+                global.set_max_coin_margin_pct(pct, config.exchange.leverage);
+                global.set_equity(equity);
+            }
+            Some(global)
         }
-    }
-    // Print debug info
-    pub fn print(&self) {
-        let s = &self.state;
-        println!(
-"[Signal] Trend: {:.3} | TWAP: {:.2} | Slide: {:.3} | NormSlide: {:.3} | FillScore: {:.2} | Dev: {:.4} | Vol: {:.2} | Aggro: {}",
-s.trend_score, s.twap, s.sliding_signal, s.normalized_slide,
-s.fill_score, s.twap_deviation, s.volatility, s.aggressive_mode
-);
-        io::stdout().flush().unwrap();
-    }
-}
-// === Signal computation helpers ===
-fn compute_momentum(hist: &VecDeque<BookSample>) -> f64 {
-    if hist.len() < 2 {
-        return 0.0;
-    }
-    // Sum of last-10 price changes
-    let recent: Vec<_> = hist.iter().rev().take(10).collect();
-    recent
-        .windows(2)
-        .map(|w| w[0].mid_price - w[1].mid_price)
-        .sum()
-}
-fn compute_twap(hist: &VecDeque<BookSample>) -> f64 {
-    let n = hist.len().min(TWAP_WINDOW);
-    if n == 0 {
-        return 0.0;
-    }
-    hist.iter().rev().take(n).map(|b| b.mid_price).sum::<f64>() / n as f64
-}
-fn compute_decay_weighted_slide(trades: &VecDeque<TradeSample>, now: u64) -> (f64, f64) {
-    let half_life_ms = 8000.0;
-    let mut weighted_net = 0.0;
-    let mut weighted_total = 0.0;
-    for trade in trades {
-        let age = (now as f64 - trade.timestamp_ms as f64).max(0.0);
-        let weight = (-age.ln_1p() / half_life_ms).exp();
-        let signed = if trade.is_buy { 1.0 } else { -1.0 };
-        weighted_net += signed * trade.size * weight;
-        weighted_total += trade.size * weight;
-    }
-    let norm = if weighted_total > 1e-6 {
-        weighted_net / weighted_total
-    } else {
-        0.0
     };
-    (weighted_net, norm)
-}
+    // `--min-free-margin USD` refuses to add a coin or raise its `max_notional`
+    // when the margin that notional needs would leave less than USD withdrawable
+    // (live execution only; every coin needs a `max_notional` to be sized by)
+    let min_free_margin = parse_usd("--min-free-margin")?.or(config.risk.min_free_margin);
+    let resolve = |config: &BotConfig| -> Result<BTreeMap<String, CoinConfig>, String> {
+        let mut coin_configs = BTreeMap::new();
+        for coin in &coins {
+            let mut coin_config = config.for_coin(coin).map_err(|e| e.to_string())?;
+            if let Some(name) = flag("--strategy") {
+                coin_config.strategy.name = name.clone();
+            }
+            let risk = &mut coin_config.risk;
+            risk.max_notional = max_notional.or(risk.max_notional);
+            risk.max_equity_pct = max_equity_pct.or(risk.max_equity_pct);
+            risk.equity = equity.or(risk.equity);
+            if risk.max_equity_pct.is_some() && risk.equity.is_none() {
+                return Err(format!("{coin}: --max-equity-pct needs --equity"));
+            }
+            if min_free_margin.is_some() && risk.max_notional.is_none() {
+                return Err(format!("{coin}: --min-free-margin needs --max-notional"));
+            }
+            coin_configs.insert(coin.clone(), coin_config);
+        }
+        Ok(coin_configs)
+    };
+    let mut coin_configs = resolve(&config)?;
+    let base_url = config.exchange.base_url()?;
+
+    // Every log line and journal record carries the run id from this manifest
+    let manifest = RunManifest::new(
+        "trade_new",
+        &config.exchange.network,
+        None,
+        json!({
+            "coins": coins,
+            "config": coin_configs,
+            "max_net_notional": max_net_notional,
+            "max_coin_margin_pct": max_coin_margin_pct,
+            "min_free_margin": min_free_margin,
+            "strategy": strategy_label,
+            "chaos": chaos.as_ref().map(|_| format!("{:?}", ChaosConfig::moderate())),
+            "script": flag("--script"),
+            "wasm": flag("--wasm"),
+            "admin": flag("--admin"),
+            "metrics_addr": flag("--metrics-addr"),
+            "flow": format!("{flow:?}"),
+            "execution": flag("--execution"),
+            "rotate": flag("--rotate"),
+            "max_markets": flag("--max-markets"),
+            "event_log": flag("--event-log"),
+            "dedup_ms": flag("--dedup-ms"),
+            "request_budget": flag("--request-budget"),
+            "batch_ms": flag("--batch-ms"),
+            "funding": flag("--funding"),
+        }),
+    );
+    manifest.init_logging();
+    // `--journal PATH` (default journal.jsonl)
+    let journal_path = flag("--journal").map_or("journal.jsonl", String::as_str);
+    let journal = Arc::new(Journal::open(journal_path, &manifest)?);
+    info!(
+        "Run {} ({} {}), journal {journal_path}",
+        manifest.run_id, manifest.git_hash, manifest.build_profile
+    );
+    info!("Running strategy {strategy_label} on {}", coins.join(", "));
+    // `--event-log PATH` writes every order intent, exchange response, fill,
+    // final order status and risk refusal as JSON lines, rotated by size;
+    // HL_LOG_FORMAT=json does the same for the log output
+    let event_log = flag("--event-log")
+        .map(|path| EventLog::open(path, &manifest.run_id))
+        .transpose()?;
+
+    // `--metrics-addr HOST:PORT` serves Prometheus metrics (execution quality per coin)
+    let metrics = flag("--metrics-addr").map(|addr| {
+        let metrics = Metrics::new();
+        let server = metrics.clone();
+        let addr = addr.clone();
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(&addr).await {
+                error!("Metrics server stopped: {e}");
+            }
+        });
+        metrics
+    });
+
+    // Round trips of every order and cancel the executor sends (admin
+    // `latency`); `risk.latency_spike_ms` throttles quoting when they spike
+    let latency = match &metrics {
+        Some(metrics) => LatencyTracker::new().with_metrics(metrics.clone()),
+        None => LatencyTracker::new(),
+    };
 
-fn compute_twap_deviation(p: f64, t: f64) -> f64 {
-    if t.abs() < 1e-6 {
-        0.0
-    } else {
-        (p - t) / t
+    // `--status-ms N` sets how often the signal status is printed (default 1000) and
+    // `--layout compact|multi` how (default: compact for one coin, multi otherwise)
+    let status_ms: u64 = flag("--status-ms").map_or(Ok(1000), |v| v.parse())?;
+    let layout = match flag("--layout").map(String::as_str) {
+        Some("compact") => ReportLayout::Compact,
+        Some("multi") => ReportLayout::MultiCoin,
+        Some(other) => return Err(format!("unknown --layout {other}").into()),
+        None if coins.len() > 1 => ReportLayout::MultiCoin,
+        None => ReportLayout::Compact,
+    };
+    let reporter = StatusReporter::new(Duration::from_millis(status_ms), layout);
+
+    // One websocket connection shared by all coins; each coin gets its own channel
+    // and task, and the multi-threaded runtime spreads the tasks over its workers.
+    // It reconnects when dropped or silent, resending each book before telling
+    // the routers the feed is back.
+    let mut info_client = InfoClient::with_reconnect(None, Some(base_url))
+        .await?
+        .with_stale_timeout(FEED_STALE);
+    // Price ticks and size decimals of every perp and spot pair, for rounding
+    // hedges and checking orders before they are sent
+    let specs = AssetSpecs::load(&info_client).await?;
+    // `--execution paper|live` sends hedges through an executor: `paper` fills them
+    // against the live book at the config's fee tier, `live` trades the
+    // HL_PRIVATE_KEY account. Without it hedges are assumed to fill at the touch.
+    let mut orders = OrderManager::new();
+    if let Some(log) = &event_log {
+        orders = orders.with_event_log(log.clone());
     }
-}
-fn interpret_mean_reversion(d: f64) -> String {
-    if d > DEVIATION_THRESHOLD {
-        "Fade breakout".into()
-    } else if d < -DEVIATION_THRESHOLD {
-        "Scalp retracement".into()
-    } else {
-        "Neutral".into()
+    // Live PnL from the account's own fills, fees and funding (admin `pnl`)
+    let pnl = PnlTracker::new();
+    let mut tracking_pnl = false;
+    // Liquidations, ADL fills and exchange cancels of the live account halt or
+    // pause every coin as they arrive, and are sent to the notifier
+    let account = AccountGuard::new().with_notifier(Arc::new(Notifier::from_env()));
+    let mut margin_guard = None;
+    // `--request-budget N` is the request weight per minute shared by the live
+    // executor and the REST pollers (default 1200, the exchange's limit per IP);
+    // cancels go first when it runs low and new quotes wait for it to refill
+    let limiter = match flag("--request-budget") {
+        Some(budget) => {
+            let per_min: f64 = budget
+                .parse()
+                .map_err(|_| format!("bad --request-budget {budget}"))?;
+            RateLimiter::new(per_min / 12.0, per_min / 60.0)
+        }
+        None => RateLimiter::default(),
+    };
+    let executor = match flag("--execution").map(String::as_str) {
+        Some("paper") => {
+            let (events_tx, mut events_rx) = unbounded_channel();
+            let paper = PaperExchange::new(PaperConfig {
+                fees: config.fees.schedule(),
+                ..Default::default()
+            })
+            .with_events(events_tx);
+            let manager = orders.clone();
+            tokio::spawn(async move {
+                while let Some(msg) = events_rx.recv().await {
+                    manager.on_message(&msg);
+                }
+            });
+            Some(Executor::with_backend(Arc::new(paper)))
+        }
+        Some("live") => {
+            let wallet: LocalWallet = env::var("HL_PRIVATE_KEY")
+                .map_err(|_| "--execution live needs HL_PRIVATE_KEY")?
+                .parse()?;
+            orders.subscribe(&mut info_client, wallet.address()).await?;
+            pnl.subscribe(&mut info_client, wallet.address()).await?;
+            let since_ms = chrono::Utc::now().timestamp_millis() as u64;
+            let history = InfoClient::new(None, Some(base_url))
+                .await?
+                .with_rate_limiter(limiter.clone());
+            pnl.poll_funding(history, wallet.address(), since_ms, FUNDING_POLL);
+            tracking_pnl = true;
+            let events = InfoClient::with_reconnect(None, Some(base_url)).await?;
+            account.spawn(events, wallet.address()).await?;
+            if let Some(buffer) = min_free_margin {
+                let poller = InfoClient::new(None, Some(base_url))
+                    .await?
+                    .with_rate_limiter(limiter.clone());
+                let mut state = Reconciler::new(MARGIN_POLL, 0.0).spawn(poller, wallet.address());
+                state.wait_for(Option::is_some).await?;
+                margin_guard = Some(MarginGuard::new(buffer, config.exchange.leverage, state));
+            }
+            let exchange = ExchangeClient::new(None, wallet, Some(base_url), None, None).await?;
+            // Each coin's fat-finger limits, checked before an order is signed
+            let checks = coin_configs.iter().fold(
+                PreTradeChecks::new(specs.clone()),
+                |checks, (coin, coin_config)| {
+                    checks.with_limits(coin, coin_config.risk.pre_trade_limits())
+                },
+            );
+            let exchange = exchange.with_pre_trade_checks(checks);
+            Some(Executor::spawn(Arc::new(exchange)).with_rate_limiter(limiter.clone()))
+        }
+        Some(other) => return Err(format!("unknown --execution {other}").into()),
+        None => None,
+    };
+    let executor = match (executor, &event_log) {
+        (Some(executor), Some(log)) => Some(executor.with_event_log(log.clone())),
+        (executor, _) => executor,
+    };
+    // `--dedup-ms N` drops an order identical to the last one sent for its coin
+    // and side less than N ms earlier (quotes re-sent as the book flickers)
+    let dedup = flag("--dedup-ms")
+        .map(|ms| ms.parse().map(Duration::from_millis))
+        .transpose()
+        .map_err(|_| "--dedup-ms needs a number of milliseconds")?;
+    let executor = match (executor, dedup) {
+        (Some(executor), Some(window)) => Some(executor.with_dedup_window(window)),
+        (executor, _) => executor,
+    };
+    // `--batch-ms N` sends the orders and cancels queued together in bulk
+    // requests, a new quote waiting up to N ms (0: no wait) for others to join it
+    let batch = flag("--batch-ms")
+        .map(|ms| ms.parse().map(Duration::from_millis))
+        .transpose()
+        .map_err(|_| "--batch-ms needs a number of milliseconds")?;
+    let executor = match (executor, batch) {
+        (Some(executor), Some(window)) => Some(executor.with_batching(window)),
+        (executor, _) => executor,
+    };
+    let executor = executor.map(|executor| executor.with_latency(latency.clone()));
+    // SIGINT, SIGTERM, the admin `kill` command or a coin's loss limit halting
+    // trips the kill switch: quoting stops, open orders are cancelled and
+    // positions closed before the bot exits
+    let kill_switch = KillSwitch::new();
+    if let Some(executor) = &executor {
+        kill_switch.register(flag("--execution").map_or("", String::as_str), executor);
     }
-}
-// === Quote Construction ===
-#[derive(Debug, Clone)]
-pub struct QuoteProposal {
-    pub side: String, // "Buy" or "Sell"
-    pub price: f64,
-    pub size: f64,
-}
-pub struct QuoteLayerManager;
-impl QuoteLayerManager {
-    pub fn new() -> Self {
-        Self
+    kill_switch.trigger_on_signals();
+    // Every coin's subscriptions feed one channel; the SymbolManager routes each
+    // message to that coin's router, which runs in its own task with its own
+    // signals, strategy, position and limits
+    let (feed_tx, feed_rx) = unbounded_channel();
+    let mut feed = match &chaos {
+        Some(chaos) => chaos.wrap_receiver(feed_rx),
+        None => feed_rx,
+    };
+    // `--rotate SECS` quotes only the best `--max-markets N` (default 3) of the
+    // `--coins`, rescanning them every SECS: each market is scored on its spread
+    // and traded flow, less what quoting it has cost per $1M of volume (see
+    // MarketRotation). Rotated-out markets stop quoting and hedge flat before
+    // their router is removed and their data unsubscribed.
+    let rotate_period = flag("--rotate")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()
+        .map_err(|_| "--rotate needs a number of seconds")?;
+    let max_markets = flag("--max-markets")
+        .map_or(Ok(RotationConfig::default().max_markets), |n| {
+            n.parse().map_err(|_| format!("bad --max-markets {n}"))
+        })?;
+    let rotation = rotate_period.map(|_| {
+        MarketRotation::new(RotationConfig {
+            max_markets,
+            ..Default::default()
+        })
+    });
+    let (scan_tx, mut scan_rx) = unbounded_channel();
+    if let Some(period) = rotate_period {
+        // Scans run off the main loop so the REST calls never hold up routing
+        let scanner = InfoClient::new(None, Some(base_url))
+            .await?
+            .with_rate_limiter(limiter.clone());
+        let candidates = coins.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(period);
+            loop {
+                timer.tick().await;
+                if scan_tx
+                    .send(scan_markets(&scanner, &candidates).await)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
     }
-    pub fn build_quotes(signal: &SignalState) -> Vec<QuoteProposal> {
-        let mut quotes = vec![];
-        // Determine spread in ticks (wider if high volatility)
-        let base_spread = if signal.aggressive_mode {
-            AGGRESSIVE_SPREAD_TICKS
-        } else {
-            2.0
-        };
-        let spread_tick = base_spread * (1.0 + signal.volatility * 0.1).min(3.0);
-        // Adaptive size (smaller in high-volatility)
-        let vol_adj_size = BASE_QUOTE_SIZE * (1.0 / (1.0 + signal.volatility)).clamp(0.5, 2.0);
-        if signal.aggressive_mode {
-            // Quote both sides aggressively
-            quotes.push(QuoteProposal {
-                side: "Buy".into(),
-                price: signal.best_bid + spread_tick,
-                size: vol_adj_size * 1.5,
-            });
 
-            quotes.push(QuoteProposal {
-                side: "Sell".into(),
-                price: signal.best_ask - spread_tick,
-                size: vol_adj_size * 1.5,
-            });
-        } else {
-            // Quote only side suggested by fill_score
-            if signal.fill_score > 0.1 {
-                quotes.push(QuoteProposal {
-                    side: "Buy".into(),
-                    price: signal.best_bid + spread_tick,
-                    size: vol_adj_size,
-                });
-            } else if signal.fill_score < -0.1 {
-                quotes.push(QuoteProposal {
-                    side: "Sell".into(),
-                    price: signal.best_ask - spread_tick,
-                    size: vol_adj_size,
-                });
-            }
+    // `--funding SECS` leans each coin's quotes toward the side earning funding
+    // and cuts positions shortly before paying it (`funding_weight` and
+    // `funding_reduce_ms` in the strategy config). The rate is the predicted
+    // one from the asset context feed, or the last paid, polled every SECS.
+    let funding_poll = flag("--funding")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()
+        .map_err(|_| "--funding needs a number of seconds")?;
+    let funding_info = match funding_poll {
+        Some(_) => Some(Arc::new(
+            InfoClient::new(None, Some(base_url))
+                .await?
+                .with_rate_limiter(limiter.clone()),
+        )),
+        None => None,
+    };
+
+    let mut symbols = SymbolManager::new();
+    let mut subscriptions: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    // Every coin's config, current after reloads, whether it is trading or not
+    let config_updates: BTreeMap<_, _> = coin_configs
+        .iter()
+        .map(|(coin, coin_config)| (coin.clone(), watch::channel(coin_config.clone()).0))
+        .collect();
+    let make_router = |coin: &str| -> Result<MessageRouter, Box<dyn std::error::Error>> {
+        let updates = &config_updates[coin];
+        let coin_config = updates.borrow().clone();
+        let mut strategy = make_strategy(&coin_config.strategy.name)?;
+        strategy.configure(&coin_config.strategy);
+        let mut risk_mgr = coin_config
+            .risk
+            .risk_manager()
+            .with_account_guard(account.clone());
+        if let Some(global) = &global {
+            risk_mgr = risk_mgr.with_exposure(global.slot(strategy.name(), coin));
         }
-        quotes
+        if let Some(throttle) = coin_config.risk.latency_throttle(&latency) {
+            risk_mgr = risk_mgr.with_latency_throttle(throttle);
+        }
+        let risk_mgr = Arc::new(risk_mgr);
+        let mut router = MessageRouter::new(strategy, risk_mgr, coin)
+            .with_signal_windows(coin_config.strategy.signal_windows())
+            .with_config_updates(updates.subscribe())
+            .with_journal(journal.clone())
+            .with_flow_measure(flow)
+            .with_reporter(reporter.clone())
+            .with_fee_model(config.fees.schedule())
+            .with_asset_spec(specs.spec(coin))
+            .with_hedge_tif(coin_config.strategy.tif.hedge)
+            .with_sides(coin_config.strategy.sides)
+            .with_kill_switch(kill_switch.clone());
+        if let Some(log) = &event_log {
+            router = router.with_event_log(log.clone());
+        }
+        if let Some(executor) = &executor {
+            router = router
+                .with_executor(executor.clone())
+                .with_order_manager(orders.clone());
+        }
+        if let Some(metrics) = &metrics {
+            router = router.with_metrics(metrics.clone());
+        }
+        if let Some(chaos) = &chaos {
+            router = router.with_chaos(chaos.clone());
+        }
+        if let Some(rotation) = &rotation {
+            router = router.with_rotation(rotation.clone());
+        }
+        if let (Some(info), Some(period)) = (&funding_info, funding_poll) {
+            let mut funding = FundingMonitor::new(coin);
+            funding.configure(&coin_config.strategy);
+            funding.poll(info.clone(), period);
+            router = router.with_funding(funding);
+        }
+        Ok(router)
+    };
+    let initial = match &rotation {
+        Some(rotation) => {
+            let snapshots = scan_rx.recv().await.unwrap_or_default();
+            rotation.rebalance(&snapshots).added
+        }
+        None => coins.clone(),
+    };
+    // With `--min-free-margin`, coins whose `max_notional` the free margin cannot
+    // take on are left out. Coins admitted together are checked together, as
+    // none of them has used any margin yet.
+    let admit = |coins: Vec<String>| -> Vec<String> {
+        let Some(guard) = &margin_guard else {
+            return coins;
+        };
+        let mut added_notional = 0.0;
+        coins
+            .into_iter()
+            .filter(|coin| {
+                let notional = config_updates[coin]
+                    .borrow()
+                    .risk
+                    .max_notional
+                    .unwrap_or(0.0);
+                match guard.check(added_notional + notional) {
+                    Ok(()) => {
+                        added_notional += notional;
+                        true
+                    }
+                    Err(e) => {
+                        error!("Not adding {coin}: {e}");
+                        if let Some(rotation) = &rotation {
+                            rotation.refuse(coin);
+                        }
+                        false
+                    }
+                }
+            })
+            .collect()
+    };
+    for coin in &admit(initial) {
+        let oracle = config_updates[coin].borrow().strategy.fair_value == FairValue::Oracle;
+        let asset_ctx = oracle || funding_poll.is_some();
+        let ids = subscribe_coin(&mut info_client, coin, asset_ctx, &feed_tx).await?;
+        subscriptions.insert(coin.clone(), ids);
+        symbols.add(coin, make_router(coin)?);
     }
-}
-// === Risk Manager ===
-pub struct RiskManager {
-    pub max_position: f64,
-}
-impl RiskManager {
-    pub fn new(max_position: f64) -> Self {
-        Self { max_position }
+    let mut retire_poll = tokio::time::interval(RETIRE_POLL);
+
+    // `--admin PATH` serves an inspection REPL on a Unix socket (`nc -U PATH`)
+    let (admin_tx, mut admin_rx) = unbounded_channel();
+    if let Some(path) = flag("--admin") {
+        info!("Admin REPL on {path}");
+        tokio::spawn(serve_admin(path.clone(), admin_tx));
     }
-    // Evaluate and (optionally) execute or cancel quotes
-    pub fn evaluate(&self, state: &mut SignalState, quotes: &[QuoteProposal]) {
-        for q in quotes {
-            let mut approved = true;
-            // Simple position limit check:
-            if q.side == "Buy" && state.position.base + q.size > self.max_position {
-                approved = false;
-            }
-            if q.side == "Sell" && state.position.base - q.size < -self.max_position {
-                approved = false;
-            }
 
-            if approved {
-                println!("[Risk] Approved Quote: {:?}", q);
-                // For demonstration, assume fill and update position
-                if q.side == "Buy" {
-                    state.position.base += q.size;
-                    state.position.quote -= q.size * q.price;
-                } else {
-                    state.position.base -= q.size;
-                    state.position.quote += q.size * q.price;
+    // With `--config`, edits to the file (checked every CONFIG_POLL) and the admin
+    // `reload` command re-resolve every coin's settings. Nothing is applied unless
+    // all coins resolve; each coin then swaps in its new signal windows, spreads,
+    // burst cooldown and risk limits between two events, keeping its
+    // subscriptions, orders and position. Settings fixed at startup are reported
+    // and left as they are.
+    let mut watcher = flag("--config").map(ConfigWatcher::new);
+    let mut config_poll = tokio::time::interval(CONFIG_POLL);
+    let mut reload = |loaded: Result<BotConfig, hyperliquid_rust_sdk::Error>| -> String {
+        let next = match loaded.map_err(|e| e.to_string()).and_then(|c| resolve(&c)) {
+            Ok(next) => next,
+            Err(e) => return format!("config reload rejected, settings unchanged: {e}"),
+        };
+        let mut lines = Vec::new();
+        for (coin, current) in coin_configs.iter_mut() {
+            let (mut applied, cold) = current.hot_reload(&next[coin]);
+            if let Some(guard) = &margin_guard {
+                let before = current.risk.max_notional.unwrap_or(0.0);
+                let after = applied.risk.max_notional.unwrap_or(0.0);
+                if let Err(e) = guard.check(after - before) {
+                    lines.push(format!("{coin}: max_notional kept at {before}: {e}"));
+                    applied.risk.max_notional = current.risk.max_notional;
                 }
-            } else {
-                println!("[Risk] Canceled Quote due to position limit: {:?}", q);
+            }
+            if !cold.is_empty() {
+                lines.push(format!("{coin}: restart needed for {}", cold.join(", ")));
+            }
+            if applied != *current {
+                lines.push(format!("{coin}: reloaded"));
+                config_updates[coin].send_replace(applied.clone());
+                *current = applied;
             }
         }
-    }
-}
-// === Router for incoming messages ===
-pub struct MessageRouter {
-    signal: Arc<Mutex<SignalEngine>>,
-    quote_mgr: Arc<QuoteLayerManager>,
-    risk_mgr: Arc<RiskManager>,
-}
-impl MessageRouter {
-    pub fn new(
-        signal: Arc<Mutex<SignalEngine>>,
-        quote_mgr: Arc<QuoteLayerManager>,
-        risk_mgr: Arc<RiskManager>,
-    ) -> Self {
-        Self {
-            signal,
-            quote_mgr,
-            risk_mgr,
+        if let Err(e) = journal.record("config_reload", json!({ "config": coin_configs })) {
+            error!("Journal write failed: {e}");
         }
-    }
-    pub async fn handle(&self, msg: Message) {
-        match msg {
-            Message::L2Book(book) => {
-                let bids = &book.data.levels[0];
-                let asks = &book.data.levels[1];
-                if bids.is_empty() || asks.is_empty() {
-                    return;
+        if lines.is_empty() {
+            lines.push("config unchanged".to_string());
+        }
+        lines.join("\n")
+    };
+    loop {
+        tokio::select! {
+            msg = feed.recv() => match msg {
+                Some(msg) => symbols.route(msg),
+                None => {
+                    info!("Market data feed closed, stopping");
+                    break;
+                }
+            },
+            Some(done) = symbols.join_next() => {
+                if let Err(e) = done {
+                    error!("Coin task failed: {e}");
                 }
-                // Parse top-of-book
-                let bid_px = bids[0].px.parse::<f64>().unwrap_or(0.0);
-                let ask_px = asks[0].px.parse::<f64>().unwrap_or(0.0);
-                let bid_vol: f64 = bids.iter().map(|x| x.sz.parse().unwrap_or(0.0)).sum();
-                let ask_vol: f64 = asks.iter().map(|x| x.sz.parse().unwrap_or(0.0)).sum();
-                // Update signals
-                let mut engine = self.signal.lock().await;
-                engine.process_l2_book(book.data.time, bid_px, ask_px, bid_vol, ask_vol);
-                engine.print();
-                // Build and evaluate quotes
-                let quotes = QuoteLayerManager::build_quotes(&engine.state);
-                self.risk_mgr.evaluate(&mut engine.state, &quotes);
             }
-            Message::Trades(trade_msg) => {
-                let mut engine = self.signal.lock().await;
-                // Update trade-based signals
-                for t in trade_msg.data {
-                    let price = t.px.parse::<f64>().unwrap_or(0.0);
-                    let size = t.sz.parse::<f64>().unwrap_or(0.0);
-                    let is_buy = t.side == "B";
-                    engine.process_trade(price, size, is_buy, t.time);
+            Some(req) = admin_rx.recv() => match req.command.as_str() {
+                "help" => req.reply(
+                    "commands: pos, orders, signals [COIN], ladder [COIN], sides [COIN [both|bid|ask|none]], funding [COIN], ledger, efficiency, pnl, latency, rotation, reload, kill, quit",
+                ),
+                "pnl" if tracking_pnl => req.reply(pnl.summary()),
+                "pnl" => req.reply("account PnL needs --execution live"),
+                "latency" => req.reply(latency.summary()),
+                "rotation" => req.reply(rotation.as_ref().map_or(
+                    "rotation off (see --rotate)".to_string(),
+                    MarketRotation::summary,
+                )),
+                "kill" => {
+                    let text = kill_switch
+                        .trigger("admin kill")
+                        .await
+                        .unwrap_or_else(|| "kill switch already tripped".to_string());
+                    req.reply(text);
+                }
+                "reload" => {
+                    let text = match watcher.as_mut() {
+                        Some(watcher) => reload(watcher.load()),
+                        None => "no --config file to reload".to_string(),
+                    };
+                    info!("{text}");
+                    req.reply(text);
+                }
+                _ => symbols.dispatch_admin(req),
+            },
+            Some(snapshots) = scan_rx.recv() => {
+                let Some(rotation) = &rotation else { continue };
+                for coin in admit(rotation.rebalance(&snapshots).added) {
+                    let oracle =
+                        config_updates[&coin].borrow().strategy.fair_value == FairValue::Oracle;
+                    let asset_ctx = oracle || funding_poll.is_some();
+                    match subscribe_coin(&mut info_client, &coin, asset_ctx, &feed_tx).await {
+                        Ok(ids) => {
+                            subscriptions.insert(coin.clone(), ids);
+                            symbols.add(&coin, make_router(&coin)?);
+                        }
+                        Err(e) => error!("Subscribing to {coin} failed: {e}"),
+                    }
+                }
+            }
+            _ = retire_poll.tick(), if rotation.is_some() => {
+                for coin in rotation.iter().flat_map(MarketRotation::take_retired) {
+                    symbols.remove(&coin);
+                    for id in subscriptions.remove(&coin).unwrap_or_default() {
+                        if let Err(e) = info_client.unsubscribe(id).await {
+                            error!("Unsubscribing from {coin} failed: {e}");
+                        }
+                    }
+                    info!("Stopped quoting {coin}");
+                }
+            }
+            reason = kill_switch.flattened() => {
+                info!("Kill switch ({reason}) flattened the account, stopping");
+                break;
+            }
+            _ = config_poll.tick(), if watcher.is_some() => {
+                if let Some(loaded) = watcher.as_mut().and_then(ConfigWatcher::poll) {
+                    info!("{}", reload(loaded));
                 }
             }
-            _ => {}
         }
     }
-}
-// === Main Execution ===
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    let mut info_client = InfoClient::new(None, Some(BaseUrl::Mainnet)).await?;
-    let (sender, mut receiver) = unbounded_channel();
-    // Subscribe to L2 book and trades for BTC (example)
-    info_client
-        .subscribe(Subscription::L2Book { coin: "BTC".into() }, sender.clone())
-        .await?;
-    info_client
-        .subscribe(Subscription::Trades { coin: "BTC".into() }, sender.clone())
-        .await?;
-    let signal_engine = Arc::new(Mutex::new(SignalEngine::new()));
-    let quote_mgr = Arc::new(QuoteLayerManager::new());
-    let risk_mgr = Arc::new(RiskManager::new(POSITION_LIMIT));
-    let router = MessageRouter::new(signal_engine.clone(), quote_mgr, risk_mgr);
-    // Event loop: route incoming messages
-    while let Some(msg) = receiver.recv().await {
-        router.handle(msg).await;
-    }
     Ok(())
 }
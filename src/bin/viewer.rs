@@ -0,0 +1,115 @@
+/*
+Recorded-session viewer for post-mortems. Replays a market-data recording next to
+the journal of the run that traded it, one book tick at a time, showing the
+depth ladder, the signals the bot computed and the fills and hedges it journaled
+on that tick.
+
+    viewer --file orderbook_log.json --journal journal.jsonl [--coin BTC] [--run RUN_ID]
+
+Commands on stdin:
+    <enter> | n [N]   next frame (or N frames)
+    d                 next frame with a decision
+    t MS              first frame at or after a timestamp
+    r                 back to the start
+    q                 quit
+*/
+use hyperliquid_rust_sdk::{load_recording, read_journal, SessionFrame, SessionViewer};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process,
+};
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: viewer --file PATH --journal PATH [--coin COIN] [--run RUN_ID]");
+    process::exit(2)
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{msg}");
+    process::exit(1)
+}
+
+fn main() {
+    let (mut file, mut journal, mut coin, mut run_id) = (None, None, None, None);
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        match flag.as_str() {
+            "--file" => file = Some(value),
+            "--journal" => journal = Some(value),
+            "--coin" => coin = Some(value.to_uppercase()),
+            "--run" => run_id = Some(value),
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+    let file = file.unwrap_or_else(|| usage("--file is required"));
+    let journal = journal.unwrap_or_else(|| usage("--journal is required"));
+    let recording =
+        load_recording(&file).unwrap_or_else(|e| fail(format!("failed to load {file}: {e}")));
+    let mut records =
+        read_journal(&journal).unwrap_or_else(|e| fail(format!("failed to read {journal}: {e}")));
+    // A journal may hold several runs; default to the last one
+    let run_id = run_id
+        .or_else(|| records.last().map(|r| r.run_id.clone()))
+        .unwrap_or_else(|| fail(format!("{journal} is empty")));
+    records.retain(|r| r.run_id == run_id);
+    let coin = coin
+        .or_else(|| recording.events.first().map(|e| e.coin.clone()))
+        .unwrap_or_else(|| fail(format!("{file} has no events")));
+    let events = recording
+        .events
+        .into_iter()
+        .filter(|e| e.coin == coin)
+        .map(|e| e.event)
+        .collect();
+    let mut viewer = SessionViewer::new(&coin, events, records);
+    println!(
+        "{coin} run {run_id}: {} decisions; enter/n [N], d, t MS, r, q",
+        viewer.decision_count()
+    );
+
+    let show = |viewer: &SessionViewer, frame: Option<SessionFrame>| match frame {
+        Some(frame) => println!("{}\n", viewer.render(&frame)),
+        None => println!("end of session (r to restart)"),
+    };
+    let first = viewer.next_frame();
+    show(&viewer, first);
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let mut words = line.split_whitespace();
+        let frame = match (words.next(), words.next()) {
+            (None | Some("n"), count) => {
+                let count = count.and_then(|c| c.parse().ok()).unwrap_or(1usize);
+                (0..count).map_while(|_| viewer.next_frame()).last()
+            }
+            (Some("d"), _) => viewer.next_decision(),
+            (Some("t"), Some(time)) => match time.parse() {
+                Ok(time) => viewer.seek(time),
+                Err(_) => {
+                    println!("t needs a millisecond timestamp");
+                    continue;
+                }
+            },
+            (Some("r"), _) => {
+                viewer.reset();
+                viewer.next_frame()
+            }
+            (Some("q"), _) => break,
+            (Some(other), _) => {
+                println!("unknown command {other:?}; enter/n [N], d, t MS, r, q");
+                continue;
+            }
+        };
+        show(&viewer, frame);
+    }
+}
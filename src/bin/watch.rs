@@ -0,0 +1,239 @@
+/*
+Watchlist alerting. Monitors coins over websocket subscriptions and raises alerts
+through the notifier; never trades.
+
+    watch --coin BTC --above 70000 --below 60000 --max-spread-bps 5 \
+          --max-funding 0.0005 --volume-spike 5 --coin ETH --below 3000 [--network mainnet|testnet]
+
+Flags after each --coin apply to that coin. Volume spikes compare the notional
+traded in the current minute against an EWMA of previous minutes.
+Set HL_ALERT_WEBHOOK to also post alerts to a webhook.
+*/
+use hyperliquid_rust_sdk::{
+    Alert, AlertLevel, AssetCtx, BaseUrl, InfoClient, Message, Notifier, Subscription,
+};
+use log::warn;
+use std::{collections::HashMap, env, process};
+use tokio::sync::mpsc::unbounded_channel;
+
+const VOLUME_BUCKET_MS: u64 = 60_000;
+const VOLUME_EWMA_ALPHA: f64 = 0.2;
+const VOLUME_WARMUP_BUCKETS: u32 = 5;
+
+#[derive(Debug, Default)]
+struct WatchRule {
+    above: Option<f64>,
+    below: Option<f64>,
+    max_spread_bps: Option<f64>,
+    max_funding: Option<f64>,  // Absolute hourly funding rate
+    volume_spike: Option<f64>, // Multiple of the baseline minute volume
+}
+
+#[derive(Debug, Default)]
+struct VolumeTracker {
+    bucket_start: u64,
+    bucket_notional: f64,
+    baseline: f64,
+    buckets_seen: u32,
+    alerted_bucket: u64,
+}
+
+impl VolumeTracker {
+    // Returns (current bucket notional, baseline) once the baseline has warmed up
+    fn add(&mut self, time: u64, notional: f64) -> Option<(f64, f64)> {
+        let bucket = time - time % VOLUME_BUCKET_MS;
+        if bucket != self.bucket_start {
+            if self.bucket_start != 0 {
+                self.baseline = if self.buckets_seen == 0 {
+                    self.bucket_notional
+                } else {
+                    VOLUME_EWMA_ALPHA * self.bucket_notional
+                        + (1.0 - VOLUME_EWMA_ALPHA) * self.baseline
+                };
+                self.buckets_seen += 1;
+            }
+            self.bucket_start = bucket;
+            self.bucket_notional = 0.0;
+        }
+        self.bucket_notional += notional;
+        (self.buckets_seen >= VOLUME_WARMUP_BUCKETS && self.baseline > 0.0)
+            .then_some((self.bucket_notional, self.baseline))
+    }
+}
+
+fn usage(err: &str) -> ! {
+    eprintln!("error: {err}");
+    eprintln!("usage: watch --coin COIN [--above PX] [--below PX] [--max-spread-bps BPS] [--max-funding RATE] [--volume-spike MULT] [--coin ...] [--network mainnet|testnet]");
+    process::exit(2)
+}
+
+fn parse_args() -> (HashMap<String, WatchRule>, BaseUrl) {
+    let mut rules: HashMap<String, WatchRule> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut base_url = BaseUrl::Mainnet;
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("missing value for {flag}")));
+        if flag == "--coin" {
+            rules.entry(value.clone()).or_default();
+            current = Some(value);
+            continue;
+        }
+        if flag == "--network" {
+            base_url = match value.as_str() {
+                "mainnet" => BaseUrl::Mainnet,
+                "testnet" => BaseUrl::Testnet,
+                other => usage(&format!("unknown network {other}")),
+            };
+            continue;
+        }
+        let coin = current
+            .as_ref()
+            .unwrap_or_else(|| usage(&format!("{flag} must follow --coin")));
+        let rule = rules.entry(coin.clone()).or_default();
+        let value = Some(
+            value
+                .parse::<f64>()
+                .unwrap_or_else(|_| usage(&format!("{flag} must be a number"))),
+        );
+        match flag.as_str() {
+            "--above" => rule.above = value,
+            "--below" => rule.below = value,
+            "--max-spread-bps" => rule.max_spread_bps = value,
+            "--max-funding" => rule.max_funding = value,
+            "--volume-spike" => rule.volume_spike = value,
+            other => usage(&format!("unknown flag {other}")),
+        }
+    }
+    if rules.is_empty() {
+        usage("at least one --coin is required");
+    }
+    (rules, base_url)
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let (rules, base_url) = parse_args();
+    let notifier = Notifier::from_env();
+    let mut info_client = InfoClient::with_reconnect(None, Some(base_url))
+        .await
+        .unwrap_or_else(|e| usage(&format!("could not create info client: {e}")));
+
+    let (sender, mut receiver) = unbounded_channel();
+    for (coin, rule) in &rules {
+        let mut subscriptions = Vec::new();
+        if rule.above.is_some() || rule.below.is_some() || rule.max_spread_bps.is_some() {
+            subscriptions.push(Subscription::L2Book { coin: coin.clone() });
+        }
+        if rule.max_funding.is_some() {
+            subscriptions.push(Subscription::ActiveAssetCtx { coin: coin.clone() });
+        }
+        if rule.volume_spike.is_some() {
+            subscriptions.push(Subscription::Trades { coin: coin.clone() });
+        }
+        for subscription in subscriptions {
+            if let Err(e) = info_client.subscribe(subscription, sender.clone()).await {
+                usage(&format!("subscription for {coin} failed: {e}"));
+            }
+        }
+        println!("watching {coin}: {rule:?}");
+    }
+
+    let mut volume: HashMap<String, VolumeTracker> = HashMap::new();
+    while let Some(message) = receiver.recv().await {
+        let mut alerts = Vec::new();
+        match message {
+            Message::L2Book(book) => {
+                let Some(rule) = rules.get(&book.data.coin) else {
+                    continue;
+                };
+                let levels = &book.data.levels;
+                let (Some(bid), Some(ask)) = (
+                    levels.first().and_then(|l| l.first()),
+                    levels.get(1).and_then(|l| l.first()),
+                ) else {
+                    continue;
+                };
+                let (bid, ask) = match (bid.px.parse::<f64>(), ask.px.parse::<f64>()) {
+                    (Ok(bid), Ok(ask)) => (bid, ask),
+                    _ => continue,
+                };
+                let coin = &book.data.coin;
+                let mid = (bid + ask) / 2.0;
+                let spread_bps = (ask - bid) / mid * 10_000.0;
+                if let Some(above) = rule.above.filter(|above| mid > *above) {
+                    alerts.push(Alert::new(
+                        AlertLevel::Warning,
+                        format!("{coin}:above"),
+                        format!("{coin} mid {mid} is above {above}"),
+                    ));
+                }
+                if let Some(below) = rule.below.filter(|below| mid < *below) {
+                    alerts.push(Alert::new(
+                        AlertLevel::Warning,
+                        format!("{coin}:below"),
+                        format!("{coin} mid {mid} is below {below}"),
+                    ));
+                }
+                if let Some(max) = rule.max_spread_bps.filter(|max| spread_bps > *max) {
+                    alerts.push(Alert::new(
+                        AlertLevel::Warning,
+                        format!("{coin}:spread"),
+                        format!("{coin} spread {spread_bps:.2} bps exceeds {max} bps"),
+                    ));
+                }
+            }
+            Message::ActiveAssetCtx(ctx) => {
+                let coin = &ctx.data.coin;
+                let (Some(max), AssetCtx::Perps(perps)) =
+                    (rules.get(coin).and_then(|r| r.max_funding), &ctx.data.ctx)
+                else {
+                    continue;
+                };
+                let funding: f64 = perps.funding.parse().unwrap_or(0.0);
+                if funding.abs() > max {
+                    alerts.push(Alert::new(
+                        AlertLevel::Warning,
+                        format!("{coin}:funding"),
+                        format!("{coin} funding {funding:.6}/hr exceeds {max}"),
+                    ));
+                }
+            }
+            Message::Trades(trades) => {
+                for trade in trades.data {
+                    let Some(mult) = rules.get(&trade.coin).and_then(|r| r.volume_spike) else {
+                        continue;
+                    };
+                    let notional = trade.px.parse::<f64>().unwrap_or(0.0)
+                        * trade.sz.parse::<f64>().unwrap_or(0.0);
+                    let tracker = volume.entry(trade.coin.clone()).or_default();
+                    if let Some((current, baseline)) = tracker.add(trade.time, notional) {
+                        if current > mult * baseline
+                            && tracker.alerted_bucket != tracker.bucket_start
+                        {
+                            tracker.alerted_bucket = tracker.bucket_start;
+                            alerts.push(Alert::new(
+                                AlertLevel::Warning,
+                                format!("{}:volume", trade.coin),
+                                format!(
+                                    "{} volume spike: {current:.0} this minute vs {baseline:.0} baseline",
+                                    trade.coin
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+            Message::HyperliquidError(e) => warn!("websocket error: {e}"),
+            _ => {}
+        }
+        for alert in alerts {
+            if let Err(e) = notifier.notify(alert).await {
+                warn!("alert delivery failed: {e}");
+            }
+        }
+    }
+}
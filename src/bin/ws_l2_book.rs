@@ -1,109 +1,194 @@
-use ethers::signers::LocalWallet;
+use ethers::signers::{LocalWallet, Signer};
 use hyperliquid_rust_sdk::{
-    BaseUrl, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient, ExchangeDataStatus,
-    ExchangeResponseStatus, InfoClient, Message, Subscription,
-};
-use log::info;
-use std::{
-    collections::VecDeque,
-    io::{self, Write},
-    thread::sleep,
-    time::Duration,
+    AssetSpecs, BaseUrl, ExchangeClient, Executor, FeeModel, InfoClient, KillSwitch, Metrics,
+    Notifier, OrderManager, RateLimiter, Reconciler, ReportLayout, StatusReporter, StrategyRunner,
+    Subscription, TradeGovernor, TrendScalper,
 };
+use log::{info, warn};
+use std::{fs::File, sync::Arc, thread::sleep, time::Duration};
 use tokio::sync::mpsc::unbounded_channel;
 
-#[derive(Debug, Clone)]
-struct BookSample {
-    timestamp_ms: u64,
-    mid_price: f64,
-    best_bid: f64,
-    best_ask: f64,
-    bid_volume: f64,
-    ask_volume: f64,
-}
-
-#[derive(Debug, Clone)]
-struct TradeState {
-    position: Option<(String, f64, u64, f64)>, // (direction, entry price, entry time, extreme price)
-    realized_pnl: f64,
-    cooldown_until_ms: Option<u64>,
+const METRICS_ADDR: &str = "127.0.0.1:9185"; // Cooldown state for tuning
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(5); // How often user_state is polled
+const RECONCILE_THRESHOLD: f64 = 0.0005; // BTC; larger gaps to the exchange position are alerted
+const FEED_STALE: Duration = Duration::from_secs(10); // Silence after which the feed is reconnected
+const MAX_BOOK_AGE_MS: u64 = 500; // Books older than this by the local clock pull the quotes
+const REDRAW: Duration = Duration::from_millis(250); // How often the `--tui` dashboard is redrawn
+
+// The `--tui` dashboard, fed from the runner between redraws
+#[cfg(feature = "tui")]
+struct Screen {
+    dashboard: hyperliquid_rust_sdk::Dashboard,
+    state: hyperliquid_rust_sdk::DashboardState,
+    fills: tokio::sync::broadcast::Receiver<hyperliquid_rust_sdk::OrderFill>,
+    reporter: StatusReporter,
 }
-
-fn linear_regression_slope(data: &[f64]) -> f64 {
-    let n = data.len() as f64;
-    let sum_x: f64 = (0..data.len()).map(|x| x as f64).sum();
-    let sum_y: f64 = data.iter().sum();
-    let sum_xy: f64 = data.iter().enumerate().map(|(x, y)| x as f64 * y).sum();
-    let sum_x2: f64 = (0..data.len()).map(|x| (x as f64).powi(2)).sum();
-
-    let numerator = n * sum_xy - sum_x * sum_y;
-    let denominator = n * sum_x2 - sum_x.powi(2);
-    if denominator.abs() < 1e-8 {
-        0.0
-    } else {
-        numerator / denominator
+#[cfg(feature = "tui")]
+impl Screen {
+    fn open(
+        orders: &OrderManager,
+        reporter: StatusReporter,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            dashboard: hyperliquid_rust_sdk::Dashboard::start(),
+            state: hyperliquid_rust_sdk::DashboardState::new("BTC"),
+            fills: orders.subscribe_fills(),
+            reporter,
+        })
     }
-}
-
-fn price_volatility(prices: &[f64]) -> f64 {
-    let mean = prices.iter().copied().sum::<f64>() / prices.len() as f64;
-    let variance = prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / prices.len() as f64;
-    variance.sqrt()
-}
 
-fn compute_qty(price: f64, usd_margin: f64, leverage: f64) -> f64 {
-    let notional = usd_margin * leverage;
-    (notional / price * 1000.0).round() / 1000.0
-}
-
-async fn send_order(
-    exchange_client: &ExchangeClient,
-    asset: &str,
-    is_buy: bool,
-    px: f64, // Limit price
-    qty: f64,
-    reduce_only: bool,
-    wallet: &LocalWallet,
-) {
-    let order = ClientOrderRequest {
-        asset: asset.to_string(),
-        is_buy,
-        reduce_only,
-        limit_px: px, // Use limit price here
-        sz: qty,
-        cloid: None,
-        order_type: ClientOrder::Limit(ClientLimit {
-            tif: "Gtc".to_string(),
-        }), // Change this to Limit for maker orders
-    };
-
-    let response = exchange_client.order(order, Some(wallet)).await.unwrap();
-    info!("Order placed: {response:?}");
-
-    match response {
-        ExchangeResponseStatus::Ok(exchange_response) => {
-            let status = exchange_response.data.unwrap().statuses[0].clone();
-            match status {
-                ExchangeDataStatus::Filled(order) => info!("Order filled: {order:?}"),
-                ExchangeDataStatus::Resting(order) => info!("Order resting: {order:?}"),
-                _ => panic!("Unexpected status: {status:?}"),
+    // Redraws and acts on the keys pressed since the last redraw: `p` pauses
+    // or resumes quoting, `f` trips the kill switch. True once `q` is pressed.
+    async fn refresh(
+        &mut self,
+        runner: &mut StrategyRunner,
+        kill_switch: &KillSwitch,
+    ) -> std::io::Result<bool> {
+        use hyperliquid_rust_sdk::DashboardAction;
+        while let Ok(fill) = self.fills.try_recv() {
+            self.state.on_fill(&fill);
+        }
+        self.state.on_book(runner.book(), runner.signals());
+        self.state.orders = runner.orders().open_orders();
+        self.state.position = runner.position();
+        self.state.paused = runner.is_paused();
+        self.state.status = self
+            .reporter
+            .lines()
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        self.state.sample_pnl();
+        self.dashboard.draw(&self.state)?;
+        match self.dashboard.next_action()? {
+            Some(DashboardAction::TogglePause) => {
+                runner.set_paused(!runner.is_paused());
+                if runner.is_paused() {
+                    let cancelled = runner.cancel_open_orders().await;
+                    info!("Quoting paused from the dashboard, {cancelled} orders cancelled");
+                }
+            }
+            Some(DashboardAction::Flatten) => {
+                kill_switch.trigger("dashboard flatten").await;
             }
+            Some(DashboardAction::Quit) => return Ok(true),
+            None => {}
         }
-        ExchangeResponseStatus::Err(e) => panic!("Order error: {e}"),
+        Ok(false)
+    }
+}
+#[cfg(not(feature = "tui"))]
+struct Screen;
+#[cfg(not(feature = "tui"))]
+impl Screen {
+    fn open(
+        _orders: &OrderManager,
+        _reporter: StatusReporter,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Err("--tui needs a build with `--features tui`".into())
+    }
+
+    async fn refresh(
+        &mut self,
+        _runner: &mut StrategyRunner,
+        _kill_switch: &KillSwitch,
+    ) -> std::io::Result<bool> {
+        Ok(false)
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    let mut info_client = InfoClient::new(None, Some(BaseUrl::Mainnet)).await.unwrap();
+    // `--tui` replaces the status line with a dashboard (tui feature) and sends
+    // the log to `--log PATH` (default ws_l2_book.log), which would otherwise
+    // scroll over it
+    let args: Vec<String> = std::env::args().collect();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    let tui = args.iter().any(|a| a == "--tui");
+    let mut logger = env_logger::Builder::from_default_env();
+    if tui {
+        let path = flag("--log").map_or("ws_l2_book.log", String::as_str);
+        logger.target(env_logger::Target::Pipe(Box::new(File::create(path)?)));
+    }
+    logger.init();
+    // A dropped or silent feed is reconnected and the book resynced; the
+    // runner pulls its orders for the gap
+    let mut info_client = InfoClient::with_reconnect(None, Some(BaseUrl::Mainnet))
+        .await
+        .unwrap()
+        .with_stale_timeout(FEED_STALE);
     let (sender, mut receiver) = unbounded_channel();
 
     let wallet: LocalWallet = "".parse().unwrap();
-    let exchange_client =
-        ExchangeClient::new(None, wallet.clone(), Some(BaseUrl::Mainnet), None, None)
+    let exchange = ExchangeClient::new(None, wallet, Some(BaseUrl::Mainnet), None, None)
+        .await
+        .unwrap();
+    let user = exchange.wallet.address();
+    let orders = OrderManager::new();
+    orders.subscribe(&mut info_client, user).await.unwrap();
+    // The exchange's request budget, shared by the reconciler's polling and
+    // the executor; new quotes wait while it is low so cancels get through
+    let limiter = RateLimiter::default();
+    let reconciler = Reconciler::new(RECONCILE_INTERVAL, RECONCILE_THRESHOLD);
+    let mut account = reconciler.spawn(
+        InfoClient::new(None, Some(BaseUrl::Mainnet))
             .await
-            .unwrap();
+            .unwrap()
+            .with_rate_limiter(limiter.clone()),
+        user,
+    );
+    let notifier = Notifier::from_env();
+    let metrics = Metrics::new();
+    let server = metrics.clone();
+    tokio::spawn(async move {
+        if let Err(e) = server.serve(METRICS_ADDR).await {
+            warn!("metrics server stopped: {e}");
+        }
+    });
+
+    // The scalper's entries and exits go through the executor; its fills come
+    // back from the order manager's subscription
+    // `--confirm-ticks K` holds entries until their condition has lasted K books
+    let confirm_ticks = match flag("--confirm-ticks") {
+        Some(k) => k
+            .parse()
+            .map_err(|_| "--confirm-ticks needs a number of book updates")?,
+        None => 1,
+    };
+    let spec = AssetSpecs::load(&info_client).await?.spec("BTC");
+    let reporter = match tui {
+        true => StatusReporter::new(REDRAW, ReportLayout::Captured),
+        false => StatusReporter::default(),
+    };
+    let mut screen = tui
+        .then(|| Screen::open(&orders, reporter.clone()))
+        .transpose()?;
+    // Fewer trades while the edge per round trip, after a maker entry and a
+    // taker exit at the base fee tier, is thin
+    let fees = FeeModel::default();
+    let governor = TradeGovernor::default().with_cost_bps(fees.maker_bps + fees.taker_bps);
+    let scalper = TrendScalper::new()
+        .with_reporter(reporter)
+        .with_asset_spec(spec)
+        .with_metrics(metrics)
+        .with_entry_confirmation(confirm_ticks)
+        .with_governor(governor);
+    // SIGINT or SIGTERM cancels our orders and closes the position before exiting
+    let executor = Executor::spawn(Arc::new(exchange)).with_rate_limiter(limiter);
+    let kill_switch = KillSwitch::new();
+    kill_switch.register("scalper", &executor);
+    kill_switch.trigger_on_signals();
+    // A book lagging the local clock by more than MAX_BOOK_AGE_MS cancels the
+    // scalper's orders and holds new ones until the feed catches up
+    let mut runner = StrategyRunner::new(Box::new(scalper), "BTC")
+        .with_asset_spec(spec)
+        .with_executor(executor.clone())
+        .with_order_manager(orders)
+        .with_max_book_age(MAX_BOOK_AGE_MS);
 
     let subscription_id = info_client
         .subscribe(
@@ -115,243 +200,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .unwrap();
 
-    let mut book_buffer: VecDeque<BookSample> = VecDeque::with_capacity(240);
-    let mut trade_state = TradeState {
-        position: None,
-        realized_pnl: 0.0,
-        cooldown_until_ms: None,
-    };
-    let mut last_direction: Option<String> = None;
-    let mut last_direction_change: u64 = 0;
-
-    while let Some(Message::L2Book(l2_book)) = receiver.recv().await {
-        let now_ms = l2_book.data.time;
-        let bids = &l2_book.data.levels[0];
-        let asks = &l2_book.data.levels[1];
-        if bids.is_empty() || asks.is_empty() {
-            continue;
-        }
-
-        let best_bid = bids[0].px.parse::<f64>().unwrap();
-        let best_ask = asks[0].px.parse::<f64>().unwrap();
-        let mid_price = (best_bid + best_ask) / 2.0;
-        let spread = best_ask - best_bid;
-        let bid_volume: f64 = bids.iter().map(|b| b.sz.parse::<f64>().unwrap()).sum();
-        let ask_volume: f64 = asks.iter().map(|a| a.sz.parse::<f64>().unwrap()).sum();
-        let imbalance = (bid_volume - ask_volume) / (bid_volume + ask_volume);
-
-        book_buffer.push_back(BookSample {
-            timestamp_ms: now_ms,
-            mid_price,
-            best_bid,
-            best_ask,
-            bid_volume,
-            ask_volume,
-        });
-
-        if book_buffer.len() > 40 {
-            book_buffer.pop_front();
-        }
-
-        if book_buffer.len() >= 10 {
-            let recent_prices: Vec<f64> = book_buffer.iter().map(|b| b.mid_price).collect();
-            let slope = linear_regression_slope(&recent_prices);
-            let volatility = price_volatility(&recent_prices);
-
-            let exit_duration_threshold = (3000.0 + 10000.0 * volatility.min(0.01)) as u64;
-            let exit_threshold_pct = if spread < 0.5 {
-                0.001
-            } else if volatility > 5.0 {
-                0.004
-            } else {
-                0.0025
-            };
-
-            let trend_direction = if slope > 0.005 {
-                "long"
-            } else if slope < -0.005 {
-                "short"
-            } else {
-                "neutral"
-            };
-            let volume_direction = if imbalance > 0.2 {
-                "long"
-            } else if imbalance < -0.2 {
-                "short"
-            } else {
-                "neutral"
-            };
-
-            let mut direction = if trend_direction == volume_direction {
-                trend_direction
-            } else if trend_direction != "neutral" {
-                trend_direction
-            } else {
-                volume_direction
-            };
-
-            // Close long or short positions based on conditions
-            if let Some((pos_dir, entry_price, entry_time, _)) = &mut trade_state.position {
-                let duration = now_ms - *entry_time;
-
-                match pos_dir.as_str() {
-                    "long" => {
-                        // Profit tracking for long position
-                        let profit = mid_price - *entry_price;
-                        if profit > 0.05 {
-                            // Lock profits if a certain percentage is reached
-                            let exit_price = best_bid;
-                            trade_state.realized_pnl += profit;
-                            send_order(
-                                &exchange_client,
-                                "BTC",
-                                true,       // Close long position (limit order)
-                                exit_price, // Limit price is set here
-                                compute_qty(mid_price, 11.0, 20.0),
-                                true, // Reduce only
-                                &wallet,
-                            )
-                            .await;
-                            trade_state.position = None;
-                            trade_state.cooldown_until_ms = Some(now_ms + 10_000);
-                        }
-
-                        // Trend reversal check for long position
-                        if slope < -0.005 {
-                            // A negative slope indicates the market might reverse
-                            let new_qty = compute_qty(mid_price, 11.0, 20.0);
-                            let price = best_bid - 1.00;
-                            send_order(
-                                &exchange_client,
-                                "BTC",
-                                false,         // Short position
-                                price.floor(), // Slightly above the best bid for the short limit order
-                                new_qty,
-                                false, // Do not reduce only
-                                &wallet,
-                            )
-                            .await;
-                            trade_state.position =
-                                Some(("short".to_string(), best_bid, now_ms, best_bid));
-                        }
-                    }
-                    "short" => {
-                        // Profit tracking for short position
-                        let profit = *entry_price - mid_price;
-                        if profit > 0.05 {
-                            // Lock profits if a certain percentage is reached
-                            let exit_price = best_ask;
-                            trade_state.realized_pnl += profit;
-                            send_order(
-                                &exchange_client,
-                                "BTC",
-                                false,              // Close short position
-                                exit_price.floor(), // Limit price is set here
-                                compute_qty(mid_price, 11.0, 20.0),
-                                true, // Reduce only
-                                &wallet,
-                            )
-                            .await;
-                            trade_state.position = None;
-                            trade_state.cooldown_until_ms = Some(now_ms + 10_000);
-                        }
-
-                        // Trend reversal check for short position
-                        if slope > 0.005 {
-                            // A positive slope indicates the market might reverse
-                            let new_qty = compute_qty(mid_price, 11.0, 20.0);
-                            let price = best_bid + 1.00;
-
-                            send_order(
-                                &exchange_client,
-                                "BTC",
-                                true,          // Long position
-                                price.floor(), // Slightly below the best ask for the long limit order
-                                new_qty,
-                                false, // Do not reduce only
-                                &wallet,
-                            )
-                            .await;
-                            trade_state.position =
-                                Some(("long".to_string(), best_ask, now_ms, best_ask));
-                        }
-                    }
-                    _ => {}
+    let mut redraw = tokio::time::interval(REDRAW);
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.recv() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = redraw.tick(), if screen.is_some() => {
+                let Some(screen) = screen.as_mut() else { continue };
+                if screen.refresh(&mut runner, &kill_switch).await? {
+                    break;
                 }
+                continue;
             }
-
-            // If no position is open, attempt to enter based on current conditions
-            let can_enter = trade_state
-                .cooldown_until_ms
-                .map_or(true, |until| now_ms >= until);
-
-            if trade_state.position.is_none() && can_enter {
-                let confidence = slope.abs() > 0.004 && volatility < 20.0;
-                if confidence {
-                    let qty = compute_qty(mid_price, 11.0, 20.0);
-                    fn adjust_price_for_tick_size(price: f64, tick_size: f64) -> f64 {
-                        let precision = (1.0 / tick_size).round() as u64; // Calculate the precision multiplier
-                        (price * precision as f64).round() / precision as f64
-                    }
-
-                    let tick_size = 0.01; // Assuming the tick size is 0.01
-                    if direction == "long" && spread < 5.0 {
-                        let taker_price = best_ask - 1.00; // Limit price just below best ask for long order
-                        let adjusted_price = adjust_price_for_tick_size(taker_price, tick_size);
-                        info!("LONG IT adjusted_price: {adjusted_price:?}, qty: {qty:?}");
-
-                        send_order(
-                            &exchange_client,
-                            "BTC",
-                            true,
-                            adjusted_price.floor(), // Limit price
-                            qty,
-                            false,
-                            &wallet,
-                        )
-                        .await;
-
-                        trade_state.position =
-                            Some(("long".to_string(), best_ask, now_ms, best_ask));
-                    } else if direction == "short" && spread < 5.0 {
-                        let taker_price = best_bid + 1.00; // Limit price just above best bid for short order
-                        let adjusted_price = adjust_price_for_tick_size(taker_price, tick_size);
-                        info!("SHORT IT adjusted_price: {adjusted_price:?}, qty: {qty:?}");
-
-                        send_order(
-                            &exchange_client,
-                            "BTC",
-                            false,
-                            adjusted_price.floor(), // Limit price
-                            qty,
-                            false,
-                            &wallet,
-                        )
-                        .await;
-
-                        trade_state.position =
-                            Some(("short".to_string(), best_bid, now_ms, best_bid));
+            reason = kill_switch.flattened() => {
+                info!("Kill switch ({reason}) flattened the account, stopping");
+                break;
+            }
+        };
+        // The exchange's position wins over what we inferred from our orders
+        if account.has_changed().unwrap_or(false) {
+            let snapshot = account.borrow_and_update().clone();
+            if let Some(snapshot) = snapshot {
+                if let Some(divergence) = reconciler.check("BTC", runner.position(), &snapshot) {
+                    if let Err(e) = notifier.notify(divergence.alert()).await {
+                        warn!("alert delivery failed: {e}");
                     }
                 }
+                let exchange = snapshot.position("BTC");
+                runner.on_position(exchange.size, exchange.entry_px);
             }
-
-            // Print out the current market information and position state
-            let pos_string = match &trade_state.position {
-                Some((dir, price, _, _)) => format!("{} @ {:.2}", dir.to_uppercase(), price),
-                None => "NONE".to_string(),
-            };
-
-            print!(
-                "\r[{}] Mid: {:.2} | Spread: {:.4} | Slope: {:.5} | Pos: {} | Total PnL: {:.4}",
-                chrono::Utc::now().format("%H:%M:%S%.3f"),
-                mid_price,
-                spread,
-                slope,
-                pos_string,
-                trade_state.realized_pnl
-            );
-            io::stdout().flush().unwrap();
         }
+        runner.handle(&msg).await;
     }
 
     tokio::spawn(async move {
@@ -0,0 +1,204 @@
+use log::warn;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+    time::{sleep, Instant},
+};
+
+use crate::{ExchangeResponseStatus, Message};
+
+// Fault probabilities are per message (websocket) or per request (exchange).
+// The default injects nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosConfig {
+    pub disconnect_prob: f64,
+    pub outage_ms: u64, // Messages are dropped for this long after an injected disconnect
+    pub delay_prob: f64,
+    pub max_delay_ms: u64,
+    pub duplicate_prob: f64,
+    pub exchange_error_prob: f64,
+    pub seed: Option<u64>,
+}
+
+impl ChaosConfig {
+    // Frequent enough to exercise recovery paths within minutes of paper trading
+    pub fn moderate() -> ChaosConfig {
+        ChaosConfig {
+            disconnect_prob: 0.002,
+            outage_ms: 2_000,
+            delay_prob: 0.02,
+            max_delay_ms: 1_500,
+            duplicate_prob: 0.01,
+            exchange_error_prob: 0.05,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ChaosStats {
+    pub disconnects: AtomicU64,
+    pub dropped: AtomicU64,
+    pub delayed: AtomicU64,
+    pub duplicated: AtomicU64,
+    pub exchange_errors: AtomicU64,
+}
+
+impl ChaosStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "disconnects {} | dropped {} | delayed {} | duplicated {} | exchange errors {}",
+            self.disconnects.load(Ordering::Relaxed),
+            self.dropped.load(Ordering::Relaxed),
+            self.delayed.load(Ordering::Relaxed),
+            self.duplicated.load(Ordering::Relaxed),
+            self.exchange_errors.load(Ordering::Relaxed)
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Chaos {
+    pub config: ChaosConfig,
+    pub stats: ChaosStats,
+    rng: Mutex<StdRng>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Arc<Chaos> {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Arc::new(Chaos {
+            config,
+            stats: ChaosStats::default(),
+            rng: Mutex::new(rng),
+        })
+    }
+
+    fn roll(&self, prob: f64) -> bool {
+        if prob <= 0.0 {
+            return false;
+        }
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        rng.gen_bool(prob.min(1.0))
+    }
+
+    fn delay(&self) -> Duration {
+        let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+        Duration::from_millis(rng.gen_range(0..=self.config.max_delay_ms))
+    }
+
+    // Interposes between a subscription channel and its consumer. An injected
+    // disconnect forwards `Message::NoData`, as the real WsManager does, then drops
+    // everything until the outage ends. Delayed messages arrive out of order.
+    pub fn wrap_receiver(
+        self: &Arc<Self>,
+        mut receiver: UnboundedReceiver<Message>,
+    ) -> UnboundedReceiver<Message> {
+        let (sender, wrapped) = unbounded_channel();
+        let chaos = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut outage_until: Option<Instant> = None;
+            while let Some(message) = receiver.recv().await {
+                if outage_until.is_some_and(|until| Instant::now() < until) {
+                    chaos.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                if chaos.roll(chaos.config.disconnect_prob) {
+                    warn!("[Chaos] injected websocket disconnect");
+                    chaos.stats.disconnects.fetch_add(1, Ordering::Relaxed);
+                    outage_until =
+                        Some(Instant::now() + Duration::from_millis(chaos.config.outage_ms));
+                    if sender.send(Message::NoData).is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                if chaos.roll(chaos.config.duplicate_prob) {
+                    chaos.stats.duplicated.fetch_add(1, Ordering::Relaxed);
+                    if sender.send(message.clone()).is_err() {
+                        break;
+                    }
+                }
+                if chaos.roll(chaos.config.delay_prob) {
+                    chaos.stats.delayed.fetch_add(1, Ordering::Relaxed);
+                    let delay = chaos.delay();
+                    let sender = sender.clone();
+                    tokio::spawn(async move {
+                        sleep(delay).await;
+                        let _ = sender.send(message);
+                    });
+                    continue;
+                }
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        wrapped
+    }
+
+    // Call before each (real or simulated) exchange request; Some means the request
+    // should be treated as rejected with this response instead.
+    pub fn exchange_fault(&self) -> Option<ExchangeResponseStatus> {
+        if !self.roll(self.config.exchange_error_prob) {
+            return None;
+        }
+        self.stats.exchange_errors.fetch_add(1, Ordering::Relaxed);
+        warn!("[Chaos] injected exchange error");
+        Some(ExchangeResponseStatus::Err(
+            "chaos: injected exchange error".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_duplicates_and_disconnects() {
+        let chaos = Chaos::new(ChaosConfig {
+            duplicate_prob: 1.0,
+            seed: Some(1),
+            ..ChaosConfig::default()
+        });
+        let (sender, receiver) = unbounded_channel();
+        let mut wrapped = chaos.wrap_receiver(receiver);
+        sender.send(Message::Pong).unwrap();
+        drop(sender);
+        assert!(matches!(wrapped.recv().await, Some(Message::Pong)));
+        assert!(matches!(wrapped.recv().await, Some(Message::Pong)));
+        assert!(wrapped.recv().await.is_none());
+
+        let chaos = Chaos::new(ChaosConfig {
+            disconnect_prob: 1.0,
+            outage_ms: 60_000,
+            seed: Some(1),
+            ..ChaosConfig::default()
+        });
+        let (sender, receiver) = unbounded_channel();
+        let mut wrapped = chaos.wrap_receiver(receiver);
+        sender.send(Message::Pong).unwrap();
+        sender.send(Message::Pong).unwrap();
+        drop(sender);
+        assert!(matches!(wrapped.recv().await, Some(Message::NoData)));
+        assert!(wrapped.recv().await.is_none());
+        assert_eq!(chaos.stats.dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_default_injects_nothing() {
+        let chaos = Chaos::new(ChaosConfig::default());
+        assert!((0..1000).all(|_| chaos.exchange_fault().is_none()));
+    }
+}
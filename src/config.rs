@@ -0,0 +1,669 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use toml::{Table, Value};
+
+use crate::{
+    funding::{FUNDING_REDUCE_MS, FUNDING_WEIGHT},
+    latency::{THROTTLE_MAX_GAP_MS, THROTTLE_WIDEN_TICKS},
+    prelude::*,
+    quoting::{AGGRESSIVE_SPREAD_TICKS, SPREAD_TICKS},
+    risk::SKIP_ENTRY_MS,
+    signals::{
+        BURST_COOLDOWN_MS, DEEP_SIZE, DEVIATION_THRESHOLD, ENTRY_CONFIRM_TICKS, TIGHT_SPREAD,
+        TRADE_WINDOW, TWAP_WINDOW, WIDE_SPREAD,
+    },
+    soft_start::{RAMP_MAX_GAP_MS, RAMP_START},
+    streak::{MAX_WIDEN_STEPS, WIDEN_SIZE_CUT, WIDEN_STEP_TICKS},
+    BaseUrl, Error, FairValue, FeeModel, LatencyThrottle, LatencyTracker, LossAction, LossLimits,
+    OrderRole, PreTradeLimits, QuoteSides, RebateTier, RegimeThresholds, RiskManager,
+    SignalWindows, SoftStart, SpreadRegime, StreakWidener, TimeInForce, VolumeTier,
+};
+
+// `HL_CFG_RISK_POSITION_LIMIT=3` sets `risk.position_limit`, and
+// `HL_CFG_COINS_BTC_RISK_POSITION_LIMIT=1` the BTC override of it
+const ENV_PREFIX: &str = "HL_CFG_";
+const SECTIONS: [&str; 3] = ["strategy", "risk", "exchange"];
+// Account-wide sections, which coins cannot override
+const ACCOUNT_SECTIONS: [&str; 1] = ["fees"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub name: String, // registered strategy
+    pub twap_window: usize,
+    pub trade_window: usize,
+    pub deviation_threshold: f64, // TWAP deviation that reads as a breakout
+    pub spread_ticks: f64,
+    pub aggressive_spread_ticks: f64,
+    pub burst_cooldown_ms: u64, // calm period before quoting resumes after a burst
+    pub risk_aversion: Option<f64>, // inventory skew of the layered quotes
+    pub entry_confirm_ticks: usize, // book updates an entry signal must hold before firing
+    pub fair_value: FairValue,  // reference price for signals, quotes and PnL marks
+    pub tight_spread: f64,      // spreads at or under this (price units) are tight
+    pub wide_spread: f64,       // spreads at or over this are wide
+    pub deep_size: f64,         // size a tight book shows on both sides to count as deep
+    pub regimes: Vec<SpreadRegime>, // regimes the strategy trades in; empty keeps its default
+    pub sides: QuoteSides,      // sides quoted; the admin `sides` command overrides it
+    pub funding_weight: f64,    // lean toward the side earning funding, with `--funding`
+    pub funding_reduce_ms: u64, // how long before paying funding the position is cut; 0 never
+    pub tif: TifConfig,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            name: "layered".to_string(),
+            twap_window: TWAP_WINDOW,
+            trade_window: TRADE_WINDOW,
+            deviation_threshold: DEVIATION_THRESHOLD,
+            spread_ticks: SPREAD_TICKS,
+            aggressive_spread_ticks: AGGRESSIVE_SPREAD_TICKS,
+            burst_cooldown_ms: BURST_COOLDOWN_MS,
+            risk_aversion: None,
+            entry_confirm_ticks: ENTRY_CONFIRM_TICKS,
+            fair_value: FairValue::Mid,
+            tight_spread: TIGHT_SPREAD,
+            wide_spread: WIDE_SPREAD,
+            deep_size: DEEP_SIZE,
+            regimes: Vec::new(),
+            sides: QuoteSides::Both,
+            funding_weight: FUNDING_WEIGHT,
+            funding_reduce_ms: FUNDING_REDUCE_MS,
+            tif: TifConfig::default(),
+        }
+    }
+}
+
+impl StrategyConfig {
+    pub fn signal_windows(&self) -> SignalWindows {
+        SignalWindows {
+            twap: self.twap_window,
+            trades: self.trade_window,
+            deviation_threshold: self.deviation_threshold,
+            burst_cooldown_ms: self.burst_cooldown_ms,
+            fair_value: self.fair_value,
+            regime: RegimeThresholds {
+                tight_spread: self.tight_spread,
+                wide_spread: self.wide_spread,
+                deep_size: self.deep_size,
+            },
+        }
+    }
+}
+
+// Time in force of each order role, e.g. `[strategy.tif] quote = "alo"`, or
+// `entry = { gtd = 5000 }` for entries cancelled after 5s. Taker entries a
+// strategy chooses to send as IOC stay IOC.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TifConfig {
+    pub quote: TimeInForce,
+    pub entry: TimeInForce,
+    pub exit: TimeInForce,
+    pub hedge: TimeInForce,
+}
+
+impl Default for TifConfig {
+    fn default() -> Self {
+        Self {
+            quote: TimeInForce::Gtc,
+            entry: TimeInForce::Gtc,
+            exit: TimeInForce::Gtc,
+            hedge: TimeInForce::Ioc,
+        }
+    }
+}
+
+impl TifConfig {
+    pub fn for_role(&self, role: OrderRole) -> TimeInForce {
+        match role {
+            OrderRole::Quote => self.quote,
+            OrderRole::Entry => self.entry,
+            OrderRole::Exit => self.exit,
+            OrderRole::Hedge => self.hedge,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskConfig {
+    pub position_limit: f64, // base units
+    pub max_notional: Option<f64>,
+    pub max_equity_pct: Option<f64>,
+    pub equity: Option<f64>,
+    pub max_net_notional: Option<f64>,    // all coins combined
+    pub max_coin_margin_pct: Option<f64>, // percent of equity one coin's margin may take
+    pub min_free_margin: Option<f64>,     // USD withdrawable that size increases must leave
+    pub max_trade_loss: Option<f64>,      // USD lost on one round trip
+    pub trade_loss_action: LossAction,
+    pub max_hourly_loss: Option<f64>, // USD lost over the rolling hour
+    pub hourly_loss_action: LossAction,
+    pub max_daily_loss: Option<f64>, // USD since UTC midnight, open position marked at the mid
+    pub daily_loss_action: LossAction,
+    pub max_drawdown: Option<f64>, // USD below the session's equity high
+    pub drawdown_action: LossAction,
+    pub skip_entry_ms: u64,    // how long `skip_entry` refuses new inventory
+    pub flatten_on_halt: bool, // trip the kill switch when a loss limit halts
+    pub widen_after_losses: Option<usize>, // losing round trips in a row per widening step
+    pub widen_step_ticks: f64,
+    pub widen_size_cut: f64, // share of size removed per step
+    pub max_widen_steps: u32,
+    pub ramp_ms: Option<u64>, // soft start: time to reach full size after startup or a risk pause
+    pub ramp_start: f64,      // share of full size a ramp starts from
+    pub ramp_max_gap_ms: u64, // time between quote rounds when a ramp starts
+    pub latency_spike_ms: Option<f64>, // p95 order/cancel round trip that starts throttling quotes
+    pub latency_widen_ticks: f64, // extra distance from the touch at full throttle
+    pub latency_max_gap_ms: u64, // time between quote rounds at full throttle
+    pub max_order_size: Option<f64>, // base units, one order
+    pub max_order_notional: Option<f64>, // USD, one order
+    pub price_collar_pct: Option<f64>, // percent from the mid an order may be priced
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            position_limit: 5.0,
+            max_notional: None,
+            max_equity_pct: None,
+            equity: None,
+            max_net_notional: None,
+            max_coin_margin_pct: None,
+            min_free_margin: None,
+            max_trade_loss: None,
+            trade_loss_action: LossAction::SkipEntry,
+            max_hourly_loss: None,
+            hourly_loss_action: LossAction::Halt,
+            max_daily_loss: None,
+            daily_loss_action: LossAction::Halt,
+            max_drawdown: None,
+            drawdown_action: LossAction::Halt,
+            skip_entry_ms: SKIP_ENTRY_MS,
+            flatten_on_halt: true,
+            widen_after_losses: None,
+            widen_step_ticks: WIDEN_STEP_TICKS,
+            widen_size_cut: WIDEN_SIZE_CUT,
+            max_widen_steps: MAX_WIDEN_STEPS,
+            ramp_ms: None,
+            ramp_start: RAMP_START,
+            ramp_max_gap_ms: RAMP_MAX_GAP_MS,
+            latency_spike_ms: None,
+            latency_widen_ticks: THROTTLE_WIDEN_TICKS,
+            latency_max_gap_ms: THROTTLE_MAX_GAP_MS,
+            max_order_size: None,
+            max_order_notional: None,
+            price_collar_pct: None,
+        }
+    }
+}
+
+impl RiskConfig {
+    // Everything but the shared net limit, which needs the bot's GlobalExposure
+    pub fn risk_manager(&self) -> RiskManager {
+        let mut risk_mgr = RiskManager::new(self.position_limit).with_loss_limits(LossLimits {
+            max_trade_loss: self.max_trade_loss,
+            trade_action: self.trade_loss_action,
+            max_hourly_loss: self.max_hourly_loss,
+            hourly_action: self.hourly_loss_action,
+            max_daily_loss: self.max_daily_loss,
+            daily_action: self.daily_loss_action,
+            max_drawdown: self.max_drawdown,
+            drawdown_action: self.drawdown_action,
+            skip_entry_ms: self.skip_entry_ms,
+            flatten_on_halt: self.flatten_on_halt,
+        });
+        if let Some(losses) = self.widen_after_losses {
+            let widener = StreakWidener::new(losses)
+                .with_step(self.widen_step_ticks, self.widen_size_cut)
+                .with_max_steps(self.max_widen_steps);
+            risk_mgr = risk_mgr.with_streak_widener(widener);
+        }
+        if let Some(ramp_ms) = self.ramp_ms {
+            let ramp = SoftStart::new(ramp_ms).with_start(self.ramp_start, self.ramp_max_gap_ms);
+            risk_mgr = risk_mgr.with_soft_start(ramp);
+        }
+        if let Some(usd) = self.max_notional {
+            risk_mgr = risk_mgr.with_max_notional(usd);
+        }
+        if let Some(pct) = self.max_equity_pct {
+            risk_mgr = risk_mgr.with_max_equity_pct(pct);
+        }
+        if let Some(usd) = self.equity {
+            risk_mgr.set_equity(usd);
+        }
+        risk_mgr
+    }
+
+    // The latency throttle, fed by the round trips of the bot's executor
+    pub fn latency_throttle(&self, latency: &LatencyTracker) -> Option<LatencyThrottle> {
+        self.latency_spike_ms.map(|spike_ms| {
+            LatencyThrottle::new(latency.clone(), spike_ms)
+                .with_limits(self.latency_widen_ticks, self.latency_max_gap_ms)
+        })
+    }
+
+    // Fat-finger limits for the exchange client's pre-trade checks
+    pub fn pre_trade_limits(&self) -> PreTradeLimits {
+        PreTradeLimits {
+            max_order_size: self.max_order_size,
+            max_order_notional: self.max_order_notional,
+            price_collar: self.price_collar_pct.map(|pct| pct / 100.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExchangeConfig {
+    pub network: String, // mainnet, testnet or localhost
+    pub leverage: f64,
+    pub balance: f64, // USD margin sized into each quote
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            network: "mainnet".to_string(),
+            leverage: 20.0,
+            balance: 5.5,
+        }
+    }
+}
+
+impl ExchangeConfig {
+    pub fn base_url(&self) -> Result<BaseUrl> {
+        match self.network.as_str() {
+            "mainnet" => Ok(BaseUrl::Mainnet),
+            "testnet" => Ok(BaseUrl::Testnet),
+            "localhost" => Ok(BaseUrl::Localhost),
+            other => Err(Error::Config(format!("unknown network {other}"))),
+        }
+    }
+}
+
+// The simulated account's fees, for the paper exchange and backtests. The
+// volume picks a tier and the maker share a rebate; both default to the
+// exchange's published schedule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeConfig {
+    pub volume: f64,      // 14-day volume, USD
+    pub maker_share: f64, // of the exchange's 14-day maker volume
+    pub builder_bps: f64, // charged on every fill
+    pub tiers: Vec<VolumeTier>,
+    pub rebates: Vec<RebateTier>,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            volume: 0.0,
+            maker_share: 0.0,
+            builder_bps: 0.0,
+            tiers: crate::fees::default_volume_tiers(),
+            rebates: crate::fees::default_rebate_tiers(),
+        }
+    }
+}
+
+impl FeeConfig {
+    pub fn schedule(&self) -> FeeModel {
+        FeeModel::for_account(&self.tiers, &self.rebates, self.volume, self.maker_share)
+            .with_builder_bps(self.builder_bps)
+    }
+}
+
+// One coin's settings after its overrides are applied
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoinConfig {
+    pub strategy: StrategyConfig,
+    pub risk: RiskConfig,
+    pub exchange: ExchangeConfig,
+}
+
+impl CoinConfig {
+    // What a running coin takes from a reloaded config: `next` except for the
+    // settings fixed at startup (the strategy itself, the account-wide limits
+    // and the exchange connection), which keep their current values. Changes to
+    // those are named in the second half so they can be reported.
+    pub fn hot_reload(&self, next: &CoinConfig) -> (CoinConfig, Vec<&'static str>) {
+        let mut applied = next.clone();
+        let mut cold = Vec::new();
+        if next.strategy.name != self.strategy.name {
+            cold.push("strategy.name");
+            applied.strategy.name = self.strategy.name.clone();
+        }
+        if next.risk.max_net_notional != self.risk.max_net_notional {
+            cold.push("risk.max_net_notional");
+            applied.risk.max_net_notional = self.risk.max_net_notional;
+        }
+        if next.risk.max_coin_margin_pct != self.risk.max_coin_margin_pct {
+            cold.push("risk.max_coin_margin_pct");
+            applied.risk.max_coin_margin_pct = self.risk.max_coin_margin_pct;
+        }
+        if next.risk.min_free_margin != self.risk.min_free_margin {
+            cold.push("risk.min_free_margin");
+            applied.risk.min_free_margin = self.risk.min_free_margin;
+        }
+        let throttle = |risk: &RiskConfig| {
+            (
+                risk.latency_spike_ms,
+                risk.latency_widen_ticks,
+                risk.latency_max_gap_ms,
+            )
+        };
+        if throttle(&next.risk) != throttle(&self.risk) {
+            cold.push("risk.latency_*");
+            applied.risk.latency_spike_ms = self.risk.latency_spike_ms;
+            applied.risk.latency_widen_ticks = self.risk.latency_widen_ticks;
+            applied.risk.latency_max_gap_ms = self.risk.latency_max_gap_ms;
+        }
+        if next.exchange != self.exchange {
+            cold.push("exchange");
+            applied.exchange = self.exchange.clone();
+        }
+        (applied, cold)
+    }
+}
+
+// Notices edits to a config file by its modification time, for bots that
+// reload their settings while running
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let modified = Self::modified(&path);
+        Self { path, modified }
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Reads the file again, whether or not it changed
+    pub fn load(&mut self) -> Result<BotConfig> {
+        self.modified = Self::modified(&self.path);
+        BotConfig::load(Some(&self.path))
+    }
+
+    // The reloaded config if the file changed since it was last read. A file
+    // caught mid-save fails to parse and is read again on its next change.
+    pub fn poll(&mut self) -> Option<Result<BotConfig>> {
+        let modified = Self::modified(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        Some(self.load())
+    }
+}
+
+// The bot's settings, loaded from TOML:
+//
+//     [strategy]
+//     name = "layered"
+//     [risk]
+//     position_limit = 5.0
+//     [coins.ETH.risk]
+//     position_limit = 50.0
+//
+// Missing values keep their defaults. `coins.<COIN>` tables override any of
+// the sections but `fees`, which is the account's, for that coin only.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+    pub strategy: StrategyConfig,
+    pub risk: RiskConfig,
+    pub exchange: ExchangeConfig,
+    pub fees: FeeConfig,
+    pub coins: BTreeMap<String, Table>,
+}
+
+// Environment values are read as TOML values, so numbers and booleans keep
+// their type; anything else is a string
+fn env_value(raw: &str) -> Value {
+    format!("v = {raw}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+fn table<'a>(parent: &'a mut Table, key: &str) -> Result<&'a mut Table> {
+    parent
+        .entry(key)
+        .or_insert_with(|| Value::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| Error::Config(format!("{key} is not a table")))
+}
+
+fn apply_env_override(config: &mut Table, key: &str, raw: &str) -> Result<()> {
+    let bad = || Error::Config(format!("cannot apply {ENV_PREFIX}{key}"));
+    let key = key.to_lowercase();
+    let (target, rest, per_coin) = match key.strip_prefix("coins_") {
+        Some(rest) => {
+            let (coin, rest) = rest.split_once('_').ok_or_else(bad)?;
+            let coins = table(config, "coins")?;
+            (table(coins, &coin.to_uppercase())?, rest, true)
+        }
+        None => (config, key.as_str(), false),
+    };
+    let (section, field) = rest.split_once('_').ok_or_else(bad)?;
+    let account = !per_coin && ACCOUNT_SECTIONS.contains(&section);
+    if !SECTIONS.contains(&section) && !account {
+        return Err(bad());
+    }
+    table(target, section)?.insert(field.to_string(), env_value(raw));
+    Ok(())
+}
+
+fn merge<T: Serialize + DeserializeOwned>(base: &T, overrides: Option<&Value>) -> Result<T> {
+    let err = |e: String| Error::Config(e);
+    let mut merged = Table::try_from(base).map_err(|e| err(e.to_string()))?;
+    if let Some(overrides) = overrides {
+        let overrides = overrides
+            .as_table()
+            .ok_or_else(|| err("coin overrides must be tables".to_string()))?;
+        merged.extend(overrides.clone());
+    }
+    Value::Table(merged)
+        .try_into()
+        .map_err(|e: toml::de::Error| err(e.to_string()))
+}
+
+impl BotConfig {
+    // Defaults, then the file if given, then `HL_CFG_*` environment variables
+    pub fn load(path: Option<impl AsRef<Path>>) -> Result<Self> {
+        let text = match path {
+            Some(path) => {
+                let path = path.as_ref();
+                fs::read_to_string(path)
+                    .map_err(|e| Error::Config(format!("{}: {e}", path.display())))?
+            }
+            None => String::new(),
+        };
+        Self::parse(&text, std::env::vars())
+    }
+
+    pub fn parse(text: &str, env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut raw: Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| Error::Config(e.to_string()))?;
+        for (key, value) in env {
+            if let Some(key) = key.strip_prefix(ENV_PREFIX) {
+                apply_env_override(&mut raw, key, &value)?;
+            }
+        }
+        let config: Self = Value::Table(raw)
+            .try_into()
+            .map_err(|e: toml::de::Error| Error::Config(e.to_string()))?;
+        // Surface typos in coin overrides now rather than when the coin starts
+        for coin in config.coins.keys() {
+            config.for_coin(coin)?;
+        }
+        Ok(config)
+    }
+
+    pub fn for_coin(&self, coin: &str) -> Result<CoinConfig> {
+        let overrides = self.coins.get(coin);
+        if let Some(section) = overrides
+            .into_iter()
+            .flat_map(|o| o.keys())
+            .find(|k| !SECTIONS.contains(&k.as_str()))
+        {
+            return Err(Error::Config(format!(
+                "unknown section coins.{coin}.{section}"
+            )));
+        }
+        let section = |name: &str| overrides.and_then(|o| o.get(name));
+        Ok(CoinConfig {
+            strategy: merge(&self.strategy, section("strategy"))?,
+            risk: merge(&self.risk, section("risk"))?,
+            exchange: merge(&self.exchange, section("exchange"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_coin_and_env_overrides() {
+        let defaults = BotConfig::parse("", []).unwrap().for_coin("BTC").unwrap();
+        assert_eq!(defaults.strategy.twap_window, TWAP_WINDOW);
+        assert_eq!(defaults.risk.position_limit, 5.0);
+        assert!(matches!(defaults.exchange.base_url(), Ok(BaseUrl::Mainnet)));
+
+        let text = r#"
+            [risk]
+            position_limit = 2.0
+            max_notional = 10000
+            max_hourly_loss = 50.0
+            hourly_loss_action = "skip_entry"
+
+            [coins.ETH.risk]
+            position_limit = 30.0
+            [coins.ETH.strategy]
+            twap_window = 60
+            sides = "ask"
+        "#;
+        let env = [
+            ("HL_CFG_EXCHANGE_NETWORK", "testnet"),
+            ("HL_CFG_COINS_ETH_EXCHANGE_BALANCE", "8.5"),
+            ("HL_PRIVATE_KEY", "ignored"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let config = BotConfig::parse(text, env).unwrap();
+        let btc = config.for_coin("BTC").unwrap();
+        assert_eq!(
+            (btc.risk.position_limit, btc.risk.max_notional),
+            (2.0, Some(10_000.0))
+        );
+        assert!(matches!(btc.exchange.base_url(), Ok(BaseUrl::Testnet)));
+        let limits = btc.risk.risk_manager().loss_limits;
+        assert_eq!(
+            (limits.max_hourly_loss, limits.hourly_action),
+            (Some(50.0), LossAction::SkipEntry)
+        );
+        assert_eq!(btc.exchange.balance, 5.5);
+        let eth = config.for_coin("ETH").unwrap();
+        assert_eq!(
+            (eth.risk.position_limit, eth.risk.max_notional),
+            (30.0, Some(10_000.0))
+        );
+        assert_eq!((eth.strategy.twap_window, eth.exchange.balance), (60, 8.5));
+        // ETH only sells down what it holds
+        assert_eq!(btc.strategy.sides, QuoteSides::Both);
+        assert!(!eth.strategy.sides.allows("Buy") && eth.strategy.sides.allows("Sell"));
+
+        // Time in force per order role, with GTD given in ms
+        let text = "[strategy.tif]\nquote = \"alo\"\nentry = { gtd = 5000 }";
+        let tif = BotConfig::parse(text, []).unwrap().strategy.tif;
+        assert_eq!(tif.for_role(OrderRole::Quote), TimeInForce::Alo);
+        assert_eq!(tif.for_role(OrderRole::Entry), TimeInForce::Gtd(5_000));
+        assert_eq!(tif.for_role(OrderRole::Hedge), TimeInForce::Ioc);
+
+        // Misspelled fields are errors, not silently ignored
+        assert!(BotConfig::parse("[risk]\nposition_limt = 1.0", []).is_err());
+        assert!(BotConfig::parse("[coins.BTC.risk]\nposition_limt = 1.0", []).is_err());
+        let env = [("HL_CFG_RISK_LIMIT".to_string(), "1".to_string())];
+        assert!(BotConfig::parse("", env).is_err());
+
+        // Fees are the account's: set at the top level, never per coin
+        let env = [("HL_CFG_FEES_BUILDER_BPS".to_string(), "0.5".to_string())];
+        let fees = BotConfig::parse("[fees]\nvolume = 30e6", env).unwrap().fees;
+        assert_eq!(
+            fees.schedule(),
+            FeeModel {
+                taker_bps: 3.5,
+                maker_bps: 0.8,
+                builder_bps: 0.5
+            }
+        );
+        assert!(BotConfig::parse("[coins.BTC.fees]\nvolume = 1.0", []).is_err());
+        let env = [("HL_CFG_COINS_BTC_FEES_VOLUME".to_string(), "1".to_string())];
+        assert!(BotConfig::parse("", env).is_err());
+    }
+
+    #[test]
+    fn test_reload_applies_live_settings_and_reports_startup_ones() {
+        let path = std::env::temp_dir().join(format!("bot-{}.toml", uuid::Uuid::new_v4()));
+        fs::write(&path, "[risk]\nposition_limit = 2.0").unwrap();
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(watcher.poll().is_none());
+        let running = watcher.load().unwrap().for_coin("BTC").unwrap();
+
+        let text = r#"
+            [strategy]
+            name = "other"
+            spread_ticks = 3.0
+            burst_cooldown_ms = 10000
+            [risk]
+            position_limit = 1.0
+            [exchange]
+            network = "testnet"
+        "#;
+        fs::write(&path, text).unwrap();
+        // Some filesystems keep whole-second modification times
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later))
+            .unwrap();
+        let next = watcher.poll().unwrap().unwrap().for_coin("BTC").unwrap();
+        assert!(watcher.poll().is_none());
+        let (applied, cold) = running.hot_reload(&next);
+        assert_eq!(cold, vec!["strategy.name", "exchange"]);
+        assert_eq!(applied.strategy.name, "layered");
+        assert_eq!(applied.strategy.signal_windows().burst_cooldown_ms, 10_000);
+        assert_eq!(
+            (applied.strategy.spread_ticks, applied.risk.position_limit),
+            (3.0, 1.0)
+        );
+        assert_eq!(applied.exchange, running.exchange);
+
+        // A broken edit is an error for the caller to report, not a panic
+        fs::write(&path, "[risk\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later + std::time::Duration::from_secs(5)))
+            .unwrap();
+        assert!(matches!(watcher.poll(), Some(Err(Error::Config(_)))));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,3 +3,4 @@ pub static TESTNET_API_URL: &str = "https://api.hyperliquid-testnet.xyz";
 pub static LOCAL_API_URL: &str = "http://localhost:3001";
 pub const EPSILON: f64 = 1e-9;
 pub(crate) const INF_BPS: u16 = 10_001;
+pub(crate) const CANCEL_BATCH_SIZE: usize = 50;
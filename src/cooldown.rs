@@ -0,0 +1,119 @@
+use crate::Metrics;
+
+pub(crate) const COOLDOWN_LOSS_GROWTH: f64 = 2.0; // Multiplier applied after each losing exit
+pub(crate) const COOLDOWN_WIN_DECAY: f64 = 0.75; // Multiplier applied after a clean profitable exit
+pub(crate) const COOLDOWN_MIN_RATIO: f64 = 0.25; // Floor, as a fraction of the base cooldown
+pub(crate) const COOLDOWN_MAX_RATIO: f64 = 8.0; // Ceiling, as a multiple of the base cooldown
+
+// Re-entry pause after an exit that adapts to realized outcomes: every losing
+// exit doubles it (up to the ceiling), every profitable one shrinks it toward
+// the floor. A win also resets the losing streak.
+#[derive(Debug, Clone)]
+pub struct AdaptiveCooldown {
+    base_ms: u64,
+    current_ms: u64,
+    losing_streak: u32,
+    exits: u64,
+    until_ms: Option<u64>,
+}
+
+impl AdaptiveCooldown {
+    pub fn new(base_ms: u64) -> Self {
+        Self {
+            base_ms,
+            current_ms: base_ms,
+            losing_streak: 0,
+            exits: 0,
+            until_ms: None,
+        }
+    }
+
+    fn bounds(&self) -> (f64, f64) {
+        let base = self.base_ms as f64;
+        (base * COOLDOWN_MIN_RATIO, base * COOLDOWN_MAX_RATIO)
+    }
+
+    // Record an exit at `now_ms` with its realized pnl and start the next cooldown
+    pub fn on_exit(&mut self, now_ms: u64, pnl: f64) {
+        let (min, max) = self.bounds();
+        let factor = if pnl > 0.0 {
+            self.losing_streak = 0;
+            COOLDOWN_WIN_DECAY
+        } else {
+            self.losing_streak += 1;
+            COOLDOWN_LOSS_GROWTH
+        };
+        self.current_ms = (self.current_ms as f64 * factor).clamp(min, max) as u64;
+        self.exits += 1;
+        self.until_ms = Some(now_ms + self.current_ms);
+    }
+
+    // Holds off re-entry for `factor` times the current cooldown from `now_ms`,
+    // e.g. while a TradeGovernor wants fewer trades
+    pub fn stretch(&mut self, now_ms: u64, factor: f64) {
+        self.until_ms = Some(now_ms + (self.current_ms as f64 * factor.max(1.0)) as u64);
+    }
+
+    pub fn ready(&self, now_ms: u64) -> bool {
+        self.until_ms.is_none_or(|until| now_ms >= until)
+    }
+
+    pub fn remaining_ms(&self, now_ms: u64) -> u64 {
+        self.until_ms
+            .map_or(0, |until| until.saturating_sub(now_ms))
+    }
+
+    pub fn current_ms(&self) -> u64 {
+        self.current_ms
+    }
+
+    pub fn losing_streak(&self) -> u32 {
+        self.losing_streak
+    }
+
+    pub fn export(&self, metrics: &Metrics, now_ms: u64) {
+        metrics.set("cooldown_ms", self.current_ms as f64);
+        metrics.set("cooldown_remaining_ms", self.remaining_ms(now_ms) as f64);
+        metrics.set("cooldown_losing_streak", self.losing_streak as f64);
+        metrics.set("cooldown_exits_total", self.exits as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_adapts_to_outcomes() {
+        let mut cooldown = AdaptiveCooldown::new(10_000);
+        assert!(cooldown.ready(0));
+
+        cooldown.on_exit(0, -1.0);
+        cooldown.on_exit(0, -1.0);
+        assert_eq!(cooldown.current_ms(), 40_000);
+        assert_eq!(cooldown.losing_streak(), 2);
+        assert!(!cooldown.ready(39_999));
+        assert!(cooldown.ready(40_000));
+
+        // Capped at the ceiling however long the streak runs
+        for _ in 0..10 {
+            cooldown.on_exit(0, -1.0);
+        }
+        assert_eq!(cooldown.current_ms(), 80_000);
+
+        cooldown.on_exit(100_000, 0.5);
+        assert_eq!(cooldown.losing_streak(), 0);
+        assert_eq!(cooldown.current_ms(), 60_000);
+        assert_eq!(cooldown.remaining_ms(110_000), 50_000);
+
+        for _ in 0..20 {
+            cooldown.on_exit(0, 0.5);
+        }
+        assert_eq!(cooldown.current_ms(), 2_500);
+
+        let metrics = Metrics::new();
+        cooldown.export(&metrics, 0);
+        assert_eq!(metrics.gauge("cooldown_ms"), Some(2_500.0));
+        assert_eq!(metrics.gauge("cooldown_losing_streak"), Some(0.0));
+    }
+}
@@ -0,0 +1,277 @@
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Sparkline, Table},
+    DefaultTerminal, Frame,
+};
+use std::{collections::VecDeque, io, time::Duration};
+
+use crate::{BookSide, OrderBook, OrderFill, SignalState, TrackedOrder};
+
+pub(crate) const DASHBOARD_DEPTH: usize = 5; // Book levels shown per side
+pub(crate) const DASHBOARD_FILLS: usize = 10; // Most recent fills kept
+pub(crate) const DASHBOARD_PNL_POINTS: usize = 240; // PnL samples in the sparkline
+
+// What a key press on the dashboard asks the bot to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DashboardAction {
+    TogglePause, // `p`: stop quoting and cancel resting orders, or resume
+    Flatten,     // `f`: trip the kill switch
+    Quit,        // `q` or Esc
+}
+
+// Everything the dashboard shows, updated by the bot's loop between draws.
+// PnL is marked at the mid from the fills seen since the dashboard started.
+#[derive(Debug, Clone, Default)]
+pub struct DashboardState {
+    pub coin: String,
+    pub bids: Vec<(f64, f64)>, // best first
+    pub asks: Vec<(f64, f64)>,
+    pub signals: Vec<(&'static str, String)>,
+    pub orders: Vec<TrackedOrder>,
+    pub position: f64,
+    pub fills: VecDeque<OrderFill>, // newest first
+    pub pnl: VecDeque<f64>,
+    pub status: String, // the strategy's own status line
+    pub paused: bool,
+    cash: f64,
+    mid: f64,
+}
+
+impl DashboardState {
+    pub fn new(coin: &str) -> Self {
+        Self {
+            coin: coin.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn on_book(&mut self, book: &OrderBook, state: &SignalState) {
+        self.bids = book.levels(BookSide::Bid).take(DASHBOARD_DEPTH).collect();
+        self.asks = book.levels(BookSide::Ask).take(DASHBOARD_DEPTH).collect();
+        if let Some((bid, ask, _, _)) = book.top() {
+            self.mid = (bid + ask) / 2.0;
+        }
+        self.signals = vec![
+            ("fair value", format!("{:.2}", state.fair_value)),
+            ("fill score", format!("{:+.3}", state.fill_score)),
+            ("trend", format!("{:+.3}", state.trend_score)),
+            ("imbalance", format!("{:+.3}", state.imbalance)),
+            ("vpin", format!("{:.3}", state.vpin)),
+            ("volatility", format!("{:.4}", state.volatility)),
+            ("regime", format!("{:?}", state.spread_regime)),
+            ("burst pause", state.quoting_paused.to_string()),
+        ];
+    }
+
+    pub fn on_fill(&mut self, fill: &OrderFill) {
+        let signed = if fill.is_buy { fill.sz } else { -fill.sz };
+        self.position += signed;
+        self.cash -= signed * fill.px;
+        self.fills.push_front(fill.clone());
+        self.fills.truncate(DASHBOARD_FILLS);
+    }
+
+    // Adds a point to the PnL curve, at the last mid
+    pub fn sample_pnl(&mut self) {
+        self.pnl.push_back(self.cash + self.position * self.mid);
+        if self.pnl.len() > DASHBOARD_PNL_POINTS {
+            self.pnl.pop_front();
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+        let [book, signals] = Layout::vertical([
+            Constraint::Length(DASHBOARD_DEPTH as u16 * 2 + 3),
+            Constraint::Min(0),
+        ])
+        .areas(left);
+        let [orders, pnl, fills] = Layout::vertical([
+            Constraint::Percentage(30),
+            Constraint::Length(6),
+            Constraint::Min(0),
+        ])
+        .areas(right);
+
+        let pnl_now = self.pnl.back().copied().unwrap_or(0.0);
+        let state = if self.paused { "PAUSED" } else { "quoting" };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} | {state} | position {:.4} | PnL {pnl_now:+.2}",
+                self.coin, self.position
+            )),
+            header,
+        );
+        self.render_book(frame, book);
+        let rows = self
+            .signals
+            .iter()
+            .map(|(name, value)| Row::new([name.to_string(), value.clone()]));
+        frame.render_widget(
+            Table::new(rows, [Constraint::Length(12), Constraint::Min(0)])
+                .block(Block::bordered().title("Signals")),
+            signals,
+        );
+        let rows = self.orders.iter().map(|o| {
+            Row::new([
+                if o.is_buy { "buy" } else { "sell" }.to_string(),
+                format!("{:.4}", o.remaining()),
+                format!("{:.2}", o.limit_px),
+                format!("{:?}", o.status),
+            ])
+        });
+        frame.render_widget(
+            Table::new(rows, [Constraint::Ratio(1, 4); 4])
+                .block(Block::bordered().title("Open orders")),
+            orders,
+        );
+        // Sparklines take unsigned points, so the curve is shifted to its low
+        let low = self.pnl.iter().copied().fold(f64::INFINITY, f64::min);
+        let points: Vec<u64> = self
+            .pnl
+            .iter()
+            .map(|p| ((p - low) * 100.0).round() as u64)
+            .collect();
+        frame.render_widget(
+            Sparkline::default()
+                .data(&points)
+                .style(Style::default().fg(Color::Cyan))
+                .block(Block::bordered().title("PnL")),
+            pnl,
+        );
+        let rows = self.fills.iter().map(|f| {
+            Row::new([
+                f.time_ms.to_string(),
+                if f.is_buy { "buy" } else { "sell" }.to_string(),
+                format!("{:.4}", f.sz),
+                format!("{:.2}", f.px),
+            ])
+        });
+        frame.render_widget(
+            Table::new(rows, [Constraint::Ratio(1, 4); 4])
+                .block(Block::bordered().title("Recent fills")),
+            fills,
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(format!(
+                "p pause/resume | f flatten | q quit | {}",
+                self.status
+            ))),
+            footer,
+        );
+    }
+
+    // Asks above bids, best prices meeting in the middle
+    fn render_book(&self, frame: &mut Frame, area: Rect) {
+        let row = |(px, sz): &(f64, f64), color| {
+            Row::new([
+                Cell::from(format!("{px:.2}")),
+                Cell::from(format!("{sz:.4}")),
+            ])
+            .style(Style::default().fg(color))
+        };
+        let rows = self
+            .asks
+            .iter()
+            .rev()
+            .map(|level| row(level, Color::Red))
+            .chain(self.bids.iter().map(|level| row(level, Color::Green)));
+        frame.render_widget(
+            Table::new(rows, [Constraint::Ratio(1, 2); 2])
+                .header(Row::new(["price", "size"]))
+                .block(Block::bordered().title("Book")),
+            area,
+        );
+    }
+}
+
+// Full-screen terminal dashboard for a running bot. It takes over the
+// terminal until dropped, so the bot's log output should go to a file while
+// it is open.
+pub struct Dashboard {
+    terminal: DefaultTerminal,
+}
+
+impl Dashboard {
+    pub fn start() -> Self {
+        Self {
+            terminal: ratatui::init(),
+        }
+    }
+
+    pub fn draw(&mut self, state: &DashboardState) -> io::Result<()> {
+        self.terminal.draw(|frame| state.render(frame))?;
+        Ok(())
+    }
+
+    // The next key press waiting, without blocking
+    pub fn next_action(&self) -> io::Result<Option<DashboardAction>> {
+        while event::poll(Duration::ZERO)? {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('p') => return Ok(Some(DashboardAction::TogglePause)),
+                KeyCode::Char('f') => return Ok(Some(DashboardAction::Flatten)),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Some(DashboardAction::Quit)),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BookLevel, OrderStatus};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_marks_fills_at_the_mid() {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        let mut book = OrderBook::new();
+        assert!(book.apply_snapshot(0, &[level(100.0), level(99.0)], &[level(101.0)]));
+        let mut state = DashboardState::new("BTC");
+        state.on_book(&book, &SignalState::default());
+        assert_eq!(state.bids, vec![(100.0, 1.0), (99.0, 1.0)]);
+
+        state.on_fill(&OrderFill {
+            cloid: Uuid::new_v4(),
+            coin: "BTC".to_string(),
+            is_buy: true,
+            px: 99.0,
+            sz: 2.0,
+            time_ms: 0,
+            status: OrderStatus::Filled,
+        });
+        state.sample_pnl();
+        // Bought 2 at 99, marked at 100.5
+        assert_eq!(state.position, 2.0);
+        assert_eq!(state.pnl.back(), Some(&3.0));
+    }
+}
@@ -61,4 +61,30 @@ pub enum Error {
     SignatureFailure(String),
     #[error("Vault address not found")]
     VaultAddressNotFound,
+    #[error("Order book is empty")]
+    EmptyOrderBook,
+    #[error("Recording error: {0:?}")]
+    Recording(String),
+    #[error("Unknown strategy {0:?}")]
+    UnknownStrategy(String),
+    #[error("Script error: {0:?}")]
+    Script(String),
+    #[error("WASM strategy error: {0:?}")]
+    Wasm(String),
+    #[error("Arrow export error: {0:?}")]
+    Arrow(String),
+    #[error("Admin socket error: {0:?}")]
+    Admin(String),
+    #[error("Journal error: {0:?}")]
+    Journal(String),
+    #[error("Backtest error: {0:?}")]
+    Backtest(String),
+    #[error("Config error: {0:?}")]
+    Config(String),
+    #[error("Pre-trade check failed: {0:?}")]
+    PreTrade(String),
+    #[error("Margin guard refused: {0:?}")]
+    MarginGuard(String),
+    #[error("Duplicate of an intent just sent")]
+    DuplicateIntent,
 }
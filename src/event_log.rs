@@ -0,0 +1,247 @@
+use chrono::Utc;
+use log::error;
+use serde_json::{json, Map, Value};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{prelude::*, Error, ExchangeResponseStatus, OrderIntent, OrderOutcome};
+
+pub(crate) const EVENT_LOG_MAX_BYTES: u64 = 64 * 1024 * 1024; // A file is rotated once it reaches this size
+pub(crate) const EVENT_LOG_KEEP: usize = 5; // Rotated files kept as PATH.1 (newest) to PATH.N
+
+struct LogFile {
+    writer: BufWriter<File>,
+    written: u64,
+}
+
+// Structured JSONL log of everything the bot does with orders, for post-trade
+// analysis: each intent submitted and the exchange's response to it with its
+// latency, fills, final order statuses and quotes the risk manager refused.
+// Every line carries the run id, the wall-clock time in ms, an `event` kind
+// and the event's fields. Files rotate by size. Clones share one log.
+#[derive(Clone)]
+pub struct EventLog {
+    run_id: String,
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Arc<Mutex<LogFile>>,
+}
+
+impl EventLog {
+    pub fn open(path: impl AsRef<Path>, run_id: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_file(&path)?;
+        Ok(Self {
+            run_id: run_id.to_string(),
+            path,
+            max_bytes: EVENT_LOG_MAX_BYTES,
+            keep: EVENT_LOG_KEEP,
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    pub fn with_rotation(mut self, max_bytes: u64, keep: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self.keep = keep;
+        self
+    }
+
+    // One line of `event` with `fields`, which should be a JSON object.
+    // Failures are logged rather than returned so no order waits on the log.
+    pub fn record(&self, event: &str, fields: Value) {
+        let mut line = Map::new();
+        line.insert("run_id".into(), self.run_id.clone().into());
+        line.insert("time".into(), Utc::now().timestamp_millis().into());
+        line.insert("event".into(), event.into());
+        match fields {
+            Value::Object(fields) => line.extend(fields),
+            Value::Null => {}
+            other => {
+                line.insert("data".into(), other);
+            }
+        }
+        let mut bytes = Value::Object(line).to_string().into_bytes();
+        bytes.push(b'\n');
+        if let Err(e) = self.write(&bytes) {
+            error!("[EventLog] Write failed: {e}");
+        }
+    }
+
+    pub fn intent(&self, intent: &OrderIntent) {
+        self.record("intent", intent_fields(intent));
+    }
+
+    // The exchange's answer to `intent`, sent `latency_us` earlier
+    pub fn response(
+        &self,
+        intent: &Value,
+        result: &Result<ExchangeResponseStatus>,
+        latency_us: u64,
+    ) {
+        let mut fields = intent.clone();
+        fields["latency_us"] = latency_us.into();
+        fields["outcome"] = match result {
+            Ok(response) => outcome_fields(&OrderOutcome::from(response.clone())),
+            Err(e) => json!({ "status": "error", "error": e.to_string() }),
+        };
+        self.record("response", fields);
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if file.written > 0 && file.written + bytes.len() as u64 > self.max_bytes {
+            file.writer
+                .flush()
+                .map_err(|e| Error::Journal(e.to_string()))?;
+            self.rotate()?;
+            *file = open_file(&self.path)?;
+        }
+        file.writer
+            .write_all(bytes)
+            .and_then(|_| file.writer.flush())
+            .map_err(|e| Error::Journal(e.to_string()))?;
+        file.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    // PATH.N-1 becomes PATH.N, down to PATH becoming PATH.1; the oldest is
+    // overwritten
+    fn rotate(&self) -> Result<()> {
+        let numbered = |n: usize| PathBuf::from(format!("{}.{n}", self.path.display()));
+        let renamed = if self.keep == 0 {
+            fs::remove_file(&self.path)
+        } else {
+            (1..self.keep)
+                .rev()
+                .filter(|n| numbered(*n).exists())
+                .try_for_each(|n| fs::rename(numbered(n), numbered(n + 1)))
+                .and_then(|_| fs::rename(&self.path, numbered(1)))
+        };
+        renamed.map_err(|e| Error::Journal(e.to_string()))
+    }
+}
+
+fn open_file(path: &Path) -> Result<LogFile> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| Error::Journal(e.to_string()))?;
+    let written = file
+        .metadata()
+        .map_err(|e| Error::Journal(e.to_string()))?
+        .len();
+    Ok(LogFile {
+        writer: BufWriter::new(file),
+        written,
+    })
+}
+
+// What an intent asks for, cloid included when it has one
+pub(crate) fn intent_fields(intent: &OrderIntent) -> Value {
+    match intent {
+        OrderIntent::Place(order) => json!({
+            "action": "place",
+            "coin": order.asset,
+            "cloid": order.cloid.map(|c| c.to_string()),
+            "is_buy": order.is_buy,
+            "px": order.limit_px,
+            "sz": order.sz,
+            "reduce_only": order.reduce_only,
+        }),
+        OrderIntent::Cancel(cancel) => json!({
+            "action": "cancel",
+            "coin": cancel.asset,
+            "oid": cancel.oid,
+        }),
+        OrderIntent::CancelByCloid(cancel) => json!({
+            "action": "cancel",
+            "coin": cancel.asset,
+            "cloid": cancel.cloid.to_string(),
+        }),
+        OrderIntent::Modify(modify) => json!({
+            "action": "modify",
+            "coin": modify.order.asset,
+            "oid": modify.oid,
+            "cloid": modify.order.cloid.map(|c| c.to_string()),
+            "is_buy": modify.order.is_buy,
+            "px": modify.order.limit_px,
+            "sz": modify.order.sz,
+            "reduce_only": modify.order.reduce_only,
+        }),
+    }
+}
+
+fn outcome_fields(outcome: &OrderOutcome) -> Value {
+    match outcome {
+        OrderOutcome::Resting { oid } => json!({ "status": "resting", "oid": oid }),
+        OrderOutcome::Filled { oid, size, avg_px } => {
+            json!({ "status": "filled", "oid": oid, "sz": size, "avg_px": avg_px })
+        }
+        OrderOutcome::Pending => json!({ "status": "accepted" }),
+        OrderOutcome::Rejected(reason) => json!({ "status": "rejected", "error": reason }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{limit_order, ClientCancelRequest, TimeInForce};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_events_are_json_lines_and_rotate_by_size() {
+        let dir = std::env::temp_dir().join(format!("event-log-{}", Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join("events.jsonl");
+        let log = EventLog::open(&path, "run-1")
+            .unwrap()
+            .with_rotation(400, 2);
+
+        let cloid = Uuid::new_v4();
+        let order = limit_order(
+            "BTC",
+            true,
+            100.0,
+            0.5,
+            false,
+            TimeInForce::Gtc,
+            Some(cloid),
+        );
+        let intent = OrderIntent::Place(order);
+        log.intent(&intent);
+        let rejected = Err(Error::GenericRequest("executor halted".to_string()));
+        log.response(&intent_fields(&intent), &rejected, 1_500);
+        let cancel = OrderIntent::Cancel(ClientCancelRequest {
+            asset: "BTC".to_string(),
+            oid: 7,
+        });
+        log.intent(&cancel);
+
+        let lines = |path: &Path| -> Vec<Value> {
+            fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|l| serde_json::from_str(l).unwrap())
+                .collect()
+        };
+        // The response would have taken the first file over 400 bytes
+        let rotated = lines(&dir.join("events.jsonl.1"));
+        let current = lines(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(rotated[0]["event"], "intent");
+        assert_eq!(rotated[0]["cloid"], cloid.to_string());
+        assert_eq!(rotated[0]["run_id"], "run-1");
+        assert_eq!(current[0]["event"], "response");
+        assert_eq!(current[0]["latency_us"], 1_500);
+        assert_eq!(current[0]["outcome"]["status"], "error");
+        assert_eq!(current[1]["action"], "cancel");
+        assert_eq!(current[1]["oid"], 7);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::signature::sign_typed_data;
 use crate::{
+    consts::CANCEL_BATCH_SIZE,
     exchange::{
         actions::{
             ApproveAgent, ApproveBuilderFee, BulkCancel, BulkModify, BulkOrder, SetReferrer,
@@ -9,13 +10,13 @@ use crate::{
         modify::{ClientModifyRequest, ModifyRequest},
         ClientCancelRequest, ClientOrderRequest,
     },
-    helpers::{generate_random_key, next_nonce, uuid_to_hex_string},
+    helpers::{generate_random_key, next_nonce, protected_limit_px, uuid_to_hex_string},
     info::info_client::InfoClient,
     meta::Meta,
     prelude::*,
     req::HttpClient,
     signature::sign_l1_action,
-    BaseUrl, BulkCancelCloid, Error, ExchangeResponseStatus,
+    AssetSpec, AssetSpecs, BaseUrl, BulkCancelCloid, Error, ExchangeResponseStatus, PreTradeChecks,
 };
 use crate::{ClassTransfer, SpotSend, SpotUser, VaultTransfer, Withdraw3};
 use ethers::{
@@ -39,6 +40,7 @@ pub struct ExchangeClient {
     pub meta: Meta,
     pub vault_address: Option<H160>,
     pub coin_to_asset: HashMap<String, u32>,
+    pub pre_trade: Option<PreTradeChecks>, // validates every order before it is signed
 }
 
 #[derive(Serialize, Deserialize)]
@@ -123,9 +125,23 @@ impl ExchangeClient {
                 base_url: base_url.get_url(),
             },
             coin_to_asset,
+            pre_trade: None,
         })
     }
 
+    // Rejects orders failing `checks` locally instead of sending them
+    pub fn with_pre_trade_checks(mut self, checks: PreTradeChecks) -> Self {
+        self.pre_trade = Some(checks);
+        self
+    }
+
+    pub(crate) fn pre_trade_check(&self, orders: &[ClientOrderRequest]) -> Result<()> {
+        match &self.pre_trade {
+            Some(checks) => orders.iter().try_for_each(|order| checks.check(order)),
+            None => Ok(()),
+        }
+    }
+
     async fn post(
         &self,
         action: serde_json::Value,
@@ -252,7 +268,7 @@ impl ExchangeClient {
         params: MarketOrderParams<'_>,
     ) -> Result<ExchangeResponseStatus> {
         let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
-        let (px, sz_decimals) = self
+        let (px, spec) = self
             .calculate_slippage_price(params.asset, params.is_buy, slippage, params.px)
             .await?;
 
@@ -261,7 +277,7 @@ impl ExchangeClient {
             is_buy: params.is_buy,
             reduce_only: false,
             limit_px: px,
-            sz: round_to_decimals(params.sz, sz_decimals),
+            sz: spec.round_size(params.sz),
             cloid: params.cloid,
             order_type: ClientOrder::Limit(ClientLimit {
                 tif: "Ioc".to_string(),
@@ -277,7 +293,7 @@ impl ExchangeClient {
         builder: BuilderInfo,
     ) -> Result<ExchangeResponseStatus> {
         let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
-        let (px, sz_decimals) = self
+        let (px, spec) = self
             .calculate_slippage_price(params.asset, params.is_buy, slippage, params.px)
             .await?;
 
@@ -286,7 +302,7 @@ impl ExchangeClient {
             is_buy: params.is_buy,
             reduce_only: false,
             limit_px: px,
-            sz: round_to_decimals(params.sz, sz_decimals),
+            sz: spec.round_size(params.sz),
             cloid: params.cloid,
             order_type: ClientOrder::Limit(ClientLimit {
                 tif: "Ioc".to_string(),
@@ -303,20 +319,9 @@ impl ExchangeClient {
         let slippage = params.slippage.unwrap_or(0.05); // Default 5% slippage
         let wallet = params.wallet.unwrap_or(&self.wallet);
 
-        let base_url = match self.http_client.base_url.as_str() {
-            "https://api.hyperliquid.xyz" => BaseUrl::Mainnet,
-            "https://api.hyperliquid-testnet.xyz" => BaseUrl::Testnet,
-            _ => return Err(Error::GenericRequest("Invalid base URL".to_string())),
-        };
-        let info_client = InfoClient::new(None, Some(base_url)).await?;
-        let user_state = info_client
-            .user_state(
-                "0x3daBb4cB399B36A1b35Bea776A9bAAB186BE21F5"
-                    .to_string()
-                    .parse()
-                    .unwrap(),
-            )
-            .await?;
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let user = self.vault_address.unwrap_or_else(|| wallet.address());
+        let user_state = info_client.user_state(user).await?;
 
         let position = user_state
             .asset_positions
@@ -330,11 +335,11 @@ impl ExchangeClient {
             .parse::<f64>()
             .map_err(|_| Error::FloatStringParse)?;
 
-        let (px, sz_decimals) = self
+        let (px, spec) = self
             .calculate_slippage_price(params.asset, szi < 0.0, slippage, params.px)
             .await?;
 
-        let sz = round_to_decimals(params.sz.unwrap_or_else(|| szi.abs()), sz_decimals);
+        let sz = spec.round_size(params.sz.unwrap_or_else(|| szi.abs()));
 
         let order = ClientOrderRequest {
             asset: params.asset.to_string(),
@@ -351,35 +356,109 @@ impl ExchangeClient {
         self.order(order, Some(wallet)).await
     }
 
+    // Limit price is the worst book level needed to fill `sz`, capped at `max_slippage` from the touch
+    pub async fn market_open_protected(
+        &self,
+        params: MarketOrderParams<'_>,
+        max_slippage: f64,
+    ) -> Result<ExchangeResponseStatus> {
+        let px = self
+            .book_protected_price(params.asset, params.is_buy, params.sz, max_slippage)
+            .await?;
+        self.market_open(MarketOrderParams {
+            px: Some(px),
+            slippage: Some(0.0),
+            ..params
+        })
+        .await
+    }
+
+    pub async fn market_close_protected(
+        &self,
+        params: MarketCloseParams<'_>,
+        max_slippage: f64,
+    ) -> Result<ExchangeResponseStatus> {
+        let wallet = params.wallet.unwrap_or(&self.wallet);
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let user = self.vault_address.unwrap_or_else(|| wallet.address());
+        let szi = info_client
+            .user_state(user)
+            .await?
+            .asset_positions
+            .iter()
+            .find(|p| p.position.coin == params.asset)
+            .ok_or(Error::AssetNotFound)?
+            .position
+            .szi
+            .parse::<f64>()
+            .map_err(|_| Error::FloatStringParse)?;
+
+        let sz = params.sz.unwrap_or_else(|| szi.abs());
+        let px = self
+            .book_protected_price(params.asset, szi < 0.0, sz, max_slippage)
+            .await?;
+        self.market_close(MarketCloseParams {
+            sz: Some(sz),
+            px: Some(px),
+            slippage: Some(0.0),
+            ..params
+        })
+        .await
+    }
+
+    async fn book_protected_price(
+        &self,
+        asset: &str,
+        is_buy: bool,
+        sz: f64,
+        max_slippage: f64,
+    ) -> Result<f64> {
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let book = info_client.l2_snapshot(asset.to_string()).await?;
+        // levels[0] are bids, levels[1] asks; a buy walks the asks
+        let side = book
+            .levels
+            .get(if is_buy { 1 } else { 0 })
+            .ok_or(Error::EmptyOrderBook)?;
+        let levels = side
+            .iter()
+            .map(|level| {
+                let px = level.px.parse::<f64>();
+                let sz = level.sz.parse::<f64>();
+                match (px, sz) {
+                    (Ok(px), Ok(sz)) => Ok((px, sz)),
+                    _ => Err(Error::FloatStringParse),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let px =
+            protected_limit_px(&levels, sz, is_buy, max_slippage).ok_or(Error::EmptyOrderBook)?;
+        debug!("protected px for {asset} (is_buy={is_buy}, sz={sz}): {px}");
+        Ok(px)
+    }
+
+    fn base_url(&self) -> Result<BaseUrl> {
+        match self.http_client.base_url.as_str() {
+            "https://api.hyperliquid.xyz" => Ok(BaseUrl::Mainnet),
+            "https://api.hyperliquid-testnet.xyz" => Ok(BaseUrl::Testnet),
+            _ => Err(Error::GenericRequest("Invalid base URL".to_string())),
+        }
+    }
+
     async fn calculate_slippage_price(
         &self,
         asset: &str,
         is_buy: bool,
         slippage: f64,
         px: Option<f64>,
-    ) -> Result<(f64, u32)> {
-        let base_url = match self.http_client.base_url.as_str() {
-            "https://api.hyperliquid.xyz" => BaseUrl::Mainnet,
-            "https://api.hyperliquid-testnet.xyz" => BaseUrl::Testnet,
-            _ => return Err(Error::GenericRequest("Invalid base URL".to_string())),
-        };
-        let info_client = InfoClient::new(None, Some(base_url)).await?;
-        let meta = info_client.meta().await?;
-
-        let asset_meta = meta
-            .universe
-            .iter()
-            .find(|a| a.name == asset)
+    ) -> Result<(f64, AssetSpec)> {
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let spec = AssetSpecs::load(&info_client)
+            .await?
+            .get(asset)
             .ok_or(Error::AssetNotFound)?;
 
-        let sz_decimals = asset_meta.sz_decimals;
-        let max_decimals: u32 = if self.coin_to_asset[asset] < 10000 {
-            6
-        } else {
-            8
-        };
-        let price_decimals = max_decimals.saturating_sub(sz_decimals);
-
         let px = if let Some(px) = px {
             px
         } else {
@@ -400,10 +479,10 @@ impl ExchangeClient {
         let px = px * slippage_factor;
 
         // Round to the correct number of decimal places and significant figures
-        let px = round_to_significant_and_decimal(px, 5, price_decimals);
+        let px = spec.round_price(px);
 
         debug!("px after slippage: {px:?}");
-        Ok((px, sz_decimals))
+        Ok((px, spec))
     }
 
     pub async fn order(
@@ -429,6 +508,7 @@ impl ExchangeClient {
         orders: Vec<ClientOrderRequest>,
         wallet: Option<&LocalWallet>,
     ) -> Result<ExchangeResponseStatus> {
+        self.pre_trade_check(&orders)?;
         let wallet = wallet.unwrap_or(&self.wallet);
         let timestamp = next_nonce();
 
@@ -457,6 +537,7 @@ impl ExchangeClient {
         wallet: Option<&LocalWallet>,
         mut builder: BuilderInfo,
     ) -> Result<ExchangeResponseStatus> {
+        self.pre_trade_check(&orders)?;
         let wallet = wallet.unwrap_or(&self.wallet);
         let timestamp = next_nonce();
 
@@ -521,6 +602,73 @@ impl ExchangeClient {
         self.post(action, signature, timestamp).await
     }
 
+    // Cancels every open order for the user in batches of `CANCEL_BATCH_SIZE`, one response per
+    // batch. A batch that fails is reported as an error response and the rest are still sent.
+    pub async fn cancel_all_orders(
+        &self,
+        wallet: Option<&LocalWallet>,
+    ) -> Result<Vec<ExchangeResponseStatus>> {
+        let wallet = wallet.unwrap_or(&self.wallet);
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let user = self.vault_address.unwrap_or_else(|| wallet.address());
+        let open_orders = info_client.open_orders(user).await?;
+        debug!("cancelling {} open orders", open_orders.len());
+
+        let mut responses = Vec::new();
+        for batch in open_orders.chunks(CANCEL_BATCH_SIZE) {
+            let cancels = batch
+                .iter()
+                .map(|order| ClientCancelRequest {
+                    asset: order.coin.clone(),
+                    oid: order.oid,
+                })
+                .collect();
+            let first = batch[0].oid;
+            responses.push(
+                self.bulk_cancel(cancels, Some(wallet))
+                    .await
+                    .unwrap_or_else(|e| {
+                        ExchangeResponseStatus::Err(format!("batch from oid {first}: {e}"))
+                    }),
+            );
+        }
+        Ok(responses)
+    }
+
+    // Closes every open position with a reduce-only IOC at the default market close
+    // slippage, one response per position. A close that fails is reported as an error
+    // response and the rest are still sent.
+    pub async fn close_all_positions(
+        &self,
+        wallet: Option<&LocalWallet>,
+    ) -> Result<Vec<ExchangeResponseStatus>> {
+        let wallet = wallet.unwrap_or(&self.wallet);
+        let info_client = InfoClient::new(None, Some(self.base_url()?)).await?;
+        let user = self.vault_address.unwrap_or_else(|| wallet.address());
+        let positions = info_client.user_state(user).await?.asset_positions;
+
+        let mut responses = Vec::new();
+        for asset_position in positions {
+            let position = asset_position.position;
+            if position.szi.parse::<f64>().unwrap_or(0.0) == 0.0 {
+                continue;
+            }
+            debug!("closing {} {}", position.szi, position.coin);
+            let params = MarketCloseParams {
+                asset: &position.coin,
+                sz: None,
+                px: None,
+                slippage: None,
+                cloid: None,
+                wallet: Some(wallet),
+            };
+            responses.push(self.market_close(params).await.unwrap_or_else(|e| {
+                ExchangeResponseStatus::Err(format!("{}: {e}", position.coin))
+            }));
+        }
+        Ok(responses)
+    }
+
     pub async fn modify(
         &self,
         modify: ClientModifyRequest,
@@ -788,19 +936,6 @@ impl ExchangeClient {
     }
 }
 
-fn round_to_decimals(value: f64, decimals: u32) -> f64 {
-    let factor = 10f64.powi(decimals as i32);
-    (value * factor).round() / factor
-}
-
-fn round_to_significant_and_decimal(value: f64, sig_figs: u32, max_decimals: u32) -> f64 {
-    let abs_value = value.abs();
-    let magnitude = abs_value.log10().floor() as i32;
-    let scale = 10f64.powi(sig_figs as i32 - magnitude - 1);
-    let rounded = (abs_value * scale).round() / scale;
-    round_to_decimals(rounded.copysign(value), max_decimals)
-}
-
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
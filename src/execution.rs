@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::{
+    prelude::*, ClientCancelRequestCloid, ClientLimit, ClientOrder, ClientOrderRequest,
+    ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, HedgeOrder,
+};
+
+// How long a limit order may rest. The exchange has no good-till-date, so
+// `Gtd` goes out as GTC and whoever placed it cancels it once `ms` of market
+// time have passed (see `StrategyRunner`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    #[default]
+    Gtc, // rests until filled or cancelled
+    Alo, // post only: rejected if it would take
+    Ioc, // takes what it can, the rest is cancelled
+    Gtd(u64),
+}
+
+impl TimeInForce {
+    // The exchange's name for it
+    pub fn wire(self) -> &'static str {
+        match self {
+            TimeInForce::Gtc | TimeInForce::Gtd(_) => "Gtc",
+            TimeInForce::Alo => "Alo",
+            TimeInForce::Ioc => "Ioc",
+        }
+    }
+
+    // Time to live of an emulated GTD order
+    pub fn expiry_ms(self) -> Option<u64> {
+        match self {
+            TimeInForce::Gtd(ms) => Some(ms),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimeInForce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeInForce::Gtd(ms) => write!(f, "Gtd({ms}ms)"),
+            tif => f.write_str(tif.wire()),
+        }
+    }
+}
+
+impl From<TimeInForce> for ClientLimit {
+    fn from(tif: TimeInForce) -> Self {
+        ClientLimit {
+            tif: tif.wire().to_string(),
+        }
+    }
+}
+
+// What an order is for, which picks its time in force (see `TifConfig`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderRole {
+    Quote, // a maker's resting quotes
+    Entry, // opens a position
+    Exit,  // closes one
+    Hedge, // the risk manager's inventory hedges
+}
+
+// What became of a single order, taken from the first status of the response
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderOutcome {
+    Resting { oid: u64 },
+    Filled { oid: u64, size: f64, avg_px: f64 },
+    Pending,          // accepted without a fill or resting status (e.g. waiting for trigger)
+    Rejected(String), // refused by the exchange, for the whole request or this order
+}
+
+impl From<ExchangeResponseStatus> for OrderOutcome {
+    fn from(response: ExchangeResponseStatus) -> Self {
+        let response = match response {
+            ExchangeResponseStatus::Ok(response) => response,
+            ExchangeResponseStatus::Err(e) => return OrderOutcome::Rejected(e),
+        };
+        match response.data.and_then(|d| d.statuses.into_iter().next()) {
+            Some(ExchangeDataStatus::Resting(order)) => OrderOutcome::Resting { oid: order.oid },
+            Some(ExchangeDataStatus::Filled(fill)) => {
+                match (fill.total_sz.parse(), fill.avg_px.parse()) {
+                    (Ok(size), Ok(avg_px)) => OrderOutcome::Filled {
+                        oid: fill.oid,
+                        size,
+                        avg_px,
+                    },
+                    _ => OrderOutcome::Rejected(format!("unparseable fill {fill:?}")),
+                }
+            }
+            Some(ExchangeDataStatus::Error(e)) => OrderOutcome::Rejected(e),
+            Some(_) | None => OrderOutcome::Pending,
+        }
+    }
+}
+
+// Order size for a margin budget at the given leverage, in 0.001 lots
+pub fn compute_qty(price: f64, usd_margin: f64, leverage: f64) -> f64 {
+    let notional = usd_margin * leverage;
+    (notional / price * 1000.0).round() / 1000.0
+}
+
+pub fn round_to_tick(price: f64, tick_size: f64) -> f64 {
+    (price / tick_size).round() * tick_size
+}
+
+// Limit order with the given time in force
+pub fn limit_order(
+    asset: &str,
+    is_buy: bool,
+    px: f64,
+    sz: f64,
+    reduce_only: bool,
+    tif: TimeInForce,
+    cloid: Option<Uuid>,
+) -> ClientOrderRequest {
+    ClientOrderRequest {
+        asset: asset.to_string(),
+        is_buy,
+        reduce_only,
+        limit_px: px,
+        sz,
+        cloid,
+        order_type: ClientOrder::Limit(tif.into()),
+    }
+}
+
+// Reducing order for a risk-manager hedge, normally an IOC
+pub fn hedge_order(asset: &str, hedge: &HedgeOrder, tif: TimeInForce) -> ClientOrderRequest {
+    limit_order(
+        asset,
+        hedge.is_buy,
+        hedge.limit_px,
+        hedge.size,
+        true,
+        tif,
+        None,
+    )
+}
+
+pub async fn place_order(
+    exchange: &ExchangeClient,
+    order: ClientOrderRequest,
+) -> Result<OrderOutcome> {
+    exchange.order(order, None).await.map(OrderOutcome::from)
+}
+
+pub async fn cancel_by_cloid(
+    exchange: &ExchangeClient,
+    asset: &str,
+    cloid: Uuid,
+) -> Result<ExchangeResponseStatus> {
+    let cancel = ClientCancelRequestCloid {
+        asset: asset.to_string(),
+        cloid,
+    };
+    exchange.cancel_by_cloid(cancel, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_outcome_from_response() {
+        let parse = |json: &str| -> OrderOutcome {
+            serde_json::from_str::<ExchangeResponseStatus>(json)
+                .unwrap()
+                .into()
+        };
+        assert_eq!(
+            parse(
+                r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"filled":{"totalSz":"0.02","avgPx":"1891.4","oid":77738308}}]}}}"#
+            ),
+            OrderOutcome::Filled {
+                oid: 77738308,
+                size: 0.02,
+                avg_px: 1891.4
+            }
+        );
+        assert_eq!(
+            parse(
+                r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"resting":{"oid":5}}]}}}"#
+            ),
+            OrderOutcome::Resting { oid: 5 }
+        );
+        assert_eq!(
+            parse(
+                r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"error":"Insufficient margin"}]}}}"#
+            ),
+            OrderOutcome::Rejected("Insufficient margin".into())
+        );
+        assert_eq!(
+            parse(r#"{"status":"err","response":"User or API Wallet does not exist."}"#),
+            OrderOutcome::Rejected("User or API Wallet does not exist.".into())
+        );
+        assert_eq!(compute_qty(50_000.0, 11.0, 20.0), 0.004);
+        assert!((round_to_tick(101.26, 0.5) - 101.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gtd_goes_out_as_gtc() {
+        let order = limit_order(
+            "BTC",
+            true,
+            100.0,
+            1.0,
+            false,
+            TimeInForce::Gtd(5_000),
+            None,
+        );
+        let ClientOrder::Limit(limit) = order.order_type else {
+            panic!("expected a limit order");
+        };
+        assert_eq!(limit.tif, "Gtc");
+        assert_eq!(TimeInForce::Gtd(5_000).expiry_ms(), Some(5_000));
+        assert_eq!(TimeInForce::Alo.to_string(), "Alo");
+        let parsed: Vec<TimeInForce> = serde_json::from_str(r#"["ioc", {"gtd": 250}]"#).unwrap();
+        assert_eq!(parsed, vec![TimeInForce::Ioc, TimeInForce::Gtd(250)]);
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::VecDeque;
+
+use crate::{FeeModel, Metrics, QuoteProposal};
+
+pub(crate) const REALIZED_SPREAD_HORIZON_MS: u64 = 5_000; // Mid this long after a fill marks its outcome
+pub(crate) const SPREAD_WINDOW: usize = 200; // Fills per role in the rolling averages
+pub(crate) const EFFICIENCY_VOLUME: f64 = 1_000_000.0; // Volume the efficiency cost is quoted per
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRole {
+    Maker,
+    Taker,
+}
+
+impl FillRole {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            FillRole::Maker => "maker",
+            FillRole::Taker => "taker",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingFill {
+    ts: u64,
+    px: f64,
+    is_buy: bool,
+    role: FillRole,
+    mid: f64,
+}
+
+// Execution quality per fill, in bps of the mid at fill time:
+//   effective spread = 2 * |fill - mid at fill|
+//   realized spread  = 2 * (fill - mid T later), signed so that positive means the
+//                      price then moved in our favour (sold above / bought below it)
+// For maker fills effective minus realized is the adverse selection paid; for taker
+// fills effective spread is the cost of crossing.
+#[derive(Debug, Clone)]
+pub struct SpreadTracker {
+    horizon_ms: u64,
+    window: usize,
+    pending: VecDeque<PendingFill>,
+    effective: [VecDeque<f64>; 2],
+    realized: [VecDeque<f64>; 2],
+}
+
+impl Default for SpreadTracker {
+    fn default() -> Self {
+        Self::new(REALIZED_SPREAD_HORIZON_MS, SPREAD_WINDOW)
+    }
+}
+
+fn push_bounded(samples: &mut VecDeque<f64>, value: f64, window: usize) {
+    samples.push_back(value);
+    while samples.len() > window {
+        samples.pop_front();
+    }
+}
+
+fn mean(samples: &VecDeque<f64>) -> Option<f64> {
+    (!samples.is_empty()).then(|| samples.iter().sum::<f64>() / samples.len() as f64)
+}
+
+impl SpreadTracker {
+    pub fn new(horizon_ms: u64, window: usize) -> Self {
+        Self {
+            horizon_ms,
+            window: window.max(1),
+            pending: VecDeque::new(),
+            effective: Default::default(),
+            realized: Default::default(),
+        }
+    }
+
+    // Records a fill and returns its effective spread in bps
+    pub fn on_fill(&mut self, ts: u64, px: f64, is_buy: bool, role: FillRole, mid: f64) -> f64 {
+        if mid <= 0.0 {
+            return 0.0;
+        }
+        let effective = 2.0 * (px - mid).abs() / mid * 10_000.0;
+        push_bounded(&mut self.effective[role.index()], effective, self.window);
+        self.pending.push_back(PendingFill {
+            ts,
+            px,
+            is_buy,
+            role,
+            mid,
+        });
+        effective
+    }
+
+    // Feeds the current mid; fills at least the horizon old get their realized spread
+    pub fn on_mid(&mut self, ts: u64, mid: f64) {
+        while let Some(fill) = self.pending.front() {
+            if ts < fill.ts + self.horizon_ms {
+                break;
+            }
+            let direction = if fill.is_buy { -1.0 } else { 1.0 };
+            let realized = 2.0 * direction * (fill.px - mid) / fill.mid * 10_000.0;
+            let role = fill.role;
+            push_bounded(&mut self.realized[role.index()], realized, self.window);
+            self.pending.pop_front();
+        }
+    }
+
+    pub fn effective_bps(&self, role: FillRole) -> Option<f64> {
+        mean(&self.effective[role.index()])
+    }
+
+    pub fn realized_bps(&self, role: FillRole) -> Option<f64> {
+        mean(&self.realized[role.index()])
+    }
+
+    // One-line rolling averages, for logs and the admin REPL
+    pub fn summary(&self) -> String {
+        let fmt = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{v:.2}"));
+        [FillRole::Maker, FillRole::Taker]
+            .iter()
+            .map(|&role| {
+                format!(
+                    "{} eff {} / real {} bps",
+                    role.label(),
+                    fmt(self.effective_bps(role)),
+                    fmt(self.realized_bps(role))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    // Publishes the rolling averages as gauges labelled by coin and role
+    pub fn export(&self, metrics: &Metrics, coin: &str) {
+        for role in [FillRole::Maker, FillRole::Taker] {
+            let labels = format!("{{coin=\"{coin}\",role=\"{}\"}}", role.label());
+            if let Some(v) = self.effective_bps(role) {
+                metrics.set(&format!("effective_spread_bps{labels}"), v);
+            }
+            if let Some(v) = self.realized_bps(role) {
+                metrics.set(&format!("realized_spread_bps{labels}"), v);
+            }
+        }
+    }
+}
+
+// Per-session quoting behaviour: how often quotes are replaced versus filled,
+// and how much of the session the book carried our quotes on both sides and at
+// the touch. Quote sets are diffed update to update, so a repriced quote counts
+// as one cancel plus one placement.
+#[derive(Debug, Clone, Default)]
+pub struct QuoteActivity {
+    placed: u64,
+    cancelled: u64,
+    fills: u64,
+    last_ts: Option<u64>,
+    live: Vec<QuoteProposal>,
+    two_sided: bool,
+    at_touch: bool,
+    elapsed_ms: u64,
+    two_sided_ms: u64,
+    touch_ms: u64,
+}
+
+fn same_quote(a: &QuoteProposal, b: &QuoteProposal) -> bool {
+    a.side == b.side && (a.price - b.price).abs() < 1e-9 && (a.size - b.size).abs() < 1e-9
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+impl QuoteActivity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces the live quote set at `ts`; the previous set is credited for the
+    // time it was live
+    pub fn on_quotes(&mut self, ts: u64, quotes: &[QuoteProposal], best_bid: f64, best_ask: f64) {
+        if let Some(last) = self.last_ts {
+            let dt = ts.saturating_sub(last);
+            self.elapsed_ms += dt;
+            if self.two_sided {
+                self.two_sided_ms += dt;
+            }
+            if self.at_touch {
+                self.touch_ms += dt;
+            }
+        }
+        self.last_ts = Some(ts);
+        self.cancelled += self
+            .live
+            .iter()
+            .filter(|old| !quotes.iter().any(|q| same_quote(old, q)))
+            .count() as u64;
+        self.placed += quotes
+            .iter()
+            .filter(|q| !self.live.iter().any(|old| same_quote(old, q)))
+            .count() as u64;
+        let has = |side: &str| quotes.iter().any(|q| q.side == side);
+        self.two_sided = has("Buy") && has("Sell");
+        self.at_touch = quotes.iter().any(|q| {
+            (q.side == "Buy" && q.price >= best_bid) || (q.side == "Sell" && q.price <= best_ask)
+        });
+        self.live = quotes.to_vec();
+    }
+
+    pub fn on_fill(&mut self) {
+        self.fills += 1;
+    }
+
+    // Cancels per fill; with no fills yet this is the raw cancel count
+    pub fn cancel_to_fill(&self) -> f64 {
+        self.cancelled as f64 / self.fills.max(1) as f64
+    }
+
+    pub fn two_sided_uptime_pct(&self) -> f64 {
+        100.0 * ratio(self.two_sided_ms, self.elapsed_ms)
+    }
+
+    pub fn time_at_touch_pct(&self) -> f64 {
+        100.0 * ratio(self.touch_ms, self.elapsed_ms)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "placed {} | cancelled {} | fills {} | cancel/fill {:.1} | two-sided {:.1}% | at touch {:.1}%",
+            self.placed,
+            self.cancelled,
+            self.fills,
+            self.cancel_to_fill(),
+            self.two_sided_uptime_pct(),
+            self.time_at_touch_pct()
+        )
+    }
+
+    pub fn export(&self, metrics: &Metrics, coin: &str) {
+        let labels = format!("{{coin=\"{coin}\"}}");
+        metrics.set(&format!("quotes_placed_total{labels}"), self.placed as f64);
+        metrics.set(
+            &format!("quotes_cancelled_total{labels}"),
+            self.cancelled as f64,
+        );
+        metrics.set(&format!("quote_fills_total{labels}"), self.fills as f64);
+        metrics.set(
+            &format!("cancel_to_fill_ratio{labels}"),
+            self.cancel_to_fill(),
+        );
+        metrics.set(
+            &format!("two_sided_uptime_pct{labels}"),
+            self.two_sided_uptime_pct(),
+        );
+        metrics.set(
+            &format!("time_at_touch_pct{labels}"),
+            self.time_at_touch_pct(),
+        );
+    }
+}
+
+// What a coin's volume costs: fees paid plus PnL lost, per $1M traded. Fees
+// come from the account's schedule, by fill role; the PnL is the position
+// marked at the fair value, before fees. Cheaper coins are the ones to shift
+// volume-generating quotes to.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeEfficiency {
+    fees: FeeModel,
+    volume: f64,
+    fees_paid: f64,
+}
+
+impl VolumeEfficiency {
+    pub fn new(fees: FeeModel) -> Self {
+        Self {
+            fees,
+            ..Default::default()
+        }
+    }
+
+    pub fn on_fill(&mut self, notional: f64, role: FillRole) {
+        self.volume += notional;
+        self.fees_paid += self.fees.fee(notional, role == FillRole::Taker);
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    pub fn fees_paid(&self) -> f64 {
+        self.fees_paid
+    }
+
+    // Fees plus PnL lost per $1M of volume; negative when the coin pays for
+    // its volume. None before any volume.
+    pub fn cost_per_million(&self, pnl: f64) -> Option<f64> {
+        (self.volume > 0.0).then(|| (self.fees_paid - pnl) / self.volume * EFFICIENCY_VOLUME)
+    }
+
+    pub fn summary(&self, pnl: f64) -> String {
+        let cost = self
+            .cost_per_million(pnl)
+            .map_or("-".to_string(), |cost| format!("${cost:.2}"));
+        format!(
+            "volume ${:.2} | fees ${:.2} | PnL ${pnl:.2} | cost per $1M {cost}",
+            self.volume, self.fees_paid
+        )
+    }
+
+    pub fn export(&self, metrics: &Metrics, coin: &str, pnl: f64) {
+        let labels = format!("{{coin=\"{coin}\"}}");
+        metrics.set(&format!("volume_usd_total{labels}"), self.volume);
+        metrics.set(&format!("fees_usd_total{labels}"), self.fees_paid);
+        if let Some(cost) = self.cost_per_million(pnl) {
+            metrics.set(&format!("cost_per_million_usd{labels}"), cost);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maker_fill_adverse_selection() {
+        let mut tracker = SpreadTracker::new(1_000, 10);
+        // Bought 0.01 under a 100.0 mid (2 bps effective), then the mid falls through the fill
+        let effective = tracker.on_fill(0, 99.99, true, FillRole::Maker, 100.0);
+        assert!((effective - 2.0).abs() < 1e-9);
+        tracker.on_mid(500, 99.98);
+        assert_eq!(tracker.realized_bps(FillRole::Maker), None);
+        tracker.on_mid(1_000, 99.98);
+        let realized = tracker.realized_bps(FillRole::Maker).unwrap();
+        assert!((realized + 2.0).abs() < 1e-9);
+        assert_eq!(tracker.effective_bps(FillRole::Taker), None);
+
+        // Taker sell one tick under mid, price then falls further: realized is positive
+        tracker.on_fill(2_000, 99.97, false, FillRole::Taker, 99.98);
+        tracker.on_mid(3_000, 99.94);
+        assert!(tracker.realized_bps(FillRole::Taker).unwrap() > 0.0);
+
+        let metrics = Metrics::new();
+        tracker.export(&metrics, "BTC");
+        assert!(metrics
+            .gauge("effective_spread_bps{coin=\"BTC\",role=\"maker\"}")
+            .is_some());
+    }
+
+    #[test]
+    fn test_quote_activity_uptime_and_cancels() {
+        let quote = |side: &str, price: f64| QuoteProposal {
+            side: side.to_string(),
+            price,
+            size: 1.0,
+        };
+        let mut activity = QuoteActivity::new();
+        // Two-sided at the touch for 1s, then bid-only (behind the touch) for 3s
+        activity.on_quotes(
+            0,
+            &[quote("Buy", 100.0), quote("Sell", 101.0)],
+            100.0,
+            101.0,
+        );
+        activity.on_quotes(1_000, &[quote("Buy", 99.0)], 100.0, 101.0);
+        activity.on_fill();
+        activity.on_quotes(4_000, &[quote("Buy", 99.0)], 100.0, 101.0);
+
+        assert_eq!(activity.placed, 3);
+        assert_eq!(activity.cancelled, 2);
+        assert_eq!(activity.cancel_to_fill(), 2.0);
+        assert!((activity.two_sided_uptime_pct() - 25.0).abs() < 1e-9);
+        assert!((activity.time_at_touch_pct() - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_volume_efficiency_counts_fees_and_lost_pnl() {
+        let fees = FeeModel {
+            taker_bps: 4.5,
+            maker_bps: 1.5,
+            builder_bps: 0.0,
+        };
+        let mut efficiency = VolumeEfficiency::new(fees);
+        assert_eq!(efficiency.cost_per_million(0.0), None);
+        efficiency.on_fill(400_000.0, FillRole::Maker);
+        efficiency.on_fill(100_000.0, FillRole::Taker);
+        // $60 + $45 in fees on $500k, plus $20 lost
+        assert!((efficiency.fees_paid() - 105.0).abs() < 1e-9);
+        assert!((efficiency.cost_per_million(-20.0).unwrap() - 250.0).abs() < 1e-9);
+        // Profit can cover the fees
+        assert!(efficiency.cost_per_million(200.0).unwrap() < 0.0);
+
+        let metrics = Metrics::new();
+        efficiency.export(&metrics, "ETH", -20.0);
+        let cost = metrics.gauge("cost_per_million_usd{coin=\"ETH\"}");
+        assert!((cost.unwrap() - 250.0).abs() < 1e-9);
+    }
+}
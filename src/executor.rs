@@ -0,0 +1,590 @@
+use log::warn;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{oneshot, Notify};
+
+use crate::{
+    prelude::*, ClientCancelRequest, ClientCancelRequestCloid, ClientModifyRequest,
+    ClientOrderRequest, Error, EventLog, ExchangeClient, ExchangeResponseStatus, ExecutionBackend,
+    LatencyTracker, LiveExchange, MarketEvent, RateLimiter, RequestClass,
+};
+
+pub(crate) const MAX_BATCH: usize = 40; // Intents per bulk request
+
+// Submission classes, most urgent first. Under load the executor always sends
+// whatever reduces risk before anything that adds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntentPriority {
+    RiskReducing, // cancels, reduce-only closes and hedges
+    NewQuote,
+}
+
+#[derive(Debug)]
+pub enum OrderIntent {
+    Place(ClientOrderRequest),
+    Cancel(ClientCancelRequest),
+    CancelByCloid(ClientCancelRequestCloid),
+    Modify(ClientModifyRequest),
+}
+
+impl OrderIntent {
+    // Reduce-only is what marks a place or modify as risk reducing, so hedges
+    // must be built reduce-only (`hedge_order`) to go ahead of quotes
+    pub fn priority(&self) -> IntentPriority {
+        match self {
+            OrderIntent::Place(order) if !order.reduce_only => IntentPriority::NewQuote,
+            OrderIntent::Modify(modify) if !modify.order.reduce_only => IntentPriority::NewQuote,
+            _ => IntentPriority::RiskReducing,
+        }
+    }
+
+    pub fn request_class(&self) -> RequestClass {
+        match self {
+            OrderIntent::Cancel(_) | OrderIntent::CancelByCloid(_) => RequestClass::Cancel,
+            OrderIntent::Place(_) | OrderIntent::Modify(_) => RequestClass::Order,
+        }
+    }
+
+    // The order a place or modify asks for, for spotting repeats of it.
+    // Reduce-only and the time in force stand in for the order's role.
+    fn fingerprint(&self) -> Option<(SideKey, IntentKey)> {
+        let (order, modify) = match self {
+            OrderIntent::Place(order) => (order, None),
+            OrderIntent::Modify(modify) => (&modify.order, Some(modify.oid)),
+            _ => return None,
+        };
+        let side = (order.asset.clone(), order.is_buy);
+        let key = IntentKey {
+            modify,
+            px: order.limit_px.to_bits(),
+            sz: order.sz.to_bits(),
+            reduce_only: order.reduce_only,
+            order_type: format!("{:?}", order.order_type),
+        };
+        Some((side, key))
+    }
+}
+
+type SideKey = (String, bool); // asset, is_buy
+
+#[derive(Debug, PartialEq)]
+struct IntentKey {
+    modify: Option<u64>, // the oid modified, None for a new order
+    px: u64,
+    sz: u64,
+    reduce_only: bool,
+    order_type: String,
+}
+
+// Collapses an order intent identical to the last one sent for the same coin
+// and side within `window`, as signal jitter produces when the book flickers
+// between two states. Cancels always go through.
+struct IntentDedup {
+    window: Duration,
+    last: HashMap<SideKey, (IntentKey, Instant)>,
+}
+
+impl IntentDedup {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: HashMap::new(),
+        }
+    }
+
+    // True when `intent` repeats the last one for its side inside the window
+    fn is_duplicate(&mut self, now: Instant, intent: &OrderIntent) -> bool {
+        let Some((side, key)) = intent.fingerprint() else {
+            return false;
+        };
+        if let Some((last, sent)) = self.last.get(&side) {
+            if *last == key && now.duration_since(*sent) < self.window {
+                return true;
+            }
+        }
+        self.last.insert(side, (key, now));
+        false
+    }
+}
+
+struct Queued<T> {
+    priority: IntentPriority,
+    seq: u64,
+    item: T,
+}
+
+// Max-heap order: higher priority first, then first-in first-out within a class
+impl<T> Ord for Queued<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<T> PartialOrd for Queued<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Queued<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Queued<T> {}
+
+// Priority queue shared between submitters and the executor's worker
+pub struct IntentQueue<T> {
+    heap: Mutex<BinaryHeap<Queued<T>>>,
+    next_seq: Mutex<u64>,
+    notify: Notify,
+}
+
+impl<T> Default for IntentQueue<T> {
+    fn default() -> Self {
+        Self {
+            heap: Mutex::new(BinaryHeap::new()),
+            next_seq: Mutex::new(0),
+            notify: Notify::new(),
+        }
+    }
+}
+
+impl<T> IntentQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, priority: IntentPriority, item: T) {
+        self.push_all(vec![(priority, item)]);
+    }
+
+    // Queues `items` at once, so the worker never takes only some of them
+    pub fn push_all(&self, items: Vec<(IntentPriority, T)>) {
+        if items.is_empty() {
+            return;
+        }
+        let mut next = self.next_seq.lock().unwrap_or_else(|e| e.into_inner());
+        let mut heap = self.heap.lock().unwrap_or_else(|e| e.into_inner());
+        for (priority, item) in items {
+            *next += 1;
+            heap.push(Queued {
+                priority,
+                seq: *next,
+                item,
+            });
+        }
+        drop(heap);
+        drop(next);
+        self.notify.notify_one();
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        let mut heap = self.heap.lock().unwrap_or_else(|e| e.into_inner());
+        heap.pop().map(|q| q.item)
+    }
+
+    // Waits for the most urgent item
+    pub async fn pop(&self) -> T {
+        loop {
+            if let Some(item) = self.try_pop() {
+                return item;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+type Submission = (OrderIntent, oneshot::Sender<Result<ExchangeResponseStatus>>);
+
+// Serializes requests to the exchange through a priority queue: requests are sent
+// one at a time, and whenever the worker is free it takes the most urgent one.
+// Once halted, new quotes are refused, including those already queued; cancels
+// and reduce-only orders still go out.
+#[derive(Clone)]
+pub struct Executor {
+    queue: Arc<IntentQueue<Submission>>,
+    backend: Arc<dyn ExecutionBackend>,
+    halted: Arc<AtomicBool>,
+    event_log: Option<EventLog>, // every intent and its response
+    dedup: Option<Arc<Mutex<IntentDedup>>>,
+    settings: Arc<Mutex<WorkerSettings>>,
+}
+
+// How the worker sends what is queued, set after it is spawned
+#[derive(Default)]
+struct WorkerSettings {
+    limiter: Option<RateLimiter>,    // request budget it waits on
+    batch_window: Option<Duration>,  // None sends intents one at a time
+    latency: Option<LatencyTracker>, // round trip of each request
+}
+
+impl Executor {
+    pub fn spawn(exchange: Arc<ExchangeClient>) -> Self {
+        Self::with_backend(Arc::new(LiveExchange::new(exchange)))
+    }
+
+    // Same queueing in front of any backend, e.g. a PaperExchange
+    pub fn with_backend(backend: Arc<dyn ExecutionBackend>) -> Self {
+        let queue = Arc::new(IntentQueue::<Submission>::new());
+        let halted = Arc::new(AtomicBool::new(false));
+        let settings = Arc::new(Mutex::new(WorkerSettings::default()));
+        tokio::spawn(run_worker(
+            queue.clone(),
+            backend.clone(),
+            halted.clone(),
+            settings.clone(),
+        ));
+        Self {
+            queue,
+            backend,
+            halted,
+            event_log: None,
+            dedup: None,
+            settings,
+        }
+    }
+
+    pub fn with_event_log(mut self, log: EventLog) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+
+    // Refuses a place or modify identical to the last one for its coin and
+    // side (price, size, reduce-only, time in force) sent within `window`
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        self.dedup = Some(Arc::new(Mutex::new(IntentDedup::new(window))));
+        self
+    }
+
+    // Waits for `limiter` before sending each request, cancels ahead of
+    // orders when the budget runs low. Share it with the info clients polling
+    // from the same IP.
+    pub fn with_rate_limiter(self, limiter: RateLimiter) -> Self {
+        self.settings().limiter = Some(limiter);
+        self
+    }
+
+    // Sends what is queued together through the backend's bulk endpoints, up
+    // to MAX_BATCH intents a request. A new quote waits `window` for others
+    // to join it; risk-reducing intents go with whatever is already queued.
+    pub fn with_batching(self, window: Duration) -> Self {
+        self.settings().batch_window = Some(window);
+        self
+    }
+
+    // Records how long each request takes to come back from the backend
+    pub fn with_latency(self, latency: LatencyTracker) -> Self {
+        self.settings().latency = Some(latency);
+        self
+    }
+
+    fn settings(&self) -> MutexGuard<'_, WorkerSettings> {
+        self.settings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // True while new orders would wait on the request budget; quoting holds
+    // them back until it refills
+    pub fn is_backpressured(&self) -> bool {
+        self.settings()
+            .limiter
+            .as_ref()
+            .is_some_and(|limiter| limiter.is_throttled(RequestClass::Order))
+    }
+
+    // Forwards market data to the backend (used by simulated backends)
+    pub fn on_market_event(&self, coin: &str, event: &MarketEvent) {
+        self.backend.on_market_event(coin, event);
+    }
+
+    // Requests waiting to be sent
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    // Refuses new quotes from now on
+    pub fn halt(&self) {
+        self.halted.store(true, AtomicOrdering::SeqCst);
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(AtomicOrdering::SeqCst)
+    }
+
+    // Halts, then cancels every open order and closes every position on the
+    // backend, bypassing the queue so nothing waiting in it goes first
+    pub async fn flatten(&self) -> Result<Vec<ExchangeResponseStatus>> {
+        self.halt();
+        self.backend.flatten().await
+    }
+
+    pub async fn submit(&self, intent: OrderIntent) -> Result<ExchangeResponseStatus> {
+        self.submit_all(vec![intent])
+            .await
+            .pop()
+            .unwrap_or_else(|| Err(executor_stopped()))
+    }
+
+    // Queues `intents` together, e.g. one quote cycle's, so that with batching
+    // they go out in as few requests as possible. One result per intent.
+    pub async fn submit_all(
+        &self,
+        intents: Vec<OrderIntent>,
+    ) -> Vec<Result<ExchangeResponseStatus>> {
+        let sent = Instant::now();
+        let mut queued = Vec::new();
+        let mut answers = Vec::new();
+        for intent in intents {
+            let fields = self.event_log.as_ref().map(|log| {
+                log.intent(&intent);
+                crate::event_log::intent_fields(&intent)
+            });
+            let answer = if self.is_halted() && intent.priority() == IntentPriority::NewQuote {
+                Err(halted_error())
+            } else if self.is_duplicate(&intent) {
+                Err(Error::DuplicateIntent)
+            } else {
+                let (reply, response) = oneshot::channel();
+                queued.push((intent.priority(), (intent, reply)));
+                Ok(response)
+            };
+            answers.push((fields, answer));
+        }
+        self.queue.push_all(queued);
+        let mut results = Vec::new();
+        for (fields, answer) in answers {
+            let result = match answer {
+                Ok(response) => response.await.unwrap_or_else(|_| Err(executor_stopped())),
+                Err(e) => Err(e),
+            };
+            if let (Some(log), Some(fields)) = (&self.event_log, &fields) {
+                log.response(fields, &result, sent.elapsed().as_micros() as u64);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    fn is_duplicate(&self, intent: &OrderIntent) -> bool {
+        self.dedup.as_ref().is_some_and(|dedup| {
+            dedup
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .is_duplicate(Instant::now(), intent)
+        })
+    }
+}
+
+// Takes the most urgent intent queued and, when batching, the others it can
+// go out with, then sends them and answers each submitter
+async fn run_worker(
+    queue: Arc<IntentQueue<Submission>>,
+    backend: Arc<dyn ExecutionBackend>,
+    halted: Arc<AtomicBool>,
+    settings: Arc<Mutex<WorkerSettings>>,
+) {
+    loop {
+        let first = queue.pop().await;
+        let (limiter, window, latency) = {
+            let settings = settings.lock().unwrap_or_else(|e| e.into_inner());
+            let latency = settings.latency.clone();
+            (settings.limiter.clone(), settings.batch_window, latency)
+        };
+        let mut batch = vec![first];
+        if let Some(window) = window {
+            if batch[0].0.priority() == IntentPriority::NewQuote && !window.is_zero() {
+                tokio::time::sleep(window).await;
+            }
+            while batch.len() < MAX_BATCH {
+                match queue.try_pop() {
+                    Some(next) => batch.push(next),
+                    None => break,
+                }
+            }
+        }
+        let stopped = halted.load(AtomicOrdering::SeqCst);
+        let (intents, replies): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .filter_map(|(intent, reply)| {
+                if stopped && intent.priority() == IntentPriority::NewQuote {
+                    let _ = reply.send(Err(halted_error()));
+                    return None;
+                }
+                Some((intent, reply))
+            })
+            .unzip();
+        let Some(class) = intents.iter().map(OrderIntent::request_class).min() else {
+            continue;
+        };
+        if let Some(limiter) = limiter {
+            // The exchange weighs a bulk request 1 plus 1 per 40 intents
+            let weight = 1.0 + (intents.len() / 40) as f64;
+            limiter.acquire(class, weight).await;
+        }
+        let sent = Instant::now();
+        let results = if intents.len() == 1 {
+            let intent = intents.into_iter().next().expect("one intent");
+            vec![backend.execute(intent).await]
+        } else {
+            backend.execute_batch(intents).await
+        };
+        if let Some(latency) = latency {
+            latency.record(class, sent.elapsed());
+        }
+        for (reply, result) in replies.into_iter().zip(results) {
+            if reply.send(result).is_err() {
+                warn!("executor result dropped: submitter went away");
+            }
+        }
+    }
+}
+
+fn halted_error() -> Error {
+    Error::GenericRequest("executor halted, new orders refused".to_string())
+}
+
+fn executor_stopped() -> Error {
+    Error::GenericRequest("executor stopped".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hedge_order, ClientOrder, ExchangeResponse, HedgeOrder, TimeInForce};
+    use futures_util::future::BoxFuture;
+
+    // Acknowledges everything, recording the size of each request it gets
+    #[derive(Default)]
+    struct Requests(Mutex<Vec<usize>>);
+
+    impl ExecutionBackend for Requests {
+        fn execute(&self, intent: OrderIntent) -> BoxFuture<'_, Result<ExchangeResponseStatus>> {
+            Box::pin(async move {
+                self.execute_batch(vec![intent])
+                    .await
+                    .pop()
+                    .expect("one result")
+            })
+        }
+
+        fn execute_batch(
+            &self,
+            intents: Vec<OrderIntent>,
+        ) -> BoxFuture<'_, Vec<Result<ExchangeResponseStatus>>> {
+            self.0.lock().unwrap().push(intents.len());
+            let ack = ExchangeResponseStatus::Ok(ExchangeResponse {
+                response_type: "default".to_string(),
+                data: None,
+            });
+            Box::pin(async move { intents.iter().map(|_| Ok(ack.clone())).collect() })
+        }
+
+        fn flatten(&self) -> BoxFuture<'_, Result<Vec<ExchangeResponseStatus>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    fn order(reduce_only: bool) -> OrderIntent {
+        OrderIntent::Place(ClientOrderRequest {
+            asset: "BTC".into(),
+            is_buy: true,
+            reduce_only,
+            limit_px: 100.0,
+            sz: 1.0,
+            cloid: None,
+            order_type: ClientOrder::Limit(TimeInForce::Gtc.into()),
+        })
+    }
+
+    #[test]
+    fn test_risk_reducing_intents_jump_the_queue() {
+        assert_eq!(order(false).priority(), IntentPriority::NewQuote);
+        assert_eq!(order(true).priority(), IntentPriority::RiskReducing);
+        let cancel = OrderIntent::Cancel(ClientCancelRequest {
+            asset: "BTC".into(),
+            oid: 1,
+        });
+        assert_eq!(cancel.priority(), IntentPriority::RiskReducing);
+        let hedge = HedgeOrder {
+            is_buy: false,
+            size: 0.5,
+            limit_px: 99.0,
+        };
+        let hedge = OrderIntent::Place(hedge_order("BTC", &hedge, TimeInForce::Ioc));
+        assert_eq!(hedge.priority(), IntentPriority::RiskReducing);
+
+        let queue = IntentQueue::new();
+        queue.push(IntentPriority::NewQuote, "quote 1");
+        queue.push(IntentPriority::NewQuote, "quote 2");
+        queue.push(IntentPriority::RiskReducing, "cancel");
+        queue.push(IntentPriority::NewQuote, "quote 3");
+        queue.push(IntentPriority::RiskReducing, "close");
+        let order: Vec<_> = std::iter::from_fn(|| queue.try_pop()).collect();
+        assert_eq!(order, ["cancel", "close", "quote 1", "quote 2", "quote 3"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_collapses_repeated_intents_within_the_window() {
+        let mut dedup = IntentDedup::new(Duration::from_millis(100));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert!(!dedup.is_duplicate(at(0), &order(false)));
+        assert!(dedup.is_duplicate(at(50), &order(false)));
+        // Another role on the same side replaces the last intent
+        assert!(!dedup.is_duplicate(at(60), &order(true)));
+        assert!(!dedup.is_duplicate(at(70), &order(false)));
+        assert!(!dedup.is_duplicate(at(200), &order(false)));
+        let cancel = OrderIntent::Cancel(ClientCancelRequest {
+            asset: "BTC".into(),
+            oid: 1,
+        });
+        assert!(!dedup.is_duplicate(at(210), &cancel));
+        assert!(!dedup.is_duplicate(at(220), &cancel));
+    }
+
+    #[tokio::test]
+    async fn test_batches_a_quote_cycle_into_one_request() {
+        let cycle = || {
+            vec![
+                order(false),
+                OrderIntent::Cancel(ClientCancelRequest {
+                    asset: "BTC".into(),
+                    oid: 1,
+                }),
+                order(true),
+            ]
+        };
+        let backend = Arc::new(Requests::default());
+        let executor = Executor::with_backend(backend.clone());
+        let results = executor.submit_all(cycle()).await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(*backend.0.lock().unwrap(), [1, 1, 1]);
+
+        let backend = Arc::new(Requests::default());
+        let executor = Executor::with_backend(backend.clone()).with_batching(Duration::ZERO);
+        let results = executor.submit_all(cycle()).await;
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(*backend.0.lock().unwrap(), [3]);
+    }
+}
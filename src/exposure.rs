@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// === Global Exposure ===
+// Account-level net exposure across every strategy and coin in the process.
+// Each RiskManager only sees its own inventory, so a momentum strategy long 2
+// BTC and a maker long 3 BTC are both "within limits"; this book adds them up.
+// Net exposure is the sum over coins of |net base position| * mid, so a long
+// in one strategy offsets a short in another on the same coin. Optionally no
+// single coin's net position may tie up more than a share of account equity
+// as margin, so coins competing for margin cannot pile into one of them.
+#[derive(Debug)]
+pub struct GlobalExposure {
+    pub max_net_notional: f64, // USD
+    book: Mutex<ExposureBook>,
+}
+
+#[derive(Debug, Default)]
+struct ExposureBook {
+    positions: HashMap<(String, String), f64>, // (strategy, coin) -> base
+    marks: HashMap<String, f64>,               // coin -> last mid
+    max_coin_margin: Option<(f64, f64)>,       // (percent of equity, leverage)
+    equity: f64,                               // USD; 0 = not known yet
+}
+
+impl ExposureBook {
+    // Margin `coin`'s net position ties up at the configured leverage
+    fn coin_margin(&self, coin: &str, leverage: f64) -> f64 {
+        let base: f64 = self
+            .positions
+            .iter()
+            .filter(|((_, c), _)| c == coin)
+            .map(|(_, base)| base)
+            .sum();
+        base.abs() * self.marks.get(coin).copied().unwrap_or(0.0) / leverage
+    }
+
+    fn net_notional(&self) -> f64 {
+        let mut net: HashMap<&str, f64> = HashMap::new();
+        for ((_, coin), base) in &self.positions {
+            *net.entry(coin.as_str()).or_default() += base;
+        }
+        net.iter()
+            .map(|(coin, base)| base.abs() * self.marks.get(*coin).copied().unwrap_or(0.0))
+            .sum()
+    }
+}
+
+impl GlobalExposure {
+    pub fn new(max_net_notional: f64) -> Arc<Self> {
+        Arc::new(Self {
+            max_net_notional,
+            book: Mutex::new(ExposureBook::default()),
+        })
+    }
+
+    // Handle for one strategy trading one coin
+    pub fn slot(self: &Arc<Self>, strategy: &str, coin: &str) -> ExposureSlot {
+        ExposureSlot {
+            global: self.clone(),
+            key: (strategy.to_string(), coin.to_string()),
+        }
+    }
+
+    pub fn net_notional(&self) -> f64 {
+        self.book.lock().unwrap().net_notional()
+    }
+
+    // Caps the margin each coin's net position ties up, at `leverage`, to
+    // `pct` percent of account equity. Not applied until `set_equity` has
+    // been called with a positive value.
+    pub fn set_max_coin_margin_pct(&self, pct: f64, leverage: f64) {
+        self.book.lock().unwrap().max_coin_margin = Some((pct, leverage.max(1.0)));
+    }
+
+    pub fn set_equity(&self, usd: f64) {
+        self.book.lock().unwrap().equity = usd;
+    }
+
+    // Net base position per coin, summed over strategies
+    pub fn net_positions(&self) -> HashMap<String, f64> {
+        let book = self.book.lock().unwrap();
+        let mut net = HashMap::new();
+        for ((_, coin), base) in &book.positions {
+            *net.entry(coin.clone()).or_default() += base;
+        }
+        net
+    }
+}
+
+// One strategy's view of the global book: reports its position and asks
+// whether a change would push the account over the limit
+#[derive(Debug, Clone)]
+pub struct ExposureSlot {
+    global: Arc<GlobalExposure>,
+    key: (String, String),
+}
+
+impl ExposureSlot {
+    pub fn update(&self, base: f64, mid: f64) {
+        let mut book = self.global.book.lock().unwrap();
+        book.positions.insert(self.key.clone(), base);
+        if mid > 0.0 {
+            book.marks.insert(self.key.1.clone(), mid);
+        }
+    }
+
+    // Whether moving this slot's position by `delta` base units keeps the
+    // account within its net limit and the coin within its share of margin.
+    // Changes that reduce net exposure, or the coin's margin, are always
+    // allowed, even above the limit.
+    pub fn allows(&self, delta: f64, mid: f64) -> bool {
+        let mut book = self.global.book.lock().unwrap();
+        let coin = &self.key.1;
+        if mid > 0.0 {
+            book.marks.insert(coin.clone(), mid);
+        }
+        let concentration = book
+            .max_coin_margin
+            .filter(|_| book.equity > 0.0)
+            .map(|(pct, leverage)| (book.equity * pct / 100.0, leverage));
+        let before = book.net_notional();
+        let margin_before = concentration.map(|(_, leverage)| book.coin_margin(coin, leverage));
+        let current = book.positions.get(&self.key).copied().unwrap_or(0.0);
+        book.positions.insert(self.key.clone(), current + delta);
+        let after = book.net_notional();
+        let margin_after = concentration.map(|(_, leverage)| book.coin_margin(coin, leverage));
+        book.positions.insert(self.key.clone(), current);
+        let concentrated = match (concentration, margin_before, margin_after) {
+            (Some((cap, _)), Some(before), Some(after)) => after > cap && after > before,
+            _ => false,
+        };
+        (after <= self.global.max_net_notional || after <= before) && !concentrated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_strategies_cannot_exceed_the_account_limit() {
+        let global = GlobalExposure::new(200_000.0);
+        let momentum = global.slot("momentum", "BTC");
+        let maker = global.slot("maker", "BTC");
+        momentum.update(2.0, 50_000.0);
+        maker.update(1.5, 50_000.0);
+        assert_eq!(global.net_notional(), 175_000.0);
+
+        // Each strategy alone would be fine; together they would be $225k
+        assert!(!maker.allows(1.0, 50_000.0));
+        assert!(maker.allows(0.5, 50_000.0));
+        // Going short offsets the other strategy's long
+        assert!(maker.allows(-3.0, 50_000.0));
+
+        // Above the limit (e.g. after a price jump) reductions still pass
+        momentum.update(2.0, 70_000.0);
+        assert!(global.net_notional() > global.max_net_notional);
+        assert!(maker.allows(-0.5, 70_000.0));
+        assert!(!maker.allows(0.1, 70_000.0));
+    }
+
+    #[test]
+    fn test_no_coin_ties_up_more_than_its_share_of_margin() {
+        let global = GlobalExposure::new(f64::INFINITY);
+        global.set_max_coin_margin_pct(40.0, 10.0);
+        let btc = global.slot("maker", "BTC");
+        let eth = global.slot("maker", "ETH");
+        btc.update(0.5, 50_000.0);
+        // Equity not known yet: no cap
+        assert!(btc.allows(1.0, 50_000.0));
+
+        // $10k equity lets a coin use $4k of margin, $40k of notional at 10x
+        global.set_equity(10_000.0);
+        assert!(btc.allows(0.3, 50_000.0));
+        assert!(!btc.allows(0.4, 50_000.0));
+        assert!(eth.allows(10.0, 3_000.0));
+        // Past the cap after a rally, only reductions pass
+        btc.update(0.9, 50_000.0);
+        assert!(btc.allows(-0.1, 50_000.0));
+        assert!(!btc.allows(0.01, 50_000.0));
+    }
+}
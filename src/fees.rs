@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+// Hyperliquid's perp tiers: (14-day volume in USD, taker bps, maker bps)
+pub(crate) const VOLUME_TIERS: [(f64, f64, f64); 7] = [
+    (0.0, 4.5, 1.5),
+    (5e6, 4.0, 1.2),
+    (25e6, 3.5, 0.8),
+    (100e6, 3.0, 0.4),
+    (500e6, 2.8, 0.0),
+    (2e9, 2.6, 0.0),
+    (7e9, 2.4, 0.0),
+];
+// (share of the exchange's 14-day maker volume, maker bps), replacing the
+// volume tier's maker fee once reached
+pub(crate) const REBATE_TIERS: [(f64, f64); 3] = [(0.005, -0.1), (0.015, -0.2), (0.03, -0.3)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolumeTier {
+    pub min_volume: f64, // 14-day volume, USD
+    pub taker_bps: f64,
+    pub maker_bps: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RebateTier {
+    pub min_maker_share: f64, // of the exchange's maker volume, 0.01 = 1%
+    pub maker_bps: f64,       // negative for a rebate
+}
+
+pub(crate) fn default_volume_tiers() -> Vec<VolumeTier> {
+    VOLUME_TIERS
+        .iter()
+        .map(|&(min_volume, taker_bps, maker_bps)| VolumeTier {
+            min_volume,
+            taker_bps,
+            maker_bps,
+        })
+        .collect()
+}
+
+pub(crate) fn default_rebate_tiers() -> Vec<RebateTier> {
+    REBATE_TIERS
+        .iter()
+        .map(|&(min_maker_share, maker_bps)| RebateTier {
+            min_maker_share,
+            maker_bps,
+        })
+        .collect()
+}
+
+// What a simulated account pays per fill, in bps of notional. A negative
+// maker fee is a rebate; the builder fee is charged on every fill on top of
+// the exchange's.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeModel {
+    pub taker_bps: f64,
+    pub maker_bps: f64,
+    pub builder_bps: f64,
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        let (_, taker_bps, maker_bps) = VOLUME_TIERS[0];
+        Self {
+            taker_bps,
+            maker_bps,
+            builder_bps: 0.0,
+        }
+    }
+}
+
+impl FeeModel {
+    // The highest tier `volume` reaches, with the maker rebate `maker_share`
+    // earns if that beats the tier's maker fee
+    pub fn for_account(
+        tiers: &[VolumeTier],
+        rebates: &[RebateTier],
+        volume: f64,
+        maker_share: f64,
+    ) -> Self {
+        let mut schedule = Self::default();
+        if let Some(tier) = tiers
+            .iter()
+            .filter(|t| volume >= t.min_volume)
+            .max_by(|a, b| a.min_volume.total_cmp(&b.min_volume))
+        {
+            schedule.taker_bps = tier.taker_bps;
+            schedule.maker_bps = tier.maker_bps;
+        }
+        if let Some(rebate) = rebates
+            .iter()
+            .filter(|r| maker_share >= r.min_maker_share)
+            .map(|r| r.maker_bps)
+            .min_by(f64::total_cmp)
+        {
+            schedule.maker_bps = schedule.maker_bps.min(rebate);
+        }
+        schedule
+    }
+
+    pub fn with_builder_bps(mut self, bps: f64) -> Self {
+        self.builder_bps = bps;
+        self
+    }
+
+    pub fn bps(&self, taker: bool) -> f64 {
+        let exchange = if taker {
+            self.taker_bps
+        } else {
+            self.maker_bps
+        };
+        exchange + self.builder_bps
+    }
+
+    // Fee on a fill of `notional`; negative when the rebate outweighs the
+    // builder fee
+    pub fn fee(&self, notional: f64, taker: bool) -> f64 {
+        notional * self.bps(taker) / 10_000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers_and_rebates_pick_the_account_fees() {
+        let (tiers, rebates) = (default_volume_tiers(), default_rebate_tiers());
+        let base = FeeModel::for_account(&tiers, &rebates, 1e6, 0.0);
+        assert_eq!(base, FeeModel::default());
+        assert_eq!(base.fee(10_000.0, true), 4.5);
+
+        let tier = FeeModel::for_account(&tiers, &rebates, 30e6, 0.0);
+        assert_eq!((tier.taker_bps, tier.maker_bps), (3.5, 0.8));
+
+        // A 2% maker share earns the second rebate tier; the builder's cut
+        // comes out of it
+        let maker = FeeModel::for_account(&tiers, &rebates, 30e6, 0.02).with_builder_bps(0.1);
+        assert_eq!(maker.maker_bps, -0.2);
+        assert!((maker.fee(10_000.0, false) + 0.1).abs() < 1e-12);
+        assert!((maker.fee(10_000.0, true) - 3.6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,240 @@
+// C ABI over the signal/quoting/risk core for embedding into non-Rust execution
+// stacks. Declarations are in include/hl_engine.h; build the library with
+//
+//     cargo rustc --release --lib --features ffi --crate-type cdylib   (or staticlib)
+//
+// The caller owns execution: it pushes market data, polls quote intents, and
+// reports its own fills back, which is the only thing that moves the position.
+use std::{
+    collections::VecDeque,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{QuoteLayerManager, QuoteProposal, RiskManager, SignalEngine};
+
+const HL_ABI_VERSION: u32 = 1;
+
+pub const HL_OK: i32 = 0;
+pub const HL_ERR_NULL: i32 = -1;
+pub const HL_ERR_INVALID: i32 = -2;
+pub const HL_ERR_PANIC: i32 = -3;
+
+pub struct HlEngine {
+    signal: SignalEngine,
+    risk: RiskManager,
+    pending: VecDeque<QuoteProposal>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct HlQuoteIntent {
+    pub is_buy: i32,
+    pub price: f64,
+    pub size: f64,
+}
+
+// Functions taking an engine pointer are unsafe: it must be null or come from
+// `hl_engine_new` and not have been freed.
+unsafe fn guarded(engine: *mut HlEngine, f: impl FnOnce(&mut HlEngine) -> i32) -> i32 {
+    let Some(engine) = engine.as_mut() else {
+        return HL_ERR_NULL;
+    };
+    catch_unwind(AssertUnwindSafe(|| f(engine))).unwrap_or(HL_ERR_PANIC)
+}
+
+#[no_mangle]
+pub extern "C" fn hl_abi_version() -> u32 {
+    HL_ABI_VERSION
+}
+
+// Returns null if max_position is not a positive finite number
+#[no_mangle]
+pub extern "C" fn hl_engine_new(max_position: f64) -> *mut HlEngine {
+    if !(max_position.is_finite() && max_position > 0.0) {
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new(HlEngine {
+        signal: SignalEngine::new(),
+        risk: RiskManager::new(max_position),
+        pending: VecDeque::new(),
+    }))
+}
+
+/// # Safety
+/// `engine` must come from `hl_engine_new` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_free(engine: *mut HlEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Updates signals from a top-of-book snapshot and replaces the pending intents
+/// with the risk-checked quotes for this book.
+///
+/// # Safety
+/// `engine` must be null or a live pointer from `hl_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_push_book(
+    engine: *mut HlEngine,
+    time_ms: u64,
+    bid: f64,
+    ask: f64,
+    bid_volume: f64,
+    ask_volume: f64,
+) -> i32 {
+    guarded(engine, |e| {
+        if !(bid > 0.0 && ask > bid && ask.is_finite()) {
+            return HL_ERR_INVALID;
+        }
+        e.signal
+            .process_l2_book(time_ms, bid, ask, bid_volume, ask_volume);
+        let quotes = QuoteLayerManager::build_quotes(&e.signal.state);
+        // Evaluate on a copy: the position only moves on reported fills
+        let mut scratch = e.signal.state.clone();
+        e.pending = e.risk.evaluate(&mut scratch, &quotes).into();
+        HL_OK
+    })
+}
+
+/// # Safety
+/// `engine` must be null or a live pointer from `hl_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_push_trade(
+    engine: *mut HlEngine,
+    time_ms: u64,
+    price: f64,
+    size: f64,
+    is_buy: i32,
+) -> i32 {
+    guarded(engine, |e| {
+        if !(price.is_finite() && size.is_finite() && price > 0.0 && size > 0.0) {
+            return HL_ERR_INVALID;
+        }
+        e.signal.process_trade(price, size, is_buy != 0, time_ms);
+        HL_OK
+    })
+}
+
+/// Copies up to `capacity` pending intents into `out` and returns how many were
+/// written (or a negative error); intents that did not fit stay queued.
+///
+/// # Safety
+/// `engine` must be null or a live pointer from `hl_engine_new`, and `out` must
+/// point to at least `capacity` writable `HlQuoteIntent`s.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_poll_quotes(
+    engine: *mut HlEngine,
+    out: *mut HlQuoteIntent,
+    capacity: usize,
+) -> i64 {
+    if out.is_null() && capacity > 0 {
+        return HL_ERR_NULL as i64;
+    }
+    let mut written = 0;
+    let status = guarded(engine, |e| {
+        while written < capacity {
+            let Some(q) = e.pending.pop_front() else {
+                break;
+            };
+            out.add(written).write(HlQuoteIntent {
+                is_buy: (q.side == "Buy") as i32,
+                price: q.price,
+                size: q.size,
+            });
+            written += 1;
+        }
+        HL_OK
+    });
+    if status == HL_OK {
+        written as i64
+    } else {
+        status as i64
+    }
+}
+
+/// # Safety
+/// `engine` must be null or a live pointer from `hl_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_on_fill(
+    engine: *mut HlEngine,
+    is_buy: i32,
+    price: f64,
+    size: f64,
+) -> i32 {
+    guarded(engine, |e| {
+        if !(price.is_finite() && size.is_finite() && price > 0.0 && size > 0.0) {
+            return HL_ERR_INVALID;
+        }
+        let signed = if is_buy != 0 { size } else { -size };
+        e.signal.state.position.base += signed;
+        e.signal.state.position.quote -= signed * price;
+        HL_OK
+    })
+}
+
+/// NaN for a null engine.
+///
+/// # Safety
+/// `engine` must be null or a live pointer from `hl_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hl_engine_position(engine: *const HlEngine) -> f64 {
+    engine
+        .as_ref()
+        .map_or(f64::NAN, |e| e.signal.state.position.base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_round_trip() {
+        unsafe { round_trip() }
+    }
+
+    unsafe fn round_trip() {
+        assert_eq!(hl_abi_version(), HL_ABI_VERSION);
+        assert!(hl_engine_new(-1.0).is_null());
+        assert_eq!(
+            hl_engine_push_book(ptr::null_mut(), 0, 1.0, 2.0, 1.0, 1.0),
+            HL_ERR_NULL
+        );
+
+        let engine = hl_engine_new(5.0);
+        assert_eq!(
+            hl_engine_push_book(engine, 1, 101.0, 100.0, 1.0, 1.0),
+            HL_ERR_INVALID
+        );
+        assert_eq!(
+            hl_engine_push_trade(engine, 1, f64::NAN, 1.0, 1),
+            HL_ERR_INVALID
+        );
+        let mut out = [HlQuoteIntent {
+            is_buy: 0,
+            price: 0.0,
+            size: 0.0,
+        }; 4];
+        let mut polled = 0;
+        for t in 0..50u64 {
+            // Bid-heavy book drifting up
+            let bid = 100.0 + t as f64 * 0.5;
+            assert_eq!(
+                hl_engine_push_book(engine, t * 500, bid, bid + 1.0, 9.0, 1.0),
+                HL_OK
+            );
+            assert_eq!(
+                hl_engine_push_trade(engine, t * 500, bid + 1.0, 0.5, 1),
+                HL_OK
+            );
+            polled += hl_engine_poll_quotes(engine, out.as_mut_ptr(), out.len());
+        }
+        assert!(polled > 0);
+        // Quotes alone never move the position; fills do
+        assert_eq!(hl_engine_position(engine), 0.0);
+        assert_eq!(hl_engine_on_fill(engine, 1, 100.0, 2.0), HL_OK);
+        assert_eq!(hl_engine_position(engine), 2.0);
+        hl_engine_free(engine);
+    }
+}
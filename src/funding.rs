@@ -0,0 +1,189 @@
+use chrono::Utc;
+use log::{info, warn};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{prelude::*, InfoClient, StrategyConfig};
+
+pub(crate) const FUNDING_INTERVAL_MS: u64 = 3_600_000; // Funding is paid every hour, on the hour
+pub(crate) const FULL_BIAS_RATE: f64 = 0.0001; // Hourly rate (1bp) that earns the full bias
+pub(crate) const FUNDING_WEIGHT: f64 = 0.25; // Bias added to the flow pressure at FULL_BIAS_RATE
+pub(crate) const FUNDING_REDUCE_MS: u64 = 60_000; // How long before an unfavourable payment the position is cut
+pub(crate) const FUNDING_REDUCE_SHARE: f64 = 0.5; // Share of the position cut before it
+
+#[derive(Debug, Default)]
+struct FundingRates {
+    predicted: Option<f64>,       // hourly, from the asset context feed
+    realized: Option<(u64, f64)>, // (time, hourly rate) of the last payment
+}
+
+// Funding of one coin, and what to do about it: a bias for `SignalEngine`
+// leaning the maker toward the side that earns funding, and a reduction of the
+// side that pays it shortly before each payment. The rate is the predicted one
+// from the asset context feed when there is one, otherwise the last paid,
+// polled from `funding_history`. Clones share the same rates.
+#[derive(Debug, Clone)]
+pub struct FundingMonitor {
+    pub coin: String,
+    pub weight: f64,
+    pub reduce_before_ms: u64, // 0 never reduces
+    pub reduce_share: f64,
+    rates: Arc<Mutex<FundingRates>>,
+    reduced_for: Option<u64>, // payment time the position was last cut ahead of
+}
+
+impl FundingMonitor {
+    pub fn new(coin: &str) -> Self {
+        Self {
+            coin: coin.to_string(),
+            weight: FUNDING_WEIGHT,
+            reduce_before_ms: FUNDING_REDUCE_MS,
+            reduce_share: FUNDING_REDUCE_SHARE,
+            rates: Arc::default(),
+            reduced_for: None,
+        }
+    }
+
+    pub fn with_reduce(mut self, before_ms: u64, share: f64) -> Self {
+        self.reduce_before_ms = before_ms;
+        self.reduce_share = share.clamp(0.0, 1.0);
+        self
+    }
+
+    // Weight and reduction window from the coin's `StrategyConfig`
+    pub fn configure(&mut self, config: &StrategyConfig) {
+        self.weight = config.funding_weight;
+        self.reduce_before_ms = config.funding_reduce_ms;
+    }
+
+    pub fn on_predicted(&self, rate: f64) {
+        if rate.is_finite() {
+            self.rates
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .predicted = Some(rate);
+        }
+    }
+
+    pub fn on_realized(&self, time: u64, rate: f64) {
+        let mut rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        if rate.is_finite() && rates.realized.is_none_or(|(last, _)| time >= last) {
+            rates.realized = Some((time, rate));
+        }
+    }
+
+    // Hourly rate longs pay shorts (negative: shorts pay longs)
+    pub fn rate(&self) -> Option<f64> {
+        let rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        rates.predicted.or(rates.realized.map(|(_, rate)| rate))
+    }
+
+    // -weight to weight; positive leans toward holding longs
+    pub fn bias(&self) -> f64 {
+        self.rate().map_or(0.0, |rate| {
+            -(rate / FULL_BIAS_RATE).clamp(-1.0, 1.0) * self.weight
+        })
+    }
+
+    // First payment after `time`
+    pub fn next_payment(time: u64) -> u64 {
+        (time / FUNDING_INTERVAL_MS + 1) * FUNDING_INTERVAL_MS
+    }
+
+    // Whether to cut a `base` position at book `time`: it pays funding and the
+    // next payment is within `reduce_before_ms`. Fires once per payment.
+    pub fn should_reduce(&mut self, time: u64, base: f64) -> bool {
+        let Some(rate) = self.rate() else {
+            return false;
+        };
+        let next = Self::next_payment(time);
+        if self.reduce_before_ms == 0
+            || base * rate <= 0.0
+            || next - time > self.reduce_before_ms
+            || self.reduced_for == Some(next)
+        {
+            return false;
+        }
+        self.reduced_for = Some(next);
+        info!(
+            "[Funding] {} pays {:.4}%/h on a {base:.4} position, cutting it before the payment",
+            self.coin,
+            rate * 100.0
+        );
+        true
+    }
+
+    pub async fn load(&self, info: &InfoClient) -> Result<()> {
+        let since = (Utc::now().timestamp_millis() as u64).saturating_sub(2 * FUNDING_INTERVAL_MS);
+        let history = info.funding_history(self.coin.clone(), since, None).await?;
+        for payment in history {
+            if let Ok(rate) = payment.funding_rate.parse() {
+                self.on_realized(payment.time, rate);
+            }
+        }
+        Ok(())
+    }
+
+    // Reloads the last paid rate every `period`, until every clone is dropped
+    pub fn poll(&self, info: Arc<InfoClient>, period: Duration) {
+        let rates = Arc::downgrade(&self.rates);
+        let coin = self.coin.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(period);
+            loop {
+                timer.tick().await;
+                let Some(rates) = rates.upgrade() else {
+                    break;
+                };
+                let monitor = Self {
+                    rates,
+                    ..Self::new(&coin)
+                };
+                if let Err(e) = monitor.load(&info).await {
+                    warn!("[Funding] {coin} funding history failed: {e}");
+                }
+            }
+        });
+    }
+
+    // Rate, bias and time to the next payment, for the admin `funding` command
+    pub fn summary(&self, time: u64) -> String {
+        let minutes = (Self::next_payment(time) - time) / 60_000;
+        match self.rate() {
+            Some(rate) => format!(
+                "{} funding {:.4}%/h | bias {:+.2} | next payment in {minutes}m",
+                self.coin,
+                rate * 100.0,
+                self.bias()
+            ),
+            None => format!("{} funding not known yet", self.coin),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bias_favours_the_earning_side_and_cuts_the_paying_one() {
+        let mut funding = FundingMonitor::new("BTC").with_reduce(60_000, 0.5);
+        assert_eq!(funding.bias(), 0.0);
+        // Longs pay: lean short, fully at the full-bias rate
+        funding.on_realized(0, 0.0002);
+        assert_eq!(funding.bias(), -FUNDING_WEIGHT);
+        // The predicted rate wins over the last paid one
+        funding.on_predicted(-0.00005);
+        assert_eq!(funding.bias(), FUNDING_WEIGHT / 2.0);
+
+        // Shorts pay: a short is cut once, inside the window before the hour
+        let hour = FUNDING_INTERVAL_MS;
+        assert!(!funding.should_reduce(hour - 120_000, -1.0));
+        assert!(!funding.should_reduce(hour - 30_000, 1.0));
+        assert!(funding.should_reduce(hour - 30_000, -1.0));
+        assert!(!funding.should_reduce(hour - 10_000, -1.0));
+        assert!(funding.should_reduce(2 * hour - 10_000, -1.0));
+    }
+}
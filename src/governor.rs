@@ -0,0 +1,131 @@
+use log::info;
+use std::collections::VecDeque;
+
+use crate::Metrics;
+
+pub(crate) const EDGE_WINDOW: usize = 20; // Round trips the rolling edge is taken over
+pub(crate) const EDGE_MIN_TRADES: usize = 5; // Round trips before the governor acts
+pub(crate) const EDGE_TARGET_BPS: f64 = 2.0; // Net edge per trade that earns the full trading rate
+pub(crate) const GOVERNOR_MAX_FACTOR: f64 = 3.0; // Strictness at no edge at all
+
+// Trades less when trading stops paying: keeps the net edge of the last
+// `window` round trips (gross bps of the entry price, less `cost_bps` for the
+// fees of the round trip) and turns its mean into a strictness factor. The
+// factor is 1 while the edge is at or above `target_bps` and rises linearly to
+// `max_factor` as it falls to zero, for strategies to scale their entry
+// thresholds and cooldowns by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeGovernor {
+    pub window: usize,
+    pub target_bps: f64,
+    pub max_factor: f64,
+    pub cost_bps: f64,
+    edges: VecDeque<f64>, // net bps per round trip
+    strict: bool,         // the factor was above 1 after the last round trip
+}
+
+impl Default for TradeGovernor {
+    fn default() -> Self {
+        Self::new(EDGE_WINDOW)
+    }
+}
+
+impl TradeGovernor {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            target_bps: EDGE_TARGET_BPS,
+            max_factor: GOVERNOR_MAX_FACTOR,
+            cost_bps: 0.0,
+            edges: VecDeque::new(),
+            strict: false,
+        }
+    }
+
+    pub fn with_target(mut self, target_bps: f64, max_factor: f64) -> Self {
+        self.target_bps = target_bps.max(f64::EPSILON);
+        self.max_factor = max_factor.max(1.0);
+        self
+    }
+
+    // Fees of a round trip in bps, taken off every trade's gross edge
+    pub fn with_cost_bps(mut self, bps: f64) -> Self {
+        self.cost_bps = bps;
+        self
+    }
+
+    pub fn on_round_trip(&mut self, gross_bps: f64) {
+        self.edges.push_back(gross_bps - self.cost_bps);
+        while self.edges.len() > self.window {
+            self.edges.pop_front();
+        }
+        let strict = self.factor() > 1.0;
+        if strict != self.strict {
+            self.strict = strict;
+            match self.edge_bps() {
+                Some(edge) if strict => info!(
+                    "[Governor] Edge {edge:.2}bps per trade, trading {:.1}x less often",
+                    self.factor()
+                ),
+                _ => info!("[Governor] Edge back on target, trading at the full rate"),
+            }
+        }
+    }
+
+    // Mean net edge per round trip in bps; None before EDGE_MIN_TRADES
+    pub fn edge_bps(&self) -> Option<f64> {
+        if self.edges.len() < EDGE_MIN_TRADES.min(self.window) {
+            return None;
+        }
+        Some(self.edges.iter().sum::<f64>() / self.edges.len() as f64)
+    }
+
+    // Multiplier for entry thresholds and cooldowns, 1.0 to `max_factor`
+    pub fn factor(&self) -> f64 {
+        let Some(edge) = self.edge_bps() else {
+            return 1.0;
+        };
+        let shortfall = (1.0 - edge / self.target_bps).clamp(0.0, 1.0);
+        1.0 + (self.max_factor - 1.0) * shortfall
+    }
+
+    pub fn export(&self, metrics: &Metrics) {
+        if let Some(edge) = self.edge_bps() {
+            metrics.set("governor_edge_bps", edge);
+        }
+        metrics.set("governor_factor", self.factor());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thinning_edge_makes_trading_stricter() {
+        let mut governor = TradeGovernor::new(10)
+            .with_target(4.0, 3.0)
+            .with_cost_bps(1.0);
+        for _ in 0..EDGE_MIN_TRADES - 1 {
+            governor.on_round_trip(0.0);
+        }
+        // Too few trades to judge
+        assert_eq!(governor.factor(), 1.0);
+        for _ in 0..10 {
+            governor.on_round_trip(6.0);
+        }
+        assert_eq!(governor.edge_bps(), Some(5.0));
+        assert_eq!(governor.factor(), 1.0);
+
+        // Half the target after fees: halfway to the maximum
+        for _ in 0..10 {
+            governor.on_round_trip(3.0);
+        }
+        assert_eq!(governor.factor(), 2.0);
+        // Paying fees for nothing: as strict as it gets
+        for _ in 0..10 {
+            governor.on_round_trip(0.5);
+        }
+        assert_eq!(governor.factor(), 3.0);
+    }
+}
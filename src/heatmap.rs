@@ -0,0 +1,159 @@
+use chrono::{DateTime, Datelike, Timelike};
+use std::io::{self, Write};
+
+use crate::UserFillsResponse;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeatmapCell {
+    pub fills: u64,
+    pub volume: f64, // notional
+    pub pnl: f64,    // realized, net of fees
+}
+
+impl HeatmapCell {
+    fn add(&mut self, other: &HeatmapCell) {
+        self.fills += other.fills;
+        self.volume += other.volume;
+        self.pnl += other.pnl;
+    }
+}
+
+// Fills and PnL by UTC day of week and hour of day, for when a strategy
+// actually makes money. `hour_profile` pools the days into the per-hour
+// evidence a time-of-day parameter profile is set from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerformanceHeatmap {
+    cells: [[HeatmapCell; 24]; 7], // [Monday first][hour]
+}
+
+impl PerformanceHeatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Exchange fills, each counted at its closed PnL less its fee
+    pub fn from_user_fills(fills: &[UserFillsResponse]) -> Self {
+        let mut heatmap = Self::new();
+        for f in fills {
+            let parse = |v: &str| v.parse::<f64>().unwrap_or(0.0);
+            let notional = parse(&f.px) * parse(&f.sz);
+            heatmap.on_fill(f.time, notional, parse(&f.closed_pnl) - parse(&f.fee));
+        }
+        heatmap
+    }
+
+    pub fn on_fill(&mut self, time_ms: u64, notional: f64, pnl: f64) {
+        let Some(time) = DateTime::from_timestamp_millis(time_ms as i64) else {
+            return;
+        };
+        let day = time.weekday().num_days_from_monday() as usize;
+        let cell = &mut self.cells[day][time.hour() as usize];
+        cell.fills += 1;
+        cell.volume += notional;
+        cell.pnl += pnl;
+    }
+
+    // `weekday` 0 is Monday
+    pub fn cell(&self, weekday: usize, hour: usize) -> HeatmapCell {
+        self.cells[weekday][hour]
+    }
+
+    // Every day's cells for each hour of the day
+    pub fn hour_profile(&self) -> [HeatmapCell; 24] {
+        let mut hours = [HeatmapCell::default(); 24];
+        for day in &self.cells {
+            for (total, cell) in hours.iter_mut().zip(day) {
+                total.add(cell);
+            }
+        }
+        hours
+    }
+
+    // Table of `value` (e.g. `|c| c.pnl`) with a row per weekday and a column
+    // per hour, plus row totals and an `All` row pooling the days
+    pub fn write_csv(
+        &self,
+        mut out: impl Write,
+        value: impl Fn(&HeatmapCell) -> f64,
+    ) -> io::Result<()> {
+        let hours: Vec<_> = (0..24).map(|h| format!("{h:02}")).collect();
+        writeln!(out, "day,{},total", hours.join(","))?;
+        let rows = WEEKDAYS
+            .iter()
+            .zip(&self.cells)
+            .map(|(name, day)| (*name, *day))
+            .chain([("All", self.hour_profile())]);
+        for (name, day) in rows {
+            let values: Vec<_> = day.iter().map(|c| format!("{:.4}", value(c))).collect();
+            let total: f64 = day.iter().map(&value).sum();
+            writeln!(out, "{name},{},{total:.4}", values.join(","))?;
+        }
+        Ok(())
+    }
+
+    // The best and worst hours of the pooled profile
+    pub fn summary(&self) -> String {
+        let mut hours: Vec<_> = self
+            .hour_profile()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| c.fills > 0)
+            .collect();
+        if hours.is_empty() {
+            return "no fills".to_string();
+        }
+        hours.sort_by(|a, b| b.1.pnl.total_cmp(&a.1.pnl));
+        let show = |(hour, c): &(usize, HeatmapCell)| {
+            format!("{hour:02}h {:+.2} over {} fills", c.pnl, c.fills)
+        };
+        format!(
+            "best {} | worst {}",
+            show(&hours[0]),
+            show(&hours[hours.len() - 1])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_fills_by_weekday_and_hour() {
+        // 2024-01-01 was a Monday
+        let monday = 1_704_067_200_000;
+        let hour = 3_600_000;
+        let mut heatmap = PerformanceHeatmap::new();
+        heatmap.on_fill(monday + 9 * hour, 100.0, 1.5);
+        heatmap.on_fill(monday + 9 * hour + 60_000, 50.0, -0.5);
+        heatmap.on_fill(monday + 24 * hour + 9 * hour, 10.0, 2.0);
+        heatmap.on_fill(monday + 6 * 24 * hour + 23 * hour, 10.0, -3.0);
+
+        assert_eq!(
+            heatmap.cell(0, 9),
+            HeatmapCell {
+                fills: 2,
+                volume: 150.0,
+                pnl: 1.0
+            }
+        );
+        assert_eq!(heatmap.cell(1, 9).pnl, 2.0);
+        assert_eq!(heatmap.cell(6, 23).pnl, -3.0);
+        assert_eq!(heatmap.hour_profile()[9].fills, 3);
+        assert_eq!(
+            heatmap.summary(),
+            "best 09h +3.00 over 3 fills | worst 23h -3.00 over 1 fills"
+        );
+
+        let mut csv = Vec::new();
+        heatmap.write_csv(&mut csv, |c| c.pnl).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].starts_with("day,00,01,"));
+        assert!(lines[1].starts_with("Mon,") && lines[1].ends_with(",1.0000"));
+        assert!(lines[8].starts_with("All,") && lines[8].ends_with(",0.0000"));
+    }
+}
@@ -77,6 +77,37 @@ pub fn bps_diff(x: f64, y: f64) -> u16 {
     }
 }
 
+/// Walks `levels` (best price first, as `(px, sz)`) until `sz` is covered and returns
+/// the worst price touched, capped at `max_slippage` away from the top of book.
+/// If the book is too thin to cover `sz` inside the bound, the bound itself is returned
+/// so an IOC at that price fills what it can.
+pub(crate) fn protected_limit_px(
+    levels: &[(f64, f64)],
+    sz: f64,
+    is_buy: bool,
+    max_slippage: f64,
+) -> Option<f64> {
+    let &(top_px, _) = levels.first()?;
+    let bound = if is_buy {
+        top_px * (1.0 + max_slippage)
+    } else {
+        top_px * (1.0 - max_slippage)
+    };
+    let within = |px: f64| if is_buy { px <= bound } else { px >= bound };
+
+    let mut remaining = sz;
+    for &(px, level_sz) in levels {
+        if !within(px) {
+            break;
+        }
+        remaining -= level_sz;
+        if remaining <= EPSILON {
+            return Some(px);
+        }
+    }
+    Some(bound)
+}
+
 #[derive(Copy, Clone)]
 pub enum BaseUrl {
     Localhost,
@@ -102,6 +133,21 @@ lazy_static! {
 mod tests {
     use super::*;
 
+    #[test]
+    fn protected_limit_px_test() {
+        let asks = [(100.0, 1.0), (100.5, 2.0), (101.0, 5.0), (103.0, 10.0)];
+        assert_eq!(protected_limit_px(&asks, 0.5, true, 0.02), Some(100.0));
+        assert_eq!(protected_limit_px(&asks, 2.5, true, 0.02), Some(100.5));
+        assert_eq!(protected_limit_px(&asks, 6.0, true, 0.02), Some(101.0));
+        // Depth inside the bound runs out, so the bound caps the price
+        assert_eq!(protected_limit_px(&asks, 20.0, true, 0.02), Some(102.0));
+
+        let bids = [(100.0, 1.0), (99.0, 1.0)];
+        assert_eq!(protected_limit_px(&bids, 1.5, false, 0.05), Some(99.0));
+        assert_eq!(protected_limit_px(&bids, 1.5, false, 0.005), Some(99.5));
+        assert_eq!(protected_limit_px(&[], 1.0, false, 0.01), None);
+    }
+
     #[test]
     fn float_to_string_for_hashing_test() {
         assert_eq!(float_to_string_for_hashing(0.), "0".to_string());
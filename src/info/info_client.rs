@@ -7,14 +7,14 @@ use crate::{
     prelude::*,
     req::HttpClient,
     ws::{Subscription, WsManager},
-    BaseUrl, Error, Message, OrderStatusResponse, ReferralResponse, UserFeesResponse,
-    UserFundingResponse, UserTokenBalanceResponse,
+    BaseUrl, Error, Message, OrderStatusResponse, RateLimiter, ReferralResponse, RequestClass,
+    UserFeesResponse, UserFundingResponse, UserTokenBalanceResponse,
 };
 
 use ethers::types::H160;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 use tokio::sync::mpsc::UnboundedSender;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -60,6 +60,12 @@ pub enum InfoRequest {
         user: H160,
     },
     #[serde(rename_all = "camelCase")]
+    UserFillsByTime {
+        user: H160,
+        start_time: u64,
+        end_time: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
     FundingHistory {
         coin: String,
         start_time: u64,
@@ -89,11 +95,27 @@ pub enum InfoRequest {
     },
 }
 
+impl InfoRequest {
+    // Weight the exchange counts against the IP's request budget
+    fn weight(&self) -> f64 {
+        match self {
+            InfoRequest::L2Book { .. }
+            | InfoRequest::AllMids
+            | InfoRequest::UserState { .. }
+            | InfoRequest::UserTokenBalances { .. }
+            | InfoRequest::OrderStatus { .. } => 2.0,
+            _ => 20.0,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct InfoClient {
     pub http_client: HttpClient,
     pub(crate) ws_manager: Option<WsManager>,
     reconnect: bool,
+    stale_after: Option<Duration>,
+    limiter: Option<RateLimiter>,
 }
 
 impl InfoClient {
@@ -120,21 +142,47 @@ impl InfoClient {
             http_client: HttpClient { client, base_url },
             ws_manager: None,
             reconnect,
+            stale_after: None,
+            limiter: None,
         })
     }
 
+    // Treats the websocket as dropped once it has sent nothing for `limit`,
+    // reconnecting if enabled. Only for feeds that never go quiet for that
+    // long, such as books; set it before the first subscription.
+    pub fn with_stale_timeout(mut self, limit: Duration) -> Self {
+        self.stale_after = Some(limit);
+        self
+    }
+
+    // Waits for `limiter` before each request, leaving what it reserves to
+    // orders and cancels sharing it
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    async fn ws_manager(&self) -> Result<WsManager> {
+        let http = HttpClient {
+            client: self.http_client.client.clone(),
+            base_url: self.http_client.base_url.clone(),
+        };
+        WsManager::new(
+            format!("ws{}/ws", &self.http_client.base_url[4..]),
+            self.reconnect,
+            self.stale_after,
+            http,
+        )
+        .await
+    }
+
     pub async fn subscribe(
         &mut self,
         subscription: Subscription,
         sender_channel: UnboundedSender<Message>,
     ) -> Result<u32> {
         if self.ws_manager.is_none() {
-            let ws_manager = WsManager::new(
-                format!("ws{}/ws", &self.http_client.base_url[4..]),
-                self.reconnect,
-            )
-            .await?;
-            self.ws_manager = Some(ws_manager);
+            self.ws_manager = Some(self.ws_manager().await?);
         }
 
         let identifier =
@@ -149,12 +197,7 @@ impl InfoClient {
 
     pub async fn unsubscribe(&mut self, subscription_id: u32) -> Result<()> {
         if self.ws_manager.is_none() {
-            let ws_manager = WsManager::new(
-                format!("ws{}/ws", &self.http_client.base_url[4..]),
-                self.reconnect,
-            )
-            .await?;
-            self.ws_manager = Some(ws_manager);
+            self.ws_manager = Some(self.ws_manager().await?);
         }
 
         self.ws_manager
@@ -170,6 +213,11 @@ impl InfoClient {
     ) -> Result<T> {
         let data =
             serde_json::to_string(&info_request).map_err(|e| Error::JsonParse(e.to_string()))?;
+        if let Some(limiter) = &self.limiter {
+            limiter
+                .acquire(RequestClass::Info, info_request.weight())
+                .await;
+        }
 
         let return_data = self.http_client.post("/info", data).await?;
         serde_json::from_str(&return_data).map_err(|e| Error::JsonParse(e.to_string()))
@@ -225,6 +273,20 @@ impl InfoClient {
         self.send_info_request(input).await
     }
 
+    pub async fn user_fills_by_time(
+        &self,
+        user: H160,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> Result<Vec<UserFillsResponse>> {
+        let input = InfoRequest::UserFillsByTime {
+            user,
+            start_time,
+            end_time,
+        };
+        self.send_info_request(input).await
+    }
+
     pub async fn funding_history(
         &self,
         coin: String,
@@ -239,6 +301,32 @@ impl InfoClient {
         self.send_info_request(input).await
     }
 
+    // Every funding interval from `start_time` to `end_time`. The endpoint caps
+    // each response, so this pages forward from the last returned timestamp.
+    pub async fn funding_history_range(
+        &self,
+        coin: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<FundingHistoryResponse>> {
+        let mut history: Vec<FundingHistoryResponse> = Vec::new();
+        let mut cursor = start_time;
+        while cursor < end_time {
+            let page = self
+                .funding_history(coin.to_string(), cursor, Some(end_time))
+                .await?;
+            let Some(last) = page.last().map(|f| f.time) else {
+                break;
+            };
+            history.extend(page.into_iter().filter(|f| f.time >= cursor));
+            if last < cursor {
+                break;
+            }
+            cursor = last + 1;
+        }
+        Ok(history)
+    }
+
     pub async fn user_funding_history(
         &self,
         user: H160,
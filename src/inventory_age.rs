@@ -0,0 +1,117 @@
+// === Inventory half-life ===
+// Staying flat is enforced by price: inventory is expected to decay with a
+// target half-life from its peak, and the further the position lags that
+// schedule, the harder the reducing quote leans towards (but never through)
+// the far touch and the adding quote backs away.
+#[derive(Debug, Clone)]
+pub struct InventoryHalfLife {
+    pub half_life_ms: f64,
+    pub max_skew_bps: f64, // price shift, in bps of mid, once the schedule is fully missed
+    opened_ms: Option<u64>,
+    peak: f64, // largest |position| since it was opened
+    sign: f64,
+}
+
+impl InventoryHalfLife {
+    pub fn new(half_life_ms: f64, max_skew_bps: f64) -> Self {
+        Self {
+            half_life_ms,
+            max_skew_bps,
+            opened_ms: None,
+            peak: 0.0,
+            sign: 0.0,
+        }
+    }
+
+    // Feed the current position; the clock restarts when it goes flat or flips
+    pub fn on_position(&mut self, now_ms: u64, base: f64) {
+        if base.abs() <= f64::EPSILON {
+            self.opened_ms = None;
+            self.peak = 0.0;
+            self.sign = 0.0;
+            return;
+        }
+        if self.opened_ms.is_none() || base.signum() != self.sign {
+            self.opened_ms = Some(now_ms);
+            self.peak = 0.0;
+            self.sign = base.signum();
+        }
+        self.peak = self.peak.max(base.abs());
+    }
+
+    pub fn held_ms(&self, now_ms: u64) -> u64 {
+        self.opened_ms.map_or(0, |t| now_ms.saturating_sub(t))
+    }
+
+    // Position the schedule allows by now: the peak halved every half-life
+    pub fn target(&self, now_ms: u64) -> f64 {
+        if self.half_life_ms <= 0.0 {
+            return 0.0;
+        }
+        self.peak * 0.5f64.powf(self.held_ms(now_ms) as f64 / self.half_life_ms)
+    }
+
+    // How far behind schedule the position is, from 0.0 (on target) to 1.0
+    // (nothing reduced after many half-lives)
+    pub fn penalty(&self, now_ms: u64, base: f64) -> f64 {
+        if self.peak <= 0.0 {
+            return 0.0;
+        }
+        ((base.abs() - self.target(now_ms)) / self.peak).clamp(0.0, 1.0)
+    }
+
+    // Shifts a quote by the penalty: a reducing quote moves towards the far
+    // touch, stopping a tick short of crossing, and an adding quote moves away
+    pub fn adjust(
+        &self,
+        now_ms: u64,
+        base: f64,
+        is_buy: bool,
+        px: f64,
+        (best_bid, best_ask): (f64, f64),
+        tick: f64,
+    ) -> f64 {
+        let mid = (best_bid + best_ask) / 2.0;
+        let skew = mid * self.max_skew_bps / 10_000.0 * self.penalty(now_ms, base);
+        if skew <= 0.0 {
+            return px;
+        }
+        let reduces = (is_buy && base < 0.0) || (!is_buy && base > 0.0);
+        match (is_buy, reduces) {
+            (true, true) => (px + skew).min(best_ask - tick).max(px),
+            (false, true) => (px - skew).max(best_bid + tick).min(px),
+            (true, false) => px - skew,
+            (false, false) => px + skew,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalty_grows_while_inventory_lags_its_half_life() {
+        let mut clock = InventoryHalfLife::new(10_000.0, 4.0);
+        clock.on_position(0, 2.0);
+        assert_eq!(clock.penalty(0, 2.0), 0.0);
+        // Untouched for one half-life: target 1.0, lagging by half the peak
+        clock.on_position(10_000, 2.0);
+        assert!((clock.penalty(10_000, 2.0) - 0.5).abs() < 1e-9);
+        // Reduced on schedule: no penalty
+        assert_eq!(clock.penalty(10_000, 1.0), 0.0);
+
+        // Long inventory: the ask leans down towards the bid, the bid backs off
+        let ask = clock.adjust(10_000, 2.0, false, 100.05, (99.95, 100.05), 0.01);
+        assert!((ask - 100.03).abs() < 1e-9);
+        let bid = clock.adjust(10_000, 2.0, true, 99.95, (99.95, 100.05), 0.01);
+        assert!((bid - 99.93).abs() < 1e-9);
+        // Never crosses: capped a tick above the bid
+        let late = clock.adjust(1_000_000, 2.0, false, 100.01, (100.0, 100.01), 0.001);
+        assert!((late - 100.001).abs() < 1e-9);
+
+        // Flipping short restarts the clock
+        clock.on_position(20_000, -1.0);
+        assert_eq!(clock.held_ms(20_000), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use chrono::Utc;
+use ethers::types::H160;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+use uuid::Uuid;
+
+use crate::{prelude::*, Error};
+
+pub(crate) const LOG_FORMAT_ENV: &str = "HL_LOG_FORMAT"; // `json` for JSON log lines
+
+// Identifies one run of a bot: the exact build and parameters it ran with. Its
+// `run_id` is stamped on every log line and journal record of the run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunManifest {
+    pub run_id: String,
+    pub started_at: u64,
+    pub binary: String,
+    pub version: String,
+    pub git_hash: String,
+    pub build_profile: String,
+    pub network: String,
+    pub wallet: Option<H160>,
+    pub config: Value, // fully resolved parameters
+}
+
+impl RunManifest {
+    pub fn new(binary: &str, network: &str, wallet: Option<H160>, config: Value) -> Self {
+        Self {
+            run_id: Uuid::new_v4().to_string(),
+            started_at: Utc::now().timestamp_millis() as u64,
+            binary: binary.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("HL_GIT_HASH").to_string(),
+            build_profile: env!("HL_BUILD_PROFILE").to_string(),
+            network: network.to_string(),
+            wallet,
+            config,
+        }
+    }
+
+    // env_logger with the run id on every line; replaces `env_logger::init()`.
+    // With HL_LOG_FORMAT=json each line is a JSON object instead.
+    pub fn init_logging(&self) {
+        let run_id = self.run_id.clone();
+        let json_lines = std::env::var(LOG_FORMAT_ENV).is_ok_and(|f| f == "json");
+        env_logger::Builder::from_default_env()
+            .format(move |buf, record| {
+                if json_lines {
+                    let line = json!({
+                        "time": Utc::now().timestamp_millis(),
+                        "level": record.level().as_str(),
+                        "target": record.target(),
+                        "run_id": run_id,
+                        "message": record.args().to_string(),
+                    });
+                    return writeln!(buf, "{line}");
+                }
+                writeln!(
+                    buf,
+                    "[{} {} {} run={}] {}",
+                    Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                    record.level(),
+                    record.target(),
+                    run_id,
+                    record.args()
+                )
+            })
+            .init();
+    }
+}
+
+// Append-only JSONL record of what a run did. Every line carries the run id, the
+// wall-clock time, a kind and its data; the manifest is the first record.
+#[derive(Debug)]
+pub struct Journal {
+    run_id: String,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl Journal {
+    pub fn open(path: impl AsRef<Path>, manifest: &RunManifest) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| Error::Journal(e.to_string()))?;
+        let journal = Self {
+            run_id: manifest.run_id.clone(),
+            writer: Mutex::new(BufWriter::new(file)),
+        };
+        journal.record("manifest", manifest)?;
+        Ok(journal)
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn record(&self, kind: &str, data: impl Serialize) -> Result<()> {
+        let line = json!({
+            "run_id": self.run_id,
+            "time": Utc::now().timestamp_millis(),
+            "kind": kind,
+            "data": data,
+        });
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        serde_json::to_writer(&mut *writer, &line).map_err(|e| Error::Journal(e.to_string()))?;
+        writer
+            .write_all(b"\n")
+            .and_then(|_| writer.flush())
+            .map_err(|e| Error::Journal(e.to_string()))
+    }
+}
+
+// One line of a journal as read back
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournalRecord {
+    pub run_id: String,
+    pub time: u64,
+    pub kind: String,
+    pub data: Value,
+}
+
+// Reads every record of a journal file, which may hold several runs
+pub fn read_journal(path: impl AsRef<Path>) -> Result<Vec<JournalRecord>> {
+    let file = File::open(path).map_err(|e| Error::Journal(e.to_string()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.map_err(|e| Error::Journal(e.to_string()))?;
+            serde_json::from_str(&line).map_err(|e| Error::Journal(format!("line {}: {e}", i + 1)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_records_carry_run_id() {
+        let path = std::env::temp_dir().join(format!("journal-{}.jsonl", Uuid::new_v4()));
+        let manifest = RunManifest::new("test", "testnet", None, json!({"max_position": 5.0}));
+        let journal = Journal::open(&path, &manifest).unwrap();
+        journal
+            .record("fill", json!({"side": "Buy", "px": 100.0}))
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let records = read_journal(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(records[1].kind, "fill");
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines
+            .iter()
+            .all(|l| l["run_id"] == manifest.run_id.as_str()));
+        assert_eq!(lines[0]["kind"], "manifest");
+        assert_eq!(lines[0]["data"]["config"]["max_position"], 5.0);
+        assert!(!lines[0]["data"]["git_hash"].as_str().unwrap().is_empty());
+        assert_eq!(lines[1]["data"]["side"], "Buy");
+    }
+}
@@ -0,0 +1,170 @@
+use log::{error, info, warn};
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use crate::{ExchangeResponseStatus, Executor, OrderOutcome};
+
+#[derive(Default)]
+struct KillState {
+    reason: Mutex<Option<String>>,
+    executors: Mutex<Vec<(String, Executor)>>,
+    flattened: watch::Sender<bool>,
+}
+
+// Stops a bot with nothing left on the exchange. Bots register the executors
+// they trade through; tripping the switch, from a signal handler, the admin
+// socket or a risk breach, halts every one of them so no new quote goes out,
+// then bulk cancels their open orders and closes their positions with
+// reduce-only market orders. Only the first trigger acts. Clones share one
+// switch.
+#[derive(Clone, Default)]
+pub struct KillSwitch {
+    state: Arc<KillState>,
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, name: &str, executor: &Executor) {
+        let mut executors = self.state.executors.lock().unwrap();
+        executors.push((name.to_string(), executor.clone()));
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.state.reason.lock().unwrap().clone()
+    }
+
+    // Halts, cancels and flattens every registered executor, returning one
+    // line per executor. None if the switch had already tripped.
+    pub async fn trigger(&self, reason: &str) -> Option<String> {
+        {
+            let mut tripped = self.state.reason.lock().unwrap();
+            if tripped.is_some() {
+                return None;
+            }
+            *tripped = Some(reason.to_string());
+        }
+        warn!("[Kill] Tripped: {reason}; cancelling orders and flattening positions");
+        let executors = self.state.executors.lock().unwrap().clone();
+        // Every executor stops quoting before any of them starts flattening
+        for (_, executor) in &executors {
+            executor.halt();
+        }
+        let mut lines = Vec::new();
+        for (name, executor) in &executors {
+            let line = match executor.flatten().await {
+                Ok(responses) => {
+                    let failed = responses.iter().filter(|r| rejected(r)).count();
+                    format!("{name}: {} requests, {failed} failed", responses.len())
+                }
+                Err(e) => format!("{name}: flatten failed: {e}"),
+            };
+            info!("[Kill] {line}");
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            lines.push("no executors registered".to_string());
+        }
+        self.state.flattened.send_replace(true);
+        Some(lines.join("\n"))
+    }
+
+    // Resolves with the reason once a trigger has finished flattening, for
+    // the main loop to exit on
+    pub async fn flattened(&self) -> String {
+        let mut done = self.state.flattened.subscribe();
+        if done.wait_for(|flattened| *flattened).await.is_err() {
+            return std::future::pending().await;
+        }
+        self.reason().unwrap_or_default()
+    }
+
+    // Trips the switch on SIGINT or SIGTERM instead of letting the process
+    // exit mid-position
+    pub fn trigger_on_signals(&self) {
+        let switch = self.clone();
+        tokio::spawn(async move {
+            let reason = match shutdown_signal().await {
+                Ok(reason) => reason,
+                Err(e) => {
+                    error!("[Kill] Signal handler not installed: {e}");
+                    return;
+                }
+            };
+            switch.trigger(reason).await;
+        });
+    }
+}
+
+// Resolves with the name of the first SIGINT or SIGTERM received, for bots
+// that shut down their own way
+pub async fn shutdown_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.map(|_| "SIGINT"),
+        _ = terminate.recv() => Ok("SIGTERM"),
+    }
+}
+
+fn rejected(response: &ExchangeResponseStatus) -> bool {
+    matches!(
+        OrderOutcome::from(response.clone()),
+        OrderOutcome::Rejected(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        limit_order, BookLevel, MarketEvent, OrderIntent, PaperConfig, PaperExchange, TimeInForce,
+    };
+
+    fn level(px: f64) -> BookLevel {
+        BookLevel {
+            px: px.to_string(),
+            sz: "5".to_string(),
+            n: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trigger_cancels_flattens_and_halts() {
+        let paper = Arc::new(PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        }));
+        let executor = Executor::with_backend(paper.clone());
+        let book = MarketEvent::Book {
+            time: 1,
+            bids: vec![level(100.0)],
+            asks: vec![level(101.0)],
+        };
+        executor.on_market_event("BTC", &book);
+        // A long taken at the ask and a bid left resting below the touch
+        for px in [101.0, 99.0] {
+            let order = limit_order("BTC", true, px, 1.0, false, TimeInForce::Gtc, None);
+            executor.submit(OrderIntent::Place(order)).await.unwrap();
+        }
+        assert_eq!((paper.position("BTC"), paper.resting_orders()), (1.0, 1));
+
+        let switch = KillSwitch::new();
+        switch.register("BTC", &executor);
+        let report = switch.trigger("test").await.unwrap();
+        assert_eq!(report, "BTC: 2 requests, 0 failed");
+        assert_eq!((paper.position("BTC"), paper.resting_orders()), (0.0, 0));
+        assert_eq!(switch.flattened().await, "test");
+
+        // New quotes are refused; a second trigger does nothing
+        let quote = limit_order("BTC", true, 99.0, 1.0, false, TimeInForce::Gtc, None);
+        assert!(executor.submit(OrderIntent::Place(quote)).await.is_err());
+        assert!(switch.trigger("again").await.is_none());
+    }
+}
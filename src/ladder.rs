@@ -0,0 +1,177 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::{BookSide, OrderBook};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LadderTrade {
+    pub time: u64,
+    pub px: f64,
+    pub sz: f64,
+    pub is_buy: bool, // aggressor side
+}
+
+// Text depth ladder for the admin REPL: the book around the touch, our own
+// resting orders in the columns beside their price, and how much recently
+// traded there. Prices run from the highest ask down to the lowest bid with the
+// mid marked, so a quote inside the spread sits between the two touches.
+#[derive(Debug, Clone)]
+pub struct PriceLadder {
+    pub depth: usize,      // levels shown per side
+    pub max_trades: usize, // recent trades kept for the traded column and tape
+    trades: VecDeque<LadderTrade>,
+}
+
+impl Default for PriceLadder {
+    fn default() -> Self {
+        Self::new(10, 50)
+    }
+}
+
+// Same f64-bits keying as the order book; prices here are positive and finite
+fn key(px: f64) -> u64 {
+    px.to_bits()
+}
+
+impl PriceLadder {
+    pub fn new(depth: usize, max_trades: usize) -> Self {
+        Self {
+            depth,
+            max_trades,
+            trades: VecDeque::with_capacity(max_trades),
+        }
+    }
+
+    pub fn on_trade(&mut self, time: u64, px: f64, sz: f64, is_buy: bool) {
+        if self.max_trades == 0 {
+            return;
+        }
+        if self.trades.len() == self.max_trades {
+            self.trades.pop_front();
+        }
+        self.trades.push_back(LadderTrade {
+            time,
+            px,
+            sz,
+            is_buy,
+        });
+    }
+
+    pub fn trades(&self) -> impl Iterator<Item = &LadderTrade> {
+        self.trades.iter()
+    }
+
+    // `ours` holds (is_buy, price, remaining size) for our resting orders;
+    // orders away from the shown depth still get a row of their own
+    pub fn render(&self, coin: &str, book: &OrderBook, ours: &[(bool, f64, f64)]) -> String {
+        let Some(mid) = book.mid() else {
+            return format!("{coin} no book yet");
+        };
+        let mut prices: BTreeSet<u64> = BTreeSet::new();
+        for side in [BookSide::Bid, BookSide::Ask] {
+            prices.extend(book.levels(side).take(self.depth).map(|(px, _)| key(px)));
+        }
+        prices.extend(ours.iter().map(|(_, px, _)| key(*px)));
+
+        let size_at = |is_buy: bool, px: f64| -> f64 {
+            ours.iter()
+                .filter(|(b, p, _)| *b == is_buy && key(*p) == key(px))
+                .map(|(_, _, sz)| sz)
+                .sum()
+        };
+        let traded_at = |px: f64| -> f64 {
+            self.trades
+                .iter()
+                .filter(|t| key(t.px) == key(px))
+                .map(|t| t.sz)
+                .sum()
+        };
+        let cell = |sz: f64| {
+            if sz > 0.0 {
+                format!("{sz:.4}")
+            } else {
+                String::new()
+            }
+        };
+
+        let mut lines = vec![
+            format!("{coin} ladder @ book {}", book.time),
+            format!(
+                "{:>10} {:>10} {:>12} {:>10} {:>10} {:>10}",
+                "our bid", "bid", "price", "ask", "our ask", "traded"
+            ),
+        ];
+        let mut mid_shown = false;
+        for px in prices.iter().rev().map(|k| f64::from_bits(*k)) {
+            if !mid_shown && px < mid {
+                lines.push(format!("{:>10} {:>10} {:>12.4} ---", "", "---", mid));
+                mid_shown = true;
+            }
+            let (our_bid, our_ask) = (size_at(true, px), size_at(false, px));
+            let marker = if our_bid > 0.0 || our_ask > 0.0 {
+                "*"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "{:>10} {:>10} {:>12} {:>10} {:>10} {:>10}",
+                cell(our_bid),
+                cell(book.depth_at_price(BookSide::Bid, px)),
+                format!("{marker}{px}"),
+                cell(book.depth_at_price(BookSide::Ask, px)),
+                cell(our_ask),
+                cell(traded_at(px))
+            ));
+        }
+        let tape: Vec<_> = self
+            .trades
+            .iter()
+            .rev()
+            .take(5)
+            .map(|t| format!("{} {}@{}", if t.is_buy { "B" } else { "S" }, t.sz, t.px))
+            .collect();
+        if !tape.is_empty() {
+            lines.push(format!("tape: {}", tape.join(" | ")));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BookLevel;
+
+    fn level(px: &str, sz: &str) -> BookLevel {
+        BookLevel {
+            px: px.to_string(),
+            sz: sz.to_string(),
+            n: 1,
+        }
+    }
+
+    #[test]
+    fn test_renders_book_our_orders_and_trades_around_the_mid() {
+        let mut book = OrderBook::new();
+        let bids = [level("100", "2"), level("99", "4")];
+        let asks = [level("101", "1"), level("102", "3")];
+        assert!(book.apply_snapshot(7, &bids, &asks));
+        let mut ladder = PriceLadder::new(2, 2);
+        ladder.on_trade(1, 101.0, 0.5, true);
+        ladder.on_trade(2, 101.0, 0.25, true);
+        ladder.on_trade(3, 100.0, 1.0, false);
+        assert_eq!(ladder.trades().count(), 2);
+
+        // One bid joins the touch, one improves inside the spread
+        let ours = [(true, 100.0, 0.1), (true, 100.5, 0.2), (false, 102.0, 0.3)];
+        let text = ladder.render("BTC", &book, &ours);
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows[0], "BTC ladder @ book 7");
+        assert!(rows[2].contains("*102") && rows[2].contains("0.3000"));
+        assert!(rows[3].contains(" 101 ") && rows[3].trim_end().ends_with("0.2500"));
+        assert!(rows[4].contains("*100.5") && rows[4].contains("0.2000"));
+        assert!(rows[5].contains("100.5000 ---"));
+        assert!(rows[6].contains("*100") && rows[6].trim_end().ends_with("1.0000"));
+        assert!(rows[7].contains(" 99 "));
+        assert_eq!(rows.last().unwrap(), &"tape: S 1@100 | B 0.25@101");
+    }
+}
@@ -0,0 +1,229 @@
+use log::{info, warn};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{Metrics, QuoteProposal, RequestClass};
+
+pub(crate) const LATENCY_WINDOW: usize = 50; // Round trips per request kind in the percentiles
+pub(crate) const THROTTLE_WIDEN_TICKS: f64 = 2.0; // Extra distance from the touch at full throttle
+pub(crate) const THROTTLE_MAX_GAP_MS: u64 = 2_000; // Time between quote rounds at full throttle
+
+#[derive(Debug, Default)]
+struct RoundTrips {
+    orders: VecDeque<f64>, // ms, places and modifies
+    cancels: VecDeque<f64>,
+}
+
+impl RoundTrips {
+    fn window(&mut self, class: RequestClass) -> Option<&mut VecDeque<f64>> {
+        match class {
+            RequestClass::Order => Some(&mut self.orders),
+            RequestClass::Cancel => Some(&mut self.cancels),
+            RequestClass::Info => None,
+        }
+    }
+}
+
+// Round-trip times of the orders and cancels the executor sends, over the
+// last LATENCY_WINDOW of each. Clones share the same samples.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTracker {
+    samples: Arc<Mutex<RoundTrips>>,
+    metrics: Option<Metrics>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Also observe each round trip as `request_latency_ms{kind="order"}` or
+    // `{kind="cancel"}`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn record(&self, class: RequestClass, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1_000.0;
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(window) = samples.window(class) else {
+            return;
+        };
+        window.push_back(ms);
+        while window.len() > LATENCY_WINDOW {
+            window.pop_front();
+        }
+        if let Some(metrics) = &self.metrics {
+            let kind = if class == RequestClass::Cancel {
+                "cancel"
+            } else {
+                "order"
+            };
+            metrics.observe(&format!("request_latency_ms{{kind=\"{kind}\"}}"), ms);
+        }
+    }
+
+    // The `q` quantile (0.0 to 1.0) of recent `class` round trips, in ms
+    pub fn quantile(&self, class: RequestClass, q: f64) -> Option<f64> {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let window = samples.window(class)?;
+        if window.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<_> = window.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[rank])
+    }
+
+    // The slower of the order and cancel p95s
+    pub fn p95(&self) -> Option<f64> {
+        let orders = self.quantile(RequestClass::Order, 0.95);
+        let cancels = self.quantile(RequestClass::Cancel, 0.95);
+        orders.into_iter().chain(cancels).reduce(f64::max)
+    }
+
+    // p50 / p95 of each kind, for the admin `latency` command
+    pub fn summary(&self) -> String {
+        let kinds = [
+            ("orders", RequestClass::Order),
+            ("cancels", RequestClass::Cancel),
+        ];
+        kinds
+            .iter()
+            .map(
+                |(name, class)| match (self.quantile(*class, 0.5), self.quantile(*class, 0.95)) {
+                    (Some(p50), Some(p95)) => format!("{name} p50 {p50:.0}ms p95 {p95:.0}ms"),
+                    _ => format!("{name}: none sent"),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+// Backs quoting off while the exchange is slow, since quotes left stale by a
+// slowdown are where adverse fills come from. Once the p95 round trip passes
+// `spike_ms`, quotes move away from the touch by up to `widen_ticks` and quote
+// rounds are spaced up to `max_gap_ms` apart, in proportion to how far past it
+// latency is: fully at twice `spike_ms`.
+#[derive(Debug, Clone)]
+pub struct LatencyThrottle {
+    pub spike_ms: f64,
+    pub widen_ticks: f64,
+    pub max_gap_ms: u64,
+    latency: LatencyTracker,
+    last_round: Option<u64>,
+    throttled: bool, // latency was past the spike on the last book
+}
+
+impl LatencyThrottle {
+    pub fn new(latency: LatencyTracker, spike_ms: f64) -> Self {
+        Self {
+            spike_ms: spike_ms.max(1.0),
+            widen_ticks: THROTTLE_WIDEN_TICKS,
+            max_gap_ms: THROTTLE_MAX_GAP_MS,
+            latency,
+            last_round: None,
+            throttled: false,
+        }
+    }
+
+    pub fn with_limits(mut self, widen_ticks: f64, max_gap_ms: u64) -> Self {
+        self.widen_ticks = widen_ticks;
+        self.max_gap_ms = max_gap_ms;
+        self
+    }
+
+    // Keeps `previous`'s spacing of quote rounds, when limits are reloaded
+    pub fn carry_over(&mut self, previous: &LatencyThrottle) {
+        self.last_round = previous.last_round;
+        self.throttled = previous.throttled;
+    }
+
+    // 0.0 while round trips are under the spike, 1.0 from twice it
+    pub fn severity(&self) -> f64 {
+        self.latency.p95().map_or(0.0, |p95| {
+            ((p95 - self.spike_ms) / self.spike_ms).clamp(0.0, 1.0)
+        })
+    }
+
+    // This book's quotes, widened or held back while latency is high
+    pub fn apply(&mut self, time: u64, quotes: &[QuoteProposal]) -> Vec<QuoteProposal> {
+        let severity = self.severity();
+        let throttled = severity > 0.0;
+        if throttled != self.throttled {
+            self.throttled = throttled;
+            if throttled {
+                warn!(
+                    "[Risk] Exchange round trips past {:.0}ms, throttling quotes",
+                    self.spike_ms
+                );
+            } else {
+                info!("[Risk] Exchange latency back to normal");
+            }
+        }
+        if !throttled {
+            self.last_round = Some(time);
+            return quotes.to_vec();
+        }
+        let gap = (self.max_gap_ms as f64 * severity) as u64;
+        if self.last_round.is_some_and(|last| time < last + gap) {
+            return Vec::new();
+        }
+        self.last_round = Some(time);
+        let widen = self.widen_ticks * severity;
+        quotes
+            .iter()
+            .map(|q| QuoteProposal {
+                side: q.side.clone(),
+                price: if q.side == "Buy" {
+                    q.price - widen
+                } else {
+                    q.price + widen
+                },
+                size: q.size,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slow_round_trips_widen_and_space_out_quotes() {
+        let latency = LatencyTracker::new();
+        let mut throttle = LatencyThrottle::new(latency.clone(), 100.0).with_limits(2.0, 1_000);
+        let bid = QuoteProposal {
+            side: "Buy".to_string(),
+            price: 100.0,
+            size: 1.0,
+        };
+        let price = |quotes: Vec<QuoteProposal>| quotes.first().map(|q| q.price);
+        let quotes = std::slice::from_ref(&bid);
+        for _ in 0..10 {
+            latency.record(RequestClass::Order, Duration::from_millis(50));
+        }
+        assert_eq!(price(throttle.apply(0, quotes)), Some(100.0));
+
+        // Cancels at 1.5x the spike: half the widening and half the gap
+        for _ in 0..10 {
+            latency.record(RequestClass::Cancel, Duration::from_millis(150));
+        }
+        assert_eq!(latency.p95(), Some(150.0));
+        assert_eq!(price(throttle.apply(100, quotes)), None);
+        assert_eq!(price(throttle.apply(500, quotes)), Some(99.0));
+        assert_eq!(price(throttle.apply(900, quotes)), None);
+
+        for _ in 0..LATENCY_WINDOW {
+            latency.record(RequestClass::Cancel, Duration::from_millis(20));
+        }
+        assert_eq!(price(throttle.apply(901, quotes)), Some(100.0));
+    }
+}
@@ -1,21 +1,178 @@
 #![deny(unreachable_pub)]
+mod account_events;
+mod admin;
+#[cfg(feature = "arrow")]
+mod arrow_export;
+mod asset_specs;
+mod attribution;
+mod backend;
+mod backtest;
+mod bars;
+mod chaos;
+mod config;
 mod consts;
+mod cooldown;
+#[cfg(feature = "tui")]
+mod dashboard;
 mod errors;
+mod event_log;
 mod exchange;
+pub mod execution;
+mod execution_quality;
+mod executor;
+mod exposure;
+mod fees;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod funding;
+mod governor;
+mod heatmap;
 mod helpers;
 mod info;
+mod inventory_age;
+mod journal;
+mod kill_switch;
+mod ladder;
+mod latency;
+mod margin_guard;
 mod market_maker;
 mod meta;
+mod metrics;
+mod monte_carlo;
+mod notifier;
+mod order_book;
+mod order_manager;
+mod ping_pong;
+mod pnl;
+mod pre_trade;
 mod prelude;
 mod proxy_digest;
+mod queue_value;
+mod quote_reconciler;
+mod quoting;
+mod rate_limit;
+mod reconcile;
+mod recording;
+mod replay_diff;
+mod reporter;
 mod req;
+pub mod risk;
+mod rotation;
+mod router;
+mod scalper;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod session;
+pub mod signals;
 mod signature;
+mod soak;
+mod soft_start;
+mod strategy;
+mod strategy_runner;
+mod streak;
+mod stress;
+mod symbols;
+mod tape;
+pub mod types;
+#[cfg(feature = "wasm")]
+mod wasm;
 mod ws;
+pub use account_events::{AccountEvent, AccountGuard};
+pub use admin::{serve_admin, AdminRequest};
+#[cfg(feature = "arrow")]
+pub use arrow_export::{write_features, write_fills, write_ticks, FeatureRow, FillRow, TickRow};
+pub use asset_specs::{AssetSpec, AssetSpecs};
+pub use attribution::{SignalAttribution, SignalContributions};
+pub use backend::{ExecutionBackend, LiveExchange, PaperConfig, PaperExchange};
+pub use backtest::{
+    candle_events, compare_results, funding_rates, run_backtest, BacktestConfig, BacktestFill,
+    BacktestResult, BacktestRun, BacktestStats, Comparison, MeanDiff, MetricDelta, Verdict,
+    DEFAULT_SIGNIFICANCE_Z,
+};
+pub use bars::{load_bars, Bar, BarBuilder, BarStore};
+pub use chaos::{Chaos, ChaosConfig, ChaosStats};
+pub use config::{
+    BotConfig, CoinConfig, ConfigWatcher, ExchangeConfig, FeeConfig, RiskConfig, StrategyConfig,
+    TifConfig,
+};
 pub use consts::{EPSILON, LOCAL_API_URL, MAINNET_API_URL, TESTNET_API_URL};
+pub use cooldown::AdaptiveCooldown;
+#[cfg(feature = "tui")]
+pub use dashboard::{Dashboard, DashboardAction, DashboardState};
 pub use errors::Error;
+pub use event_log::EventLog;
 pub use exchange::*;
+pub use execution::{
+    cancel_by_cloid, compute_qty, hedge_order, limit_order, place_order, round_to_tick,
+    OrderOutcome, OrderRole, TimeInForce,
+};
+pub use execution_quality::{FillRole, QuoteActivity, SpreadTracker, VolumeEfficiency};
+pub use executor::{Executor, IntentPriority, IntentQueue, OrderIntent};
+pub use exposure::{ExposureSlot, GlobalExposure};
+pub use fees::{FeeModel, RebateTier, VolumeTier};
+#[cfg(feature = "ffi")]
+pub use ffi::{
+    hl_abi_version, hl_engine_free, hl_engine_new, hl_engine_on_fill, hl_engine_poll_quotes,
+    hl_engine_position, hl_engine_push_book, hl_engine_push_trade, HlEngine, HlQuoteIntent,
+    HL_ERR_INVALID, HL_ERR_NULL, HL_ERR_PANIC, HL_OK,
+};
+pub use funding::FundingMonitor;
+pub use governor::TradeGovernor;
+pub use heatmap::{HeatmapCell, PerformanceHeatmap};
 pub use helpers::{bps_diff, truncate_float, BaseUrl};
 pub use info::{info_client::*, *};
+#[doc(hidden)]
+pub use inventory;
+pub use inventory_age::InventoryHalfLife;
+pub use journal::{read_journal, Journal, JournalRecord, RunManifest};
+pub use kill_switch::{shutdown_signal, KillSwitch};
+pub use ladder::{LadderTrade, PriceLadder};
+pub use latency::{LatencyThrottle, LatencyTracker};
+pub use margin_guard::MarginGuard;
 pub use market_maker::{MarketMaker, MarketMakerInput, MarketMakerRestingOrder};
 pub use meta::{AssetMeta, Meta, SpotAssetMeta, SpotMeta};
+pub use metrics::Metrics;
+pub use monte_carlo::{
+    run_monte_carlo, trade_pnls_from_journal, MonteCarloConfig, MonteCarloReport,
+};
+pub use notifier::{Alert, AlertLevel, Notifier, NotifierSink};
+pub use order_book::{BookSide, OrderBook};
+pub use order_manager::{OrderFill, OrderManager, OrderStatus, TrackedOrder};
+pub use ping_pong::PingPongMaker;
+pub use pnl::{CoinPnl, PnlTotals, PnlTracker};
+pub use pre_trade::{PreTradeChecks, PreTradeLimits};
+pub use queue_value::{QueueFlow, QueuePosition, QuoteCandidate, QuoteEv, QuoteValueModel};
+pub use quote_reconciler::{DesiredQuote, QuoteReconciler, RestingQuote};
+pub use quoting::{QuoteLayerManager, QuoteSides};
+pub use rate_limit::{RateLimiter, RequestClass};
+pub use reconcile::{AccountSnapshot, Divergence, ExchangePosition, Reconciler};
+pub use recording::{load_recording, parse_recorded_line, RecordedEvent, Recording};
+pub use replay_diff::{DiffPanel, DiffSide, ReplayDiff};
+pub use reporter::{ReportLayout, StatusReporter};
+pub use risk::{HedgeOrder, LossAction, LossLimits, RiskManager};
+pub use rotation::{scan_markets, MarketRotation, MarketSnapshot, RotationConfig, RotationPlan};
+pub use router::MessageRouter;
+pub use scalper::TrendScalper;
+#[cfg(feature = "scripting")]
+pub use scripting::{ScriptedStrategy, SignalScript};
+pub use session::{SessionFrame, SessionViewer};
+pub use signals::{
+    compute_realized_vol, compute_volatility, linear_regression_slope, percent_change,
+    price_volatility, top_of_book, BurstCircuit, DecayKernel, EntryConfirmation, ExitTargets,
+    FairValue, FlowMeasure, PriceOffset, RegimeThresholds, SignalEngine, SignalState,
+    SignalWindows, SpreadRegime, VolumeBuckets,
+};
+pub use soak::{run_soak, SoakConfig, SoakReport};
+pub use soft_start::SoftStart;
+pub use strategy::{
+    build_strategy, registered_strategies, Strategy, StrategyOrder, StrategyRegistration,
+};
+pub use strategy_runner::StrategyRunner;
+pub use streak::StreakWidener;
+pub use stress::{generate_scenario, run_scenario, Scenario, StressReport};
+pub use symbols::SymbolManager;
+pub use tape::{prune_tapes, read_tape, TapeEvent, TapeLevel, TapeRecord, TapeWriter};
+pub use types::{BookSample, MarketEvent, Position, QuoteProposal, TradeSample};
+#[cfg(feature = "wasm")]
+pub use wasm::WasmStrategy;
 pub use ws::*;
@@ -0,0 +1,73 @@
+use tokio::sync::watch;
+
+use crate::{prelude::*, AccountSnapshot, Error};
+
+// Keeps the bot's own sizing from exhausting the account's margin. Before a
+// coin's position limit grows or a coin is added, the margin the extra
+// notional would tie up at `leverage` is taken from the withdrawable balance
+// of the latest `user_state` poll; expansions that would leave less than
+// `buffer_usd` free are refused. Until the first poll has arrived every
+// expansion is refused.
+#[derive(Debug, Clone)]
+pub struct MarginGuard {
+    pub buffer_usd: f64,
+    pub leverage: f64,
+    account: watch::Receiver<Option<AccountSnapshot>>,
+}
+
+impl MarginGuard {
+    // `account` as returned by `Reconciler::spawn`
+    pub fn new(
+        buffer_usd: f64,
+        leverage: f64,
+        account: watch::Receiver<Option<AccountSnapshot>>,
+    ) -> Self {
+        Self {
+            buffer_usd,
+            leverage: leverage.max(1.0),
+            account,
+        }
+    }
+
+    // Ok if `added_notional` more USD of position leaves the buffer free
+    pub fn check(&self, added_notional: f64) -> Result<()> {
+        if added_notional <= 0.0 {
+            return Ok(());
+        }
+        let Some(free) = self.account.borrow().as_ref().map(|a| a.withdrawable) else {
+            return Err(Error::MarginGuard(
+                "account state not loaded yet".to_string(),
+            ));
+        };
+        let needed = added_notional / self.leverage;
+        if free - needed < self.buffer_usd {
+            return Err(Error::MarginGuard(format!(
+                "${added_notional:.2} more notional needs ${needed:.2} of margin, leaving ${:.2} of ${free:.2} withdrawable, under the ${:.2} buffer",
+                free - needed,
+                self.buffer_usd
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refuses_expansions_that_eat_into_the_buffer() {
+        let (tx, rx) = watch::channel(None);
+        let guard = MarginGuard::new(200.0, 10.0, rx);
+        assert!(guard.check(1_000.0).is_err()); // no account state yet
+        assert!(guard.check(0.0).is_ok()); // shrinking is always allowed
+
+        tx.send_replace(Some(AccountSnapshot {
+            withdrawable: 500.0,
+            ..Default::default()
+        }));
+        assert!(guard.check(3_000.0).is_ok()); // $300 of margin leaves $200
+        let err = guard.check(3_500.0).unwrap_err();
+        assert!(err.to_string().contains("under the $200.00 buffer"));
+    }
+}
@@ -7,9 +7,9 @@ use log::{error, info};
 use tokio::sync::mpsc::unbounded_channel;
 
 use crate::{
-    bps_diff, truncate_float, BaseUrl, ClientCancelRequest, ClientLimit, ClientOrder,
-    ClientOrderRequest, ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
-    Message, Subscription, UserData, EPSILON,
+    bps_diff, truncate_float, BaseUrl, ClientCancelRequest, ClientOrder, ClientOrderRequest,
+    ExchangeClient, ExchangeDataStatus, ExchangeResponseStatus, InfoClient, Message, Subscription,
+    TimeInForce, UserData, EPSILON,
 };
 #[derive(Debug)]
 pub struct MarketMakerRestingOrder {
@@ -201,9 +201,7 @@ impl MarketMaker {
                     limit_px: price,
                     sz: amount,
                     cloid: None,
-                    order_type: ClientOrder::Limit(ClientLimit {
-                        tif: "Gtc".to_string(),
-                    }),
+                    order_type: ClientOrder::Limit(TimeInForce::Gtc.into()),
                 },
                 None,
             )
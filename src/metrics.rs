@@ -0,0 +1,161 @@
+use log::{debug, warn};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{prelude::*, Error};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Summary {
+    count: u64,
+    sum: f64,
+    max: f64,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    counters: BTreeMap<String, f64>,
+    gauges: BTreeMap<String, f64>,
+    summaries: BTreeMap<String, Summary>,
+}
+
+// Names may carry Prometheus labels, e.g. `ws_latency_ms{channel="l2Book"}`
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+fn split_labels(name: &str) -> (&str, &str) {
+    match name.find('{') {
+        Some(i) => name.split_at(i),
+        None => (name, ""),
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Registry) -> T) -> T {
+        let mut registry = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut registry)
+    }
+
+    pub fn inc(&self, name: &str, by: f64) {
+        self.with(|r| *r.counters.entry(name.to_string()).or_default() += by);
+    }
+
+    pub fn set(&self, name: &str, value: f64) {
+        self.with(|r| {
+            r.gauges.insert(name.to_string(), value);
+        });
+    }
+
+    pub fn observe(&self, name: &str, value: f64) {
+        self.with(|r| {
+            let s = r.summaries.entry(name.to_string()).or_default();
+            s.count += 1;
+            s.sum += value;
+            s.max = if s.count == 1 {
+                value
+            } else {
+                s.max.max(value)
+            };
+        });
+    }
+
+    pub fn gauge(&self, name: &str) -> Option<f64> {
+        self.with(|r| r.gauges.get(name).copied())
+    }
+
+    pub fn counter(&self, name: &str) -> Option<f64> {
+        self.with(|r| r.counters.get(name).copied())
+    }
+
+    // Prometheus text exposition format
+    pub fn render(&self) -> String {
+        self.with(|r| {
+            let mut out = String::new();
+            let mut typed = None;
+            let mut type_line = |out: &mut String, base: &str, kind: &str| {
+                if typed.as_deref() != Some(base) {
+                    let _ = writeln!(out, "# TYPE {base} {kind}");
+                    typed = Some(base.to_string());
+                }
+            };
+            for (name, value) in &r.counters {
+                type_line(&mut out, split_labels(name).0, "counter");
+                let _ = writeln!(out, "{name} {value}");
+            }
+            for (name, value) in &r.gauges {
+                type_line(&mut out, split_labels(name).0, "gauge");
+                let _ = writeln!(out, "{name} {value}");
+            }
+            for (name, s) in &r.summaries {
+                let (base, labels) = split_labels(name);
+                type_line(&mut out, base, "summary");
+                let _ = writeln!(out, "{base}_count{labels} {}", s.count);
+                let _ = writeln!(out, "{base}_sum{labels} {}", s.sum);
+                let _ = writeln!(out, "{base}_max{labels} {}", s.max);
+            }
+            out
+        })
+    }
+
+    // Serves `render()` over plain HTTP on `addr` until the task is dropped
+    pub async fn serve(self, addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| Error::GenericRequest(e.to_string()))?;
+        debug!("serving metrics on {addr}");
+        loop {
+            let (mut stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("metrics accept failed: {e}");
+                    continue;
+                }
+            };
+            let body = self.render();
+            tokio::spawn(async move {
+                // The request itself is irrelevant, every path returns the metrics
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.inc("ws_messages_total", 1.0);
+        metrics.inc("ws_messages_total", 2.0);
+        metrics.set("rest_rtt_ms{endpoint=\"info\"}", 12.5);
+        metrics.observe("ws_latency_ms{channel=\"l2Book\"}", 10.0);
+        metrics.observe("ws_latency_ms{channel=\"l2Book\"}", 30.0);
+
+        let out = metrics.render();
+        assert!(out.contains("# TYPE ws_messages_total counter\nws_messages_total 3\n"));
+        assert!(out.contains("# TYPE rest_rtt_ms gauge\nrest_rtt_ms{endpoint=\"info\"} 12.5\n"));
+        assert!(out.contains("ws_latency_ms_count{channel=\"l2Book\"} 2\n"));
+        assert!(out.contains("ws_latency_ms_sum{channel=\"l2Book\"} 40\n"));
+        assert!(out.contains("ws_latency_ms_max{channel=\"l2Book\"} 30\n"));
+    }
+}
@@ -0,0 +1,216 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashMap, fmt};
+
+use crate::JournalRecord;
+
+// Bootstrap settings. Each path draws `trades_per_path` trades (default: as many
+// as the sample) with replacement from the observed trade PnLs.
+#[derive(Debug, Clone)]
+pub struct MonteCarloConfig {
+    pub paths: usize,
+    pub trades_per_path: Option<usize>,
+    pub starting_equity: f64,
+    pub ruin_drawdown: f64, // a path is ruined once it loses this fraction of starting equity
+    pub seed: Option<u64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            paths: 10_000,
+            trades_per_path: None,
+            starting_equity: 1_000.0,
+            ruin_drawdown: 0.5,
+            seed: None,
+        }
+    }
+}
+
+// Distribution of outcomes across resampled paths, in PnL units
+#[derive(Debug, Clone)]
+pub struct MonteCarloReport {
+    pub paths: usize,
+    pub trades_per_path: usize,
+    pub final_pnl: [f64; 3],    // p5, p50, p95
+    pub max_drawdown: [f64; 3], // p50, p95, p99 of the peak-to-trough loss
+    pub risk_of_ruin: f64,      // fraction of paths that hit the ruin drawdown
+}
+
+impl MonteCarloReport {
+    // Factor to scale position sizes by so the p99 drawdown fits within `budget`;
+    // above 1.0 means there is room to size up
+    pub fn size_scale_for(&self, budget: f64) -> f64 {
+        let p99 = self.max_drawdown[2];
+        if p99 <= 0.0 {
+            return 1.0;
+        }
+        budget / p99
+    }
+}
+
+impl fmt::Display for MonteCarloReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [f5, f50, f95] = self.final_pnl;
+        let [d50, d95, d99] = self.max_drawdown;
+        writeln!(f, "{} paths x {} trades", self.paths, self.trades_per_path)?;
+        writeln!(f, "final PnL     p5 {f5:.4} | p50 {f50:.4} | p95 {f95:.4}")?;
+        writeln!(
+            f,
+            "max drawdown  p50 {d50:.4} | p95 {d95:.4} | p99 {d99:.4}"
+        )?;
+        write!(f, "risk of ruin  {:.2}%", 100.0 * self.risk_of_ruin)
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx]
+}
+
+// Resamples the trade sequence; None when there are no trades to draw from
+pub fn run_monte_carlo(pnls: &[f64], config: &MonteCarloConfig) -> Option<MonteCarloReport> {
+    if pnls.is_empty() || config.paths == 0 {
+        return None;
+    }
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let n = config.trades_per_path.unwrap_or(pnls.len()).max(1);
+    let ruin_at = config.starting_equity * config.ruin_drawdown;
+    let mut finals = Vec::with_capacity(config.paths);
+    let mut drawdowns = Vec::with_capacity(config.paths);
+    let mut ruined = 0;
+    for _ in 0..config.paths {
+        let (mut equity, mut peak, mut max_dd) = (0.0f64, 0.0f64, 0.0f64);
+        let mut hit_ruin = false;
+        for _ in 0..n {
+            equity += pnls[rng.gen_range(0..pnls.len())];
+            peak = peak.max(equity);
+            max_dd = max_dd.max(peak - equity);
+            hit_ruin |= -equity >= ruin_at;
+        }
+        finals.push(equity);
+        drawdowns.push(max_dd);
+        ruined += hit_ruin as usize;
+    }
+    finals.sort_by(f64::total_cmp);
+    drawdowns.sort_by(f64::total_cmp);
+    Some(MonteCarloReport {
+        paths: config.paths,
+        trades_per_path: n,
+        final_pnl: [0.05, 0.5, 0.95].map(|p| percentile(&finals, p)),
+        max_drawdown: [0.5, 0.95, 0.99].map(|p| percentile(&drawdowns, p)),
+        risk_of_ruin: ruined as f64 / config.paths as f64,
+    })
+}
+
+// Realized PnL of every position-reducing fill in a live session, using average
+// cost per coin. Reads journal `fill` (`side`) and `hedge` (`is_buy`) records.
+pub fn trade_pnls_from_journal(records: &[JournalRecord]) -> Vec<f64> {
+    let mut books: HashMap<String, (f64, f64)> = HashMap::new(); // coin -> (position, avg cost)
+    let mut pnls = Vec::new();
+    for record in records {
+        let data = &record.data;
+        let is_buy = match record.kind.as_str() {
+            "fill" => data["side"].as_str().map(|s| s == "Buy"),
+            "hedge" => data["is_buy"].as_bool(),
+            _ => None,
+        };
+        let (Some(is_buy), Some(size), Some(px)) =
+            (is_buy, data["size"].as_f64(), data["px"].as_f64())
+        else {
+            continue;
+        };
+        let coin = data["coin"].as_str().unwrap_or_default().to_string();
+        let (pos, cost) = books.entry(coin).or_insert((0.0, 0.0));
+        let signed = if is_buy { size } else { -size };
+        if *pos != 0.0 && pos.signum() != signed.signum() {
+            let closed = size.min(pos.abs());
+            pnls.push(closed * (px - *cost) * pos.signum());
+            let remaining = signed + *pos;
+            if remaining.signum() != pos.signum() {
+                *cost = px; // flipped through flat: the rest opens at this price
+            }
+            *pos = remaining;
+        } else {
+            *cost = (*cost * pos.abs() + px * size) / (pos.abs() + size);
+            *pos += signed;
+        }
+    }
+    pnls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bootstrap_distributions() {
+        let config = MonteCarloConfig {
+            paths: 2_000,
+            starting_equity: 10.0,
+            ruin_drawdown: 0.5,
+            seed: Some(7),
+            ..Default::default()
+        };
+        // Always winning: no drawdown, no ruin
+        let report = run_monte_carlo(&[1.0, 2.0], &config).unwrap();
+        assert_eq!(report.max_drawdown, [0.0; 3]);
+        assert_eq!(report.risk_of_ruin, 0.0);
+
+        // Coin flips of +/-1 over 100 trades: some paths lose 5 (half the equity)
+        let report = run_monte_carlo(
+            &[1.0, -1.0],
+            &MonteCarloConfig {
+                trades_per_path: Some(100),
+                ..config
+            },
+        )
+        .unwrap();
+        assert!(report.risk_of_ruin > 0.2 && report.risk_of_ruin < 0.9);
+        assert!(report.max_drawdown[0] <= report.max_drawdown[2]);
+        assert!(report.final_pnl[0] < 0.0 && report.final_pnl[2] > 0.0);
+        assert!((report.size_scale_for(report.max_drawdown[2] / 2.0) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_journal_fills_to_trade_pnls() {
+        let record = |kind: &str, data| JournalRecord {
+            run_id: "r".into(),
+            time: 0,
+            kind: kind.into(),
+            data,
+        };
+        let records = [
+            record(
+                "fill",
+                json!({"coin": "BTC", "side": "Buy", "size": 1.0, "px": 100.0}),
+            ),
+            record(
+                "fill",
+                json!({"coin": "BTC", "side": "Buy", "size": 1.0, "px": 102.0}),
+            ),
+            record(
+                "hedge",
+                json!({"coin": "BTC", "is_buy": false, "size": 1.5, "px": 103.0}),
+            ),
+            record(
+                "fill",
+                json!({"coin": "BTC", "side": "Sell", "size": 1.0, "px": 99.0}),
+            ),
+            record(
+                "fill",
+                json!({"coin": "BTC", "side": "Buy", "size": 0.5, "px": 98.0}),
+            ),
+        ];
+        let pnls = trade_pnls_from_journal(&records);
+        // avg cost 101: +3.0 on 1.5, then -1.0 on the last 0.5 (rest opens short at 99),
+        // then the short 0.5 is covered at 98 for +0.5
+        assert_eq!(pnls.len(), 3);
+        assert!((pnls[0] - 3.0).abs() < 1e-9);
+        assert!((pnls[1] + 1.0).abs() < 1e-9);
+        assert!((pnls[2] - 0.5).abs() < 1e-9);
+    }
+}
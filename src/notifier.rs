@@ -0,0 +1,158 @@
+use log::{error, info, warn};
+use reqwest::Client;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    env, fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{prelude::*, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum AlertLevel {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for AlertLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AlertLevel::Info => "INFO",
+            AlertLevel::Warning => "WARNING",
+            AlertLevel::Critical => "CRITICAL",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub level: AlertLevel,
+    pub key: String, // Alerts sharing a key are rate limited together
+    pub message: String,
+}
+
+impl Alert {
+    pub fn new(level: AlertLevel, key: impl Into<String>, message: impl Into<String>) -> Alert {
+        Alert {
+            level,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NotifierSink {
+    Log,
+    // Posts `{"text": ...}`, which Slack and Discord-compatible webhooks accept
+    Webhook { url: String },
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+}
+
+#[derive(Debug)]
+pub struct Notifier {
+    sinks: Vec<NotifierSink>,
+    cooldown: Duration,
+    last_sent: Mutex<HashMap<String, Instant>>,
+    client: Client,
+}
+
+impl Notifier {
+    pub const DEFAULT_COOLDOWN_SECS: u64 = 60;
+    pub const WEBHOOK_ENV: &'static str = "HL_ALERT_WEBHOOK";
+
+    pub fn new(sinks: Vec<NotifierSink>) -> Notifier {
+        Notifier {
+            sinks,
+            cooldown: Duration::from_secs(Self::DEFAULT_COOLDOWN_SECS),
+            last_sent: Mutex::new(HashMap::new()),
+            client: Client::new(),
+        }
+    }
+
+    // Always logs; also posts to the webhook in HL_ALERT_WEBHOOK when set
+    pub fn from_env() -> Notifier {
+        let mut sinks = vec![NotifierSink::Log];
+        if let Ok(url) = env::var(Self::WEBHOOK_ENV) {
+            sinks.push(NotifierSink::Webhook { url });
+        }
+        Notifier::new(sinks)
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Notifier {
+        self.cooldown = cooldown;
+        self
+    }
+
+    // Returns false without sending if an alert with the same key went out within the cooldown.
+    // Critical alerts are never suppressed.
+    fn should_send(&self, alert: &Alert) -> bool {
+        if alert.level == AlertLevel::Critical {
+            return true;
+        }
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        match last_sent.get(&alert.key) {
+            Some(sent) if now.duration_since(*sent) < self.cooldown => false,
+            _ => {
+                last_sent.insert(alert.key.clone(), now);
+                true
+            }
+        }
+    }
+
+    pub async fn notify(&self, alert: Alert) -> Result<bool> {
+        if !self.should_send(&alert) {
+            return Ok(false);
+        }
+        let text = format!("[{}] {}", alert.level, alert.message);
+        let mut result = Ok(true);
+        for sink in &self.sinks {
+            match sink {
+                NotifierSink::Log => match alert.level {
+                    AlertLevel::Info => info!("{text}"),
+                    AlertLevel::Warning => warn!("{text}"),
+                    AlertLevel::Critical => error!("{text}"),
+                },
+                NotifierSink::Webhook { url } => {
+                    let sent = self
+                        .client
+                        .post(url)
+                        .json(&WebhookPayload { text: &text })
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status());
+                    if let Err(e) = sent {
+                        result = Err(Error::GenericRequest(e.to_string()));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cooldown_suppresses_repeats() {
+        let notifier = Notifier::new(vec![]).with_cooldown(Duration::from_secs(60));
+        let alert = Alert::new(AlertLevel::Warning, "BTC:spread", "spread blowout");
+        assert!(notifier.notify(alert.clone()).await.unwrap());
+        assert!(!notifier.notify(alert).await.unwrap());
+        let other = Alert::new(AlertLevel::Warning, "ETH:spread", "spread blowout");
+        assert!(notifier.notify(other).await.unwrap());
+        let critical = Alert::new(AlertLevel::Critical, "BTC:spread", "still blown out");
+        assert!(notifier.notify(critical).await.unwrap());
+    }
+}
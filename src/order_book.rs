@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use crate::BookLevel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+// Prices are keyed by their f64 bit pattern, which sorts like the number for
+// positive finite values, the only prices the book accepts
+fn key(px: f64) -> u64 {
+    px.to_bits()
+}
+
+fn price(key: u64) -> f64 {
+    f64::from_bits(key)
+}
+
+// Full L2 depth for one coin. Hyperliquid's `l2Book` channel sends whole
+// snapshots (`apply_snapshot`); `apply_level` applies single-level deltas for
+// feeds that send increments, where a zero size removes the level.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    pub time: u64,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces both sides. Unparseable, non-positive or non-finite levels are
+    // skipped. Returns false, leaving the book empty, for a crossed or
+    // one-sided snapshot.
+    pub fn apply_snapshot(&mut self, time: u64, bids: &[BookLevel], asks: &[BookLevel]) -> bool {
+        self.time = time;
+        self.bids.clear();
+        self.asks.clear();
+        for (side, levels) in [(BookSide::Bid, bids), (BookSide::Ask, asks)] {
+            for level in levels {
+                if let (Ok(px), Ok(sz)) = (level.px.parse(), level.sz.parse()) {
+                    self.apply_level(side, px, sz);
+                }
+            }
+        }
+        if self.best_bid().is_none() || self.best_ask().is_none() || self.is_crossed() {
+            self.bids.clear();
+            self.asks.clear();
+            return false;
+        }
+        true
+    }
+
+    pub fn apply_level(&mut self, side: BookSide, px: f64, sz: f64) {
+        if !(px.is_finite() && px > 0.0 && sz.is_finite()) {
+            return;
+        }
+        let levels = self.side_mut(side);
+        if sz <= 0.0 {
+            levels.remove(&key(px));
+        } else {
+            levels.insert(key(px), sz);
+        }
+    }
+
+    fn side_mut(&mut self, side: BookSide) -> &mut BTreeMap<u64, f64> {
+        match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        }
+    }
+
+    // (price, size) from the touch outwards
+    pub fn levels(&self, side: BookSide) -> Box<dyn Iterator<Item = (f64, f64)> + '_> {
+        match side {
+            BookSide::Bid => Box::new(self.bids.iter().rev().map(|(k, sz)| (price(*k), *sz))),
+            BookSide::Ask => Box::new(self.asks.iter().map(|(k, sz)| (price(*k), *sz))),
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.levels(BookSide::Bid).next()
+    }
+
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.levels(BookSide::Ask).next()
+    }
+
+    pub fn is_crossed(&self) -> bool {
+        matches!((self.best_bid(), self.best_ask()), (Some((bid, _)), Some((ask, _))) if bid >= ask)
+    }
+
+    pub fn mid(&self) -> Option<f64> {
+        Some((self.best_bid()?.0 + self.best_ask()?.0) / 2.0)
+    }
+
+    // Same shape as `top_of_book`: best prices and total resting size per side
+    pub fn top(&self) -> Option<(f64, f64, f64, f64)> {
+        let (bid, _) = self.best_bid()?;
+        let (ask, _) = self.best_ask()?;
+        Some((bid, ask, self.bids.values().sum(), self.asks.values().sum()))
+    }
+
+    // Size resting at exactly this price (0.0 if there is no such level)
+    pub fn depth_at_price(&self, side: BookSide, px: f64) -> f64 {
+        let levels = match side {
+            BookSide::Bid => &self.bids,
+            BookSide::Ask => &self.asks,
+        };
+        levels.get(&key(px)).copied().unwrap_or(0.0)
+    }
+
+    // Average price of taking `size` from the book: a buy walks the asks, a
+    // sell the bids. None if the visible depth is too thin.
+    pub fn vwap_to_size(&self, is_buy: bool, size: f64) -> Option<f64> {
+        if size <= 0.0 {
+            return None;
+        }
+        let side = if is_buy { BookSide::Ask } else { BookSide::Bid };
+        let (mut left, mut notional) = (size, 0.0);
+        for (px, sz) in self.levels(side) {
+            let take = sz.min(left);
+            notional += take * px;
+            left -= take;
+            if left <= 0.0 {
+                return Some(notional / size);
+            }
+        }
+        None
+    }
+
+    // Touch prices weighted by the opposite side's size: leans towards the side
+    // more likely to be traded through next
+    pub fn microprice(&self) -> Option<f64> {
+        let (bid, bid_sz) = self.best_bid()?;
+        let (ask, ask_sz) = self.best_ask()?;
+        Some((bid * ask_sz + ask * bid_sz) / (bid_sz + ask_sz))
+    }
+
+    // Mean of each side's size-weighted price over its first `levels` levels:
+    // unlike the microprice, a thin touch with depth behind it barely moves it
+    pub fn weighted_mid(&self, levels: usize) -> Option<f64> {
+        let vwap = |side| -> Option<f64> {
+            let (notional, size) = self
+                .levels(side)
+                .take(levels)
+                .fold((0.0, 0.0), |(n, s), (px, sz)| (n + px * sz, s + sz));
+            (size > 0.0).then(|| notional / size)
+        };
+        Some((vwap(BookSide::Bid)? + vwap(BookSide::Ask)?) / 2.0)
+    }
+
+    // Size imbalance over the first `levels` levels per side, weighting level i
+    // (0 = touch) by 1 / (i + 1). In [-1, 1]; > 0 is bid-heavy.
+    pub fn weighted_imbalance(&self, levels: usize) -> Option<f64> {
+        let weighted = |side| -> f64 {
+            self.levels(side)
+                .take(levels)
+                .enumerate()
+                .map(|(i, (_, sz))| sz / (i + 1) as f64)
+                .sum()
+        };
+        let (bid, ask) = (weighted(BookSide::Bid), weighted(BookSide::Ask));
+        (bid + ask > 0.0).then(|| (bid - ask) / (bid + ask))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(px: &str, sz: &str) -> BookLevel {
+        BookLevel {
+            px: px.to_string(),
+            sz: sz.to_string(),
+            n: 1,
+        }
+    }
+
+    fn book() -> OrderBook {
+        let mut book = OrderBook::new();
+        let bids = [level("99", "1"), level("100", "2"), level("98", "4")];
+        let asks = [level("101", "1"), level("102", "3"), level("bad", "1")];
+        assert!(book.apply_snapshot(1, &bids, &asks));
+        book
+    }
+
+    #[test]
+    fn test_queries_full_depth() {
+        let mut book = book();
+        assert_eq!(book.top(), Some((100.0, 101.0, 7.0, 4.0)));
+        assert_eq!(book.depth_at_price(BookSide::Bid, 98.0), 4.0);
+        assert_eq!(book.depth_at_price(BookSide::Ask, 100.0), 0.0);
+        // 1 @ 101 + 2 @ 102
+        assert!((book.vwap_to_size(true, 3.0).unwrap() - 101.666_666_666).abs() < 1e-6);
+        assert!(book.vwap_to_size(true, 10.0).is_none());
+        // Bid-heavy touch pulls the microprice above mid
+        assert!((book.microprice().unwrap() - 100.666_666_666).abs() < 1e-6);
+        // bids (200 + 99 + 392) / 7, asks (101 + 306) / 4
+        let expected = (691.0 / 7.0 + 407.0 / 4.0) / 2.0;
+        assert!((book.weighted_mid(3).unwrap() - expected).abs() < 1e-9);
+        // bids 2 + 1/2 + 4/3, asks 1 + 3/2
+        let expected = (23.0 / 6.0 - 2.5) / (23.0 / 6.0 + 2.5);
+        assert!((book.weighted_imbalance(3).unwrap() - expected).abs() < 1e-9);
+
+        book.apply_level(BookSide::Bid, 100.0, 0.0);
+        assert_eq!(book.best_bid(), Some((99.0, 1.0)));
+        book.apply_level(BookSide::Ask, 100.5, 2.0);
+        assert_eq!(book.best_ask(), Some((100.5, 2.0)));
+    }
+
+    #[test]
+    fn test_rejects_crossed_snapshots() {
+        let mut book = book();
+        assert!(!book.apply_snapshot(2, &[level("101", "1")], &[level("100", "1")]));
+        assert!(book.mid().is_none());
+    }
+}
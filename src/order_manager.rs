@@ -0,0 +1,454 @@
+use ethers::types::H160;
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{broadcast, mpsc::unbounded_channel};
+use uuid::Uuid;
+
+use crate::{
+    prelude::*, EventLog, InfoClient, Message, OrderOutcome, OrderUpdate, Subscription, TradeInfo,
+    UserData,
+};
+
+const FILL_CHANNEL_CAPACITY: usize = 256;
+const SIZE_EPSILON: f64 = 1e-9;
+
+// Lifecycle of an order we placed. Filled, Cancelled and Rejected are final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    New, // sent, not yet acknowledged
+    Resting,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderStatus {
+    pub fn is_open(self) -> bool {
+        matches!(
+            self,
+            OrderStatus::New | OrderStatus::Resting | OrderStatus::PartiallyFilled
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub cloid: Uuid,
+    pub oid: Option<u64>,
+    pub coin: String,
+    pub is_buy: bool,
+    pub limit_px: f64,
+    pub size: f64,
+    pub filled: f64,
+    pub avg_fill_px: f64,
+    pub status: OrderStatus,
+    pub updated_ms: u64,
+    fill_notional: f64, // sum of px * sz over the fills counted in `filled`
+    unconfirmed: f64,   // filled per the placement response, not yet seen as fills
+}
+
+impl TrackedOrder {
+    pub fn remaining(&self) -> f64 {
+        (self.size - self.filled).max(0.0)
+    }
+}
+
+// One increment of filled quantity, published to `subscribe_fills` receivers
+#[derive(Debug, Clone)]
+pub struct OrderFill {
+    pub cloid: Uuid,
+    pub coin: String,
+    pub is_buy: bool,
+    pub px: f64,
+    pub sz: f64,
+    pub time_ms: u64,
+    pub status: OrderStatus, // order status after this fill
+}
+
+#[derive(Default)]
+struct OrderBook {
+    orders: HashMap<Uuid, TrackedOrder>,
+    by_oid: HashMap<u64, Uuid>,
+    seen_tids: HashMap<u64, Uuid>, // fills already counted, by the order they were for
+}
+
+impl OrderBook {
+    fn resolve(&self, oid: u64, cloid: Option<&str>) -> Option<Uuid> {
+        self.by_oid
+            .get(&oid)
+            .copied()
+            .or_else(|| cloid.and_then(parse_cloid))
+            .filter(|cloid| self.orders.contains_key(cloid))
+    }
+}
+
+// The exchange reports cloids as 0x-prefixed hex
+fn parse_cloid(s: &str) -> Option<Uuid> {
+    Uuid::parse_str(s.trim_start_matches("0x")).ok()
+}
+
+// Tracks every order the bot places from submission to a final state, driven
+// by placement responses and the user's WebSocket order and fill events.
+// Clones share the same book.
+#[derive(Clone)]
+pub struct OrderManager {
+    book: Arc<Mutex<OrderBook>>,
+    fills: broadcast::Sender<OrderFill>,
+    event_log: Option<EventLog>, // fills and final statuses
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self {
+            book: Arc::new(Mutex::new(OrderBook::default())),
+            fills: broadcast::channel(FILL_CHANNEL_CAPACITY).0,
+            event_log: None,
+        }
+    }
+
+    pub fn with_event_log(mut self, log: EventLog) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+
+    // Subscribes to the user's order updates and events and applies them in a
+    // background task until the feed closes
+    pub async fn subscribe(&self, info: &mut InfoClient, user: H160) -> Result<()> {
+        let (tx, mut rx) = unbounded_channel();
+        info.subscribe(Subscription::OrderUpdates { user }, tx.clone())
+            .await?;
+        info.subscribe(Subscription::UserEvents { user }, tx)
+            .await?;
+        let manager = self.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                manager.on_message(&msg);
+            }
+        });
+        Ok(())
+    }
+
+    // Receiver for fills on tracked orders. A receiver that falls more than
+    // the channel capacity behind loses the oldest fills.
+    pub fn subscribe_fills(&self) -> broadcast::Receiver<OrderFill> {
+        self.fills.subscribe()
+    }
+
+    // Starts tracking an order before it is sent
+    pub fn track(&self, cloid: Uuid, coin: &str, is_buy: bool, limit_px: f64, size: f64) {
+        let order = TrackedOrder {
+            cloid,
+            oid: None,
+            coin: coin.to_string(),
+            is_buy,
+            limit_px,
+            size,
+            filled: 0.0,
+            avg_fill_px: 0.0,
+            status: OrderStatus::New,
+            updated_ms: 0,
+            fill_notional: 0.0,
+            unconfirmed: 0.0,
+        };
+        self.book.lock().unwrap().orders.insert(cloid, order);
+    }
+
+    // Applies the exchange's response to placing a tracked order
+    pub fn on_outcome(&self, cloid: Uuid, outcome: &OrderOutcome) {
+        let mut book = self.book.lock().unwrap();
+        let Some(order) = book.orders.get_mut(&cloid) else {
+            return;
+        };
+        let oid = match outcome {
+            OrderOutcome::Resting { oid } => {
+                advance(order, OrderStatus::Resting);
+                Some(*oid)
+            }
+            OrderOutcome::Filled { oid, size, avg_px } => {
+                // The fill may also arrive on the WebSocket; only the excess
+                // over what was already counted is new
+                let new = size - order.filled;
+                if new > SIZE_EPSILON {
+                    order.unconfirmed += new;
+                    let fill = apply_fill(order, *avg_px, new, order.updated_ms);
+                    self.publish(fill);
+                }
+                advance(order, OrderStatus::Filled);
+                Some(*oid)
+            }
+            OrderOutcome::Rejected(_) => {
+                if order.status.is_open() {
+                    advance(order, OrderStatus::Rejected);
+                    self.log_status(order);
+                }
+                None
+            }
+            OrderOutcome::Pending => None,
+        };
+        if let Some(oid) = oid {
+            order.oid = Some(oid);
+            book.by_oid.insert(oid, cloid);
+        }
+    }
+
+    pub fn on_message(&self, msg: &Message) {
+        match msg {
+            Message::OrderUpdates(updates) => {
+                for update in &updates.data {
+                    self.on_order_update(update);
+                }
+            }
+            Message::User(user) => {
+                if let UserData::Fills(fills) = &user.data {
+                    self.on_fills(fills);
+                }
+            }
+            // Snapshots replay history from before this session
+            Message::UserFills(fills) if fills.data.is_snapshot != Some(true) => {
+                self.on_fills(&fills.data.fills)
+            }
+            _ => {}
+        }
+    }
+
+    pub fn on_order_update(&self, update: &OrderUpdate) {
+        let mut book = self.book.lock().unwrap();
+        let Some(cloid) = book.resolve(update.order.oid, update.order.cloid.as_deref()) else {
+            return;
+        };
+        book.by_oid.insert(update.order.oid, cloid);
+        let order = book.orders.get_mut(&cloid).expect("resolved order");
+        order.oid = Some(update.order.oid);
+        order.updated_ms = order.updated_ms.max(update.status_timestamp);
+        let status = match update.status.as_str() {
+            "open" | "triggered" if order.filled > SIZE_EPSILON => OrderStatus::PartiallyFilled,
+            "open" | "triggered" => OrderStatus::Resting,
+            "filled" => OrderStatus::Filled,
+            s if s.ends_with("ejected") => OrderStatus::Rejected,
+            // canceled, marginCanceled, reduceOnlyCanceled, ...
+            s if s.ends_with("anceled") => OrderStatus::Cancelled,
+            _ => return,
+        };
+        let was_open = order.status.is_open();
+        advance(order, status);
+        if was_open && !order.status.is_open() {
+            self.log_status(order);
+        }
+    }
+
+    pub fn on_fills(&self, fills: &[TradeInfo]) {
+        let mut book = self.book.lock().unwrap();
+        for trade in fills {
+            let Some(cloid) = book.resolve(trade.oid, trade.cloid.as_deref()) else {
+                continue;
+            };
+            if book.seen_tids.insert(trade.tid, cloid).is_some() {
+                continue;
+            }
+            let (Ok(px), Ok(sz)) = (trade.px.parse::<f64>(), trade.sz.parse::<f64>()) else {
+                continue;
+            };
+            book.by_oid.insert(trade.oid, cloid);
+            let order = book.orders.get_mut(&cloid).expect("resolved order");
+            order.oid = Some(trade.oid);
+            // Quantity already counted from the placement response is not new
+            let confirmed = sz.min(order.unconfirmed);
+            order.unconfirmed -= confirmed;
+            if sz - confirmed > SIZE_EPSILON {
+                let fill = apply_fill(order, px, sz - confirmed, trade.time);
+                self.publish(fill);
+            }
+        }
+    }
+
+    fn publish(&self, fill: OrderFill) {
+        if let Some(log) = &self.event_log {
+            log.record(
+                "fill",
+                json!({
+                    "coin": fill.coin, "cloid": fill.cloid.to_string(), "is_buy": fill.is_buy,
+                    "px": fill.px, "sz": fill.sz, "fill_time": fill.time_ms,
+                    "status": format!("{:?}", fill.status),
+                }),
+            );
+        }
+        let _ = self.fills.send(fill);
+    }
+
+    // An order reaching a final state
+    fn log_status(&self, order: &TrackedOrder) {
+        if let Some(log) = &self.event_log {
+            log.record(
+                "order_status",
+                json!({
+                    "coin": order.coin, "cloid": order.cloid.to_string(), "oid": order.oid,
+                    "status": format!("{:?}", order.status), "filled": order.filled,
+                }),
+            );
+        }
+    }
+
+    pub fn get(&self, cloid: Uuid) -> Option<TrackedOrder> {
+        self.book.lock().unwrap().orders.get(&cloid).cloned()
+    }
+
+    pub fn open_orders(&self) -> Vec<TrackedOrder> {
+        let book = self.book.lock().unwrap();
+        let mut open: Vec<_> = book
+            .orders
+            .values()
+            .filter(|o| o.status.is_open())
+            .cloned()
+            .collect();
+        open.sort_by_key(|o| o.updated_ms);
+        open
+    }
+
+    pub fn filled_qty(&self, cloid: Uuid) -> f64 {
+        self.get(cloid).map_or(0.0, |o| o.filled)
+    }
+
+    // Drops orders in a final state; call periodically in long-running bots
+    pub fn clear_closed(&self) {
+        let mut book = self.book.lock().unwrap();
+        book.orders.retain(|_, o| o.status.is_open());
+        // Fills for a dropped order no longer resolve, so neither do their repeats
+        let OrderBook {
+            orders,
+            by_oid,
+            seen_tids,
+        } = &mut *book;
+        by_oid.retain(|_, cloid| orders.contains_key(cloid));
+        seen_tids.retain(|_, cloid| orders.contains_key(cloid));
+    }
+}
+
+// Final states are sticky: a late "open" update never reopens a filled or
+// cancelled order
+fn advance(order: &mut TrackedOrder, status: OrderStatus) {
+    if order.status.is_open() {
+        order.status = status;
+    }
+}
+
+fn apply_fill(order: &mut TrackedOrder, px: f64, sz: f64, time_ms: u64) -> OrderFill {
+    order.filled += sz;
+    order.fill_notional += px * sz;
+    order.avg_fill_px = order.fill_notional / order.filled;
+    order.updated_ms = order.updated_ms.max(time_ms);
+    if order.remaining() <= SIZE_EPSILON {
+        advance(order, OrderStatus::Filled);
+    } else {
+        advance(order, OrderStatus::PartiallyFilled);
+    }
+    OrderFill {
+        cloid: order.cloid,
+        coin: order.coin.clone(),
+        is_buy: order.is_buy,
+        px,
+        sz,
+        time_ms,
+        status: order.status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(oid: u64, tid: u64, px: &str, sz: &str) -> TradeInfo {
+        serde_json::from_value(serde_json::json!({
+            "coin": "BTC", "side": "B", "px": px, "sz": sz, "time": 1_000 + tid,
+            "hash": "0x0", "startPosition": "0", "dir": "Open Long", "closedPnl": "0",
+            "oid": oid, "cloid": null, "crossed": false, "fee": "0", "feeToken": "USDC",
+            "tid": tid
+        }))
+        .unwrap()
+    }
+
+    fn update(oid: u64, cloid: Uuid, status: &str) -> OrderUpdate {
+        serde_json::from_value(serde_json::json!({
+            "order": {
+                "coin": "BTC", "side": "B", "limitPx": "100", "sz": "1", "oid": oid,
+                "timestamp": 1_000, "origSz": "2", "cloid": format!("0x{}", cloid.simple())
+            },
+            "status": status,
+            "statusTimestamp": 2_000
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tracks_order_through_partial_and_full_fill() {
+        let manager = OrderManager::new();
+        let mut fills = manager.subscribe_fills();
+        let cloid = Uuid::new_v4();
+        manager.track(cloid, "BTC", true, 100.0, 2.0);
+        assert_eq!(manager.open_orders().len(), 1);
+
+        // The first update carries only the cloid; it also teaches us the oid
+        manager.on_order_update(&update(7, cloid, "open"));
+        assert_eq!(manager.get(cloid).unwrap().status, OrderStatus::Resting);
+
+        manager.on_fills(&[trade(7, 1, "100", "0.5")]);
+        manager.on_fills(&[trade(7, 1, "100", "0.5")]); // redelivered
+        assert_eq!(
+            manager.get(cloid).unwrap().status,
+            OrderStatus::PartiallyFilled
+        );
+        assert_eq!(manager.filled_qty(cloid), 0.5);
+
+        manager.on_fills(&[trade(7, 2, "101", "1.5")]);
+        let order = manager.get(cloid).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert!((order.avg_fill_px - 100.75).abs() < 1e-9);
+        assert!(manager.open_orders().is_empty());
+
+        assert_eq!(fills.try_recv().unwrap().sz, 0.5);
+        assert_eq!(fills.try_recv().unwrap().status, OrderStatus::Filled);
+        assert!(fills.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_final_states_are_sticky_and_fills_are_not_double_counted() {
+        let manager = OrderManager::new();
+        let cloid = Uuid::new_v4();
+        manager.track(cloid, "BTC", false, 100.0, 1.0);
+        manager.on_outcome(
+            cloid,
+            &OrderOutcome::Filled {
+                oid: 9,
+                size: 1.0,
+                avg_px: 99.5,
+            },
+        );
+        // The same fill echoed on the WebSocket, then a stale open update
+        manager.on_fills(&[trade(9, 3, "99.5", "1")]);
+        manager.on_order_update(&update(9, cloid, "open"));
+        let order = manager.get(cloid).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled, 1.0);
+
+        let cancelled = Uuid::new_v4();
+        manager.track(cancelled, "BTC", true, 90.0, 1.0);
+        manager.on_order_update(&update(10, cancelled, "marginCanceled"));
+        assert_eq!(
+            manager.get(cancelled).unwrap().status,
+            OrderStatus::Cancelled
+        );
+
+        manager.clear_closed();
+        assert!(manager.get(cloid).is_none());
+        assert!(manager.book.lock().unwrap().seen_tids.is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use log::info;
+use std::collections::VecDeque;
+
+use crate::{
+    percent_change, AssetSpec, BookSample, DesiredQuote, ExchangeConfig, InventoryHalfLife,
+    OrderBook, OrderFill, QueueFlow, QuoteCandidate, QuoteReconciler, QuoteValueModel,
+    RestingQuote, SignalState, StatusReporter, Strategy, StrategyConfig, StrategyOrder,
+};
+
+pub(crate) const PING_PONG_MAX_POSITION: f64 = 0.01; // base units
+pub(crate) const QUOTE_HORIZON_MS: u64 = 2_000; // how long a quote is expected to rest, for its value
+pub(crate) const REQUOTE_TICKS: f64 = 1.0; // drift a resting quote may have before it is amended
+pub(crate) const TREND_THRESHOLD: f64 = 0.02; // percent change over five books that counts as a trend
+pub(crate) const STOP_LOSS: f64 = 3.0; // unrealized loss, in quote currency, that pulls every quote
+pub(crate) const INVENTORY_HALF_LIFE_MS: f64 = 30_000.0;
+pub(crate) const INVENTORY_MAX_SKEW_BPS: f64 = 5.0;
+pub(crate) const BOOK_HISTORY: usize = 50;
+
+// Resting size at the best level, i.e. the queue a new order joins behind
+fn touch_size(level: Option<(f64, f64)>) -> f64 {
+    level.map_or(0.0, |(_, sz)| sz)
+}
+
+// Volume-generating maker that aims to stay flat: leans into a trend with a
+// single quote on its side, and otherwise ping-pongs both sides, skipping a
+// side whose quote would not pay for its fees and adverse selection at the
+// current queue. Inventory that outlives its half-life schedule skews the
+// reducing quote towards the far touch. Each book updates the quotes it wants
+// and each timer reconciles the resting ones with them, leaving quotes within
+// a tick alone and amending the rest. A loss beyond `STOP_LOSS` pulls them all.
+pub struct PingPongMaker {
+    exchange: ExchangeConfig, // leverage and the margin sized into each quote
+    spec: AssetSpec,          // price tick and size decimals of the quoted coin
+    ev_model: QuoteValueModel,
+    half_life: InventoryHalfLife,
+    bid_flow: QueueFlow,
+    ask_flow: QueueFlow,
+    reconciler: QuoteReconciler,
+    desired: Vec<DesiredQuote>,
+    resting: Vec<RestingQuote>,
+    position_size: f64,
+    net_volume: f64,
+    open_price: Option<f64>,
+    trend_score: f64,
+    book_history: VecDeque<BookSample>,
+    reporter: StatusReporter,
+}
+
+impl Default for PingPongMaker {
+    fn default() -> Self {
+        Self::new(ExchangeConfig::default())
+    }
+}
+
+impl PingPongMaker {
+    pub fn new(exchange: ExchangeConfig) -> Self {
+        Self {
+            // Tolerance follows the tick at the current price, set per book
+            reconciler: QuoteReconciler::new(0.0),
+            exchange,
+            spec: AssetSpec::default(),
+            // Quotes are only placed when their expected value over the
+            // horizon is positive
+            ev_model: QuoteValueModel {
+                horizon_ms: QUOTE_HORIZON_MS,
+                ..Default::default()
+            },
+            half_life: InventoryHalfLife::new(INVENTORY_HALF_LIFE_MS, INVENTORY_MAX_SKEW_BPS),
+            bid_flow: QueueFlow::new(),
+            ask_flow: QueueFlow::new(),
+            desired: Vec::new(),
+            resting: Vec::new(),
+            position_size: 0.0,
+            net_volume: 0.0,
+            open_price: None,
+            trend_score: 0.0,
+            book_history: VecDeque::with_capacity(BOOK_HISTORY + 1),
+            reporter: StatusReporter::default(),
+        }
+    }
+
+    pub fn with_asset_spec(mut self, spec: AssetSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    fn cancel_all(&mut self) -> Vec<StrategyOrder> {
+        self.desired.clear();
+        self.resting
+            .drain(..)
+            .map(|q| StrategyOrder::Cancel { cloid: q.cloid })
+            .collect()
+    }
+}
+
+impl Strategy for PingPongMaker {
+    fn name(&self) -> &str {
+        "ping_pong"
+    }
+
+    fn on_book(&mut self, book: &OrderBook, _state: &SignalState) -> Vec<StrategyOrder> {
+        let Some((bid_px, ask_px, bid_volume, ask_volume)) = book.top() else {
+            return Vec::new();
+        };
+        let now_ms = book.time;
+        let mid = (bid_px + ask_px) / 2.0;
+        let spread = ask_px - bid_px;
+        let (bid_queue, ask_queue) = (touch_size(book.best_bid()), touch_size(book.best_ask()));
+        self.bid_flow.on_touch(now_ms, bid_px, bid_queue);
+        self.ask_flow.on_touch(now_ms, ask_px, ask_queue);
+        self.book_history.push_back(BookSample {
+            timestamp_ms: now_ms,
+            mid_price: mid,
+            best_bid: bid_px,
+            best_ask: ask_px,
+            bid_volume,
+            ask_volume,
+        });
+        if self.book_history.len() > BOOK_HISTORY {
+            self.book_history.pop_front();
+        }
+        self.trend_score = percent_change(&self.book_history, 5);
+        self.half_life.on_position(now_ms, self.position_size);
+        let tick = self.spec.tick_size(mid);
+        self.reconciler.px_tolerance = tick * REQUOTE_TICKS;
+        let touch = (bid_px, ask_px);
+
+        if let Some(open_px) = self.open_price {
+            if self.position_size * (mid - open_px) < -STOP_LOSS {
+                self.position_size = 0.0;
+                self.open_price = None;
+                return self.cancel_all();
+            }
+        }
+
+        // Lean into a trend with a quote on its side only; without one,
+        // ping-pong both sides where the quote is worth its costs
+        let trending = self.trend_score.abs() >= TREND_THRESHOLD;
+        self.desired.clear();
+        for (is_bid, quote, queue) in [(true, bid_px, bid_queue), (false, ask_px, ask_queue)] {
+            let key = if is_bid { "bid" } else { "ask" };
+            let with_trend = if is_bid {
+                self.trend_score > TREND_THRESHOLD && self.position_size < PING_PONG_MAX_POSITION
+            } else {
+                self.trend_score < -TREND_THRESHOLD && self.position_size > -PING_PONG_MAX_POSITION
+            };
+            if trending && !with_trend {
+                continue;
+            }
+            let px = self
+                .half_life
+                .adjust(now_ms, self.position_size, is_bid, quote, touch, tick);
+            let px = self.spec.round_price(px);
+            let size = self
+                .spec
+                .qty(px, self.exchange.balance, self.exchange.leverage);
+            if !trending {
+                let flow = if is_bid {
+                    &self.bid_flow
+                } else {
+                    &self.ask_flow
+                };
+                let ev = self.ev_model.evaluate(&QuoteCandidate {
+                    is_buy: is_bid,
+                    price: px,
+                    size,
+                    mid,
+                    queue_ahead: queue,
+                    flow_per_sec: flow.rate(),
+                });
+                if !ev.worth_quoting() {
+                    info!("Skipping {key}: {ev:?}");
+                    continue;
+                }
+            }
+            self.desired.push(DesiredQuote {
+                is_buy: is_bid,
+                px,
+                sz: size,
+            });
+        }
+
+        self.reporter.report(
+            "ping_pong",
+            format!(
+                "[Bot] Pos: {:.3} | Vol: {:.2} | Mid: {:.2} | Spr: {:.4} | Trend: {:.2}",
+                self.position_size, self.net_volume, mid, spread, self.trend_score
+            ),
+        );
+        Vec::new()
+    }
+
+    // Brings the resting quotes in line with the ones the last book wanted
+    fn on_timer(&mut self, _now_ms: u64) -> Vec<StrategyOrder> {
+        let orders = self.reconciler.reconcile(&self.desired, &self.resting);
+        for order in &orders {
+            match *order {
+                StrategyOrder::Place {
+                    cloid,
+                    is_buy,
+                    px,
+                    sz,
+                    ..
+                } => self.resting.push(RestingQuote {
+                    cloid,
+                    is_buy,
+                    px,
+                    sz,
+                }),
+                StrategyOrder::Modify { cloid, px, sz, .. } => {
+                    if let Some(q) = self.resting.iter_mut().find(|q| q.cloid == cloid) {
+                        (q.px, q.sz) = (px, sz);
+                    }
+                }
+                StrategyOrder::Cancel { cloid } => self.resting.retain(|q| q.cloid != cloid),
+            }
+        }
+        orders
+    }
+
+    fn configure(&mut self, config: &StrategyConfig) {
+        self.reconciler.tif = config.tif.quote;
+    }
+
+    fn on_fill(&mut self, fill: &OrderFill) {
+        let signed = if fill.is_buy { fill.sz } else { -fill.sz };
+        self.position_size += signed;
+        self.net_volume += fill.px * fill.sz;
+        if self.open_price.is_none() {
+            self.open_price = Some(fill.px);
+        }
+        info!("Filled {} {:.4} @ {:.2}", fill.coin, signed, fill.px);
+        // A filled quote frees its side for the next one
+        if let Some(q) = self.resting.iter_mut().find(|q| q.cloid == fill.cloid) {
+            q.sz -= fill.sz;
+        }
+        if !fill.status.is_open() {
+            self.resting.retain(|q| q.cloid != fill.cloid);
+        }
+    }
+
+    fn on_position(&mut self, size: f64, entry_px: Option<f64>) {
+        self.position_size = size;
+        self.open_price = entry_px;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BookLevel, OrderStatus};
+
+    fn book(time: u64, bid: f64) -> OrderBook {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        let mut book = OrderBook::new();
+        assert!(book.apply_snapshot(time, &[level(bid)], &[level(bid + 1.0)]));
+        book
+    }
+
+    #[test]
+    fn test_quotes_with_the_trend_and_amends_instead_of_replacing() {
+        let mut maker = PingPongMaker::default();
+        let state = SignalState::default();
+        // A still book has no flow to fill a quote; then the mid climbs: bid only
+        for (i, bid) in [100.0, 100.0, 100.0, 100.2, 100.4].into_iter().enumerate() {
+            assert!(maker.on_book(&book(i as u64 * 100, bid), &state).is_empty());
+        }
+        let orders = maker.on_timer(450);
+        let [StrategyOrder::Place {
+            cloid,
+            is_buy: true,
+            px,
+            ..
+        }] = orders[..]
+        else {
+            panic!("expected a single bid, got {orders:?}");
+        };
+        assert_eq!(px, 100.4);
+        // An unchanged quote rests however old it gets
+        assert!(maker.on_timer(3_000).is_empty());
+        // A moved touch amends the bid rather than cancelling it
+        maker.on_book(&book(3_100, 100.6), &state);
+        let orders = maker.on_timer(3_200);
+        let [StrategyOrder::Modify {
+            cloid: c, px, sz, ..
+        }] = orders[..]
+        else {
+            panic!("expected the bid amended, got {orders:?}");
+        };
+        assert_eq!(c, cloid);
+        assert!((px - 100.6).abs() < 1e-9);
+
+        // A filled quote frees its side for a new one
+        let fill = OrderFill {
+            cloid,
+            coin: "BTC".to_string(),
+            is_buy: true,
+            px: 100.6,
+            sz,
+            time_ms: 3_300,
+            status: OrderStatus::Filled,
+        };
+        maker.on_fill(&fill);
+        assert!(maker.resting.is_empty());
+        let orders = maker.on_timer(3_400);
+        assert!(matches!(orders[..], [StrategyOrder::Place { cloid: c, .. }] if c != cloid));
+    }
+}
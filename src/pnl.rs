@@ -0,0 +1,450 @@
+use ethers::types::H160;
+use log::{info, warn};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::{
+    prelude::*, FillRole, InfoClient, Message, Subscription, TradeInfo, UserData, UserStateResponse,
+};
+
+pub(crate) const POSITION_EPSILON: f64 = 1e-12; // Smaller positions are flat
+pub(crate) const SEEN_RETENTION_MS: u64 = 24 * 3_600_000; // Trade ids and payments kept this far behind the newest
+const PRUNE_INTERVAL_MS: u64 = 3_600_000; // How far the retention horizon moves before pruning
+
+// One coin's account from its fills: position at an average entry price,
+// PnL realized by reducing it, fees paid by role and funding received
+// (negative when paid). Unrealized PnL is marked at the last mark price.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CoinPnl {
+    pub position: f64, // signed, base units
+    pub avg_entry: f64,
+    pub mark: f64,
+    pub realized: f64,
+    pub maker_fees: f64, // negative for rebates
+    pub taker_fees: f64,
+    pub funding: f64,
+    pub volume: f64, // notional filled
+}
+
+impl CoinPnl {
+    pub fn unrealized(&self) -> f64 {
+        if self.mark > 0.0 {
+            self.position * (self.mark - self.avg_entry)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn fees(&self) -> f64 {
+        self.maker_fees + self.taker_fees
+    }
+
+    // Realized and unrealized PnL after fees and funding
+    pub fn net(&self) -> f64 {
+        self.realized + self.unrealized() - self.fees() + self.funding
+    }
+
+    fn fill(&mut self, is_buy: bool, px: f64, sz: f64) {
+        let signed = if is_buy { sz } else { -sz };
+        self.volume += px * sz;
+        if self.position * signed >= 0.0 {
+            // Opening or adding: the entry is the size-weighted average
+            let size = self.position.abs() + sz;
+            self.avg_entry = (self.avg_entry * self.position.abs() + px * sz) / size;
+            self.position += signed;
+            return;
+        }
+        // Reducing realizes against the entry; the rest opens the other side
+        let closed = sz.min(self.position.abs());
+        self.realized += closed * (px - self.avg_entry) * self.position.signum();
+        self.position += signed;
+        if self.position.abs() <= POSITION_EPSILON {
+            self.position = 0.0;
+            self.avg_entry = 0.0;
+        } else if self.position * signed > 0.0 {
+            self.avg_entry = px;
+        }
+    }
+}
+
+// Every coin's account summed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PnlTotals {
+    pub realized: f64,
+    pub unrealized: f64,
+    pub maker_fees: f64,
+    pub taker_fees: f64,
+    pub funding: f64,
+    pub volume: f64,
+}
+
+impl PnlTotals {
+    pub fn fees(&self) -> f64 {
+        self.maker_fees + self.taker_fees
+    }
+
+    pub fn net(&self) -> f64 {
+        self.realized + self.unrealized - self.fees() + self.funding
+    }
+}
+
+#[derive(Debug, Default)]
+struct PnlBook {
+    coins: BTreeMap<String, CoinPnl>,
+    seen_tids: HashMap<u64, u64>, // trade id -> fill time
+    seen_funding: HashSet<(String, u64)>,
+    horizon_ms: u64, // fills and payments before this are forgotten, and ignored
+}
+
+impl PnlBook {
+    // Whether a fill or payment at `time` is too old to tell apart from one
+    // already counted. The horizon trails the newest time by
+    // `SEEN_RETENTION_MS`; moving it drops what fell behind.
+    fn too_old(&mut self, time: u64) -> bool {
+        let horizon = time.saturating_sub(SEEN_RETENTION_MS);
+        if horizon >= self.horizon_ms + PRUNE_INTERVAL_MS {
+            self.horizon_ms = horizon;
+            self.seen_tids.retain(|_, t| *t >= horizon);
+            self.seen_funding.retain(|(_, t)| *t >= horizon);
+        }
+        time < self.horizon_ms
+    }
+}
+
+// PnL accounting from the exchange's own fills and funding instead of mid
+// deltas. Fills come from the user fills or user events subscription (each
+// trade id counted once, snapshots of past fills skipped), funding from the
+// user fundings subscription and `user_funding_history`, deduplicated by coin
+// and time, and marks from all mids. Positions opened before tracking began
+// are seeded from the account's state, or from a fill's start position.
+// Fills and payments more than `SEEN_RETENTION_MS` older than the newest are
+// ignored. Clones share one tracker.
+#[derive(Debug, Clone, Default)]
+pub struct PnlTracker {
+    book: Arc<Mutex<PnlBook>>,
+}
+
+impl PnlTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Follows `user`'s fills and funding, and every mid, on its own
+    // subscriptions, from the positions the account holds now
+    pub async fn subscribe(&self, info: &mut InfoClient, user: H160) -> Result<()> {
+        let (tx, mut rx) = unbounded_channel();
+        info.subscribe(Subscription::UserFills { user }, tx.clone())
+            .await?;
+        info.subscribe(Subscription::UserFundings { user }, tx.clone())
+            .await?;
+        info.subscribe(Subscription::AllMids, tx).await?;
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                tracker.on_message(&msg);
+            }
+        });
+        self.seed(&info.user_state(user).await?);
+        Ok(())
+    }
+
+    // Takes each open position and its entry from the account's state, for
+    // coins with no fills counted yet
+    pub fn seed(&self, state: &UserStateResponse) {
+        for asset in &state.asset_positions {
+            let p = &asset.position;
+            let Ok(size) = p.szi.parse::<f64>() else {
+                continue;
+            };
+            let entry = p.entry_px.as_deref().and_then(|px| px.parse().ok());
+            self.seed_position(&p.coin, size, entry.unwrap_or(0.0));
+        }
+    }
+
+    fn seed_position(&self, coin: &str, position: f64, avg_entry: f64) {
+        if position.abs() <= POSITION_EPSILON {
+            return;
+        }
+        let mut book = self.book.lock().unwrap();
+        if !book.coins.contains_key(coin) {
+            info!("[PnL] {coin} starts at {position} @ {avg_entry}");
+            book.coins.insert(
+                coin.to_string(),
+                CoinPnl {
+                    position,
+                    avg_entry,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    // Polls `user_funding_history` from `since_ms` every `period`, for
+    // payments the subscription missed while disconnected
+    pub fn poll_funding(&self, info: InfoClient, user: H160, since_ms: u64, period: Duration) {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(period);
+            loop {
+                timer.tick().await;
+                if let Err(e) = tracker.load_funding(&info, user, since_ms).await {
+                    warn!("[PnL] Funding history failed: {e}");
+                }
+            }
+        });
+    }
+
+    pub async fn load_funding(&self, info: &InfoClient, user: H160, since_ms: u64) -> Result<()> {
+        for payment in info.user_funding_history(user, since_ms, None).await? {
+            if let Ok(usdc) = payment.delta.usdc.parse() {
+                self.on_funding(&payment.delta.coin, payment.time, usdc);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn on_message(&self, msg: &Message) {
+        match msg {
+            Message::UserFills(fills) if fills.data.is_snapshot != Some(true) => {
+                fills.data.fills.iter().for_each(|f| self.on_trade(f));
+            }
+            Message::User(user) => match &user.data {
+                UserData::Fills(fills) => fills.iter().for_each(|f| self.on_trade(f)),
+                UserData::Funding(f) => {
+                    if let Ok(usdc) = f.usdc.parse() {
+                        self.on_funding(&f.coin, f.time, usdc);
+                    }
+                }
+                _ => {}
+            },
+            Message::UserFundings(fundings) if fundings.data.is_snapshot != Some(true) => {
+                for f in &fundings.data.fundings {
+                    if let Ok(usdc) = f.usdc.parse() {
+                        self.on_funding(&f.coin, f.time, usdc);
+                    }
+                }
+            }
+            Message::AllMids(mids) => {
+                for (coin, mid) in &mids.data.mids {
+                    if let Ok(mid) = mid.parse() {
+                        self.set_mark(coin, mid);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // A fill as the exchange reports it; repeated trade ids are ignored. The
+    // first fill of a coin seeds its position from the fill's start position,
+    // entered at the fill's price.
+    pub fn on_trade(&self, trade: &TradeInfo) {
+        let (Ok(px), Ok(sz), Ok(fee)) = (
+            trade.px.parse::<f64>(),
+            trade.sz.parse::<f64>(),
+            trade.fee.parse::<f64>(),
+        ) else {
+            return;
+        };
+        {
+            let mut book = self.book.lock().unwrap();
+            if book.too_old(trade.time) || book.seen_tids.insert(trade.tid, trade.time).is_some() {
+                return;
+            }
+        }
+        if let Ok(start) = trade.start_position.parse() {
+            self.seed_position(&trade.coin, start, px);
+        }
+        let role = if trade.crossed {
+            FillRole::Taker
+        } else {
+            FillRole::Maker
+        };
+        self.on_fill(&trade.coin, trade.side == "B", px, sz, fee, role);
+    }
+
+    pub fn on_fill(&self, coin: &str, is_buy: bool, px: f64, sz: f64, fee: f64, role: FillRole) {
+        let mut book = self.book.lock().unwrap();
+        let pnl = book.coins.entry(coin.to_string()).or_default();
+        pnl.fill(is_buy, px, sz);
+        match role {
+            FillRole::Maker => pnl.maker_fees += fee,
+            FillRole::Taker => pnl.taker_fees += fee,
+        }
+    }
+
+    // A funding payment, counted once per coin and time
+    pub fn on_funding(&self, coin: &str, time: u64, usdc: f64) {
+        let mut book = self.book.lock().unwrap();
+        if !book.too_old(time) && book.seen_funding.insert((coin.to_string(), time)) {
+            info!("[PnL] {coin} funding {usdc:+.4} USDC");
+            book.coins.entry(coin.to_string()).or_default().funding += usdc;
+        }
+    }
+
+    pub fn set_mark(&self, coin: &str, px: f64) {
+        if let Some(pnl) = self.book.lock().unwrap().coins.get_mut(coin) {
+            pnl.mark = px;
+        }
+    }
+
+    pub fn coin(&self, coin: &str) -> CoinPnl {
+        let book = self.book.lock().unwrap();
+        book.coins.get(coin).copied().unwrap_or_default()
+    }
+
+    pub fn total(&self) -> PnlTotals {
+        let book = self.book.lock().unwrap();
+        book.coins
+            .values()
+            .fold(PnlTotals::default(), |mut total, c| {
+                total.realized += c.realized;
+                total.unrealized += c.unrealized();
+                total.maker_fees += c.maker_fees;
+                total.taker_fees += c.taker_fees;
+                total.funding += c.funding;
+                total.volume += c.volume;
+                total
+            })
+    }
+
+    // One line per coin and a total, for the admin `pnl` command
+    pub fn summary(&self) -> String {
+        let coins: Vec<_> = {
+            let book = self.book.lock().unwrap();
+            book.coins
+                .iter()
+                .map(|(coin, c)| (coin.clone(), *c))
+                .collect()
+        };
+        if coins.is_empty() {
+            return "no fills yet".to_string();
+        }
+        let mut lines: Vec<_> = coins
+            .iter()
+            .map(|(coin, c)| {
+                format!(
+                    "{coin}: net {:+.4} | realized {:+.4} | unrealized {:+.4} | fees {:.4} (maker {:.4}, taker {:.4}) | funding {:+.4} | pos {:.4} @ {:.4}",
+                    c.net(),
+                    c.realized,
+                    c.unrealized(),
+                    c.fees(),
+                    c.maker_fees,
+                    c.taker_fees,
+                    c.funding,
+                    c.position,
+                    c.avg_entry
+                )
+            })
+            .collect();
+        let t = self.total();
+        lines.push(format!(
+            "total: net {:+.4} | realized {:+.4} | unrealized {:+.4} | fees {:.4} | funding {:+.4} | volume ${:.2}",
+            t.net(),
+            t.realized,
+            t.unrealized,
+            t.fees(),
+            t.funding,
+            t.volume
+        ));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn trade(coin: &str, tid: u64, time: u64, side: &str, px: &str, start: &str) -> TradeInfo {
+        serde_json::from_value(json!({
+            "coin": coin, "side": side, "px": px, "sz": "1", "time": time,
+            "hash": "0x0", "startPosition": start, "dir": "Close Long", "closedPnl": "0",
+            "oid": tid, "cloid": null, "crossed": true, "fee": "0", "feeToken": "USDC",
+            "tid": tid
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_average_entry_realized_fees_and_funding() {
+        let pnl = PnlTracker::new();
+        pnl.on_fill("BTC", true, 100.0, 1.0, 0.01, FillRole::Maker);
+        pnl.on_fill("BTC", true, 110.0, 1.0, 0.02, FillRole::Taker);
+        let btc = pnl.coin("BTC");
+        assert_eq!((btc.position, btc.avg_entry), (2.0, 105.0));
+
+        // Selling through the position realizes against the average entry
+        // and opens a short at the fill price
+        pnl.on_fill("BTC", false, 120.0, 3.0, -0.01, FillRole::Maker);
+        pnl.set_mark("BTC", 118.0);
+        pnl.on_funding("BTC", 3_600_000, -0.5);
+        pnl.on_funding("BTC", 3_600_000, -0.5); // the same payment again
+        let btc = pnl.coin("BTC");
+        assert_eq!((btc.position, btc.avg_entry), (-1.0, 120.0));
+        assert_eq!(btc.realized, 30.0);
+        assert_eq!(btc.unrealized(), 2.0);
+        assert_eq!((btc.maker_fees, btc.taker_fees), (0.0, 0.02));
+        assert_eq!(btc.funding, -0.5);
+        assert!((btc.net() - 31.48).abs() < 1e-9);
+        assert_eq!(btc.volume, 570.0);
+        assert!((pnl.total().net() - 31.48).abs() < 1e-9);
+        assert!(pnl.summary().contains("total: net +31.4800"));
+    }
+
+    #[test]
+    fn test_seeds_open_positions_and_forgets_old_fills() {
+        let summary = json!({
+            "accountValue": "1000", "totalMarginUsed": "0", "totalNtlPos": "0", "totalRawUsd": "1000"
+        });
+        let state: UserStateResponse = serde_json::from_value(json!({
+            "assetPositions": [{
+                "type": "oneWay",
+                "position": {
+                    "coin": "BTC", "entryPx": "100.0", "szi": "2.0",
+                    "leverage": {"type": "cross", "value": 20},
+                    "liquidationPx": null, "marginUsed": "10.0", "positionValue": "200.0",
+                    "returnOnEquity": "0.0", "unrealizedPnl": "0.0", "maxLeverage": 40,
+                    "cumFunding": {"allTime": "0", "sinceOpen": "0", "sinceChange": "0"}
+                }
+            }],
+            "crossMarginSummary": summary,
+            "marginSummary": summary,
+            "withdrawable": "1000"
+        }))
+        .unwrap();
+        let pnl = PnlTracker::new();
+        pnl.seed(&state);
+        // Selling out of the held position realizes against its entry
+        let day = SEEN_RETENTION_MS;
+        pnl.on_trade(&trade("BTC", 1, day, "A", "110", "2.0"));
+        let btc = pnl.coin("BTC");
+        assert_eq!(
+            (btc.position, btc.avg_entry, btc.realized),
+            (1.0, 100.0, 10.0)
+        );
+        // A coin missing from the state starts from the fill's start position
+        pnl.on_trade(&trade("ETH", 2, day, "A", "50", "-3.0"));
+        assert_eq!(pnl.coin("ETH").position, -4.0);
+
+        // A day and more later the first trade id is forgotten, and the same
+        // fill arriving again is ignored as too old
+        pnl.on_trade(&trade(
+            "BTC",
+            3,
+            2 * day + PRUNE_INTERVAL_MS,
+            "A",
+            "120",
+            "1.0",
+        ));
+        assert!(!pnl.book.lock().unwrap().seen_tids.contains_key(&1));
+        pnl.on_trade(&trade("BTC", 1, day, "A", "110", "2.0"));
+        assert_eq!(pnl.coin("BTC").position, 0.0);
+        pnl.on_funding("BTC", day, 1.0);
+        assert_eq!(pnl.coin("BTC").funding, 0.0);
+    }
+}
@@ -0,0 +1,189 @@
+use log::warn;
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+    asset_specs::PRICE_SIG_FIGS, prelude::*, AssetSpecs, ClientOrderRequest, Error, MarketEvent,
+};
+
+// Fat-finger limits on a single order. `price_collar` is a fraction of the
+// mid: 0.05 rejects orders more than 5% from it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PreTradeLimits {
+    pub max_order_size: Option<f64>,     // base units
+    pub max_order_notional: Option<f64>, // USD at the limit price
+    pub price_collar: Option<f64>,
+}
+
+// Validates orders before they are signed and sent, rejecting locally what
+// the exchange would reject or what no strategy should ever send: sizes and
+// notionals over the coin's limits, prices outside the collar around the
+// latest mid, sizes or prices off the asset's tick and orders under the
+// asset's minimum value. Reduce-only orders only have to be on the tick,
+// so a kill switch can always close. Without a mid for the coin the collar
+// is skipped, and coins missing from the specs skip the tick checks.
+#[derive(Debug, Default)]
+pub struct PreTradeChecks {
+    default_limits: PreTradeLimits,
+    limits: HashMap<String, PreTradeLimits>,
+    specs: AssetSpecs,
+    mids: Mutex<HashMap<String, f64>>,
+}
+
+impl PreTradeChecks {
+    pub fn new(specs: AssetSpecs) -> Self {
+        Self {
+            specs,
+            ..Default::default()
+        }
+    }
+
+    // Limits for coins without their own
+    pub fn with_default_limits(mut self, limits: PreTradeLimits) -> Self {
+        self.default_limits = limits;
+        self
+    }
+
+    pub fn with_limits(mut self, coin: &str, limits: PreTradeLimits) -> Self {
+        self.limits.insert(coin.to_string(), limits);
+        self
+    }
+
+    pub fn set_mid(&self, coin: &str, mid: f64) {
+        if mid.is_finite() && mid > 0.0 {
+            self.mids.lock().unwrap().insert(coin.to_string(), mid);
+        }
+    }
+
+    pub fn mid(&self, coin: &str) -> Option<f64> {
+        self.mids.lock().unwrap().get(coin).copied()
+    }
+
+    // Keeps the collar's mid current from the bot's market data
+    pub fn on_market_event(&self, coin: &str, event: &MarketEvent) {
+        if let MarketEvent::Book { bids, asks, .. } = event {
+            if let Some((bid_px, ask_px, _, _)) = crate::top_of_book(bids, asks) {
+                self.set_mid(coin, (bid_px + ask_px) / 2.0);
+            }
+        }
+    }
+
+    pub fn check(&self, order: &ClientOrderRequest) -> Result<()> {
+        let reject = |reason: String| {
+            let side = if order.is_buy { "buy" } else { "sell" };
+            let reason = format!(
+                "{} {side} {} @ {}: {reason}",
+                order.asset, order.sz, order.limit_px
+            );
+            warn!("[PreTrade] Rejected {reason}");
+            Err(Error::PreTrade(reason))
+        };
+        if !(order.sz.is_finite() && order.sz > 0.0) {
+            return reject("size must be positive".to_string());
+        }
+        if !(order.limit_px.is_finite() && order.limit_px > 0.0) {
+            return reject("price must be positive".to_string());
+        }
+        let spec = self.specs.get(&order.asset);
+        if let Some(spec) = spec {
+            if !spec.is_valid_size(order.sz) {
+                return reject(format!("size has more than {} decimals", spec.sz_decimals));
+            }
+            if !spec.is_valid_price(order.limit_px) {
+                return reject(format!(
+                    "price has more than {PRICE_SIG_FIGS} significant figures or {} decimals",
+                    spec.price_decimals()
+                ));
+            }
+        }
+        if order.reduce_only {
+            return Ok(());
+        }
+        let limits = self
+            .limits
+            .get(&order.asset)
+            .unwrap_or(&self.default_limits);
+        let notional = order.sz * order.limit_px;
+        let min_notional = spec.unwrap_or_default().min_notional;
+        if notional < min_notional {
+            return reject(format!(
+                "notional ${notional:.2} under the ${min_notional} minimum"
+            ));
+        }
+        if let Some(max) = limits.max_order_size.filter(|max| order.sz > *max) {
+            return reject(format!("size over the {max} limit"));
+        }
+        if let Some(max) = limits.max_order_notional.filter(|max| notional > *max) {
+            return reject(format!("notional ${notional:.2} over the ${max} limit"));
+        }
+        if let (Some(collar), Some(mid)) = (limits.price_collar, self.mid(&order.asset)) {
+            let away = (order.limit_px - mid).abs() / mid;
+            if away > collar {
+                return reject(format!(
+                    "{:.2}% from the mid {mid}, collar {:.2}%",
+                    away * 100.0,
+                    collar * 100.0
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{limit_order, AssetSpec, TimeInForce};
+
+    fn checks() -> PreTradeChecks {
+        let mut specs = AssetSpecs::default();
+        specs.insert("BTC", AssetSpec::perp(5));
+        let limits = PreTradeLimits {
+            max_order_size: Some(1.0),
+            max_order_notional: Some(30_000.0),
+            price_collar: Some(0.05),
+        };
+        PreTradeChecks::new(specs).with_limits("BTC", limits)
+    }
+
+    fn order(px: f64, sz: f64, reduce_only: bool) -> ClientOrderRequest {
+        limit_order("BTC", true, px, sz, reduce_only, TimeInForce::Gtc, None)
+    }
+
+    #[test]
+    fn test_rejects_fat_fingers_and_off_tick_orders() {
+        let checks = checks();
+        checks.set_mid("BTC", 40_000.0);
+        assert!(checks.check(&order(40_001.0, 0.5, false)).is_ok());
+        // Integer prices are always on the tick; this one has 6 figures
+        assert!(checks.check(&order(40_001.5, 0.5, false)).is_err());
+        assert!(checks.check(&order(40_001.0, 0.000_001, false)).is_err());
+        assert!(checks.check(&order(40_001.0, 1.5, false)).is_err());
+        // $4 of notional, under the minimum
+        assert!(checks.check(&order(40_001.0, 0.000_1, false)).is_err());
+        // Over the notional limit, then outside the 5% collar
+        assert!(checks.check(&order(40_100.0, 0.9, false)).is_err());
+        assert!(checks.check(&order(43_000.0, 0.1, false)).is_err());
+        assert!(checks.check(&order(43_000.0, 0.1, true)).is_ok());
+        let err = checks.check(&order(43_000.0, 0.1, false)).unwrap_err();
+        assert!(err.to_string().contains("collar"));
+
+        // No limits for other coins, and no collar before a mid
+        let eth = limit_order(
+            "ETH",
+            false,
+            1.234_567,
+            100.0,
+            false,
+            TimeInForce::Gtc,
+            None,
+        );
+        assert!(checks.check(&eth).is_ok());
+        assert!(PreTradeChecks::default()
+            .with_default_limits(PreTradeLimits {
+                price_collar: Some(0.01),
+                ..Default::default()
+            })
+            .check(&order(43_000.0, 0.1, false))
+            .is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+use uuid::Uuid;
+
+use crate::{StrategyOrder, TimeInForce};
+
+pub(crate) const SIZE_TOLERANCE: f64 = 0.1; // Share of the desired size a resting quote may differ by
+
+// A quote a strategy wants on the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DesiredQuote {
+    pub is_buy: bool,
+    pub px: f64,
+    pub sz: f64,
+}
+
+// One of the strategy's orders resting on the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestingQuote {
+    pub cloid: Uuid,
+    pub is_buy: bool,
+    pub px: f64,
+    pub sz: f64, // remaining
+}
+
+// Turns the quotes a strategy wants into the fewest orders that get them on
+// the book, in place of cancelling and re-placing everything each tick.
+// Resting orders already within tolerance of a desired quote are left alone,
+// others on the same side are modified to the remaining desired quotes, and
+// only what is left over is cancelled or newly placed.
+#[derive(Debug, Clone)]
+pub struct QuoteReconciler {
+    pub px_tolerance: f64, // absolute price difference that still counts as the same quote
+    pub size_tolerance: f64, // share of the desired size
+    pub tif: TimeInForce,  // for new and modified orders
+}
+
+impl Default for QuoteReconciler {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl QuoteReconciler {
+    pub fn new(px_tolerance: f64) -> Self {
+        Self {
+            px_tolerance,
+            size_tolerance: SIZE_TOLERANCE,
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    pub fn with_size_tolerance(mut self, share: f64) -> Self {
+        self.size_tolerance = share;
+        self
+    }
+
+    pub fn with_tif(mut self, tif: TimeInForce) -> Self {
+        self.tif = tif;
+        self
+    }
+
+    fn matches(&self, resting: &RestingQuote, desired: &DesiredQuote) -> bool {
+        resting.is_buy == desired.is_buy
+            && (resting.px - desired.px).abs() <= self.px_tolerance + 1e-9
+            && (resting.sz - desired.sz).abs() <= self.size_tolerance * desired.sz + 1e-9
+    }
+
+    // Cancels first, so they free margin before anything new needs it, then
+    // modifies, then placements
+    pub fn reconcile(
+        &self,
+        desired: &[DesiredQuote],
+        resting: &[RestingQuote],
+    ) -> Vec<StrategyOrder> {
+        let (mut cancels, mut modifies, mut places) = (Vec::new(), Vec::new(), Vec::new());
+        for is_buy in [true, false] {
+            let mut stale: Vec<_> = resting.iter().filter(|r| r.is_buy == is_buy).collect();
+            let mut missing = Vec::new();
+            for quote in desired.iter().filter(|d| d.is_buy == is_buy) {
+                match stale.iter().position(|r| self.matches(r, quote)) {
+                    Some(i) => {
+                        stale.remove(i);
+                    }
+                    None => missing.push(quote),
+                }
+            }
+            // Pair the rest best price first, so ladders shift level for level
+            let by_price = |a: f64, b: f64| {
+                if is_buy {
+                    b.total_cmp(&a)
+                } else {
+                    a.total_cmp(&b)
+                }
+            };
+            stale.sort_by(|a, b| by_price(a.px, b.px));
+            missing.sort_by(|a, b| by_price(a.px, b.px));
+            let paired = stale.len().min(missing.len());
+            for (r, d) in stale.iter().zip(&missing) {
+                modifies.push(StrategyOrder::Modify {
+                    cloid: r.cloid,
+                    is_buy,
+                    px: d.px,
+                    sz: d.sz,
+                    tif: self.tif,
+                });
+            }
+            cancels.extend(
+                stale[paired..]
+                    .iter()
+                    .map(|r| StrategyOrder::Cancel { cloid: r.cloid }),
+            );
+            places.extend(
+                missing[paired..]
+                    .iter()
+                    .map(|d| StrategyOrder::limit(is_buy, d.px, d.sz, self.tif)),
+            );
+        }
+        cancels.into_iter().chain(modifies).chain(places).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_modifies_then_cancels_or_places_the_rest() {
+        let reconciler = QuoteReconciler::new(0.05);
+        let resting = |is_buy, px| RestingQuote {
+            cloid: Uuid::new_v4(),
+            is_buy,
+            px,
+            sz: 1.0,
+        };
+        let want = |is_buy, px| DesiredQuote {
+            is_buy,
+            px,
+            sz: 1.0,
+        };
+        let (bid, ask, deep_ask) = (
+            resting(true, 100.0),
+            resting(false, 101.0),
+            resting(false, 103.0),
+        );
+
+        // Unchanged quotes, and a bid within tolerance, send nothing
+        let same = [want(true, 100.04), want(false, 101.0), want(false, 103.0)];
+        assert!(reconciler
+            .reconcile(&same, &[bid, ask, deep_ask])
+            .is_empty());
+
+        // The ask moves and the deep ask goes: one modify and one cancel; a
+        // second bid is new
+        let moved = [want(true, 100.0), want(true, 99.0), want(false, 101.5)];
+        let orders = reconciler.reconcile(&moved, &[bid, ask, deep_ask]);
+        let [StrategyOrder::Cancel { cloid: cancelled }, StrategyOrder::Modify {
+            cloid: modified,
+            px,
+            ..
+        }, StrategyOrder::Place {
+            is_buy: true,
+            px: placed,
+            ..
+        }] = orders[..]
+        else {
+            panic!("unexpected orders {orders:?}");
+        };
+        assert_eq!(
+            (cancelled, modified, px, placed),
+            (deep_ask.cloid, ask.cloid, 101.5, 99.0)
+        );
+    }
+}
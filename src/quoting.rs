@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+use crate::{
+    prelude::*, register_strategy, Error, QuoteProposal, SignalState, SpreadRegime, Strategy,
+    StrategyConfig,
+};
+
+pub(crate) const SPREAD_TICKS: f64 = 2.0;
+pub(crate) const AGGRESSIVE_SPREAD_TICKS: f64 = 0.5;
+pub(crate) const BASE_QUOTE_SIZE: f64 = 1.0;
+
+// Sides a coin may quote, e.g. `sides = "ask"` to only sell down a long
+// position without stopping the coin. Hedges are never held back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteSides {
+    #[default]
+    Both,
+    Bid,
+    Ask,
+    None,
+}
+
+impl QuoteSides {
+    // Whether a quote on `side` ("Buy" or "Sell") may be sent
+    pub fn allows(self, side: &str) -> bool {
+        match self {
+            QuoteSides::Both => true,
+            QuoteSides::Bid => side == "Buy",
+            QuoteSides::Ask => side == "Sell",
+            QuoteSides::None => false,
+        }
+    }
+}
+
+impl FromStr for QuoteSides {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "both" => Ok(QuoteSides::Both),
+            "bid" => Ok(QuoteSides::Bid),
+            "ask" => Ok(QuoteSides::Ask),
+            "none" => Ok(QuoteSides::None),
+            _ => Err(Error::GenericParse(format!("bad quote sides {s:?}"))),
+        }
+    }
+}
+
+impl fmt::Display for QuoteSides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            QuoteSides::Both => "both",
+            QuoteSides::Bid => "bid",
+            QuoteSides::Ask => "ask",
+            QuoteSides::None => "none",
+        };
+        f.write_str(name)
+    }
+}
+
+// === Quote Construction ===
+#[derive(Debug, Clone)]
+pub struct QuoteLayerManager {
+    pub spread_ticks: f64, // distance inside the touch before volatility widening
+    pub aggressive_spread_ticks: f64, // the same in aggressive mode
+    // Avellaneda–Stoikov inventory skew; None quotes the same whatever the
+    // position
+    pub risk_aversion: Option<f64>,
+    pub regimes: Vec<SpreadRegime>, // regimes to quote in; empty quotes in all
+}
+
+impl Default for QuoteLayerManager {
+    fn default() -> Self {
+        Self {
+            spread_ticks: SPREAD_TICKS,
+            aggressive_spread_ticks: AGGRESSIVE_SPREAD_TICKS,
+            risk_aversion: None,
+            regimes: Vec::new(),
+        }
+    }
+}
+
+impl QuoteLayerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Quotes at the default spreads
+    pub fn build_quotes(signal: &SignalState) -> Vec<QuoteProposal> {
+        Self::default().quotes_for(signal)
+    }
+
+    // Moves the reservation price, and both quotes with it, by
+    // -position * risk_aversion * volatility², so a long leans its quotes
+    // down and a short up
+    pub fn reservation_offset(&self, signal: &SignalState) -> f64 {
+        self.risk_aversion.map_or(0.0, |gamma| {
+            -signal.position.base * gamma * signal.volatility.powi(2)
+        })
+    }
+
+    pub fn quotes_for(&self, signal: &SignalState) -> Vec<QuoteProposal> {
+        let mut quotes = vec![];
+        // Volatility-burst circuit overrides everything, including aggressive mode
+        if signal.quoting_paused {
+            return quotes;
+        }
+        if !self.regimes.is_empty() && !self.regimes.contains(&signal.spread_regime) {
+            return quotes;
+        }
+        // Determine spread in ticks (wider if high volatility)
+        let base_spread = if signal.aggressive_mode {
+            self.aggressive_spread_ticks
+        } else {
+            self.spread_ticks
+        };
+        let spread_tick = base_spread * (1.0 + signal.volatility * 0.1).min(3.0);
+        // Adaptive size (smaller in high-volatility)
+        let vol_adj_size = BASE_QUOTE_SIZE * (1.0 / (1.0 + signal.volatility)).clamp(0.5, 2.0);
+        if signal.aggressive_mode {
+            // Quote both sides aggressively
+            quotes.push(QuoteProposal {
+                side: "Buy".into(),
+                price: signal.best_bid + spread_tick,
+                size: vol_adj_size * 1.5,
+            });
+
+            quotes.push(QuoteProposal {
+                side: "Sell".into(),
+                price: signal.best_ask - spread_tick,
+                size: vol_adj_size * 1.5,
+            });
+        } else {
+            // Quote only side suggested by fill_score
+            if signal.fill_score > 0.1 {
+                quotes.push(QuoteProposal {
+                    side: "Buy".into(),
+                    price: signal.best_bid + spread_tick,
+                    size: vol_adj_size,
+                });
+            } else if signal.fill_score < -0.1 {
+                quotes.push(QuoteProposal {
+                    side: "Sell".into(),
+                    price: signal.best_ask - spread_tick,
+                    size: vol_adj_size,
+                });
+            }
+        }
+        // Quotes follow the fair value away from the mid, and the reducing
+        // side may lean up to, but not across, the far touch
+        let offset = self.reservation_offset(signal) + fair_value_shift(signal);
+        if offset != 0.0 {
+            for q in &mut quotes {
+                q.price = if q.side == "Buy" {
+                    (q.price + offset).min(signal.best_ask - AGGRESSIVE_SPREAD_TICKS)
+                } else {
+                    (q.price + offset).max(signal.best_bid + AGGRESSIVE_SPREAD_TICKS)
+                };
+            }
+        }
+        quotes
+    }
+}
+
+// Distance of the chosen fair value from the mid; none before the first book
+fn fair_value_shift(signal: &SignalState) -> f64 {
+    if signal.fair_value > 0.0 {
+        signal.fair_value - (signal.best_bid + signal.best_ask) / 2.0
+    } else {
+        0.0
+    }
+}
+
+impl Strategy for QuoteLayerManager {
+    fn name(&self) -> &str {
+        "layered"
+    }
+
+    fn quote(&mut self, state: &SignalState) -> Vec<QuoteProposal> {
+        self.quotes_for(state)
+    }
+
+    fn configure(&mut self, config: &StrategyConfig) {
+        self.spread_ticks = config.spread_ticks;
+        self.aggressive_spread_ticks = config.aggressive_spread_ticks;
+        self.risk_aversion = config.risk_aversion;
+        self.regimes = config.regimes.clone();
+    }
+}
+
+register_strategy!(
+    "layered",
+    "One-sided quotes on fill score, both sides in aggressive mode; spread and size scale with volatility",
+    || Box::new(QuoteLayerManager::new())
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Position, RiskManager};
+    use std::{env, fmt::Write, fs};
+
+    // Golden output lives in testdata/; rerun with UPDATE_GOLDEN=1 to accept an
+    // intentional pricing change, then review the diff.
+    const GOLDEN_PATH: &str = "testdata/golden_quotes.txt";
+    const MAX_POSITION: f64 = 5.0;
+
+    fn state(fill_score: f64, volatility: f64, aggressive: bool, base: f64) -> SignalState {
+        SignalState {
+            best_bid: 100.0,
+            best_ask: 101.0,
+            fill_score,
+            volatility,
+            aggressive_mode: aggressive,
+            position: Position {
+                base,
+                quote: 0.0 - base * 100.5,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn canonical_cases() -> Vec<(&'static str, SignalState)> {
+        let mut paused = state(0.0, 0.0, true, 0.0);
+        paused.quoting_paused = true;
+        vec![
+            ("calm_buy", state(0.5, 0.0, false, 0.0)),
+            ("calm_sell", state(-0.5, 0.0, false, 0.0)),
+            ("neutral", state(0.05, 0.0, false, 0.0)),
+            ("vol_buy", state(0.5, 4.0, false, 0.0)),
+            ("aggressive", state(0.0, 1.0, true, 0.0)),
+            ("aggressive_high_vol", state(0.0, 50.0, true, 0.0)),
+            ("paused", paused),
+            ("soft_zone_long", state(0.0, 0.0, true, 3.8)),
+            ("soft_zone_short", state(0.0, 0.0, true, -4.0)),
+            ("soft_zone_deep_long", state(0.5, 0.0, false, 4.9)),
+            ("hard_limit_long", state(0.0, 0.0, true, 4.9)),
+            ("hard_limit_short", state(0.0, 0.0, true, -5.0)),
+        ]
+    }
+
+    fn render(out: &mut String, label: &str, quotes: &[QuoteProposal]) {
+        if quotes.is_empty() {
+            writeln!(out, "{label:<8} none").unwrap();
+        }
+        for q in quotes {
+            writeln!(
+                out,
+                "{label:<8} {:<4} {:.6} x {:.6}",
+                q.side, q.price, q.size
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_risk_aversion_leans_quotes_against_inventory() {
+        let skewed = QuoteLayerManager {
+            risk_aversion: Some(0.1),
+            ..Default::default()
+        };
+        let prices = |manager: &QuoteLayerManager, base: f64| -> Vec<f64> {
+            let mut wide = state(0.0, 2.0, true, base);
+            wide.best_ask = 110.0;
+            let quotes = manager.quotes_for(&wide);
+            quotes.iter().map(|q| q.price).collect()
+        };
+        let flat = prices(&QuoteLayerManager::default(), 2.0);
+        assert_eq!(prices(&skewed, 0.0), flat);
+        // Long 2 at volatility 2: both quotes drop 0.8, shading the bid away
+        // and the ask towards the bid
+        let long = prices(&skewed, 2.0);
+        assert!((long[0] - (flat[0] - 0.8)).abs() < 1e-9);
+        assert!((long[1] - (flat[1] - 0.8)).abs() < 1e-9);
+        // A deep short lifts the bid no further than just inside the ask
+        assert_eq!(prices(&skewed, -50.0)[0], 109.5);
+    }
+
+    #[test]
+    fn test_golden_quotes() {
+        let risk = RiskManager::new(MAX_POSITION);
+        let mut actual = String::new();
+        for (name, mut state) in canonical_cases() {
+            writeln!(actual, "[{name}]").unwrap();
+            let quotes = QuoteLayerManager::build_quotes(&state);
+            render(&mut actual, "build", &quotes);
+            let approved = risk.evaluate(&mut state, &quotes);
+            render(&mut actual, "approved", &approved);
+            writeln!(
+                actual,
+                "position {:.6} {:.6}",
+                state.position.base, state.position.quote
+            )
+            .unwrap();
+        }
+
+        let path = format!("{}/{GOLDEN_PATH}", env!("CARGO_MANIFEST_DIR"));
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap();
+        assert!(
+            expected == actual,
+            "quote output differs from {GOLDEN_PATH}; rerun with UPDATE_GOLDEN=1 if intended\n--- expected\n{expected}\n--- actual\n{actual}"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub(crate) const REQUEST_BUDGET_PER_MIN: f64 = 1_200.0; // Hyperliquid's request weight per IP
+pub(crate) const REQUEST_BURST: f64 = 100.0; // Weight that may go out at once
+pub(crate) const CANCEL_RESERVE: f64 = 0.2; // Share of the bucket left to cancels alone
+pub(crate) const INFO_RESERVE: f64 = 0.5; // Share info queries leave to orders and cancels
+
+// What a request is for, most urgent first. Each class stops drawing on the
+// budget while what is left is its reserve, so that a burst of quotes or
+// polling never leaves nothing for the cancels that pull them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RequestClass {
+    Cancel,
+    Order, // places and modifies, reduce-only or not
+    Info,
+}
+
+impl RequestClass {
+    // Share of the bucket this class leaves to more urgent ones
+    fn reserve(self) -> f64 {
+        match self {
+            RequestClass::Cancel => 0.0,
+            RequestClass::Order => CANCEL_RESERVE,
+            RequestClass::Info => INFO_RESERVE,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    per_sec: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.capacity);
+        self.refilled = now;
+    }
+
+    fn floor(&self, class: RequestClass) -> f64 {
+        self.capacity * class.reserve()
+    }
+
+    // Takes `weight` for a `class` request at `now`, or says how long until it can
+    fn take(&mut self, now: Instant, class: RequestClass, weight: f64) -> Result<(), Duration> {
+        self.refill(now);
+        let floor = self.floor(class);
+        let weight = weight.min(self.capacity - floor);
+        if self.tokens - weight >= floor - 1e-9 {
+            self.tokens -= weight;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (floor + weight - self.tokens) / self.per_sec,
+        ))
+    }
+}
+
+// Token bucket shared by everything that calls the exchange from one IP:
+// `burst` weight may go out at once and it refills at `per_sec`. Clones share
+// the same budget.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(REQUEST_BURST, REQUEST_BUDGET_PER_MIN / 60.0)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(burst: f64, per_sec: f64) -> Self {
+        Self {
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                capacity: burst,
+                per_sec: per_sec.max(1e-9),
+                tokens: burst,
+                refilled: Instant::now(),
+            })),
+        }
+    }
+
+    // Waits until a `class` request of `weight` fits in the budget, and takes it
+    pub async fn acquire(&self, class: RequestClass, weight: f64) {
+        loop {
+            let taken = self.bucket.lock().unwrap_or_else(|e| e.into_inner()).take(
+                Instant::now(),
+                class,
+                weight,
+            );
+            match taken {
+                Ok(()) => return,
+                Err(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    // Backpressure: true while a `class` request would have to wait. Quoting
+    // holds new orders back while orders are throttled.
+    pub fn is_throttled(&self, class: RequestClass) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill(Instant::now());
+        bucket.tokens - 1.0 < bucket.floor(class) - 1e-9
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_class_leaves_its_reserve_to_more_urgent_ones() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket {
+            capacity: 10.0,
+            per_sec: 10.0,
+            tokens: 10.0,
+            refilled: start,
+        };
+        // Info stops at half the bucket
+        assert!(bucket.take(start, RequestClass::Info, 2.0).is_ok());
+        assert!(bucket.take(start, RequestClass::Info, 2.0).is_ok());
+        assert!(bucket.take(start, RequestClass::Info, 2.0).is_err());
+        // Orders stop at a fifth
+        for _ in 0..4 {
+            assert!(bucket.take(start, RequestClass::Order, 1.0).is_ok());
+        }
+        assert!(bucket.take(start, RequestClass::Order, 1.0).is_err());
+        // Cancels use the rest, then wait for the refill
+        assert!(bucket.take(start, RequestClass::Cancel, 1.0).is_ok());
+        assert!(bucket.take(start, RequestClass::Cancel, 1.0).is_ok());
+        assert_eq!(
+            bucket.take(start, RequestClass::Cancel, 1.0),
+            Err(Duration::from_millis(100))
+        );
+        let later = start + Duration::from_millis(100);
+        assert!(bucket.take(later, RequestClass::Cancel, 1.0).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+use ethers::types::H160;
+use log::warn;
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::watch;
+
+use crate::{prelude::*, Alert, AlertLevel, Error, InfoClient, UserStateResponse};
+
+// Exchange view of one coin's position; size is signed (negative = short)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExchangePosition {
+    pub size: f64,
+    pub entry_px: Option<f64>,
+    pub margin_used: f64,
+}
+
+// Parsed `user_state`: the source of truth local position tracking is
+// corrected against
+#[derive(Debug, Clone, Default)]
+pub struct AccountSnapshot {
+    pub positions: HashMap<String, ExchangePosition>,
+    pub account_value: f64,
+    pub margin_used: f64,
+    pub withdrawable: f64, // free margin
+}
+
+fn parse_field(name: &str, value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|_| Error::GenericParse(format!("bad {name} {value:?}")))
+}
+
+impl AccountSnapshot {
+    pub fn from_user_state(state: &UserStateResponse) -> Result<Self> {
+        let mut positions = HashMap::new();
+        for asset in &state.asset_positions {
+            let p = &asset.position;
+            let position = ExchangePosition {
+                size: parse_field("szi", &p.szi)?,
+                entry_px: p
+                    .entry_px
+                    .as_deref()
+                    .map(|px| parse_field("entryPx", px))
+                    .transpose()?,
+                margin_used: parse_field("marginUsed", &p.margin_used)?,
+            };
+            positions.insert(p.coin.clone(), position);
+        }
+        Ok(Self {
+            positions,
+            account_value: parse_field("accountValue", &state.margin_summary.account_value)?,
+            margin_used: parse_field("totalMarginUsed", &state.margin_summary.total_margin_used)?,
+            withdrawable: parse_field("withdrawable", &state.withdrawable)?,
+        })
+    }
+
+    // Coins missing from the response are flat
+    pub fn position(&self, coin: &str) -> ExchangePosition {
+        self.positions.get(coin).cloned().unwrap_or_default()
+    }
+}
+
+// Local position that disagrees with the exchange by more than the threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub coin: String,
+    pub local: f64,
+    pub exchange: f64,
+}
+
+impl Divergence {
+    pub fn alert(&self) -> Alert {
+        Alert::new(
+            AlertLevel::Warning,
+            format!("{}:position", self.coin),
+            format!(
+                "{} local position {:.4} diverged from exchange {:.4}; resyncing",
+                self.coin, self.local, self.exchange
+            ),
+        )
+    }
+}
+
+// Polls `user_state` on a fixed interval so bots can correct position, entry
+// price and margin that they otherwise only infer from their own orders
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    pub interval: Duration,
+    pub threshold: f64, // base units; smaller differences are corrected silently
+}
+
+impl Reconciler {
+    pub fn new(interval: Duration, threshold: f64) -> Self {
+        Self {
+            interval,
+            threshold,
+        }
+    }
+
+    pub fn check(&self, coin: &str, local: f64, snapshot: &AccountSnapshot) -> Option<Divergence> {
+        let exchange = snapshot.position(coin).size;
+        ((local - exchange).abs() > self.threshold).then(|| Divergence {
+            coin: coin.to_string(),
+            local,
+            exchange,
+        })
+    }
+
+    // Starts the polling task. The receiver holds None until the first
+    // successful poll; failed polls are logged and retried next interval.
+    pub fn spawn(&self, info: InfoClient, user: H160) -> watch::Receiver<Option<AccountSnapshot>> {
+        let (tx, rx) = watch::channel(None);
+        let interval = self.interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let snapshot = match info.user_state(user).await {
+                    Ok(state) => AccountSnapshot::from_user_state(&state),
+                    Err(e) => Err(e),
+                };
+                match snapshot {
+                    Ok(snapshot) => {
+                        if tx.send(Some(snapshot)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!("Reconciliation poll failed: {e}"),
+                }
+            }
+        });
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user_state(szi: &str) -> UserStateResponse {
+        let summary = json!({
+            "accountValue": "1000.5", "totalMarginUsed": "120.0",
+            "totalNtlPos": "2400.0", "totalRawUsd": "1000.5"
+        });
+        serde_json::from_value(json!({
+            "assetPositions": [{
+                "type": "oneWay",
+                "position": {
+                    "coin": "BTC", "entryPx": "60000.0", "szi": szi,
+                    "leverage": {"type": "cross", "value": 20},
+                    "liquidationPx": null, "marginUsed": "120.0", "positionValue": "2400.0",
+                    "returnOnEquity": "0.0", "unrealizedPnl": "0.0", "maxLeverage": 40,
+                    "cumFunding": {"allTime": "0", "sinceOpen": "0", "sinceChange": "0"}
+                }
+            }],
+            "crossMarginSummary": summary,
+            "marginSummary": summary,
+            "withdrawable": "880.5"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flags_positions_that_drift_beyond_the_threshold() {
+        let snapshot = AccountSnapshot::from_user_state(&user_state("-0.04")).unwrap();
+        assert_eq!(snapshot.account_value, 1000.5);
+        assert_eq!(snapshot.withdrawable, 880.5);
+        let btc = snapshot.position("BTC");
+        assert_eq!(btc.entry_px, Some(60000.0));
+        assert_eq!(btc.margin_used, 120.0);
+        assert_eq!(snapshot.position("ETH"), ExchangePosition::default());
+
+        let reconciler = Reconciler::new(Duration::from_secs(5), 0.005);
+        assert!(reconciler.check("BTC", -0.042, &snapshot).is_none());
+        let divergence = reconciler.check("BTC", 0.0, &snapshot).unwrap();
+        assert_eq!(divergence.exchange, -0.04);
+        assert_eq!(divergence.alert().key, "BTC:position");
+    }
+}
@@ -0,0 +1,144 @@
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{
+    prelude::*,
+    read_tape,
+    tape::{tape_order, TAPE_EXTENSION},
+    BookLevel, Error, MarketEvent, TapeEvent, TapeRecord,
+};
+
+#[derive(Debug, Clone)]
+pub struct RecordedEvent {
+    pub coin: String,
+    pub event: MarketEvent,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    pub events: Vec<RecordedEvent>,
+    pub skipped: usize, // lines that could not be parsed
+    // (since, until) in local received time where the recorder captured
+    // nothing; events on either side are not continuous
+    pub gaps: Vec<(u64, u64)>,
+}
+
+fn num(v: &Value) -> Option<f64> {
+    match v {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_f64(),
+    }
+}
+
+// Millisecond timestamps, or "YYYY-MM-DD HH:MM:SS.mmm" (UTC) as in orderbook_log.json
+fn time_ms(v: &Value) -> Option<u64> {
+    match v {
+        Value::String(s) => NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|t| t.and_utc().timestamp_millis() as u64),
+        v => v.as_u64(),
+    }
+}
+
+// Book lines carry `coin`, `timestamp` (or `time`) and `bids`/`asks` levels; trade
+// lines carry `coin`, `time`, `px`, `sz` and `side` ("B"/"A").
+pub fn parse_recorded_line(line: &str) -> Option<RecordedEvent> {
+    let v: Value = serde_json::from_str(line).ok()?;
+    let coin = v["coin"].as_str()?.to_string();
+    let event = if v.get("bids").is_some() {
+        MarketEvent::Book {
+            time: time_ms(&v["timestamp"]).or_else(|| time_ms(&v["time"]))?,
+            bids: serde_json::from_value::<Vec<BookLevel>>(v["bids"].clone()).ok()?,
+            asks: serde_json::from_value::<Vec<BookLevel>>(v["asks"].clone()).ok()?,
+        }
+    } else {
+        MarketEvent::Trade {
+            time: time_ms(&v["time"])?,
+            px: num(&v["px"])?,
+            sz: num(&v["sz"])?,
+            is_buy: v["side"].as_str() == Some("B"),
+        }
+    };
+    Some(RecordedEvent { coin, event })
+}
+
+fn from_tape(records: Vec<TapeRecord>, recording: &mut Recording) {
+    for record in records {
+        if let TapeEvent::Gap { since_ms } = record.event {
+            recording.gaps.push((since_ms, record.received_ms));
+        } else if let Some(event) = record.market_event() {
+            recording.events.push(RecordedEvent {
+                coin: record.coin,
+                event,
+            });
+        }
+    }
+}
+
+// JSON lines (`orderbook_log.json`), a recorder tape file, or a directory of
+// tape files read in recording order
+pub fn load_recording(path: impl AsRef<Path>) -> Result<Recording> {
+    let path = path.as_ref();
+    let mut recording = Recording::default();
+    if path.is_dir() {
+        let mut tapes: Vec<_> = std::fs::read_dir(path)
+            .map_err(|e| Error::Recording(e.to_string()))?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == TAPE_EXTENSION))
+            .collect();
+        tapes.sort_by_key(|p| tape_order(p));
+        for tape in tapes {
+            from_tape(read_tape(tape)?, &mut recording);
+        }
+        return Ok(recording);
+    }
+    if path.extension().is_some_and(|ext| ext == TAPE_EXTENSION) {
+        from_tape(read_tape(path)?, &mut recording);
+        return Ok(recording);
+    }
+    let file = File::open(path).map_err(|e| Error::Recording(e.to_string()))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| Error::Recording(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_recorded_line(&line) {
+            Some(event) => recording.events.push(event),
+            None => recording.skipped += 1,
+        }
+    }
+    Ok(recording)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recorded_line() {
+        let book = r#"{"coin":"BTC","timestamp":"2025-07-03 17:31:07.050","delta_ms":500,"bids":[{"px":"109414.0","sz":"6.15","n":16}],"asks":[{"px":"109415.0","sz":"1.2","n":3}]}"#;
+        let event = parse_recorded_line(book).unwrap();
+        assert_eq!(event.coin, "BTC");
+        match event.event {
+            MarketEvent::Book { time, bids, asks } => {
+                assert_eq!(time, 1_751_563_867_050);
+                assert_eq!(bids[0].px, "109414.0");
+                assert_eq!(asks[0].sz, "1.2");
+            }
+            _ => panic!("expected a book"),
+        }
+
+        let trade = r#"{"coin":"ETH","time":1700000000000,"px":"2000.5","sz":0.1,"side":"A"}"#;
+        let event = parse_recorded_line(trade).unwrap();
+        assert!(matches!(
+            event.event,
+            MarketEvent::Trade { is_buy: false, px, .. } if px == 2000.5
+        ));
+        assert!(parse_recorded_line(r#"{"coin":"BTC","bids":[{"px":"1""#).is_none());
+    }
+}
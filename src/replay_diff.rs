@@ -0,0 +1,232 @@
+use std::fmt::Write;
+
+use crate::{JournalRecord, SessionFrame, SessionViewer};
+
+// One book tick of a window: its rendering and the bot's decisions on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffPanel {
+    pub offset_ms: u64, // since the window's first book
+    pub text: String,
+    pub decisions: Vec<String>,
+}
+
+// A labelled window of a recorded session, e.g. "expected" from a run that
+// behaved and "actual" from the one in the report
+#[derive(Debug, Clone, Default)]
+pub struct DiffSide {
+    pub label: String,
+    pub panels: Vec<DiffPanel>,
+}
+
+impl DiffSide {
+    // Up to `limit` frames of `viewer`'s session from `from` to `to`
+    pub fn capture(
+        label: &str,
+        viewer: &mut SessionViewer,
+        from: u64,
+        to: u64,
+        limit: usize,
+    ) -> Self {
+        let frames = viewer.window(from, to, limit);
+        let start = frames.first().map_or(0, |f| f.time);
+        let panels = frames
+            .iter()
+            .map(|frame| DiffPanel {
+                offset_ms: frame.time - start,
+                text: viewer.render(frame),
+                decisions: decisions(frame),
+            })
+            .collect();
+        Self {
+            label: label.to_string(),
+            panels,
+        }
+    }
+}
+
+// "fill Buy 1.0000 @ 101.50" for each journal entry of the frame
+fn decisions(frame: &SessionFrame) -> Vec<String> {
+    frame.decisions.iter().map(describe).collect()
+}
+
+fn describe(record: &JournalRecord) -> String {
+    let data = &record.data;
+    let side = match data["is_buy"].as_bool() {
+        Some(true) => "Buy",
+        Some(false) => "Sell",
+        None => data["side"].as_str().unwrap_or("?"),
+    };
+    match (data["size"].as_f64(), data["px"].as_f64()) {
+        (Some(size), Some(px)) => format!("{} {side} {size:.4} @ {px:.2}", record.kind),
+        _ => record.kind.clone(),
+    }
+}
+
+// Two windows side by side, paired book by book from their starts, with the
+// steps where the bot decided differently flagged. The HTML rendering is a
+// standalone file to attach to a bug report.
+#[derive(Debug, Clone)]
+pub struct ReplayDiff {
+    pub expected: DiffSide,
+    pub actual: DiffSide,
+}
+
+impl ReplayDiff {
+    pub fn new(expected: DiffSide, actual: DiffSide) -> Self {
+        Self { expected, actual }
+    }
+
+    fn steps(&self) -> usize {
+        self.expected.panels.len().max(self.actual.panels.len())
+    }
+
+    // Steps (from 0) whose decisions differ between the two sides
+    pub fn divergent_steps(&self) -> Vec<usize> {
+        (0..self.steps())
+            .filter(|&i| {
+                let decided = |side: &DiffSide| side.panels.get(i).map(|p| p.decisions.clone());
+                decided(&self.expected).unwrap_or_default()
+                    != decided(&self.actual).unwrap_or_default()
+            })
+            .collect()
+    }
+
+    pub fn render_text(&self) -> String {
+        let divergent = self.divergent_steps();
+        let width = self
+            .expected
+            .panels
+            .iter()
+            .flat_map(|p| p.text.lines())
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(self.expected.label.len());
+        let mut out = format!("  {:width$} | {}\n", self.expected.label, self.actual.label);
+        for step in 0..self.steps() {
+            let marker = if divergent.contains(&step) { "!" } else { " " };
+            let lines = |side: &DiffSide| -> Vec<String> {
+                side.panels.get(step).map_or(vec![], |p| {
+                    let mut lines = vec![format!("+{}ms", p.offset_ms)];
+                    lines.extend(p.text.lines().map(str::to_string));
+                    lines
+                })
+            };
+            let (left, right) = (lines(&self.expected), lines(&self.actual));
+            let _ = writeln!(out, "{marker} {:-<width$}-+-", "");
+            for i in 0..left.len().max(right.len()) {
+                let _ = writeln!(
+                    out,
+                    "{marker} {:width$} | {}",
+                    left.get(i).map_or("", String::as_str),
+                    right.get(i).map_or("", String::as_str)
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "{} of {} steps decided differently",
+            divergent.len(),
+            self.steps()
+        );
+        out
+    }
+
+    pub fn render_html(&self, title: &str) -> String {
+        let divergent = self.divergent_steps();
+        let title = escape(title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n<style>\nbody {{ font-family: sans-serif; }}\ntable {{ border-collapse: collapse; }}\ntd, th {{ border: 1px solid #ccc; padding: 4px; vertical-align: top; }}\ntr.diff td {{ background: #fff0f0; }}\npre {{ margin: 0; font-size: 12px; }}\nul {{ margin: 4px 0; padding-left: 16px; }}\n</style></head><body>\n<h1>{title}</h1>\n<p>{} of {} steps decided differently; they are shaded.</p>\n<table>\n<tr><th>step</th><th>{}</th><th>{}</th></tr>\n",
+            divergent.len(),
+            self.steps(),
+            escape(&self.expected.label),
+            escape(&self.actual.label)
+        );
+        for step in 0..self.steps() {
+            let class = if divergent.contains(&step) {
+                " class=\"diff\""
+            } else {
+                ""
+            };
+            let _ = write!(html, "<tr{class}><td>{step}</td>");
+            for side in [&self.expected, &self.actual] {
+                match side.panels.get(step) {
+                    Some(panel) => {
+                        let _ = write!(html, "<td>+{}ms", panel.offset_ms);
+                        if !panel.decisions.is_empty() {
+                            html.push_str("<ul>");
+                            for d in &panel.decisions {
+                                let _ = write!(html, "<li><b>{}</b></li>", escape(d));
+                            }
+                            html.push_str("</ul>");
+                        }
+                        let _ = write!(html, "<pre>{}</pre></td>", escape(&panel.text));
+                    }
+                    None => html.push_str("<td></td>"),
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BookLevel, MarketEvent};
+    use serde_json::json;
+
+    fn session(fill_on: u64) -> SessionViewer {
+        let book = |time: u64| MarketEvent::Book {
+            time,
+            bids: vec![BookLevel {
+                px: "100".to_string(),
+                sz: "1".to_string(),
+                n: 1,
+            }],
+            asks: vec![BookLevel {
+                px: "101".to_string(),
+                sz: "1".to_string(),
+                n: 1,
+            }],
+        };
+        let fill = JournalRecord {
+            run_id: "run".to_string(),
+            time: fill_on,
+            kind: "fill".to_string(),
+            data: json!({"coin": "BTC", "book_time": fill_on, "side": "Buy", "size": 1.0, "px": 100.5}),
+        };
+        let events = (0..5).map(|i| book(1_000 + i * 100)).collect();
+        SessionViewer::new("BTC", events, vec![fill])
+    }
+
+    #[test]
+    fn test_pairs_windows_and_flags_divergent_decisions() {
+        // Expected fills on its second book, actual on its third
+        let expected = DiffSide::capture("expected", &mut session(1_100), 1_000, 1_300, 10);
+        let actual = DiffSide::capture("actual", &mut session(1_200), 1_000, 2_000, 3);
+        assert_eq!(expected.panels.len(), 4);
+        assert_eq!(actual.panels.len(), 3);
+        assert_eq!(expected.panels[1].offset_ms, 100);
+        assert_eq!(expected.panels[1].decisions, ["fill Buy 1.0000 @ 100.50"]);
+
+        let diff = ReplayDiff::new(expected, actual);
+        assert_eq!(diff.divergent_steps(), [1, 2]);
+        let text = diff.render_text();
+        assert!(text.starts_with("  expected"));
+        assert!(text.ends_with("2 of 4 steps decided differently\n"));
+        let html = diff.render_html("BTC <fill> late");
+        assert!(html.contains("<title>BTC &lt;fill&gt; late</title>"));
+        assert_eq!(html.matches("<tr class=\"diff\">").count(), 2);
+        assert!(html.contains("<li><b>fill Buy 1.0000 @ 100.50</b></li>"));
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::Utc;
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub(crate) const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportLayout {
+    #[default]
+    Compact, // one line redrawn in place, coins side by side
+    MultiCoin, // a timestamped block with one line per coin
+    Captured,  // nothing written; the lines are read back with `lines` (e.g. by a dashboard)
+}
+
+#[derive(Debug, Default)]
+struct ReporterState {
+    last_emit: Option<Instant>,
+    lines: BTreeMap<String, String>,
+    dirty: bool,
+}
+
+// Status output for hot loops: callers hand over their latest status line on
+// every update, and the reporter writes the newest line per key at most once per
+// interval instead of printing and flushing stdout each tick. Clones share state.
+#[derive(Debug, Clone)]
+pub struct StatusReporter {
+    interval: Duration,
+    layout: ReportLayout,
+    state: Arc<Mutex<ReporterState>>,
+}
+
+impl Default for StatusReporter {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPORT_INTERVAL, ReportLayout::default())
+    }
+}
+
+impl StatusReporter {
+    pub fn new(interval: Duration, layout: ReportLayout) -> Self {
+        Self {
+            interval,
+            layout,
+            state: Arc::new(Mutex::new(ReporterState::default())),
+        }
+    }
+
+    pub fn report(&self, key: &str, line: impl Into<String>) {
+        if let Some(text) = self.report_at(Instant::now(), key, line.into()) {
+            self.write(&text);
+        }
+    }
+
+    // Writes whatever arrived since the last report, regardless of the interval
+    pub fn flush(&self) {
+        let text = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.dirty {
+                return;
+            }
+            state.dirty = false;
+            state.last_emit = Some(Instant::now());
+            self.render(&state.lines)
+        };
+        self.write(&text);
+    }
+
+    // The latest line per key
+    pub fn lines(&self) -> Vec<(String, String)> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .lines
+            .iter()
+            .map(|(key, line)| (key.clone(), line.clone()))
+            .collect()
+    }
+
+    fn report_at(&self, now: Instant, key: &str, line: String) -> Option<String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.lines.insert(key.to_string(), line);
+        state.dirty = true;
+        let due = state
+            .last_emit
+            .is_none_or(|last| now.duration_since(last) >= self.interval);
+        if !due {
+            return None;
+        }
+        state.last_emit = Some(now);
+        state.dirty = false;
+        Some(self.render(&state.lines))
+    }
+
+    fn render(&self, lines: &BTreeMap<String, String>) -> String {
+        match self.layout {
+            ReportLayout::Compact | ReportLayout::Captured => lines
+                .iter()
+                .map(|(key, line)| format!("[{key}] {line}"))
+                .collect::<Vec<_>>()
+                .join(" || "),
+            ReportLayout::MultiCoin => {
+                let mut out = format!("--- {} ---", Utc::now().format("%H:%M:%S%.3f"));
+                for (key, line) in lines {
+                    out.push_str(&format!("\n{key:>8} {line}"));
+                }
+                out
+            }
+        }
+    }
+
+    fn write(&self, text: &str) {
+        if self.layout == ReportLayout::Captured {
+            return;
+        }
+        let mut stdout = io::stdout().lock();
+        let _ = match self.layout {
+            ReportLayout::MultiCoin => writeln!(stdout, "{text}"),
+            _ => write!(stdout, "\r{text}"),
+        };
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_are_throttled_and_keep_latest_line() {
+        let reporter = StatusReporter::new(Duration::from_millis(500), ReportLayout::Compact);
+        let t0 = Instant::now();
+        assert_eq!(
+            reporter.report_at(t0, "BTC", "mid 1".into()).as_deref(),
+            Some("[BTC] mid 1")
+        );
+        assert!(reporter
+            .report_at(t0 + Duration::from_millis(100), "BTC", "mid 2".into())
+            .is_none());
+        assert!(reporter
+            .report_at(t0 + Duration::from_millis(200), "ETH", "mid 3".into())
+            .is_none());
+        assert_eq!(
+            reporter
+                .report_at(t0 + Duration::from_millis(600), "BTC", "mid 4".into())
+                .as_deref(),
+            Some("[BTC] mid 4 || [ETH] mid 3")
+        );
+    }
+}
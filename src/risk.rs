@@ -0,0 +1,724 @@
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    quoting::AGGRESSIVE_SPREAD_TICKS, AccountGuard, AssetSpec, ExposureSlot, LatencyThrottle,
+    QuoteProposal, SignalState, SoftStart, StreakWidener, EPSILON,
+};
+
+pub(crate) const SOFT_LIMIT_RATIO: f64 = 0.6; // Fraction of max inventory where the soft zone starts
+pub(crate) const SOFT_SKEW_TICKS: f64 = 2.0; // Max price shift (at the hard limit) applied in the soft zone
+pub(crate) const HEDGE_MAX_SLIPPAGE: f64 = 0.002; // Max distance (fraction of touch) a hedge may trade through
+pub(crate) const LOSS_WINDOW_MS: u64 = 3_600_000; // Rolling window of the hourly loss limit
+pub(crate) const SKIP_ENTRY_MS: u64 = 60_000; // Default pause on entries after a breached loss limit
+pub(crate) const DAY_MS: u64 = 86_400_000; // The daily loss limit resets at each UTC midnight
+
+// === Risk Manager ===
+// Reducing IOC order used to pull inventory back inside the band after a breach
+#[derive(Debug, Clone)]
+pub struct HedgeOrder {
+    pub is_buy: bool,
+    pub size: f64,
+    pub limit_px: f64, // worst acceptable price (touch +/- HEDGE_MAX_SLIPPAGE)
+}
+
+impl HedgeOrder {
+    // Price and size on the asset's tick, as the exchange requires
+    pub fn rounded(&self, spec: &AssetSpec) -> Self {
+        Self {
+            is_buy: self.is_buy,
+            size: spec.round_size(self.size),
+            limit_px: spec.round_price(self.limit_px),
+        }
+    }
+}
+
+// What a breached loss limit does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossAction {
+    SkipEntry, // refuse quotes that add inventory for `skip_entry_ms`
+    Halt,      // refuse every quote for the rest of the session
+}
+
+// Limits on losses, marked at the fair value. The trade and hourly limits
+// count closed round trips (flat to flat, or up to a flip); the daily and
+// drawdown limits watch the session's equity, open position included, on
+// every book.
+// Each limit has its own action. `flatten_on_halt` asks the bot's kill switch
+// to cancel and flatten once a limit halts quoting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossLimits {
+    pub max_trade_loss: Option<f64>, // USD, one round trip
+    pub trade_action: LossAction,
+    pub max_hourly_loss: Option<f64>, // USD, round trips closed in the rolling hour
+    pub hourly_action: LossAction,
+    pub max_daily_loss: Option<f64>, // USD since the UTC day started
+    pub daily_action: LossAction,
+    pub max_drawdown: Option<f64>, // USD below the session's high-water mark
+    pub drawdown_action: LossAction,
+    pub skip_entry_ms: u64,
+    pub flatten_on_halt: bool,
+}
+
+impl Default for LossLimits {
+    fn default() -> Self {
+        Self {
+            max_trade_loss: None,
+            trade_action: LossAction::SkipEntry,
+            max_hourly_loss: None,
+            hourly_action: LossAction::Halt,
+            max_daily_loss: None,
+            daily_action: LossAction::Halt,
+            max_drawdown: None,
+            drawdown_action: LossAction::Halt,
+            skip_entry_ms: SKIP_ENTRY_MS,
+            flatten_on_halt: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct LossState {
+    side: f64,                    // sign of the position last seen, 0 when flat
+    start_equity: Option<f64>,    // mark when the open round trip started
+    closed: VecDeque<(u64, f64)>, // (time, PnL) of round trips in the window
+    day: u64,                     // UTC day of `day_start_equity`
+    day_start_equity: Option<f64>,
+    high_water: Option<f64>, // best equity this session
+    skip_until: u64,
+    halted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LossGate {
+    Open,
+    NoEntries,
+    Halted,
+}
+
+// Position limits. `max_position` (base units) always applies; the USD notional
+// and equity limits convert to base units at the current mid, and the tightest
+// of the three is the hard limit. The soft limit keeps its ratio to it.
+#[derive(Debug)]
+pub struct RiskManager {
+    pub max_position: f64,
+    pub soft_limit: f64, // inventory beyond this is bled off via asymmetric quoting
+    pub max_notional: Option<f64>, // USD
+    pub max_equity_pct: Option<f64>, // percent of account equity
+    equity: AtomicU64,   // USD as f64 bits; 0 = not known yet
+    exposure: Option<ExposureSlot>, // share of the account-wide net limit
+    account: Option<AccountGuard>, // halts and pauses on liquidations, ADL and margin cancels
+    pub loss_limits: LossLimits,
+    losses: Mutex<LossState>,
+    streak: Mutex<Option<StreakWidener>>, // widens quotes after losing round trips
+    soft_start: Mutex<Option<SoftStart>>, // ramps quoting up at startup and after risk pauses
+    latency: Mutex<Option<LatencyThrottle>>, // backs quoting off while the exchange is slow
+}
+
+impl RiskManager {
+    pub fn new(max_position: f64) -> Self {
+        Self {
+            max_position,
+            soft_limit: max_position * SOFT_LIMIT_RATIO,
+            max_notional: None,
+            max_equity_pct: None,
+            equity: AtomicU64::new(0.0f64.to_bits()),
+            exposure: None,
+            account: None,
+            loss_limits: LossLimits::default(),
+            losses: Mutex::new(LossState::default()),
+            streak: Mutex::new(None),
+            soft_start: Mutex::new(None),
+            latency: Mutex::new(None),
+        }
+    }
+
+    pub fn with_max_notional(mut self, usd: f64) -> Self {
+        self.max_notional = Some(usd);
+        self
+    }
+
+    // Cap inventory at `pct` percent of account equity. Not applied until
+    // `set_equity` has been called with a positive value.
+    pub fn with_max_equity_pct(mut self, pct: f64) -> Self {
+        self.max_equity_pct = Some(pct);
+        self
+    }
+
+    // Also hold quotes to the account-level net limit shared with other
+    // strategies and coins
+    pub fn with_exposure(mut self, slot: ExposureSlot) -> Self {
+        self.exposure = Some(slot);
+        self
+    }
+
+    // Also halt on an account liquidation and pause entries when the exchange
+    // deleverages or cancels orders
+    pub fn with_account_guard(mut self, guard: AccountGuard) -> Self {
+        self.account = Some(guard);
+        self
+    }
+
+    pub fn with_loss_limits(mut self, limits: LossLimits) -> Self {
+        self.loss_limits = limits;
+        self
+    }
+
+    pub fn with_streak_widener(mut self, widener: StreakWidener) -> Self {
+        self.streak = Mutex::new(Some(widener));
+        self
+    }
+
+    pub fn with_soft_start(mut self, ramp: SoftStart) -> Self {
+        self.soft_start = Mutex::new(Some(ramp));
+        self
+    }
+
+    // Widen and space out quotes while `throttle`'s round trips are slow
+    pub fn with_latency_throttle(mut self, throttle: LatencyThrottle) -> Self {
+        self.latency = Mutex::new(Some(throttle));
+        self
+    }
+
+    // `next`'s limits with this manager's share of the net limit, latency
+    // throttle, loss history, and its known equity if `next` was not given
+    // one. Used when limits are reloaded.
+    pub fn carry_over(&self, mut next: RiskManager) -> RiskManager {
+        next.exposure = self.exposure.clone();
+        next.account = self.account.clone();
+        next.latency = Mutex::new(self.latency.lock().unwrap().clone());
+        next.losses = Mutex::new(self.losses.lock().unwrap().clone());
+        if let (Some(next), Some(previous)) = (
+            next.streak.get_mut().unwrap().as_mut(),
+            self.streak.lock().unwrap().as_ref(),
+        ) {
+            next.carry_over(previous);
+        }
+        if let (Some(next), Some(previous)) = (
+            next.soft_start.get_mut().unwrap().as_mut(),
+            self.soft_start.lock().unwrap().as_ref(),
+        ) {
+            next.carry_over(previous);
+        }
+        if next.equity() <= 0.0 {
+            next.set_equity(self.equity());
+        }
+        next
+    }
+
+    pub fn set_equity(&self, usd: f64) {
+        self.equity.store(usd.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn equity(&self) -> f64 {
+        f64::from_bits(self.equity.load(Ordering::Relaxed))
+    }
+
+    // Widening steps the losing streak has built up; 0 without a widener
+    pub fn streak_steps(&self) -> u32 {
+        self.streak
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |w| w.steps())
+    }
+
+    // Whether a loss limit or the account guard has stopped all quoting for
+    // the session
+    pub fn halted(&self) -> bool {
+        self.halt_reason().is_some()
+    }
+
+    pub fn halt_reason(&self) -> Option<String> {
+        if let Some(reason) = self.account.as_ref().and_then(|a| a.halted()) {
+            return Some(reason);
+        }
+        self.losses
+            .lock()
+            .unwrap()
+            .halted
+            .then(|| "loss limit".to_string())
+    }
+
+    // Whether the bot should flatten now: halted, with `flatten_on_halt` set
+    pub fn wants_flatten(&self) -> bool {
+        self.loss_limits.flatten_on_halt && self.halted()
+    }
+
+    // Watches the position for round trips closing, applies the loss limits
+    // to them, and says which quotes may go out
+    fn loss_gate(&self, time: u64, base: f64, equity: f64) -> LossGate {
+        let limits = &self.loss_limits;
+        let mut losses = self.losses.lock().unwrap();
+        let side = if base.abs() < EPSILON {
+            0.0
+        } else {
+            base.signum()
+        };
+        let start = *losses.start_equity.get_or_insert(equity);
+        let closed = losses.side != 0.0 && side != losses.side;
+        if closed {
+            let pnl = equity - start;
+            losses.closed.push_back((time, pnl));
+            if let Some(widener) = self.streak.lock().unwrap().as_mut() {
+                widener.on_round_trip(pnl);
+            }
+            let mut breaches = Vec::new();
+            if limits.max_trade_loss.is_some_and(|max| pnl < -max) {
+                breaches.push((limits.trade_action, format!("round trip lost {:.2}", -pnl)));
+            }
+            while losses
+                .closed
+                .front()
+                .is_some_and(|&(t, _)| t + LOSS_WINDOW_MS < time)
+            {
+                losses.closed.pop_front();
+            }
+            let hourly: f64 = losses.closed.iter().map(|(_, pnl)| pnl).sum();
+            if limits.max_hourly_loss.is_some_and(|max| hourly < -max) {
+                breaches.push((
+                    limits.hourly_action,
+                    format!("lost {:.2} this hour", -hourly),
+                ));
+            }
+            for (action, reason) in breaches {
+                error!("[Risk] Loss limit: {reason}, {action:?}");
+                match action {
+                    LossAction::SkipEntry => losses.skip_until = time + limits.skip_entry_ms,
+                    LossAction::Halt => losses.halted = true,
+                }
+            }
+        }
+        // Session limits are checked on every book, so they act once per
+        // breach rather than extending a pause that is already running
+        let day = time / DAY_MS;
+        if losses.day != day || losses.day_start_equity.is_none() {
+            losses.day = day;
+            losses.day_start_equity = Some(equity);
+        }
+        let daily = equity - losses.day_start_equity.unwrap_or(equity);
+        let high_water = losses.high_water.map_or(equity, |h| h.max(equity));
+        losses.high_water = Some(high_water);
+        let mut breaches = Vec::new();
+        if limits.max_daily_loss.is_some_and(|max| daily < -max) {
+            breaches.push((limits.daily_action, format!("lost {:.2} today", -daily)));
+        }
+        if limits
+            .max_drawdown
+            .is_some_and(|max| high_water - equity > max)
+        {
+            breaches.push((
+                limits.drawdown_action,
+                format!("{:.2} below the session high", high_water - equity),
+            ));
+        }
+        for (action, reason) in breaches {
+            let in_effect = match action {
+                LossAction::SkipEntry => time < losses.skip_until,
+                LossAction::Halt => losses.halted,
+            };
+            if in_effect {
+                continue;
+            }
+            error!("[Risk] Loss limit: {reason}, {action:?}");
+            match action {
+                LossAction::SkipEntry => losses.skip_until = time + limits.skip_entry_ms,
+                LossAction::Halt => losses.halted = true,
+            }
+        }
+        // A round trip starts from the last flat mark, or at a flip
+        if side == 0.0 || closed {
+            losses.start_equity = Some(equity);
+        }
+        losses.side = side;
+        let account = self.account.as_ref();
+        if losses.halted || account.is_some_and(|a| a.halted().is_some()) {
+            LossGate::Halted
+        } else if time < losses.skip_until || account.is_some_and(|a| a.entries_paused(time)) {
+            LossGate::NoEntries
+        } else {
+            LossGate::Open
+        }
+    }
+
+    // Hard inventory limit in base units at this mid price. Without a usable
+    // mid only the base-unit cap applies.
+    pub fn position_limit(&self, mid: f64) -> f64 {
+        let mut limit = self.max_position;
+        if mid.is_nan() || mid <= 0.0 {
+            return limit;
+        }
+        if let Some(usd) = self.max_notional {
+            limit = limit.min(usd / mid);
+        }
+        let equity = self.equity();
+        if let (Some(pct), true) = (self.max_equity_pct, equity > 0.0) {
+            limit = limit.min(equity * pct / 100.0 / mid);
+        }
+        limit.max(0.0)
+    }
+
+    // (hard, soft) limits in base units for the state's current mid
+    fn limits(&self, state: &SignalState) -> (f64, f64) {
+        let hard = self.position_limit(mark(state));
+        let soft = if self.max_position > 0.0 {
+            self.soft_limit * hard / self.max_position
+        } else {
+            0.0
+        };
+        (hard, soft)
+    }
+
+    // How deep inventory is into the soft zone: 0.0 at the soft limit, 1.0 at the hard limit
+    fn soft_zone_depth(base: f64, hard: f64, soft: f64) -> f64 {
+        let band = hard - soft;
+        if band <= 0.0 {
+            return 0.0;
+        }
+        ((base.abs() - soft) / band).clamp(0.0, 1.0)
+    }
+
+    // Inside the soft zone, shrink and push away the side that adds inventory and
+    // improve the side that reduces it, without crossing the current touch.
+    pub fn apply_soft_limits(
+        &self,
+        state: &SignalState,
+        quotes: &[QuoteProposal],
+    ) -> Vec<QuoteProposal> {
+        let base = state.position.base;
+        let (hard, soft) = self.limits(state);
+        let depth = Self::soft_zone_depth(base, hard, soft);
+        if depth <= 0.0 {
+            return quotes.to_vec();
+        }
+        let shift = SOFT_SKEW_TICKS * depth;
+        quotes
+            .iter()
+            .filter_map(|q| {
+                let is_buy = q.side == "Buy";
+                let adds_inventory = (is_buy && base > 0.0) || (!is_buy && base < 0.0);
+                let mut q = q.clone();
+                if adds_inventory {
+                    q.size *= 1.0 - depth;
+                    q.price += if is_buy { -shift } else { shift };
+                } else if is_buy {
+                    let improved = (q.price + shift).min(state.best_ask - AGGRESSIVE_SPREAD_TICKS);
+                    q.price = q.price.max(improved);
+                } else {
+                    let improved = (q.price - shift).max(state.best_bid + AGGRESSIVE_SPREAD_TICKS);
+                    q.price = q.price.min(improved);
+                }
+                if q.size <= 1e-9 {
+                    info!("[Risk] Soft limit suppressed quote: {:?}", q);
+                    return None;
+                }
+                Some(q)
+            })
+            .collect()
+    }
+
+    // If inventory is beyond the hard limit (e.g. after a double fill), build a
+    // slippage-bounded reducing order that brings it back to the soft limit.
+    pub fn overflow_hedge(&self, state: &SignalState) -> Option<HedgeOrder> {
+        let base = state.position.base;
+        let (hard, soft) = self.limits(state);
+        if base.abs() <= hard || state.best_bid <= 0.0 || state.best_ask <= 0.0 {
+            return None;
+        }
+        Some(hedge(state, base.abs() - soft))
+    }
+
+    // A slippage-bounded order closing the whole position, for winding a
+    // market down
+    pub fn flatten_hedge(&self, state: &SignalState) -> Option<HedgeOrder> {
+        let base = state.position.base;
+        if base == 0.0 || state.best_bid <= 0.0 || state.best_ask <= 0.0 {
+            return None;
+        }
+        Some(hedge(state, base.abs()))
+    }
+
+    // A slippage-bounded order closing `share` of the position, e.g. ahead of
+    // a funding payment it would make
+    pub fn reduce_hedge(&self, state: &SignalState, share: f64) -> Option<HedgeOrder> {
+        let size = state.position.base.abs() * share.clamp(0.0, 1.0);
+        if size <= 0.0 || state.best_bid <= 0.0 || state.best_ask <= 0.0 {
+            return None;
+        }
+        Some(hedge(state, size))
+    }
+
+    // Evaluate and (optionally) execute or cancel quotes; returns the approved
+    // quotes, which are assumed filled
+    pub fn evaluate(
+        &self,
+        state: &mut SignalState,
+        quotes: &[QuoteProposal],
+    ) -> Vec<QuoteProposal> {
+        let quotes = self.apply_soft_limits(state, quotes);
+        let (hard, _) = self.limits(state);
+        let mid = mark(state);
+        let time = state.book_history.back().map_or(0, |b| b.timestamp_ms);
+        let equity = state.position.quote + state.position.base * mid;
+        let gate = self.loss_gate(time, state.position.base, equity);
+        let quotes = match self.streak.lock().unwrap().as_ref() {
+            Some(widener) => widener.apply(&quotes),
+            None => quotes,
+        };
+        let quotes = match self.soft_start.lock().unwrap().as_mut() {
+            Some(ramp) => ramp.apply(time, gate == LossGate::Open, &quotes),
+            None => quotes,
+        };
+        let quotes = match self.latency.lock().unwrap().as_mut() {
+            Some(throttle) => throttle.apply(time, &quotes),
+            None => quotes,
+        };
+        // Hedges and fills outside `evaluate` move the position too
+        if let Some(slot) = &self.exposure {
+            slot.update(state.position.base, mid);
+        }
+        let mut approved_quotes = Vec::new();
+        for q in quotes {
+            let mut approved = true;
+            // Hard position limit check:
+            if q.side == "Buy" && state.position.base + q.size > hard {
+                approved = false;
+            }
+            if q.side == "Sell" && state.position.base - q.size < -hard {
+                approved = false;
+            }
+            let delta = if q.side == "Buy" { q.size } else { -q.size };
+            let adds_inventory = state.position.base * delta >= 0.0;
+            if gate == LossGate::Halted || (gate == LossGate::NoEntries && adds_inventory) {
+                info!("[Risk] Canceled Quote due to loss limit: {:?}", q);
+                continue;
+            }
+            if approved
+                && self
+                    .exposure
+                    .as_ref()
+                    .is_some_and(|s| !s.allows(delta, mid))
+            {
+                info!(
+                    "[Risk] Canceled Quote due to global exposure limit: {:?}",
+                    q
+                );
+                continue;
+            }
+
+            if approved {
+                info!("[Risk] Approved Quote: {:?}", q);
+                // For demonstration, assume fill and update position
+                if q.side == "Buy" {
+                    state.position.base += q.size;
+                    state.position.quote -= q.size * q.price;
+                } else {
+                    state.position.base -= q.size;
+                    state.position.quote += q.size * q.price;
+                }
+                if let Some(slot) = &self.exposure {
+                    slot.update(state.position.base, mid);
+                }
+                approved_quotes.push(q);
+            } else {
+                info!("[Risk] Canceled Quote due to position limit: {:?}", q);
+            }
+        }
+        approved_quotes
+    }
+}
+
+// The state's fair value, falling back to the mid when none is set
+fn mark(state: &SignalState) -> f64 {
+    if state.fair_value > 0.0 {
+        state.fair_value
+    } else if state.best_bid > 0.0 && state.best_ask > 0.0 {
+        (state.best_bid + state.best_ask) / 2.0
+    } else {
+        0.0
+    }
+}
+
+// Reduces the position by `size` with an IOC at most HEDGE_MAX_SLIPPAGE
+// through the touch
+fn hedge(state: &SignalState, size: f64) -> HedgeOrder {
+    let is_buy = state.position.base < 0.0;
+    let limit_px = if is_buy {
+        state.best_ask * (1.0 + HEDGE_MAX_SLIPPAGE)
+    } else {
+        state.best_bid * (1.0 - HEDGE_MAX_SLIPPAGE)
+    };
+    HedgeOrder {
+        is_buy,
+        size,
+        limit_px,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GlobalExposure;
+
+    fn state_at(mid: f64, base: f64) -> SignalState {
+        let mut state = SignalState {
+            best_bid: mid - 0.5,
+            best_ask: mid + 0.5,
+            ..Default::default()
+        };
+        state.position.base = base;
+        state
+    }
+
+    #[test]
+    fn test_tightest_limit_applies_at_the_current_mid() {
+        let risk = RiskManager::new(5.0)
+            .with_max_notional(100_000.0)
+            .with_max_equity_pct(50.0);
+        // $100k at $50k is 2 BTC; equity is not known yet
+        assert!((risk.position_limit(50_000.0) - 2.0).abs() < 1e-9);
+        risk.set_equity(60_000.0);
+        assert!((risk.position_limit(50_000.0) - 0.6).abs() < 1e-9);
+        // At a low price the base-unit cap takes over
+        assert_eq!(risk.position_limit(1_000.0), 5.0);
+        assert_eq!(risk.position_limit(0.0), 5.0);
+    }
+
+    #[test]
+    fn test_notional_limit_rejects_quotes_and_sizes_hedges() {
+        let risk = RiskManager::new(5.0).with_max_notional(100_000.0);
+        let buy = QuoteProposal {
+            side: "Buy".to_string(),
+            price: 49_999.0,
+            size: 0.2,
+        };
+        // 2 BTC is $100k: the buy is refused, though far inside the 5 BTC cap
+        let mut state = state_at(50_000.0, 2.0);
+        assert!(risk
+            .evaluate(&mut state, std::slice::from_ref(&buy))
+            .is_empty());
+        let mut state = state_at(50_000.0, 2.0);
+        assert_eq!(RiskManager::new(5.0).evaluate(&mut state, &[buy]).len(), 1);
+
+        let state = state_at(50_000.0, -2.5);
+        let hedge = risk.overflow_hedge(&state).expect("beyond 2 BTC");
+        assert!(hedge.is_buy);
+        // Back to the soft limit: 60% of 2 BTC
+        assert!((hedge.size - 1.3).abs() < 1e-9);
+        // Five significant figures and the coin's size decimals when sent
+        let hedge = risk.overflow_hedge(&state_at(50_000.0, -2.50004)).unwrap();
+        let sent = hedge.rounded(&AssetSpec::perp(4));
+        assert_eq!((sent.size, sent.limit_px), (1.3, 50_101.0));
+    }
+
+    #[test]
+    fn test_loss_limits_skip_entries_then_halt() {
+        let risk = RiskManager::new(5.0).with_loss_limits(LossLimits {
+            max_trade_loss: Some(5.0),
+            max_hourly_loss: Some(8.0),
+            skip_entry_ms: 1_000,
+            ..Default::default()
+        });
+        let quote = |side: &str| QuoteProposal {
+            side: side.to_string(),
+            price: 100.0,
+            size: 1.0,
+        };
+        // (time, base, quote) as the book arrives: a round trip losing 6, then one losing 3
+        let step = |time: u64, base: f64, cash: f64, side: &str| {
+            let mut state = state_at(100.0, base);
+            state.position.quote = cash;
+            state.book_history.push_back(crate::BookSample {
+                timestamp_ms: time,
+                mid_price: 100.0,
+                best_bid: 99.5,
+                best_ask: 100.5,
+                bid_volume: 1.0,
+                ask_volume: 1.0,
+            });
+            risk.evaluate(&mut state, &[quote(side)]).len()
+        };
+        assert_eq!(step(0, 0.0, 0.0, "Buy"), 1);
+        assert_eq!(step(100, 1.0, -100.0, "Sell"), 1);
+        // The 6 loss breaches the trade limit: entries wait, exits do not
+        assert_eq!(step(200, 0.0, -6.0, "Buy"), 0);
+        assert_eq!(step(300, 0.0, -6.0, "Sell"), 0);
+        assert_eq!(step(1_300, 0.0, -6.0, "Sell"), 1);
+        assert_eq!(step(1_400, -1.0, 94.0, "Buy"), 1);
+        assert!(!risk.halted());
+        // 3 more is within the trade limit but 9 this hour halts everything
+        assert_eq!(step(1_500, 0.0, -9.0, "Buy"), 0);
+        assert!(risk.halted());
+        assert_eq!(step(LOSS_WINDOW_MS * 2, 0.0, -9.0, "Buy"), 0);
+        // A reload keeps the halt
+        assert!(risk.carry_over(RiskManager::new(5.0)).halted());
+    }
+
+    #[test]
+    fn test_daily_loss_and_drawdown_track_session_equity() {
+        let risk = RiskManager::new(5.0).with_loss_limits(LossLimits {
+            max_daily_loss: Some(10.0),
+            daily_action: LossAction::SkipEntry,
+            max_drawdown: Some(15.0),
+            skip_entry_ms: 1_000,
+            ..Default::default()
+        });
+        let buy = QuoteProposal {
+            side: "Buy".to_string(),
+            price: 100.0,
+            size: 1.0,
+        };
+        // A long 1, bought at 100, marked at `mid`
+        let step = |time: u64, mid: f64| {
+            let mut state = state_at(mid, 1.0);
+            state.position.quote = -100.0;
+            state.book_history.push_back(crate::BookSample {
+                timestamp_ms: time,
+                mid_price: mid,
+                best_bid: mid - 0.5,
+                best_ask: mid + 0.5,
+                bid_volume: 1.0,
+                ask_volume: 1.0,
+            });
+            risk.evaluate(&mut state, std::slice::from_ref(&buy)).len()
+        };
+        assert_eq!(step(0, 100.0), 1);
+        // 11 down on the day, still open: no more buying while it lasts
+        assert_eq!(step(100, 89.0), 0);
+        assert_eq!(step(1_200, 89.0), 0);
+        assert_eq!(step(2_400, 95.0), 1);
+        // A new day starts from the current mark
+        assert_eq!(step(DAY_MS + 100, 108.0), 1);
+        assert!(!risk.wants_flatten());
+        // 16 off the high at 108 halts, and asks the bot to flatten
+        assert_eq!(step(DAY_MS + 200, 92.0), 0);
+        assert!(risk.wants_flatten());
+    }
+
+    #[test]
+    fn test_shared_exposure_limits_strategies_that_are_each_within_their_cap() {
+        let global = GlobalExposure::new(150_000.0);
+        let momentum = RiskManager::new(5.0).with_exposure(global.slot("momentum", "BTC"));
+        let maker = RiskManager::new(5.0).with_exposure(global.slot("maker", "BTC"));
+        let buy = |size| QuoteProposal {
+            side: "Buy".to_string(),
+            price: 50_000.0,
+            size,
+        };
+        let mut momentum_state = state_at(50_000.0, 0.0);
+        assert_eq!(momentum.evaluate(&mut momentum_state, &[buy(2.0)]).len(), 1);
+        // 2 BTC already held elsewhere leaves room for 1 more
+        let mut maker_state = state_at(50_000.0, 0.0);
+        assert!(maker.evaluate(&mut maker_state, &[buy(1.5)]).is_empty());
+        assert_eq!(maker.evaluate(&mut maker_state, &[buy(1.0)]).len(), 1);
+        assert_eq!(global.net_notional(), 150_000.0);
+    }
+}
@@ -0,0 +1,336 @@
+use log::{info, warn};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use crate::InfoClient;
+
+pub(crate) const FLOW_WINDOW_MS: u64 = 300_000; // Recent trades counted towards a market's flow
+pub(crate) const USD_PER_COST_BP: f64 = 100.0; // $ lost per $1M of volume that make one bp
+
+// What the scanner saw of one market: the touch and the notional trading
+// through it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketSnapshot {
+    pub coin: String,
+    pub spread_bps: f64,
+    pub depth_usd: f64, // thinner side of the touch
+    pub flow_usd_per_min: f64,
+}
+
+impl MarketSnapshot {
+    // From the best bid and ask (price, size) and recent (time, price, size)
+    // trades; None for a one-sided or crossed touch
+    pub fn from_touch(
+        coin: &str,
+        bid: (f64, f64),
+        ask: (f64, f64),
+        trades: &[(u64, f64, f64)],
+        now_ms: u64,
+    ) -> Option<Self> {
+        let ((bid_px, bid_sz), (ask_px, ask_sz)) = (bid, ask);
+        if !(bid_px > 0.0 && ask_px > bid_px) {
+            return None;
+        }
+        let mid = (bid_px + ask_px) / 2.0;
+        let since = now_ms.saturating_sub(FLOW_WINDOW_MS);
+        let traded: f64 = trades
+            .iter()
+            .filter(|(time, _, _)| *time >= since)
+            .map(|(_, px, sz)| px * sz)
+            .sum();
+        Some(Self {
+            coin: coin.to_string(),
+            spread_bps: (ask_px - bid_px) / mid * 10_000.0,
+            depth_usd: (bid_px * bid_sz).min(ask_px * ask_sz),
+            flow_usd_per_min: traded / (FLOW_WINDOW_MS as f64 / 60_000.0),
+        })
+    }
+}
+
+// One snapshot per coin from the L2 snapshot and recent trades endpoints.
+// Coins whose data cannot be fetched are left out.
+pub async fn scan_markets(info: &InfoClient, coins: &[String]) -> Vec<MarketSnapshot> {
+    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+    let mut snapshots = Vec::new();
+    for coin in coins {
+        let (book, trades) = match (
+            info.l2_snapshot(coin.clone()).await,
+            info.recent_trades(coin.clone()).await,
+        ) {
+            (Ok(book), Ok(trades)) => (book, trades),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("[Rotation] Scan of {coin} failed: {e}");
+                continue;
+            }
+        };
+        let touch = |side: usize| {
+            let level = book.levels.get(side)?.first()?;
+            Some((level.px.parse().ok()?, level.sz.parse().ok()?))
+        };
+        let trades: Vec<_> = trades
+            .iter()
+            .filter_map(|t| Some((t.time, t.px.parse().ok()?, t.sz.parse().ok()?)))
+            .collect();
+        let snapshot = touch(0)
+            .zip(touch(1))
+            .and_then(|(bid, ask)| MarketSnapshot::from_touch(coin, bid, ask, &trades, now_ms));
+        snapshots.extend(snapshot);
+    }
+    snapshots
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RotationConfig {
+    pub max_markets: usize,
+    pub min_depth_usd: f64, // thinner markets are never picked
+    // How much a newcomer must outscore a market being quoted to take its
+    // slot, as a fraction: 0.2 needs a 20% better score
+    pub switch_margin: f64,
+}
+
+impl Default for RotationConfig {
+    fn default() -> Self {
+        Self {
+            max_markets: 3,
+            min_depth_usd: 1_000.0,
+            switch_margin: 0.2,
+        }
+    }
+}
+
+// Markets a rebalance starts and stops quoting
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RotationPlan {
+    pub added: Vec<String>,
+    pub dropped: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct RotationState {
+    active: BTreeSet<String>,
+    winding_down: BTreeSet<String>,
+    retired: Vec<String>,                    // flat and ready to unsubscribe
+    cost_per_million: BTreeMap<String, f64>, // reported by each coin's router
+    scores: BTreeMap<String, f64>,           // from the last rebalance
+}
+
+// Moves quoting capital to the best-scoring markets. A market scores the
+// half spread a maker earns, less what quoting it has cost per $1M of volume,
+// in bps, times the notional trading through it per minute. Each rebalance
+// quotes the best `max_markets` with a positive score; a market already
+// quoted keeps its slot unless the newcomer beats it by `switch_margin`.
+// Dropped markets wind down: their routers stop quoting and hedge their
+// inventory flat, then report themselves retired for the bot to remove and
+// unsubscribe. Clones share one rotation.
+#[derive(Debug, Clone, Default)]
+pub struct MarketRotation {
+    config: RotationConfig,
+    state: Arc<Mutex<RotationState>>,
+}
+
+impl MarketRotation {
+    pub fn new(config: RotationConfig) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    // A router's realized cost per $1M of volume, None before it has traded
+    pub fn report_cost(&self, coin: &str, cost_per_million: Option<f64>) {
+        if let Some(cost) = cost_per_million {
+            let mut state = self.state.lock().unwrap();
+            state.cost_per_million.insert(coin.to_string(), cost);
+        }
+    }
+
+    pub fn score(&self, snapshot: &MarketSnapshot) -> Option<f64> {
+        if snapshot.depth_usd < self.config.min_depth_usd {
+            return None;
+        }
+        let state = self.state.lock().unwrap();
+        let cost = state.cost_per_million.get(&snapshot.coin).copied();
+        let edge_bps = snapshot.spread_bps / 2.0 - cost.unwrap_or(0.0) / USD_PER_COST_BP;
+        Some(edge_bps * snapshot.flow_usd_per_min)
+    }
+
+    pub fn rebalance(&self, snapshots: &[MarketSnapshot]) -> RotationPlan {
+        let scores: BTreeMap<String, f64> = snapshots
+            .iter()
+            .filter_map(|s| Some((s.coin.clone(), self.score(s)?)))
+            .collect();
+        let mut state = self.state.lock().unwrap();
+        let mut ranked: Vec<_> = scores.iter().filter(|(_, score)| **score > 0.0).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(a.1));
+        let mut chosen: Vec<_> = ranked.iter().take(self.config.max_markets).collect();
+        // Swap back incumbents the newcomers do not beat by the margin
+        loop {
+            let newcomer = chosen
+                .iter()
+                .enumerate()
+                .filter(|(_, (coin, _))| !state.active.contains(*coin))
+                .min_by(|a, b| a.1 .1.total_cmp(b.1 .1));
+            let incumbent = ranked
+                .iter()
+                .filter(|(coin, _)| state.active.contains(*coin))
+                .find(|entry| !chosen.contains(entry));
+            match (newcomer, incumbent) {
+                (Some((i, (_, new))), Some(kept))
+                    if *kept.1 * (1.0 + self.config.switch_margin) >= **new =>
+                {
+                    chosen[i] = kept;
+                }
+                _ => break,
+            }
+        }
+        let chosen: BTreeSet<String> = chosen.iter().map(|(coin, _)| coin.to_string()).collect();
+
+        let mut plan = RotationPlan::default();
+        for coin in &chosen {
+            // Markets still winding down are revived, not restarted
+            let revived = state.winding_down.remove(coin);
+            let unretired = state.retired.iter().any(|c| c == coin);
+            state.retired.retain(|c| c != coin);
+            if !state.active.contains(coin) && !revived && !unretired {
+                plan.added.push(coin.clone());
+            }
+        }
+        for coin in state.active.difference(&chosen) {
+            plan.dropped.push(coin.clone());
+        }
+        state.winding_down.extend(plan.dropped.iter().cloned());
+        state.active = chosen;
+        state.scores = scores;
+        if !plan.added.is_empty() || !plan.dropped.is_empty() {
+            info!(
+                "[Rotation] Adding {:?}, winding down {:?}",
+                plan.added, plan.dropped
+            );
+        }
+        plan
+    }
+
+    pub fn is_winding_down(&self, coin: &str) -> bool {
+        self.state.lock().unwrap().winding_down.contains(coin)
+    }
+
+    // Called by a winding-down router once it is flat
+    pub fn retire(&self, coin: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.winding_down.remove(coin) {
+            info!("[Rotation] {coin} wound down");
+            state.retired.push(coin.to_string());
+        }
+    }
+
+    // Markets flat since the last call, to remove and unsubscribe
+    pub fn take_retired(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.lock().unwrap().retired)
+    }
+
+    // Takes back a market the bot could not start quoting, so a later
+    // rebalance can add it again
+    pub fn refuse(&self, coin: &str) {
+        self.state.lock().unwrap().active.remove(coin);
+    }
+
+    pub fn active(&self) -> Vec<String> {
+        self.state.lock().unwrap().active.iter().cloned().collect()
+    }
+
+    // One line per scanned market, for the admin `rotation` command
+    pub fn summary(&self) -> String {
+        let state = self.state.lock().unwrap();
+        if state.scores.is_empty() {
+            return "no markets scanned yet".to_string();
+        }
+        let mut scores: Vec<_> = state.scores.iter().collect();
+        scores.sort_by(|a, b| b.1.total_cmp(a.1));
+        scores
+            .iter()
+            .map(|(coin, score)| {
+                let status = if state.active.contains(*coin) {
+                    "quoting"
+                } else if state.winding_down.contains(*coin) {
+                    "winding down"
+                } else {
+                    "idle"
+                };
+                let cost = state
+                    .cost_per_million
+                    .get(*coin)
+                    .map_or("-".to_string(), |c| format!("${c:.0}/1M"));
+                format!("{coin}: score {score:.1} | cost {cost} | {status}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(coin: &str, spread_bps: f64, flow: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            coin: coin.to_string(),
+            spread_bps,
+            depth_usd: 5_000.0,
+            flow_usd_per_min: flow,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_from_touch_and_trades() {
+        let trades = [(0, 100.0, 1.0), (400_000, 100.0, 3.0)];
+        let snapshot =
+            MarketSnapshot::from_touch("BTC", (99.9, 20.0), (100.1, 50.0), &trades, 500_000)
+                .unwrap();
+        assert!((snapshot.spread_bps - 20.0).abs() < 1e-9);
+        assert!((snapshot.depth_usd - 1_998.0).abs() < 1e-9);
+        assert!((snapshot.flow_usd_per_min - 60.0).abs() < 1e-9); // $300 over 5 minutes
+        assert!(MarketSnapshot::from_touch("BTC", (100.1, 1.0), (99.9, 1.0), &[], 0).is_none());
+    }
+
+    #[test]
+    fn test_rotates_to_the_best_markets_with_hysteresis() {
+        let rotation = MarketRotation::new(RotationConfig {
+            max_markets: 2,
+            ..Default::default()
+        });
+        let plan = rotation.rebalance(&[
+            market("BTC", 2.0, 1_000.0),
+            market("ETH", 4.0, 1_000.0),
+            market("SOL", 1.0, 1_000.0),
+        ]);
+        assert_eq!(plan.added, ["BTC", "ETH"]);
+
+        // SOL edges past BTC, but not by the switch margin
+        let plan = rotation.rebalance(&[
+            market("BTC", 2.0, 1_000.0),
+            market("ETH", 4.0, 1_000.0),
+            market("SOL", 2.2, 1_000.0),
+        ]);
+        assert_eq!(plan, RotationPlan::default());
+
+        // BTC's fills have cost $200 per $1M, 2bps, leaving no edge
+        rotation.report_cost("BTC", Some(200.0));
+        let plan = rotation.rebalance(&[
+            market("BTC", 2.0, 1_000.0),
+            market("ETH", 4.0, 1_000.0),
+            market("SOL", 2.2, 1_000.0),
+        ]);
+        assert_eq!(
+            (plan.added, plan.dropped),
+            (vec!["SOL".into()], vec!["BTC".into()])
+        );
+        assert!(rotation.is_winding_down("BTC"));
+        assert!(rotation.take_retired().is_empty());
+        rotation.retire("BTC");
+        assert_eq!(rotation.take_retired(), ["BTC"]);
+        assert_eq!(rotation.active(), ["ETH", "SOL"]);
+        assert!(rotation.summary().starts_with("ETH: score 2000.0"));
+    }
+}
@@ -0,0 +1,667 @@
+use log::{debug, error, info, warn};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::{mpsc::UnboundedReceiver, watch, Mutex};
+use uuid::Uuid;
+
+use crate::{
+    hedge_order, AdminRequest, AssetCtx, AssetSpec, BookLevel, Chaos, ClientCancelRequestCloid,
+    CoinConfig, EventLog, Executor, FeeModel, FillRole, FlowMeasure, FundingMonitor, HedgeOrder,
+    Journal, KillSwitch, MarketEvent, MarketRotation, Message, Metrics, OrderBook, OrderIntent,
+    OrderManager, OrderOutcome, PriceLadder, QuoteActivity, QuoteProposal, QuoteSides, RiskManager,
+    SignalAttribution, SignalContributions, SignalEngine, SignalState, SignalWindows,
+    SpreadTracker, StatusReporter, Strategy, TimeInForce, VolumeEfficiency,
+};
+
+pub(crate) const MIN_HEDGE_SIZE: f64 = 1e-9; // Smaller positions count as flat when winding down
+
+// Running totals of simulated activity, for the admin `ledger` command
+#[derive(Debug, Default)]
+struct Ledger {
+    fills: u64,
+    hedges: u64,
+    volume: f64, // notional
+    last_quotes: Vec<QuoteProposal>,
+}
+
+// Routes one coin's market data through signals, strategy and risk. One per
+// coin, each driven by its own task with isolated signal, strategy and risk
+// state. The replay binary drives the same router from recorded data.
+pub struct MessageRouter {
+    signal: Mutex<SignalEngine>,
+    book: Mutex<OrderBook>,     // full L2 depth behind the signals
+    ladder: Mutex<PriceLadder>, // recent trades for the admin `ladder` view
+    strategy: Mutex<Box<dyn Strategy>>,
+    risk_mgr: Mutex<Arc<RiskManager>>, // replaced whole when limits are reloaded
+    coin: String,
+    executor: Option<Executor>,   // None = simulated fills only
+    orders: Option<OrderManager>, // lifecycle of orders sent through the executor
+    chaos: Option<Arc<Chaos>>,    // fault injection for paper trading
+    ledger: Mutex<Ledger>,
+    journal: Option<Arc<Journal>>,
+    spreads: Mutex<SpreadTracker>, // effective / realized spread per fill
+    activity: Mutex<QuoteActivity>, // cancel/fill ratio, two-sided uptime, time at touch
+    attribution: Mutex<SignalAttribution>, // per-signal hit rates of fills
+    efficiency: Mutex<VolumeEfficiency>, // fees and PnL lost per $1M of volume
+    metrics: Option<Metrics>,
+    reporter: StatusReporter, // throttled status output, shared by all coins
+    config_updates: Option<watch::Receiver<CoinConfig>>,
+    kill_switch: Option<KillSwitch>, // tripped when the risk manager halts
+    spec: Option<AssetSpec>,         // rounds hedges sent through the executor
+    hedge_tif: Mutex<TimeInForce>,   // of hedges sent through the executor
+    sides: Mutex<QuoteSides>,        // sides quoted, from the config or the admin `sides`
+    rotation: Option<MarketRotation>, // winds this coin down when it is rotated out
+    event_log: Option<EventLog>,     // quotes the risk manager refused
+    funding: Mutex<Option<FundingMonitor>>, // funding bias and cuts ahead of payments
+}
+impl MessageRouter {
+    pub fn new(strategy: Box<dyn Strategy>, risk_mgr: Arc<RiskManager>, coin: &str) -> Self {
+        Self {
+            signal: Mutex::new(SignalEngine::new()),
+            book: Mutex::new(OrderBook::new()),
+            ladder: Mutex::new(PriceLadder::default()),
+            strategy: Mutex::new(strategy),
+            risk_mgr: Mutex::new(risk_mgr),
+            coin: coin.to_string(),
+            executor: None,
+            orders: None,
+            chaos: None,
+            ledger: Mutex::new(Ledger::default()),
+            journal: None,
+            spreads: Mutex::new(SpreadTracker::default()),
+            activity: Mutex::new(QuoteActivity::new()),
+            attribution: Mutex::new(SignalAttribution::default()),
+            efficiency: Mutex::new(VolumeEfficiency::default()),
+            metrics: None,
+            reporter: StatusReporter::default(),
+            config_updates: None,
+            kill_switch: None,
+            spec: None,
+            hedge_tif: Mutex::new(TimeInForce::Ioc),
+            sides: Mutex::new(QuoteSides::Both),
+            rotation: None,
+            event_log: None,
+            funding: Mutex::new(None),
+        }
+    }
+    // Route hedges to the exchange instead of simulating them. The executor can
+    // be shared across coins so one queue orders all of the bot's requests.
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+    // Track exchange orders through their lifecycle. Pass the manager the
+    // user's order and fill events, either via its own subscription or by
+    // routing them through `handle`.
+    pub fn with_order_manager(mut self, orders: OrderManager) -> Self {
+        self.orders = Some(orders);
+        self
+    }
+    // Inject exchange errors into hedge requests
+    pub fn with_chaos(mut self, chaos: Arc<Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+    // Record the risk manager's refusals in the structured event log
+    pub fn with_event_log(mut self, log: EventLog) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+    // Record simulated fills and hedges in the run journal
+    pub fn with_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+    // Measure trade flow with volume buckets instead of the time-decayed slide
+    pub fn with_flow_measure(mut self, flow: FlowMeasure) -> Self {
+        let engine = std::mem::take(self.signal.get_mut());
+        *self.signal.get_mut() = engine.with_flow_measure(flow);
+        self
+    }
+    // History lengths and thresholds from the coin's `StrategyConfig`
+    pub fn with_signal_windows(mut self, windows: SignalWindows) -> Self {
+        let engine = std::mem::take(self.signal.get_mut());
+        *self.signal.get_mut() = engine.with_windows(windows);
+        self
+    }
+    // Charge the account's fees in the volume efficiency; without a schedule
+    // fills are free
+    pub fn with_fee_model(mut self, fees: FeeModel) -> Self {
+        *self.efficiency.get_mut() = VolumeEfficiency::new(fees);
+        self
+    }
+    // Round hedge prices and sizes to the coin's exchange precision
+    pub fn with_asset_spec(mut self, spec: AssetSpec) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+    // Report this coin's cost per $1M of volume to `rotation`, and stop
+    // quoting and flatten once it rotates the coin out
+    pub fn with_rotation(mut self, rotation: MarketRotation) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+    pub fn with_reporter(mut self, reporter: StatusReporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+    // Publish execution-quality gauges (labelled by coin) to a shared registry
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    // Send hedges with the config's hedge time in force instead of as IOCs
+    pub fn with_hedge_tif(mut self, tif: TimeInForce) -> Self {
+        *self.hedge_tif.get_mut() = tif;
+        self
+    }
+    // Quote only the config's sides, e.g. asks alone to sell down a long
+    pub fn with_sides(mut self, sides: QuoteSides) -> Self {
+        *self.sides.get_mut() = sides;
+        self
+    }
+    // Lean quotes toward the side earning funding and cut the position ahead
+    // of payments it would make. Pass the coin's asset context through `handle`
+    // for the predicted rate.
+    pub fn with_funding(mut self, funding: FundingMonitor) -> Self {
+        *self.funding.get_mut() = Some(funding);
+        self
+    }
+    // Apply config reloads sent on `updates` while running
+    pub fn with_config_updates(mut self, updates: watch::Receiver<CoinConfig>) -> Self {
+        self.config_updates = Some(updates);
+        self
+    }
+    // Trip `kill_switch` when this coin's loss limits halt trading, flattening
+    // every coin registered with it
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+    // Swaps in reloaded signal windows, strategy settings and risk limits
+    // between two events. Subscriptions, orders and positions are untouched.
+    pub async fn reconfigure(&self, config: &CoinConfig) {
+        self.signal
+            .lock()
+            .await
+            .set_windows(config.strategy.signal_windows());
+        self.strategy.lock().await.configure(&config.strategy);
+        *self.hedge_tif.lock().await = config.strategy.tif.hedge;
+        *self.sides.lock().await = config.strategy.sides;
+        if let Some(funding) = self.funding.lock().await.as_mut() {
+            funding.configure(&config.strategy);
+        }
+        let mut risk_mgr = self.risk_mgr.lock().await;
+        *risk_mgr = Arc::new(risk_mgr.carry_over(config.risk.risk_manager()));
+        info!("{} config reloaded", self.coin);
+    }
+    fn journal(&self, kind: &str, data: serde_json::Value) {
+        if let Some(Err(e)) = self.journal.as_ref().map(|j| j.record(kind, data)) {
+            error!("Journal write failed: {e}");
+        }
+    }
+    // Send (or simulate) a reducing hedge and update the tracked position. A
+    // hedge that rests instead of filling is cancelled straight away.
+    async fn execute_hedge(&self, state: &mut SignalState, hedge: &HedgeOrder) {
+        warn!("{} inventory breach, hedging: {hedge:?}", self.coin);
+        // Sent, or simulated, at the exchange's precision
+        let hedge = match &self.spec {
+            Some(spec) => hedge.rounded(spec),
+            None => hedge.clone(),
+        };
+        if hedge.size <= 0.0 {
+            return;
+        }
+        // A rejected hedge is retried on the next book update while the breach persists
+        if let Some(fault) = self.chaos.as_ref().and_then(|c| c.exchange_fault()) {
+            error!("Hedge rejected: {fault:?}");
+            return;
+        }
+        let (filled_sz, avg_px) = match &self.executor {
+            Some(executor) => {
+                let cloid = Uuid::new_v4();
+                let tif = *self.hedge_tif.lock().await;
+                let mut order = hedge_order(&self.coin, &hedge, tif);
+                order.cloid = Some(cloid);
+                if let Some(orders) = &self.orders {
+                    orders.track(cloid, &self.coin, hedge.is_buy, order.limit_px, order.sz);
+                }
+                let outcome = executor
+                    .submit(OrderIntent::Place(order))
+                    .await
+                    .map(OrderOutcome::from);
+                if let (Some(orders), Ok(outcome)) = (&self.orders, &outcome) {
+                    orders.on_outcome(cloid, outcome);
+                }
+                match outcome {
+                    Ok(outcome) => match outcome {
+                        OrderOutcome::Filled { size, avg_px, .. } => (size, avg_px),
+                        OrderOutcome::Rejected(e) => {
+                            error!("Hedge rejected: {e}");
+                            return;
+                        }
+                        OrderOutcome::Resting { .. } => {
+                            error!("Hedge rested ({tif}), cancelling");
+                            let cancel = ClientCancelRequestCloid {
+                                asset: self.coin.clone(),
+                                cloid,
+                            };
+                            if let Err(e) =
+                                executor.submit(OrderIntent::CancelByCloid(cancel)).await
+                            {
+                                error!("Hedge cancel failed: {e}");
+                            }
+                            return;
+                        }
+                        outcome => {
+                            error!("Hedge not filled: {outcome:?}");
+                            return;
+                        }
+                    },
+                    Err(e) => {
+                        error!("Hedge request failed: {e}");
+                        return;
+                    }
+                }
+            }
+            // Demo mode: assume the IOC fills in full at the touch
+            None => {
+                let px = if hedge.is_buy {
+                    state.best_ask
+                } else {
+                    state.best_bid
+                };
+                (hedge.size, px)
+            }
+        };
+        info!("Hedge filled {filled_sz} @ {avg_px}");
+        let time = state.book_history.back().map_or(0, |b| b.timestamp_ms);
+        let mid = (state.best_bid + state.best_ask) / 2.0;
+        self.spreads
+            .lock()
+            .await
+            .on_fill(time, avg_px, hedge.is_buy, FillRole::Taker, mid);
+        self.efficiency
+            .lock()
+            .await
+            .on_fill(filled_sz * avg_px, FillRole::Taker);
+        let mut ledger = self.ledger.lock().await;
+        ledger.hedges += 1;
+        ledger.volume += filled_sz * avg_px;
+        drop(ledger);
+        self.journal(
+            "hedge",
+            json!({"coin": self.coin, "is_buy": hedge.is_buy, "size": filled_sz, "px": avg_px}),
+        );
+        if hedge.is_buy {
+            state.position.base += filled_sz;
+            state.position.quote -= filled_sz * avg_px;
+        } else {
+            state.position.base -= filled_sz;
+            state.position.quote += filled_sz * avg_px;
+        }
+    }
+    async fn on_book(&self, time: u64, bids: &[BookLevel], asks: &[BookLevel]) {
+        // Update signals, ignoring duplicated or out-of-order books
+        let mut engine = self.signal.lock().await;
+        let last_ms = engine.state.book_history.back().map(|b| b.timestamp_ms);
+        if last_ms.is_some_and(|last| time <= last) {
+            return;
+        }
+        // Rebuild the full depth; one-sided or crossed books are skipped
+        let mut book = self.book.lock().await;
+        if !book.apply_snapshot(time, bids, asks) {
+            return;
+        }
+        let Some((bid_px, ask_px, _, _)) = book.top() else {
+            return;
+        };
+        let mut funding = self.funding.lock().await;
+        if let Some(funding) = funding.as_ref() {
+            engine.set_funding_bias(funding.bias());
+        }
+        engine.process_book(&book);
+        drop(book);
+        engine.report(&self.reporter, &self.coin);
+        let mid = (bid_px + ask_px) / 2.0;
+        self.spreads.lock().await.on_mid(time, mid);
+        self.attribution.lock().await.on_mid(time, mid);
+        // Build and evaluate quotes; a market being wound down gets none
+        let winding_down = self
+            .rotation
+            .as_ref()
+            .is_some_and(|r| r.is_winding_down(&self.coin));
+        let risk_mgr = self.risk_mgr.lock().await.clone();
+        let sides = *self.sides.lock().await;
+        let quotes = if winding_down {
+            Vec::new()
+        } else {
+            let mut quotes = self.strategy.lock().await.quote(&engine.state);
+            quotes.retain(|q| sides.allows(&q.side));
+            quotes
+        };
+        let approved = risk_mgr.evaluate(&mut engine.state, &quotes);
+        if let Some(log) = self
+            .event_log
+            .as_ref()
+            .filter(|_| approved.len() < quotes.len())
+        {
+            let refused: Vec<_> = quotes
+                .iter()
+                .filter(|q| !approved.iter().any(|a| a.side == q.side))
+                .map(|q| json!({"side": q.side, "px": q.price, "sz": q.size}))
+                .collect();
+            log.record(
+                "risk",
+                json!({
+                    "coin": self.coin, "book_time": time, "refused": refused,
+                    "position": engine.state.position.base, "halt": risk_mgr.halt_reason(),
+                }),
+            );
+        }
+        if let Some(kill_switch) = &self.kill_switch {
+            if risk_mgr.wants_flatten() && !kill_switch.is_tripped() {
+                // Flattening waits on the exchange; the feed keeps flowing meanwhile
+                let reason = risk_mgr.halt_reason().unwrap_or_default();
+                let (kill_switch, reason) =
+                    (kill_switch.clone(), format!("{} {reason}", self.coin));
+                tokio::spawn(async move { kill_switch.trigger(&reason).await });
+            }
+        }
+        let mut ledger = self.ledger.lock().await;
+        ledger.fills += approved.len() as u64;
+        ledger.volume += approved.iter().map(|q| q.price * q.size).sum::<f64>();
+        let mut spreads = self.spreads.lock().await;
+        let mut activity = self.activity.lock().await;
+        let mut attribution = self.attribution.lock().await;
+        let mut efficiency = self.efficiency.lock().await;
+        activity.on_quotes(time, &approved, bid_px, ask_px);
+        // What each signal contributed to the decisions on this book
+        let contributions = SignalContributions::from_state(&engine.state);
+        for q in &approved {
+            spreads.on_fill(time, q.price, q.side == "Buy", FillRole::Maker, mid);
+            activity.on_fill();
+            efficiency.on_fill(q.price * q.size, FillRole::Maker);
+            attribution.on_fill(time, q.price, q.side == "Buy", contributions);
+            debug!(
+                "{} {} {:.4} @ {:.2} from {contributions:?}",
+                self.coin, q.side, q.size, q.price
+            );
+            self.journal(
+                "fill",
+                json!({"coin": self.coin, "book_time": time, "side": q.side, "size": q.size, "px": q.price, "signals": contributions}),
+            );
+        }
+        drop(attribution);
+        ledger.last_quotes = approved;
+        drop(ledger);
+        if let Some(metrics) = &self.metrics {
+            spreads.export(metrics, &self.coin);
+            activity.export(metrics, &self.coin);
+            efficiency.export(metrics, &self.coin, marked_pnl(&engine.state));
+        }
+        if let Some(rotation) = &self.rotation {
+            let cost = efficiency.cost_per_million(marked_pnl(&engine.state));
+            rotation.report_cost(&self.coin, cost);
+        }
+        drop(spreads);
+        drop(activity);
+        drop(efficiency);
+        if winding_down {
+            self.wind_down(&risk_mgr, &mut engine.state).await;
+            return;
+        }
+        // Pull inventory back inside the band if it overflowed
+        if let Some(hedge) = risk_mgr.overflow_hedge(&engine.state) {
+            self.execute_hedge(&mut engine.state, &hedge).await;
+        }
+        // Then cut a position that is about to pay funding
+        let base = engine.state.position.base;
+        let reduce = funding
+            .as_mut()
+            .and_then(|f| f.should_reduce(time, base).then_some(f.reduce_share))
+            .and_then(|share| risk_mgr.reduce_hedge(&engine.state, share));
+        drop(funding);
+        if let Some(hedge) = reduce {
+            self.execute_hedge(&mut engine.state, &hedge).await;
+        }
+    }
+    // Hedges a rotated-out coin flat, one book at a time, and retires it once
+    // what is left rounds to nothing
+    async fn wind_down(&self, risk_mgr: &RiskManager, state: &mut SignalState) {
+        let base = state.position.base.abs();
+        let flat = match &self.spec {
+            Some(spec) => spec.round_size(base) == 0.0,
+            None => base <= MIN_HEDGE_SIZE,
+        };
+        if flat {
+            if let Some(rotation) = &self.rotation {
+                rotation.retire(&self.coin);
+            }
+        } else if let Some(hedge) = risk_mgr.flatten_hedge(state) {
+            self.execute_hedge(state, &hedge).await;
+        }
+    }
+    pub async fn handle(&self, msg: Message) {
+        if let Message::NoData = msg {
+            warn!("{} feed disconnected, waiting for reconnect", self.coin);
+            if let Some(chaos) = &self.chaos {
+                info!("{} chaos: {}", self.coin, chaos.stats.summary());
+            }
+            return;
+        }
+        if let Message::Reconnected = msg {
+            info!("{} feed reconnected, book resynced", self.coin);
+            return;
+        }
+        if let Some(orders) = &self.orders {
+            orders.on_message(&msg);
+        }
+        // The oracle price feeds `FairValue::Oracle`, the funding rate the
+        // funding bias
+        if let Message::ActiveAssetCtx(ctx) = &msg {
+            if let AssetCtx::Perps(ctx) = &ctx.data.ctx {
+                if let Ok(px) = ctx.oracle_px.parse() {
+                    self.signal.lock().await.set_oracle_price(px);
+                }
+                if let (Some(funding), Ok(rate)) =
+                    (self.funding.lock().await.as_ref(), ctx.funding.parse())
+                {
+                    funding.on_predicted(rate);
+                }
+            }
+            return;
+        }
+        // Malformed levels and trades are dropped during normalization
+        for event in MarketEvent::from_message(&msg) {
+            self.handle_event(event).await;
+        }
+    }
+    // One normalized book or trade, live or from a recording
+    pub async fn handle_event(&self, event: MarketEvent) {
+        // A paper backend fills resting orders against this coin's data
+        if let Some(executor) = &self.executor {
+            executor.on_market_event(&self.coin, &event);
+        }
+        match event {
+            MarketEvent::Book { time, bids, asks } => self.on_book(time, &bids, &asks).await,
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => {
+                self.signal.lock().await.process_trade(px, sz, is_buy, time);
+                self.ladder.lock().await.on_trade(time, px, sz, is_buy);
+                self.strategy.lock().await.on_trade(px, sz, is_buy, time);
+            }
+        }
+    }
+    // Answers a command from the admin REPL
+    pub async fn handle_admin(&self, req: AdminRequest) {
+        let engine = self.signal.lock().await;
+        let state = &engine.state;
+        let mid = (state.best_bid + state.best_ask) / 2.0;
+        let text = match req.command.as_str() {
+            "pos" => format!(
+                "{} base {:.4} quote {:.2} | mid {:.2} | fair {:.2} | PnL {:.2}",
+                self.coin,
+                state.position.base,
+                state.position.quote,
+                mid,
+                mark(state),
+                marked_pnl(state)
+            ),
+            "orders" => {
+                let ledger = self.ledger.lock().await;
+                let mut lines: Vec<_> = ledger
+                    .last_quotes
+                    .iter()
+                    .map(|q| format!("{} {} {:.4} @ {:.2}", self.coin, q.side, q.size, q.price))
+                    .collect();
+                if lines.is_empty() {
+                    lines.push("no quotes on the last book".to_string());
+                }
+                // Orders live on the exchange, with fill progress
+                let open = self.orders.as_ref().map(|o| o.open_orders()).unwrap_or_default();
+                for o in open.iter().filter(|o| o.coin == self.coin) {
+                    lines.push(format!(
+                        "{} {} {:.4}/{:.4} @ {:.2} {:?}",
+                        self.coin,
+                        if o.is_buy { "Buy" } else { "Sell" },
+                        o.filled,
+                        o.size,
+                        o.limit_px,
+                        o.status
+                    ));
+                }
+                lines.join("\n")
+            }
+            "ladder" => {
+                // Exchange orders when trading, otherwise the simulated quotes
+                let ours: Vec<_> = match &self.orders {
+                    Some(orders) => orders
+                        .open_orders()
+                        .iter()
+                        .filter(|o| o.coin == self.coin)
+                        .map(|o| (o.is_buy, o.limit_px, o.size - o.filled))
+                        .collect(),
+                    None => self
+                        .ledger
+                        .lock()
+                        .await
+                        .last_quotes
+                        .iter()
+                        .map(|q| (q.side == "Buy", q.price, q.size))
+                        .collect(),
+                };
+                let book = self.book.lock().await;
+                self.ladder.lock().await.render(&self.coin, &book, &ours)
+            }
+            "signals" => format!(
+                "{} bid {:.2} ask {:.2} | micro {:.2} | depth imb {:.2} | trend {:.3} | twap {:.2} (dev {:.4}) | fill score {:.2} | vol {:.2} | rvol {:.5} | rate {:.1}/s | aggressive {} | paused {}",
+                self.coin,
+                state.best_bid,
+                state.best_ask,
+                state.microprice,
+                state.depth_imbalance,
+                state.trend_score,
+                state.twap,
+                state.twap_deviation,
+                state.fill_score,
+                state.volatility,
+                state.realized_vol,
+                state.update_rate,
+                state.aggressive_mode,
+                state.quoting_paused
+            ),
+            "ledger" => {
+                let ledger = self.ledger.lock().await;
+                let spreads = self.spreads.lock().await;
+                let activity = self.activity.lock().await;
+                let attribution = self.attribution.lock().await;
+                format!(
+                    "{} fills {} | hedges {} | volume ${:.2} | {}\n{} quotes {}\n{} signal hit rates {}",
+                    self.coin,
+                    ledger.fills,
+                    ledger.hedges,
+                    ledger.volume,
+                    spreads.summary(),
+                    self.coin,
+                    activity.summary(),
+                    self.coin,
+                    attribution.summary()
+                )
+            }
+            "efficiency" => format!(
+                "{} {}",
+                self.coin,
+                self.efficiency.lock().await.summary(marked_pnl(state))
+            ),
+            // `sides COIN both|bid|ask|none` until the next config reload
+            "sides" => match req.args.get(1).map(|mode| mode.parse::<QuoteSides>()) {
+                Some(Ok(sides)) => {
+                    *self.sides.lock().await = sides;
+                    info!("{} quoting sides set to {sides}", self.coin);
+                    format!("{} quoting {sides}", self.coin)
+                }
+                Some(Err(e)) => format!("{e}; sides are both, bid, ask or none"),
+                None => format!("{} quoting {}", self.coin, self.sides.lock().await),
+            },
+            "funding" => {
+                let time = state.book_history.back().map_or(0, |b| b.timestamp_ms);
+                match self.funding.lock().await.as_ref() {
+                    Some(funding) => funding.summary(time),
+                    None => format!("{} funding off (see --funding)", self.coin),
+                }
+            }
+            other => format!("unknown command {other:?}; try help"),
+        };
+        req.reply(text);
+    }
+    // Per-coin event loop: market data from this coin's subscriptions, admin
+    // commands relayed by the dispatcher and config reloads
+    pub async fn run(
+        mut self,
+        mut receiver: UnboundedReceiver<Message>,
+        mut admin: UnboundedReceiver<AdminRequest>,
+    ) {
+        let mut updates = self.config_updates.take();
+        loop {
+            tokio::select! {
+                msg = receiver.recv() => match msg {
+                    Some(msg) => self.handle(msg).await,
+                    None => break,
+                },
+                Some(req) = admin.recv() => self.handle_admin(req).await,
+                config = next_config(&mut updates) => self.reconfigure(&config).await,
+            }
+        }
+        info!("{} feed closed, stopping", self.coin);
+    }
+}
+
+// PnL is marked at the configured fair value, or the mid before one is set
+fn mark(state: &SignalState) -> f64 {
+    if state.fair_value > 0.0 {
+        state.fair_value
+    } else {
+        (state.best_bid + state.best_ask) / 2.0
+    }
+}
+
+fn marked_pnl(state: &SignalState) -> f64 {
+    state.position.quote + state.position.base * mark(state)
+}
+
+// The next config sent to a router; never resolves without an update channel
+// or once its sender is gone
+async fn next_config(updates: &mut Option<watch::Receiver<CoinConfig>>) -> CoinConfig {
+    let Some(updates) = updates else {
+        return std::future::pending().await;
+    };
+    if updates.changed().await.is_err() {
+        return std::future::pending().await;
+    }
+    updates.borrow_and_update().clone()
+}
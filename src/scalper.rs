@@ -0,0 +1,642 @@
+use log::info;
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+use crate::{
+    linear_regression_slope, price_volatility, AdaptiveCooldown, AssetSpec, EntryConfirmation,
+    Metrics, OrderBook, OrderFill, OrderOutcome, OrderRole, SignalState, SpreadRegime,
+    StatusReporter, Strategy, StrategyConfig, StrategyOrder, TifConfig, TimeInForce, TradeGovernor,
+};
+
+// Execution policy: rest passively unless the signal is strong enough to pay the spread
+pub(crate) const TAKER_CONFIDENCE: f64 = 0.8; // |fill_score| at or above this switches to IOC taking
+pub(crate) const TAKER_MAX_CROSS_BPS: f64 = 5.0; // Worst price an IOC entry may reach, in bps beyond the touch
+pub(crate) const SLOPE_SCALE: f64 = 0.01; // Slope that maps to ~0.76 trend strength via tanh
+pub(crate) const IMBALANCE_PERSIST_MS: f64 = 2_000.0; // One-sided book must hold this long to count as a volume signal
+pub(crate) const BASE_COOLDOWN_MS: u64 = 10_000; // Re-entry pause after an exit, adapted by outcome
+pub(crate) const SCALPER_MARGIN: f64 = 11.0; // USD margin per entry
+pub(crate) const SCALPER_LEVERAGE: f64 = 20.0;
+pub(crate) const TREND_SLOPE: f64 = 0.005; // Mid slope per book that counts as a trend
+pub(crate) const MID_HISTORY: usize = 40; // Books the slope and volatility are taken over
+pub(crate) const MIN_HISTORY: usize = 10; // Books needed before trading
+const SIZE_EPSILON: f64 = 1e-9; // Smaller sizes are flat
+                                // Entries wait out wide spreads unless configured otherwise
+pub(crate) const SCALPER_REGIMES: [SpreadRegime; 3] = [
+    SpreadRegime::TightDeep,
+    SpreadRegime::TightThin,
+    SpreadRegime::Normal,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExecutionMode {
+    Maker,
+    Taker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Long,
+    Short,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    direction: Direction,
+    entry_px: f64,
+    extreme: f64, // best mid since entry
+}
+
+// Directional confidence in [-1, 1] blending trend slope and book imbalance
+fn fill_score(slope: f64, imbalance: f64) -> f64 {
+    (0.6 * (slope / SLOPE_SCALE).tanh() + 0.4 * imbalance).clamp(-1.0, 1.0)
+}
+
+fn execution_mode(score: f64) -> ExecutionMode {
+    if score.abs() >= TAKER_CONFIDENCE {
+        ExecutionMode::Taker
+    } else {
+        ExecutionMode::Maker
+    }
+}
+
+// Trend-following scalper: enters in the direction of the mid's slope (or of
+// a book imbalance that persists), takes profit at the volatility-scaled
+// target and flips on a real retrace against the trend. Entries rest just
+// inside the touch unless the signal is strong enough to take with an IOC; a
+// cooldown that adapts to realized outcomes spaces them out. The entry
+// condition must hold for the configured number of consecutive books first.
+// With a governor, thin edge per trade raises the entry thresholds and
+// stretches the cooldown.
+pub struct TrendScalper {
+    mids: VecDeque<f64>,
+    entry: EntryConfirmation<Direction>,
+    position: Option<Position>,
+    pending_entry: Option<(Uuid, Position)>, // sent, not yet resting or filled
+    pending_exit: Option<Uuid>,              // sent, not yet filled or rejected
+    closing: Option<Position>,               // replaced by a flip, until the flip's fills close it
+    size: f64,                               // signed, from fills and the exchange's position
+    realized_pnl: f64,
+    book_ms: u64, // time of the latest book, which the cooldown runs on
+    cooldown: AdaptiveCooldown,
+    governor: Option<TradeGovernor>,
+    regimes: Vec<SpreadRegime>, // spread regimes entries are allowed in
+    spec: AssetSpec,            // price tick and size decimals of the traded coin
+    tif: TifConfig,             // of maker entries and exits
+    metrics: Option<Metrics>,
+    reporter: StatusReporter,
+}
+
+impl Default for TrendScalper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrendScalper {
+    pub fn new() -> Self {
+        Self {
+            mids: VecDeque::with_capacity(MID_HISTORY + 1),
+            entry: EntryConfirmation::default(),
+            position: None,
+            pending_entry: None,
+            pending_exit: None,
+            closing: None,
+            size: 0.0,
+            realized_pnl: 0.0,
+            book_ms: 0,
+            cooldown: AdaptiveCooldown::new(BASE_COOLDOWN_MS),
+            governor: None,
+            regimes: SCALPER_REGIMES.to_vec(),
+            spec: AssetSpec::default(),
+            tif: TifConfig::default(),
+            metrics: None,
+            reporter: StatusReporter::default(),
+        }
+    }
+
+    // Publish the cooldown's state, for tuning
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    // Price and size orders on the coin's exchange precision
+    pub fn with_asset_spec(mut self, spec: AssetSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    // Status lines go to `reporter` instead of a private one
+    pub fn with_reporter(mut self, reporter: StatusReporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    // Enter only once the entry condition has held for `ticks` books in a row
+    pub fn with_entry_confirmation(mut self, ticks: usize) -> Self {
+        self.entry = EntryConfirmation::new(ticks);
+        self
+    }
+
+    // Trade less often while the realized edge per round trip is thin
+    pub fn with_governor(mut self, governor: TradeGovernor) -> Self {
+        self.governor = Some(governor);
+        self
+    }
+
+    // Starts the re-entry cooldown after closing a position entered at
+    // `entry_px` for `profit` per unit
+    fn on_exit(&mut self, now_ms: u64, entry_px: f64, profit: f64) {
+        self.cooldown.on_exit(now_ms, profit);
+        if let Some(governor) = &mut self.governor {
+            if entry_px > 0.0 {
+                governor.on_round_trip(profit / entry_px * 10_000.0);
+            }
+            self.cooldown.stretch(now_ms, governor.factor());
+        }
+    }
+
+    // Sends the entry `order`; the position is taken once the exchange has
+    // filled it, or rests it as a maker order
+    fn enter(&mut self, order: StrategyOrder, direction: Direction, px: f64) -> StrategyOrder {
+        let position = Position {
+            direction,
+            entry_px: px,
+            extreme: px,
+        };
+        self.pending_entry = Some((order.cloid(), position));
+        order
+    }
+}
+
+impl Strategy for TrendScalper {
+    fn name(&self) -> &str {
+        "scalper"
+    }
+
+    fn on_book(&mut self, book: &OrderBook, state: &SignalState) -> Vec<StrategyOrder> {
+        let Some((best_bid, best_ask, bid_volume, ask_volume)) = book.top() else {
+            return Vec::new();
+        };
+        let now_ms = book.time;
+        self.book_ms = now_ms;
+        let mid_price = (best_bid + best_ask) / 2.0;
+        let spread = best_ask - best_bid;
+        let imbalance = (bid_volume - ask_volume) / (bid_volume + ask_volume);
+        self.mids.push_back(mid_price);
+        if self.mids.len() > MID_HISTORY {
+            self.mids.pop_front();
+        }
+        if self.mids.len() < MIN_HISTORY {
+            return Vec::new();
+        }
+        let recent: Vec<f64> = self.mids.iter().copied().collect();
+        let slope = linear_regression_slope(&recent);
+        let volatility = price_volatility(&recent);
+        let qty = self.spec.qty(mid_price, SCALPER_MARGIN, SCALPER_LEVERAGE);
+        let mut orders = Vec::new();
+        // Entry thresholds, raised by the governor while edge is thin
+        let strictness = self.governor.as_ref().map_or(1.0, TradeGovernor::factor);
+        let trend_slope = TREND_SLOPE * strictness;
+        let persist_ms = IMBALANCE_PERSIST_MS * strictness;
+
+        let trend_direction = if slope > trend_slope {
+            Some(Direction::Long)
+        } else if slope < -trend_slope {
+            Some(Direction::Short)
+        } else {
+            None
+        };
+        // A single lopsided snapshot is noise; only imbalance that persists predicts drift
+        let held = state.imbalance_persistence_ms;
+        let volume_direction = if held >= persist_ms {
+            Some(Direction::Long)
+        } else if held <= -persist_ms {
+            Some(Direction::Short)
+        } else {
+            None
+        };
+        let direction = trend_direction.or(volume_direction);
+
+        // Take profit, or flip on a retrace against the trend, unless an exit or
+        // flip is already on its way
+        let settling = self.pending_exit.is_some() || self.pending_entry.is_some();
+        if let Some(mut position) = self.position.filter(|_| !settling) {
+            let (profit, retrace) = match position.direction {
+                Direction::Long => {
+                    position.extreme = position.extreme.max(mid_price);
+                    (mid_price - position.entry_px, position.extreme - mid_price)
+                }
+                Direction::Short => {
+                    position.extreme = position.extreme.min(mid_price);
+                    (position.entry_px - mid_price, mid_price - position.extreme)
+                }
+            };
+            self.position = Some(position);
+            let long = position.direction == Direction::Long;
+            // The whole position, once fills have shown it
+            let held = if self.size.abs() > SIZE_EPSILON {
+                self.spec.round_size(self.size.abs())
+            } else {
+                qty
+            };
+            let reversed = if long {
+                slope < -TREND_SLOPE
+            } else {
+                slope > TREND_SLOPE
+            };
+            if profit > state.profit_target {
+                // Lock profits once the move clears the volatility-scaled target.
+                // The position closes, and the profit is booked, as the exit fills.
+                let exit_px = self
+                    .spec
+                    .round_price(if long { best_bid } else { best_ask });
+                let tif = self.tif.for_role(OrderRole::Exit);
+                let exit = StrategyOrder::limit(!long, exit_px, held, tif).reduce_only();
+                self.pending_exit = Some(exit.cloid());
+                orders.push(exit);
+            } else if reversed && retrace >= state.reversal_threshold {
+                // A slope against us after a real pullback from the extreme
+                // indicates the market might reverse: close and open the other
+                // way in one order
+                let (px, flipped, entry) = if long {
+                    (best_bid - 1.0, Direction::Short, best_bid)
+                } else {
+                    (best_ask + 1.0, Direction::Long, best_ask)
+                };
+                let px = self.spec.round_price(px);
+                let tif = self.tif.for_role(OrderRole::Entry);
+                let order = StrategyOrder::limit(!long, px, held + qty, tif);
+                orders.push(self.enter(order, flipped, entry));
+            }
+        }
+
+        // With no position open, enter with the trend once the cooldown allows
+        // and the condition has been confirmed
+        let can_enter = self.cooldown.ready(now_ms);
+        if let Some(metrics) = &self.metrics {
+            self.cooldown.export(metrics, now_ms);
+            if let Some(governor) = &self.governor {
+                governor.export(metrics);
+            }
+        }
+        let confident = slope.abs() > 0.004 * strictness && volatility < 20.0;
+        let tradable = self.regimes.contains(&state.spread_regime);
+        let condition = direction.filter(|_| confident && tradable);
+        let confirmed = self.entry.update(condition);
+        let idle = self.position.is_none() && self.pending_entry.is_none();
+        if let (true, true, Some(direction)) = (idle, can_enter, confirmed) {
+            let score = fill_score(slope, imbalance);
+            let mode = execution_mode(score);
+            // Taker mode crosses the spread with an IOC capped at TAKER_MAX_CROSS_BPS;
+            // maker mode keeps resting just inside the touch.
+            let cross = TAKER_MAX_CROSS_BPS / 10_000.0;
+            let tif = match mode {
+                ExecutionMode::Taker => TimeInForce::Ioc,
+                ExecutionMode::Maker => self.tif.for_role(OrderRole::Entry),
+            };
+            let long = direction == Direction::Long;
+            let limit_px = self.spec.round_price(match (mode, long) {
+                (ExecutionMode::Taker, true) => best_ask * (1.0 + cross),
+                (ExecutionMode::Taker, false) => best_bid * (1.0 - cross),
+                (ExecutionMode::Maker, true) => best_ask - 1.00,
+                (ExecutionMode::Maker, false) => best_bid + 1.00,
+            });
+            info!(
+                "{} IT mode: {mode:?}, score: {score:.2}, price: {limit_px:?}, qty: {qty:?}",
+                if long { "LONG" } else { "SHORT" }
+            );
+            let entry = if long { best_ask } else { best_bid };
+            let order = StrategyOrder::limit(long, limit_px, qty, tif);
+            orders.push(self.enter(order, direction, entry));
+        }
+
+        let position = match &self.position {
+            Some(p) => format!(
+                "{} @ {:.2}",
+                if p.direction == Direction::Long {
+                    "LONG"
+                } else {
+                    "SHORT"
+                },
+                p.entry_px
+            ),
+            None => "NONE".to_string(),
+        };
+        let status = format!(
+            "{} Mid: {:.2} | Spread: {:.4} | Slope: {:.5} | Pos: {} ({:.4}) | Total PnL: {:.4} | Cooldown: {}ms",
+            chrono::Utc::now().format("%H:%M:%S%.3f"),
+            mid_price,
+            spread,
+            slope,
+            position,
+            self.size,
+            self.realized_pnl,
+            self.cooldown.remaining_ms(now_ms)
+        );
+        self.reporter.report("scalper", status);
+        orders
+    }
+
+    fn configure(&mut self, config: &StrategyConfig) {
+        if config.entry_confirm_ticks != self.entry.ticks {
+            self.entry = EntryConfirmation::new(config.entry_confirm_ticks);
+        }
+        if !config.regimes.is_empty() {
+            self.regimes = config.regimes.clone();
+        }
+        self.tif = config.tif;
+    }
+
+    // The slope and entry confirmation restart after a gap in the books
+    fn on_feed(&mut self, connected: bool) -> Vec<StrategyOrder> {
+        if !connected {
+            self.mids.clear();
+            self.entry = EntryConfirmation::new(self.entry.ticks);
+        }
+        Vec::new()
+    }
+
+    // An IOC entry that could not match leaves no position behind. An exit
+    // that was refused, or only partly filled, is sent again on the next book.
+    fn on_outcome(&mut self, cloid: Uuid, outcome: &OrderOutcome) {
+        if self.pending_exit == Some(cloid) {
+            match outcome {
+                OrderOutcome::Filled { .. } => self.pending_exit = None,
+                OrderOutcome::Rejected(e) => {
+                    info!("Exit not taken: {e}");
+                    self.pending_exit = None;
+                }
+                OrderOutcome::Resting { .. } | OrderOutcome::Pending => {}
+            }
+            return;
+        }
+        let Some((pending, position)) = self.pending_entry else {
+            return;
+        };
+        if pending != cloid {
+            return;
+        }
+        self.pending_entry = None;
+        match outcome {
+            OrderOutcome::Filled { .. } | OrderOutcome::Resting { .. } => {
+                // A flip's fills close the position it replaces first
+                self.closing = self.position.replace(position);
+            }
+            OrderOutcome::Rejected(e) => info!("Entry not taken: {e}"),
+            OrderOutcome::Pending => {}
+        }
+    }
+
+    // Fills against the position book its profit; once they close it, the
+    // re-entry cooldown starts
+    fn on_fill(&mut self, fill: &OrderFill) {
+        let side = if fill.is_buy { "BUY" } else { "SELL" };
+        info!(
+            "Filled {side} {} @ {:.2} ({:?})",
+            fill.sz, fill.px, fill.status
+        );
+        let before = self.size;
+        self.size += if fill.is_buy { fill.sz } else { -fill.sz };
+        if before.abs() <= SIZE_EPSILON || (before > 0.0) == fill.is_buy {
+            return;
+        }
+        let held = if before > 0.0 {
+            Direction::Long
+        } else {
+            Direction::Short
+        };
+        let Some(closed) = self
+            .closing
+            .or(self.position)
+            .filter(|p| p.direction == held)
+        else {
+            return;
+        };
+        let profit = match held {
+            Direction::Long => fill.px - closed.entry_px,
+            Direction::Short => closed.entry_px - fill.px,
+        };
+        self.realized_pnl += profit * fill.sz.min(before.abs());
+        if fill.sz + SIZE_EPSILON >= before.abs() {
+            if self.closing.take().is_none() {
+                self.position = None;
+            }
+            self.pending_exit = None;
+            self.on_exit(self.book_ms, closed.entry_px, profit);
+        }
+    }
+
+    fn on_position(&mut self, size: f64, entry_px: Option<f64>) {
+        self.size = size;
+        self.closing = None;
+        if size == 0.0 {
+            self.pending_exit = None;
+        }
+        let entry = entry_px.or(self.mids.back().copied()).unwrap_or(0.0);
+        let direction = if size > 0.0 {
+            Direction::Long
+        } else {
+            Direction::Short
+        };
+        self.position = match self.position.take() {
+            _ if size == 0.0 => None,
+            // Same side: keep the extreme, take the exchange's entry
+            Some(p) if p.direction == direction => Some(Position {
+                entry_px: entry,
+                ..p
+            }),
+            _ => Some(Position {
+                direction,
+                entry_px: entry,
+                extreme: entry,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BookLevel, OrderStatus};
+
+    fn book(time: u64, bid: f64) -> OrderBook {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        let mut book = OrderBook::new();
+        assert!(book.apply_snapshot(time, &[level(bid)], &[level(bid + 1.0)]));
+        book
+    }
+
+    fn fill(is_buy: bool, px: f64, sz: f64) -> OrderFill {
+        OrderFill {
+            cloid: Uuid::new_v4(),
+            coin: "BTC".to_string(),
+            is_buy,
+            px,
+            sz,
+            time_ms: 0,
+            status: OrderStatus::Filled,
+        }
+    }
+
+    #[test]
+    fn test_enters_with_the_trend_once_history_is_full() {
+        let mut scalper = TrendScalper::new();
+        let mut state = SignalState {
+            profit_target: 5.0,
+            reversal_threshold: 5.0,
+            ..Default::default()
+        };
+        let mut orders = Vec::new();
+        for i in 0..MIN_HISTORY as u64 {
+            orders.extend(scalper.on_book(&book(i * 100, 100.0 + i as f64), &state));
+        }
+        // A steady climb without a strong enough score rests inside the ask
+        let [StrategyOrder::Place {
+            is_buy: true,
+            px,
+            sz,
+            tif: TimeInForce::Gtc,
+            ..
+        }] = orders[..]
+        else {
+            panic!("expected a resting bid, got {orders:?}");
+        };
+        assert_eq!(px, 109.0);
+        // No second entry while the first is waiting on the exchange or open
+        assert!(scalper.on_book(&book(950, 109.5), &state).is_empty());
+        scalper.on_outcome(orders[0].cloid(), &OrderOutcome::Resting { oid: 1 });
+        scalper.on_fill(&fill(true, 109.0, sz));
+        assert!(scalper.on_book(&book(1_000, 110.0), &state).is_empty());
+        // Profit past the target sells the whole position, reduce-only
+        state.profit_target = 1.0;
+        let exit = scalper.on_book(&book(1_100, 111.0), &state);
+        let [StrategyOrder::Place {
+            is_buy: false,
+            px: 111.0,
+            sz: exit_sz,
+            reduce_only: true,
+            ..
+        }] = exit[..]
+        else {
+            panic!("expected a reduce-only sell, got {exit:?}");
+        };
+        assert_eq!(exit_sz, sz);
+        // Nothing else goes out, or is booked, until the exit fills
+        assert!(scalper.on_book(&book(1_150, 111.0), &state).is_empty());
+        assert!(scalper.position.is_some());
+        assert_eq!(scalper.realized_pnl, 0.0);
+        let done = OrderOutcome::Filled {
+            oid: 2,
+            size: sz,
+            avg_px: 111.0,
+        };
+        scalper.on_outcome(exit[0].cloid(), &done);
+        scalper.on_fill(&fill(false, 111.0, sz));
+        assert!(scalper.position.is_none());
+        assert!((scalper.realized_pnl - sz).abs() < 1e-9);
+        // And the cooldown holds off re-entry
+        assert!(scalper.on_book(&book(1_200, 112.0), &state).is_empty());
+    }
+
+    #[test]
+    fn test_flip_closes_the_short_and_buys_at_the_ask() {
+        let mut scalper = TrendScalper::new();
+        let state = SignalState {
+            profit_target: 100.0,
+            reversal_threshold: 2.0,
+            ..Default::default()
+        };
+        let mut orders = Vec::new();
+        for i in 0..MIN_HISTORY as u64 {
+            orders.extend(scalper.on_book(&book(i * 100, 200.0 - i as f64), &state));
+        }
+        let [StrategyOrder::Place {
+            is_buy: false, sz, ..
+        }] = orders[..]
+        else {
+            panic!("expected a resting offer, got {orders:?}");
+        };
+        scalper.on_outcome(orders[0].cloid(), &OrderOutcome::Resting { oid: 1 });
+        scalper.on_fill(&fill(false, 192.0, sz));
+
+        // The market turns up until the slope and the retrace both flip it
+        let (mut time, mut bid) = (1_000, 191.0);
+        let flip = loop {
+            bid += 2.0;
+            time += 100;
+            let flip = scalper.on_book(&book(time, bid), &state);
+            if !flip.is_empty() || bid > 260.0 {
+                break flip;
+            }
+        };
+        let [StrategyOrder::Place {
+            is_buy: true,
+            px,
+            sz: flip_sz,
+            reduce_only: false,
+            ..
+        }] = flip[..]
+        else {
+            panic!("expected a flip to long, got {flip:?}");
+        };
+        assert_eq!(px, bid + 2.0);
+        // The short it closes, plus a new entry's size
+        let qty = AssetSpec::default().qty(bid + 0.5, SCALPER_MARGIN, SCALPER_LEVERAGE);
+        assert!((flip_sz - (sz + qty)).abs() < 1e-9);
+
+        let done = OrderOutcome::Filled {
+            oid: 2,
+            size: flip_sz,
+            avg_px: px,
+        };
+        scalper.on_outcome(flip[0].cloid(), &done);
+        scalper.on_fill(&fill(true, px, flip_sz));
+        let position = scalper.position.expect("flipped long");
+        assert_eq!(position.direction, Direction::Long);
+        assert!((scalper.size - qty).abs() < 1e-9);
+        assert!(scalper.realized_pnl < 0.0);
+    }
+
+    #[test]
+    fn test_unmatched_entry_leaves_no_position() {
+        let mut scalper = TrendScalper::new();
+        let state = SignalState::default();
+        let mut orders = Vec::new();
+        for i in 0..MIN_HISTORY as u64 {
+            orders.extend(scalper.on_book(&book(i * 100, 100.0 + i as f64), &state));
+        }
+        let [entry] = &orders[..] else {
+            panic!("expected an entry, got {orders:?}");
+        };
+        let error = "Order could not immediately match against any resting orders.";
+        scalper.on_outcome(entry.cloid(), &OrderOutcome::Rejected(error.to_string()));
+        // Nothing was opened, so the next book enters again
+        let again = scalper.on_book(&book(1_000, 110.0), &state);
+        assert!(matches!(
+            again[..],
+            [StrategyOrder::Place { is_buy: true, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_entry_waits_for_the_confirmation_ticks() {
+        let mut scalper = TrendScalper::new().with_entry_confirmation(3);
+        let state = SignalState::default();
+        let mut entered_at = None;
+        for i in 0..MIN_HISTORY as u64 + 5 {
+            if !scalper
+                .on_book(&book(i * 100, 100.0 + i as f64), &state)
+                .is_empty()
+            {
+                entered_at.get_or_insert(i);
+            }
+        }
+        // The trend reads from the tenth book on and has to hold for three
+        assert_eq!(entered_at, Some(MIN_HISTORY as u64 + 1));
+    }
+}
@@ -0,0 +1,160 @@
+use log::error;
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::{fs, path::Path};
+
+use crate::{prelude::*, Error, QuoteProposal, SignalState, Strategy, StrategyConfig};
+
+// Runaway scripts (e.g. an accidental infinite loop) are cut off after this many
+// operations instead of stalling the book handler
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+
+// A Rhai script deciding the quote side from the signal state. Every numeric
+// SignalState field is in scope under its own name (plus `position`, `mid` and
+// `spread`). The script's value is either a number, used as the new fill_score,
+// or one of "buy", "sell", "both" (aggressive two-sided) or "none".
+pub struct SignalScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl SignalScript {
+    pub fn compile(source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        let ast = engine
+            .compile(source)
+            .map_err(|e| Error::Script(e.to_string()))?;
+        Ok(Self { engine, ast })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let source = fs::read_to_string(path).map_err(|e| Error::Script(e.to_string()))?;
+        Self::compile(&source)
+    }
+
+    // Runs the script and writes its decision into `state.fill_score` and
+    // `state.aggressive_mode`
+    pub fn apply(&self, state: &mut SignalState) -> Result<()> {
+        let mut scope = Scope::new();
+        scope.push("trend_score", state.trend_score);
+        scope.push("twap", state.twap);
+        scope.push("sliding_signal", state.sliding_signal);
+        scope.push("normalized_slide", state.normalized_slide);
+        scope.push("fill_score", state.fill_score);
+        scope.push("twap_deviation", state.twap_deviation);
+        scope.push("best_bid", state.best_bid);
+        scope.push("best_ask", state.best_ask);
+        scope.push("mid", (state.best_bid + state.best_ask) / 2.0);
+        scope.push("spread", state.best_ask - state.best_bid);
+        scope.push("volatility", state.volatility);
+        scope.push("realized_vol", state.realized_vol);
+        scope.push("update_rate", state.update_rate);
+        scope.push("aggressive_mode", state.aggressive_mode);
+        scope.push("position", state.position.base);
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| Error::Script(e.to_string()))?;
+
+        if let Ok(score) = value.as_float() {
+            state.fill_score = score;
+            state.aggressive_mode = false;
+            return Ok(());
+        }
+        if let Ok(score) = value.as_int() {
+            state.fill_score = score as f64;
+            state.aggressive_mode = false;
+            return Ok(());
+        }
+        let side = value.into_string().map_err(|t| {
+            Error::Script(format!("script returned {t}, expected a number or side"))
+        })?;
+        let (fill_score, aggressive) = match side.to_lowercase().as_str() {
+            "buy" => (1.0, false),
+            "sell" => (-1.0, false),
+            "both" => (0.0, true),
+            "none" => (0.0, false),
+            other => return Err(Error::Script(format!("unknown side {other:?}"))),
+        };
+        state.fill_score = fill_score;
+        state.aggressive_mode = aggressive;
+        Ok(())
+    }
+}
+
+// Wraps a strategy so the script decides the side before the inner strategy prices
+// the quotes. A failing script quotes nothing for that update.
+pub struct ScriptedStrategy {
+    name: String,
+    script: SignalScript,
+    inner: Box<dyn Strategy>,
+}
+
+impl ScriptedStrategy {
+    pub fn new(script: SignalScript, inner: Box<dyn Strategy>) -> Self {
+        Self {
+            name: format!("{}+script", inner.name()),
+            script,
+            inner,
+        }
+    }
+}
+
+impl Strategy for ScriptedStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn quote(&mut self, state: &SignalState) -> Vec<QuoteProposal> {
+        let mut scripted = state.clone();
+        if let Err(e) = self.script.apply(&mut scripted) {
+            error!("Signal script failed, not quoting: {e}");
+            return vec![];
+        }
+        self.inner.quote(&scripted)
+    }
+
+    fn configure(&mut self, config: &StrategyConfig) {
+        self.inner.configure(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuoteLayerManager;
+
+    #[test]
+    fn test_signal_script() {
+        let mut state = SignalState {
+            best_bid: 100.0,
+            best_ask: 101.0,
+            trend_score: 2.0,
+            twap_deviation: -0.01,
+            ..Default::default()
+        };
+        let script = SignalScript::compile("0.5 * trend_score + 10.0 * twap_deviation").unwrap();
+        script.apply(&mut state).unwrap();
+        assert!((state.fill_score - 0.9).abs() < 1e-12);
+
+        let sides = r#"if spread > 2.0 { "none" } else if position > 1 { "sell" } else { "both" }"#;
+        let mut strategy = ScriptedStrategy::new(
+            SignalScript::compile(sides).unwrap(),
+            Box::new(QuoteLayerManager::new()),
+        );
+        assert_eq!(strategy.name(), "layered+script");
+        assert_eq!(strategy.quote(&state).len(), 2);
+
+        let runaway = SignalScript::compile("loop {}").unwrap();
+        assert!(runaway.apply(&mut state).is_err());
+        assert!(SignalScript::compile("let x = ").is_err());
+        let bad_side = SignalScript::compile(r#""sideways""#).unwrap();
+        assert!(bad_side.apply(&mut state).is_err());
+
+        let example = concat!(env!("CARGO_MANIFEST_DIR"), "/scripts/signal_blend.rhai");
+        SignalScript::from_file(example)
+            .unwrap()
+            .apply(&mut state)
+            .unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    JournalRecord, MarketEvent, OrderBook, PriceLadder, SignalEngine, SignalState, TradeSample,
+};
+
+const VIEW_DEPTH: usize = 5;
+
+// One book tick of a recorded session: the book and signals after it, the
+// trades since the previous tick and the bot's journal entries for it
+#[derive(Debug, Clone)]
+pub struct SessionFrame {
+    pub index: usize, // book ticks replayed before this one
+    pub time: u64,
+    pub book: OrderBook,
+    pub signals: SignalState,
+    pub trades: Vec<TradeSample>,
+    pub decisions: Vec<JournalRecord>,
+}
+
+// Journal entries are placed on the book they reacted to (`book_time`), or by
+// wall-clock time for entries that don't carry one, such as hedges
+fn decision_time(record: &JournalRecord) -> u64 {
+    record.data["book_time"].as_u64().unwrap_or(record.time)
+}
+
+// Steps a recorded session (market data plus the run's journal) forward one
+// book tick at a time, replaying the signal engine so each frame shows what the
+// bot saw when it decided. Seeking backwards replays from the start.
+pub struct SessionViewer {
+    coin: String,
+    events: Vec<MarketEvent>,
+    decisions: Vec<JournalRecord>, // sorted by decision time
+    next_event: usize,
+    next_decision: usize,
+    frames: usize,
+    engine: SignalEngine,
+    book: OrderBook,
+    ladder: PriceLadder,
+}
+
+impl SessionViewer {
+    // `events` are this coin's market data; journal entries for other coins and
+    // the manifest are dropped
+    pub fn new(coin: &str, events: Vec<MarketEvent>, journal: Vec<JournalRecord>) -> Self {
+        let mut decisions: Vec<_> = journal
+            .into_iter()
+            .filter(|r| r.kind != "manifest" && r.data["coin"] == coin)
+            .collect();
+        decisions.sort_by_key(decision_time);
+        Self {
+            coin: coin.to_string(),
+            events,
+            decisions,
+            next_event: 0,
+            next_decision: 0,
+            frames: 0,
+            engine: SignalEngine::new(),
+            book: OrderBook::new(),
+            ladder: PriceLadder::new(VIEW_DEPTH, VIEW_DEPTH),
+        }
+    }
+
+    pub fn decision_count(&self) -> usize {
+        self.decisions.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.next_event = 0;
+        self.next_decision = 0;
+        self.frames = 0;
+        self.engine = SignalEngine::new();
+        self.book = OrderBook::new();
+        self.ladder = PriceLadder::new(VIEW_DEPTH, VIEW_DEPTH);
+    }
+
+    // Replays up to and including the next usable book; None at the end
+    pub fn next_frame(&mut self) -> Option<SessionFrame> {
+        let mut trades = Vec::new();
+        while let Some(event) = self.events.get(self.next_event) {
+            self.next_event += 1;
+            match event {
+                MarketEvent::Trade {
+                    time,
+                    px,
+                    sz,
+                    is_buy,
+                } => {
+                    self.engine.process_trade(*px, *sz, *is_buy, *time);
+                    self.ladder.on_trade(*time, *px, *sz, *is_buy);
+                    trades.push(TradeSample {
+                        price: *px,
+                        size: *sz,
+                        is_buy: *is_buy,
+                        timestamp_ms: *time,
+                    });
+                }
+                MarketEvent::Book { time, bids, asks } => {
+                    if !self.book.apply_snapshot(*time, bids, asks) {
+                        continue;
+                    }
+                    self.engine.process_book(&self.book);
+                    let start = self.next_decision;
+                    while self
+                        .decisions
+                        .get(self.next_decision)
+                        .is_some_and(|d| decision_time(d) <= *time)
+                    {
+                        self.next_decision += 1;
+                    }
+                    let frame = SessionFrame {
+                        index: self.frames,
+                        time: *time,
+                        book: self.book.clone(),
+                        signals: self.engine.state.clone(),
+                        trades,
+                        decisions: self.decisions[start..self.next_decision].to_vec(),
+                    };
+                    self.frames += 1;
+                    return Some(frame);
+                }
+            }
+        }
+        None
+    }
+
+    // First frame at or after `time`
+    pub fn seek(&mut self, time: u64) -> Option<SessionFrame> {
+        if self.book.time >= time {
+            self.reset();
+        }
+        std::iter::from_fn(|| self.next_frame()).find(|f| f.time >= time)
+    }
+
+    // Frames from `from` to `to` (inclusive), at most `limit` of them
+    pub fn window(&mut self, from: u64, to: u64, limit: usize) -> Vec<SessionFrame> {
+        let Some(first) = self.seek(from) else {
+            return Vec::new();
+        };
+        std::iter::once(first)
+            .chain(std::iter::from_fn(|| self.next_frame()))
+            .take_while(|f| f.time <= to)
+            .take(limit)
+            .collect()
+    }
+
+    // Next frame where the bot did something
+    pub fn next_decision(&mut self) -> Option<SessionFrame> {
+        std::iter::from_fn(|| self.next_frame()).find(|f| !f.decisions.is_empty())
+    }
+
+    // Ladder with the frame's quoted or filled prices marked as ours, then the
+    // signals and the raw journal entries
+    pub fn render(&self, frame: &SessionFrame) -> String {
+        let ours: Vec<_> = frame
+            .decisions
+            .iter()
+            .filter_map(|d| {
+                let px = d.data["px"].as_f64()?;
+                let size = d.data["size"].as_f64()?;
+                let is_buy = d.data["is_buy"]
+                    .as_bool()
+                    .unwrap_or(d.data["side"] == "Buy");
+                Some((is_buy, px, size))
+            })
+            .collect();
+        let s = &frame.signals;
+        let mut lines = vec![
+            format!("#{} {} @ {}", frame.index, self.coin, frame.time),
+            self.ladder.render(&self.coin, &frame.book, &ours),
+            format!(
+                "signals: micro {:.2} | depth imb {:.2} | trend {:.3} | twap {:.2} (dev {:.4}) | fill score {:.2} | vol {:.2} | aggressive {} | paused {}",
+                s.microprice,
+                s.depth_imbalance,
+                s.trend_score,
+                s.twap,
+                s.twap_deviation,
+                s.fill_score,
+                s.volatility,
+                s.aggressive_mode,
+                s.quoting_paused
+            ),
+        ];
+        if !frame.trades.is_empty() {
+            lines.push(format!("{} trades since last book", frame.trades.len()));
+        }
+        for d in &frame.decisions {
+            lines.push(format!("decision {}: {}", d.kind, d.data));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BookLevel;
+    use serde_json::{json, Value};
+
+    fn book(time: u64, bid: f64) -> MarketEvent {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "1".to_string(),
+            n: 1,
+        };
+        MarketEvent::Book {
+            time,
+            bids: vec![level(bid)],
+            asks: vec![level(bid + 1.0)],
+        }
+    }
+
+    fn record(kind: &str, time: u64, data: Value) -> JournalRecord {
+        JournalRecord {
+            run_id: "run".to_string(),
+            time,
+            kind: kind.to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_steps_through_books_with_their_decisions() {
+        let events = vec![
+            book(1, 100.0),
+            MarketEvent::Trade {
+                time: 2,
+                px: 101.0,
+                sz: 0.5,
+                is_buy: true,
+            },
+            book(3, 101.0),
+            book(5, 102.0),
+        ];
+        let journal = vec![
+            record("manifest", 0, json!({})),
+            record(
+                "fill",
+                99,
+                json!({"coin": "BTC", "book_time": 3, "side": "Buy", "size": 1.0, "px": 101.5}),
+            ),
+            record("fill", 99, json!({"coin": "ETH", "book_time": 3})),
+            record(
+                "hedge",
+                4,
+                json!({"coin": "BTC", "is_buy": false, "size": 0.5, "px": 102.0}),
+            ),
+        ];
+        let mut viewer = SessionViewer::new("BTC", events, journal);
+        assert_eq!(viewer.decision_count(), 2);
+
+        let first = viewer.next_frame().unwrap();
+        assert!(first.decisions.is_empty() && first.trades.is_empty());
+        let second = viewer.next_decision().unwrap();
+        assert_eq!((second.index, second.time), (1, 3));
+        assert_eq!(second.trades.len(), 1);
+        assert_eq!(second.decisions[0].kind, "fill");
+        assert!(viewer.render(&second).contains("*101.5"));
+        // The hedge has no book time; it lands on the first book after it
+        let third = viewer.next_frame().unwrap();
+        assert_eq!(third.decisions[0].kind, "hedge");
+        assert!(viewer.next_frame().is_none());
+
+        // Seeking backwards replays from the start
+        let again = viewer.seek(2).unwrap();
+        assert_eq!((again.index, again.decisions.len()), (1, 1));
+    }
+}
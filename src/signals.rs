@@ -0,0 +1,1035 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, str::FromStr};
+
+use crate::{
+    prelude::*, BookLevel, BookSample, Error, OrderBook, Position, StatusReporter, TradeSample,
+};
+
+// Parameters for signal windows and thresholds
+pub(crate) const TWAP_WINDOW: usize = 120;
+pub(crate) const TRADE_WINDOW: usize = 80;
+pub(crate) const DEVIATION_THRESHOLD: f64 = 0.002;
+// Volatility-burst circuit: pause quoting when the short horizon gets too hot
+pub(crate) const BURST_WINDOW_MS: u64 = 5_000; // Short horizon for realized vol and update rate
+pub(crate) const BURST_VOL_TRIP: f64 = 0.0015; // Realized vol (log returns) that trips the circuit
+pub(crate) const BURST_VOL_RESUME: f64 = 0.0008; // Realized vol must fall below this to resume
+pub(crate) const BURST_RATE_TRIP: f64 = 8.0; // Book updates per second that trip the circuit
+pub(crate) const BURST_RATE_RESUME: f64 = 4.0; // Update rate must fall below this to resume
+pub(crate) const BURST_COOLDOWN_MS: u64 = 3_000; // Conditions must stay calm this long before resuming
+pub(crate) const FEED_GAP_TRIP_MS: u64 = BURST_WINDOW_MS; // A silent feed this long trips the circuit
+                                                          // Exit targets scale with price and volatility instead of fixed price offsets
+pub(crate) const ATR_WINDOW: usize = 14; // Book updates averaged into the tick ATR
+pub(crate) const DEFAULT_PROFIT_TARGET_BPS: f64 = 5.0;
+pub(crate) const DEFAULT_REVERSAL_ATR: f64 = 3.0;
+pub(crate) const DEFAULT_DECAY_HALF_LIFE_MS: f64 = 8_000.0; // Trade-flow weighting
+pub(crate) const IMBALANCE_THRESHOLD: f64 = 0.2; // |imbalance| above this counts as one-sided
+pub(crate) const DEPTH_LEVELS: usize = 5; // Levels per side in the depth-weighted imbalance
+pub(crate) const ENTRY_CONFIRM_TICKS: usize = 1; // Book updates an entry condition must hold; 1 fires on the first
+                                                 // Spread regimes: spreads in price units, depth as the thinner side's shown size
+pub(crate) const TIGHT_SPREAD: f64 = 2.0; // At or under this the spread is tight
+pub(crate) const WIDE_SPREAD: f64 = 5.0; // At or over this the spread is wide
+pub(crate) const DEEP_SIZE: f64 = 1.0; // A tight book showing this much on both sides is deep
+pub(crate) const MIN_KALMAN_VARIANCE: f64 = 1e-12; // Keeps the fair-value filter's gain defined on a flat book
+
+// State holding recent history and signals
+#[derive(Debug, Default, Clone)]
+pub struct SignalState {
+    pub book_history: VecDeque<BookSample>,
+    pub trade_history: VecDeque<TradeSample>,
+    pub trend_score: f64,
+    pub twap: f64,
+    pub sliding_signal: f64,
+    pub normalized_slide: f64,
+    pub fill_score: f64,
+    pub twap_deviation: f64,
+    pub mean_revert_signal: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub volatility: f64,
+    pub aggressive_mode: bool,
+    pub realized_vol: f64,             // short-horizon realized volatility
+    pub update_rate: f64,              // book updates per second over the burst window
+    pub quoting_paused: bool,          // set by the volatility-burst circuit
+    pub position: Position,            // track current inventory
+    pub atr: f64,                      // mean absolute mid change per update over ATR_WINDOW
+    pub profit_target: f64,            // favourable move (price units) that takes profit
+    pub reversal_threshold: f64, // retrace from the best price (price units) that flips a position
+    pub imbalance: f64,          // (bid_vol - ask_vol) / total on the latest book
+    pub imbalance_persistence_ms: f64, // how long imbalance has stayed one-sided; > 0 bid-heavy, < 0 ask-heavy
+    pub vpin: f64, // mean |buy - sell| / bucket volume over completed volume buckets
+    pub microprice: f64, // size-weighted touch price; set by `process_book`, 0.0 before
+    pub depth_imbalance: f64, // level-weighted imbalance over DEPTH_LEVELS; set by `process_book`
+    pub fair_value: f64, // reference price chosen by `SignalWindows::fair_value`; 0.0 before the first book
+    pub spread_regime: SpreadRegime, // spread and depth class of the latest book
+    pub funding_bias: f64, // lean toward the side earning funding; > 0 favours longs, set by `set_funding_bias`
+}
+
+// How good the venue is to trade right now, from the spread and the size
+// shown around it. Strategies list the regimes they trade in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadRegime {
+    TightDeep, // tight spread, both sides at least `deep_size`
+    TightThin, // tight spread, a thin side
+    #[default]
+    Normal, // between tight and wide
+    Wide,      // too wide to trade on
+}
+
+impl SpreadRegime {
+    pub fn classify(spread: f64, depth: f64, thresholds: &RegimeThresholds) -> Self {
+        if spread >= thresholds.wide_spread {
+            SpreadRegime::Wide
+        } else if spread > thresholds.tight_spread {
+            SpreadRegime::Normal
+        } else if depth >= thresholds.deep_size {
+            SpreadRegime::TightDeep
+        } else {
+            SpreadRegime::TightThin
+        }
+    }
+
+    pub fn is_tight(&self) -> bool {
+        matches!(self, SpreadRegime::TightDeep | SpreadRegime::TightThin)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegimeThresholds {
+    pub tight_spread: f64, // price units
+    pub wide_spread: f64,  // price units
+    pub deep_size: f64,    // base units on the thinner side
+}
+
+impl Default for RegimeThresholds {
+    fn default() -> Self {
+        Self {
+            tight_spread: TIGHT_SPREAD,
+            wide_spread: WIDE_SPREAD,
+            deep_size: DEEP_SIZE,
+        }
+    }
+}
+
+// The reference price signals, quotes and PnL marks are taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FairValue {
+    #[default]
+    Mid, // (bid + ask) / 2
+    WeightedMid, // mean of each side's size-weighted price over DEPTH_LEVELS
+    Microprice,  // touch prices weighted by the opposite side's size
+    Kalman,      // mid filtered by touch noise, drifting with the tick ATR
+    Oracle,      // the exchange's oracle price, the mid until one arrives
+}
+
+impl FromStr for FairValue {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mid" => Ok(FairValue::Mid),
+            "weighted_mid" => Ok(FairValue::WeightedMid),
+            "microprice" => Ok(FairValue::Microprice),
+            "kalman" => Ok(FairValue::Kalman),
+            "oracle" => Ok(FairValue::Oracle),
+            _ => Err(Error::GenericParse(format!("bad fair value {s:?}"))),
+        }
+    }
+}
+
+// A price distance expressed relative to the market rather than in raw price units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceOffset {
+    Bps(f64), // basis points of mid
+    Atr(f64), // multiples of the tick ATR
+}
+
+impl PriceOffset {
+    pub fn resolve(&self, mid: f64, atr: f64) -> f64 {
+        match *self {
+            PriceOffset::Bps(bps) => mid * bps / 10_000.0,
+            PriceOffset::Atr(mult) => atr * mult,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitTargets {
+    pub profit: PriceOffset,
+    pub reversal: PriceOffset,
+}
+
+impl Default for ExitTargets {
+    fn default() -> Self {
+        Self {
+            profit: PriceOffset::Bps(DEFAULT_PROFIT_TARGET_BPS),
+            reversal: PriceOffset::Atr(DEFAULT_REVERSAL_ATR),
+        }
+    }
+}
+
+// History lengths, the mean-reversion threshold, the burst circuit's calm
+// period, the fair value and the spread regimes; `StrategyConfig` sets them
+// per coin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalWindows {
+    pub twap: usize,   // book updates averaged into the TWAP
+    pub trades: usize, // trades kept for the flow signals
+    pub deviation_threshold: f64,
+    pub burst_cooldown_ms: u64,
+    pub fair_value: FairValue,
+    pub regime: RegimeThresholds,
+}
+
+impl Default for SignalWindows {
+    fn default() -> Self {
+        Self {
+            twap: TWAP_WINDOW,
+            trades: TRADE_WINDOW,
+            deviation_threshold: DEVIATION_THRESHOLD,
+            burst_cooldown_ms: BURST_COOLDOWN_MS,
+            fair_value: FairValue::Mid,
+            regime: RegimeThresholds::default(),
+        }
+    }
+}
+
+// Best bid/ask prices and total resting size per side. None for a one-sided,
+// crossed or unparseable book, which must never reach the signal engine.
+pub fn top_of_book(bids: &[BookLevel], asks: &[BookLevel]) -> Option<(f64, f64, f64, f64)> {
+    let bid_px = bids.first()?.px.parse::<f64>().ok()?;
+    let ask_px = asks.first()?.px.parse::<f64>().ok()?;
+    if !(bid_px > 0.0 && ask_px > bid_px && ask_px.is_finite()) {
+        return None;
+    }
+    let bid_vol = bids.iter().map(|x| x.sz.parse().unwrap_or(0.0)).sum();
+    let ask_vol = asks.iter().map(|x| x.sz.parse().unwrap_or(0.0)).sum();
+    Some((bid_px, ask_px, bid_vol, ask_vol))
+}
+
+// Compute standard deviation of mid-prices
+pub fn compute_volatility(history: &VecDeque<BookSample>) -> f64 {
+    let mids: Vec<f64> = history.iter().map(|s| s.mid_price).collect();
+    price_volatility(&mids)
+}
+
+// Population standard deviation of a price series (0.0 for fewer than two prices)
+pub fn price_volatility(prices: &[f64]) -> f64 {
+    let n = prices.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let mean = prices.iter().sum::<f64>() / n as f64;
+    let var = prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n as f64;
+    var.sqrt()
+}
+
+// Least-squares slope of a series against its index, i.e. price change per sample
+pub fn linear_regression_slope(data: &[f64]) -> f64 {
+    let n = data.len() as f64;
+    let sum_x: f64 = (0..data.len()).map(|x| x as f64).sum();
+    let sum_y: f64 = data.iter().sum();
+    let sum_xy: f64 = data.iter().enumerate().map(|(x, y)| x as f64 * y).sum();
+    let sum_x2: f64 = (0..data.len()).map(|x| (x as f64).powi(2)).sum();
+
+    let numerator = n * sum_xy - sum_x * sum_y;
+    let denominator = n * sum_x2 - sum_x.powi(2);
+    if denominator.abs() < 1e-8 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+// Percent change of the mid over the last `window` samples (0.0 for fewer than three)
+pub fn percent_change(history: &VecDeque<BookSample>, window: usize) -> f64 {
+    if history.len() < 3 {
+        return 0.0;
+    }
+    let recent: Vec<_> = history.iter().rev().take(window).collect();
+    let (Some(last), Some(first)) = (recent.first(), recent.last()) else {
+        return 0.0;
+    };
+    (last.mid_price - first.mid_price) / first.mid_price * 100.0
+}
+
+// Realized volatility (root of summed squared log returns) over samples newer than
+// `since_ms`. Not demeaned, so a one-directional crash counts as volatile.
+pub fn compute_realized_vol(history: &VecDeque<BookSample>, since_ms: u64) -> f64 {
+    let mids: Vec<f64> = history
+        .iter()
+        .filter(|s| s.timestamp_ms >= since_ms && s.mid_price > 0.0)
+        .map(|s| s.mid_price)
+        .collect();
+    if mids.len() < 3 {
+        return 0.0;
+    }
+    mids.windows(2)
+        .map(|w| (w[1] / w[0]).ln().powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// === Volatility-burst circuit ===
+// Trips when realized vol or the book update rate spikes past the trip thresholds,
+// or when the feed resumes after a gap, and only resets once both have stayed under
+// the (lower) resume thresholds for `cooldown_ms` (BURST_COOLDOWN_MS by default),
+// so quoting does not flap on the edge of a burst.
+#[derive(Debug, Default, Clone)]
+pub struct BurstCircuit {
+    pub tripped: bool,
+    pub calm_since_ms: Option<u64>,
+    update_times: VecDeque<u64>, // book update timestamps inside the burst window
+    last_gap_ms: u64,            // time since the previous book update
+}
+
+impl BurstCircuit {
+    // Record a book update and return the update rate (per second) over the window
+    pub fn record_update(&mut self, ts: u64) -> f64 {
+        self.last_gap_ms = self
+            .update_times
+            .back()
+            .map_or(0, |&last| ts.saturating_sub(last));
+        self.update_times.push_back(ts);
+        let since = ts.saturating_sub(BURST_WINDOW_MS);
+        while self.update_times.front().is_some_and(|&t| t < since) {
+            self.update_times.pop_front();
+        }
+        self.update_times.len() as f64 / (BURST_WINDOW_MS as f64 / 1000.0)
+    }
+
+    // Number of update timestamps currently held for the rate window
+    pub fn tracked_updates(&self) -> usize {
+        self.update_times.len()
+    }
+
+    pub fn update(
+        &mut self,
+        ts: u64,
+        realized_vol: f64,
+        update_rate: f64,
+        cooldown_ms: u64,
+    ) -> bool {
+        let feed_gap = self.last_gap_ms > FEED_GAP_TRIP_MS;
+        if realized_vol > BURST_VOL_TRIP || update_rate > BURST_RATE_TRIP || feed_gap {
+            if !self.tripped {
+                info!(
+                    "[Circuit] Quoting paused: realized vol {:.5}, update rate {:.1}/s, feed gap {}ms",
+                    realized_vol, update_rate, self.last_gap_ms
+                );
+            }
+            self.tripped = true;
+            self.calm_since_ms = None;
+        } else if self.tripped {
+            if realized_vol < BURST_VOL_RESUME && update_rate < BURST_RATE_RESUME {
+                let calm_since = *self.calm_since_ms.get_or_insert(ts);
+                if ts.saturating_sub(calm_since) >= cooldown_ms {
+                    info!("[Circuit] Quoting resumed after calm period");
+                    self.tripped = false;
+                    self.calm_since_ms = None;
+                }
+            } else {
+                self.calm_since_ms = None;
+            }
+        }
+        self.tripped
+    }
+}
+
+// === Entry confirmation ===
+// An entry condition has to hold, pointing the same way, for `ticks`
+// consecutive book updates before it fires, so a single noisy tick across a
+// threshold does not open a position. A tick without the condition, or with it
+// pointing the other way, restarts the count.
+#[derive(Debug, Clone)]
+pub struct EntryConfirmation<T> {
+    pub ticks: usize,
+    held: Option<(T, usize)>,
+}
+
+impl<T: Copy + PartialEq> Default for EntryConfirmation<T> {
+    fn default() -> Self {
+        Self::new(ENTRY_CONFIRM_TICKS)
+    }
+}
+
+impl<T: Copy + PartialEq> EntryConfirmation<T> {
+    pub fn new(ticks: usize) -> Self {
+        Self {
+            ticks: ticks.max(1),
+            held: None,
+        }
+    }
+
+    // Feed this update's condition; returns it once it has held `ticks` updates in a row
+    pub fn update(&mut self, condition: Option<T>) -> Option<T> {
+        self.held = condition.map(|c| match self.held {
+            Some((held, n)) if held == c => (c, n + 1),
+            _ => (c, 1),
+        });
+        self.held
+            .filter(|(_, n)| *n >= self.ticks)
+            .map(|(condition, _)| condition)
+    }
+
+    // Consecutive updates the current condition has held for
+    pub fn streak(&self) -> usize {
+        self.held.map_or(0, |(_, n)| n)
+    }
+}
+
+// Weight of a trade by its age in the decayed flow slide. Parses from config as
+// `exp:HALF_LIFE_MS`, `linear:WINDOW_MS` or `power:SCALE_MS:EXPONENT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecayKernel {
+    Exponential { half_life_ms: f64 },         // halves every half-life
+    Linear { window_ms: f64 },                 // falls to zero at the window edge
+    PowerLaw { scale_ms: f64, exponent: f64 }, // (1 + age/scale)^-exponent, long memory
+}
+
+impl Default for DecayKernel {
+    fn default() -> Self {
+        DecayKernel::Exponential {
+            half_life_ms: DEFAULT_DECAY_HALF_LIFE_MS,
+        }
+    }
+}
+
+impl DecayKernel {
+    pub fn weight(&self, age_ms: f64) -> f64 {
+        let age = age_ms.max(0.0);
+        match *self {
+            DecayKernel::Exponential { half_life_ms } => 0.5f64.powf(age / half_life_ms),
+            DecayKernel::Linear { window_ms } => (1.0 - age / window_ms).max(0.0),
+            DecayKernel::PowerLaw { scale_ms, exponent } => (1.0 + age / scale_ms).powf(-exponent),
+        }
+    }
+}
+
+impl FromStr for DecayKernel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bad = || Error::GenericParse(format!("bad decay kernel {s:?}"));
+        let parts: Vec<&str> = s.split(':').collect();
+        let num = |i: usize| -> Result<f64> {
+            parts
+                .get(i)
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .ok_or_else(bad)
+        };
+        match (parts[0], parts.len()) {
+            ("exp", 2) => Ok(DecayKernel::Exponential {
+                half_life_ms: num(1)?,
+            }),
+            ("linear", 2) => Ok(DecayKernel::Linear { window_ms: num(1)? }),
+            ("power", 3) => Ok(DecayKernel::PowerLaw {
+                scale_ms: num(1)?,
+                exponent: num(2)?,
+            }),
+            _ => Err(bad()),
+        }
+    }
+}
+
+// Where `sliding_signal` / `normalized_slide` come from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowMeasure {
+    DecayedSlide(DecayKernel), // trades weighted by age
+    VolumeBuckets { bucket_volume: f64, buckets: usize }, // VPIN-style, clocked by volume
+}
+
+impl Default for FlowMeasure {
+    fn default() -> Self {
+        FlowMeasure::DecayedSlide(DecayKernel::default())
+    }
+}
+
+// Trades grouped into equal-volume buckets, so a burst of prints fills many
+// buckets quickly instead of being squashed into a few milliseconds of decay.
+// Trades that straddle a boundary are split across buckets.
+#[derive(Debug, Clone)]
+pub struct VolumeBuckets {
+    bucket_volume: f64,
+    max_buckets: usize,
+    current: (f64, f64),             // (buy, sell) volume in the open bucket
+    completed: VecDeque<(f64, f64)>, // newest last
+}
+
+impl VolumeBuckets {
+    pub fn new(bucket_volume: f64, max_buckets: usize) -> Self {
+        Self {
+            bucket_volume,
+            max_buckets: max_buckets.max(1),
+            current: (0.0, 0.0),
+            completed: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, size: f64, is_buy: bool) {
+        if self.bucket_volume <= 0.0 {
+            return;
+        }
+        let mut left = size;
+        while left > 0.0 {
+            let room = self.bucket_volume - self.current.0 - self.current.1;
+            let take = left.min(room);
+            if is_buy {
+                self.current.0 += take;
+            } else {
+                self.current.1 += take;
+            }
+            left -= take;
+            if take >= room - 1e-12 {
+                self.completed.push_back(self.current);
+                self.current = (0.0, 0.0);
+                if self.completed.len() > self.max_buckets {
+                    self.completed.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.len()
+    }
+
+    // Net signed (buy - sell) volume over the completed buckets
+    pub fn net(&self) -> f64 {
+        self.completed.iter().map(|(b, s)| b - s).sum()
+    }
+
+    // Signed imbalance in [-1, 1] over the completed buckets
+    pub fn imbalance(&self) -> f64 {
+        if self.completed.is_empty() {
+            return 0.0;
+        }
+        self.net() / (self.completed.len() as f64 * self.bucket_volume)
+    }
+
+    pub fn vpin(&self) -> f64 {
+        if self.completed.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self.completed.iter().map(|(b, s)| (b - s).abs()).sum();
+        total / (self.completed.len() as f64 * self.bucket_volume)
+    }
+}
+
+// Core signal processing engine
+#[derive(Debug, Default)]
+pub struct SignalEngine {
+    pub state: SignalState,
+    pub circuit: BurstCircuit,
+    pub exit_targets: ExitTargets,
+    windows: SignalWindows,
+    imbalance_run: Option<(bool, u64)>, // (bid-heavy, since ts) of the current one-sided stretch
+    buckets: Option<VolumeBuckets>,     // set when FlowMeasure::VolumeBuckets is selected
+    decay: DecayKernel,
+    kalman: Option<(f64, f64)>, // (estimate, variance) of the Kalman fair value
+    oracle_px: Option<f64>,     // latest oracle price from the asset context feed
+    depth_fair_value: Option<f64>, // weighted mid or microprice of the book being processed
+}
+
+impl SignalEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_exit_targets(mut self, exit_targets: ExitTargets) -> Self {
+        self.exit_targets = exit_targets;
+        self
+    }
+
+    pub fn with_windows(mut self, windows: SignalWindows) -> Self {
+        self.windows = windows;
+        self
+    }
+
+    // Swaps the windows on a running engine. Shorter windows drop the oldest
+    // history now; longer ones fill up as data arrives.
+    pub fn set_windows(&mut self, windows: SignalWindows) {
+        self.windows = windows;
+        let books = self.state.book_history.len().saturating_sub(windows.twap);
+        self.state.book_history.drain(..books);
+        let trades = self
+            .state
+            .trade_history
+            .len()
+            .saturating_sub(windows.trades);
+        self.state.trade_history.drain(..trades);
+    }
+
+    pub fn with_flow_measure(mut self, flow: FlowMeasure) -> Self {
+        match flow {
+            FlowMeasure::DecayedSlide(kernel) => {
+                self.decay = kernel;
+                self.buckets = None;
+            }
+            FlowMeasure::VolumeBuckets {
+                bucket_volume,
+                buckets,
+            } => self.buckets = Some(VolumeBuckets::new(bucket_volume, buckets)),
+        }
+        self
+    }
+
+    // The oracle price for `FairValue::Oracle`
+    pub fn set_oracle_price(&mut self, px: f64) {
+        if px.is_finite() && px > 0.0 {
+            self.oracle_px = Some(px);
+        }
+    }
+
+    // Funding bias from `FundingMonitor::bias`, added to the flow pressure so
+    // the fill score needs less flow to quote the side that earns funding
+    pub fn set_funding_bias(&mut self, bias: f64) {
+        if bias.is_finite() {
+            self.state.funding_bias = bias.clamp(-1.0, 1.0);
+        }
+    }
+
+    // Process a full-depth book: the top-of-book signals plus microprice and
+    // depth-weighted imbalance. Empty or crossed books are ignored.
+    pub fn process_book(&mut self, book: &OrderBook) {
+        let Some((bid_px, ask_px, bid_vol, ask_vol)) = book.top() else {
+            return;
+        };
+        self.depth_fair_value = match self.windows.fair_value {
+            FairValue::WeightedMid => book.weighted_mid(DEPTH_LEVELS),
+            FairValue::Microprice => book.microprice(),
+            _ => None,
+        };
+        self.process_l2_book(book.time, bid_px, ask_px, bid_vol, ask_vol);
+        self.state.microprice = book.microprice().unwrap_or((bid_px + ask_px) / 2.0);
+        self.state.depth_imbalance = book.weighted_imbalance(DEPTH_LEVELS).unwrap_or(0.0);
+    }
+
+    // Process each order-book update
+    pub fn process_l2_book(
+        &mut self,
+        ts: u64,
+        bid_px: f64,
+        ask_px: f64,
+        bid_vol: f64,
+        ask_vol: f64,
+    ) {
+        // Add new book sample
+        let mid = (bid_px + ask_px) / 2.0;
+        self.state.book_history.push_back(BookSample {
+            timestamp_ms: ts,
+            mid_price: mid,
+            best_bid: bid_px,
+            best_ask: ask_px,
+            bid_volume: bid_vol,
+            ask_volume: ask_vol,
+        });
+        if self.state.book_history.len() > self.windows.twap {
+            self.state.book_history.pop_front();
+        }
+        // Update best prices
+
+        self.state.best_bid = bid_px;
+        self.state.best_ask = ask_px;
+        // Compute signals, measuring deviation and exits from the fair value:
+        let current_spread = ask_px - bid_px;
+        self.state.atr = compute_tick_atr(&self.state.book_history);
+        let fair = self.fair_value(mid, current_spread, bid_vol, ask_vol);
+        self.state.fair_value = fair;
+        self.state.trend_score = compute_momentum(&self.state.book_history);
+        self.state.twap = compute_twap(&self.state.book_history);
+        self.state.twap_deviation = compute_twap_deviation(fair, self.state.twap);
+        self.state.mean_revert_signal =
+            interpret_mean_reversion(self.state.twap_deviation, self.windows.deviation_threshold);
+        self.state.volatility = compute_volatility(&self.state.book_history);
+        self.state.profit_target = self.exit_targets.profit.resolve(fair, self.state.atr);
+        self.state.reversal_threshold = self.exit_targets.reversal.resolve(fair, self.state.atr);
+        // Classify the venue; aggressive mode needs a tight market & low vol
+        self.state.spread_regime =
+            SpreadRegime::classify(current_spread, bid_vol.min(ask_vol), &self.windows.regime);
+        self.state.aggressive_mode =
+            self.state.spread_regime.is_tight() && self.state.volatility < 10.0;
+        // Short-horizon burst detection (can pause quoting outright)
+        let since = ts.saturating_sub(BURST_WINDOW_MS);
+        self.state.realized_vol = compute_realized_vol(&self.state.book_history, since);
+        self.state.update_rate = self.circuit.record_update(ts);
+        self.state.quoting_paused = self.circuit.update(
+            ts,
+            self.state.realized_vol,
+            self.state.update_rate,
+            self.windows.burst_cooldown_ms,
+        );
+        // Book imbalance and how long it has held on one side
+        let total_vol = bid_vol + ask_vol;
+        self.state.imbalance = if total_vol > 0.0 {
+            (bid_vol - ask_vol) / total_vol
+        } else {
+            0.0
+        };
+        self.state.imbalance_persistence_ms = self.track_imbalance_run(ts);
+        // Compute order-flow imbalance (decay-weighted or volume-bucketed)
+        let (slide, norm) = match &self.buckets {
+            Some(buckets) => {
+                self.state.vpin = buckets.vpin();
+                (buckets.net(), buckets.imbalance())
+            }
+            None => compute_decay_weighted_slide(&self.state.trade_history, ts, &self.decay),
+        };
+        self.state.sliding_signal = slide;
+        self.state.normalized_slide = norm;
+        // Combine signals into final directional fill_score
+        let trend_strength = self.state.trend_score.tanh();
+        let micro_pressure = self.state.normalized_slide + self.state.funding_bias;
+        self.state.fill_score = if trend_strength.abs() > 0.1 {
+            trend_strength.signum()
+        } else if micro_pressure.abs() > 0.4 {
+            micro_pressure.signum()
+        } else {
+            0.0
+        };
+    }
+
+    // The configured reference price for this book. Without full depth the
+    // microprice weights the touch by the sides' total sizes and the weighted
+    // mid falls back to the mid.
+    fn fair_value(&mut self, mid: f64, spread: f64, bid_vol: f64, ask_vol: f64) -> f64 {
+        let depth = self.depth_fair_value.take();
+        match self.windows.fair_value {
+            FairValue::Mid => mid,
+            FairValue::WeightedMid => depth.unwrap_or(mid),
+            FairValue::Microprice => depth.unwrap_or_else(|| {
+                let total = bid_vol + ask_vol;
+                if total > 0.0 {
+                    mid + spread / 2.0 * (bid_vol - ask_vol) / total
+                } else {
+                    mid
+                }
+            }),
+            FairValue::Kalman => {
+                // The mid is observed with touch noise, (spread / 2)², while
+                // the true price drifts by about one ATR per update
+                let noise = (spread / 2.0).powi(2).max(MIN_KALMAN_VARIANCE);
+                let (estimate, variance) = match self.kalman {
+                    Some((x, p)) => {
+                        let p = p + self.state.atr.powi(2).max(MIN_KALMAN_VARIANCE);
+                        let gain = p / (p + noise);
+                        (x + gain * (mid - x), (1.0 - gain) * p)
+                    }
+                    None => (mid, noise),
+                };
+                self.kalman = Some((estimate, variance));
+                estimate
+            }
+            FairValue::Oracle => self.oracle_px.unwrap_or(mid),
+        }
+    }
+
+    // Signed duration of the current one-sided imbalance stretch, 0.0 when balanced
+    fn track_imbalance_run(&mut self, ts: u64) -> f64 {
+        let imbalance = self.state.imbalance;
+        if imbalance.abs() <= IMBALANCE_THRESHOLD {
+            self.imbalance_run = None;
+            return 0.0;
+        }
+        let bid_heavy = imbalance > 0.0;
+        let since = match self.imbalance_run {
+            Some((side, since)) if side == bid_heavy => since,
+            _ => ts,
+        };
+        self.imbalance_run = Some((bid_heavy, since));
+        let held = ts.saturating_sub(since) as f64;
+        if bid_heavy {
+            held
+        } else {
+            -held
+        }
+    }
+
+    // Process trade executions for trade flow
+    pub fn process_trade(&mut self, price: f64, size: f64, is_buy: bool, ts: u64) {
+        self.state.trade_history.push_back(TradeSample {
+            price,
+            size,
+            is_buy,
+            timestamp_ms: ts,
+        });
+        if self.state.trade_history.len() > self.windows.trades {
+            self.state.trade_history.pop_front();
+        }
+        if let Some(buckets) = &mut self.buckets {
+            buckets.add(size, is_buy);
+        }
+    }
+
+    // One-line signal summary
+    pub fn summary(&self) -> String {
+        let s = &self.state;
+        format!(
+            "Trend: {:.3} | TWAP: {:.2} | Slide: {:.3} | NormSlide: {:.3} | FillScore: {:.2} | Dev: {:.4} | Vol: {:.2} | Aggro: {} | RVol: {:.5} | Rate: {:.1}/s | Paused: {} | ImbHeld: {:.1}s",
+            s.trend_score, s.twap, s.sliding_signal, s.normalized_slide,
+            s.fill_score, s.twap_deviation, s.volatility, s.aggressive_mode,
+            s.realized_vol, s.update_rate, s.quoting_paused,
+            s.imbalance_persistence_ms / 1000.0
+        )
+    }
+
+    // Hand the summary to a throttled reporter under `key` (e.g. the coin)
+    pub fn report(&self, reporter: &StatusReporter, key: &str) {
+        reporter.report(key, self.summary());
+    }
+}
+
+// === Signal computation helpers ===
+fn compute_momentum(hist: &VecDeque<BookSample>) -> f64 {
+    if hist.len() < 2 {
+        return 0.0;
+    }
+    // Sum of last-10 price changes
+    let recent: Vec<_> = hist.iter().rev().take(10).collect();
+    recent
+        .windows(2)
+        .map(|w| w[0].mid_price - w[1].mid_price)
+        .sum()
+}
+
+fn compute_tick_atr(hist: &VecDeque<BookSample>) -> f64 {
+    let recent: Vec<f64> = hist
+        .iter()
+        .rev()
+        .take(ATR_WINDOW + 1)
+        .map(|b| b.mid_price)
+        .collect();
+    if recent.len() < 2 {
+        return 0.0;
+    }
+    recent.windows(2).map(|w| (w[0] - w[1]).abs()).sum::<f64>() / (recent.len() - 1) as f64
+}
+
+// The history is already trimmed to the TWAP window
+fn compute_twap(hist: &VecDeque<BookSample>) -> f64 {
+    if hist.is_empty() {
+        return 0.0;
+    }
+    hist.iter().map(|b| b.mid_price).sum::<f64>() / hist.len() as f64
+}
+
+fn compute_decay_weighted_slide(
+    trades: &VecDeque<TradeSample>,
+    now: u64,
+    kernel: &DecayKernel,
+) -> (f64, f64) {
+    let mut weighted_net = 0.0;
+    let mut weighted_total = 0.0;
+    for trade in trades {
+        let weight = kernel.weight(now as f64 - trade.timestamp_ms as f64);
+        let signed = if trade.is_buy { 1.0 } else { -1.0 };
+        weighted_net += signed * trade.size * weight;
+        weighted_total += trade.size * weight;
+    }
+    let norm = if weighted_total > 1e-6 {
+        weighted_net / weighted_total
+    } else {
+        0.0
+    };
+    (weighted_net, norm)
+}
+
+fn compute_twap_deviation(p: f64, t: f64) -> f64 {
+    if t.abs() < 1e-6 {
+        0.0
+    } else {
+        (p - t) / t
+    }
+}
+
+fn interpret_mean_reversion(d: f64, threshold: f64) -> String {
+    if d > threshold {
+        "Fade breakout".into()
+    } else if d < -threshold {
+        "Scalp retracement".into()
+    } else {
+        "Neutral".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_targets_scale_with_market() {
+        let mut engine = SignalEngine::new().with_exit_targets(ExitTargets {
+            profit: PriceOffset::Bps(10.0),
+            reversal: PriceOffset::Atr(2.0),
+        });
+        // Mid alternates 1000.0 / 1001.0, so every update moves it by 1.0
+        for i in 0..20u64 {
+            let bid = 999.5 + (i % 2) as f64;
+            engine.process_l2_book(i * 100, bid, bid + 1.0, 1.0, 1.0);
+        }
+        let s = &engine.state;
+        assert!((s.atr - 1.0).abs() < 1e-9);
+        assert!((s.reversal_threshold - 2.0).abs() < 1e-9);
+        assert!((s.profit_target - 1.001).abs() < 1e-9); // 10 bps of the last mid (1001.0)
+    }
+
+    #[test]
+    fn test_imbalance_persistence_resets_on_flip() {
+        let mut engine = SignalEngine::new();
+        for ts in (0..=3_000).step_by(500) {
+            engine.process_l2_book(ts, 100.0, 101.0, 8.0, 2.0);
+        }
+        assert_eq!(engine.state.imbalance_persistence_ms, 3_000.0);
+
+        // A balanced book ends the stretch
+        engine.process_l2_book(3_500, 100.0, 101.0, 5.0, 5.0);
+        assert_eq!(engine.state.imbalance_persistence_ms, 0.0);
+
+        engine.process_l2_book(4_000, 100.0, 101.0, 8.0, 2.0);
+        engine.process_l2_book(4_500, 100.0, 101.0, 1.0, 9.0);
+        engine.process_l2_book(5_500, 100.0, 101.0, 1.0, 9.0);
+        assert_eq!(engine.state.imbalance_persistence_ms, -1_000.0);
+    }
+
+    #[test]
+    fn test_volume_buckets_split_trades() {
+        let mut buckets = VolumeBuckets::new(10.0, 3);
+        buckets.add(25.0, true); // two full buy buckets, 5 carried over
+        buckets.add(5.0, false); // closes the third bucket at 5/5
+        assert_eq!(buckets.completed(), 3);
+        assert!((buckets.net() - 20.0).abs() < 1e-9);
+        assert!((buckets.vpin() - 2.0 / 3.0).abs() < 1e-9);
+
+        // Oldest bucket rolls off once the window is full
+        buckets.add(10.0, false);
+        assert_eq!(buckets.completed(), 3);
+        assert!((buckets.imbalance() - 0.0).abs() < 1e-9);
+
+        let mut engine = SignalEngine::new().with_flow_measure(FlowMeasure::VolumeBuckets {
+            bucket_volume: 1.0,
+            buckets: 4,
+        });
+        for ts in 0..4 {
+            engine.process_trade(100.0, 1.0, true, ts);
+        }
+        engine.process_l2_book(10, 100.0, 101.0, 1.0, 1.0);
+        assert_eq!(engine.state.normalized_slide, 1.0);
+        assert_eq!(engine.state.vpin, 1.0);
+    }
+
+    #[test]
+    fn test_decay_kernels() {
+        let exp: DecayKernel = "exp:1000".parse().unwrap();
+        assert_eq!(exp.weight(0.0), 1.0);
+        assert!((exp.weight(1_000.0) - 0.5).abs() < 1e-12);
+        assert!((exp.weight(3_000.0) - 0.125).abs() < 1e-12);
+
+        let linear: DecayKernel = "linear:2000".parse().unwrap();
+        assert!((linear.weight(500.0) - 0.75).abs() < 1e-12);
+        assert_eq!(linear.weight(2_000.0), 0.0);
+        assert_eq!(linear.weight(5_000.0), 0.0);
+
+        let power: DecayKernel = "power:1000:2".parse().unwrap();
+        assert!((power.weight(1_000.0) - 0.25).abs() < 1e-12);
+        // Heavier tail than exponential with the same value at one scale
+        assert!(power.weight(10_000.0) > 0.5f64.powf(10_000.0 / 1_000.0 * 2.0));
+
+        // Future timestamps (clock skew) count as brand new
+        assert_eq!(exp.weight(-50.0), 1.0);
+        for bad in ["exp", "exp:-1", "linear:x", "power:1000", "cubic:10"] {
+            assert!(bad.parse::<DecayKernel>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_decayed_slide_uses_kernel() {
+        let mut engine =
+            SignalEngine::new().with_flow_measure(FlowMeasure::DecayedSlide(DecayKernel::Linear {
+                window_ms: 1_000.0,
+            }));
+        engine.process_trade(100.0, 4.0, true, 0); // expired by ts 1000
+        engine.process_trade(100.0, 1.0, false, 500); // weight 0.5
+        engine.process_l2_book(1_000, 100.0, 101.0, 1.0, 1.0);
+        assert!((engine.state.sliding_signal + 0.5).abs() < 1e-12);
+        assert_eq!(engine.state.normalized_slide, -1.0);
+    }
+
+    #[test]
+    fn test_fair_value_choices() {
+        let fair_value = |choice: &str, oracle: Option<f64>| {
+            let windows = SignalWindows {
+                fair_value: choice.parse().unwrap(),
+                ..Default::default()
+            };
+            let mut engine = SignalEngine::new().with_windows(windows);
+            if let Some(px) = oracle {
+                engine.set_oracle_price(px);
+            }
+            engine.process_l2_book(0, 100.0, 101.0, 3.0, 1.0);
+            engine
+        };
+        assert_eq!(fair_value("mid", None).state.fair_value, 100.5);
+        assert_eq!(fair_value("weighted_mid", None).state.fair_value, 100.5);
+        // Bid-heavy: half the spread times the imbalance above the mid
+        assert_eq!(fair_value("microprice", None).state.fair_value, 100.75);
+        assert_eq!(fair_value("oracle", None).state.fair_value, 100.5);
+        let oracle = fair_value("oracle", Some(99.5));
+        assert_eq!(oracle.state.fair_value, 99.5);
+        // Deviation is measured from the fair value
+        assert!((oracle.state.twap_deviation - (99.5 - 100.5) / 100.5).abs() < 1e-12);
+        assert!("vwap".parse::<FairValue>().is_err());
+
+        // The filter follows a jump part of the way, then catches up
+        let mut kalman = fair_value("kalman", None);
+        assert_eq!(kalman.state.fair_value, 100.5);
+        kalman.process_l2_book(100, 110.0, 111.0, 1.0, 1.0);
+        let first = kalman.state.fair_value;
+        assert!(first > 100.5 && first < 110.5);
+        kalman.process_l2_book(200, 110.0, 111.0, 1.0, 1.0);
+        assert!(kalman.state.fair_value > first && kalman.state.fair_value < 110.5);
+    }
+
+    #[test]
+    fn test_spread_regimes() {
+        let thresholds = RegimeThresholds::default();
+        let classify = |spread, depth| SpreadRegime::classify(spread, depth, &thresholds);
+        assert_eq!(classify(1.0, 3.0), SpreadRegime::TightDeep);
+        assert_eq!(classify(2.0, 0.5), SpreadRegime::TightThin);
+        assert_eq!(classify(3.0, 3.0), SpreadRegime::Normal);
+        assert_eq!(classify(5.0, 3.0), SpreadRegime::Wide);
+
+        // Aggressive mode follows the tight regimes, with the thinner side as depth
+        let mut engine = SignalEngine::new();
+        engine.process_l2_book(0, 100.0, 101.0, 4.0, 0.5);
+        assert_eq!(engine.state.spread_regime, SpreadRegime::TightThin);
+        assert!(engine.state.aggressive_mode);
+        engine.process_l2_book(100, 100.0, 106.0, 4.0, 4.0);
+        assert_eq!(engine.state.spread_regime, SpreadRegime::Wide);
+        assert!(!engine.state.aggressive_mode);
+    }
+
+    #[test]
+    fn test_trend_and_volatility_helpers() {
+        assert!((linear_regression_slope(&[1.0, 3.0, 5.0, 7.0]) - 2.0).abs() < 1e-12);
+        assert_eq!(linear_regression_slope(&[4.0]), 0.0);
+        assert!((price_volatility(&[1.0, 3.0]) - 1.0).abs() < 1e-12);
+        assert_eq!(price_volatility(&[]), 0.0);
+
+        let history: VecDeque<BookSample> = [100.0, 101.0, 102.0, 104.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &mid)| BookSample {
+                timestamp_ms: i as u64,
+                mid_price: mid,
+                best_bid: mid - 0.5,
+                best_ask: mid + 0.5,
+                bid_volume: 1.0,
+                ask_volume: 1.0,
+            })
+            .collect();
+        assert!((percent_change(&history, 3) - 100.0 * 3.0 / 101.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,401 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+use tokio::sync::mpsc::unbounded_channel;
+use uuid::Uuid;
+
+use crate::{
+    hedge_order, limit_order,
+    signals::{TRADE_WINDOW, TWAP_WINDOW},
+    top_of_book, ClientCancelRequestCloid, Executor, MarketEvent, Message, OrderIntent,
+    OrderManager, OrderOutcome, OrderStatus, PaperConfig, PaperExchange, QuoteLayerManager,
+    RiskManager, SignalEngine, TimeInForce, UserData, EPSILON,
+};
+
+// Stop recording violations past this many; the count keeps going
+const MAX_REPORTED_VIOLATIONS: usize = 20;
+// Update timestamps kept by the burst circuit; far above any sane book rate
+const MAX_TRACKED_UPDATES: usize = 10_000;
+// Orders the manager may hold open at once; quotes are replaced every book
+const MAX_OPEN_ORDERS: usize = 100;
+const COIN: &str = "SOAK";
+
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    pub max_position: f64,
+    pub loops: usize, // replay the recording this many times, shifted forward in time
+    pub latency_ms: u64, // market time between sending an intent and the paper exchange acting on it
+}
+
+// Independent record of the paper exchange's fills, straight from its fill
+// messages, checked against the exchange's position and the bot's own
+#[derive(Debug, Default)]
+struct FillLedger {
+    last_tid: u64,
+    fills: u64,
+    base: f64,
+    quote: f64,
+    turnover: f64, // traded notional, which the quote's rounding error grows with
+}
+
+impl FillLedger {
+    fn on_message(&mut self, msg: &Message) {
+        let Message::User(user) = msg else {
+            return;
+        };
+        let UserData::Fills(fills) = &user.data else {
+            return;
+        };
+        for fill in fills {
+            // The paper exchange numbers its fills in order
+            if fill.tid <= self.last_tid {
+                continue;
+            }
+            self.last_tid = fill.tid;
+            let (Ok(px), Ok(sz)) = (fill.px.parse::<f64>(), fill.sz.parse::<f64>()) else {
+                continue;
+            };
+            let signed = if fill.side == "B" { sz } else { -sz };
+            self.base += signed;
+            self.quote -= signed * px;
+            self.turnover += sz * px;
+            self.fills += 1;
+        }
+    }
+}
+
+// An intent on its way to the paper exchange, with the cloid of the order it
+// places
+struct InFlight {
+    due: u64,
+    intent: OrderIntent,
+    cloid: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SoakReport {
+    pub ticks: u64,
+    pub rejected_books: u64,
+    pub orders: u64,
+    pub fills: u64,
+    pub partial_fills: u64, // fills that left their order open
+    pub hedges: u64,
+    pub simulated_ms: u64,
+    pub max_abs_position: f64,
+    pub max_open_orders: usize,
+    pub max_book_history: usize,
+    pub max_trade_history: usize,
+    pub violation_count: u64,
+    pub violations: Vec<String>,
+}
+
+impl SoakReport {
+    pub fn passed(&self) -> bool {
+        self.violation_count == 0
+    }
+
+    fn violation(&mut self, msg: String) {
+        self.violation_count += 1;
+        if self.violations.len() < MAX_REPORTED_VIOLATIONS {
+            self.violations.push(msg);
+        }
+    }
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "soak {} | ticks {} (rejected {}) | orders {} | fills {} (partial {}) | hedges {} | max |pos| {:.3} | max open orders {} | max history books {} trades {} | violations {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.ticks,
+            self.rejected_books,
+            self.orders,
+            self.fills,
+            self.partial_fills,
+            self.hedges,
+            self.max_abs_position,
+            self.max_open_orders,
+            self.max_book_history,
+            self.max_trade_history,
+            self.violation_count
+        )?;
+        for v in &self.violations {
+            write!(f, "\n    violation: {v}")?;
+        }
+        Ok(())
+    }
+}
+
+// `event` moved `offset` later, for replaying a recording back to back
+fn shifted(event: &MarketEvent, offset: u64) -> MarketEvent {
+    match event.clone() {
+        MarketEvent::Book { time, bids, asks } => MarketEvent::Book {
+            time: time + offset,
+            bids,
+            asks,
+        },
+        MarketEvent::Trade {
+            time,
+            px,
+            sz,
+            is_buy,
+        } => MarketEvent::Trade {
+            time: time + offset,
+            px,
+            sz,
+            is_buy,
+        },
+    }
+}
+
+fn close(a: f64, b: f64, scale: f64) -> bool {
+    (a - b).abs() <= EPSILON.max(1e-12 * scale)
+}
+
+// Runs the quoting stack over `events` (one coin) `config.loops` times against
+// a `PaperExchange` behind an `Executor`, and checks the invariants after every
+// book tick. Intents reach the paper exchange `config.latency_ms` of market
+// time after they are sent, so quotes rest, fill in parts and are cancelled
+// while the book moves on; an `OrderManager` tracks them from the exchange's
+// responses and messages, and the bot's position comes from its fills.
+pub async fn run_soak(events: &[MarketEvent], config: &SoakConfig) -> SoakReport {
+    let mut engine = SignalEngine::new();
+    let risk = RiskManager::new(config.max_position);
+    let (tx, mut published) = unbounded_channel();
+    let paper = Arc::new(
+        PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(tx),
+    );
+    let executor = Executor::with_backend(paper.clone());
+    let orders = OrderManager::new();
+    let mut fills = orders.subscribe_fills();
+    let mut ledger = FillLedger::default();
+    let mut in_flight: VecDeque<InFlight> = VecDeque::new();
+    let mut cancelling = HashSet::new();
+    let mut report = SoakReport::default();
+    let (Some(first), Some(last)) = (events.first(), events.last()) else {
+        return report;
+    };
+    let span = last.time().saturating_sub(first.time()) + 1;
+    let mut last_time = 0;
+
+    for lap in 0..config.loops as u64 {
+        let offset = lap * span;
+        for event in events {
+            let event = shifted(event, offset);
+            let time = event.time();
+            if time < last_time {
+                report.violation(format!("time went backwards: {time} < {last_time}"));
+            }
+            last_time = time;
+
+            // The exchange sees the event, then the intents due by now
+            executor.on_market_event(COIN, &event);
+            let mut due = Vec::new();
+            while in_flight.front().is_some_and(|f| f.due <= time) {
+                due.extend(in_flight.pop_front());
+            }
+            let cloids: Vec<_> = due.iter().map(|f| f.cloid).collect();
+            let results = executor
+                .submit_all(due.into_iter().map(|f| f.intent).collect())
+                .await;
+            for (result, cloid) in results.into_iter().zip(cloids) {
+                let outcome = result
+                    .map(OrderOutcome::from)
+                    .unwrap_or_else(|e| OrderOutcome::Rejected(e.to_string()));
+                if let Some(cloid) = cloid {
+                    orders.on_outcome(cloid, &outcome);
+                }
+            }
+            while let Ok(msg) = published.try_recv() {
+                ledger.on_message(&msg);
+                orders.on_message(&msg);
+            }
+            let position = &mut engine.state.position;
+            while let Ok(fill) = fills.try_recv() {
+                let signed = if fill.is_buy { fill.sz } else { -fill.sz };
+                position.base += signed;
+                position.quote -= signed * fill.px;
+                if fill.status.is_open() {
+                    report.partial_fills += 1;
+                }
+            }
+            orders.clear_closed();
+
+            let (bids, asks) = match &event {
+                MarketEvent::Trade { px, sz, is_buy, .. } => {
+                    engine.process_trade(*px, *sz, *is_buy, time);
+                    continue;
+                }
+                MarketEvent::Book { bids, asks, .. } => (bids, asks),
+            };
+            let Some((bid_px, ask_px, bid_vol, ask_vol)) = top_of_book(bids, asks) else {
+                report.rejected_books += 1;
+                continue;
+            };
+            report.ticks += 1;
+            engine.process_l2_book(time, bid_px, ask_px, bid_vol, ask_vol);
+
+            // Invariants, on what the exchange and the bot know at this book
+            let open = orders.open_orders();
+            let abs_position = paper.position(COIN).abs();
+            report.max_abs_position = report.max_abs_position.max(abs_position);
+            if abs_position > config.max_position + EPSILON {
+                report.violation(format!("position {abs_position} beyond limit at {time}"));
+            }
+            let pending: HashSet<_> = in_flight.iter().filter_map(|f| f.cloid).collect();
+            let resting: HashSet<_> = paper.resting_cloids().into_iter().collect();
+            let tracked: HashSet<_> = open.iter().map(|o| o.cloid).collect();
+            let untracked = resting.difference(&tracked).count();
+            let lost = open
+                .iter()
+                .filter(|o| !resting.contains(&o.cloid))
+                .filter(|o| o.status != OrderStatus::New || !pending.contains(&o.cloid))
+                .count();
+            if untracked > 0 || lost > 0 {
+                report.violation(format!(
+                    "orphaned cloids at {time}: {untracked} resting but not tracked, {lost} tracked but not resting"
+                ));
+            }
+            let state = &engine.state;
+            if !close(ledger.base, paper.position(COIN), ledger.base.abs())
+                || !close(ledger.base, state.position.base, ledger.base.abs())
+                || !close(ledger.quote, state.position.quote, ledger.turnover)
+            {
+                report.violation(format!(
+                    "ledger drift at {time}: ledger ({}, {}) vs exchange {} vs bot ({}, {})",
+                    ledger.base,
+                    ledger.quote,
+                    paper.position(COIN),
+                    state.position.base,
+                    state.position.quote
+                ));
+            }
+            report.max_open_orders = report.max_open_orders.max(open.len());
+            report.max_book_history = report.max_book_history.max(state.book_history.len());
+            report.max_trade_history = report.max_trade_history.max(state.trade_history.len());
+            if state.book_history.len() > TWAP_WINDOW
+                || state.trade_history.len() > TRADE_WINDOW
+                || engine.circuit.tracked_updates() > MAX_TRACKED_UPDATES
+                || open.len() > MAX_OPEN_ORDERS
+            {
+                report.violation(format!(
+                    "unbounded state at {time}: books {}, trades {}, circuit {}, open orders {}",
+                    state.book_history.len(),
+                    state.trade_history.len(),
+                    engine.circuit.tracked_updates(),
+                    open.len()
+                ));
+            }
+
+            // Replace the quotes. Each side is approved as if everything still
+            // open on it fills, so cancels in flight cannot overshoot the limit.
+            let due = time + config.latency_ms;
+            cancelling.retain(|cloid| tracked.contains(cloid));
+            for order in open.iter().filter(|o| cancelling.insert(o.cloid)) {
+                let cancel = ClientCancelRequestCloid {
+                    asset: COIN.to_string(),
+                    cloid: order.cloid,
+                };
+                in_flight.push_back(InFlight {
+                    due,
+                    intent: OrderIntent::CancelByCloid(cancel),
+                    cloid: None,
+                });
+            }
+            let quotes = QuoteLayerManager::build_quotes(state);
+            let mut approved = Vec::new();
+            for (side, is_buy) in [("Buy", true), ("Sell", false)] {
+                let open_size: f64 = open
+                    .iter()
+                    .filter(|o| o.is_buy == is_buy)
+                    .map(|o| o.remaining())
+                    .sum();
+                let mut worst = state.clone();
+                worst.position.base += if is_buy { open_size } else { -open_size };
+                let side_quotes: Vec<_> =
+                    quotes.iter().filter(|q| q.side == side).cloned().collect();
+                approved.extend(risk.evaluate(&mut worst, &side_quotes));
+            }
+            for q in approved {
+                let cloid = Uuid::new_v4();
+                let is_buy = q.side == "Buy";
+                orders.track(cloid, COIN, is_buy, q.price, q.size);
+                let order = limit_order(
+                    COIN,
+                    is_buy,
+                    q.price,
+                    q.size,
+                    false,
+                    TimeInForce::Gtc,
+                    Some(cloid),
+                );
+                in_flight.push_back(InFlight {
+                    due,
+                    intent: OrderIntent::Place(order),
+                    cloid: Some(cloid),
+                });
+                report.orders += 1;
+            }
+            // One hedge at a time; the next book sees what it left
+            let hedging = in_flight
+                .iter()
+                .any(|f| matches!(&f.intent, OrderIntent::Place(o) if o.reduce_only));
+            if let Some(hedge) = risk.overflow_hedge(state).filter(|_| !hedging) {
+                let cloid = Uuid::new_v4();
+                orders.track(cloid, COIN, hedge.is_buy, hedge.limit_px, hedge.size);
+                let mut order = hedge_order(COIN, &hedge, TimeInForce::Ioc);
+                order.cloid = Some(cloid);
+                in_flight.push_back(InFlight {
+                    due,
+                    intent: OrderIntent::Place(order),
+                    cloid: Some(cloid),
+                });
+                report.hedges += 1;
+            }
+        }
+    }
+    report.fills = ledger.fills;
+    report.simulated_ms = span * config.loops as u64;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_scenario, Scenario};
+
+    #[tokio::test]
+    async fn test_soak_holds_invariants() {
+        // A thin book, so quotes that cross or are crossed fill in parts
+        let events: Vec<_> = generate_scenario(Scenario::FlashCrash, 3)
+            .into_iter()
+            .map(|mut event| {
+                if let MarketEvent::Book { bids, asks, .. } = &mut event {
+                    for level in bids.iter_mut().chain(asks.iter_mut()) {
+                        level.sz = "0.3".to_string();
+                    }
+                }
+                event
+            })
+            .collect();
+        let report = run_soak(
+            &events,
+            &SoakConfig {
+                max_position: 5.0,
+                loops: 20,
+                latency_ms: 200,
+            },
+        )
+        .await;
+        assert!(report.passed(), "{report}");
+        assert!(report.fills > 0 && report.partial_fills > 0, "{report}");
+        assert!(report.max_book_history <= TWAP_WINDOW);
+    }
+}
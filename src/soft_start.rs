@@ -0,0 +1,123 @@
+use log::info;
+
+use crate::QuoteProposal;
+
+pub(crate) const RAMP_START: f64 = 0.1; // Share of full size quoted when a ramp begins
+pub(crate) const RAMP_MAX_GAP_MS: u64 = 2_000; // Time between quote rounds when a ramp begins
+
+// Gradual return to full quoting: from the first book, and again whenever the
+// risk manager lets entries through after pausing or halting them, quote sizes
+// start at `start_fraction` of full and grow linearly to full over `ramp_ms`.
+// Quote rounds are spaced out the same way, `max_gap_ms` apart at the start
+// and on every book by the end of the ramp.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftStart {
+    pub ramp_ms: u64,
+    pub start_fraction: f64,
+    pub max_gap_ms: u64,
+    since: Option<u64>, // book time the current ramp began
+    blocked: bool,      // entries were refused on the last book
+    last_round: Option<u64>,
+}
+
+impl SoftStart {
+    pub fn new(ramp_ms: u64) -> Self {
+        Self {
+            ramp_ms,
+            start_fraction: RAMP_START,
+            max_gap_ms: RAMP_MAX_GAP_MS,
+            since: None,
+            blocked: false,
+            last_round: None,
+        }
+    }
+
+    pub fn with_start(mut self, fraction: f64, max_gap_ms: u64) -> Self {
+        self.start_fraction = fraction.clamp(0.0, 1.0);
+        self.max_gap_ms = max_gap_ms;
+        self
+    }
+
+    // Keeps `previous`'s ramp under these settings, when limits are reloaded
+    pub fn carry_over(&mut self, previous: &SoftStart) {
+        self.since = previous.since;
+        self.blocked = previous.blocked;
+        self.last_round = previous.last_round;
+    }
+
+    // Share of full size and quoting rate at `time`; 1.0 once ramped
+    pub fn fraction(&self, time: u64) -> f64 {
+        let Some(since) = self.since else {
+            return self.start_fraction;
+        };
+        if self.ramp_ms == 0 {
+            return 1.0;
+        }
+        let progress = (time.saturating_sub(since) as f64 / self.ramp_ms as f64).min(1.0);
+        self.start_fraction + (1.0 - self.start_fraction) * progress
+    }
+
+    // This book's quotes, scaled down or held back while ramping. `open` is
+    // whether the risk manager lets entries through on this book; the ramp
+    // restarts on the first book it does after refusing them.
+    pub fn apply(&mut self, time: u64, open: bool, quotes: &[QuoteProposal]) -> Vec<QuoteProposal> {
+        if !open {
+            self.blocked = true;
+            return quotes.to_vec();
+        }
+        if self.since.is_none() || self.blocked {
+            info!(
+                "[Risk] Soft start: ramping to full size over {}ms",
+                self.ramp_ms
+            );
+            self.since = Some(time);
+            self.blocked = false;
+            self.last_round = None;
+        }
+        let fraction = self.fraction(time);
+        if fraction >= 1.0 {
+            return quotes.to_vec();
+        }
+        let gap = (self.max_gap_ms as f64 * (1.0 - fraction)) as u64;
+        if self.last_round.is_some_and(|last| time < last + gap) {
+            return Vec::new();
+        }
+        self.last_round = Some(time);
+        quotes
+            .iter()
+            .map(|q| QuoteProposal {
+                size: q.size * fraction,
+                ..q.clone()
+            })
+            .filter(|q| q.size > 1e-9)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ramps_up_from_startup_and_again_after_a_pause() {
+        let mut ramp = SoftStart::new(10_000).with_start(0.1, 1_000);
+        let bid = QuoteProposal {
+            side: "Buy".to_string(),
+            price: 100.0,
+            size: 1.0,
+        };
+        let size = |quotes: Vec<QuoteProposal>| quotes.first().map(|q| q.size);
+        let quotes = std::slice::from_ref(&bid);
+        assert_eq!(size(ramp.apply(0, true, quotes)), Some(0.1));
+        // Rounds are held back while the ramp is young
+        assert_eq!(size(ramp.apply(500, true, quotes)), None);
+        let half = size(ramp.apply(5_000, true, quotes)).unwrap();
+        assert!((half - 0.55).abs() < 1e-9);
+        assert_eq!(size(ramp.apply(10_000, true, quotes)), Some(1.0));
+        assert_eq!(size(ramp.apply(10_001, true, quotes)), Some(1.0));
+
+        // A risk pause restarts it once entries are allowed again
+        assert_eq!(size(ramp.apply(20_000, false, quotes)), Some(1.0));
+        assert_eq!(size(ramp.apply(30_000, true, quotes)), Some(0.1));
+    }
+}
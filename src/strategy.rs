@@ -0,0 +1,218 @@
+use uuid::Uuid;
+
+use crate::{
+    prelude::*, AssetSpec, Error, OrderBook, OrderFill, OrderOutcome, QuoteProposal, SignalState,
+    StrategyConfig, TimeInForce,
+};
+
+// An order a strategy asks its runner to send. Placements carry their own
+// cloid, so the strategy can match the fills it is told about and amend or
+// cancel the order later.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrategyOrder {
+    Place {
+        cloid: Uuid,
+        is_buy: bool,
+        px: f64,
+        sz: f64,
+        reduce_only: bool,
+        tif: TimeInForce,
+    },
+    // Moves a resting order to a new price and size in place of a
+    // cancel-replace; the order keeps its cloid
+    Modify {
+        cloid: Uuid,
+        is_buy: bool,
+        px: f64,
+        sz: f64,
+        tif: TimeInForce,
+    },
+    Cancel {
+        cloid: Uuid,
+    },
+}
+
+impl StrategyOrder {
+    // A limit order under a fresh cloid
+    pub fn limit(is_buy: bool, px: f64, sz: f64, tif: TimeInForce) -> Self {
+        StrategyOrder::Place {
+            cloid: Uuid::new_v4(),
+            is_buy,
+            px,
+            sz,
+            reduce_only: false,
+            tif,
+        }
+    }
+
+    // Price and size on the asset's tick, as the exchange requires
+    pub fn rounded(self, spec: &AssetSpec) -> Self {
+        match self {
+            StrategyOrder::Place {
+                cloid,
+                is_buy,
+                px,
+                sz,
+                reduce_only,
+                tif,
+            } => StrategyOrder::Place {
+                cloid,
+                is_buy,
+                px: spec.round_price(px),
+                sz: spec.round_size(sz),
+                reduce_only,
+                tif,
+            },
+            StrategyOrder::Modify {
+                cloid,
+                is_buy,
+                px,
+                sz,
+                tif,
+            } => StrategyOrder::Modify {
+                cloid,
+                is_buy,
+                px: spec.round_price(px),
+                sz: spec.round_size(sz),
+                tif,
+            },
+            cancel => cancel,
+        }
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        if let StrategyOrder::Place { reduce_only, .. } = &mut self {
+            *reduce_only = true;
+        }
+        self
+    }
+
+    pub fn cloid(&self) -> Uuid {
+        match self {
+            StrategyOrder::Place { cloid, .. }
+            | StrategyOrder::Modify { cloid, .. }
+            | StrategyOrder::Cancel { cloid } => *cloid,
+        }
+    }
+}
+
+// A trading strategy. Quoting strategies turn the signal state into quote
+// proposals through `quote`, which the `MessageRouter` puts through the risk
+// manager like any other quotes. Strategies that manage their own orders use
+// the event hooks instead and are driven by a `StrategyRunner`, which sends the
+// orders they return and reports their outcomes and fills back.
+pub trait Strategy: Send {
+    fn name(&self) -> &str;
+    fn quote(&mut self, _state: &SignalState) -> Vec<QuoteProposal> {
+        Vec::new()
+    }
+    // A new book, after the signal state has taken it in
+    fn on_book(&mut self, _book: &OrderBook, _state: &SignalState) -> Vec<StrategyOrder> {
+        Vec::new()
+    }
+    // Trades are already folded into the signal state; strategies that keep their
+    // own trade state can hook in here
+    fn on_trade(&mut self, _price: f64, _size: f64, _is_buy: bool, _time: u64) {}
+    // A fill on one of the orders this strategy placed
+    fn on_fill(&mut self, _fill: &OrderFill) {}
+    // The exchange's answer to a placement or modify, before any of its fills
+    fn on_outcome(&mut self, _cloid: Uuid, _outcome: &OrderOutcome) {}
+    // Fires every timer period of market data time, so replays see the same
+    // timers as the live session they were recorded from
+    fn on_timer(&mut self, _now_ms: u64) -> Vec<StrategyOrder> {
+        Vec::new()
+    }
+    // The market data feed dropped (`connected` false) or is back with its
+    // book resynced. The runner cancels resting orders and holds placements
+    // during the gap; strategies drop state that should not span it here.
+    fn on_feed(&mut self, _connected: bool) -> Vec<StrategyOrder> {
+        Vec::new()
+    }
+    // The exchange's position, which wins over what the strategy inferred from
+    // its fills
+    fn on_position(&mut self, _size: f64, _entry_px: Option<f64>) {}
+    // Takes the coin's strategy settings, at startup and again whenever the
+    // config is reloaded. Strategies without tunable settings ignore it.
+    fn configure(&mut self, _config: &StrategyConfig) {}
+}
+
+// Registry entry. Strategies register themselves with `register_strategy!` next to
+// their implementation, so runners can pick one by name without listing them.
+pub struct StrategyRegistration {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub build: fn() -> Box<dyn Strategy>,
+}
+
+inventory::collect!(StrategyRegistration);
+
+#[macro_export]
+macro_rules! register_strategy {
+    ($name:expr, $description:expr, $build:expr) => {
+        $crate::inventory::submit! {
+            $crate::StrategyRegistration {
+                name: $name,
+                description: $description,
+                build: $build,
+            }
+        }
+    };
+}
+
+// All registered strategies, sorted by name
+pub fn registered_strategies() -> Vec<&'static StrategyRegistration> {
+    let mut all: Vec<_> = inventory::iter::<StrategyRegistration>().collect();
+    all.sort_by_key(|r| r.name);
+    all
+}
+
+pub fn build_strategy(name: &str) -> Result<Box<dyn Strategy>> {
+    inventory::iter::<StrategyRegistration>()
+        .find(|r| r.name.eq_ignore_ascii_case(name))
+        .map(|r| (r.build)())
+        .ok_or_else(|| Error::UnknownStrategy(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AlwaysBid;
+
+    impl Strategy for AlwaysBid {
+        fn name(&self) -> &str {
+            "test_always_bid"
+        }
+
+        fn quote(&mut self, state: &SignalState) -> Vec<QuoteProposal> {
+            vec![QuoteProposal {
+                side: "Buy".into(),
+                price: state.best_bid,
+                size: 1.0,
+            }]
+        }
+    }
+
+    register_strategy!("test_always_bid", "Bids at the touch", || Box::new(
+        AlwaysBid
+    ));
+
+    #[test]
+    fn test_strategy_registry() {
+        let names: Vec<_> = registered_strategies().iter().map(|r| r.name).collect();
+        assert!(names.contains(&"layered"));
+        assert!(names.contains(&"test_always_bid"));
+
+        let mut strategy = build_strategy("TEST_ALWAYS_BID").unwrap();
+        assert_eq!(strategy.name(), "test_always_bid");
+        let state = SignalState {
+            best_bid: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(strategy.quote(&state)[0].price, 10.0);
+        assert!(matches!(
+            build_strategy("missing"),
+            Err(Error::UnknownStrategy(_))
+        ));
+    }
+}
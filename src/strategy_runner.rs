@@ -0,0 +1,619 @@
+use log::{error, info, warn};
+use std::collections::HashMap;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use uuid::Uuid;
+
+use crate::{
+    limit_order, AssetSpec, ClientCancelRequestCloid, ClientModifyRequest, Executor, MarketEvent,
+    Message, OrderBook, OrderFill, OrderIntent, OrderManager, OrderOutcome, SignalEngine,
+    SignalState, SignalWindows, Strategy, StrategyOrder, TimeInForce,
+};
+
+pub(crate) const DEFAULT_TIMER_MS: u64 = 1_000;
+
+// Drives one coin's order-placing `Strategy` from market data, live or
+// replayed. Books update the full depth and signal state before reaching
+// `on_book`, trades reach `on_trade`, and timers fire on market data time. The
+// orders a strategy returns go through the executor and are tracked by the
+// order manager, whose fills come back through `on_fill`. With a
+// `PaperExchange` behind the executor, a recording replays through the same
+// code as the live session. Orders sent as GTD are cancelled by the runner
+// once they have rested their time, also in market data time. Live, a book
+// older than the configured age by the local clock pulls the strategy's
+// orders and holds its placements until a fresh one arrives.
+pub struct StrategyRunner {
+    coin: String,
+    strategy: Box<dyn Strategy>,
+    signal: SignalEngine,
+    book: OrderBook,
+    executor: Option<Executor>, // None logs orders without sending them
+    orders: OrderManager,
+    fills: broadcast::Receiver<OrderFill>,
+    position: f64, // from fills, until the exchange's position replaces it
+    fill_count: u64,
+    volume: f64, // notional filled
+    timer_ms: u64,
+    next_timer: Option<u64>,
+    spec: Option<AssetSpec>, // None sends prices and sizes as the strategy gave them
+    expiries: HashMap<Uuid, u64>, // GTD orders by cloid, to the time they are cancelled
+    now_ms: u64,             // time of the last market event
+    paused: bool,            // placements and modifies are dropped, cancels still go
+    feed_gap: bool,          // the feed dropped and has not resynced; quoting is paused
+    max_book_age_ms: Option<u64>, // live books older than this by the local clock are stale
+    stale: bool,             // the last live book was stale; quoting is paused
+}
+
+impl StrategyRunner {
+    pub fn new(strategy: Box<dyn Strategy>, coin: &str) -> Self {
+        let orders = OrderManager::new();
+        Self {
+            coin: coin.to_string(),
+            strategy,
+            signal: SignalEngine::new(),
+            book: OrderBook::new(),
+            executor: None,
+            fills: orders.subscribe_fills(),
+            orders,
+            position: 0.0,
+            fill_count: 0,
+            volume: 0.0,
+            timer_ms: DEFAULT_TIMER_MS,
+            next_timer: None,
+            spec: None,
+            expiries: HashMap::new(),
+            now_ms: 0,
+            paused: false,
+            feed_gap: false,
+            max_book_age_ms: None,
+            stale: false,
+        }
+    }
+    // Send the strategy's orders through `executor`, live or paper
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+    // Track orders with a manager fed the user's order and fill events, either
+    // via its own subscription or by routing them through `handle`
+    pub fn with_order_manager(mut self, orders: OrderManager) -> Self {
+        self.fills = orders.subscribe_fills();
+        self.orders = orders;
+        self
+    }
+    // Period of `on_timer`, in market data time
+    pub fn with_timer(mut self, period_ms: u64) -> Self {
+        self.timer_ms = period_ms;
+        self
+    }
+    pub fn with_signal_windows(mut self, windows: SignalWindows) -> Self {
+        self.signal = self.signal.with_windows(windows);
+        self
+    }
+    // Round every order's price and size to the coin's exchange precision
+    pub fn with_asset_spec(mut self, spec: AssetSpec) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+    // Treat live books stamped more than `ms` before the local clock as stale
+    pub fn with_max_book_age(mut self, ms: u64) -> Self {
+        self.max_book_age_ms = Some(ms);
+        self
+    }
+    pub fn strategy(&self) -> &dyn Strategy {
+        self.strategy.as_ref()
+    }
+    pub fn orders(&self) -> &OrderManager {
+        &self.orders
+    }
+    // Full depth of the coin's book, as the strategy last saw it
+    pub fn book(&self) -> &OrderBook {
+        &self.book
+    }
+    pub fn signals(&self) -> &SignalState {
+        &self.signal.state
+    }
+    // While paused the strategy keeps seeing market data, but only its
+    // cancels are sent. Resting orders stay up; see `cancel_open_orders`.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    // Signed size, from this coin's fills and the last exchange position
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+    // The exchange's position replaces the one built up from fills
+    pub fn on_position(&mut self, size: f64, entry_px: Option<f64>) {
+        self.position = size;
+        self.strategy.on_position(size, entry_px);
+    }
+    // Cancels this coin's open orders by cloid, for shutting down; returns how
+    // many cancels the exchange accepted
+    pub async fn cancel_open_orders(&mut self) -> usize {
+        let open: Vec<_> = self
+            .orders
+            .open_orders()
+            .into_iter()
+            .filter(|o| o.coin == self.coin)
+            .collect();
+        let cancels = open
+            .into_iter()
+            .map(|order| StrategyOrder::Cancel { cloid: order.cloid })
+            .collect();
+        let cancelled = self
+            .send_all(cancels)
+            .await
+            .iter()
+            .filter(|outcome| !matches!(outcome, Some(OrderOutcome::Rejected(_))))
+            .count();
+        self.deliver_fills();
+        cancelled
+    }
+    // Position, fills, volume and open orders, for the exit report
+    pub fn summary(&self) -> String {
+        let open = self
+            .orders
+            .open_orders()
+            .iter()
+            .filter(|o| o.coin == self.coin)
+            .count();
+        format!(
+            "{} {}: position {:.4} | fills {} | volume ${:.2} | open orders {open}",
+            self.coin,
+            self.strategy.name(),
+            self.position,
+            self.fill_count,
+            self.volume
+        )
+    }
+    // A websocket message: market data for this runner's coin, or the user's
+    // order and fill events
+    pub async fn handle(&mut self, msg: &Message) {
+        self.orders.on_message(msg);
+        self.deliver_fills();
+        if let Message::NoData | Message::Reconnected = msg {
+            self.on_feed(matches!(msg, Message::Reconnected)).await;
+            return;
+        }
+        if let Message::L2Book(book) = msg {
+            let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+            self.check_book_age(now_ms, book.data.time).await;
+        }
+        for event in MarketEvent::from_message(msg) {
+            self.handle_event(event).await;
+        }
+    }
+    // Quoting pauses from the moment the feed drops, with the strategy's
+    // resting orders cancelled, until it is back with its book resynced
+    async fn on_feed(&mut self, connected: bool) {
+        if self.feed_gap != connected {
+            return;
+        }
+        self.feed_gap = !connected;
+        let orders = self.strategy.on_feed(connected);
+        if connected {
+            info!("{} feed resynced, quoting resumes", self.coin);
+        } else {
+            let cancelled = self.cancel_open_orders().await;
+            info!("{} feed lost, {cancelled} orders cancelled", self.coin);
+        }
+        self.send_all(orders).await;
+    }
+    // Pulls the strategy's orders when a book arrives `max_book_age_ms` late,
+    // and lets it quote again on the next book that is not
+    async fn check_book_age(&mut self, now_ms: u64, book_ms: u64) {
+        let Some(max_age) = self.max_book_age_ms else {
+            return;
+        };
+        let age = now_ms.saturating_sub(book_ms);
+        let stale = age > max_age;
+        if stale == self.stale {
+            return;
+        }
+        self.stale = stale;
+        if stale {
+            let cancelled = self.cancel_open_orders().await;
+            warn!(
+                "{} book {age}ms old, quoting paused and {cancelled} orders cancelled",
+                self.coin
+            );
+        } else {
+            info!("{} book fresh again ({age}ms), quoting resumes", self.coin);
+        }
+    }
+    pub async fn handle_event(&mut self, event: MarketEvent) {
+        // A paper backend fills resting orders against this coin's data
+        if let Some(executor) = &self.executor {
+            executor.on_market_event(&self.coin, &event);
+        }
+        self.deliver_fills();
+        let time = event.time();
+        self.now_ms = time;
+        let mut orders = self.expired(time);
+        let next_timer = *self.next_timer.get_or_insert(time + self.timer_ms);
+        if self.timer_ms > 0 && time >= next_timer {
+            orders.extend(self.strategy.on_timer(time));
+            // Quiet periods fire once, not once per missed period
+            let missed = (time - next_timer) / self.timer_ms;
+            self.next_timer = Some(next_timer + (missed + 1) * self.timer_ms);
+        }
+        match event {
+            MarketEvent::Book { time, bids, asks } => {
+                // Duplicated, out-of-order, one-sided or crossed books are skipped
+                let last_ms = self
+                    .signal
+                    .state
+                    .book_history
+                    .back()
+                    .map(|b| b.timestamp_ms);
+                let fresh = last_ms.is_none_or(|last| time > last);
+                if fresh && self.book.apply_snapshot(time, &bids, &asks) {
+                    self.signal.process_book(&self.book);
+                    orders.extend(self.strategy.on_book(&self.book, &self.signal.state));
+                }
+            }
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => {
+                self.signal.process_trade(px, sz, is_buy, time);
+                self.strategy.on_trade(px, sz, is_buy, time);
+            }
+        }
+        // New orders also wait while the request budget is low, so the
+        // cancels it keeps in reserve can still get out
+        let throttled = self
+            .executor
+            .as_ref()
+            .is_some_and(Executor::is_backpressured);
+        let paused = self.paused || self.feed_gap || self.stale || throttled;
+        let orders = orders
+            .into_iter()
+            .filter(|order| !paused || matches!(order, StrategyOrder::Cancel { .. }))
+            .map(|order| match &self.spec {
+                Some(spec) => order.rounded(spec),
+                None => order,
+            })
+            .collect();
+        self.send_all(orders).await;
+        self.deliver_fills();
+    }
+    // Cancels for the GTD orders due by `time` that are still open
+    fn expired(&mut self, time: u64) -> Vec<StrategyOrder> {
+        let due: Vec<_> = self
+            .expiries
+            .iter()
+            .filter(|(_, expiry)| **expiry <= time)
+            .map(|(cloid, _)| *cloid)
+            .collect();
+        due.into_iter()
+            .filter(|cloid| {
+                self.expiries.remove(cloid);
+                self.orders.get(*cloid).is_some_and(|o| o.status.is_open())
+            })
+            .map(|cloid| {
+                info!("{} GTD order {cloid} expired", self.coin);
+                StrategyOrder::Cancel { cloid }
+            })
+            .collect()
+    }
+    fn deliver_fills(&mut self) {
+        loop {
+            match self.fills.try_recv() {
+                Ok(fill) if fill.coin == self.coin => {
+                    self.position += if fill.is_buy { fill.sz } else { -fill.sz };
+                    self.fill_count += 1;
+                    self.volume += fill.px * fill.sz;
+                    self.strategy.on_fill(&fill);
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Lagged(skipped)) => {
+                    error!("{} strategy missed {skipped} fills", self.coin);
+                }
+                Err(_) => break,
+            }
+        }
+        self.orders.clear_closed();
+    }
+    // The exchange's answer to each order, or None when it was not sent. The
+    // orders are submitted together, so a batching executor sends them in as
+    // few requests as it can.
+    async fn send_all(&mut self, orders: Vec<StrategyOrder>) -> Vec<Option<OrderOutcome>> {
+        let Some(executor) = self.executor.clone() else {
+            for order in &orders {
+                info!("{} {} (not sent)", self.coin, describe(order));
+            }
+            return orders.iter().map(|_| None).collect();
+        };
+        let mut intents = Vec::new();
+        let mut placed = Vec::new();
+        let mut sent = Vec::new();
+        for order in orders {
+            let intent = self.intent(order);
+            sent.push(intent.is_some());
+            if let Some((intent, cloid)) = intent {
+                intents.push(intent);
+                placed.push(cloid);
+            }
+        }
+        let results = executor.submit_all(intents).await;
+        let mut outcomes = Vec::new();
+        for (result, cloid) in results.into_iter().zip(placed) {
+            let outcome = result
+                .map(OrderOutcome::from)
+                .unwrap_or_else(|e| OrderOutcome::Rejected(e.to_string()));
+            if let Some(cloid) = cloid {
+                if let OrderOutcome::Rejected(e) = &outcome {
+                    info!("{} order rejected: {e}", self.coin);
+                }
+                self.orders.on_outcome(cloid, &outcome);
+                self.strategy.on_outcome(cloid, &outcome);
+            }
+            outcomes.push(outcome);
+        }
+        let mut outcomes = outcomes.into_iter();
+        sent.into_iter()
+            .map(|sent| if sent { outcomes.next() } else { None })
+            .collect()
+    }
+    // Tracks `order` and turns it into an intent, with the cloid of the order
+    // it places or modifies; None when there is nothing to send
+    fn intent(&mut self, order: StrategyOrder) -> Option<(OrderIntent, Option<Uuid>)> {
+        match order {
+            StrategyOrder::Place {
+                cloid,
+                is_buy,
+                px,
+                sz,
+                reduce_only,
+                tif,
+            } => {
+                self.orders.track(cloid, &self.coin, is_buy, px, sz);
+                schedule_expiry(&mut self.expiries, cloid, tif, self.now_ms);
+                let order = limit_order(&self.coin, is_buy, px, sz, reduce_only, tif, Some(cloid));
+                Some((OrderIntent::Place(order), Some(cloid)))
+            }
+            StrategyOrder::Modify {
+                cloid,
+                is_buy,
+                px,
+                sz,
+                tif,
+            } => {
+                // Modifies go by exchange oid, known once the order was acked
+                let Some(oid) = self.orders.get(cloid).and_then(|o| o.oid) else {
+                    info!("{} modify of {cloid} dropped: not resting", self.coin);
+                    return None;
+                };
+                self.orders.track(cloid, &self.coin, is_buy, px, sz);
+                schedule_expiry(&mut self.expiries, cloid, tif, self.now_ms);
+                let order = limit_order(&self.coin, is_buy, px, sz, false, tif, Some(cloid));
+                let modify = ClientModifyRequest { oid, order };
+                Some((OrderIntent::Modify(modify), Some(cloid)))
+            }
+            StrategyOrder::Cancel { cloid } => {
+                let cancel = ClientCancelRequestCloid {
+                    asset: self.coin.clone(),
+                    cloid,
+                };
+                Some((OrderIntent::CancelByCloid(cancel), None))
+            }
+        }
+    }
+}
+
+// A GTD order's time to live starts when it is sent, and over when it is modified
+fn schedule_expiry(expiries: &mut HashMap<Uuid, u64>, cloid: Uuid, tif: TimeInForce, now_ms: u64) {
+    match tif.expiry_ms() {
+        Some(ms) => expiries.insert(cloid, now_ms + ms),
+        None => expiries.remove(&cloid),
+    };
+}
+
+fn describe(order: &StrategyOrder) -> String {
+    match order {
+        StrategyOrder::Place {
+            is_buy,
+            px,
+            sz,
+            tif,
+            ..
+        } => format!("{} {sz} @ {px} {tif}", if *is_buy { "buy" } else { "sell" }),
+        StrategyOrder::Modify { cloid, px, sz, .. } => format!("modify {cloid} to {sz} @ {px}"),
+        StrategyOrder::Cancel { cloid } => format!("cancel {cloid}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BookLevel, PaperConfig, PaperExchange};
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    // Bids once at the touch and records what it is told
+    #[derive(Default)]
+    struct Recorder {
+        tif: TimeInForce,
+        placed: bool,
+        fills: Arc<Mutex<Vec<f64>>>,
+        timers: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl Strategy for Recorder {
+        fn name(&self) -> &str {
+            "test_recorder"
+        }
+
+        fn on_book(&mut self, book: &OrderBook, _state: &SignalState) -> Vec<StrategyOrder> {
+            if std::mem::replace(&mut self.placed, true) {
+                return Vec::new();
+            }
+            let (bid, _) = book.best_bid().unwrap();
+            vec![StrategyOrder::limit(true, bid, 1.0, self.tif)]
+        }
+
+        fn on_fill(&mut self, fill: &OrderFill) {
+            self.fills.lock().unwrap().push(fill.sz);
+        }
+
+        fn on_timer(&mut self, now_ms: u64) -> Vec<StrategyOrder> {
+            self.timers.lock().unwrap().push(now_ms);
+            Vec::new()
+        }
+    }
+
+    fn book(time: u64) -> MarketEvent {
+        let level = |px: f64| BookLevel {
+            px: px.to_string(),
+            sz: "2".to_string(),
+            n: 1,
+        };
+        MarketEvent::Book {
+            time,
+            bids: vec![level(100.0)],
+            asks: vec![level(101.0)],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replays_through_a_paper_exchange() {
+        let (events, mut published) = unbounded_channel();
+        let paper = PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(events);
+        let strategy = Recorder::default();
+        let (fills, timers) = (strategy.fills.clone(), strategy.timers.clone());
+        let mut runner = StrategyRunner::new(Box::new(strategy), "BTC")
+            .with_executor(Executor::with_backend(Arc::new(paper)))
+            .with_timer(1_000);
+
+        // The bid joins behind the 2 resting at 100; a 3 lot sell reaches it
+        let sell = MarketEvent::Trade {
+            time: 500,
+            px: 100.0,
+            sz: 3.0,
+            is_buy: false,
+        };
+        for event in [book(0), sell, book(2_500)] {
+            runner.handle_event(event).await;
+            while let Ok(msg) = published.try_recv() {
+                runner.handle(&msg).await;
+            }
+        }
+        assert_eq!(*fills.lock().unwrap(), vec![1.0]);
+        assert_eq!(runner.position(), 1.0);
+        // The quiet second fired one timer, not two
+        assert_eq!(*timers.lock().unwrap(), vec![2_500]);
+        assert!(runner.orders().open_orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_resting_orders() {
+        let (events, mut published) = unbounded_channel();
+        let paper = PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(events);
+        let mut runner = StrategyRunner::new(Box::new(Recorder::default()), "BTC")
+            .with_executor(Executor::with_backend(Arc::new(paper)));
+        runner.handle_event(book(0)).await;
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert_eq!(runner.orders().open_orders().len(), 1);
+
+        assert_eq!(runner.cancel_open_orders().await, 1);
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert_eq!(
+            runner.summary(),
+            "BTC test_recorder: position 0.0000 | fills 0 | volume $0.00 | open orders 0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pulls_orders_while_the_feed_is_down() {
+        let (events, mut published) = unbounded_channel();
+        let paper = PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(events);
+        let mut runner = StrategyRunner::new(Box::new(Recorder::default()), "BTC")
+            .with_executor(Executor::with_backend(Arc::new(paper)));
+        runner.handle_event(book(0)).await;
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert_eq!(runner.orders().open_orders().len(), 1);
+
+        runner.handle(&Message::NoData).await;
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert!(runner.orders().open_orders().is_empty());
+        runner.handle(&Message::Reconnected).await;
+        assert!(!runner.feed_gap);
+    }
+
+    #[tokio::test]
+    async fn test_stale_books_pause_quoting_until_a_fresh_one() {
+        let (events, mut published) = unbounded_channel();
+        let paper = PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(events);
+        let mut runner = StrategyRunner::new(Box::new(Recorder::default()), "BTC")
+            .with_executor(Executor::with_backend(Arc::new(paper)))
+            .with_max_book_age(500);
+        runner.handle_event(book(0)).await;
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert_eq!(runner.orders().open_orders().len(), 1);
+
+        runner.check_book_age(10_000, 9_600).await;
+        assert_eq!(runner.orders().open_orders().len(), 1);
+        runner.check_book_age(10_000, 9_000).await;
+        while let Ok(msg) = published.try_recv() {
+            runner.handle(&msg).await;
+        }
+        assert!(runner.orders().open_orders().is_empty());
+        assert!(runner.stale);
+        runner.check_book_age(11_000, 10_900).await;
+        assert!(!runner.stale);
+    }
+
+    #[tokio::test]
+    async fn test_cancels_gtd_orders_once_they_expire() {
+        let (events, mut published) = unbounded_channel();
+        let paper = PaperExchange::new(PaperConfig {
+            latency_ms: 0,
+            ..Default::default()
+        })
+        .with_events(events);
+        let strategy = Recorder {
+            tif: TimeInForce::Gtd(2_000),
+            ..Default::default()
+        };
+        let mut runner = StrategyRunner::new(Box::new(strategy), "BTC")
+            .with_executor(Executor::with_backend(Arc::new(paper)));
+        for (time, open) in [(0, 1), (1_500, 1), (2_000, 0)] {
+            runner.handle_event(book(time)).await;
+            while let Ok(msg) = published.try_recv() {
+                runner.handle(&msg).await;
+            }
+            assert_eq!(runner.orders().open_orders().len(), open, "at {time}ms");
+        }
+    }
+}
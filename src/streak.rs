@@ -0,0 +1,128 @@
+use log::info;
+
+use crate::QuoteProposal;
+
+pub(crate) const WIDEN_STEP_TICKS: f64 = 1.0; // Extra distance from the touch per widening step
+pub(crate) const WIDEN_SIZE_CUT: f64 = 0.25; // Share of quote size removed per widening step
+pub(crate) const MAX_WIDEN_STEPS: u32 = 4;
+
+// Feedback on round trip outcomes: every `losses_to_widen` losing round trips
+// in a row push the quotes another step away from the touch and shrink them,
+// up to `max_steps`. Each winning round trip takes one step back, so quoting
+// returns to normal gradually once the strategy makes money again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreakWidener {
+    pub losses_to_widen: usize,
+    pub step_ticks: f64,
+    pub size_cut: f64,
+    pub max_steps: u32,
+    losing: usize, // losing round trips since the last step or win
+    steps: u32,
+}
+
+impl StreakWidener {
+    pub fn new(losses_to_widen: usize) -> Self {
+        Self {
+            losses_to_widen: losses_to_widen.max(1),
+            step_ticks: WIDEN_STEP_TICKS,
+            size_cut: WIDEN_SIZE_CUT,
+            max_steps: MAX_WIDEN_STEPS,
+            losing: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn with_step(mut self, ticks: f64, size_cut: f64) -> Self {
+        self.step_ticks = ticks;
+        self.size_cut = size_cut.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_max_steps(mut self, steps: u32) -> Self {
+        self.max_steps = steps;
+        self
+    }
+
+    // Keeps `previous`'s streak and steps under these settings, when limits
+    // are reloaded
+    pub fn carry_over(&mut self, previous: &StreakWidener) {
+        self.losing = previous.losing;
+        self.steps = previous.steps.min(self.max_steps);
+    }
+
+    pub fn on_round_trip(&mut self, pnl: f64) {
+        let steps = self.steps;
+        if pnl < 0.0 {
+            self.losing += 1;
+            if self.losing >= self.losses_to_widen {
+                self.losing = 0;
+                self.steps = (self.steps + 1).min(self.max_steps);
+            }
+        } else {
+            self.losing = 0;
+            self.steps = self.steps.saturating_sub(1);
+        }
+        if self.steps != steps {
+            info!("[Risk] Losing streak widening at step {}", self.steps);
+        }
+    }
+
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    // Bids move down and asks up by the current widening, with sizes cut to
+    // match
+    pub fn apply(&self, quotes: &[QuoteProposal]) -> Vec<QuoteProposal> {
+        if self.steps == 0 {
+            return quotes.to_vec();
+        }
+        let widen = self.step_ticks * self.steps as f64;
+        let scale = (1.0 - self.size_cut).powi(self.steps as i32);
+        quotes
+            .iter()
+            .map(|q| QuoteProposal {
+                side: q.side.clone(),
+                price: if q.side == "Buy" {
+                    q.price - widen
+                } else {
+                    q.price + widen
+                },
+                size: q.size * scale,
+            })
+            .filter(|q| q.size > 1e-9)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_losing_streaks_widen_and_wins_restore() {
+        let mut widener = StreakWidener::new(2).with_max_steps(2);
+        let bid = QuoteProposal {
+            side: "Buy".to_string(),
+            price: 100.0,
+            size: 1.0,
+        };
+        // A loss alone, or broken up by a win, is not a streak
+        for pnl in [-1.0, 1.0, -1.0] {
+            widener.on_round_trip(pnl);
+        }
+        assert_eq!(widener.steps(), 0);
+        assert_eq!(widener.apply(std::slice::from_ref(&bid))[0].price, 100.0);
+        // Every second loss in a row adds a step, up to the cap
+        for _ in 0..5 {
+            widener.on_round_trip(-1.0);
+        }
+        assert_eq!(widener.steps(), 2);
+        let wide = widener.apply(std::slice::from_ref(&bid));
+        assert_eq!((wide[0].price, wide[0].size), (98.0, 0.5625));
+        // Each win gives one step back
+        widener.on_round_trip(0.5);
+        assert_eq!(widener.steps(), 1);
+        assert_eq!(widener.apply(&[bid])[0].price, 99.0);
+    }
+}
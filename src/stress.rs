@@ -0,0 +1,358 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt;
+
+use crate::{
+    top_of_book, BookLevel, HedgeOrder, LossLimits, MarketEvent, QuoteLayerManager, RiskManager,
+    SignalEngine, SignalState, EPSILON,
+};
+
+// Synthetic feed parameters
+const START_MS: u64 = 1_700_000_000_000;
+const BOOK_INTERVAL_MS: u64 = 500;
+const START_MID: f64 = 100_000.0;
+const TICK: f64 = 1.0;
+const CALM_MS: u64 = 120_000; // Warm-up before the adverse event, and recovery after it
+const LEVELS: usize = 5;
+const KILL_DRAWDOWN: f64 = 0.01; // Drawdown that halts and flattens, as a share of the position limit's notional
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    FlashCrash,    // 8% drop in 10s with one-sided selling, then partial recovery
+    SpreadBlowout, // Spread widens to 200 ticks with thin depth for 20s
+    OneSidedBook,  // Asks disappear for 10s
+    FeedFreeze,    // No messages for 30s, then the feed resumes 1% away
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 4] = [
+        Scenario::FlashCrash,
+        Scenario::SpreadBlowout,
+        Scenario::OneSidedBook,
+        Scenario::FeedFreeze,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scenario::FlashCrash => "flash_crash",
+            Scenario::SpreadBlowout => "spread_blowout",
+            Scenario::OneSidedBook => "one_sided_book",
+            Scenario::FeedFreeze => "feed_freeze",
+        }
+    }
+
+    // Length of the adverse phase, which starts right after the calm warm-up
+    fn event_ms(&self) -> u64 {
+        match self {
+            Scenario::FlashCrash | Scenario::OneSidedBook => 10_000,
+            Scenario::SpreadBlowout => 20_000,
+            Scenario::FeedFreeze => 30_000,
+        }
+    }
+}
+
+fn levels(best: f64, step: f64, sz: f64) -> Vec<BookLevel> {
+    (0..LEVELS)
+        .map(|i| BookLevel {
+            px: format!("{:.1}", best + step * i as f64),
+            sz: format!("{sz:.4}"),
+            n: 1,
+        })
+        .collect()
+}
+
+// Deterministic event stream for a scenario: calm, adverse phase, calm recovery
+pub fn generate_scenario(scenario: Scenario, seed: u64) -> Vec<MarketEvent> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let event_start = START_MS + CALM_MS;
+    let event_end = event_start + scenario.event_ms();
+    let end = event_end + CALM_MS;
+
+    let mut events = Vec::new();
+    let mut mid = START_MID;
+    let mut time = START_MS;
+    while time < end {
+        let in_event = time >= event_start && time < event_end;
+        let progress = (time.saturating_sub(event_start)) as f64 / scenario.event_ms() as f64;
+        let mut half_spread = TICK / 2.0;
+        let mut depth = 2.0;
+        let mut one_sided = false;
+        mid += TICK * rng.gen_range(-1..=1) as f64;
+
+        match scenario {
+            Scenario::FlashCrash if in_event => {
+                mid = START_MID * (1.0 - 0.08 * progress) + rng.gen_range(-20.0..20.0);
+                half_spread = 5.0 * TICK;
+                for _ in 0..3 {
+                    events.push(MarketEvent::Trade {
+                        time,
+                        px: mid - half_spread,
+                        sz: rng.gen_range(0.5..3.0),
+                        is_buy: false,
+                    });
+                }
+            }
+            Scenario::FlashCrash if time >= event_end && time < event_end + BOOK_INTERVAL_MS => {
+                // Partial bounce once the selling stops
+                mid = START_MID * 0.95;
+            }
+            Scenario::SpreadBlowout if in_event => {
+                half_spread = 100.0 * TICK;
+                depth = 0.05;
+            }
+            Scenario::OneSidedBook if in_event => one_sided = true,
+            Scenario::FeedFreeze if in_event => {
+                time += BOOK_INTERVAL_MS;
+                continue;
+            }
+            Scenario::FeedFreeze if time >= event_end && time < event_end + BOOK_INTERVAL_MS => {
+                mid *= 1.01;
+            }
+            _ => {}
+        }
+
+        let best_bid = ((mid - half_spread) / TICK).floor() * TICK;
+        let best_ask = (best_bid + 2.0 * half_spread).max(best_bid + TICK);
+        events.push(MarketEvent::Book {
+            time,
+            bids: levels(best_bid, -TICK, depth),
+            asks: if one_sided {
+                vec![]
+            } else {
+                levels(best_ask, TICK, depth)
+            },
+        });
+        time += BOOK_INTERVAL_MS;
+    }
+    events
+}
+
+#[derive(Debug, Clone)]
+pub struct StressReport {
+    pub scenario: Scenario,
+    pub book_updates: usize,
+    pub rejected_books: usize, // one-sided/crossed books kept away from the engine
+    pub quotes_built: usize,
+    pub paused_updates: usize,
+    pub circuit_tripped_in_event: bool,
+    pub hedges: usize,
+    pub max_abs_position: f64,
+    pub halted_at: Option<u64>, // when the drawdown limit tripped the kill switch
+    pub violations: Vec<String>,
+}
+
+impl StressReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<15} {} | books {} (rejected {}) | quotes {} | paused {} | tripped in event {} | hedges {} | max |pos| {:.3} | halted {}",
+            self.scenario.name(),
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.book_updates,
+            self.rejected_books,
+            self.quotes_built,
+            self.paused_updates,
+            self.circuit_tripped_in_event,
+            self.hedges,
+            self.max_abs_position,
+            self.halted_at.map_or("no".to_string(), |t| format!("at {t}"))
+        )?;
+        for v in &self.violations {
+            write!(f, "\n    violation: {v}")?;
+        }
+        Ok(())
+    }
+}
+
+// Hedges fill in full at the touch
+fn fill_hedge(state: &mut SignalState, hedge: &HedgeOrder) {
+    let px = if hedge.is_buy {
+        state.best_ask
+    } else {
+        state.best_bid
+    };
+    let signed = if hedge.is_buy {
+        hedge.size
+    } else {
+        -hedge.size
+    };
+    state.position.base += signed;
+    state.position.quote -= signed * px;
+}
+
+// Drives the signal engine, quote builder and risk manager through a scenario in
+// simulation (the risk manager fills approved quotes immediately) and checks the
+// guards that should hold under stress. A drawdown limit stands in for the
+// bot's kill switch: once it halts, nothing may be quoted and the position is
+// flattened as the switch would.
+pub fn run_scenario(scenario: Scenario, max_position: f64, seed: u64) -> StressReport {
+    let mut engine = SignalEngine::new();
+    let risk = RiskManager::new(max_position).with_loss_limits(LossLimits {
+        max_drawdown: Some(max_position * START_MID * KILL_DRAWDOWN),
+        ..Default::default()
+    });
+    let event_start = START_MS + CALM_MS;
+    // A freeze is only observable when the feed resumes
+    let event_end = event_start + scenario.event_ms() + BOOK_INTERVAL_MS;
+    let mut report = StressReport {
+        scenario,
+        book_updates: 0,
+        rejected_books: 0,
+        quotes_built: 0,
+        paused_updates: 0,
+        circuit_tripped_in_event: false,
+        hedges: 0,
+        max_abs_position: 0.0,
+        halted_at: None,
+        violations: Vec::new(),
+    };
+    let mut loaded = false;
+
+    for event in generate_scenario(scenario, seed) {
+        let (time, bids, asks) = match event {
+            MarketEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => {
+                engine.process_trade(px, sz, is_buy, time);
+                continue;
+            }
+            MarketEvent::Book { time, bids, asks } => (time, bids, asks),
+        };
+        report.book_updates += 1;
+        let Some((bid_px, ask_px, bid_vol, ask_vol)) = top_of_book(&bids, &asks) else {
+            report.rejected_books += 1;
+            continue;
+        };
+        engine.process_l2_book(time, bid_px, ask_px, bid_vol, ask_vol);
+        let state = &mut engine.state;
+        // The adverse phase opens on a book long to the soft limit, the worst
+        // case for a crash
+        if time >= event_start && !loaded {
+            loaded = true;
+            let add = risk.soft_limit - state.position.base;
+            state.position.base += add;
+            state.position.quote -= add * (bid_px + ask_px) / 2.0;
+        }
+        if state.quoting_paused {
+            report.paused_updates += 1;
+            if (event_start..=event_end).contains(&time) {
+                report.circuit_tripped_in_event = true;
+            }
+        }
+
+        let quotes = QuoteLayerManager::build_quotes(state);
+        if state.quoting_paused && !quotes.is_empty() {
+            report.violations.push(format!(
+                "{} quotes built while paused at {time}",
+                quotes.len()
+            ));
+        }
+        if ask_px - bid_px > 2.0 && state.aggressive_mode {
+            report.violations.push(format!(
+                "aggressive mode on a {} wide spread",
+                ask_px - bid_px
+            ));
+        }
+        if let Some(q) = quotes.iter().find(|q| !(q.price > 0.0 && q.size > 0.0)) {
+            report.violations.push(format!("degenerate quote {q:?}"));
+        }
+        report.quotes_built += quotes.len();
+
+        let approved = risk.evaluate(state, &quotes);
+        if report.halted_at.is_some() && !approved.is_empty() {
+            report.violations.push(format!(
+                "{} quotes approved after the halt at {time}",
+                approved.len()
+            ));
+        }
+        if risk.halted() {
+            report.halted_at.get_or_insert(time);
+        }
+        let hedge = match risk.wants_flatten() {
+            true => risk.flatten_hedge(state),
+            false => risk.overflow_hedge(state),
+        };
+        if let Some(hedge) = hedge {
+            report.hedges += 1;
+            fill_hedge(state, &hedge);
+        }
+        let abs_position = state.position.base.abs();
+        report.max_abs_position = report.max_abs_position.max(abs_position);
+        if abs_position > max_position + EPSILON {
+            report
+                .violations
+                .push(format!("position {abs_position} beyond limit at {time}"));
+        }
+    }
+
+    let must_trip = matches!(scenario, Scenario::FlashCrash | Scenario::FeedFreeze);
+    if must_trip && !report.circuit_tripped_in_event {
+        report
+            .violations
+            .push("burst circuit did not trip during the event".to_string());
+    }
+    if scenario == Scenario::OneSidedBook && report.rejected_books == 0 {
+        report
+            .violations
+            .push("one-sided books were not rejected".to_string());
+    }
+    // The crash runs a position into the drawdown limit; calmer scenarios don't
+    match (scenario, report.halted_at) {
+        (Scenario::FlashCrash, None) => report
+            .violations
+            .push("kill switch did not trip on the crash".to_string()),
+        (Scenario::FlashCrash, Some(_)) => {}
+        (_, Some(time)) => report
+            .violations
+            .push(format!("kill switch tripped at {time} without a crash")),
+        (_, None) => {}
+    }
+    if report.halted_at.is_some() && engine.state.position.base.abs() > EPSILON {
+        report.violations.push(format!(
+            "position {} left open by the kill switch",
+            engine.state.position.base
+        ));
+    }
+    if engine.state.quoting_paused {
+        report
+            .violations
+            .push("quoting still paused after the calm recovery".to_string());
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress_scenarios() {
+        for scenario in Scenario::ALL {
+            let report = run_scenario(scenario, 5.0, 7);
+            assert!(report.passed(), "{report}");
+        }
+    }
+
+    #[test]
+    fn test_calm_feed_does_not_trip() {
+        let mut engine = SignalEngine::new();
+        for event in generate_scenario(Scenario::SpreadBlowout, 1) {
+            if let MarketEvent::Book { time, bids, asks } = event {
+                if time >= START_MS + CALM_MS {
+                    break;
+                }
+                let (b, a, bv, av) = top_of_book(&bids, &asks).unwrap();
+                engine.process_l2_book(time, b, a, bv, av);
+                assert!(!engine.state.quoting_paused, "tripped at {time}");
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use log::debug;
+use std::collections::BTreeMap;
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    task::{JoinError, JoinSet},
+};
+
+use crate::{AdminRequest, Message, MessageRouter};
+
+// The coin a market data message is for; None for account and connection
+// messages
+fn message_coin(msg: &Message) -> Option<&str> {
+    match msg {
+        Message::L2Book(book) => Some(&book.data.coin),
+        Message::Trades(trades) => trades.data.first().map(|t| t.coin.as_str()),
+        Message::Bbo(bbo) => Some(&bbo.data.coin),
+        Message::ActiveAssetCtx(ctx) => Some(&ctx.data.coin),
+        _ => None,
+    }
+}
+
+// Runs one `MessageRouter` per coin, each in its own task with its own signal
+// engine, strategy, position and risk limits, and routes a single feed of
+// market data between them by coin. One process and one websocket connection
+// can then quote BTC, ETH and SOL side by side.
+pub struct SymbolManager {
+    feeds: BTreeMap<String, UnboundedSender<Message>>,
+    admins: BTreeMap<String, UnboundedSender<AdminRequest>>,
+    tasks: JoinSet<()>,
+    disconnected: bool, // a disconnect was already passed on to every coin
+}
+
+impl Default for SymbolManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolManager {
+    pub fn new() -> Self {
+        Self {
+            feeds: BTreeMap::new(),
+            admins: BTreeMap::new(),
+            tasks: JoinSet::new(),
+            disconnected: false,
+        }
+    }
+
+    // Starts `router`'s task for `coin`, replacing any router already running it
+    pub fn add(&mut self, coin: &str, router: MessageRouter) {
+        let (feed, receiver) = unbounded_channel();
+        let (admin, admin_rx) = unbounded_channel();
+        self.feeds.insert(coin.to_string(), feed);
+        self.admins.insert(coin.to_string(), admin);
+        self.tasks.spawn(router.run(receiver, admin_rx));
+    }
+
+    // Closes `coin`'s feed, so its router finishes what it is handling and
+    // stops; false if no router runs it
+    pub fn remove(&mut self, coin: &str) -> bool {
+        self.admins.remove(coin);
+        self.feeds.remove(coin).is_some()
+    }
+
+    pub fn coins(&self) -> impl Iterator<Item = &str> {
+        self.feeds.keys().map(String::as_str)
+    }
+
+    // Hands a message to the router for its coin. A disconnect, and the
+    // reconnect ending it, go to every coin once per outage however many
+    // subscriptions report them; messages for coins without a router are
+    // dropped.
+    pub fn route(&mut self, msg: Message) {
+        if let Message::NoData = msg {
+            if !self.disconnected {
+                for feed in self.feeds.values() {
+                    let _ = feed.send(Message::NoData);
+                }
+            }
+            self.disconnected = true;
+            return;
+        }
+        if let Message::Reconnected = msg {
+            if self.disconnected {
+                for feed in self.feeds.values() {
+                    let _ = feed.send(Message::Reconnected);
+                }
+            }
+            self.disconnected = false;
+            return;
+        }
+        let Some(coin) = message_coin(&msg) else {
+            return;
+        };
+        self.disconnected = false;
+        match self.feeds.get(coin) {
+            Some(feed) => {
+                let _ = feed.send(msg);
+            }
+            None => debug!("no router for {coin}, message dropped"),
+        }
+    }
+
+    // Answers an admin command for one coin (`signals COIN`) or every coin,
+    // joining their answers. Runs in the background so a slow coin does not
+    // hold up routing.
+    pub fn dispatch_admin(&self, req: AdminRequest) {
+        let coin = req.args.first().map(|c| c.to_uppercase());
+        let targets: Vec<_> = match (req.command.as_str(), coin) {
+            ("signals" | "ladder" | "sides" | "funding", Some(coin)) => {
+                match self.admins.get(&coin) {
+                    Some(tx) => vec![tx.clone()],
+                    None => {
+                        let known: Vec<_> = self.coins().collect();
+                        req.reply(format!("not trading {coin}; coins: {}", known.join(", ")));
+                        return;
+                    }
+                }
+            }
+            _ => self.admins.values().cloned().collect(),
+        };
+        tokio::spawn(async move {
+            let mut answers = Vec::new();
+            for tx in targets {
+                let (relayed, response) = AdminRequest::new(&req.command, req.args.clone());
+                if tx.send(relayed).is_ok() {
+                    answers.push(
+                        response
+                            .await
+                            .unwrap_or_else(|_| "coin task stopped".to_string()),
+                    );
+                }
+            }
+            req.reply(answers.join("\n"));
+        });
+    }
+
+    // Waits for a coin's task to end; None once all have
+    pub async fn join_next(&mut self) -> Option<Result<(), JoinError>> {
+        self.tasks.join_next().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_strategy, parse_ws_frame, RiskManager};
+    use std::sync::Arc;
+
+    fn book(coin: &str, bid: f64, ask: f64, time: u64) -> Message {
+        let frame = format!(
+            r#"{{"channel":"l2Book","data":{{"coin":"{coin}","time":{time},"levels":[[{{"px":"{bid}","sz":"1","n":1}}],[{{"px":"{ask}","sz":"1","n":1}}]]}}}}"#
+        );
+        match parse_ws_frame(&frame) {
+            Ok(Some((_, msg))) => msg,
+            other => panic!("bad frame: {other:?}"),
+        }
+    }
+
+    async fn ask(symbols: &SymbolManager, command: &str, args: &[&str]) -> String {
+        let args = args.iter().map(|a| a.to_string()).collect();
+        let (req, response) = AdminRequest::new(command, args);
+        symbols.dispatch_admin(req);
+        response.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routes_each_coin_to_its_own_router() {
+        let mut symbols = SymbolManager::new();
+        for (coin, limit) in [("BTC", 1.0), ("ETH", 20.0)] {
+            let risk = Arc::new(RiskManager::new(limit));
+            let router = MessageRouter::new(build_strategy("layered").unwrap(), risk, coin);
+            symbols.add(coin, router);
+        }
+        symbols.route(book("BTC", 100.0, 101.0, 1));
+        symbols.route(book("ETH", 2000.0, 2001.0, 1));
+        symbols.route(book("SOL", 150.0, 151.0, 1));
+
+        // Each router's task takes its book in its own time
+        for (coin, touch) in [
+            ("btc", "BTC bid 100.00 ask 101.00"),
+            ("ETH", "ETH bid 2000.00 ask 2001.00"),
+        ] {
+            let mut signals = String::new();
+            for _ in 0..100 {
+                signals = ask(&symbols, "signals", &[coin]).await;
+                if signals.starts_with(touch) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            assert!(signals.starts_with(touch), "{signals}");
+        }
+        let sol = ask(&symbols, "signals", &["SOL"]).await;
+        assert_eq!(sol, "not trading SOL; coins: BTC, ETH");
+        let positions = ask(&symbols, "pos", &[]).await;
+        assert_eq!(positions.lines().count(), 2);
+    }
+}
@@ -0,0 +1,699 @@
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+
+use crate::{prelude::*, BookLevel, Error, MarketEvent, Message};
+
+pub(crate) const TAPE_MAGIC: &[u8; 4] = b"HLTV";
+const LEGACY_MAGIC: &[u8; 4] = b"HLTP"; // schema 1, no header
+pub(crate) const TAPE_SCHEMA_VERSION: u16 = 3; // 3 added gap markers
+pub(crate) const TAPE_EXTENSION: &str = "tape";
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+const HOUR_MS: u64 = 3_600_000;
+
+// (price, size, order count)
+pub type TapeLevel = (f64, f64, u64);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TapeEvent {
+    Book {
+        time: u64,
+        bids: Vec<TapeLevel>,
+        asks: Vec<TapeLevel>,
+    },
+    Trade {
+        time: u64,
+        px: f64,
+        sz: f64,
+        is_buy: bool,
+    },
+    Bbo {
+        time: u64,
+        bid: Option<TapeLevel>,
+        ask: Option<TapeLevel>,
+    },
+    // Nothing was recorded between the last record received at `since_ms` and
+    // this one (recorder restart or feed outage). Covers every coin.
+    Gap {
+        since_ms: u64,
+    },
+}
+
+// One market-data message as the recorder saw it: exchange time lives in the
+// event, `received_ms` is local wall-clock time at receipt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapeRecord {
+    pub received_ms: u64,
+    pub coin: String,
+    pub event: TapeEvent,
+}
+
+fn tape_level(level: &BookLevel) -> Option<TapeLevel> {
+    Some((level.px.parse().ok()?, level.sz.parse().ok()?, level.n))
+}
+
+fn book_level((px, sz, n): &TapeLevel) -> BookLevel {
+    BookLevel {
+        px: px.to_string(),
+        sz: sz.to_string(),
+        n: *n,
+    }
+}
+
+impl TapeRecord {
+    // L2 books, trades and BBO updates; other messages yield nothing
+    pub fn from_message(received_ms: u64, msg: &Message) -> Vec<TapeRecord> {
+        let record = |coin: &str, event| TapeRecord {
+            received_ms,
+            coin: coin.to_string(),
+            event,
+        };
+        match msg {
+            Message::L2Book(book) => match book.data.levels.as_slice() {
+                [bids, asks, ..] => vec![record(
+                    &book.data.coin,
+                    TapeEvent::Book {
+                        time: book.data.time,
+                        bids: bids.iter().filter_map(tape_level).collect(),
+                        asks: asks.iter().filter_map(tape_level).collect(),
+                    },
+                )],
+                _ => vec![],
+            },
+            Message::Trades(trades) => trades
+                .data
+                .iter()
+                .filter_map(|t| {
+                    let event = TapeEvent::Trade {
+                        time: t.time,
+                        px: t.px.parse().ok()?,
+                        sz: t.sz.parse().ok()?,
+                        is_buy: t.side == "B",
+                    };
+                    Some(record(&t.coin, event))
+                })
+                .collect(),
+            Message::Bbo(bbo) => {
+                let side = |i: usize| bbo.data.bbo.get(i).cloned().flatten();
+                vec![record(
+                    &bbo.data.coin,
+                    TapeEvent::Bbo {
+                        time: bbo.data.time,
+                        bid: side(0).as_ref().and_then(tape_level),
+                        ask: side(1).as_ref().and_then(tape_level),
+                    },
+                )]
+            }
+            _ => vec![],
+        }
+    }
+
+    // Books and trades in the shape the engines consume. BBO updates have no
+    // equivalent: the L2 book carries the same top of book.
+    pub fn market_event(&self) -> Option<MarketEvent> {
+        match &self.event {
+            TapeEvent::Book { time, bids, asks } => Some(MarketEvent::Book {
+                time: *time,
+                bids: bids.iter().map(book_level).collect(),
+                asks: asks.iter().map(book_level).collect(),
+            }),
+            TapeEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => Some(MarketEvent::Trade {
+                time: *time,
+                px: *px,
+                sz: *sz,
+                is_buy: *is_buy,
+            }),
+            TapeEvent::Bbo { .. } | TapeEvent::Gap { .. } => None,
+        }
+    }
+}
+
+// Every tape starts with its header: `TAPE_MAGIC`, the schema version (u16 LE)
+// and a compression byte. Records follow, each a little-endian u32 length and
+// that many bytes of MessagePack, zstd-compressed as a stream when the header
+// says so. Files from before the header (schema 1) start with `LEGACY_MAGIC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TapeHeader {
+    version: u16,
+    compressed: bool,
+}
+
+impl TapeHeader {
+    fn current(compressed: bool) -> Self {
+        Self {
+            version: TAPE_SCHEMA_VERSION,
+            compressed,
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 7] {
+        let mut bytes = [0u8; 7];
+        bytes[..4].copy_from_slice(TAPE_MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6] = self.compressed as u8;
+        bytes
+    }
+
+    fn read(reader: &mut impl Read) -> std::io::Result<Option<Self>> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic == LEGACY_MAGIC {
+            return Ok(Some(Self {
+                version: 1,
+                compressed: false,
+            }));
+        }
+        if &magic != TAPE_MAGIC {
+            return Ok(None);
+        }
+        let mut rest = [0u8; 3];
+        reader.read_exact(&mut rest)?;
+        Ok(Some(Self {
+            version: u16::from_le_bytes([rest[0], rest[1]]),
+            compressed: rest[2] != 0,
+        }))
+    }
+}
+
+// Length prefix, then the record with named fields so later schemas can add
+// defaulted fields
+fn write_framed(writer: &mut (impl Write + ?Sized), record: &TapeRecord) -> Result<()> {
+    let bytes = rmp_serde::to_vec_named(record).map_err(|e| Error::Recording(e.to_string()))?;
+    writer
+        .write_all(&(bytes.len() as u32).to_le_bytes())
+        .and_then(|_| writer.write_all(&bytes))
+        .map_err(|e| Error::Recording(e.to_string()))
+}
+
+// Appends records to one file per UTC hour (`2025-07-03-17.tape`) under `dir`,
+// zstd-compressed at `DEFAULT_ZSTD_LEVEL` unless configured otherwise
+pub struct TapeWriter {
+    dir: PathBuf,
+    zstd_level: Option<i32>,
+    hour: Option<u64>,
+    path: Option<PathBuf>,
+    writer: Option<Box<dyn Write + Send>>,
+    last_ms: Option<u64>,   // received time of the last record written
+    gap_since: Option<u64>, // a gap marker goes before the next record
+}
+
+impl TapeWriter {
+    // Resumes after whatever an earlier run left in `dir`: the newest tape is
+    // cut back to its last whole record if that run died mid-write, and the
+    // first record written gets a gap marker back to that tape's last record
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| Error::Recording(e.to_string()))?;
+        let gap_since = match newest_tape(&dir)? {
+            Some(path) => recover_tape(&path)?,
+            None => None,
+        };
+        Ok(Self {
+            dir,
+            zstd_level: Some(DEFAULT_ZSTD_LEVEL),
+            hour: None,
+            path: None,
+            writer: None,
+            last_ms: gap_since,
+            gap_since,
+        })
+    }
+
+    // zstd level for new files, or None to write them uncompressed
+    pub fn with_compression(mut self, zstd_level: Option<i32>) -> Self {
+        self.zstd_level = zstd_level;
+        self
+    }
+
+    // File currently written to, once the first record has arrived
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    // Data is missing from now until the next record, e.g. the feed dropped
+    pub fn mark_gap(&mut self) {
+        if self.gap_since.is_none() {
+            self.gap_since = self.last_ms;
+        }
+    }
+
+    pub fn write(&mut self, record: &TapeRecord) -> Result<()> {
+        if let Some(since_ms) = self.gap_since.take() {
+            self.write_record(&TapeRecord {
+                received_ms: record.received_ms,
+                coin: String::new(),
+                event: TapeEvent::Gap { since_ms },
+            })?;
+        }
+        self.write_record(record)?;
+        self.last_ms = Some(record.received_ms);
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &TapeRecord) -> Result<()> {
+        let hour = record.received_ms / HOUR_MS;
+        if self.hour != Some(hour) {
+            self.rotate(hour)?;
+        }
+        write_framed(self.writer.as_mut().expect("rotate opens a file"), record)
+    }
+
+    // A flush ends the current zstd block, so everything written so far can be
+    // read back even if the recorder is killed before the file is finished
+    pub fn flush(&mut self) -> Result<()> {
+        match self.writer.as_mut() {
+            Some(writer) => writer.flush().map_err(|e| Error::Recording(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    // A restart within the hour appends to the existing file if its header
+    // matches what this writer would write, and otherwise starts a numbered
+    // sibling (`2025-07-03-17.1.tape`)
+    fn open_hour(&self, stem: &str) -> Result<(PathBuf, File, TapeHeader)> {
+        let header = TapeHeader::current(self.zstd_level.is_some());
+        for n in 0.. {
+            let name = match n {
+                0 => format!("{stem}.{TAPE_EXTENSION}"),
+                n => format!("{stem}.{n}.{TAPE_EXTENSION}"),
+            };
+            let path = self.dir.join(name);
+            let file = OpenOptions::new()
+                .create(true)
+                .read(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| Error::Recording(format!("{}: {e}", path.display())))?;
+            let empty = file.metadata().map(|m| m.len() == 0).unwrap_or(true);
+            if empty || matches!(TapeHeader::read(&mut &file), Ok(Some(h)) if h == header) {
+                return Ok((path, file, header));
+            }
+        }
+        unreachable!("the suffix search ends at the first free name")
+    }
+
+    fn rotate(&mut self, hour: u64) -> Result<()> {
+        self.flush()?;
+        // Dropping the old writer finishes its zstd frame
+        self.writer = None;
+        let start = DateTime::from_timestamp_millis((hour * HOUR_MS) as i64)
+            .ok_or_else(|| Error::Recording(format!("bad hour {hour}")))?;
+        let (path, mut file, header) = self.open_hour(&start.format("%Y-%m-%d-%H").to_string())?;
+        let err = |e: std::io::Error| Error::Recording(format!("{}: {e}", path.display()));
+        if file.metadata().map_err(err)?.len() == 0 {
+            file.write_all(&header.to_bytes()).map_err(err)?;
+        }
+        // The encoder buffers its own output, and its flush does not reach
+        // an inner BufWriter, so it writes to the file directly
+        self.writer = Some(match self.zstd_level {
+            Some(level) => Box::new(
+                zstd::stream::write::Encoder::new(file, level)
+                    .map_err(err)?
+                    .auto_finish(),
+            ),
+            None => Box::new(BufWriter::new(file)),
+        });
+        self.path = Some(path);
+        self.hour = Some(hour);
+        Ok(())
+    }
+}
+
+// Retention for always-on recorders: deletes tape files in `dir` whose hour
+// ended more than `keep_ms` before `now_ms`, returning the removed paths.
+// Files not named like the writer's are left alone; numbered siblings from a
+// restart go with their hour.
+pub fn prune_tapes(dir: impl AsRef<Path>, keep_ms: u64, now_ms: u64) -> Result<Vec<PathBuf>> {
+    let cutoff = now_ms.saturating_sub(keep_ms);
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| Error::Recording(e.to_string()))? {
+        let path = entry.map_err(|e| Error::Recording(e.to_string()))?.path();
+        if path.extension().is_none_or(|ext| ext != TAPE_EXTENSION) {
+            continue;
+        }
+        let Some(start) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str()?.get(..13))
+            .and_then(|stem| {
+                NaiveDateTime::parse_from_str(&format!("{stem}:00"), "%Y-%m-%d-%H:%M").ok()
+            })
+        else {
+            continue;
+        };
+        let end = start.and_utc().timestamp_millis() as u64 + HOUR_MS;
+        if end <= cutoff {
+            fs::remove_file(&path).map_err(|e| Error::Recording(e.to_string()))?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+// Reads until `buf` is full or the input ends, returning the bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+// Every whole record of one tape, and whether the file ended cleanly after
+// the last one
+struct TapeScan {
+    header: TapeHeader,
+    records: Vec<TapeRecord>,
+    valid_len: u64, // file bytes up to the end of the last record, if uncompressed
+    complete: bool,
+}
+
+fn scan_tape(path: &Path) -> Result<TapeScan> {
+    let err = |e: String| Error::Recording(format!("{}: {e}", path.display()));
+    let file = File::open(path).map_err(|e| err(e.to_string()))?;
+    let mut reader = BufReader::new(file);
+    let header = TapeHeader::read(&mut reader)
+        .map_err(|e| err(e.to_string()))?
+        .ok_or_else(|| err("not a tape file".to_string()))?;
+    if header.version > TAPE_SCHEMA_VERSION {
+        return Err(err(format!(
+            "schema {} is newer than this build ({TAPE_SCHEMA_VERSION})",
+            header.version
+        )));
+    }
+    let mut valid_len = match header.version {
+        1 => LEGACY_MAGIC.len(),
+        _ => header.to_bytes().len(),
+    } as u64;
+    let mut reader: Box<dyn Read> = if header.compressed {
+        Box::new(zstd::stream::read::Decoder::with_buffer(reader).map_err(|e| err(e.to_string()))?)
+    } else {
+        Box::new(reader)
+    };
+    let mut records = Vec::new();
+    let mut len = [0u8; 4];
+    let complete = loop {
+        // A zstd frame cut short reports a decoder error rather than EOF
+        match read_full(&mut reader, &mut len) {
+            Ok(0) => break true,
+            Ok(4) => {}
+            Ok(_) => break false,
+            Err(_) if header.compressed => break false,
+            Err(e) => return Err(err(e.to_string())),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        match read_full(&mut reader, &mut bytes) {
+            Ok(n) if n == bytes.len() => {}
+            Ok(_) => break false,
+            Err(_) if header.compressed => break false,
+            Err(e) => return Err(err(e.to_string())),
+        }
+        let record = match header.version {
+            1 => rmp_serde::from_slice::<v1::TapeRecord>(&bytes).map(TapeRecord::from),
+            _ => rmp_serde::from_slice(&bytes),
+        };
+        records.push(record.map_err(|e| err(e.to_string()))?);
+        valid_len += 4 + bytes.len() as u64;
+    };
+    Ok(TapeScan {
+        header,
+        records,
+        valid_len,
+        complete,
+    })
+}
+
+// Reads every record of one tape file of any schema version, migrating older
+// records to the current `TapeRecord`. A record cut short at the end of the
+// file (the recorder stopped mid-write) ends the read without an error.
+pub fn read_tape(path: impl AsRef<Path>) -> Result<Vec<TapeRecord>> {
+    let path = path.as_ref();
+    let scan = scan_tape(path)?;
+    if !scan.complete {
+        warn!(
+            "{}: truncated after {} records",
+            path.display(),
+            scan.records.len()
+        );
+    }
+    Ok(scan.records)
+}
+
+// Tapes in recording order: by hour, then restart siblings (`.1`, `.2`, ...)
+// after the hour's first file
+pub(crate) fn tape_order(path: &Path) -> (String, u32) {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    match stem.split_once('.') {
+        Some((hour, n)) => (hour.to_string(), n.parse().unwrap_or(u32::MAX)),
+        None => (stem.to_string(), 0),
+    }
+}
+
+fn newest_tape(dir: &Path) -> Result<Option<PathBuf>> {
+    let tapes = fs::read_dir(dir)
+        .map_err(|e| Error::Recording(e.to_string()))?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == TAPE_EXTENSION));
+    Ok(tapes.max_by_key(|p| tape_order(p)))
+}
+
+// Cuts a tape left mid-write back to its last whole record, so appending can
+// carry on after it. An uncompressed tape is truncated in place; a compressed
+// one is rewritten, since its unfinished zstd frame would swallow any frame
+// appended after it. Returns the received time of the last record.
+fn recover_tape(path: &Path) -> Result<Option<u64>> {
+    let scan = scan_tape(path)?;
+    let last_ms = scan.records.last().map(|r| r.received_ms);
+    if scan.complete {
+        return Ok(last_ms);
+    }
+    warn!(
+        "{}: recovering {} records left by an interrupted recorder",
+        path.display(),
+        scan.records.len()
+    );
+    let err = |e: std::io::Error| Error::Recording(format!("{}: {e}", path.display()));
+    if !scan.header.compressed {
+        let file = OpenOptions::new().write(true).open(path).map_err(err)?;
+        file.set_len(scan.valid_len).map_err(err)?;
+        return Ok(last_ms);
+    }
+    if scan.header.version != TAPE_SCHEMA_VERSION {
+        // Appends go to a sibling file anyway; the reader tolerates the tail
+        return Ok(last_ms);
+    }
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp).map_err(err)?;
+    file.write_all(&scan.header.to_bytes()).map_err(err)?;
+    let mut encoder =
+        zstd::stream::write::Encoder::new(BufWriter::new(file), DEFAULT_ZSTD_LEVEL).map_err(err)?;
+    for record in &scan.records {
+        write_framed(&mut encoder, record)?;
+    }
+    encoder
+        .finish()
+        .and_then(|mut file| file.flush())
+        .map_err(err)?;
+    fs::rename(&tmp, path).map_err(err)?;
+    Ok(last_ms)
+}
+
+// Frozen copies of older record layouts. Schema 1 wrote records positionally,
+// so it must be decoded with exactly the fields it had.
+mod v1 {
+    use serde::Deserialize;
+
+    use super::TapeLevel;
+
+    #[derive(Deserialize)]
+    pub enum TapeEvent {
+        Book {
+            time: u64,
+            bids: Vec<TapeLevel>,
+            asks: Vec<TapeLevel>,
+        },
+        Trade {
+            time: u64,
+            px: f64,
+            sz: f64,
+            is_buy: bool,
+        },
+        Bbo {
+            time: u64,
+            bid: Option<TapeLevel>,
+            ask: Option<TapeLevel>,
+        },
+    }
+
+    #[derive(Deserialize)]
+    pub struct TapeRecord {
+        pub received_ms: u64,
+        pub coin: String,
+        pub event: TapeEvent,
+    }
+}
+
+impl From<v1::TapeRecord> for TapeRecord {
+    fn from(old: v1::TapeRecord) -> Self {
+        let event = match old.event {
+            v1::TapeEvent::Book { time, bids, asks } => TapeEvent::Book { time, bids, asks },
+            v1::TapeEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            } => TapeEvent::Trade {
+                time,
+                px,
+                sz,
+                is_buy,
+            },
+            v1::TapeEvent::Bbo { time, bid, ask } => TapeEvent::Bbo { time, bid, ask },
+        };
+        TapeRecord {
+            received_ms: old.received_ms,
+            coin: old.coin,
+            event,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn book(received_ms: u64) -> TapeRecord {
+        TapeRecord {
+            received_ms,
+            coin: "BTC".to_string(),
+            event: TapeEvent::Book {
+                time: received_ms - 5,
+                bids: vec![(100.0, 1.5, 3)],
+                asks: vec![(101.0, 2.0, 1)],
+            },
+        }
+    }
+
+    #[test]
+    fn test_tape_round_trip_rotates_hourly() {
+        let dir = std::env::temp_dir().join(format!("tape-{}", Uuid::new_v4()));
+        let mut writer = TapeWriter::new(&dir).unwrap().with_compression(None);
+        let trade = TapeRecord {
+            received_ms: 1_751_563_867_100,
+            coin: "BTC".to_string(),
+            event: TapeEvent::Trade {
+                time: 1_751_563_867_090,
+                px: 100.5,
+                sz: 0.1,
+                is_buy: true,
+            },
+        };
+        writer.write(&book(1_751_563_867_050)).unwrap();
+        writer.write(&trade).unwrap();
+        let first = writer.path().unwrap().to_path_buf();
+        writer.write(&book(1_751_563_867_050 + HOUR_MS)).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(first.file_name().unwrap(), "2025-07-03-17.tape");
+        assert_ne!(writer.path().unwrap(), first);
+
+        // A partial record at the end (killed mid-write) is dropped
+        let mut file = OpenOptions::new().append(true).open(&first).unwrap();
+        file.write_all(&100u32.to_le_bytes()).unwrap();
+        let records = read_tape(&first).unwrap();
+        // Seven days later only the second hour is inside a week's retention
+        let now = 1_751_563_867_050 + 7 * 24 * HOUR_MS + HOUR_MS / 2;
+        let removed = prune_tapes(&dir, 7 * 24 * HOUR_MS, now).unwrap();
+        assert_eq!(removed, vec![first.clone()]);
+        assert!(writer.path().unwrap().exists());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(records, vec![book(1_751_563_867_050), trade]);
+        match records[0].market_event() {
+            Some(MarketEvent::Book { bids, .. }) => {
+                assert_eq!((bids[0].px.as_str(), bids[0].n), ("100", 3))
+            }
+            other => panic!("expected a book, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resumes_after_a_crash_with_a_gap_marker() {
+        let dir = std::env::temp_dir().join(format!("tape-{}", Uuid::new_v4()));
+        let start = 1_751_563_867_050;
+        let mut writer = TapeWriter::new(&dir).unwrap();
+        writer.write(&book(start)).unwrap();
+        writer.flush().unwrap();
+        writer.write(&book(start + 10)).unwrap();
+        writer.flush().unwrap();
+        let path = writer.path().unwrap().to_path_buf();
+        // Killed mid-write: the zstd frame is never finished and the last
+        // block is cut short
+        std::mem::forget(writer);
+        let len = fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 3).unwrap();
+
+        let mut writer = TapeWriter::new(&dir).unwrap();
+        writer.write(&book(start + 20)).unwrap();
+        drop(writer);
+        let gap = TapeRecord {
+            received_ms: start + 20,
+            coin: String::new(),
+            event: TapeEvent::Gap { since_ms: start },
+        };
+        assert_eq!(
+            read_tape(&path).unwrap(),
+            vec![book(start), gap, book(start + 20)]
+        );
+        let recording = crate::load_recording(&dir).unwrap();
+        assert_eq!(recording.events.len(), 2);
+        assert_eq!(recording.gaps, vec![(start, start + 20)]);
+
+        // An uncompressed writer in the same hour gets its own file, which is
+        // truncated back to its last record on the next start
+        let mut plain = TapeWriter::new(&dir).unwrap().with_compression(None);
+        plain.write(&book(start + 30)).unwrap();
+        plain.flush().unwrap();
+        let sibling = plain.path().unwrap().to_path_buf();
+        assert_eq!(sibling.file_name().unwrap(), "2025-07-03-17.1.tape");
+        drop(plain);
+        let len = fs::metadata(&sibling).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&sibling).unwrap();
+        file.write_all(&[9, 0]).unwrap();
+        TapeWriter::new(&dir).unwrap();
+        assert_eq!(fs::metadata(&sibling).unwrap().len(), len);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reads_schema_one_tapes() {
+        let path = std::env::temp_dir().join(format!("tape-{}.tape", Uuid::new_v4()));
+        // Schema 1: legacy magic, then positional MessagePack records
+        let mut bytes = LEGACY_MAGIC.to_vec();
+        let record = rmp_serde::to_vec(&book(1_000)).unwrap();
+        bytes.extend((record.len() as u32).to_le_bytes());
+        bytes.extend(record);
+        fs::write(&path, bytes).unwrap();
+        let records = read_tape(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(records, vec![book(1_000)]);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{BookLevel, Message};
+
+// Market data samples
+#[derive(Debug, Clone)]
+pub struct BookSample {
+    pub timestamp_ms: u64,
+    pub mid_price: f64,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub bid_volume: f64,
+    pub ask_volume: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeSample {
+    pub price: f64,
+    pub size: f64,
+    pub is_buy: bool,
+    pub timestamp_ms: u64,
+}
+
+// Internal position tracking
+#[derive(Debug, Default, Clone)]
+pub struct Position {
+    pub base: f64,  // Asset holdings (e.g. BTC)
+    pub quote: f64, // Quote currency (e.g. USD)
+}
+
+#[derive(Debug, Clone)]
+pub struct QuoteProposal {
+    pub side: String, // "Buy" or "Sell"
+    pub price: f64,
+    pub size: f64,
+}
+
+// Normalized market data event, shared by live routing, recordings and simulations
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    Book {
+        time: u64,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+    },
+    Trade {
+        time: u64,
+        px: f64,
+        sz: f64,
+        is_buy: bool,
+    },
+}
+
+impl MarketEvent {
+    pub fn time(&self) -> u64 {
+        match self {
+            MarketEvent::Book { time, .. } | MarketEvent::Trade { time, .. } => *time,
+        }
+    }
+
+    // Normalizes a websocket message into market events. Books without both sides
+    // and trades with unparseable, non-finite or non-positive numbers are dropped;
+    // whether a book is usable is left to `top_of_book`.
+    pub fn from_message(msg: &Message) -> Vec<MarketEvent> {
+        match msg {
+            Message::L2Book(book) => match book.data.levels.as_slice() {
+                [bids, asks, ..] => vec![MarketEvent::Book {
+                    time: book.data.time,
+                    bids: bids.clone(),
+                    asks: asks.clone(),
+                }],
+                _ => vec![],
+            },
+            Message::Trades(trades) => trades
+                .data
+                .iter()
+                .filter_map(|t| {
+                    let px = t.px.parse::<f64>().ok()?;
+                    let sz = t.sz.parse::<f64>().ok()?;
+                    if !(px.is_finite() && sz.is_finite() && px > 0.0 && sz > 0.0) {
+                        return None;
+                    }
+                    Some(MarketEvent::Trade {
+                        time: t.time,
+                        px,
+                        sz,
+                        is_buy: t.side == "B",
+                    })
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_ws_frame;
+
+    fn events(frame: &str) -> Vec<MarketEvent> {
+        match parse_ws_frame(frame) {
+            Ok(Some((_, msg))) => MarketEvent::from_message(&msg),
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_malformed_frames_do_not_panic() {
+        let frames = [
+            "",
+            "{",
+            "not json",
+            r#"{"channel":"l2Book","data":{"coin":"BTC","time":1,"levels":[]}}"#,
+            r#"{"channel":"l2Book","data":{"coin":"BTC","time":1,"levels":[[]]}}"#,
+            r#"{"channel":"trades","data":[]}"#,
+            r#"{"channel":"trades","data":[{"coin":"BTC","side":"B","px":"NaN","sz":"1","time":1,"hash":"","tid":1,"users":["",""]}]}"#,
+            r#"{"channel":"trades","data":[{"coin":"BTC","side":"B","px":"1e999","sz":"-1","time":1,"hash":"","tid":1,"users":["",""]}]}"#,
+        ];
+        for frame in frames {
+            assert!(events(frame).is_empty(), "{frame}");
+        }
+
+        let book = r#"{"channel":"l2Book","data":{"coin":"BTC","time":7,"levels":[[{"px":"abc","sz":"1","n":1}],[]]}}"#;
+        let parsed = events(book);
+        assert_eq!(parsed.len(), 1);
+        let MarketEvent::Book { bids, asks, .. } = &parsed[0] else {
+            panic!("expected a book");
+        };
+        assert!(crate::top_of_book(bids, asks).is_none());
+
+        let trade = r#"{"channel":"trades","data":[{"coin":"BTC","side":"A","px":"100.5","sz":"0.1","time":9,"hash":"","tid":1,"users":["",""]}]}"#;
+        assert!(matches!(
+            events(trade).as_slice(),
+            [MarketEvent::Trade {
+                time: 9,
+                is_buy: false,
+                ..
+            }]
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use log::{error, warn};
+use std::{fs, path::Path};
+use wasmi::{
+    Caller, Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+use crate::{prelude::*, Error, QuoteProposal, SignalState, Strategy};
+
+// Sandbox budget per callback: fuel bounds execution time, memory bounds growth
+const WASM_FUEL_PER_CALL: u64 = 1_000_000;
+const WASM_MAX_MEMORY_BYTES: usize = 16 << 20;
+const WASM_MAX_INTENTS_PER_CALL: usize = 8;
+
+// Host side of the sandbox. The guest can only see the data passed to its
+// callbacks and can only act through `env.quote`, which records an order intent;
+// it gets no keys, no I/O and no clock.
+struct HostState {
+    limits: StoreLimits,
+    intents: Vec<QuoteProposal>,
+    rejected: u64,
+}
+
+// A strategy compiled to WASM. The module exports
+//   on_book(time: i64, bid: f64, ask: f64, bid_vol: f64, ask_vol: f64, position: f64)
+//   on_trade(time: i64, px: f64, sz: f64, is_buy: i32)    (optional)
+// and may import
+//   env.quote(is_buy: i32, price: f64, size: f64)
+// Intents emitted during on_book become that update's quote proposals and still go
+// through the risk manager. A trap or exhausted fuel quotes nothing for the update.
+pub struct WasmStrategy {
+    name: String,
+    store: Store<HostState>,
+    on_book: TypedFunc<(i64, f64, f64, f64, f64, f64), ()>,
+    on_trade: Option<TypedFunc<(i64, f64, f64, i32), ()>>,
+}
+
+fn wasm_err(e: impl std::fmt::Display) -> Error {
+    Error::Wasm(e.to_string())
+}
+
+impl WasmStrategy {
+    pub fn new(name: &str, wasm: &[u8]) -> Result<Self> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(wasm_err)?;
+        let host = HostState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(WASM_MAX_MEMORY_BYTES)
+                .instances(1)
+                .build(),
+            intents: Vec::new(),
+            rejected: 0,
+        };
+        let mut store = Store::new(&engine, host);
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(WASM_FUEL_PER_CALL).map_err(wasm_err)?;
+
+        let mut linker = <Linker<HostState>>::new(&engine);
+        linker
+            .func_wrap(
+                "env",
+                "quote",
+                |mut caller: Caller<'_, HostState>, is_buy: i32, price: f64, size: f64| {
+                    let host = caller.data_mut();
+                    let valid = price.is_finite() && size.is_finite() && price > 0.0 && size > 0.0;
+                    if !valid || host.intents.len() >= WASM_MAX_INTENTS_PER_CALL {
+                        host.rejected += 1;
+                        return;
+                    }
+                    host.intents.push(QuoteProposal {
+                        side: if is_buy != 0 { "Buy" } else { "Sell" }.into(),
+                        price,
+                        size,
+                    });
+                },
+            )
+            .map_err(wasm_err)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(wasm_err)?;
+        let on_book = instance
+            .get_typed_func(&store, "on_book")
+            .map_err(|e| Error::Wasm(format!("on_book export: {e}")))?;
+        let on_trade = instance.get_typed_func(&store, "on_trade").ok();
+        Ok(Self {
+            name: name.to_string(),
+            store,
+            on_book,
+            on_trade,
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let wasm = fs::read(path).map_err(wasm_err)?;
+        let name = path
+            .file_stem()
+            .map_or("wasm".into(), |s| s.to_string_lossy());
+        Self::new(&name, &wasm)
+    }
+
+    // Intents dropped for being invalid or over the per-call cap
+    pub fn rejected_intents(&self) -> u64 {
+        self.store.data().rejected
+    }
+
+    fn refuel(&mut self) -> Result<()> {
+        self.store.data_mut().intents.clear();
+        self.store.set_fuel(WASM_FUEL_PER_CALL).map_err(wasm_err)
+    }
+}
+
+impl Strategy for WasmStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn quote(&mut self, state: &SignalState) -> Vec<QuoteProposal> {
+        if let Err(e) = self.refuel() {
+            error!("WASM strategy {} not refuelled: {e}", self.name);
+            return vec![];
+        }
+        let time = state
+            .book_history
+            .back()
+            .map_or(0, |b| b.timestamp_ms as i64);
+        let args = (
+            time,
+            state.best_bid,
+            state.best_ask,
+            state.book_history.back().map_or(0.0, |b| b.bid_volume),
+            state.book_history.back().map_or(0.0, |b| b.ask_volume),
+            state.position.base,
+        );
+        if let Err(e) = self.on_book.call(&mut self.store, args) {
+            error!(
+                "WASM strategy {} trapped in on_book, not quoting: {e}",
+                self.name
+            );
+            return vec![];
+        }
+        std::mem::take(&mut self.store.data_mut().intents)
+    }
+
+    fn on_trade(&mut self, price: f64, size: f64, is_buy: bool, time: u64) {
+        let Some(on_trade) = self.on_trade else {
+            return;
+        };
+        if self.refuel().is_err() {
+            return;
+        }
+        let args = (time as i64, price, size, is_buy as i32);
+        if let Err(e) = on_trade.call(&mut self.store, args) {
+            warn!("WASM strategy {} trapped in on_trade: {e}", self.name);
+        }
+        // Intents are only taken from on_book
+        self.store.data_mut().intents.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BookSample;
+
+    // Bids 1 below the touch after the first trade it sees, spins forever when
+    // long, and emits one invalid intent per update
+    const GUEST: &str = r#"
+        (module
+          (import "env" "quote" (func $quote (param i32 f64 f64)))
+          (global $seen (mut i32) (i32.const 0))
+          (func (export "on_trade") (param i64 f64 f64 i32)
+            (global.set $seen (i32.const 1)))
+          (func (export "on_book") (param i64 f64 f64 f64 f64 f64)
+            (if (f64.gt (local.get 5) (f64.const 0))
+              (then (loop $spin (br $spin))))
+            (call $quote (i32.const 0) (f64.const -1) (f64.const 1))
+            (if (global.get $seen)
+              (then (call $quote (i32.const 1)
+                                 (f64.sub (local.get 1) (f64.const 1))
+                                 (f64.const 0.5))))))
+    "#;
+
+    #[test]
+    fn test_wasm_strategy_sandbox() {
+        let wasm = wat::parse_str(GUEST).unwrap();
+        let mut strategy = WasmStrategy::new("guest", &wasm).unwrap();
+        let mut state = SignalState {
+            best_bid: 100.0,
+            best_ask: 101.0,
+            ..Default::default()
+        };
+        state.book_history.push_back(BookSample {
+            timestamp_ms: 1,
+            mid_price: 100.5,
+            best_bid: 100.0,
+            best_ask: 101.0,
+            bid_volume: 1.0,
+            ask_volume: 1.0,
+        });
+
+        assert!(strategy.quote(&state).is_empty());
+        strategy.on_trade(100.5, 1.0, true, 2);
+        let quotes = strategy.quote(&state);
+        assert_eq!(quotes.len(), 1);
+        assert_eq!((quotes[0].side.as_str(), quotes[0].price), ("Buy", 99.0));
+        assert_eq!(strategy.rejected_intents(), 2);
+
+        // Infinite loop runs out of fuel instead of hanging
+        state.position.base = 1.0;
+        assert!(strategy.quote(&state).is_empty());
+
+        // Anything beyond the constrained host API fails to link
+        let escape = r#"(module (import "env" "sign" (func)) (func (export "on_book") (param i64 f64 f64 f64 f64 f64)))"#;
+        assert!(WasmStrategy::new("escape", &wat::parse_str(escape).unwrap()).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
     prelude::*,
+    req::HttpClient,
     ws::message_types::{AllMids, Bbo, Candle, L2Book, OrderUpdates, Trades, User},
-    ActiveAssetCtx, ActiveAssetData, Error, Notification, UserFills, UserFundings,
-    UserNonFundingLedgerUpdates, WebData2,
+    ActiveAssetCtx, ActiveAssetData, BookLevel, Error, InfoRequest, L2BookData, L2SnapshotResponse,
+    Notification, UserFills, UserFundings, UserNonFundingLedgerUpdates, WebData2,
 };
 use futures_util::{stream::SplitSink, SinkExt, StreamExt};
 use log::{error, info, warn};
@@ -72,7 +73,8 @@ pub enum Subscription {
 #[serde(tag = "channel")]
 #[serde(rename_all = "camelCase")]
 pub enum Message {
-    NoData,
+    NoData,      // sent by the WsManager when the connection drops or goes stale
+    Reconnected, // and once it is back, resubscribed and its books resent
     HyperliquidError(String),
     AllMids(AllMids),
     Trades(Trades),
@@ -107,7 +109,15 @@ pub(crate) struct Ping {
 impl WsManager {
     const SEND_PING_INTERVAL: u64 = 50;
 
-    pub(crate) async fn new(url: String, reconnect: bool) -> Result<WsManager> {
+    // With `stale_after`, a connection that has sent nothing for that long is
+    // treated as dropped. After a reconnect every book subscribed to is
+    // resent from an L2 snapshot fetched through `http`, before `Reconnected`.
+    pub(crate) async fn new(
+        url: String,
+        reconnect: bool,
+        stale_after: Option<Duration>,
+        http: HttpClient,
+    ) -> Result<WsManager> {
         let stop_flag = Arc::new(AtomicBool::new(false));
 
         let (writer, mut reader) = Self::connect(&url).await?.split();
@@ -122,7 +132,17 @@ impl WsManager {
             let stop_flag = Arc::clone(&stop_flag);
             let reader_fut = async move {
                 while !stop_flag.load(Ordering::Relaxed) {
-                    if let Some(data) = reader.next().await {
+                    let data =
+                        match stale_after {
+                            Some(limit) => time::timeout(limit, reader.next())
+                                .await
+                                .unwrap_or_else(|_| {
+                                    warn!("WsManager received nothing for {limit:?}, feed stale");
+                                    None
+                                }),
+                            None => reader.next().await,
+                        };
+                    if let Some(data) = data {
                         if let Err(err) =
                             WsManager::parse_and_send_data(data, &subscriptions_copy).await
                         {
@@ -172,6 +192,8 @@ impl WsManager {
                                             error!("Could not resubscribe correctly {identifier}: {err}");
                                         }
                                     }
+                                    drop(writer_guard);
+                                    Self::resync(&http, &subscriptions_copy).await;
                                     info!("WsManager reconnect finished");
                                 }
                                 Err(err) => error!("Could not connect to websocket {err}"),
@@ -217,6 +239,47 @@ impl WsManager {
         })
     }
 
+    // Resends each subscribed book from a REST snapshot, then tells every
+    // subscriber the feed is back
+    async fn resync(
+        http: &HttpClient,
+        subscriptions: &Arc<Mutex<HashMap<String, Vec<SubscriptionData>>>>,
+    ) {
+        let books: Vec<_> = subscriptions
+            .lock()
+            .await
+            .keys()
+            .filter_map(|identifier| match serde_json::from_str(identifier) {
+                Ok(Subscription::L2Book { coin }) => Some((identifier.clone(), coin)),
+                _ => None,
+            })
+            .collect();
+        for (identifier, coin) in books {
+            match Self::l2_snapshot(http, coin).await {
+                Ok(snapshot) => {
+                    let message = snapshot_message(snapshot);
+                    if let Some(subscribers) = subscriptions.lock().await.get(&identifier) {
+                        for subscriber in subscribers {
+                            let _ = subscriber.sending_channel.send(message.clone());
+                        }
+                    }
+                }
+                Err(err) => error!("Could not resync {identifier}: {err}"),
+            }
+        }
+        if let Err(err) = Self::send_to_all_subscriptions(subscriptions, Message::Reconnected).await
+        {
+            warn!("Error sending reconnection notification err={err}");
+        }
+    }
+
+    async fn l2_snapshot(http: &HttpClient, coin: String) -> Result<L2SnapshotResponse> {
+        let request = serde_json::to_string(&InfoRequest::L2Book { coin })
+            .map_err(|e| Error::JsonParse(e.to_string()))?;
+        let response = http.post("/info", request).await?;
+        serde_json::from_str(&response).map_err(|e| Error::JsonParse(e.to_string()))
+    }
+
     async fn connect(url: &str) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
         Ok(connect_async(url)
             .await
@@ -292,7 +355,7 @@ impl WsManager {
             })
             .map_err(|e| Error::JsonParse(e.to_string())),
             Message::SubscriptionResponse | Message::Pong => Ok(String::default()),
-            Message::NoData => Ok("".to_string()),
+            Message::NoData | Message::Reconnected => Ok("".to_string()),
             Message::HyperliquidError(err) => Ok(format!("hyperliquid error: {err:?}")),
         }
     }
@@ -304,15 +367,9 @@ impl WsManager {
         match data {
             Ok(data) => match data.into_text() {
                 Ok(data) => {
-                    if !data.starts_with('{') {
-                        return Ok(());
-                    }
-                    let message = serde_json::from_str::<Message>(&data)
-                        .map_err(|e| Error::JsonParse(e.to_string()))?;
-                    let identifier = WsManager::get_identifier(&message)?;
-                    if identifier.is_empty() {
+                    let Some((identifier, message)) = parse_ws_frame(&data)? else {
                         return Ok(());
-                    }
+                    };
 
                     let mut subscriptions = subscriptions.lock().await;
                     let mut res = Ok(());
@@ -488,6 +545,46 @@ impl WsManager {
     }
 }
 
+// Parses a text frame into its subscription identifier and message. Frames that are
+// not routed to a subscriber (non-JSON, acks, pongs) give None; malformed JSON is an
+// error rather than a panic.
+pub fn parse_ws_frame(text: &str) -> Result<Option<(String, Message)>> {
+    if !text.starts_with('{') {
+        return Ok(None);
+    }
+    let message =
+        serde_json::from_str::<Message>(text).map_err(|e| Error::JsonParse(e.to_string()))?;
+    let identifier = WsManager::get_identifier(&message)?;
+    if identifier.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((identifier, message)))
+}
+
+// A REST book snapshot as the websocket would have sent it
+fn snapshot_message(snapshot: L2SnapshotResponse) -> Message {
+    let levels = snapshot
+        .levels
+        .into_iter()
+        .map(|side| {
+            side.into_iter()
+                .map(|level| BookLevel {
+                    px: level.px,
+                    sz: level.sz,
+                    n: level.n,
+                })
+                .collect()
+        })
+        .collect();
+    Message::L2Book(L2Book {
+        data: L2BookData {
+            coin: snapshot.coin,
+            time: snapshot.time,
+            levels,
+        },
+    })
+}
+
 impl Drop for WsManager {
     fn drop(&mut self) {
         self.stop_flag.store(true, Ordering::Relaxed);